@@ -1,18 +1,104 @@
-use crate::config::{Config, DatabaseConnection, SavedQueries};
-use crate::db::{AsyncOperation, Database, SchemaInfo};
-use crate::models::{AppState, Tab, TabSource, TableData};
+use crate::config::{Config, DatabaseConnection, Driver, NetworkSettings, QueryHistory, SavedFilterSet, SavedQueries, SshSettings, TableAction, TlsSettings};
+use crate::export::{batch_file_name, plan_for_visualizer, AnonymizeRule, Anonymizer, BatchProgress, BatchStatus, CodeTarget, ExportFormat, ExportJob, ExportSettings, SqlDump, Watermark, write_png};
+use crate::db::sql::TimeBucket;
+use crate::db::{self, command_tag, is_cancelled, is_timeout, routing, run_script, AsyncOperation, ColumnLineage, DatabaseBackend, PlanDestination, Privilege, QueryStream, QueryTarget, SchemaInfo, ScriptProgress, SourceObject, StatementOutcome, StatementRun, StatementSummary, StatementTimeout, TransactionControl, TransactionStatus};
+use crate::models::{clamp_tab_move, set_column_filter, table_sample, CachedSchema, SchemaCache, follow_ups, format_bytes, single_source_table, FollowUp, BarSeries, CellValue, filter_row_indices, JsonFilter, RowComparison, PastedTable, ImportPlan, ImportProgress, ValueReplace, ComputedColumn, ValueDistribution, moved_index, short_label, sql_file_title, AlterPlan, AppState, Bookmarks, ExplainPlan, OperationQueue, PendingEdits, QueryLog, QueryStats, ResultCache, Watch, SchemaChange, SchemaSnapshot, TailState, SharedQuery, SharedViewSettings, Tab, TabSource, TabView, TableData, rows_size, SHARED_QUERY_EXTENSION, WIDE_TABLE_COLUMNS};
 use crate::ui::components::*;
+use crate::ui::keymap::{self, Command};
 use crate::ui::setup_styles;
+use chrono::{DateTime, Utc};
 use eframe::egui;
 use poll_promise::Promise;
-use std::collections::HashSet;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// How often the UI wakes up to check on in-flight database operations
+const ASYNC_POLL_INTERVAL: Duration = Duration::from_millis(100);
+// How often the primary is asked whether a transaction is open
+const TRANSACTION_POLL_INTERVAL: Duration = Duration::from_secs(5);
+// Most rows a table tab loads
+const MAX_TABLE_ROWS: usize = 100_000;
+
+/// A write waiting for confirmation on a connection with production safeguards
+pub enum PendingWrite {
+    Query(String, Option<usize>),      // SQL, tab to load the result into
+    ApplyEdits(usize),                 // tab whose staged edits will be written
+    RunScript(PathBuf, bool),          // SQL file, stop on error
+    AlterColumns(String, Vec<String>), // "schema.table", statements of the ALTER assistant
+    ImportFile(ImportPlan),            // file and table of the import wizard
+}
+
+/// Work requested while the connection was busy, started once it is free
+pub enum QueuedOperation {
+    LoadTable(String, String, Option<usize>), // schema, table, tab to reload
+    Query(String, Option<usize>),             // SQL, tab to load the result into
+    ApplyEdits(usize),                        // tab whose staged edits will be written
+    RunScript(PathBuf, bool),                 // SQL file, stop on error
+    AlterColumns(String, Vec<String>),        // "schema.table", statements of the ALTER assistant
+    CreateTempTable(PastedTable, String),     // pasted rows, table name
+    ImportFile(ImportPlan),                   // file and table of the import wizard
+    SqlDump(SqlDump, PathBuf),                // dump settings, target file of Export as SQL
+}
+
+/// What the shared file path dialog is currently being used for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileAction {
+    ShareQuery,
+    OpenSharedQuery,
+    SaveExplainPlan,
+    SaveSchemaSnapshot,
+    CompareSchemaSnapshot,
+    SaveCellBytes(usize, usize), // original row and column in the active tab
+    SaveChartPng,
+    OpenSqlFile,
+    SaveSqlFile,
+}
+
+impl FileAction {
+    fn title(&self) -> &str {
+        match self {
+            FileAction::ShareQuery => "🔗 Share Query",
+            FileAction::OpenSharedQuery => "📂 Open Shared Query",
+            FileAction::SaveExplainPlan => "💾 Save EXPLAIN Plan",
+            FileAction::SaveSchemaSnapshot => "📸 Save Schema Snapshot",
+            FileAction::CompareSchemaSnapshot => "🧭 Compare Schema With Snapshot",
+            FileAction::SaveCellBytes(..) => "💾 Save Binary Value",
+            FileAction::SaveChartPng => "💾 Export Chart as PNG",
+            FileAction::OpenSqlFile => "📂 Open SQL File",
+            FileAction::SaveSqlFile => "💾 Save SQL File",
+        }
+    }
+
+    fn action_label(&self) -> &str {
+        match self {
+            FileAction::ShareQuery => "Save",
+            FileAction::OpenSharedQuery => "Open",
+            FileAction::SaveExplainPlan => "Save",
+            FileAction::SaveSchemaSnapshot => "Save",
+            FileAction::CompareSchemaSnapshot => "Compare",
+            FileAction::SaveCellBytes(..) => "Save",
+            FileAction::SaveChartPng => "Save",
+            FileAction::OpenSqlFile => "Open",
+            FileAction::SaveSqlFile => "Save",
+        }
+    }
+}
 
 pub struct DbClientApp {
     // Connection state
     pub config: Config,
     pub connection_string: String,
-    pub database: Option<Arc<Database>>,
+    pub replica_connection_strings: Vec<String>,
+    pub tls_settings: TlsSettings,
+    pub ssh_settings: SshSettings,
+    pub network_settings: NetworkSettings,
+    pub driver: Driver,
+    pub database: Option<Arc<dyn DatabaseBackend>>,
+    pub replica_database: Option<Arc<dyn DatabaseBackend>>,
     pub connection_status: String,
 
     // Tokio runtime for async operations
@@ -29,17 +115,54 @@ pub struct DbClientApp {
     pub tabs: Vec<Tab>,
     pub active_tab: usize,
     pub next_tab_id: usize,
+    // Tab results kept between sessions; None when there is no home directory
+    pub result_cache: Option<ResultCache>,
+    // Schema trees and table samples per connection, for browsing while it is unreachable
+    schema_cache: Option<SchemaCache>,
+    // When the schema shown was cached, while browsing it because connecting failed
+    offline: Option<DateTime<Utc>>,
 
-    // Query input
+    // SQL buffer of the query editor while no tab is open; tabs keep their own
     pub query_input: String,
     pub show_query_panel: bool,
+    pub force_primary: bool,
+    pub statement_timeout: StatementTimeout,
+    pub collect_resource_report: bool,
+    // Run queries in a transaction that stays open until committed or rolled back
+    pub transaction_mode: bool,
+    pub transaction_open: bool,
+    // Transaction state last reported by the primary; None when the driver cannot tell
+    pub transaction_status: Option<TransactionStatus>,
+    // Runs beside `pending_operation` so it never holds up or queues the user's work
+    pub transaction_probe: Option<Promise<anyhow::Result<Option<TransactionStatus>>>>,
+    pub last_transaction_poll: Option<Instant>,
+    // Anonymize exports and copied cells with the connection's rules
+    pub share_safely: bool,
+    pub show_find_bar: bool,
+    pub show_command_palette: bool,
+    // Open JSON filter builder of the active table tab
+    pub json_filter: Option<JsonFilter>,
+    // Rows of the active tab shown side by side
+    pub row_comparison: Option<RowComparison>,
+    // Original row and column of the active tab's cell open in the inspector
+    pub inspected_cell: Option<(usize, usize)>,
+    // Open find-and-replace over a column of the active table tab
+    pub value_replace: Option<ValueReplace>,
+    // Computed column being defined for the active tab
+    pub computed_column: Option<ComputedColumn>,
+    // Charted column of a tab, by tab id; closed when another tab is shown
+    pub value_distribution: Option<(usize, ValueDistribution)>,
 
     // Async operations
     pub pending_operation: Option<AsyncOperation>,
+    pub operation_queue: OperationQueue<QueuedOperation>,
 
     // Status
     pub status_message: String,
 
+    // Text to put on the clipboard on the next frame (set from async results)
+    pub pending_clipboard: Option<String>,
+
     // Settings dialog
     pub show_settings: bool,
     pub edit_connection: Option<DatabaseConnection>,
@@ -47,9 +170,71 @@ pub struct DbClientApp {
 
     // Saved queries
     pub saved_queries: SavedQueries,
+    pub query_history: QueryHistory,
+    pub show_query_history_dialog: bool,
+    // When the running ExecuteQuery was started, for the history duration
+    pub query_started_at: Option<Instant>,
+    // Time, rows and bytes of the last query or table load, shown in the status bar
+    pub last_query_stats: Option<QueryStats>,
+    // Rows of the running ExecuteQuery, and the tab they are being streamed into
+    pub query_stream: Option<Arc<Mutex<QueryStream>>>,
+    pub streaming_tab: Option<usize>,
     pub show_saved_queries_dialog: bool,
     pub show_save_query_dialog: bool,
 
+    // File dialog
+    pub file_action: Option<FileAction>,
+    // Where the chart goes once a screenshot arrives, and whether it was asked for yet
+    pub pending_chart_png: Option<(PathBuf, bool)>,
+    pub show_export_dialog: bool,
+    pub show_batch_export_dialog: bool,
+    // Shared with the batch export thread while it runs, kept afterwards for the result list
+    pub batch_progress: Option<Arc<Mutex<BatchProgress>>>,
+    pub show_script_dialog: bool,
+    pub show_paste_table_dialog: bool,
+    // Temporary tables created from pasted rows; they only exist on the primary's session
+    pub temp_tables: Vec<String>,
+    pub script_progress: Option<Arc<Mutex<ScriptProgress>>>,
+    // Snapshot description and its differences from the live schema, while the result is shown
+    pub schema_diff: Option<(String, Vec<SchemaChange>)>,
+    // Column changes staged in the ALTER assistant
+    pub alter_plan: Option<AlterPlan>,
+    // File, column mapping and NULL handling chosen in the import wizard
+    pub import_plan: Option<ImportPlan>,
+    // Shared with the import thread while it runs
+    pub import_progress: Option<Arc<ImportProgress>>,
+    // Schema, and table unless all of the schema's are, chosen for Export as SQL
+    pub sql_dump_target: Option<(String, Option<String>)>,
+    // Shared with the SQL export thread while it runs, kept afterwards for the result list
+    pub sql_dump_progress: Option<Arc<Mutex<BatchProgress>>>,
+    // Per-statement results of the last multi-statement query and how many were submitted
+    pub statement_summary: Option<(Vec<StatementSummary>, usize)>,
+    pub query_log: QueryLog,
+    pub show_query_log: bool,
+    // Watches of the current connection; refreshed while the panel is shown
+    pub watches: Vec<Watch>,
+    pub show_watch_panel: bool,
+    pub pending_write: Option<PendingWrite>,
+    // Close was requested during a write; exit once it finishes
+    pub show_quit_dialog: bool,
+    pub quit_when_idle: bool,
+    // Tab to run once the connection is (re-)established, e.g. a freshly opened shared query
+    pub run_after_connect: Option<usize>,
+    // State of a session that ended uncleanly, held back until the user restores or drops it
+    pub session_recovery: Option<AppState>,
+    // Passwords of ask-on-connect connections kept until the app closes, by credential reference
+    pub session_passwords: HashMap<String, String>,
+    // Open while a connection that never stores its password waits for it
+    pub password_prompt: Option<PasswordPromptDialog>,
+    // Values last given to query placeholders (":id", "$1"), kept until the app closes
+    pub param_values: HashMap<String, Option<String>>,
+    // Open while a query with placeholders waits for their values, with its tab to load into
+    pub param_prompt: Option<(ParamPromptDialog, String, Option<usize>)>,
+    // Settings files that could not be read at startup, until the user dismisses them
+    pub load_errors: Vec<String>,
+    // Reload the restored tabs once the connection is established
+    pub reload_restored_on_connect: bool,
+
     // UI Components
     menu_bar: MenuBar,
     status_bar: StatusBar,
@@ -63,12 +248,59 @@ pub struct DbClientApp {
     filter_bar: FilterBar,
     saved_queries_dialog: SavedQueriesDialog,
     save_query_dialog: SaveQueryDialog,
+    file_dialog: FileDialog,
+    edit_toolbar: EditToolbar,
+    find_bar: FindBar,
+    export_dialog: ExportDialog,
+    resource_report_panel: ResourceReportPanel,
+    plan_view: PlanView,
+    confirm_dialog: ConfirmDialog,
+    quit_dialog: QuitDialog,
+    query_history_dialog: QueryHistoryDialog,
+    batch_export_dialog: BatchExportDialog,
+    script_dialog: ScriptRunnerDialog,
+    tail_bar: TailBar,
+    bookmarks_panel: BookmarksPanel,
+    structure_view: StructureView,
+    schema_diff_dialog: SchemaDiffDialog,
+    alter_columns_dialog: AlterColumnsDialog,
+    import_dialog: ImportDialog,
+    sql_dump_dialog: SqlDumpDialog,
+    statement_summary_dialog: StatementSummaryDialog,
+    query_log_panel: QueryLogPanel,
+    watch_panel: WatchPanel,
+    restored_tab_banner: RestoredTabBanner,
+    large_table_banner: LargeTableBanner,
+    wide_table_banner: WideTableBanner,
+    follow_up_bar: FollowUpBar,
+    queue_panel: QueuePanel,
+    command_palette: CommandPalette,
+    distinct_bar: DistinctBar,
+    bar_chart: BarChart,
+    result_chart: ResultChart,
+    server_filter_bar: ServerFilterBar,
+    json_filter_dialog: JsonFilterDialog,
+    row_compare_dialog: RowCompareDialog,
+    cell_inspector: CellInspector,
+    replace_values_dialog: ReplaceValuesDialog,
+    computed_column_dialog: ComputedColumnDialog,
+    distribution_panel: DistributionPanel,
+    paste_table_dialog: PasteTableDialog,
+    session_restore_dialog: SessionRestoreDialog,
+    load_errors_dialog: LoadErrorsDialog,
 }
 
 impl DbClientApp {
     pub fn save_state(&self) {
+        // Keep the crashed session on disk until the user has decided about it
+        if self.session_recovery.is_some() {
+            return;
+        }
+        // Distinct views are not restored, tabs are saved with their full rows' settings
+        let mut tabs = self.tabs.clone();
+        tabs.iter_mut().for_each(Tab::show_all_rows);
         let state = AppState {
-            tabs: self.tabs.clone(),
+            tabs,
             active_tab: self.active_tab,
             next_tab_id: self.next_tab_id,
             expanded_schemas: self.expanded_schemas.clone(),
@@ -80,7 +312,12 @@ impl DbClientApp {
         // Setup monospace styles for better data display
         setup_styles(&cc.egui_ctx);
 
-        let config = Config::load().unwrap_or_else(|_| Config::new());
+        // Files that fail to load were copied aside; say so instead of starting empty silently
+        let mut load_errors = Vec::new();
+        let config = Config::load().unwrap_or_else(|e| {
+            load_errors.push(e.to_string());
+            Config::new()
+        });
 
         // Try to get connection from last saved connection, environment, or use default
         let connection_string = if let Some(conn) = config.get_last_connection() {
@@ -89,6 +326,21 @@ impl DbClientApp {
             std::env::var("DATABASE_URL")
                 .unwrap_or_else(|_| "host=localhost user=postgres password=postgres dbname=postgres".to_string())
         };
+        let replica_connection_strings = config.get_last_connection()
+            .map(|conn| conn.replica_connection_strings())
+            .unwrap_or_default();
+        let tls_settings = config.get_last_connection()
+            .map(|conn| conn.tls.clone())
+            .unwrap_or_default();
+        let ssh_settings = config.get_last_connection()
+            .map(|conn| conn.ssh.clone())
+            .unwrap_or_default();
+        let network_settings = config.get_last_connection()
+            .map(|conn| conn.network.clone())
+            .unwrap_or_default();
+        let driver = config.get_last_connection()
+            .map(|conn| conn.driver)
+            .unwrap_or_default();
 
         // Create a persistent tokio runtime for all async operations
         let runtime = Arc::new(
@@ -96,27 +348,53 @@ impl DbClientApp {
                 .expect("Failed to create tokio runtime")
         );
 
-        // Try to restore previous state
-        let (tabs, active_tab, next_tab_id, expanded_schemas) = if let Ok(state) = AppState::load() {
+        // Try to restore previous state. After a crash its tabs wait for the user to
+        // decide, and their cached results are dropped as they may not be consistent.
+        let crashed = AppState::begin_session().unwrap_or(false);
+        let (state, session_recovery) = match AppState::load() {
+            Ok(Some(state)) if crashed && !state.tabs.is_empty() => (None, Some(state)),
+            Ok(state) => (state, None),
+            Err(e) => {
+                load_errors.push(e.to_string());
+                (None, None)
+            }
+        };
+        let (mut tabs, active_tab, next_tab_id, expanded_schemas) = if let Some(state) = state {
             (state.tabs, state.active_tab, state.next_tab_id, state.expanded_schemas)
+        } else if let Some(state) = &session_recovery {
+            // Tab ids stay clear of the held-back tabs' cache entries
+            (Vec::new(), 0, state.next_tab_id, state.expanded_schemas.clone())
         } else {
             (Vec::new(), 0, 0, HashSet::new())
         };
+        for tab in &mut tabs {
+            tab.restored = true;
+        }
+        let result_cache = ResultCache::new().ok();
+        if let Some(cache) = &result_cache {
+            cache.retain(&tabs.iter().map(|tab| tab.id).collect::<Vec<_>>());
+        }
 
         // Load saved queries
-        let saved_queries = SavedQueries::load().unwrap_or_else(|_| SavedQueries::new());
-
-        // Initialize query_input from active tab if available
-        let initial_query_input = if active_tab < tabs.len() {
-            tabs[active_tab].query_input.clone()
-        } else {
-            String::new()
-        };
+        let saved_queries = SavedQueries::load().unwrap_or_else(|e| {
+            load_errors.push(e.to_string());
+            SavedQueries::new()
+        });
+        let query_history = QueryHistory::load().unwrap_or_else(|e| {
+            load_errors.push(e.to_string());
+            QueryHistory::default()
+        });
 
         let mut app = Self {
             config,
             connection_string,
+            replica_connection_strings,
+            tls_settings,
+            ssh_settings,
+            network_settings,
+            driver,
             database: None,
+            replica_database: None,
             connection_status: "Not connected".to_string(),
             runtime,
             schemas: Vec::new(),
@@ -127,16 +405,75 @@ impl DbClientApp {
             tabs,
             active_tab,
             next_tab_id,
-            query_input: initial_query_input,
+            result_cache,
+            schema_cache: SchemaCache::new().ok(),
+            offline: None,
+            query_input: String::new(),
             show_query_panel: false,
+            force_primary: false,
+            statement_timeout: StatementTimeout::None,
+            collect_resource_report: false,
+            transaction_mode: false,
+            transaction_open: false,
+            transaction_status: None,
+            transaction_probe: None,
+            last_transaction_poll: None,
+            share_safely: false,
+            show_find_bar: false,
+            show_command_palette: false,
+            json_filter: None,
+            row_comparison: None,
+            inspected_cell: None,
+            value_replace: None,
+            computed_column: None,
+            value_distribution: None,
             pending_operation: None,
+            operation_queue: OperationQueue::default(),
             status_message: "Ready".to_string(),
+            pending_clipboard: None,
             show_settings: false,
             edit_connection: None,
             edit_connection_index: None,
             saved_queries,
+            query_history,
+            show_query_history_dialog: false,
+            query_started_at: None,
+            last_query_stats: None,
+            query_stream: None,
+            streaming_tab: None,
             show_saved_queries_dialog: false,
             show_save_query_dialog: false,
+            file_action: None,
+            pending_chart_png: None,
+            show_export_dialog: false,
+            show_batch_export_dialog: false,
+            batch_progress: None,
+            show_script_dialog: false,
+            show_paste_table_dialog: false,
+            temp_tables: Vec::new(),
+            script_progress: None,
+            schema_diff: None,
+            alter_plan: None,
+            import_plan: None,
+            import_progress: None,
+            sql_dump_target: None,
+            sql_dump_progress: None,
+            statement_summary: None,
+            query_log: QueryLog::default(),
+            show_query_log: false,
+            watches: Vec::new(),
+            show_watch_panel: false,
+            pending_write: None,
+            show_quit_dialog: false,
+            quit_when_idle: false,
+            run_after_connect: None,
+            session_recovery,
+            session_passwords: HashMap::new(),
+            password_prompt: None,
+            param_values: HashMap::new(),
+            param_prompt: None,
+            load_errors,
+            reload_restored_on_connect: false,
             menu_bar: MenuBar::new(),
             status_bar: StatusBar::new(),
             query_panel: QueryPanel::new(),
@@ -149,34 +486,238 @@ impl DbClientApp {
             filter_bar: FilterBar::new(),
             saved_queries_dialog: SavedQueriesDialog::new(),
             save_query_dialog: SaveQueryDialog::new(),
+            file_dialog: FileDialog::new(),
+            edit_toolbar: EditToolbar::new(),
+            find_bar: FindBar::new(),
+            export_dialog: ExportDialog::new(),
+            resource_report_panel: ResourceReportPanel::new(),
+            plan_view: PlanView::new(),
+            confirm_dialog: ConfirmDialog::new(),
+            quit_dialog: QuitDialog::new(),
+            query_history_dialog: QueryHistoryDialog::new(),
+            batch_export_dialog: BatchExportDialog::new(),
+            script_dialog: ScriptRunnerDialog::new(),
+            tail_bar: TailBar::new(),
+            bookmarks_panel: BookmarksPanel::new(),
+            structure_view: StructureView::new(),
+            schema_diff_dialog: SchemaDiffDialog::new(),
+            alter_columns_dialog: AlterColumnsDialog::new(),
+            import_dialog: ImportDialog::new(),
+            sql_dump_dialog: SqlDumpDialog::new(),
+            statement_summary_dialog: StatementSummaryDialog::new(),
+            query_log_panel: QueryLogPanel::new(),
+            watch_panel: WatchPanel::new(),
+            restored_tab_banner: RestoredTabBanner::new(),
+            large_table_banner: LargeTableBanner::new(),
+            wide_table_banner: WideTableBanner::new(),
+            follow_up_bar: FollowUpBar::new(),
+            queue_panel: QueuePanel::new(),
+            command_palette: CommandPalette::new(),
+            distinct_bar: DistinctBar::new(),
+            bar_chart: BarChart::new(),
+            result_chart: ResultChart::new(),
+            server_filter_bar: ServerFilterBar::new(),
+            json_filter_dialog: JsonFilterDialog::new(),
+            row_compare_dialog: RowCompareDialog::new(),
+            cell_inspector: CellInspector::new(),
+            replace_values_dialog: ReplaceValuesDialog::new(),
+            computed_column_dialog: ComputedColumnDialog::new(),
+            distribution_panel: DistributionPanel::new(),
+            paste_table_dialog: PasteTableDialog::new(),
+            session_restore_dialog: SessionRestoreDialog::new(),
+            load_errors_dialog: LoadErrorsDialog::new(),
         };
 
-        // Auto-connect on startup
-        app.connect_to_database();
+        app.restore_active_tab_data();
+
+        // Auto-connect on startup, to the server the active tab came from if it has one
+        match app.tab_connection_index(app.active_tab) {
+            Some(idx) => app.switch_connection(idx),
+            None => app.connect_to_database(),
+        }
 
         app
     }
 
+    /// Reopen the tabs of a session that ended uncleanly in front of any opened since,
+    /// and reload them once connected to the active tab's server.
+    fn restore_crashed_session(&mut self, state: AppState) {
+        let mut tabs = state.tabs;
+        for tab in &mut tabs {
+            tab.restored = true;
+        }
+        self.active_tab = state.active_tab.min(tabs.len().saturating_sub(1));
+        tabs.append(&mut self.tabs);
+        self.tabs = tabs;
+        self.save_state();
+
+        match self.tab_connection_index(self.active_tab) {
+            Some(idx) => {
+                self.reload_restored_on_connect = true;
+                self.switch_connection(idx);
+            }
+            None if self.database.is_some() => self.reload_restored_tabs(),
+            None => self.reload_restored_on_connect = true,
+        }
+    }
+
+    /// Reload every restored tab of the current connection. Queries are only rerun when
+    /// read-only and without placeholders; the others, and tabs of other connections,
+    /// keep their reload prompt.
+    fn reload_restored_tabs(&mut self) {
+        for index in 0..self.tabs.len() {
+            if !self.tabs[index].restored || self.tab_connection_index(index).is_some() || self.tabs[index].awaits_rows() {
+                continue;
+            }
+            match self.tabs[index].source.clone() {
+                TabSource::Table { schema, table } => self.load_table_data(schema, table, Some(index)),
+                TabSource::Query { sql } if routing::is_read_only(&sql) && db::placeholders(&sql).is_empty() => {
+                    self.run_query(sql, Some(index))
+                }
+                TabSource::Query { .. } | TabSource::Explain { .. } => {}
+            }
+        }
+    }
+
+    /// Make saved connection `idx` the current one and connect to it.
+    fn switch_connection(&mut self, idx: usize) {
+        let Some(conn) = self.config.get_connection(idx) else {
+            return;
+        };
+        self.connection_string = conn.to_connection_string();
+        self.replica_connection_strings = conn.replica_connection_strings();
+        self.tls_settings = conn.tls.clone();
+        self.ssh_settings = conn.ssh.clone();
+        self.network_settings = conn.network.clone();
+        self.driver = conn.driver;
+        self.config.last_connection_index = Some(idx);
+        let _ = self.config.save();
+        self.connect_to_database();
+    }
+
+    /// Saved connection a tab's data came from, when that is not the current one.
+    fn tab_connection_index(&self, tab_index: usize) -> Option<usize> {
+        let name = self.tabs.get(tab_index)?.connection_name.as_ref()?;
+        let idx = self.config.connections.iter().position(|conn| &conn.name == name)?;
+        (self.config.last_connection_index != Some(idx)).then_some(idx)
+    }
+
     pub fn connect_to_database(&mut self) {
+        // A connection that never stores its password asks for it, unless given this session
+        if let Some(conn) = self.config.get_last_connection().filter(|conn| conn.ask_password) {
+            let Some(password) = self.session_passwords.get(&conn.credential_ref) else {
+                self.password_prompt = Some(PasswordPromptDialog::new(conn.describe()));
+                self.connection_status = "Password required".to_string();
+                return;
+            };
+            self.connect_with_password(&password.clone());
+            return;
+        }
+        self.open_connection();
+    }
+
+    /// Connect to the current connection with a password typed into the prompt.
+    fn connect_with_password(&mut self, password: &str) {
+        if let Some(conn) = self.config.get_last_connection() {
+            let conn = conn.with_password(password);
+            self.connection_string = conn.to_connection_string();
+            self.replica_connection_strings = conn.replica_connection_strings();
+        }
+        self.open_connection();
+    }
+
+    fn open_connection(&mut self) {
         let connection_string = self.connection_string.clone();
+        let replica_connection_strings = self.replica_connection_strings.clone();
+        let tls_settings = self.tls_settings.clone();
+        let ssh_settings = self.ssh_settings.clone();
+        let network_settings = self.network_settings.clone();
+        let driver = self.driver;
         self.connection_status = "Connecting...".to_string();
+        self.replica_database = None;
+        // Closing the old connection rolls back whatever it had open, and drops its temporary tables
+        self.transaction_open = false;
+        self.transaction_status = None;
+        self.temp_tables.clear();
         let runtime = Arc::clone(&self.runtime);
 
         self.pending_operation = Some(AsyncOperation::LoadStructure(
             Promise::spawn_thread("load_structure", move || {
                 runtime.block_on(async move {
-                    let db = Database::connect(&connection_string).await?;
+                    let db = db::connect(driver, &connection_string, &tls_settings, &ssh_settings, &network_settings).await?;
                     let schemas = db.list_schemas_with_tables().await?;
-                    Ok((Arc::new(db), schemas))
+
+                    // Use the first reachable replica; an unreachable replica is not fatal,
+                    // everything simply runs on the primary
+                    let mut replica = None;
+                    for replica_string in &replica_connection_strings {
+                        match db::connect(driver, replica_string, &tls_settings, &ssh_settings, &network_settings).await {
+                            Ok(replica_db) => {
+                                replica = Some(replica_db);
+                                break;
+                            }
+                            Err(e) => eprintln!("Replica connection error: {}", e),
+                        }
+                    }
+
+                    Ok((db, replica, schemas))
                 })
             })
         ));
     }
 
+    /// Describe the current connection for watermarks, without credentials.
+    pub fn source_description(&self) -> String {
+        self.config.get_last_connection()
+            .map(|conn| conn.describe())
+            .unwrap_or_else(|| "DATABASE_URL".to_string())
+    }
+
+    /// Pick the database a statement should run on, preferring a replica for reads.
+    fn database_for_query(&self, sql: &str) -> Option<(Arc<dyn DatabaseBackend>, QueryTarget)> {
+        // The replica cannot see what the open transaction has written, nor the temporary tables
+        let lowercase = sql.to_lowercase();
+        let uses_temp_table = self.temp_tables.iter().any(|table| lowercase.contains(&table.to_lowercase()));
+        let target = routing::route(sql, self.force_primary || self.transaction_open || uses_temp_table, self.replica_database.is_some());
+        self.database_for_target(target)
+    }
+
+    /// Pick the database to read whole tables from, the replica when there is one.
+    fn database_for_read(&self) -> Option<(Arc<dyn DatabaseBackend>, QueryTarget)> {
+        self.database_for_target(routing::read_target(self.force_primary || self.transaction_open, self.replica_database.is_some()))
+    }
+
+    fn database_for_target(&self, target: QueryTarget) -> Option<(Arc<dyn DatabaseBackend>, QueryTarget)> {
+        let db = match target {
+            QueryTarget::Replica => self.replica_database.as_ref(),
+            QueryTarget::Primary => self.database.as_ref(),
+        };
+        db.map(|db| (Arc::clone(db), target))
+    }
+
     pub fn load_table_data(&mut self, schema: String, table_name: String, tab_index: Option<usize>) {
-        if let Some(db) = &self.database {
+        if self.offline.is_some() {
+            self.show_cached_sample(schema, table_name, tab_index);
+            return;
+        }
+        if self.pending_operation.is_some() {
+            let label = format!("Load {}.{}", schema, table_name);
+            self.enqueue(label, QueuedOperation::LoadTable(schema, table_name, tab_index));
+            return;
+        }
+        // A large table loads no more than a preview, however the load was asked for
+        let preview_rows = self.config.preferences.preview_rows;
+        if let Some(tab) = tab_index.and_then(|index| self.tabs.get_mut(index)).filter(|tab| tab.awaits_rows()) {
+            tab.row_limit = Some(preview_rows);
+        }
+        let tab = tab_index.and_then(|index| self.tabs.get(index));
+        let filter = tab.and_then(|tab| tab.server_filter(self.driver));
+        let order = tab.and_then(Tab::server_order);
+        let limit = tab.and_then(|tab| tab.row_limit).unwrap_or(MAX_TABLE_ROWS);
+        if let Some((db, _)) = self.database_for_read() {
             self.status_message = format!("Loading table: {}.{}", schema, table_name);
-            let db_clone = Arc::clone(db);
+            let cancel_handle = db.cancel_handle();
+            let db_clone = db;
             let schema_clone = schema.clone();
             let table_name_clone = table_name.clone();
             let full_table_name = format!("{}.{}", schema, table_name);
@@ -184,244 +725,2244 @@ impl DbClientApp {
 
             let promise = Promise::spawn_thread("query_table", move || {
                 runtime.block_on(async move {
-                    db_clone.query_table(&full_table_name, 100000).await
+                    let (columns, rows) = db_clone.query_table(&full_table_name, filter.as_ref(), order.as_ref(), limit as i64).await?;
+                    // Lineage is only a hint, so failing to resolve it does not fail the load
+                    let names: Vec<String> = columns.iter().map(|c| c.name.clone()).collect();
+                    let lineage = db_clone.view_lineage(&schema, &table_name, &names).await
+                        .unwrap_or_else(|e| {
+                            eprintln!("Failed to resolve view lineage: {}", e);
+                            ColumnLineage::new()
+                        });
+                    Ok((columns, rows, lineage))
                 })
             });
 
-            self.pending_operation = Some(AsyncOperation::LoadTableData(schema_clone, table_name_clone, promise, tab_index));
+            self.query_started_at = Some(Instant::now());
+            self.pending_operation = Some(AsyncOperation::LoadTableData(schema_clone, table_name_clone, promise, tab_index, cancel_handle));
+        }
+    }
+
+    /// Offline, show the rows of `schema.table` cached when it was last opened, or only
+    /// its columns when none were.
+    fn show_cached_sample(&mut self, schema: String, table_name: String, tab_index: Option<usize>) {
+        let connection = self.config.get_last_connection().map(|conn| conn.name.clone()).unwrap_or_default();
+        let sample = self.schema_cache.as_ref().and_then(|cache| cache.load_sample(&connection, &schema, &table_name));
+        self.status_message = match &sample {
+            Some(data) => format!("Offline: showing {} cached rows of {}.{}", data.rows.len(), schema, table_name),
+            None => format!("Offline: no rows of {}.{} were cached, only its columns", schema, table_name),
+        };
+        let data = sample.unwrap_or_else(|| TableData {
+            name: format!("{}.{}", schema, table_name),
+            columns: self.schemas.iter()
+                .find(|s| s.name == schema)
+                .and_then(|s| s.table_columns.get(&table_name).cloned())
+                .unwrap_or_default(),
+            rows: Vec::new(),
+            lineage: ColumnLineage::new(),
+            generation: 0,
+        });
+
+        match tab_index.and_then(|index| self.tabs.get_mut(index)) {
+            Some(tab) => {
+                tab.show_all_rows();
+                tab.data = Some(data);
+                tab.clamp_page();
+            }
+            None => {
+                let title = format!("{}.{}", schema, table_name);
+                self.add_tab(title, Some(data), TabSource::Table { schema, table: table_name });
+            }
+        }
+    }
+
+    /// Browse the schema cached for the current connection after connecting to it failed.
+    /// False when nothing was cached for it.
+    fn go_offline(&mut self) -> bool {
+        let Some(conn) = self.config.get_last_connection() else {
+            return false;
+        };
+        let Some(CachedSchema { cached_at, schemas }) = self.schema_cache.as_ref().and_then(|cache| cache.load_schemas(&conn.name)) else {
+            // The schema cached for another connection is no use for this one
+            if self.offline.take().is_some() {
+                self.schemas.clear();
+            }
+            return false;
+        };
+        self.schemas = schemas;
+        self.offline = Some(cached_at);
+        // Nothing may run against whatever was connected before
+        self.database = None;
+        self.replica_database = None;
+        true
+    }
+
+    /// Local time the schema browsed offline was cached at, None while connected.
+    fn offline_since(&self) -> Option<String> {
+        self.offline.map(|cached_at| cached_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+    }
+
+    /// The current connection when it has production safeguards turned on.
+    fn dangerous_connection(&self) -> Option<&DatabaseConnection> {
+        self.config.get_last_connection().filter(|conn| conn.dangerous)
+    }
+
+    /// SQL buffer of the query editor: the active tab's, or the one used while no tab is open.
+    fn query_buffer(&self) -> &str {
+        self.tabs.get(self.active_tab).map_or(&self.query_input, |tab| &tab.query_input)
+    }
+
+    fn query_buffer_mut(&mut self) -> &mut String {
+        match self.tabs.get_mut(self.active_tab) {
+            Some(tab) => &mut tab.query_input,
+            None => &mut self.query_input,
         }
     }
 
+    /// Run the query in the editor, loading the result into `tab_index` or a new tab.
     pub fn execute_query(&mut self, tab_index: Option<usize>) {
-        if let Some(db) = &self.database {
-            let query = self.query_input.clone();
-            if query.trim().is_empty() {
+        self.execute_sql(self.query_buffer().to_string(), tab_index);
+    }
+
+    /// Run `query`, which need not be the whole editor buffer (e.g. the selection),
+    /// once its placeholders have values and the production safeguards had their say.
+    fn execute_sql(&mut self, query: String, tab_index: Option<usize>) {
+        if query.trim().is_empty() {
+            return;
+        }
+        if self.offline.is_some() {
+            self.status_message = "Offline: queries run once the connection is back (Retry in the banner)".to_string();
+            return;
+        }
+
+        let names = db::placeholders(&query);
+        if !names.is_empty() {
+            let params = names.into_iter()
+                .map(|name| {
+                    let value = self.param_values.get(&name).cloned().unwrap_or(Some(String::new()));
+                    (name, value)
+                })
+                .collect();
+            self.param_prompt = Some((ParamPromptDialog::new(params), query, tab_index));
+            return;
+        }
+        self.confirm_and_run(query, tab_index);
+    }
+
+    /// Ask before a write on a dangerous connection, otherwise run right away.
+    fn confirm_and_run(&mut self, query: String, tab_index: Option<usize>) {
+        if self.dangerous_connection().is_some() && !routing::is_read_only(&query) {
+            self.pending_write = Some(PendingWrite::Query(query, tab_index));
+            return;
+        }
+
+        self.run_query(query, tab_index);
+    }
+
+    fn run_query(&mut self, query: String, tab_index: Option<usize>) {
+        if self.pending_operation.is_some() {
+            self.enqueue(short_label(&query), QueuedOperation::Query(query, tab_index));
+            return;
+        }
+        if self.transaction_mode && !self.transaction_open {
+            self.control_transaction(TransactionControl::Begin, Some((query, tab_index)));
+            return;
+        }
+
+        let statements = db::split_statements(&query);
+        let parameterized = !db::placeholders(&query).is_empty();
+        if statements.len() > 1 {
+            if parameterized {
+                self.status_message = "Placeholders can only be used in a single statement".to_string();
                 return;
             }
+            self.run_statements(query, statements);
+            return;
+        }
 
-            self.status_message = "Executing query...".to_string();
-            let db_clone = Arc::clone(db);
+        if let Some((db, target)) = self.database_for_query(&query) {
+            self.status_message = if self.replica_database.is_some() {
+                format!("Executing query on {}...", target.as_str())
+            } else {
+                "Executing query...".to_string()
+            };
+            let cancel_handle = db.cancel_handle();
+            let db_clone = db;
             let query_clone = query.clone();
+            // Placeholders take the values last entered for them
+            let (bound, params) = if parameterized {
+                db::bind_placeholders(self.driver, &query, &self.param_values)
+            } else {
+                (query.clone(), Vec::new())
+            };
             let runtime = Arc::clone(&self.runtime);
+            let timeout = self.statement_timeout;
+            // The report re-runs the statement, so never do it for writes, nor without
+            // the values bound to its placeholders
+            let collect_report = self.collect_resource_report && routing::is_read_only(&query) && !parameterized;
 
+            let stream = Arc::new(Mutex::new(QueryStream::default()));
+            let stream_clone = Arc::clone(&stream);
+            self.query_stream = Some(stream);
+            self.streaming_tab = None;
+
+            self.query_started_at = Some(Instant::now());
             let promise = Promise::spawn_thread("execute_query", move || {
                 runtime.block_on(async move {
-                    db_clone.execute_query(&query_clone).await
+                    db_clone.stream_query(&bound, &params, timeout, &stream_clone).await?;
+                    let report = if collect_report {
+                        match db_clone.resource_report(&query_clone).await {
+                            Ok(report) => Some(report),
+                            Err(e) => {
+                                eprintln!("Resource report failed: {}", e);
+                                None
+                            }
+                        }
+                    } else {
+                        None
+                    };
+                    Ok(report)
                 })
             });
 
-            self.pending_operation = Some(AsyncOperation::ExecuteQuery(query, promise, tab_index));
+            self.pending_operation = Some(AsyncOperation::ExecuteQuery(query, promise, tab_index, target, cancel_handle));
         }
     }
 
-    pub fn add_tab(&mut self, title: String, data: Option<TableData>, source: TabSource) {
-        // Initialize query_input from source if it's a Query
-        let query_input = match &source {
-            TabSource::Query { sql } => sql.clone(),
-            TabSource::Table { .. } => String::new(),
-        };
-
-        let tab = Tab {
-            id: self.next_tab_id,
-            title,
-            data,
-            is_loading: false,
-            sort_column: None,
-            sort_ascending: true,
-            current_page: 0,
-            page_size: 100,
-            source,
-            filters: Vec::new(),
-            search_text: String::new(),
-            search_match_index: 0,
-            query_input,
+    /// Run the statements of a multi-statement buffer one after another. Each result set
+    /// opens its own tab; row counts and errors are listed in a summary.
+    fn run_statements(&mut self, query: String, statements: Vec<String>) {
+        let Some((db, _)) = self.database_for_query(&query) else {
+            return;
         };
-        self.next_tab_id += 1;
-        self.tabs.push(tab);
-        self.active_tab = self.tabs.len() - 1;
-        self.save_state();
+
+        let total = statements.len();
+        self.status_message = format!("Executing {} statements...", total);
+        let cancel_handle = db.cancel_handle();
+        let runtime = Arc::clone(&self.runtime);
+        let timeout = self.statement_timeout;
+
+        self.query_started_at = Some(Instant::now());
+        let promise = Promise::spawn_thread("execute_statements", move || {
+            runtime.block_on(async move { db::run_statements(db.as_ref(), statements, timeout).await })
+        });
+        self.pending_operation = Some(AsyncOperation::ExecuteStatements(query, promise, total, cancel_handle));
     }
 
-    pub fn reload_current_tab(&mut self) {
-        if let Some(tab) = self.tabs.get(self.active_tab) {
-            let source = tab.source.clone();
-            let tab_index = self.active_tab;
-            match source {
-                TabSource::Table { schema, table } => {
-                    self.load_table_data(schema, table, Some(tab_index));
+    fn open_statement_results(&mut self, runs: Vec<StatementRun>, total: usize) {
+        let summary: Vec<StatementSummary> = runs.iter().map(StatementRun::summary).collect();
+        let affected: u64 = runs.iter()
+            .filter_map(|run| match run.outcome {
+                Ok(StatementOutcome::Affected(count)) => Some(count),
+                _ => None,
+            })
+            .sum();
+
+        let mut result_tabs = 0;
+        for (index, run) in runs.into_iter().enumerate() {
+            if let Ok(StatementOutcome::Rows((columns, rows))) = run.outcome {
+                let title = format!("Result {}", index + 1);
+                let data = TableData { name: title.clone(), columns, rows, lineage: ColumnLineage::new(), generation: 0 };
+                self.add_tab(title, Some(data), TabSource::Query { sql: run.sql });
+                result_tabs += 1;
+            }
+        }
+
+        self.status_message = match summary.iter().position(|line| line.is_error) {
+            Some(index) => format!("Statement {} of {} failed: {}", index + 1, total, summary[index].result),
+            None => format!("{} statements: {} result tab(s), {} row(s) affected", total, result_tabs, affected),
+        };
+        // Result sets speak for themselves, the summary is for everything else
+        if result_tabs < summary.len() {
+            self.statement_summary = Some((summary, total));
+        }
+    }
+
+    /// Move the rows a running query has received so far into its tab, opening the
+    /// tab (or resetting the one being reloaded) once the result columns are known.
+    fn drain_query_stream(&mut self) {
+        let Some(AsyncOperation::ExecuteQuery(query, _, tab_index, _, _)) = &self.pending_operation else {
+            return;
+        };
+        let Some(stream) = self.query_stream.clone() else {
+            return;
+        };
+        let mut stream = stream.lock().unwrap();
+
+        if self.streaming_tab.is_none() {
+            let Some(columns) = stream.columns.clone() else {
+                return;
+            };
+            let data = TableData {
+                name: "Query Result".to_string(),
+                columns,
+                rows: Vec::new(),
+                lineage: ColumnLineage::new(),
+                generation: 0,
+            };
+            match *tab_index {
+                Some(idx) => {
+                    if let Some(tab) = self.tabs.get_mut(idx) {
+                        tab.show_all_rows();
+                        // Remember what produced the rows, for reloads and LIMIT/OFFSET paging
+                        if let TabSource::Query { sql } = &mut tab.source {
+                            sql.clone_from(query);
+                        }
+                        tab.data = Some(data);
+                        tab.current_page = 0;
+                        tab.fit_page_to_sql();
+                        tab.restored = false;
+                        self.streaming_tab = Some(idx);
+                    }
                 }
-                TabSource::Query { sql } => {
-                    self.query_input = sql;
-                    self.execute_query(Some(tab_index));
+                None => {
+                    let source = TabSource::Query { sql: query.clone() };
+                    self.add_tab("Query Result".to_string(), Some(data), source);
+                    self.streaming_tab = Some(self.active_tab);
                 }
             }
         }
+
+        let rows = stream.take_rows();
+        if rows.is_empty() {
+            return;
+        }
+        if let Some(data) = self.streaming_tab.and_then(|idx| self.tabs.get_mut(idx)).and_then(|tab| tab.data.as_mut()) {
+            data.rows.extend(rows);
+        }
+        self.status_message = format!("Executing query... {} rows received", stream.total_rows);
     }
 
-    pub fn sort_tab_data(&mut self, tab_index: usize, column_index: usize) {
+    /// Stage an in-grid cell edit on a table tab, keyed by the row's primary key.
+    fn stage_cell_edit(&mut self, tab_index: usize, row: usize, column: usize, value: String) {
         if let Some(tab) = self.tabs.get_mut(tab_index) {
-            // Toggle sort direction if clicking same column
-            if tab.sort_column == Some(column_index) {
-                tab.sort_ascending = !tab.sort_ascending;
-            } else {
-                tab.sort_column = Some(column_index);
-                tab.sort_ascending = true;
+            if let Some(data) = &tab.data {
+                if let (Some(key_columns), Some(row_values)) = (PendingEdits::key_columns(&data.columns), data.rows.get(row)) {
+                    let key = PendingEdits::row_key(row_values, &key_columns);
+                    let original = row_values.get(column).map(|value| value.to_string()).unwrap_or_default();
+                    tab.pending_edits.stage(key, column, original, value);
+                }
             }
+        }
+    }
 
-            // Sort the data
-            if let Some(data) = &mut tab.data {
-                let ascending = tab.sort_ascending;
-                data.rows.sort_by(|a, b| {
-                    let a_val = a.get(column_index).map(|s| s.as_str()).unwrap_or("");
-                    let b_val = b.get(column_index).map(|s| s.as_str()).unwrap_or("");
+    /// Write a tab's staged edits to the primary as one transaction of UPDATEs.
+    pub fn apply_pending_edits(&mut self, tab_index: usize) {
+        if self.dangerous_connection().is_some() {
+            self.pending_write = Some(PendingWrite::ApplyEdits(tab_index));
+            return;
+        }
+        self.write_pending_edits(tab_index);
+    }
 
-                    // Try to parse as numbers for numeric sorting
-                    let cmp = match (a_val.parse::<f64>(), b_val.parse::<f64>()) {
-                        (Ok(a_num), Ok(b_num)) => a_num.partial_cmp(&b_num).unwrap_or(std::cmp::Ordering::Equal),
-                        _ => a_val.cmp(b_val),
-                    };
+    fn write_pending_edits(&mut self, tab_index: usize) {
+        if self.pending_operation.is_some() {
+            self.enqueue("Apply staged edits".to_string(), QueuedOperation::ApplyEdits(tab_index));
+            return;
+        }
+        let statements = match self.tabs.get(tab_index) {
+            Some(Tab { source: TabSource::Table { schema, table }, data: Some(data), pending_edits, .. }) => {
+                pending_edits.to_update_statements(schema, table, &data.columns)
+            }
+            _ => return,
+        };
 
-                    if ascending { cmp } else { cmp.reverse() }
-                });
+        let statements = match statements {
+            Ok(statements) => statements,
+            Err(e) => {
+                self.status_message = format!("Cannot apply edits: {}", e);
+                return;
             }
-            self.save_state();
+        };
+
+        if let Some(db) = &self.database {
+            self.status_message = format!("Applying {} update(s)...", statements.len());
+            let db_clone = Arc::clone(db);
+            let runtime = Arc::clone(&self.runtime);
+
+            let promise = Promise::spawn_thread("apply_edits", move || {
+                runtime.block_on(async move {
+                    db_clone.apply_updates(&statements).await
+                })
+            });
+
+            self.pending_operation = Some(AsyncOperation::ApplyEdits(promise, tab_index));
         }
     }
 
-    pub fn close_tab(&mut self, index: usize) {
-        if index < self.tabs.len() {
-            self.tabs.remove(index);
-            if self.active_tab >= self.tabs.len() && self.active_tab > 0 {
-                self.active_tab = self.tabs.len() - 1;
-            }
-            self.save_state();
-        }
+    /// Run EXPLAIN (FORMAT JSON) for the current query and send the plan to `destination`.
+    pub fn export_explain_plan(&mut self, destination: PlanDestination) {
+        self.run_explain(self.query_buffer().to_string(), false, destination);
     }
-}
 
-impl eframe::App for DbClientApp {
-    fn save(&mut self, _storage: &mut dyn eframe::Storage) {
-        self.save_state();
+    /// Run EXPLAIN for `sql` and show the plan tree in an explain tab, reloading
+    /// `tab_index` if given.
+    pub fn explain_query(&mut self, sql: &str, analyze: bool, tab_index: Option<usize>) {
+        // ANALYZE executes the statement
+        if analyze && !routing::is_read_only(sql) {
+            self.status_message = "EXPLAIN ANALYZE is only available for read-only queries".to_string();
+            return;
+        }
+        let sql = sql.trim().to_string();
+        self.run_explain(sql.clone(), analyze, PlanDestination::Tab { sql, analyze, tab_index });
     }
 
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Handle pending async operations
-        self.handle_async_operations();
+    fn run_explain(&mut self, query: String, analyze: bool, destination: PlanDestination) {
+        if query.trim().is_empty() {
+            self.status_message = "No query to explain".to_string();
+            return;
+        }
 
-        // Top menu bar
-        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-            if let Some(event) = self.menu_bar.show(ui, &self.connection_status) {
-                match event {
-                    MenuBarEvent::ShowSettings => self.show_settings = true,
-                    MenuBarEvent::Quit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
-                    MenuBarEvent::ToggleQueryPanel => self.show_query_panel = !self.show_query_panel,
-                    MenuBarEvent::Refresh => self.connect_to_database(),
-                }
-            }
-        });
+        if let Some((db, _)) = self.database_for_query(&query) {
+            self.status_message = "Running EXPLAIN...".to_string();
+            let cancel_handle = db.cancel_handle();
+            let runtime = Arc::clone(&self.runtime);
 
-        // Status bar
-        let row_count = self.tabs.get(self.active_tab)
-            .and_then(|tab| tab.data.as_ref())
-            .map(|data| data.rows.len());
+            let promise = Promise::spawn_thread("explain_plan", move || {
+                runtime.block_on(async move {
+                    db.explain_json(&query, analyze).await
+                })
+            });
 
-        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
-            self.status_bar.show(ui, &self.status_message, row_count);
-        });
+            self.pending_operation = Some(AsyncOperation::ExplainPlan(promise, destination, cancel_handle));
+        }
+    }
 
-        // Query panel (if shown) - syncs with active tab's query
-        if self.show_query_panel {
-            // Collect available tables and columns for autocomplete
-            let available_tables: Vec<String> = self.schemas.iter()
-                .flat_map(|schema| schema.tables.iter().map(|t| format!("{}.{}", schema.name, t)))
-                .collect();
+    /// The current connection's anonymization rules while Share Safely is on.
+    fn anonymization_rules(&self) -> Vec<AnonymizeRule> {
+        if !self.share_safely {
+            return Vec::new();
+        }
+        self.config.get_last_connection().map(|conn| conn.anonymization.clone()).unwrap_or_default()
+    }
 
-            let available_columns: Vec<String> = if let Some(tab) = self.tabs.get(self.active_tab) {
-                if let Some(data) = &tab.data {
-                    data.columns.iter().map(|c| c.name.clone()).collect()
-                } else {
-                    Vec::new()
-                }
-            } else {
-                Vec::new()
-            };
+    /// Write the active tab's filtered rows to `path` on a background thread.
+    pub fn start_export(&mut self, format: ExportFormat, settings: ExportSettings, path: PathBuf) {
+        let Some(tab) = self.tabs.get(self.active_tab) else { return };
+        let Some(data) = &tab.data else { return };
 
-            // Get or create query for active tab
-            if self.tabs.is_empty() {
-                // No tabs, use global query_input
-                egui::TopBottomPanel::top("query_panel").show(ctx, |ui| {
-                    if let Some(event) = self.query_panel.show(ui, &mut self.query_input, &available_tables, &available_columns) {
-                        match event {
-                            QueryPanelEvent::Execute => self.execute_query(None),
-                            QueryPanelEvent::Clear => self.query_input.clear(),
-                            QueryPanelEvent::Close => self.show_query_panel = false,
-                            QueryPanelEvent::SaveQuery => {
-                                if !self.query_input.trim().is_empty() {
-                                    self.show_save_query_dialog = true;
-                                } else {
-                                    self.status_message = "Cannot save empty query".to_string();
-                                }
-                            },
-                            QueryPanelEvent::LoadQuery => self.show_saved_queries_dialog = true,
-                        }
-                    }
-                });
-            } else {
-                // Use active tab's query
-                let active_tab_query = if let Some(tab) = self.tabs.get_mut(self.active_tab) {
-                    &mut tab.query_input
-                } else {
-                    &mut self.query_input
-                };
+        let watermark = Watermark::new(self.source_description(), Some(tab.source_sql(self.driver)));
+        let (header_comment, footer_comment) = match format.comment_prefix() {
+            Some(prefix) => watermark.header_and_footer(prefix, &self.config.preferences),
+            None => (None, None),
+        };
 
-                let mut temp_query = active_tab_query.clone();
-                let mut execute = false;
-                let mut clear = false;
-                let mut close = false;
-                let mut save_query = false;
-                let mut load_query = false;
+        let mut rows = tab.filtered_rows();
+        if let Some(anonymizer) = Anonymizer::new(&self.anonymization_rules(), &data.columns) {
+            rows = anonymizer.rows(&rows);
+        }
 
-                egui::TopBottomPanel::top("query_panel").show(ctx, |ui| {
-                    if let Some(event) = self.query_panel.show(ui, &mut temp_query, &available_tables, &available_columns) {
-                        match event {
-                            QueryPanelEvent::Execute => execute = true,
-                            QueryPanelEvent::Clear => clear = true,
-                            QueryPanelEvent::Close => close = true,
-                            QueryPanelEvent::SaveQuery => save_query = true,
-                            QueryPanelEvent::LoadQuery => load_query = true,
-                        }
-                    }
+        let job = ExportJob {
+            format,
+            settings,
+            columns: data.columns.clone(),
+            rows,
+            header_comment,
+            footer_comment,
+        };
+
+        self.status_message = format!("Exporting {} rows...", job.rows.len());
+        let target = path.clone();
+        let promise = Promise::spawn_thread("export", move || job.write_to(&target));
+        self.pending_operation = Some(AsyncOperation::Export(promise, path));
+    }
+
+    /// Export each of `tables` to its own file in `directory` on a background thread,
+    /// reporting per-table progress through `batch_progress`.
+    pub fn start_batch_export(&mut self, tables: Vec<(String, String)>, format: ExportFormat, settings: ExportSettings, directory: PathBuf) {
+        let Some((db, _)) = self.database_for_read() else {
+            self.status_message = "Not connected".to_string();
+            return;
+        };
+
+        let progress = Arc::new(Mutex::new(BatchProgress::new(&tables)));
+        self.batch_progress = Some(Arc::clone(&progress));
+        self.status_message = format!("Exporting {} tables...", tables.len());
+
+        let runtime = Arc::clone(&self.runtime);
+        let source = self.source_description();
+        let preferences = self.config.preferences.clone();
+        let rules = self.anonymization_rules();
+        let target = directory.clone();
+
+        let promise = Promise::spawn_thread("batch_export", move || {
+            std::fs::create_dir_all(&target)?;
+            let mut exported = 0;
+
+            for (index, (schema, table)) in tables.iter().enumerate() {
+                if progress.lock().unwrap().stop_requested {
+                    break;
+                }
+                progress.lock().unwrap().set_status(index, BatchStatus::Running);
+
+                let full_table_name = format!("{}.{}", schema, table);
+                let result = runtime.block_on(db.query_table(&full_table_name, None, None, i64::MAX))
+                    .and_then(|(columns, rows)| {
+                        let watermark = Watermark::new(source.clone(), Some(format!("SELECT * FROM {}", full_table_name)));
+                        let (header_comment, footer_comment) = match format.comment_prefix() {
+                            Some(prefix) => watermark.header_and_footer(prefix, &preferences),
+                            None => (None, None),
+                        };
+                        let rows = match Anonymizer::new(&rules, &columns) {
+                            Some(anonymizer) => anonymizer.rows(&rows),
+                            None => rows,
+                        };
+                        let job = ExportJob {
+                            format,
+                            settings: settings.clone(),
+                            columns,
+                            rows,
+                            header_comment,
+                            footer_comment,
+                        };
+                        job.write_to(&target.join(batch_file_name(schema, table, format)))
+                    });
+
+                let status = match result {
+                    Ok(rows) => {
+                        exported += 1;
+                        BatchStatus::Done(rows)
+                    }
+                    Err(e) => BatchStatus::Failed(e.to_string()),
+                };
+                progress.lock().unwrap().set_status(index, status);
+            }
+
+            Ok(exported)
+        });
+
+        self.pending_operation = Some(AsyncOperation::BatchExport(promise, directory));
+    }
+
+    /// Open the SQL export for one table, or for every table of `schema` without one.
+    fn open_sql_dump(&mut self, schema: String, table: Option<String>) {
+        self.sql_dump_dialog.prepare(&schema, table.as_deref(), self.driver);
+        self.sql_dump_progress = None;
+        self.sql_dump_target = Some((schema, table));
+    }
+
+    /// Write CREATE TABLE statements and rows of the chosen tables to `path` on a
+    /// background thread, reporting per-table progress through `sql_dump_progress`.
+    /// Nothing is written when the export is stopped.
+    fn start_sql_dump(&mut self, dump: SqlDump, path: PathBuf) {
+        // The dialog was closed while the export was queued
+        let Some((schema, table)) = self.sql_dump_target.clone() else {
+            return;
+        };
+        if self.pending_operation.is_some() {
+            self.enqueue(format!("Export SQL to {}", path.display()), QueuedOperation::SqlDump(dump, path));
+            return;
+        }
+        let Some((db, _)) = self.database_for_read() else {
+            self.status_message = "Not connected".to_string();
+            return;
+        };
+        let tables: Vec<(String, String)> = match &table {
+            Some(table) => vec![(schema.clone(), table.clone())],
+            None => self.schemas.iter()
+                .find(|s| s.name == schema)
+                .map(|s| s.tables.iter().map(|table| (schema.clone(), table.clone())).collect())
+                .unwrap_or_default(),
+        };
+        if tables.is_empty() {
+            self.status_message = format!("{} has no tables to export", schema);
+            return;
+        }
+
+        let progress = Arc::new(Mutex::new(BatchProgress::new(&tables)));
+        self.sql_dump_progress = Some(Arc::clone(&progress));
+        self.status_message = format!("Exporting {} tables as SQL...", tables.len());
+
+        let runtime = Arc::clone(&self.runtime);
+        let watermark = Watermark::new(self.source_description(), None);
+        let (header_comment, footer_comment) = watermark.header_and_footer("-- ", &self.config.preferences);
+        let rules = self.anonymization_rules();
+        let whole_schema = table.is_none().then_some(schema);
+        let target = path.clone();
+
+        let promise = Promise::spawn_thread("sql_dump", move || {
+            // Written next to the target and moved over it once complete, so a stopped
+            // or failed export leaves no half-written file
+            let partial = target.with_extension("sql.part");
+            let mut file = std::io::BufWriter::new(std::fs::File::create(&partial)?);
+            let result = runtime.block_on(async {
+                let mut out = header_comment.unwrap_or_default();
+                out.push_str(&dump.header(whole_schema.as_deref()));
+                let mut foreign_keys = Vec::new();
+                let mut exported = 0;
+
+                for (index, (schema, table)) in tables.iter().enumerate() {
+                    if progress.lock().unwrap().stop_requested {
+                        anyhow::bail!("Export stopped");
+                    }
+                    progress.lock().unwrap().set_status(index, BatchStatus::Running);
+
+                    let definition = match db.table_definition(schema, table).await {
+                        Ok(definition) => definition,
+                        Err(e) => {
+                            progress.lock().unwrap().set_status(index, BatchStatus::Failed(e.to_string()));
+                            continue;
+                        }
+                    };
+                    let anonymizer = Anonymizer::new(&rules, &definition.columns);
+                    let mut table_dump = dump.table(&mut out, schema, table, &definition);
+                    let mut sink = |mut rows: Vec<Vec<Option<String>>>| {
+                        if progress.lock().unwrap().stop_requested {
+                            anyhow::bail!("Export stopped");
+                        }
+                        if let Some(anonymizer) = &anonymizer {
+                            for row in &mut rows {
+                                for (column, value) in row.iter_mut().enumerate() {
+                                    let cell = value.take().map_or(CellValue::Null, CellValue::Text);
+                                    *value = Some(anonymizer.value(column, &cell)).filter(|cell| !cell.is_null()).map(|cell| cell.to_string());
+                                }
+                            }
+                        }
+                        table_dump.push_rows(&mut out, &rows);
+                        file.write_all(out.as_bytes())?;
+                        out.clear();
+                        Ok(())
+                    };
+                    let result = db.dump_rows(schema, table, &definition.columns, &mut sink).await;
+                    if progress.lock().unwrap().stop_requested {
+                        anyhow::bail!("Export stopped");
+                    }
+                    let status = match result {
+                        Ok(()) => {
+                            exported += 1;
+                            BatchStatus::Done(table_dump.finish(&mut out))
+                        }
+                        Err(e) => {
+                            // Close the rows already written so the rest of the script still runs
+                            table_dump.finish(&mut out);
+                            out.push_str(&format!("-- Rows of {}.{} are incomplete: {}\n\n", schema, table, e));
+                            BatchStatus::Failed(e.to_string())
+                        }
+                    };
+                    foreign_keys.extend(definition.foreign_keys);
+                    progress.lock().unwrap().set_status(index, status);
+                }
+
+                out.push_str(&dump.footer(&foreign_keys));
+                out.push_str(&footer_comment.unwrap_or_default());
+                file.write_all(out.as_bytes())?;
+                file.flush()?;
+                anyhow::Ok(exported)
+            });
+            match result {
+                Ok(exported) => {
+                    std::fs::rename(&partial, &target)?;
+                    Ok(exported)
+                }
+                Err(e) => {
+                    let _ = std::fs::remove_file(&partial);
+                    Err(e)
+                }
+            }
+        });
+
+        self.pending_operation = Some(AsyncOperation::SqlDump(promise, path));
+    }
+
+    pub fn run_sql_file(&mut self, path: PathBuf, stop_on_error: bool) {
+        if self.dangerous_connection().is_some() {
+            self.pending_write = Some(PendingWrite::RunScript(path, stop_on_error));
+            return;
+        }
+        self.start_script(path, stop_on_error);
+    }
+
+    /// Stream the statements in `path` to the primary on a background thread.
+    fn start_script(&mut self, path: PathBuf, stop_on_error: bool) {
+        if self.pending_operation.is_some() {
+            let label = format!("Run {}", path.display());
+            self.enqueue(label, QueuedOperation::RunScript(path, stop_on_error));
+            return;
+        }
+        let Some(db) = self.database.clone() else {
+            self.status_message = "Not connected".to_string();
+            return;
+        };
+
+        let progress = Arc::new(Mutex::new(ScriptProgress::default()));
+        self.script_progress = Some(Arc::clone(&progress));
+        self.status_message = format!("Running {}...", path.display());
+
+        let cancel_handle = db.cancel_handle();
+        let runtime = Arc::clone(&self.runtime);
+        let script = path.clone();
+        let promise = Promise::spawn_thread("run_script", move || {
+            runtime.block_on(async move {
+                run_script(db.as_ref(), &script, stop_on_error, &progress).await
+            })
+        });
+
+        self.pending_operation = Some(AsyncOperation::RunScript(promise, path, cancel_handle));
+    }
+
+    /// Abort the running query or table load, if any. The pending promise resolves
+    /// with a cancellation error which is reported like any other result.
+    pub fn cancel_pending_operation(&mut self) {
+        if let Some(handle) = self.pending_operation.as_ref().and_then(|op| op.cancel_handle()) {
+            let handle = handle.clone();
+            let tls_settings = self.tls_settings.clone();
+            self.runtime.spawn(async move {
+                if let Err(e) = db::cancel(handle, &tls_settings).await {
+                    eprintln!("Failed to cancel query: {}", e);
+                }
+            });
+            self.status_message = "Cancelling...".to_string();
+        }
+    }
+
+    /// Wait for the running operation instead of replacing it.
+    fn enqueue(&mut self, label: String, operation: QueuedOperation) {
+        let position = self.operation_queue.push(label.clone(), operation);
+        self.status_message = format!("Queued {} (position {})", label, position);
+    }
+
+    /// Start queued operations until one is running or the queue is empty; some finish
+    /// without touching the connection (e.g. edits of a tab that has since closed).
+    fn start_queued_operations(&mut self) {
+        while self.pending_operation.is_none() {
+            let Some(operation) = self.operation_queue.pop() else {
+                return;
+            };
+            match operation {
+                QueuedOperation::LoadTable(schema, table, tab_index) => self.load_table_data(schema, table, tab_index),
+                QueuedOperation::Query(sql, tab_index) => self.run_query(sql, tab_index),
+                QueuedOperation::ApplyEdits(tab_index) => self.write_pending_edits(tab_index),
+                QueuedOperation::RunScript(path, stop_on_error) => self.start_script(path, stop_on_error),
+                QueuedOperation::AlterColumns(table, statements) => self.start_alter_columns(table, statements),
+                QueuedOperation::CreateTempTable(table, name) => self.create_temp_table(table, name),
+                QueuedOperation::ImportFile(plan) => self.start_import(plan),
+                QueuedOperation::SqlDump(dump, path) => self.start_sql_dump(dump, path),
+            }
+        }
+    }
+
+    fn can_cancel(&self) -> bool {
+        self.pending_operation.as_ref().is_some_and(|op| op.cancel_handle().is_some())
+    }
+
+    pub fn add_tab(&mut self, title: String, data: Option<TableData>, source: TabSource) {
+        let mut tab = Tab::new(self.next_tab_id, title, data, source);
+        tab.connection_name = self.config.get_last_connection().map(|conn| conn.name.clone());
+        self.next_tab_id += 1;
+        self.tabs.push(tab);
+        self.active_tab = self.tabs.len() - 1;
+        self.cache_tab_data(self.active_tab);
+        self.save_state();
+    }
+
+    /// Open `schema.table` with its rows, or on its structure when it is estimated to
+    /// be too large to load without asking.
+    fn open_table(&mut self, schema: String, table: String) {
+        let estimated_rows = self.schemas.iter().find(|s| s.name == schema).and_then(|s| s.estimated_rows(&table));
+        if !self.config.preferences.opens_structure_first(estimated_rows) {
+            self.load_table_data(schema, table, None);
+            return;
+        }
+        self.status_message = format!("{}.{} has about {} rows, press Load data to fetch the first ones", schema, table, estimated_rows.unwrap_or_default());
+        self.open_table_structure(schema, table, estimated_rows);
+    }
+
+    /// Open a tab for `schema.table` on its Structure view without loading any rows;
+    /// they load when switching to the Data view, or with Load Data for a large table
+    /// of `estimated_rows`.
+    fn open_table_structure(&mut self, schema: String, table: String, estimated_rows: Option<u64>) {
+        self.add_tab(format!("{}.{}", schema, table), None, TabSource::Table { schema, table });
+        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+            tab.view = TabView::Structure;
+            tab.estimated_rows = estimated_rows;
+        }
+        self.save_state();
+        self.load_table_structure(self.active_tab);
+    }
+
+    /// Insert the qualified name of `schema.table` at the query editor's cursor.
+    fn insert_table_name(&mut self, schema: &str, table: &str) {
+        let name = format!("{}.{}", db::sql::quote_ident_for(self.driver, schema), db::sql::quote_ident_for(self.driver, table));
+        let (tab_id, query) = match self.tabs.get_mut(self.active_tab) {
+            Some(tab) => (Some(tab.id), &mut tab.query_input),
+            None => (None, &mut self.query_input),
+        };
+        self.query_panel.insert_at_cursor(tab_id, query, &name);
+        self.show_query_panel = true;
+    }
+
+    /// Open a query tab for `schema.table` pre-filled from the query template at `index`.
+    fn new_query_from_template(&mut self, schema: &str, table: &str, index: usize) {
+        let Some(template) = self.config.preferences.query_templates.get(index) else {
+            self.status_message = "No query templates, add some in Settings".to_string();
+            return;
+        };
+        // The tree greys these out, the shortcut still gets here
+        if let Some(reason) = Privilege::for_statement(&template.sql).and_then(|privilege| self.table_denial(schema, table, privilege)) {
+            self.status_message = reason;
+            return;
+        }
+        let columns = self.schemas.iter()
+            .find(|s| s.name == schema)
+            .and_then(|s| s.table_columns.get(table))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let sql = db::sql::render_template(&template.sql, self.driver, schema, table, columns);
+        let title = format!("{}: {}", template.name, table);
+
+        self.open_query_tab(title, sql);
+    }
+
+    /// Queries to offer after the active tab's rows, for a table tab or a query reading
+    /// a single table the schema knows. Empty when turned off in Settings.
+    fn follow_ups(&self) -> (String, Vec<FollowUp>) {
+        let Some(tab) = self.tabs.get(self.active_tab).filter(|_| self.config.preferences.follow_up_suggestions) else {
+            return (String::new(), Vec::new());
+        };
+        let (schema, table) = match &tab.source {
+            TabSource::Table { schema, table } => (Some(schema.clone()), table.clone()),
+            TabSource::Query { sql } => match single_source_table(sql) {
+                Some(source) => source,
+                None => return (String::new(), Vec::new()),
+            },
+            TabSource::Explain { .. } => return (String::new(), Vec::new()),
+        };
+        // Unquoted names in a query may differ in case from the catalog's
+        let found = self.schemas.iter()
+            .filter(|s| schema.as_ref().is_none_or(|schema| s.name.eq_ignore_ascii_case(schema)))
+            .find_map(|s| s.table_columns.keys().find(|name| name.eq_ignore_ascii_case(&table)).map(|name| (s.name.clone(), name.clone())));
+        match found {
+            Some((schema, table)) => {
+                let follow_ups = follow_ups(self.driver, &self.schemas, &schema, &table);
+                (table, follow_ups)
+            }
+            None => (String::new(), Vec::new()),
+        }
+    }
+
+    /// Why the current role may not perform `privilege` on a table, if its grants are known.
+    fn table_denial(&self, schema: &str, table: &str, privilege: Privilege) -> Option<String> {
+        if self.offline.is_some() {
+            return Some("Offline: rows cached from an earlier session cannot be changed".to_string());
+        }
+        self.schemas.iter().find(|s| s.name == schema)?.denied(table, privilege)
+    }
+
+    /// New query tab with `sql` loaded into the editor.
+    fn open_query_tab(&mut self, title: String, sql: String) {
+        self.add_tab(title, None, TabSource::Query { sql });
+        self.show_query_panel = true;
+    }
+
+    /// Count the rows of a table per hour, day or week of `column` in a new query tab,
+    /// charted above the grid.
+    fn bucket_by_time(&mut self, schema: &str, table: &str, column: &str, bucket: TimeBucket) {
+        let sql = db::sql::bucket_query(self.driver, schema, table, column, bucket);
+        self.open_query_tab(format!("{} by {}", table, bucket.as_str()), sql);
+        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+            tab.chart = true;
+        }
+        self.execute_query(Some(self.active_tab));
+    }
+
+    /// Fetch the definition of a view or routine and open it in a query tab.
+    fn load_object_source(&mut self, schema: String, object: SourceObject) {
+        let Some(db) = self.database.clone() else {
+            return;
+        };
+
+        let title = format!("{}.{}", schema, object.name());
+        self.status_message = format!("Loading definition of {}...", title);
+        let runtime = Arc::clone(&self.runtime);
+        let promise = Promise::spawn_thread("object_source", move || {
+            runtime.block_on(async move { db.object_source(&schema, &object).await })
+        });
+        self.pending_operation = Some(AsyncOperation::LoadSource(promise, title));
+    }
+
+    /// Count the values of `column` in the active tab's rows passing the grid filters.
+    fn chart_distribution(&mut self, column: usize, histogram: Option<bool>) {
+        let Some(tab @ Tab { data: Some(data), .. }) = self.tabs.get(self.active_tab) else {
+            return;
+        };
+        let rows = filter_row_indices(&data.rows, tab.local_filters());
+        self.value_distribution = Some((tab.id, ValueDistribution::new(data, &rows, column, histogram)));
+    }
+
+    /// Begin, commit or roll back the manual transaction. `then_run` is executed once
+    /// the transaction has begun.
+    fn control_transaction(&mut self, control: TransactionControl, then_run: Option<(String, Option<usize>)>) {
+        let Some(db) = self.database.clone() else {
+            return;
+        };
+        if self.pending_operation.is_some() {
+            self.status_message = "Wait for the running operation to finish".to_string();
+            return;
+        }
+
+        let runtime = Arc::clone(&self.runtime);
+        let promise = Promise::spawn_thread("transaction", move || {
+            runtime.block_on(async move { db.transaction(control).await })
+        });
+        self.pending_operation = Some(AsyncOperation::Transaction(promise, control, then_run));
+    }
+
+    /// Re-populate a materialized view. Runs like a query from the editor, so a dangerous
+    /// connection asks for confirmation first.
+    fn refresh_materialized_view(&mut self, schema: &str, view: &str) {
+        let sql = format!(
+            "REFRESH MATERIALIZED VIEW {}.{}",
+            db::sql::quote_ident_for(self.driver, schema),
+            db::sql::quote_ident_for(self.driver, view)
+        );
+        self.execute_sql(sql, None);
+    }
+
+    /// Open the ALTER assistant for a table's columns.
+    fn open_alter_columns(&mut self, schema: &str, table: &str) {
+        if self.driver != Driver::Postgres {
+            self.status_message = "The ALTER assistant is only available for PostgreSQL connections".to_string();
+            return;
+        }
+        let columns = self.schemas.iter()
+            .find(|s| s.name == schema)
+            .and_then(|s| s.table_columns.get(table))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        self.alter_plan = Some(AlterPlan::new(schema.to_string(), table.to_string(), columns));
+    }
+
+    fn apply_alter_columns(&mut self, statements: Vec<String>) {
+        let Some(plan) = &self.alter_plan else {
+            return;
+        };
+        let table = format!("{}.{}", plan.schema, plan.table);
+        if self.dangerous_connection().is_some() {
+            self.pending_write = Some(PendingWrite::AlterColumns(table, statements));
+            return;
+        }
+        self.start_alter_columns(table, statements);
+    }
+
+    /// Run the ALTER assistant's statements in one transaction, then reload the schema
+    /// so the tree and the assistant see the new columns.
+    fn start_alter_columns(&mut self, table: String, statements: Vec<String>) {
+        if self.pending_operation.is_some() {
+            self.enqueue(format!("Alter columns of {}", table), QueuedOperation::AlterColumns(table, statements));
+            return;
+        }
+        let Some(db) = self.database.clone() else {
+            self.status_message = "Not connected".to_string();
+            return;
+        };
+
+        self.status_message = format!("Altering columns of {}...", table);
+        let total = statements.len();
+        let runtime = Arc::clone(&self.runtime);
+        let promise = Promise::spawn_thread("alter_columns", move || {
+            runtime.block_on(async move {
+                db.execute_in_transaction(&statements).await?;
+                db.list_schemas_with_tables().await
+            })
+        });
+        self.pending_operation = Some(AsyncOperation::AlterColumns(promise, table, total));
+    }
+
+    /// Create a temporary table from pasted rows on the primary, then show its rows.
+    fn create_temp_table(&mut self, table: PastedTable, name: String) {
+        if self.pending_operation.is_some() {
+            self.enqueue(format!("Create temporary table {}", name), QueuedOperation::CreateTempTable(table, name));
+            return;
+        }
+        let Some(db) = self.database.clone() else {
+            self.status_message = "Not connected".to_string();
+            return;
+        };
+
+        self.status_message = format!("Creating temporary table {}...", name);
+        let statements = table.create_statements(self.driver, &name);
+        let rows = table.rows.len();
+        let runtime = Arc::clone(&self.runtime);
+        let promise = Promise::spawn_thread("create_temp_table", move || {
+            runtime.block_on(async move { db.execute_in_transaction(&statements).await })
+        });
+        self.pending_operation = Some(AsyncOperation::CreateTempTable(promise, name, rows));
+    }
+
+    /// Open the import wizard for loading a CSV or TSV file into a table.
+    fn open_import(&mut self, schema: &str, table: &str) {
+        if self.driver != Driver::Postgres {
+            self.status_message = "Importing files is only available for PostgreSQL connections".to_string();
+            return;
+        }
+        let columns = self.schemas.iter()
+            .find(|s| s.name == schema)
+            .and_then(|s| s.table_columns.get(table))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        self.import_plan = Some(ImportPlan::new(schema.to_string(), table.to_string(), columns));
+    }
+
+    fn import_file(&mut self, plan: ImportPlan) {
+        if self.dangerous_connection().is_some() {
+            self.pending_write = Some(PendingWrite::ImportFile(plan));
+            return;
+        }
+        self.start_import(plan);
+    }
+
+    /// Stream the file's rows into the table on the primary with COPY, from a background
+    /// thread.
+    fn start_import(&mut self, plan: ImportPlan) {
+        let table = format!("{}.{}", plan.schema, plan.table);
+        if self.pending_operation.is_some() {
+            self.enqueue(format!("Import into {}", table), QueuedOperation::ImportFile(plan));
+            return;
+        }
+        let Some(db) = self.database.clone() else {
+            self.status_message = "Not connected".to_string();
+            return;
+        };
+
+        let progress = Arc::new(ImportProgress::default());
+        self.import_progress = Some(Arc::clone(&progress));
+        self.status_message = format!("Importing {} into {}...", plan.path.display(), table);
+
+        let cancel_handle = db.cancel_handle();
+        let runtime = Arc::clone(&self.runtime);
+        let promise = Promise::spawn_thread("import_file", move || {
+            let columns = plan.target_columns()?;
+            let mut rows = plan.rows()?;
+            progress.file_size.store(std::fs::metadata(&plan.path)?.len(), Ordering::Relaxed);
+            let mut rows = std::iter::from_fn(|| {
+                let row = rows.next();
+                progress.read.store(rows.position(), Ordering::Relaxed);
+                row
+            });
+            runtime.block_on(async {
+                db.copy_in(&plan.schema, &plan.table, &columns, &mut rows, &progress.sent).await
+            })
+        });
+        self.pending_operation = Some(AsyncOperation::ImportFile(promise, table, cancel_handle));
+    }
+
+    fn show_sequence_value(&mut self, schema: &str, sequence: &str) {
+        let sql = format!(
+            "SELECT last_value, is_called FROM {}.{}",
+            db::sql::quote_ident_for(self.driver, schema),
+            db::sql::quote_ident_for(self.driver, sequence)
+        );
+        self.run_query(sql, None);
+    }
+
+    /// Write a tab's result to the on-disk cache in the background.
+    fn cache_tab_data(&self, tab_index: usize) {
+        let (Some(cache), Some(tab)) = (self.result_cache.clone(), self.tabs.get(tab_index)) else {
+            return;
+        };
+        let Some(data) = tab.result_data().cloned() else {
+            return;
+        };
+        let tab_id = tab.id;
+        std::thread::spawn(move || {
+            if let Err(e) = cache.store(tab_id, &data) {
+                eprintln!("Failed to cache tab result: {}", e);
+            }
+        });
+    }
+
+    /// Keep the first rows of a table loaded from the current connection in the background,
+    /// to show while offline.
+    fn cache_sample(&self, schema: &str, table: &str, data: &TableData) {
+        let (Some(cache), Some(conn)) = (self.schema_cache.clone(), self.config.get_last_connection()) else {
+            return;
+        };
+        let (connection, schema, table) = (conn.name.clone(), schema.to_string(), table.to_string());
+        let sample = table_sample(data);
+        std::thread::spawn(move || {
+            if let Err(e) = cache.store_sample(&connection, &schema, &table, &sample) {
+                eprintln!("Failed to cache table sample: {}", e);
+            }
+        });
+    }
+
+    /// Restore the active tab's result from the cache if it has not been loaded yet.
+    fn restore_active_tab_data(&mut self) {
+        let (Some(cache), Some(tab)) = (&self.result_cache, self.tabs.get_mut(self.active_tab)) else {
+            return;
+        };
+        if tab.data.is_none() && tab.plan.is_none() {
+            tab.data = cache.load(tab.id);
+            tab.clamp_page();
+        }
+    }
+
+    /// Start the next due tail poll when no other operation is running.
+    fn poll_tails(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        if let Some(wait) = self.tabs.iter().filter_map(|tab| tab.tail.as_ref()).map(|tail| tail.until_due(now)).min() {
+            ctx.request_repaint_after(wait.max(ASYNC_POLL_INTERVAL));
+        }
+        if self.pending_operation.is_some() {
+            return;
+        }
+
+        let Some(tab_index) = self.tabs.iter().position(|tab| tab.tail.as_ref().is_some_and(|tail| tail.is_due(now))) else {
+            return;
+        };
+        let Some((db, _)) = self.database_for_read() else {
+            return;
+        };
+        let tab = &mut self.tabs[tab_index];
+        let (TabSource::Table { schema, table }, Some(data), Some(tail)) = (&tab.source, &tab.data, &mut tab.tail) else {
+            tab.tail = None;
+            return;
+        };
+        let Some(column) = data.columns.get(tail.column) else {
+            tab.tail = None;
+            return;
+        };
+        let sql = tail.query(self.driver, schema, table, &column.name);
+        tail.last_poll = Some(now);
+
+        let cancel_handle = db.cancel_handle();
+        let runtime = Arc::clone(&self.runtime);
+        let promise = Promise::spawn_thread("tail_table", move || {
+            runtime.block_on(async move {
+                db.execute_query(&sql, StatementTimeout::None).await
+            })
+        });
+        self.pending_operation = Some(AsyncOperation::TailTable(promise, tab_index, cancel_handle));
+    }
+
+    /// Save the chart of the active tab as a PNG: the screenshot is asked for a frame
+    /// after the path was picked, so the file dialog is no longer on screen.
+    fn poll_chart_screenshot(&mut self, ctx: &egui::Context) {
+        let Some((path, requested)) = &mut self.pending_chart_png else {
+            return;
+        };
+        if !*requested {
+            *requested = true;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
+            ctx.request_repaint();
+            return;
+        }
+        let screenshot = ctx.input(|i| i.events.iter().find_map(|event| match event {
+            egui::Event::Screenshot { image, .. } => Some(Arc::clone(image)),
+            _ => None,
+        }));
+        let Some(screenshot) = screenshot else {
+            ctx.request_repaint();
+            return;
+        };
+
+        let path = path.clone();
+        self.pending_chart_png = None;
+        let shown = self.tabs.get(self.active_tab).is_some_and(|tab| tab.shows_chart());
+        self.status_message = match self.result_chart.plot_rect().filter(|_| shown) {
+            Some(rect) => {
+                let image = screenshot.region(&rect.intersect(ctx.screen_rect()), Some(ctx.pixels_per_point()));
+                let written = std::fs::File::create(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|file| write_png(std::io::BufWriter::new(file), &image));
+                match written {
+                    Ok(()) => format!("Saved chart to {}", path.display()),
+                    Err(e) => format!("Error saving chart: {}", e),
+                }
+            }
+            None => "The chart is no longer shown".to_string(),
+        };
+    }
+
+    /// Ask the primary for its transaction state when the last answer is due and no
+    /// other operation is running, then take in the answer once it arrives.
+    fn poll_transaction_status(&mut self, ctx: &egui::Context) {
+        if let Some(result) = self.transaction_probe.as_ref().and_then(|probe| probe.ready()) {
+            match result {
+                Ok(status) => {
+                    self.transaction_status = *status;
+                    // The server knows about a typed BEGIN or COMMIT too
+                    if let Some(status) = status {
+                        self.transaction_open = status.is_open();
+                    }
+                }
+                Err(_) => self.transaction_status = None,
+            }
+            self.transaction_probe = None;
+        }
+
+        let Some(db) = self.database.clone() else {
+            return;
+        };
+        let now = Instant::now();
+        let due = self.last_transaction_poll.map_or(now, |last| last + TRANSACTION_POLL_INTERVAL);
+        ctx.request_repaint_after(due.saturating_duration_since(now).max(ASYNC_POLL_INTERVAL));
+        if self.pending_operation.is_some() || self.transaction_probe.is_some() || due > now {
+            return;
+        }
+
+        self.last_transaction_poll = Some(now);
+        let runtime = Arc::clone(&self.runtime);
+        self.transaction_probe = Some(Promise::spawn_thread("transaction_status", move || {
+            runtime.block_on(async move { db.transaction_status().await })
+        }));
+    }
+
+    /// Start the next due watch refresh when the panel is shown and nothing else runs.
+    fn poll_watches(&mut self, ctx: &egui::Context) {
+        if !self.show_watch_panel {
+            return;
+        }
+        let now = Instant::now();
+        if let Some(wait) = self.watches.iter().map(|watch| watch.until_due(now)).min() {
+            ctx.request_repaint_after(wait.max(ASYNC_POLL_INTERVAL));
+        }
+        if self.pending_operation.is_some() {
+            return;
+        }
+
+        let Some(index) = self.watches.iter().position(|watch| watch.is_due(now)) else {
+            return;
+        };
+        let sql = self.watches[index].definition.sql.clone();
+        let Some((db, _)) = self.database_for_query(&sql) else {
+            return;
+        };
+        self.watches[index].last_poll = Some(now);
+
+        let cancel_handle = db.cancel_handle();
+        let runtime = Arc::clone(&self.runtime);
+        let promise = Promise::spawn_thread("refresh_watch", move || {
+            runtime.block_on(async move {
+                db.execute_query(&sql, StatementTimeout::Seconds5).await
+            })
+        });
+        self.pending_operation = Some(AsyncOperation::RefreshWatch(promise, index, cancel_handle));
+    }
+
+    fn handle_watch_event(&mut self, event: WatchPanelEvent) {
+        match event {
+            WatchPanelEvent::Add(definition) => {
+                // Watches run unattended, so they must never change anything
+                if !routing::is_read_only(&definition.sql) {
+                    self.status_message = "Watches must be read-only queries".to_string();
+                    return;
+                }
+                self.watches.push(Watch::new(definition));
+            }
+            WatchPanelEvent::Refresh(index) => {
+                if let Some(watch) = self.watches.get_mut(index) {
+                    watch.last_poll = None;
+                }
+                return;
+            }
+            WatchPanelEvent::Remove(index) => {
+                // A refresh in flight would land on the watch that moves into its place
+                if matches!(self.pending_operation, Some(AsyncOperation::RefreshWatch(..))) {
+                    self.pending_operation = None;
+                }
+                if index < self.watches.len() {
+                    self.watches.remove(index);
+                }
+            }
+            WatchPanelEvent::Close => {
+                self.show_watch_panel = false;
+                return;
+            }
+        }
+
+        let definitions = self.watches.iter().map(|watch| watch.definition.clone()).collect();
+        if let Some(connection) = self.config.get_last_connection_mut() {
+            connection.watches = definitions;
+            let _ = self.config.save();
+        }
+    }
+
+    pub fn reload_current_tab(&mut self) {
+        // Reload once connected to the server the tab's data came from
+        if let Some(idx) = self.tab_connection_index(self.active_tab) {
+            self.switch_connection(idx);
+            self.run_after_connect = Some(self.active_tab);
+            return;
+        }
+        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+            tab.restored = false;
+        }
+
+        if let Some(tab) = self.tabs.get(self.active_tab) {
+            let source = tab.source.clone();
+            let tab_index = self.active_tab;
+            match source {
+                TabSource::Table { schema, table } => {
+                    self.load_table_data(schema, table, Some(tab_index));
+                }
+                TabSource::Query { sql } => {
+                    self.execute_sql(sql, Some(tab_index));
+                }
+                TabSource::Explain { sql, analyze } => {
+                    self.explain_query(&sql, analyze, Some(tab_index));
+                }
+            }
+        }
+    }
+
+    pub fn sort_tab_data(&mut self, tab_index: usize, column_index: usize) {
+        if let Some(tab) = self.tabs.get_mut(tab_index) {
+            tab.sort_by(column_index);
+            let reload = tab.sorts_on_server();
+            self.save_state();
+            // Header clicks only ever sort the active tab
+            if reload {
+                self.reload_current_tab();
+            }
+        }
+    }
+
+    /// Bookmark a row of a table tab by its primary key, or remove its bookmark.
+    fn toggle_bookmark(&mut self, tab_index: usize, row: usize) {
+        let Some(tab) = self.tabs.get_mut(tab_index) else {
+            return;
+        };
+        let Some(data) = &tab.data else {
+            return;
+        };
+        if let (Some(key_columns), Some(row_values)) = (PendingEdits::key_columns(&data.columns), data.rows.get(row)) {
+            let key = PendingEdits::row_key(row_values, &key_columns);
+            let label = Bookmarks::label(&key, &data.columns, &key_columns);
+            self.status_message = if tab.bookmarks.toggle(key) {
+                format!("Bookmarked {}", label)
+            } else {
+                format!("Removed bookmark {}", label)
+            };
+            self.save_state();
+        }
+    }
+
+    /// Copy a model definition of a table's columns to the clipboard.
+    fn copy_table_as_code(&mut self, schema: &str, table: &str, target: CodeTarget) {
+        let columns = self.schemas.iter()
+            .find(|s| s.name == schema)
+            .and_then(|s| s.table_columns.get(table));
+        match columns {
+            Some(columns) => {
+                self.pending_clipboard = Some(target.generate(table, columns));
+                self.status_message = format!("Copied {}.{} as {}", schema, table, target.as_str());
+            }
+            None => {
+                self.status_message = format!("No columns loaded for {}.{}", schema, table);
+            }
+        }
+    }
+
+    /// Fetch the indexes, constraints and triggers shown in a table tab's Structure view.
+    fn load_table_structure(&mut self, tab_index: usize) {
+        let Some(TabSource::Table { schema, table }) = self.tabs.get(tab_index).map(|tab| tab.source.clone()) else {
+            return;
+        };
+        let Some(db) = self.database.clone() else {
+            return;
+        };
+
+        self.status_message = format!("Loading structure of {}.{}...", schema, table);
+        let runtime = Arc::clone(&self.runtime);
+        let promise = Promise::spawn_thread("table_structure", move || {
+            runtime.block_on(async move { db.table_structure(&schema, &table).await })
+        });
+        self.pending_operation = Some(AsyncOperation::LoadTableStructure(promise, tab_index));
+    }
+
+    /// Look up what a foreign key cell of a table tab references, then open the referenced row.
+    fn follow_foreign_key(&mut self, tab_index: usize, row: usize, column: usize) {
+        let Some(tab) = self.tabs.get(tab_index) else {
+            return;
+        };
+        let (TabSource::Table { schema, table }, Some(data)) = (&tab.source, &tab.data) else {
+            return;
+        };
+        let (Some(column_info), Some(value)) = (data.columns.get(column), data.rows.get(row).and_then(|r| r.get(column))) else {
+            return;
+        };
+        let Some(db) = self.database.clone() else {
+            return;
+        };
+
+        let (schema, table, column_name) = (schema.clone(), table.clone(), column_info.name.clone());
+        self.status_message = format!("Following foreign key {}.{}.{}...", schema, table, column_name);
+        let runtime = Arc::clone(&self.runtime);
+        let promise = Promise::spawn_thread("foreign_key_target", move || {
+            runtime.block_on(async move { db.foreign_key_target(&schema, &table, &column_name).await })
+        });
+        self.pending_operation = Some(AsyncOperation::FollowForeignKey(promise, value.to_string()));
+    }
+
+    /// Show the page holding a bookmarked row of the active tab and scroll to it.
+    fn jump_to_bookmark(&mut self, index: usize) {
+        let Some(tab) = self.tabs.get_mut(self.active_tab) else {
+            return;
+        };
+        let (Some(data), Some(key)) = (&tab.data, tab.bookmarks.keys().get(index)) else {
+            return;
+        };
+        let Some(key_columns) = PendingEdits::key_columns(&data.columns) else {
+            return;
+        };
+
+        // Position in the filtered view, which is what the grid pages through
+        let position = Bookmarks::find_row(&data.rows, &key_columns, key).and_then(|row| {
+            filter_row_indices(&data.rows, tab.local_filters()).iter().position(|&idx| idx == row)
+        });
+        let label = Bookmarks::label(key, &data.columns, &key_columns);
+        match position {
+            Some(position) => {
+                tab.current_page = position / tab.page_size.max(1);
+                self.data_grid.reveal_row(position);
+                self.status_message = format!("Showing {}", label);
+            }
+            None => {
+                self.status_message = format!("{} is not in the current result or is filtered out", label);
+            }
+        }
+    }
+
+    pub fn begin_share_query(&mut self) {
+        if self.query_buffer().trim().is_empty() {
+            self.status_message = "Cannot share empty query".to_string();
+            return;
+        }
+
+        let file_name = self.active_tab_file_name();
+        self.file_dialog.set_path(default_file_path(&format!("{}.{}", file_name, SHARED_QUERY_EXTENSION)));
+        self.file_action = Some(FileAction::ShareQuery);
+    }
+
+    // Title of the active tab usable as a file name, without the extension
+    fn active_tab_file_name(&self) -> String {
+        let title = self.tabs.get(self.active_tab)
+            .map(|tab| tab.title.clone())
+            .unwrap_or_else(|| "query".to_string());
+        title.chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect()
+    }
+
+    pub fn begin_open_sql_file(&mut self) {
+        self.file_dialog.set_path(default_file_path("query.sql"));
+        self.file_action = Some(FileAction::OpenSqlFile);
+    }
+
+    /// Open `path` in a new query tab without running it, or switch to the tab that
+    /// already has it open.
+    fn open_sql_file(&mut self, path: PathBuf) {
+        if let Some(index) = self.tabs.iter().position(|tab| tab.sql_file.as_ref() == Some(&path)) {
+            self.handle_tab_bar_event(TabBarEvent::TabActivated(index));
+        } else {
+            let sql = match std::fs::read_to_string(&path) {
+                Ok(sql) => sql,
+                Err(e) => {
+                    self.status_message = format!("Error opening {}: {}", path.display(), e);
+                    return;
+                }
+            };
+            self.add_tab(sql_file_title(&path), None, TabSource::Query { sql: sql.clone() });
+            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                tab.sql_file = Some(path.clone());
+                tab.saved_sql = sql;
+            }
+            self.save_state();
+            self.status_message = format!("Opened {}", path.display());
+        }
+        self.config.remember_sql_file(path);
+        let _ = self.config.save();
+    }
+
+    /// Save the active query buffer to its .sql file, asking where the first time or
+    /// when `save_as` is set.
+    pub fn begin_save_sql_file(&mut self, save_as: bool) {
+        let file = self.tabs.get(self.active_tab).and_then(|tab| tab.sql_file.clone());
+        match file {
+            Some(path) if !save_as => self.save_sql_file(path),
+            Some(path) => {
+                self.file_dialog.set_path(path);
+                self.file_action = Some(FileAction::SaveSqlFile);
+            }
+            None => {
+                let file_name = self.active_tab_file_name();
+                self.file_dialog.set_path(default_file_path(&format!("{}.sql", file_name)));
+                self.file_action = Some(FileAction::SaveSqlFile);
+            }
+        }
+    }
+
+    fn save_sql_file(&mut self, path: PathBuf) {
+        let sql = self.query_buffer().to_string();
+        if let Err(e) = std::fs::write(&path, &sql) {
+            self.status_message = format!("Error saving {}: {}", path.display(), e);
+            return;
+        }
+        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+            // Table and explain tabs keep naming what they show
+            if matches!(tab.source, TabSource::Query { .. }) {
+                tab.title = sql_file_title(&path);
+            }
+            tab.sql_file = Some(path.clone());
+            tab.saved_sql = sql;
+            self.save_state();
+        }
+        self.status_message = format!("Saved {}", path.display());
+        self.config.remember_sql_file(path);
+        let _ = self.config.save();
+    }
+
+    pub fn begin_open_shared_query(&mut self) {
+        self.file_dialog.set_path(default_file_path(&format!("query.{}", SHARED_QUERY_EXTENSION)));
+        self.file_action = Some(FileAction::OpenSharedQuery);
+    }
+
+    fn share_query(&mut self, path: &Path) {
+        let (title, view) = match self.tabs.get(self.active_tab) {
+            Some(tab) => (
+                tab.title.clone(),
+                SharedViewSettings {
+                    page_size: tab.page_size,
+                    filters: tab.filters.clone(),
+                    search_text: tab.search_text.clone(),
+                },
+            ),
+            None => ("Query".to_string(), SharedViewSettings::default()),
+        };
+        let connection_name = self.config.get_last_connection().map(|conn| conn.name.clone());
+        let shared = SharedQuery::new(title, self.query_buffer().to_string(), connection_name, view);
+
+        self.status_message = match shared.save(path) {
+            Ok(()) => format!("Shared query saved to {}", path.display()),
+            Err(e) => format!("Error sharing query: {}", e),
+        };
+    }
+
+    /// Recreate a query tab from a `.dexq` file, switching to its connection if we know it.
+    pub fn open_shared_query(&mut self, path: &Path) {
+        let shared = match SharedQuery::load(path) {
+            Ok(shared) => shared,
+            Err(e) => {
+                self.status_message = format!("Error opening shared query: {}", e);
+                return;
+            }
+        };
+
+        self.add_tab(shared.title.clone(), None, TabSource::Query { sql: shared.sql.clone() });
+        let tab_index = self.active_tab;
+        if let Some(tab) = self.tabs.get_mut(tab_index) {
+            tab.page_size = shared.view.page_size;
+            tab.filters = shared.view.filters.clone();
+            tab.search_text = shared.view.search_text.clone();
+        }
+        self.save_state();
+
+        let current_name = self.config.get_last_connection().map(|conn| conn.name.clone());
+        let target_index = shared.connection_name.as_ref()
+            .and_then(|name| self.config.connections.iter().position(|conn| &conn.name == name));
+
+        match (target_index, &shared.connection_name) {
+            (Some(idx), Some(name)) if current_name.as_ref() != Some(name) => {
+                self.switch_connection(idx);
+                self.run_after_connect = Some(tab_index);
+                self.status_message = format!("Opened shared query '{}', connecting to {}", shared.title, name);
+            }
+            (None, Some(name)) => {
+                self.status_message = format!("Opened shared query '{}' (connection '{}' is not configured here)", shared.title, name);
+            }
+            _ => {
+                self.status_message = format!("Opened shared query '{}'", shared.title);
+                if self.database.is_some() && self.pending_operation.is_none() {
+                    self.execute_query(Some(tab_index));
+                } else {
+                    self.run_after_connect = Some(tab_index);
+                }
+            }
+        }
+    }
+
+    /// Move the tab at `from` to position `to`, keeping the same tab active.
+    fn move_tab(&mut self, from: usize, to: usize) {
+        if from >= self.tabs.len() || to >= self.tabs.len() {
+            return;
+        }
+        let tab = self.tabs.remove(from);
+        self.tabs.insert(to, tab);
+        self.active_tab = moved_index(self.active_tab, from, to);
+        self.streaming_tab = self.streaming_tab.map(|index| moved_index(index, from, to));
+        self.save_state();
+    }
+
+    /// Close the tabs whose index `close` picks, except pinned ones, keeping the active
+    /// tab if it stays open.
+    fn close_tabs(&mut self, close: impl Fn(usize) -> bool) {
+        let active_id = self.tabs.get(self.active_tab).map(|tab| tab.id);
+        let mut index = 0;
+        self.tabs.retain(|tab| {
+            let closed = !tab.pinned && close(index);
+            index += 1;
+            if let (true, Some(cache)) = (closed, &self.result_cache) {
+                cache.remove(tab.id);
+            }
+            if closed {
+                self.query_panel.forget_tab(tab.id);
+            }
+            !closed
+        });
+        self.active_tab = self.tabs.iter()
+            .position(|tab| Some(tab.id) == active_id)
+            .unwrap_or(self.tabs.len().saturating_sub(1));
+        self.restore_active_tab_data();
+        self.save_state();
+    }
+
+    /// Open a copy of the tab at `index` right after it.
+    fn duplicate_tab(&mut self, index: usize) {
+        let Some(tab) = self.tabs.get(index).map(|tab| tab.duplicate(self.next_tab_id)) else {
+            return;
+        };
+        self.next_tab_id += 1;
+        self.tabs.insert(index + 1, tab);
+        self.streaming_tab = self.streaming_tab.map(|streaming| if streaming > index { streaming + 1 } else { streaming });
+        self.handle_tab_bar_event(TabBarEvent::TabActivated(index + 1));
+        self.cache_tab_data(self.active_tab);
+    }
+
+    pub fn close_tab(&mut self, index: usize) {
+        if index < self.tabs.len() {
+            let tab = self.tabs.remove(index);
+            if let Some(cache) = &self.result_cache {
+                cache.remove(tab.id);
+            }
+            self.query_panel.forget_tab(tab.id);
+            if self.active_tab >= self.tabs.len() && self.active_tab > 0 {
+                self.active_tab = self.tabs.len() - 1;
+            }
+            self.restore_active_tab_data();
+            self.save_state();
+        }
+    }
+
+    fn handle_menu_event(&mut self, ctx: &egui::Context, event: MenuBarEvent) {
+        match event {
+            MenuBarEvent::ShowSettings => self.show_settings = true,
+            MenuBarEvent::ShareQuery => self.begin_share_query(),
+            MenuBarEvent::OpenSharedQuery => self.begin_open_shared_query(),
+            MenuBarEvent::Quit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            MenuBarEvent::ToggleQueryPanel => self.show_query_panel = !self.show_query_panel,
+            MenuBarEvent::ToggleQueryLog => self.show_query_log = !self.show_query_log,
+            MenuBarEvent::ToggleWatches => self.show_watch_panel = !self.show_watch_panel,
+            MenuBarEvent::Refresh => self.connect_to_database(),
+            MenuBarEvent::CopyExplainPlan => self.export_explain_plan(PlanDestination::Clipboard),
+            MenuBarEvent::SaveExplainPlan => {
+                self.file_dialog.set_path(default_file_path("plan.json"));
+                self.file_action = Some(FileAction::SaveExplainPlan);
+            }
+            MenuBarEvent::SaveSchemaSnapshot => {
+                self.file_dialog.set_path(default_file_path("schema-snapshot.json"));
+                self.file_action = Some(FileAction::SaveSchemaSnapshot);
+            }
+            MenuBarEvent::CompareSchemaSnapshot => {
+                self.file_dialog.set_path(default_file_path("schema-snapshot.json"));
+                self.file_action = Some(FileAction::CompareSchemaSnapshot);
+            }
+            MenuBarEvent::RunSqlFile => self.show_script_dialog = true,
+            MenuBarEvent::OpenSqlFile => self.begin_open_sql_file(),
+            MenuBarEvent::OpenRecentSqlFile(path) => self.open_sql_file(path),
+            MenuBarEvent::SaveSqlFile => self.begin_save_sql_file(false),
+            MenuBarEvent::SaveSqlFileAs => self.begin_save_sql_file(true),
+            MenuBarEvent::PasteTable => self.show_paste_table_dialog = true,
+            MenuBarEvent::ExportSchemaData => {
+                self.batch_export_dialog.prepare();
+                self.show_batch_export_dialog = true;
+            }
+        }
+    }
+
+    fn handle_tree_event(&mut self, event: DatabaseTreeEvent) {
+        match event {
+            DatabaseTreeEvent::TableClicked(schema, table) => {
+                self.selected_table = Some((schema.clone(), table.clone()));
+                self.open_table(schema, table);
+            }
+            DatabaseTreeEvent::SchemaStyled(schema, style) => {
+                if let Some(conn) = self.config.get_last_connection_mut() {
+                    conn.set_schema_style(&schema, style);
+                    let _ = self.config.save();
+                }
+            }
+            DatabaseTreeEvent::TableSelected(schema, table) => {
+                self.selected_table = Some((schema, table));
+            }
+            DatabaseTreeEvent::TableDoubleClicked(schema, table) => {
+                self.selected_table = Some((schema.clone(), table.clone()));
+                match self.config.preferences.table_double_click {
+                    TableAction::OpenData => self.open_table(schema, table),
+                    TableAction::OpenStructure => self.open_table_structure(schema, table, None),
+                    TableAction::InsertName => self.insert_table_name(&schema, &table),
+                }
+            }
+            DatabaseTreeEvent::TableRightClicked(schema, table) => {
+                self.selected_table = Some((schema.clone(), table.clone()));
+                self.open_table(schema, table);
+            }
+            DatabaseTreeEvent::TableToggled(schema, table) => {
+                let table_key = (schema, table);
+                if self.expanded_tables.contains(&table_key) {
+                    self.expanded_tables.remove(&table_key);
+                } else {
+                    self.expanded_tables.insert(table_key);
+                }
+            }
+            DatabaseTreeEvent::NewQueryFromTemplate(schema, table, index) => {
+                self.selected_table = Some((schema.clone(), table.clone()));
+                self.new_query_from_template(&schema, &table, index);
+            }
+            DatabaseTreeEvent::CopyAsCode(schema, table, target) => {
+                self.copy_table_as_code(&schema, &table, target);
+            }
+            DatabaseTreeEvent::ShowSource(schema, object) => {
+                self.load_object_source(schema, object);
+            }
+            DatabaseTreeEvent::RefreshMaterializedView(schema, view) => {
+                self.refresh_materialized_view(&schema, &view);
+            }
+            DatabaseTreeEvent::BucketByTime(schema, table, column, bucket) => {
+                self.bucket_by_time(&schema, &table, &column, bucket);
+            }
+            DatabaseTreeEvent::AlterColumns(schema, table) => {
+                self.open_alter_columns(&schema, &table);
+            }
+            DatabaseTreeEvent::ImportFile(schema, table) => {
+                self.open_import(&schema, &table);
+            }
+            DatabaseTreeEvent::ExportSql(schema, table) => {
+                self.open_sql_dump(schema, table);
+            }
+            DatabaseTreeEvent::SequenceValue(schema, sequence) => {
+                self.show_sequence_value(&schema, &sequence);
+            }
+            DatabaseTreeEvent::SchemaToggled(schema_name) => {
+                if self.expanded_schemas.contains(&schema_name) {
+                    self.expanded_schemas.remove(&schema_name);
+                } else {
+                    self.expanded_schemas.insert(schema_name);
+                }
+                self.save_state();
+            }
+            DatabaseTreeEvent::SearchChanged(_query) => {
+                // Search query already updated via mutable reference
+            }
+        }
+    }
+
+    fn handle_tab_bar_event(&mut self, event: TabBarEvent) {
+        match event {
+            TabBarEvent::TabActivated(i) => {
+                self.active_tab = i;
+                self.restore_active_tab_data();
+                self.save_state();
+            }
+            TabBarEvent::TabClosed(i) => {
+                self.close_tab(i);
+            }
+            TabBarEvent::TabMoved(from, to) => self.move_tab(from, clamp_tab_move(&self.tabs, from, to)),
+            TabBarEvent::CloseOthers(i) => self.close_tabs(|index| index != i),
+            TabBarEvent::CloseToRight(i) => self.close_tabs(|index| index > i),
+            TabBarEvent::CloseAll => self.close_tabs(|_| true),
+            TabBarEvent::Duplicate(i) => self.duplicate_tab(i),
+            TabBarEvent::TogglePin(i) => {
+                let pinned = self.tabs.iter().filter(|tab| tab.pinned).count();
+                if let Some(tab) = self.tabs.get_mut(i) {
+                    tab.pinned = !tab.pinned;
+                    // Last of the pinned tabs, or first of the others once unpinned
+                    let to = if tab.pinned { pinned } else { pinned - 1 };
+                    self.move_tab(i, to);
+                }
+            }
+            TabBarEvent::Renamed(i, title) => {
+                if let Some(tab) = self.tabs.get_mut(i) {
+                    tab.title = title;
+                    self.save_state();
+                }
+            }
+        }
+    }
+
+    /// Run a command from the keymap or the command palette through the same
+    /// handlers as the menu, tree and tab bar events it stands for.
+    fn run_command(&mut self, ctx: &egui::Context, command: Command) {
+        if let Some(event) = command.menu_event() {
+            self.handle_menu_event(ctx, event);
+            return;
+        }
+        match command {
+            Command::OpenPalette => {
+                self.command_palette.open();
+                self.show_command_palette = true;
+            }
+            Command::NewTab => self.open_query_tab(format!("Query {}", self.tabs.len() + 1), String::new()),
+            Command::CloseTab if !self.tabs.is_empty() => {
+                self.handle_tab_bar_event(TabBarEvent::TabClosed(self.active_tab));
+            }
+            Command::NextTab | Command::PreviousTab => {
+                let count = self.tabs.len();
+                if count > 1 {
+                    let next = if command == Command::NextTab {
+                        (self.active_tab + 1) % count
+                    } else {
+                        (self.active_tab + count - 1) % count
+                    };
+                    self.handle_tab_bar_event(TabBarEvent::TabActivated(next));
+                }
+            }
+            Command::ExecuteQuery => {
+                let tab = (!self.tabs.is_empty()).then_some(self.active_tab);
+                self.execute_query(tab);
+            }
+            Command::FocusSearch => self.database_tree.focus_search(),
+            Command::Reload => self.reload_current_tab(),
+            Command::NewQueryFromTemplate => {
+                if let Some((schema, table)) = self.selected_table.clone() {
+                    self.handle_tree_event(DatabaseTreeEvent::NewQueryFromTemplate(schema, table, 0));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Commands followed by every table and view of the connected database.
+    fn palette_items(&self) -> Vec<PaletteItem> {
+        let commands = Command::all().into_iter()
+            .filter(|command| *command != Command::OpenPalette)
+            .map(PaletteItem::Command);
+        let tables = self.schemas.iter()
+            .flat_map(|schema| schema.relations().map(|table| PaletteItem::Table(schema.name.clone(), table.clone())));
+        commands.chain(tables).collect()
+    }
+}
+
+impl eframe::App for DbClientApp {
+    fn save(&mut self, _storage: &mut dyn eframe::Storage) {
+        self.save_state();
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        let _ = AppState::end_session();
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Handle pending async operations
+        self.handle_async_operations();
+        self.poll_tails(ctx);
+        self.poll_watches(ctx);
+        self.poll_transaction_status(ctx);
+        self.poll_chart_screenshot(ctx);
+        if let Some(text) = self.pending_clipboard.take() {
+            ctx.output_mut(|o| o.copied_text = text);
+        }
+
+        // Don't let the window close under a running write
+        if ctx.input(|i| i.viewport().close_requested())
+            && self.pending_operation.as_ref().is_some_and(|op| op.is_write())
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.show_quit_dialog = true;
+        }
+        if self.quit_when_idle && self.pending_operation.is_none() {
+            self.quit_when_idle = false;
+            self.show_quit_dialog = false;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+        if self.show_quit_dialog {
+            let can_cancel = self.can_cancel();
+            match self.quit_dialog.show(ctx, self.quit_when_idle, can_cancel) {
+                Some(QuitDialogEvent::Wait) => {
+                    self.quit_when_idle = true;
+                    self.status_message = "Waiting for the running write before quitting...".to_string();
+                }
+                Some(QuitDialogEvent::CancelAndQuit) => {
+                    self.operation_queue.clear();
+                    self.cancel_pending_operation();
+                    self.quit_when_idle = true;
+                }
+                Some(QuitDialogEvent::Stay) => {
+                    self.show_quit_dialog = false;
+                    self.quit_when_idle = false;
+                }
+                None => {}
+            }
+        }
+
+        // Offer the tabs of a session that ended uncleanly
+        if let Some(tab_count) = self.session_recovery.as_ref().map(|state| state.tabs.len()) {
+            match self.session_restore_dialog.show(ctx, tab_count) {
+                Some(SessionRestoreDialogEvent::Restore) => {
+                    if let Some(state) = self.session_recovery.take() {
+                        self.restore_crashed_session(state);
+                    }
+                }
+                Some(SessionRestoreDialogEvent::Discard) => {
+                    self.session_recovery = None;
+                    self.save_state();
+                }
+                None => {}
+            }
+        }
+
+        if let Some((prompt, _, _)) = &mut self.param_prompt {
+            match prompt.show(ctx) {
+                Some(ParamPromptDialogEvent::Run(values)) => {
+                    self.param_values.extend(values);
+                    if let Some((_, query, tab_index)) = self.param_prompt.take() {
+                        self.confirm_and_run(query, tab_index);
+                    }
+                }
+                Some(ParamPromptDialogEvent::Cancel) => self.param_prompt = None,
+                None => {}
+            }
+        }
+
+        if let Some(prompt) = &mut self.password_prompt {
+            match prompt.show(ctx) {
+                Some(PasswordPromptDialogEvent::Connect(password, remember)) => {
+                    self.password_prompt = None;
+                    if let Some(conn) = self.config.get_last_connection().filter(|_| remember) {
+                        self.session_passwords.insert(conn.credential_ref.clone(), password.clone());
+                    }
+                    self.connect_with_password(&password);
+                }
+                Some(PasswordPromptDialogEvent::Cancel) => {
+                    self.password_prompt = None;
+                    self.connection_status = "Not connected".to_string();
+                }
+                None => {}
+            }
+        }
+
+        if !self.load_errors.is_empty() {
+            if let Some(LoadErrorsDialogEvent::Close) = self.load_errors_dialog.show(ctx, &self.load_errors) {
+                self.load_errors.clear();
+            }
+        }
+
+        // Persistent warning while connected to a production connection
+        if let Some(conn) = self.dangerous_connection() {
+            let text = format!("⚠ PRODUCTION: {} (writes require confirmation)", conn.name);
+            egui::TopBottomPanel::top("production_banner")
+                .exact_height(18.0)
+                .frame(egui::Frame::none().fill(egui::Color32::from_rgb(190, 30, 30)))
+                .show(ctx, |ui| {
+                    ui.centered_and_justified(|ui| {
+                        ui.label(egui::RichText::new(text).size(11.0).strong().color(egui::Color32::WHITE));
+                    });
+                });
+        }
+
+        // Cached schema shown while the database is unreachable
+        if let Some(cached_at) = self.offline_since() {
+            let text = format!("📴 OFFLINE: schema and table samples cached {}, read-only", cached_at);
+            let mut retry = false;
+            egui::TopBottomPanel::top("offline_banner")
+                .exact_height(20.0)
+                .frame(egui::Frame::none().fill(egui::Color32::from_rgb(90, 90, 110)))
+                .show(ctx, |ui| {
+                    ui.horizontal_centered(|ui| {
+                        ui.label(egui::RichText::new(text).size(11.0).strong().color(egui::Color32::WHITE));
+                        if ui.small_button("Retry").on_hover_text("Connect again").clicked() {
+                            retry = true;
+                        }
+                    });
                 });
+            if retry {
+                self.connect_to_database();
+            }
+        }
+
+        // Top menu bar
+        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+            if let Some(event) = self.menu_bar.show(ui, &self.connection_status, &mut self.share_safely, &self.config.recent_sql_files) {
+                self.handle_menu_event(ctx, event);
+            }
+        });
+
+        // Status bar
+        let row_count = self.tabs.get(self.active_tab)
+            .and_then(|tab| tab.data.as_ref())
+            .map(|data| data.rows.len());
+        let selection_summary = row_count
+            .and(self.data_grid.selection_stats())
+            .map(|stats| stats.summary());
+
+        let can_cancel = self.can_cancel();
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            if let Some(event) = self.status_bar.show(ui, &self.status_message, row_count, selection_summary, self.last_query_stats, can_cancel, self.transaction_open, self.transaction_status) {
+                match event {
+                    StatusBarEvent::Cancel => self.cancel_pending_operation(),
+                    StatusBarEvent::Commit => self.control_transaction(TransactionControl::Commit, None),
+                    StatusBarEvent::Rollback => self.control_transaction(TransactionControl::Rollback, None),
+                }
+            }
+        });
+
+        if !self.operation_queue.is_empty() {
+            let running = self.pending_operation.as_ref().map(|op| op.label());
+            let can_cancel = self.can_cancel();
+            egui::TopBottomPanel::bottom("operation_queue").resizable(true).default_height(120.0).show(ctx, |ui| {
+                match self.queue_panel.show(ui, running.as_deref(), can_cancel, &self.operation_queue) {
+                    Some(QueuePanelEvent::CancelRunning) => self.cancel_pending_operation(),
+                    Some(QueuePanelEvent::Remove(id)) => {
+                        self.operation_queue.remove(id);
+                    }
+                    Some(QueuePanelEvent::Clear) => self.operation_queue.clear(),
+                    None => {}
+                }
+            });
+        }
+
+        if self.show_query_log {
+            egui::TopBottomPanel::bottom("query_log").resizable(true).default_height(140.0).show(ctx, |ui| {
+                match self.query_log_panel.show(ui, &self.query_log) {
+                    Some(QueryLogPanelEvent::Clear) => self.query_log.clear(),
+                    Some(QueryLogPanelEvent::Close) => self.show_query_log = false,
+                    None => {}
+                }
+            });
+        }
+
+        // Query panel (if shown) - syncs with active tab's query
+        if self.show_query_panel {
+            // Columns of the current result, for autocomplete outside the loaded schemas
+            let available_columns: Vec<String> = if let Some(tab) = self.tabs.get(self.active_tab) {
+                if let Some(data) = &tab.data {
+                    data.columns.iter().map(|c| c.name.clone()).collect()
+                } else {
+                    Vec::new()
+                }
+            } else {
+                Vec::new()
+            };
+
+            // The active tab's SQL buffer, each tab editing its own
+            let tab_id = self.tabs.get(self.active_tab).map(|tab| tab.id);
+            let mut event = None;
+            egui::TopBottomPanel::top("query_panel").show(ctx, |ui| {
+                let query = match self.tabs.get_mut(self.active_tab) {
+                    Some(tab) => &mut tab.query_input,
+                    None => &mut self.query_input,
+                };
+                let force_primary = self.replica_database.is_some().then_some(&mut self.force_primary);
+                event = self.query_panel.show(ui, tab_id, query, &self.schemas, &available_columns, &self.config.preferences.snippets, self.driver, force_primary, &mut self.statement_timeout, &mut self.collect_resource_report, &mut self.transaction_mode, can_cancel);
+            });
+
+            match event {
+                // The tab's result replaces its rows
+                Some(QueryPanelEvent::Execute) => self.execute_query(tab_id.map(|_| self.active_tab)),
+                // The selection is not the tab's query, so its result gets a tab of its own
+                Some(QueryPanelEvent::ExecuteSelection(sql)) => self.execute_sql(sql, None),
+                Some(QueryPanelEvent::Clear) => self.query_buffer_mut().clear(),
+                Some(QueryPanelEvent::Close) => self.show_query_panel = false,
+                Some(QueryPanelEvent::SaveQuery) => {
+                    if !self.query_buffer().trim().is_empty() {
+                        self.show_save_query_dialog = true;
+                    } else {
+                        self.status_message = "Cannot save empty query".to_string();
+                    }
+                }
+                Some(QueryPanelEvent::LoadQuery) => self.show_saved_queries_dialog = true,
+                Some(QueryPanelEvent::ShowHistory) => self.show_query_history_dialog = true,
+                Some(QueryPanelEvent::Explain(analyze)) => {
+                    let sql = self.query_buffer().to_string();
+                    self.explain_query(&sql, analyze, None);
+                }
+                Some(QueryPanelEvent::ShareQuery) => self.begin_share_query(),
+                Some(QueryPanelEvent::Cancel) => self.cancel_pending_operation(),
+                None => {}
+            }
+        }
+
+        // Confirmation for writes on a production connection
+        if let Some(pending_write) = &self.pending_write {
+            let details = match pending_write {
+                PendingWrite::Query(sql, _) => sql.clone(),
+                PendingWrite::ApplyEdits(tab_index) => self.tabs.get(*tab_index)
+                    .and_then(|tab| match (&tab.source, &tab.data) {
+                        (TabSource::Table { schema, table }, Some(data)) => {
+                            tab.pending_edits.to_update_statements(schema, table, &data.columns).ok()
+                        }
+                        _ => None,
+                    })
+                    .map(|statements| statements.join("\n"))
+                    .unwrap_or_default(),
+                PendingWrite::RunScript(path, _) => format!("Run every statement in {}", path.display()),
+                PendingWrite::AlterColumns(_, statements) => statements.join(";\n"),
+                PendingWrite::ImportFile(plan) => format!("COPY the rows of {} into {}.{}", plan.path.display(), plan.schema, plan.table),
+            };
+            let name = self.dangerous_connection().map(|conn| conn.name.clone()).unwrap_or_default();
+            let message = format!("This will write to the production connection '{}'.", name);
+
+            if let Some(event) = self.confirm_dialog.show(ctx, "⚠ Confirm Write", &message, &details, "Run on production") {
+                match (event, self.pending_write.take()) {
+                    (ConfirmDialogEvent::Confirm, Some(PendingWrite::Query(sql, tab_index))) => self.run_query(sql, tab_index),
+                    (ConfirmDialogEvent::Confirm, Some(PendingWrite::ApplyEdits(tab_index))) => self.write_pending_edits(tab_index),
+                    (ConfirmDialogEvent::Confirm, Some(PendingWrite::RunScript(path, stop_on_error))) => {
+                        self.start_script(path, stop_on_error)
+                    }
+                    (ConfirmDialogEvent::Confirm, Some(PendingWrite::AlterColumns(table, statements))) => {
+                        self.start_alter_columns(table, statements)
+                    }
+                    (ConfirmDialogEvent::Confirm, Some(PendingWrite::ImportFile(plan))) => self.start_import(plan),
+                    _ => self.status_message = "Write cancelled".to_string(),
+                }
+            }
+        }
+
+        // Export dialog
+        if self.show_export_dialog {
+            let row_count = self.tabs.get(self.active_tab).map(|tab| tab.filtered_row_count()).unwrap_or(0);
+            if let Some(event) = self.export_dialog.show(ctx, row_count, &self.config.export_presets) {
+                match event {
+                    ExportDialogEvent::Export(format, settings, path) => {
+                        self.show_export_dialog = false;
+                        self.start_export(format, settings, path);
+                    }
+                    ExportDialogEvent::SavePreset(preset) => {
+                        self.status_message = format!("Saved export preset '{}'", preset.name);
+                        self.config.save_export_preset(preset);
+                        let _ = self.config.save();
+                    }
+                    ExportDialogEvent::DeletePreset(name) => {
+                        self.config.delete_export_preset(&name);
+                        let _ = self.config.save();
+                        self.status_message = format!("Deleted export preset '{}'", name);
+                    }
+                    ExportDialogEvent::Cancel => self.show_export_dialog = false,
+                }
+            }
+        }
+
+        // SQL file runner
+        if self.show_script_dialog {
+            let running = matches!(self.pending_operation, Some(AsyncOperation::RunScript(..)));
+            let progress = self.script_progress.as_ref().map(|progress| progress.lock().unwrap().clone());
+            if let Some(event) = self.script_dialog.show(ctx, progress.as_ref(), running) {
+                match event {
+                    ScriptRunnerDialogEvent::Run(path, stop_on_error) => self.run_sql_file(path, stop_on_error),
+                    ScriptRunnerDialogEvent::Stop => {
+                        if let Some(progress) = &self.script_progress {
+                            progress.lock().unwrap().stop_requested = true;
+                        }
+                        self.status_message = "Stopping after the current statement...".to_string();
+                    }
+                    ScriptRunnerDialogEvent::Close => {
+                        self.show_script_dialog = false;
+                        self.script_progress = None;
+                    }
+                }
+            }
+        }
+
+        // Pasted rows to a temporary table
+        if self.show_paste_table_dialog {
+            let running = matches!(self.pending_operation, Some(AsyncOperation::CreateTempTable(..)));
+            match self.paste_table_dialog.show(ctx, self.driver, running) {
+                Some(PasteTableDialogEvent::Create(table, name)) => self.create_temp_table(table, name),
+                Some(PasteTableDialogEvent::Close) => self.show_paste_table_dialog = false,
+                None => {}
+            }
+        }
 
-                // Update the active tab's query
-                if let Some(tab) = self.tabs.get_mut(self.active_tab) {
-                    tab.query_input = temp_query.clone();
+        // Batch export dialog
+        if self.show_batch_export_dialog {
+            let running = matches!(self.pending_operation, Some(AsyncOperation::BatchExport(..)));
+            let progress = self.batch_progress.as_ref().map(|progress| progress.lock().unwrap().clone());
+            if let Some(event) = self.batch_export_dialog.show(ctx, &self.schemas, &self.config.export_presets, progress.as_ref(), running) {
+                match event {
+                    BatchExportDialogEvent::Start(tables, format, settings, directory) => {
+                        self.start_batch_export(tables, format, settings, directory);
+                    }
+                    BatchExportDialogEvent::Stop => {
+                        if let Some(progress) = &self.batch_progress {
+                            progress.lock().unwrap().stop_requested = true;
+                        }
+                        self.status_message = "Stopping after the current table...".to_string();
+                    }
+                    BatchExportDialogEvent::Close => {
+                        self.show_batch_export_dialog = false;
+                        self.batch_progress = None;
+                    }
                 }
-                self.query_input = temp_query.clone();
+            }
+        }
 
-                // Handle events after updating
-                if execute {
-                    self.execute_query(Some(self.active_tab));
-                }
-                if clear {
-                    if let Some(tab) = self.tabs.get_mut(self.active_tab) {
-                        tab.query_input.clear();
-                    }
-                    self.query_input.clear();
+        // File path dialog (share / open shared query)
+        if let Some(action) = self.file_action {
+            if let Some(event) = self.file_dialog.show(ctx, action.title(), action.action_label()) {
+                self.file_action = None;
+                if let FileDialogEvent::Confirm(path) = event {
+                    self.handle_file_action(action, path);
                 }
-                if close {
-                    self.show_query_panel = false;
+            }
+        }
+
+        // Schema snapshot comparison result
+        if let Some((source, changes)) = &self.schema_diff {
+            if let Some(SchemaDiffDialogEvent::Close) = self.schema_diff_dialog.show(ctx, source, changes) {
+                self.schema_diff = None;
+            }
+        }
+
+        // ALTER assistant
+        let applying = matches!(self.pending_operation, Some(AsyncOperation::AlterColumns(..)));
+        if let Some(plan) = &mut self.alter_plan {
+            match self.alter_columns_dialog.show(ctx, plan, applying) {
+                Some(AlterColumnsDialogEvent::Apply(statements)) => self.apply_alter_columns(statements),
+                Some(AlterColumnsDialogEvent::Close) => self.alter_plan = None,
+                None => {}
+            }
+        }
+
+        // CSV/TSV import wizard
+        let importing = matches!(self.pending_operation, Some(AsyncOperation::ImportFile(..)));
+        if let Some(plan) = &mut self.import_plan {
+            match self.import_dialog.show(ctx, plan, self.import_progress.as_deref(), importing) {
+                Some(ImportDialogEvent::Import) => {
+                    let plan = plan.clone();
+                    self.import_file(plan);
                 }
-                if save_query {
-                    if !temp_query.trim().is_empty() {
-                        self.show_save_query_dialog = true;
-                    } else {
-                        self.status_message = "Cannot save empty query".to_string();
+                Some(ImportDialogEvent::Cancel) => self.cancel_pending_operation(),
+                Some(ImportDialogEvent::Close) => self.import_plan = None,
+                None => {}
+            }
+        }
+
+        // Export as SQL
+        if let Some((schema, table)) = &self.sql_dump_target {
+            let title = match table {
+                Some(table) => format!("{}.{}", schema, table),
+                None => schema.clone(),
+            };
+            let running = matches!(self.pending_operation, Some(AsyncOperation::SqlDump(..)));
+            let progress = self.sql_dump_progress.as_ref().map(|progress| progress.lock().unwrap().clone());
+            match self.sql_dump_dialog.show(ctx, &title, self.driver, progress.as_ref(), running) {
+                Some(SqlDumpDialogEvent::Start(dump, path)) => self.start_sql_dump(dump, path),
+                Some(SqlDumpDialogEvent::Stop) => {
+                    if let Some(progress) = &self.sql_dump_progress {
+                        progress.lock().unwrap().stop_requested = true;
                     }
                 }
-                if load_query {
-                    self.show_saved_queries_dialog = true;
+                Some(SqlDumpDialogEvent::Close) => {
+                    self.sql_dump_target = None;
+                    self.sql_dump_progress = None;
                 }
+                None => {}
+            }
+        }
+
+        // Multi-statement query results
+        if let Some((summary, total)) = &self.statement_summary {
+            if let Some(StatementSummaryDialogEvent::Close) = self.statement_summary_dialog.show(ctx, summary, *total) {
+                self.statement_summary = None;
             }
         }
 
@@ -430,7 +2971,7 @@ impl eframe::App for DbClientApp {
             if let Some(event) = self.save_query_dialog.show(ctx) {
                 match event {
                     SaveQueryDialogEvent::Save(name) => {
-                        self.saved_queries.add_query(name, self.query_input.clone());
+                        self.saved_queries.add_query(name, self.query_buffer().to_string());
                         let _ = self.saved_queries.save();
                         self.status_message = "Query saved successfully".to_string();
                         self.show_save_query_dialog = false;
@@ -447,12 +2988,8 @@ impl eframe::App for DbClientApp {
             if let Some(event) = self.saved_queries_dialog.show(ctx, &self.saved_queries) {
                 match event {
                     SavedQueriesDialogEvent::Load(index) => {
-                        if let Some(query) = self.saved_queries.get_query(index) {
-                            self.query_input = query.sql.clone();
-                            // Update active tab's query if there is one
-                            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
-                                tab.query_input = query.sql.clone();
-                            }
+                        if let Some(query) = self.saved_queries.get_query(index).cloned() {
+                            *self.query_buffer_mut() = query.sql;
                             self.status_message = format!("Loaded query: {}", query.name);
                             self.show_saved_queries_dialog = false;
                         }
@@ -469,16 +3006,40 @@ impl eframe::App for DbClientApp {
             }
         }
 
+        // Query history dialog
+        if self.show_query_history_dialog {
+            if let Some(event) = self.query_history_dialog.show(ctx, &self.query_history) {
+                match event {
+                    QueryHistoryDialogEvent::Load(index) | QueryHistoryDialogEvent::Rerun(index) => {
+                        if let Some(entry) = self.query_history.get(index) {
+                            *self.query_buffer_mut() = entry.sql.clone();
+                            self.show_query_panel = true;
+                            self.show_query_history_dialog = false;
+
+                            if matches!(event, QueryHistoryDialogEvent::Rerun(_)) {
+                                self.execute_query(None);
+                            } else {
+                                self.status_message = "Loaded query from history".to_string();
+                            }
+                        }
+                    }
+                    QueryHistoryDialogEvent::Clear => {
+                        self.query_history.clear();
+                        let _ = self.query_history.save();
+                        self.status_message = "Query history cleared".to_string();
+                    }
+                    QueryHistoryDialogEvent::Close => self.show_query_history_dialog = false,
+                }
+            }
+        }
+
         // Settings dialog
         if self.show_settings {
-            if let Some(event) = self.settings_dialog.show(ctx, &self.config) {
+            if let Some(event) = self.settings_dialog.show(ctx, &mut self.config) {
                 match event {
                     SettingsDialogEvent::Connect(idx) => {
-                        if let Some(conn) = self.config.get_connection(idx) {
-                            self.connection_string = conn.to_connection_string();
-                            self.config.last_connection_index = Some(idx);
-                            let _ = self.config.save();
-                            self.connect_to_database();
+                        if self.config.get_connection(idx).is_some() {
+                            self.switch_connection(idx);
                             self.show_settings = false;
                         }
                     }
@@ -496,6 +3057,9 @@ impl eframe::App for DbClientApp {
                         self.edit_connection = Some(DatabaseConnection::new());
                         self.edit_connection_index = None;
                     }
+                    SettingsDialogEvent::PreferencesChanged => {
+                        let _ = self.config.save();
+                    }
                     SettingsDialogEvent::Close => self.show_settings = false,
                 }
             }
@@ -523,6 +3087,150 @@ impl eframe::App for DbClientApp {
             }
         }
 
+        // Shortcuts of the keymap, ignored while the command palette takes the keyboard
+        if !self.show_command_palette {
+            if let Some(command) = keymap::pressed_command(ctx) {
+                self.run_command(ctx, command);
+            }
+        }
+        if self.show_command_palette {
+            let items = self.palette_items();
+            match self.command_palette.show(ctx, &items) {
+                Some(CommandPaletteEvent::Run(item)) => {
+                    self.show_command_palette = false;
+                    match item {
+                        PaletteItem::Command(command) => self.run_command(ctx, command),
+                        PaletteItem::Table(schema, table) => self.handle_tree_event(DatabaseTreeEvent::TableClicked(schema, table)),
+                    }
+                }
+                Some(CommandPaletteEvent::Close) => self.show_command_palette = false,
+                None => {}
+            }
+        }
+
+        // JSON filter builder, appending its condition to the table tab's server filters
+        if let Some(mut filter) = self.json_filter.take() {
+            let event = match self.tabs.get(self.active_tab).and_then(|tab| tab.data.as_ref()) {
+                Some(data) => self.json_filter_dialog.show(ctx, data, &mut filter),
+                None => Some(JsonFilterDialogEvent::Close),
+            };
+            match event {
+                Some(JsonFilterDialogEvent::Add(sql)) => {
+                    if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                        tab.server_filters.push(sql);
+                    }
+                    self.save_state();
+                    self.reload_current_tab();
+                }
+                Some(JsonFilterDialogEvent::Close) => {}
+                None => self.json_filter = Some(filter),
+            }
+        }
+
+        // Two rows compared side by side, closed once the active result no longer has them
+        if let Some(comparison) = self.row_comparison {
+            let anonymization_rules = self.anonymization_rules();
+            let event = match self.tabs.get(self.active_tab).and_then(|tab| tab.data.as_ref()).filter(|data| comparison.is_valid(data)) {
+                Some(data) => {
+                    let anonymizer = Anonymizer::new(&anonymization_rules, &data.columns);
+                    self.row_compare_dialog.show(ctx, data, comparison, anonymizer.as_ref())
+                }
+                None => Some(RowCompareDialogEvent::Close),
+            };
+            if let Some(RowCompareDialogEvent::Close) = event {
+                self.row_comparison = None;
+            }
+        }
+
+        // Full value of one cell, closed once the active result no longer has it
+        if let Some((row, column)) = self.inspected_cell {
+            let anonymization_rules = self.anonymization_rules();
+            let cell = self.tabs.get(self.active_tab)
+                .and_then(|tab| tab.data.as_ref())
+                .and_then(|data| Some((data, data.columns.get(column)?, data.rows.get(row)?.get(column)?)));
+            let event = match cell {
+                Some((data, column_info, value)) => {
+                    let value = match Anonymizer::new(&anonymization_rules, &data.columns) {
+                        Some(anonymizer) => anonymizer.value(column, value),
+                        None => value.clone(),
+                    };
+                    self.cell_inspector.show(ctx, column_info, &value)
+                }
+                None => Some(CellInspectorEvent::Close),
+            };
+            match event {
+                Some(CellInspectorEvent::SaveBytes) => self.begin_save_cell_bytes(row, column),
+                Some(CellInspectorEvent::Close) => self.inspected_cell = None,
+                None => {}
+            }
+        }
+
+        // Display column computed from the active tab's others
+        if let Some(mut column) = self.computed_column.take() {
+            let event = match self.tabs.get(self.active_tab).and_then(|tab| tab.data.as_ref()) {
+                Some(data) => self.computed_column_dialog.show(ctx, data, &mut column),
+                None => Some(ComputedColumnDialogEvent::Close),
+            };
+            match event {
+                Some(ComputedColumnDialogEvent::Add) => {
+                    if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                        column.name = column.name.trim().to_string();
+                        self.status_message = format!("Added computed column {}", column.name);
+                        tab.computed_columns.push(column);
+                        tab.sync_computed_columns();
+                    }
+                    self.save_state();
+                }
+                Some(ComputedColumnDialogEvent::Close) => {}
+                None => self.computed_column = Some(column),
+            }
+        }
+
+        // Find and replace over a column, staged as edits of the rows passing the grid filters
+        if let Some(mut replace) = self.value_replace.take() {
+            let event = match self.tabs.get(self.active_tab) {
+                Some(tab @ Tab { data: Some(data), .. }) => {
+                    let rows = filter_row_indices(&data.rows, tab.local_filters());
+                    self.replace_values_dialog.show(ctx, data, &rows, &tab.pending_edits, &mut replace)
+                }
+                _ => Some(ReplaceValuesDialogEvent::Close),
+            };
+            match event {
+                Some(ReplaceValuesDialogEvent::Stage) => {
+                    if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                        if let Some(data) = &tab.data {
+                            let rows = filter_row_indices(&data.rows, tab.local_filters());
+                            match replace.preview(data, &rows, &tab.pending_edits) {
+                                Ok(replacements) => {
+                                    let count = tab.pending_edits.stage_replacements(replace.column, replacements);
+                                    self.status_message = format!("Staged {} edits, review them before applying", count);
+                                }
+                                Err(e) => self.status_message = format!("Cannot replace values: {}", e),
+                            }
+                        }
+                    }
+                }
+                Some(ReplaceValuesDialogEvent::Close) => {}
+                None => self.value_replace = Some(replace),
+            }
+        }
+
+        // Value frequencies or histogram of a column of the active tab
+        if let Some((tab_id, distribution)) = self.value_distribution.take() {
+            let event = match self.tabs.get(self.active_tab).filter(|tab| tab.id == tab_id) {
+                Some(Tab { data: Some(data), .. }) => {
+                    let column = data.columns.get(distribution.column).map(|column| column.name.as_str()).unwrap_or_default();
+                    self.distribution_panel.show(ctx, column, &distribution)
+                }
+                _ => Some(DistributionPanelEvent::Close),
+            };
+            match event {
+                Some(DistributionPanelEvent::Histogram(histogram)) => self.chart_distribution(distribution.column, Some(histogram)),
+                Some(DistributionPanelEvent::Close) => {}
+                None => self.value_distribution = Some((tab_id, distribution)),
+            }
+        }
+
         // Left sidebar - Database tree
         egui::SidePanel::left("database_structure_panel")
             .resizable(true)
@@ -533,68 +3241,64 @@ impl eframe::App for DbClientApp {
                 ui.heading("Database Structure");
                 ui.separator();
 
-                if let Some(event) = self.database_tree.show(ui, &self.schemas, &self.expanded_schemas, &self.expanded_tables, &self.selected_table, &mut self.search_query) {
-                    match event {
-                        DatabaseTreeEvent::TableClicked(schema, table) => {
-                            self.selected_table = Some((schema.clone(), table.clone()));
-                            self.load_table_data(schema, table, None);
-                        }
-                        DatabaseTreeEvent::TableRightClicked(schema, table) => {
-                            self.selected_table = Some((schema.clone(), table.clone()));
-                            self.load_table_data(schema, table, None);
-                        }
-                        DatabaseTreeEvent::TableToggled(schema, table) => {
-                            let table_key = (schema, table);
-                            if self.expanded_tables.contains(&table_key) {
-                                self.expanded_tables.remove(&table_key);
-                            } else {
-                                self.expanded_tables.insert(table_key);
-                            }
-                        }
-                        DatabaseTreeEvent::SchemaToggled(schema_name) => {
-                            if self.expanded_schemas.contains(&schema_name) {
-                                self.expanded_schemas.remove(&schema_name);
-                            } else {
-                                self.expanded_schemas.insert(schema_name);
-                            }
-                            self.save_state();
-                        }
-                        DatabaseTreeEvent::SearchChanged(_query) => {
-                            // Search query already updated via mutable reference
-                        }
-                    }
+                let schema_styles = self.config.get_last_connection().map_or(&[][..], |conn| conn.schema_styles.as_slice());
+                if let Some(event) = self.database_tree.show(ui, &self.schemas, &self.expanded_schemas, &self.expanded_tables, &self.selected_table, &mut self.search_query, &self.config.preferences.query_templates, schema_styles) {
+                    self.handle_tree_event(event);
                 }
             });
 
+        if self.show_watch_panel {
+            let mut watch_event = None;
+            egui::SidePanel::right("watch_panel")
+                .resizable(true)
+                .default_width(240.0)
+                .show(ctx, |ui| {
+                    watch_event = self.watch_panel.show(ui, &self.watches);
+                });
+            if let Some(event) = watch_event {
+                self.handle_watch_event(event);
+            }
+        }
+
         // Main content area - Tabs and data grid
         egui::CentralPanel::default().show(ctx, |ui| {
             // Tab bar
-            if let Some(event) = self.tab_bar.show(ui, &self.tabs, self.active_tab) {
-                match event {
-                    TabBarEvent::TabActivated(i) => {
-                        self.active_tab = i;
-                        // Sync query_input with the newly active tab's query
-                        if let Some(tab) = self.tabs.get(i) {
-                            self.query_input = tab.query_input.clone();
-                        }
-                        self.save_state();
-                    }
-                    TabBarEvent::TabClosed(i) => {
-                        self.close_tab(i);
-                    }
+            let tab_colors: Vec<Option<egui::Color32>> = self.tabs.iter()
+                .map(|tab| {
+                    tab.connection_name.as_ref()
+                        .and_then(|name| self.config.connections.iter().find(|conn| &conn.name == name))
+                        .and_then(|conn| conn.environment.color())
+                        .map(|(r, g, b)| egui::Color32::from_rgb(r, g, b))
+                })
+                .collect();
+            if let Some(event) = self.tab_bar.show(ui, &self.tabs, self.active_tab, &tab_colors) {
+                self.handle_tab_bar_event(event);
+            }
+
+            if self.tabs.get(self.active_tab).is_some_and(|tab| tab.restored) {
+                let other_connection = self.tab_connection_index(self.active_tab)
+                    .and_then(|idx| self.config.get_connection(idx))
+                    .map(|conn| conn.name.clone());
+                match self.restored_tab_banner.show(ui, other_connection.as_deref()) {
+                    Some(RestoredTabBannerEvent::Reload) => self.reload_current_tab(),
+                    Some(RestoredTabBannerEvent::Dismiss) => self.tabs[self.active_tab].restored = false,
+                    None => {}
                 }
             }
 
             // Display SQL query for query-based tabs
+            let source = self.source_description();
             if let Some(tab) = self.tabs.get(self.active_tab) {
-                if let TabSource::Query { sql } = &tab.source {
+                if let TabSource::Query { sql } | TabSource::Explain { sql, .. } = &tab.source {
                     if !sql.is_empty() {
                         ui.group(|ui| {
                             ui.horizontal(|ui| {
                                 ui.label(egui::RichText::new("📝 SQL Query:").strong());
                                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                                     if ui.small_button("📋 Copy").on_hover_text("Copy query to clipboard").clicked() {
-                                        ui.output_mut(|o| o.copied_text = sql.clone());
+                                        let copied = Watermark::new(source.clone(), Some(sql.clone()))
+                                            .apply(sql, "-- ", &self.config.preferences);
+                                        ui.output_mut(|o| o.copied_text = copied);
                                         self.status_message = "Query copied to clipboard".to_string();
                                     }
                                     if ui.small_button("✏ Edit").on_hover_text("Edit in query panel").clicked() {
@@ -625,84 +3329,222 @@ impl eframe::App for DbClientApp {
                 }
             }
 
-            // Filter bar
+            // Table tabs switch between their rows and their indexes, constraints and triggers
+            let mut load_structure = false;
+            let mut load_data = false;
+            let mut view_changed = false;
             if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                if let TabSource::Table { .. } = tab.source {
+                    ui.horizontal(|ui| {
+                        let data = ui.selectable_value(&mut tab.view, TabView::Data, "📊 Data");
+                        let structure = ui.selectable_value(&mut tab.view, TabView::Structure, "🏗 Structure");
+                        view_changed = data.changed() || structure.changed();
+                        load_structure = structure.changed() && tab.structure.is_none();
+                        // Tabs opened on their structure have no rows yet; large tables wait for Load Data
+                        load_data = data.changed() && tab.data.is_none() && !tab.is_loading && !tab.awaits_rows();
+                    });
+                    ui.separator();
+                } else if let (TabSource::Query { .. }, Some(_)) = (&tab.source, &tab.data) {
+                    ui.horizontal(|ui| {
+                        let data = ui.selectable_value(&mut tab.view, TabView::Data, "📊 Data");
+                        let chart = ui.selectable_value(&mut tab.view, TabView::Chart, "📈 Chart");
+                        view_changed = data.changed() || chart.changed();
+                    });
+                    ui.separator();
+                }
+            }
+            if view_changed {
+                self.save_state();
+            }
+
+            // Large tables opened on their structure load their rows on request
+            let large_table = self.tabs.get(self.active_tab).and_then(|tab| Some((tab.estimated_rows?, tab.row_limit)));
+            if let Some((estimated_rows, loaded)) = large_table {
+                match self.large_table_banner.show(ui, estimated_rows, loaded, self.config.preferences.preview_rows, MAX_TABLE_ROWS) {
+                    Some(LargeTableBannerEvent::LoadPreview) => {
+                        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                            tab.view = TabView::Data;
+                        }
+                        load_data = true;
+                    }
+                    Some(LargeTableBannerEvent::LoadMore) => {
+                        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                            tab.row_limit = Some(MAX_TABLE_ROWS);
+                        }
+                        load_data = true;
+                    }
+                    None => {}
+                }
+            }
+            if load_data {
+                self.save_state();
+                self.reload_current_tab();
+            }
+            let shows_structure = self.tabs.get(self.active_tab).is_some_and(|tab| tab.shows_structure());
+
+            // Wide results draw a subset of their columns unless all are asked for
+            let wide_table = self.tabs.get(self.active_tab)
+                .filter(|tab| !tab.shows_structure() && !tab.shows_chart())
+                .and_then(|tab| Some((tab.data.as_ref()?.columns.len(), tab.shown_columns())))
+                .filter(|&(columns, _)| columns > WIDE_TABLE_COLUMNS);
+            if let Some((columns, shown)) = wide_table {
+                let show_all = match self.wide_table_banner.show(ui, columns, shown) {
+                    Some(WideTableBannerEvent::ShowAllColumns) => Some(true),
+                    Some(WideTableBannerEvent::ShowFirstColumns) => Some(false),
+                    None => None,
+                };
+                if let (Some(show_all), Some(tab)) = (show_all, self.tabs.get_mut(self.active_tab)) {
+                    tab.show_all_columns = show_all;
+                    self.save_state();
+                }
+            }
+
+            // Filter bar
+            let mut reload_filtered = false;
+            if let Some(tab) = self.tabs.get_mut(self.active_tab).filter(|_| !shows_structure) {
                 if let Some(data) = &tab.data {
-                    if let Some(event) = self.filter_bar.show(ui, &mut tab.filters, &data.columns) {
+                    // Distinct views are filtered locally, their rows are not the table's
+                    let server_side = (matches!(tab.source, TabSource::Table { .. }) && tab.distinct.is_none())
+                        .then_some(&mut tab.server_side_filtering);
+                    let table = match &tab.source {
+                        TabSource::Table { schema, table } => Some((schema.clone(), table.clone())),
+                        _ => None,
+                    };
+                    let filter_sets: Option<Vec<&str>> = table.as_ref().map(|(schema, table)| {
+                        self.saved_queries.filter_sets_for(schema, table).iter().map(|set| set.name.as_str()).collect()
+                    });
+                    let filter_row = self.config.preferences.grid_filter_row;
+                    if let Some(event) = self.filter_bar.show(ui, &mut tab.filters, &data.columns, server_side, filter_sets.as_deref(), filter_row) {
                         match event {
                             FilterBarEvent::FilterAdded | FilterBarEvent::FilterRemoved(_) | FilterBarEvent::FiltersChanged => {
-                                // Filters changed, will be applied on next render
+                                // Filters changed, keep the page inside the new result
+                                tab.on_filters_changed();
                                 self.save_state();
                             }
                             FilterBarEvent::FilterApplied => {
-                                // User clicked apply, could trigger re-render or show message
+                                // Local filters apply as they are edited, server-side ones on reload
+                                reload_filtered = tab.server_side_filtering;
+                            }
+                            FilterBarEvent::ServerSideToggled => {
+                                tab.on_filters_changed();
+                                self.save_state();
+                                reload_filtered = true;
+                            }
+                            FilterBarEvent::FilterSetSaved(name) => {
+                                if let Some((schema, table)) = table {
+                                    self.saved_queries.save_filter_set(SavedFilterSet::new(name, schema, table, &tab.filters, &data.columns));
+                                    let _ = self.saved_queries.save();
+                                }
+                            }
+                            FilterBarEvent::FilterSetApplied(name) => {
+                                if let Some(set) = table.and_then(|(schema, table)| self.saved_queries.filter_set(&schema, &table, &name)) {
+                                    tab.filters = set.rules(&data.columns);
+                                    tab.on_filters_changed();
+                                    reload_filtered = tab.server_side_filtering;
+                                    self.save_state();
+                                }
+                            }
+                            FilterBarEvent::FilterSetDeleted(name) => {
+                                if let Some((schema, table)) = table {
+                                    self.saved_queries.delete_filter_set(&schema, &table, &name);
+                                    let _ = self.saved_queries.save();
+                                }
+                            }
+                            FilterBarEvent::FilterRowToggled(shown) => {
+                                self.config.preferences.grid_filter_row = shown;
+                                let _ = self.config.save();
                             }
                         }
                     }
                     ui.separator();
                 }
             }
+            if reload_filtered {
+                self.reload_current_tab();
+            }
 
-            // Search field (above data grid) - stored for later use after getting match count
-            let mut search_changed = false;
-            let mut search_cleared = false;
-            let mut next_match = false;
-            let mut prev_match = false;
-            let mut search_text_for_ui = String::new();
-            let mut current_match_idx = 0;
+            // Server-side filters of Postgres table tabs, with the JSON filter builder
+            let mut server_filter_event = None;
+            if let Some(tab) = self.tabs.get(self.active_tab).filter(|_| !shows_structure && self.driver == Driver::Postgres) {
+                if let (TabSource::Table { .. }, Some(data)) = (&tab.source, &tab.data) {
+                    let json_column = data.columns.iter().find(|column| column.data_type.starts_with("jsonb"));
+                    server_filter_event = self.server_filter_bar.show(ui, &tab.server_filters, json_column.is_some())
+                        .map(|event| (event, json_column.map(|column| column.name.clone())));
+                }
+            }
+            if let Some((event, json_column)) = server_filter_event {
+                match event {
+                    ServerFilterBarEvent::BuildJsonFilter => self.json_filter = json_column.map(JsonFilter::new),
+                    ServerFilterBarEvent::Remove(index) => {
+                        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                            tab.server_filters.remove(index);
+                        }
+                        self.save_state();
+                        self.reload_current_tab();
+                    }
+                    ServerFilterBarEvent::Clear => {
+                        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                            tab.server_filters.clear();
+                        }
+                        self.save_state();
+                        self.reload_current_tab();
+                    }
+                }
+            }
 
-            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
-                if tab.data.is_some() {
-                    search_text_for_ui = tab.search_text.clone();
-                    current_match_idx = tab.search_match_index;
+            // Find bar (Ctrl/Cmd+F) with F3 / Shift+F3 match navigation
+            let shows_chart = self.tabs.get(self.active_tab).is_some_and(|tab| tab.shows_chart());
+            let has_tab_data = !shows_structure && !shows_chart && self.tabs.get(self.active_tab).is_some_and(|tab| tab.data.is_some());
+            let mut find_event = None;
+            if has_tab_data {
+                if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::F)) {
+                    self.show_find_bar = true;
+                    self.find_bar.focus();
+                }
+                if ctx.input_mut(|i| i.consume_key(egui::Modifiers::SHIFT, egui::Key::F3)) {
+                    find_event = Some(FindBarEvent::Previous);
+                } else if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F3)) {
+                    find_event = Some(FindBarEvent::Next);
+                }
+            }
 
-                    ui.horizontal(|ui| {
-                        ui.label("🔍");
-                        let response = ui.add(
-                            egui::TextEdit::singleline(&mut tab.search_text)
-                                .hint_text("Search in current table...")
-                                .desired_width(300.0)
-                        );
+            if let Some(tab) = self.tabs.get_mut(self.active_tab).filter(|_| has_tab_data) {
+                if let Some(data) = &tab.data {
+                    let summary = self.data_grid.summary(data, tab.sort_column, tab.sort_ascending, tab.page_size, tab.local_filters(), &tab.search_text, tab.search_match_index);
 
-                        if response.changed() {
-                            search_changed = true;
+                    // A search restored from the previous session keeps the bar open
+                    if self.show_find_bar || !tab.search_text.is_empty() {
+                        if let Some(event) = self.find_bar.show(ui, &mut tab.search_text, tab.search_match_index, summary.total_matches) {
+                            find_event = Some(event);
                         }
+                    }
 
-                        if !tab.search_text.is_empty() {
-                            // Navigation arrows
-                            if ui.button("◀").on_hover_text("Previous match").clicked() {
-                                prev_match = true;
-                            }
-                            if ui.button("▶").on_hover_text("Next match").clicked() {
-                                next_match = true;
-                            }
+                    match find_event {
+                        Some(FindBarEvent::SearchChanged) => {
+                            tab.on_search_changed();
+                            self.save_state();
                         }
-
-                        if !tab.search_text.is_empty() && ui.small_button("✖").clicked() {
+                        Some(FindBarEvent::Next) => tab.next_match(summary.total_matches),
+                        Some(FindBarEvent::Previous) => tab.previous_match(summary.total_matches),
+                        Some(FindBarEvent::Close) => {
                             tab.search_text.clear();
-                            search_cleared = true;
+                            tab.on_search_changed();
+                            self.show_find_bar = false;
+                            self.save_state();
                         }
-                    });
-                    ui.add_space(5.0);
-                }
-            }
-
-            // Handle search state changes outside the borrow
-            if search_changed || search_cleared {
-                if let Some(tab) = self.tabs.get_mut(self.active_tab) {
-                    tab.current_page = 0;
-                    tab.search_match_index = 0;
+                        None => {}
+                    }
                 }
-                self.save_state();
             }
 
-
             // Data grid with pagination
             // Extract values to avoid borrow checker issues
             let (has_data, is_loading, sort_column, sort_ascending, current_page, page_size, total_rows, search_text, search_match_index) =
                 if let Some(tab) = self.tabs.get(self.active_tab) {
                     if let Some(data) = &tab.data {
-                        (true, false, tab.sort_column, tab.sort_ascending, tab.current_page, tab.page_size, Some(data.rows.len()), tab.search_text.clone(), tab.search_match_index)
+                        // Page through the filtered rows; the grid caches this scan between frames
+                        let summary = self.data_grid.summary(data, tab.sort_column, tab.sort_ascending, tab.page_size, tab.local_filters(), &tab.search_text, tab.search_match_index);
+                        (true, false, tab.sort_column, tab.sort_ascending, tab.current_page, tab.page_size, Some(summary.filtered_rows), tab.search_text.clone(), tab.search_match_index)
                     } else {
                         (false, tab.is_loading, None, true, 0, 100, None, String::new(), 0)
                     }
@@ -710,36 +3552,262 @@ impl eframe::App for DbClientApp {
                     (false, false, None, true, 0, 100, None, String::new(), 0)
                 };
 
-            let mut total_matches = 0;
+            // Explain tabs show the plan tree instead of the grid
+            let active_plan = self.tabs.get(self.active_tab).and_then(|tab| tab.plan.as_ref().map(|plan| (tab.id, plan)));
+            if shows_structure {
+                if let Some(tab) = self.tabs.get(self.active_tab) {
+                    if let Some(StructureViewEvent::Refresh) = self.structure_view.show(ui, tab.id, tab.structure.as_ref()) {
+                        load_structure = true;
+                    }
+                }
+            } else if let Some((tab_id, plan)) = active_plan {
+                self.plan_view.show(ui, tab_id, plan);
+            } else if shows_chart {
+                // Query results plotted instead of listed, over the rows passing the filters
+                let mut chart_event = None;
+                if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                    if let Some(data) = &tab.data {
+                        let rows = filter_row_indices(&data.rows, tab.local_filters());
+                        chart_event = self.result_chart.show(ui, data, &rows, &mut tab.chart_settings);
+                    }
+                }
+                match chart_event {
+                    Some(ResultChartEvent::Changed) => self.save_state(),
+                    Some(ResultChartEvent::ExportPng) => {
+                        let title = self.tabs.get(self.active_tab).map_or_else(|| "chart".to_string(), |tab| tab.title.clone());
+                        self.file_dialog.set_path(default_file_path(&format!("{}.png", title)));
+                        self.file_action = Some(FileAction::SaveChartPng);
+                    }
+                    None => {}
+                }
+            } else if has_data {
+                // Pagination controls
+                let server_sort = self.tabs.get(self.active_tab)
+                    .filter(|tab| matches!(tab.source, TabSource::Table { .. }) && tab.distinct.is_none())
+                    .map(|tab| tab.server_side_sorting);
+                // Queries ending in LIMIT/OFFSET page on the server
+                let sql_page = self.tabs.get(self.active_tab)
+                    .and_then(|tab| tab.sql_page().zip(tab.data.as_ref().map(|data| data.rows.len())));
+                if let Some(event) = self.pagination.show(ui, current_page, page_size, total_rows.unwrap(), server_sort, sql_page) {
+                    match event {
+                        PaginationEvent::Reload => self.reload_current_tab(),
+                        PaginationEvent::Export => {
+                            if let Some(tab) = self.tabs.get(self.active_tab) {
+                                self.export_dialog.prepare(&tab.title);
+                                self.show_export_dialog = true;
+                            }
+                        }
+                        PaginationEvent::PageSizeChanged(size) => {
+                            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                                tab.set_page_size(size);
+                                self.save_state();
+                            }
+                        }
+                        PaginationEvent::PageChanged(page) => {
+                            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                                tab.current_page = page;
+                                self.save_state();
+                            }
+                        }
+                        PaginationEvent::SqlPageChanged(page) => {
+                            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                                tab.set_sql_page(page);
+                            }
+                            self.save_state();
+                            self.reload_current_tab();
+                        }
+                        PaginationEvent::ServerSortChanged(enabled) => {
+                            let mut reload = false;
+                            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                                tab.set_server_side_sorting(enabled);
+                                reload = tab.server_order().is_some();
+                            }
+                            self.save_state();
+                            if reload {
+                                self.reload_current_tab();
+                            }
+                        }
+                    }
+                }
+
+                // Distinct values of the result, collapsed client-side
+                let mut distinct_event = None;
+                if let Some(tab) = self.tabs.get(self.active_tab) {
+                    if let Some(data) = &tab.data {
+                        let unavailable = if !tab.pending_edits.is_empty() {
+                            Some("Apply or discard the staged changes first")
+                        } else if tab.tail.is_some() {
+                            Some("Stop tailing first")
+                        } else if self.streaming_tab == Some(self.active_tab) {
+                            Some("Wait for all rows to arrive")
+                        } else {
+                            None
+                        };
+                        distinct_event = self.distinct_bar.show(ui, data, &self.data_grid.selected_columns(), tab.distinct.as_ref(), unavailable);
+                    }
+                }
+                if let (Some(event), Some(tab)) = (distinct_event, self.tabs.get_mut(self.active_tab)) {
+                    match event {
+                        DistinctBarEvent::Show(columns) => tab.show_distinct(&columns),
+                        DistinctBarEvent::ShowAll => tab.show_all_rows(),
+                    }
+                    self.save_state();
+                }
+
+                // Queries to go on with, opened in the editor rather than run
+                let (follow_up_table, follow_ups) = self.follow_ups();
+                if !follow_ups.is_empty() {
+                    match self.follow_up_bar.show(ui, &follow_ups) {
+                        Some(FollowUpBarEvent::Open(index)) => {
+                            let follow_up = follow_ups[index].clone();
+                            self.open_query_tab(format!("{}: {}", follow_up_table, follow_up.label), follow_up.sql);
+                        }
+                        Some(FollowUpBarEvent::Hide) => {
+                            self.config.preferences.follow_up_suggestions = false;
+                            let _ = self.config.save();
+                            self.status_message = "Follow-up suggestions hidden, turn them back on in Settings".to_string();
+                        }
+                        None => {}
+                    }
+                }
+
+                // Live tail controls for table tabs
+                let mut tail_event = None;
+                if let Some(tab) = self.tabs.get(self.active_tab).filter(|tab| tab.distinct.is_none()) {
+                    if let (TabSource::Table { .. }, Some(data)) = (&tab.source, &tab.data) {
+                        tail_event = self.tail_bar.show(ui, &data.columns, tab.tail.as_ref());
+                    }
+                }
+                match tail_event {
+                    Some(TailBarEvent::Start(column, interval)) => {
+                        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                            if let Some(data) = &tab.data {
+                                tab.tail = Some(TailState::new(column, interval, &data.rows));
+                            }
+                        }
+                        self.status_message = "Tailing table for new rows".to_string();
+                    }
+                    Some(TailBarEvent::Stop) => {
+                        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                            tab.tail = None;
+                        }
+                        self.cache_tab_data(self.active_tab);
+                        self.status_message = "Tail stopped".to_string();
+                    }
+                    None => {}
+                }
+
+                // Staged cell edits toolbar
+                let mut edit_event = None;
+                if let Some(tab) = self.tabs.get(self.active_tab) {
+                    if let (TabSource::Table { schema, table }, Some(data)) = (&tab.source, &tab.data) {
+                        if !tab.pending_edits.is_empty() {
+                            let preview = tab.pending_edits.to_update_statements(schema, table, &data.columns)
+                                .map(|statements| statements.join(";\n"))
+                                .unwrap_or_else(|e| e.to_string());
+                            let denied = self.table_denial(schema, table, Privilege::Update);
+                            edit_event = self.edit_toolbar.show(ui, tab.pending_edits.len(), &preview, denied.as_deref());
+                        }
+                    }
+                }
+                match edit_event {
+                    Some(EditToolbarEvent::Apply) => self.apply_pending_edits(self.active_tab),
+                    Some(EditToolbarEvent::Discard) => {
+                        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                            tab.pending_edits.clear();
+                        }
+                        self.status_message = "Discarded staged changes".to_string();
+                    }
+                    None => {}
+                }
+
+                // Resource usage of the last execution, reserved below the grid
+                if let Some(report) = self.tabs.get(self.active_tab).and_then(|tab| tab.resource_report.as_ref()) {
+                    egui::TopBottomPanel::bottom("resource_report").show_inside(ui, |ui| {
+                        self.resource_report_panel.show(ui, report);
+                    });
+                }
 
-            if has_data {
-                // Pagination controls
-                if let Some(event) = self.pagination.show(ui, current_page, page_size, total_rows.unwrap()) {
-                    match event {
-                        PaginationEvent::Reload => self.reload_current_tab(),
-                        PaginationEvent::PageSizeChanged(size) => {
-                            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
-                                tab.page_size = size;
-                                tab.current_page = 0;
-                                self.save_state();
-                            }
+                // Bookmarked rows of table tabs, listed beside the grid
+                let mut bookmark_event = None;
+                if let Some(tab) = self.tabs.get(self.active_tab) {
+                    if let (TabSource::Table { .. }, Some(data)) = (&tab.source, &tab.data) {
+                        if let (false, Some(key_columns)) = (tab.bookmarks.is_empty(), PendingEdits::key_columns(&data.columns)) {
+                            egui::SidePanel::right("bookmarks_panel")
+                                .resizable(true)
+                                .default_width(220.0)
+                                .show_inside(ui, |ui| {
+                                    bookmark_event = self.bookmarks_panel.show(ui, &tab.bookmarks, &data.columns, &key_columns);
+                                });
                         }
-                        PaginationEvent::PageChanged(page) => {
-                            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
-                                tab.current_page = page;
-                                self.save_state();
-                            }
+                    }
+                }
+                match bookmark_event {
+                    Some(BookmarksPanelEvent::Jump(index)) => self.jump_to_bookmark(index),
+                    Some(BookmarksPanelEvent::Remove(index)) => {
+                        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                            tab.bookmarks.remove(index);
+                        }
+                        self.save_state();
+                    }
+                    Some(BookmarksPanelEvent::Clear) => {
+                        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                            tab.bookmarks.clear();
                         }
+                        self.save_state();
+                    }
+                    None => {}
+                }
+
+                // Chart of the result above the grid
+                let mut chart_event = None;
+                if let Some(tab) = self.tabs.get(self.active_tab).filter(|tab| tab.chart) {
+                    if let Some(series) = tab.data.as_ref().and_then(BarSeries::from_data) {
+                        chart_event = self.bar_chart.show(ui, &series);
+                    }
+                }
+                if let Some(BarChartEvent::Hide) = chart_event {
+                    if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                        tab.chart = false;
                     }
+                    self.save_state();
                 }
 
                 // Data grid
+                if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                    // Rows loaded or streamed in since the last frame get their computed values
+                    tab.sync_computed_columns();
+                }
+                let anonymization_rules = self.anonymization_rules();
                 if let Some(tab) = self.tabs.get(self.active_tab) {
                     if let Some(data) = &tab.data {
-                        let (event, match_info) = self.data_grid.show(ui, data, sort_column, sort_ascending, current_page, page_size, &tab.filters, &search_text, search_match_index);
+                        // Only table tabs with a primary key can be edited in place
+                        let (key_columns, read_only) = match &tab.source {
+                            TabSource::Table { schema, table } => (
+                                PendingEdits::key_columns(&data.columns),
+                                self.table_denial(schema, table, Privilege::Update),
+                            ),
+                            TabSource::Query { .. } | TabSource::Explain { .. } => (None, None),
+                        };
+                        if let (Some(_), Some(reason)) = (&key_columns, &read_only) {
+                            ui.label(egui::RichText::new("🔒 Read-only").color(egui::Color32::GRAY)).on_hover_text(reason);
+                        }
+                        let edit_context = key_columns.as_deref().map(|key_columns| GridEditContext {
+                            pending: &tab.pending_edits,
+                            key_columns,
+                            bookmarks: &tab.bookmarks,
+                            editable: read_only.is_none(),
+                        });
+                        let anonymizer = Anonymizer::new(&anonymization_rules, &data.columns);
+                        let filter_row = self.config.preferences.grid_filter_row.then_some(tab.filters.as_slice());
+                        let (event, match_info) = self.data_grid.show(ui, data, sort_column, sort_ascending, current_page, page_size, tab.local_filters(), &search_text, search_match_index, edit_context, anonymizer.as_ref(), &tab.frozen_columns, tab.shown_columns(), filter_row);
 
-                        // Update match info and handle navigation
-                        total_matches = match_info.total_matches;
+                        // Result shrank under the current match (reload, new filter): start over
+                        if search_match_index >= match_info.total_matches && search_match_index > 0 {
+                            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                                tab.search_match_index = 0;
+                            }
+                        }
 
                         // Auto-navigate to page containing current match
                         if let Some(page) = match_info.current_match_page {
@@ -750,25 +3818,6 @@ impl eframe::App for DbClientApp {
                             }
                         }
 
-                        // Handle navigation arrows
-                        if next_match && total_matches > 0 {
-                            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
-                                tab.search_match_index = (tab.search_match_index + 1) % total_matches;
-                                ctx.request_repaint(); // Repaint to recalculate page for new match
-                            }
-                        }
-
-                        if prev_match && total_matches > 0 {
-                            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
-                                tab.search_match_index = if tab.search_match_index == 0 {
-                                    total_matches - 1
-                                } else {
-                                    tab.search_match_index - 1
-                                };
-                                ctx.request_repaint(); // Repaint to recalculate page for new match
-                            }
-                        }
-
                         // Handle data grid events
                         if let Some(event) = event {
                             match event {
@@ -778,85 +3827,238 @@ impl eframe::App for DbClientApp {
                                 DataGridEvent::RowSelected(_) => {
                                     // Row selection handled by data_grid internally
                                 }
+                                DataGridEvent::CellEdited(row, column, value) => {
+                                    self.stage_cell_edit(self.active_tab, row, column, value);
+                                }
+                                DataGridEvent::BookmarkToggled(row) => {
+                                    self.toggle_bookmark(self.active_tab, row);
+                                }
+                                DataGridEvent::FollowForeignKey(row, column) => {
+                                    self.follow_foreign_key(self.active_tab, row, column);
+                                }
+                                DataGridEvent::CompareRows(left, right) => {
+                                    self.row_comparison = Some(RowComparison::new(left, right));
+                                }
+                                DataGridEvent::InspectCell(row, column) => {
+                                    self.cell_inspector.reset();
+                                    self.inspected_cell = Some((row, column));
+                                }
+                                DataGridEvent::SaveBytes(row, column) => self.begin_save_cell_bytes(row, column),
+                                DataGridEvent::ReplaceValues(column) => self.value_replace = Some(ValueReplace::new(column)),
+                                DataGridEvent::AddComputedColumn => self.computed_column = Some(ComputedColumn::default()),
+                                DataGridEvent::RemoveComputedColumn(column) => {
+                                    if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                                        tab.remove_computed_column(column);
+                                    }
+                                    self.save_state();
+                                }
+                                DataGridEvent::ShowDistribution(column) => self.chart_distribution(column, None),
+                                DataGridEvent::FreezeColumns(columns) => {
+                                    if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                                        tab.frozen_columns = columns;
+                                    }
+                                    self.save_state();
+                                }
+                                DataGridEvent::ColumnFilterChanged(column, text) => {
+                                    // Applied as typed, like the filter bar's rules; server-side on reload
+                                    if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                                        set_column_filter(&mut tab.filters, column, text);
+                                        tab.on_filters_changed();
+                                    }
+                                    self.save_state();
+                                }
                             }
                         }
                     }
                 }
-
-                // Show match counter if there's an active search
-                if !search_text_for_ui.is_empty() && total_matches > 0 {
-                    ui.horizontal(|ui| {
-                        ui.add_space(20.0);
-                        ui.label(
-                            egui::RichText::new(format!("Match {} of {}", current_match_idx + 1, total_matches))
-                                .size(11.0)
-                                .color(egui::Color32::from_rgb(100, 100, 255))
-                        );
-                    });
-                }
             } else if is_loading {
                 ui.centered_and_justified(|ui| {
                     ui.spinner();
                     ui.label("Loading...");
                 });
+            } else if large_table.is_some_and(|(_, loaded)| loaded.is_none()) {
+                ui.centered_and_justified(|ui| {
+                    ui.label("Rows not loaded, press Load data above");
+                });
             } else {
                 ui.centered_and_justified(|ui| {
                     ui.label("Select a table to view data");
                 });
             }
+
+            if load_structure {
+                self.load_table_structure(self.active_tab);
+            }
         });
 
-        // Request repaint if we're waiting for async operations
+        // Poll pending async operations at a modest rate instead of every frame;
+        // promises complete on another thread and cannot wake the UI themselves
         if self.pending_operation.is_some() {
-            ctx.request_repaint();
+            ctx.request_repaint_after(ASYNC_POLL_INTERVAL);
         }
     }
 }
 
 impl DbClientApp {
+    fn handle_file_action(&mut self, action: FileAction, path: PathBuf) {
+        match action {
+            FileAction::ShareQuery => self.share_query(&path),
+            FileAction::OpenSharedQuery => self.open_shared_query(&path),
+            FileAction::SaveExplainPlan => self.export_explain_plan(PlanDestination::File(path)),
+            FileAction::SaveSchemaSnapshot => self.save_schema_snapshot(&path),
+            FileAction::CompareSchemaSnapshot => self.compare_schema_snapshot(&path),
+            FileAction::SaveCellBytes(row, column) => self.save_cell_bytes(row, column, &path),
+            FileAction::SaveChartPng => self.pending_chart_png = Some((path, false)),
+            FileAction::OpenSqlFile => self.open_sql_file(path),
+            FileAction::SaveSqlFile => self.save_sql_file(path),
+        }
+    }
+
+    /// Ask where to save the binary value of a cell of the active tab.
+    fn begin_save_cell_bytes(&mut self, row: usize, column: usize) {
+        let name = self.tabs.get(self.active_tab)
+            .and_then(|tab| tab.data.as_ref())
+            .and_then(|data| data.columns.get(column))
+            .map_or_else(|| "value".to_string(), |column| column.name.clone());
+        self.file_dialog.set_path(default_file_path(&format!("{}-{}.bin", name, row + 1)));
+        self.file_action = Some(FileAction::SaveCellBytes(row, column));
+    }
+
+    /// Write the raw bytes of a binary cell of the active tab, as shared while sharing safely.
+    fn save_cell_bytes(&mut self, row: usize, column: usize, path: &Path) {
+        let Some(data) = self.tabs.get(self.active_tab).and_then(|tab| tab.data.as_ref()) else {
+            return;
+        };
+        let Some(value) = data.rows.get(row).and_then(|cells| cells.get(column)) else {
+            return;
+        };
+        let value = match Anonymizer::new(&self.anonymization_rules(), &data.columns) {
+            Some(anonymizer) => anonymizer.value(column, value),
+            None => value.clone(),
+        };
+        self.status_message = match value {
+            CellValue::Bytes(bytes) => match std::fs::write(path, &bytes) {
+                Ok(()) => format!("Saved {} to {}", format_bytes(bytes.len() as u64), path.display()),
+                Err(e) => format!("Error saving binary value: {}", e),
+            },
+            _ => "The cell no longer holds a binary value".to_string(),
+        };
+    }
+
+    /// Snapshot of the loaded schema tree of the current connection.
+    fn live_schema_snapshot(&self) -> Option<SchemaSnapshot> {
+        if self.database.is_none() || self.schemas.is_empty() {
+            return None;
+        }
+        let connection_name = self.config.get_last_connection().map(|conn| conn.name.clone());
+        Some(SchemaSnapshot::new(&self.schemas, connection_name))
+    }
+
+    fn save_schema_snapshot(&mut self, path: &Path) {
+        let Some(snapshot) = self.live_schema_snapshot() else {
+            self.status_message = "Connect to a database before taking a schema snapshot".to_string();
+            return;
+        };
+        self.status_message = match snapshot.save(path) {
+            Ok(()) => format!("Saved snapshot of {} tables to {}", snapshot.tables.len(), path.display()),
+            Err(e) => format!("Error saving schema snapshot: {}", e),
+        };
+    }
+
+    /// Diff the live schema against a saved snapshot and show what changed since.
+    fn compare_schema_snapshot(&mut self, path: &Path) {
+        let Some(live) = self.live_schema_snapshot() else {
+            self.status_message = "Connect to a database before comparing with a snapshot".to_string();
+            return;
+        };
+        let snapshot = match SchemaSnapshot::load(path) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                self.status_message = format!("Error opening schema snapshot: {}", e);
+                return;
+            }
+        };
+
+        let changes = snapshot.diff(&live);
+        let source = format!(
+            "Snapshot {} of {} taken {}",
+            path.display(),
+            snapshot.connection_name.as_deref().unwrap_or("unknown connection"),
+            snapshot.taken_at.format("%Y-%m-%d %H:%M UTC")
+        );
+        self.status_message = format!("{} schema change(s) since the snapshot", changes.len());
+        self.schema_diff = Some((source, changes));
+    }
+
     fn handle_async_operations(&mut self) {
         let mut should_clear_operation = false;
         let mut tab_to_add: Option<(String, Option<TableData>, TabSource)> = None;
+        let mut finished_query = None;
+        let mut query_to_run = None;
+        let mut transaction_open = None;
+        let mut statement_runs = None;
+        let mut source_to_open = None;
+        let mut plan_tab_to_add: Option<(String, TabSource, ExplainPlan)> = None;
         let mut new_schemas: Option<Vec<SchemaInfo>> = None;
-        let mut new_database: Option<Arc<Database>> = None;
+        let mut new_database = None;
         let mut new_status = None;
         let mut new_connection_status = None;
+        let connection_name = self.config.get_last_connection().map(|conn| conn.name.clone());
         let mut close_query_panel = false;
+        let mut go_offline = false;
 
         if let Some(operation) = &self.pending_operation {
             match operation {
                 AsyncOperation::LoadStructure(promise) => {
                     if let Some(result) = promise.ready() {
                         match result {
-                            Ok((db, schemas)) => {
+                            Ok((db, replica, schemas)) => {
                                 let total_tables: usize = schemas.iter().map(|s| s.tables.len()).sum();
                                 new_schemas = Some(schemas.clone());
-                                new_connection_status = Some(format!("Connected - {} schemas, {} tables", schemas.len(), total_tables));
+                                let replica_note = if replica.is_some() { " (+ read replica)" } else { "" };
+                                new_connection_status = Some(format!("Connected - {} schemas, {} tables{}", schemas.len(), total_tables, replica_note));
                                 new_status = Some(format!("Loaded {} schemas with {} tables", schemas.len(), total_tables));
-                                new_database = Some(Arc::clone(db));
+                                new_database = Some((Arc::clone(db), replica.clone()));
                             }
                             Err(e) => {
+                                // A mistyped password is asked for again on the next attempt
+                                if let Some(conn) = self.config.get_last_connection() {
+                                    self.session_passwords.remove(&conn.credential_ref);
+                                }
                                 new_connection_status = Some(format!("Connection failed: {}", e));
                                 new_status = Some(format!("Error: {}", e));
+                                go_offline = true;
                             }
                         }
                         should_clear_operation = true;
                     }
                 }
-                AsyncOperation::LoadTableData(schema, table_name, promise, tab_index) => {
+                AsyncOperation::LoadTableData(schema, table_name, promise, tab_index, _) => {
                     if let Some(result) = promise.ready() {
+                        let duration_ms = self.query_started_at.take()
+                            .map(|started| started.elapsed().as_millis() as u64)
+                            .unwrap_or(0);
                         match result {
-                            Ok((columns, rows)) => {
+                            Ok((columns, rows, lineage)) => {
+                                self.last_query_stats = Some(QueryStats::measure(duration_ms, rows));
                                 let data = TableData {
                                     name: format!("{}.{}", schema, table_name),
                                     columns: columns.clone(),
                                     rows: rows.clone(),
+                                    lineage: lineage.clone(),
+                                    generation: 0,
                                 };
+                                self.cache_sample(schema, table_name, &data);
 
                                 if let Some(idx) = tab_index {
                                     if let Some(tab) = self.tabs.get_mut(*idx) {
+                                        tab.show_all_rows();
                                         tab.data = Some(data);
+                                        tab.connection_name = connection_name.clone();
+                                        tab.restored = false;
+                                        tab.clamp_page();
                                     }
+                                    self.cache_tab_data(*idx);
                                     new_status = Some(format!("Reloaded {} rows from {}.{}", rows.len(), schema, table_name));
                                 } else {
                                     let source = TabSource::Table {
@@ -867,6 +4069,9 @@ impl DbClientApp {
                                     new_status = Some(format!("Loaded {} rows from {}.{}", rows.len(), schema, table_name));
                                 }
                             }
+                            Err(e) if is_cancelled(e) => {
+                                new_status = Some(format!("Loading {}.{} cancelled", schema, table_name));
+                            }
                             Err(e) => {
                                 new_status = Some(format!("Error loading table: {}", e));
                             }
@@ -874,52 +4079,435 @@ impl DbClientApp {
                         should_clear_operation = true;
                     }
                 }
-                AsyncOperation::ExecuteQuery(query, promise, tab_index) => {
+                AsyncOperation::ExecuteQuery(query, promise, tab_index, target, _) => {
+                    let target_note = if self.replica_database.is_some() {
+                        format!(" on {}", target.as_str())
+                    } else {
+                        String::new()
+                    };
                     if let Some(result) = promise.ready() {
-                        match result {
-                            Ok((columns, rows)) => {
-                                let data = TableData {
-                                    name: "Query Result".to_string(),
-                                    columns: columns.clone(),
-                                    rows: rows.clone(),
-                                };
+                        let duration_ms = self.query_started_at.take()
+                            .map(|started| started.elapsed().as_millis() as u64)
+                            .unwrap_or(0);
+                        // The promise only resolves after the last batch was pushed
+                        let (stats, affected) = self.query_stream.as_ref()
+                            .map(|stream| {
+                                let stream = stream.lock().unwrap();
+                                (QueryStats { duration_ms, rows: stream.total_rows, bytes: stream.total_bytes }, stream.affected_rows)
+                            })
+                            .unwrap_or((QueryStats { duration_ms, ..QueryStats::default() }, None));
+                        let row_count = stats.rows;
+                        let error = match result {
+                            Ok(_) => None,
+                            Err(e) if is_timeout(e) => Some("Statement timeout".to_string()),
+                            Err(e) if is_cancelled(e) => Some("Cancelled".to_string()),
+                            Err(e) => Some(e.to_string()),
+                        };
+                        let log_message = match (&error, affected) {
+                            (Some(e), _) => e.clone(),
+                            (None, Some(count)) => command_tag(query, count),
+                            (None, None) => format!("{} rows", row_count),
+                        };
+                        self.query_log.push(query, log_message.clone(), duration_ms, error.is_some());
+                        self.query_history.record(query.clone(), stats, error, connection_name.clone());
+                        let _ = self.query_history.save();
+                        self.last_query_stats = Some(stats);
 
-                                if let Some(idx) = tab_index {
-                                    if let Some(tab) = self.tabs.get_mut(*idx) {
-                                        tab.data = Some(data);
-                                    }
-                                    new_status = Some(format!("Reloaded query: {} rows", rows.len()));
+                        match result {
+                            Ok(report) => {
+                                finished_query = Some(report.clone());
+                                if affected.is_some() {
+                                    // Nothing to show in a tab; keep the editor open for the next statement
+                                    new_status = Some(format!("{} ({} ms){}", log_message, duration_ms, target_note));
+                                } else if tab_index.is_some() {
+                                    new_status = Some(format!("Reloaded query: {} rows{}", row_count, target_note));
                                 } else {
-                                    let source = TabSource::Query {
-                                        sql: query.clone(),
-                                    };
-                                    tab_to_add = Some(("Query Result".to_string(), Some(data), source));
-                                    new_status = Some(format!("Query returned {} rows", rows.len()));
+                                    new_status = Some(format!("Query returned {} rows{}", row_count, target_note));
                                     close_query_panel = true;
                                 }
                             }
+                            Err(e) if is_timeout(e) => {
+                                new_status = Some("Query cancelled: statement timeout exceeded".to_string());
+                            }
+                            Err(e) if is_cancelled(e) => {
+                                new_status = Some("Query cancelled".to_string());
+                            }
+                            Err(e) => {
+                                new_status = Some(format!("Query error{}: {}", target_note, e));
+                            }
+                        }
+                        // Rows that arrived before an error stay in the tab
+                        finished_query.get_or_insert(None);
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::ExecuteStatements(query, promise, total, _) => {
+                    if let Some(runs) = promise.ready() {
+                        let duration_ms = self.query_started_at.take()
+                            .map(|started| started.elapsed().as_millis() as u64)
+                            .unwrap_or(0);
+                        let error = runs.iter().find_map(|run| run.outcome.as_ref().err()).cloned();
+                        let mut stats = QueryStats { duration_ms, ..QueryStats::default() };
+                        for run in runs {
+                            if let Ok(StatementOutcome::Rows((_, rows))) = &run.outcome {
+                                stats.rows += rows.len();
+                                stats.bytes += rows_size(rows);
+                            }
+                            self.query_log.push(&run.sql, run.summary().result, run.duration_ms, run.outcome.is_err());
+                        }
+                        close_query_panel = error.is_none();
+                        self.query_history.record(query.clone(), stats, error, connection_name.clone());
+                        let _ = self.query_history.save();
+                        self.last_query_stats = Some(stats);
+
+                        statement_runs = Some((runs.clone(), *total));
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::RefreshWatch(promise, index, _) => {
+                    if let Some(result) = promise.ready() {
+                        if let Some(watch) = self.watches.get_mut(*index) {
+                            watch.record(result.as_ref().map(|(_, rows)| rows.as_slice()).map_err(|e| e.to_string()));
+                        }
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::TailTable(promise, tab_index, _) => {
+                    if let Some(result) = promise.ready() {
+                        match result {
+                            Ok((_, rows)) => {
+                                let is_active = *tab_index == self.active_tab;
+                                if let Some(tab) = self.tabs.get_mut(*tab_index) {
+                                    if let (Some(data), Some(tail)) = (&mut tab.data, &mut tab.tail) {
+                                        if !rows.is_empty() {
+                                            tail.record(rows);
+                                            data.rows.extend(rows.iter().cloned());
+                                            new_status = Some(format!("Tail: {} new row(s) in {}", rows.len(), tab.title));
+                                        }
+                                    }
+                                    // Follow the newest rows like `tail -f`
+                                    if is_active && !rows.is_empty() {
+                                        tab.current_page = tab.total_pages() - 1;
+                                        self.data_grid.scroll_to_bottom();
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                if let Some(tab) = self.tabs.get_mut(*tab_index) {
+                                    tab.tail = None;
+                                }
+                                new_status = Some(format!("Tail stopped: {}", e));
+                            }
+                        }
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::FollowForeignKey(promise, value) => {
+                    if let Some(result) = promise.ready() {
+                        match result {
+                            Ok(Some(target)) => {
+                                query_to_run = Some((db::sql::referenced_row_query(self.driver, target, value), None));
+                            }
+                            Ok(None) => {
+                                new_status = Some("Column has no foreign key constraint to follow".to_string());
+                            }
+                            Err(e) => {
+                                new_status = Some(format!("Error looking up foreign key: {}", e));
+                            }
+                        }
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::LoadTableStructure(promise, tab_index) => {
+                    if let Some(result) = promise.ready() {
+                        match result {
+                            Ok(structure) => {
+                                if let Some(tab) = self.tabs.get_mut(*tab_index) {
+                                    tab.structure = Some(structure.clone());
+                                    new_status = Some(format!(
+                                        "{} index(es), {} constraint(s), {} trigger(s)",
+                                        structure.indexes.len(),
+                                        structure.constraints.len(),
+                                        structure.triggers.len()
+                                    ));
+                                }
+                            }
+                            Err(e) => {
+                                new_status = Some(format!("Error loading table structure: {}", e));
+                            }
+                        }
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::Transaction(promise, control, then_run) => {
+                    if let Some(result) = promise.ready() {
+                        let began = *control == TransactionControl::Begin;
+                        match result {
+                            Ok(()) => {
+                                new_status = Some(control.done().to_string());
+                                transaction_open = Some(began);
+                                query_to_run = then_run.clone();
+                            }
+                            Err(e) => {
+                                new_status = Some(format!("{} failed: {}", control.sql(), e));
+                                // The server ends the transaction even when COMMIT fails
+                                transaction_open = Some(false);
+                            }
+                        }
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::AlterColumns(promise, table, total) => {
+                    if let Some(result) = promise.ready() {
+                        match result {
+                            Ok(schemas) => {
+                                new_schemas = Some(schemas.clone());
+                                self.alter_plan = None;
+                                new_status = Some(format!("Altered {} with {} statement(s)", table, total));
+                            }
+                            // Nothing was changed, the staged plan stays open to fix
+                            Err(e) => new_status = Some(format!("Altering {} failed, rolled back: {}", table, e)),
+                        }
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::CreateTempTable(promise, table, rows) => {
+                    if let Some(result) = promise.ready() {
+                        match result {
+                            Ok(()) => {
+                                self.show_paste_table_dialog = false;
+                                if !self.temp_tables.contains(table) {
+                                    self.temp_tables.push(table.clone());
+                                }
+                                new_status = Some(format!("Created temporary table {} with {} rows", table, rows));
+                                query_to_run = Some((format!("SELECT * FROM {}", db::sql::quote_ident_for(self.driver, table)), None));
+                            }
+                            Err(e) => new_status = Some(format!("Creating temporary table {} failed: {}", table, e)),
+                        }
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::ImportFile(promise, table, _) => {
+                    if let Some(result) = promise.ready() {
+                        match result {
+                            Ok(rows) => {
+                                self.import_plan = None;
+                                new_status = Some(format!("Imported {} rows into {}", rows, table));
+                            }
+                            Err(e) if is_cancelled(e) => new_status = Some("Import cancelled, no rows were loaded".to_string()),
+                            // The wizard stays open to fix the mapping or the file
+                            Err(e) => new_status = Some(format!("Importing into {} failed, no rows were loaded: {}", table, e)),
+                        }
+                        self.import_progress = None;
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::LoadSource(promise, title) => {
+                    if let Some(result) = promise.ready() {
+                        match result {
+                            Ok(sql) => {
+                                source_to_open = Some((title.clone(), sql.clone()));
+                                new_status = Some(format!("Opened definition of {}", title));
+                            }
+                            Err(e) => {
+                                new_status = Some(format!("Error loading definition of {}: {}", title, e));
+                            }
+                        }
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::ApplyEdits(promise, tab_index) => {
+                    if let Some(result) = promise.ready() {
+                        match result {
+                            Ok(updated) => {
+                                if let Some(tab) = self.tabs.get_mut(*tab_index) {
+                                    if let Some(data) = &mut tab.data {
+                                        if let Some(key_columns) = PendingEdits::key_columns(&data.columns) {
+                                            tab.pending_edits.apply_to_rows(&mut data.rows, &key_columns);
+                                            data.touch();
+                                        }
+                                    }
+                                    tab.pending_edits.clear();
+                                }
+                                self.cache_tab_data(*tab_index);
+                                new_status = Some(format!("Updated {} row(s)", updated));
+                            }
                             Err(e) => {
-                                new_status = Some(format!("Query error: {}", e));
+                                new_status = Some(format!("Error applying edits: {}", e));
                             }
                         }
                         should_clear_operation = true;
                     }
                 }
+                AsyncOperation::ExplainPlan(promise, destination, _) => {
+                    if let Some(result) = promise.ready() {
+                        let plan = result.as_ref().map_err(|e| anyhow::anyhow!("{}", e));
+                        new_status = Some(match destination {
+                            PlanDestination::Tab { sql, analyze, tab_index } => match plan.and_then(ExplainPlan::from_json) {
+                                Ok(plan) => {
+                                    if let Some(tab) = tab_index.and_then(|idx| self.tabs.get_mut(idx)) {
+                                        tab.plan = Some(plan);
+                                        "EXPLAIN plan reloaded".to_string()
+                                    } else {
+                                        let title = if *analyze { "Explain Analyze" } else { "Explain" };
+                                        let source = TabSource::Explain { sql: sql.clone(), analyze: *analyze };
+                                        plan_tab_to_add = Some((title.to_string(), source, plan));
+                                        "EXPLAIN plan ready".to_string()
+                                    }
+                                }
+                                Err(e) => format!("EXPLAIN error: {}", e),
+                            },
+                            PlanDestination::Clipboard => match plan.and_then(plan_for_visualizer) {
+                                Ok(text) => {
+                                    self.pending_clipboard = Some(text);
+                                    "EXPLAIN plan copied to clipboard".to_string()
+                                }
+                                Err(e) => format!("EXPLAIN error: {}", e),
+                            },
+                            PlanDestination::File(path) => match plan.and_then(plan_for_visualizer) {
+                                Ok(text) => match std::fs::write(path, text) {
+                                    Ok(()) => format!("EXPLAIN plan saved to {}", path.display()),
+                                    Err(e) => format!("Error saving plan: {}", e),
+                                },
+                                Err(e) => format!("EXPLAIN error: {}", e),
+                            },
+                        });
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::RunScript(promise, path, _) => {
+                    if let Some(result) = promise.ready() {
+                        let errors = self.script_progress.as_ref()
+                            .map(|progress| progress.lock().unwrap().error_count())
+                            .unwrap_or(0);
+                        new_status = Some(match result {
+                            Ok(count) => format!("Ran {} statement(s) from {} ({} error(s))", count, path.display(), errors),
+                            Err(e) if is_cancelled(e) => "Script cancelled".to_string(),
+                            Err(e) => format!("Script {}", e),
+                        });
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::BatchExport(promise, directory) => {
+                    if let Some(result) = promise.ready() {
+                        let (total, failed) = self.batch_progress.as_ref()
+                            .map(|progress| {
+                                let progress = progress.lock().unwrap();
+                                (progress.items.len(), progress.failed_count())
+                            })
+                            .unwrap_or((0, 0));
+                        new_status = Some(match result {
+                            Ok(count) if failed > 0 => format!(
+                                "Exported {} of {} tables to {} ({} failed)", count, total, directory.display(), failed
+                            ),
+                            Ok(count) => format!("Exported {} of {} tables to {}", count, total, directory.display()),
+                            Err(e) => format!("Export error: {}", e),
+                        });
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::SqlDump(promise, path) => {
+                    if let Some(result) = promise.ready() {
+                        let (total, failed) = self.sql_dump_progress.as_ref()
+                            .map(|progress| {
+                                let progress = progress.lock().unwrap();
+                                (progress.items.len(), progress.failed_count())
+                            })
+                            .unwrap_or((0, 0));
+                        new_status = Some(match result {
+                            Ok(count) if failed > 0 => format!(
+                                "Exported {} of {} tables as SQL to {} ({} failed)", count, total, path.display(), failed
+                            ),
+                            Ok(count) => format!("Exported {} tables as SQL to {}", count, path.display()),
+                            Err(e) => format!("SQL export error: {}", e),
+                        });
+                        should_clear_operation = true;
+                    }
+                }
+                AsyncOperation::Export(promise, path) => {
+                    if let Some(result) = promise.ready() {
+                        new_status = Some(match result {
+                            Ok(count) => format!("Exported {} rows to {}", count, path.display()),
+                            Err(e) => format!("Export error: {}", e),
+                        });
+                        should_clear_operation = true;
+                    }
+                }
             }
         }
 
         // Apply state changes
+        self.drain_query_stream();
+        if let Some(report) = finished_query {
+            if let Some(idx) = self.streaming_tab.take() {
+                if let Some(tab) = self.tabs.get_mut(idx) {
+                    tab.resource_report = report;
+                    tab.connection_name = connection_name.clone();
+                    tab.clamp_page();
+                }
+                self.cache_tab_data(idx);
+            }
+            self.query_stream = None;
+        }
         if should_clear_operation {
             self.pending_operation = None;
+            // What just ran may have opened or ended a transaction
+            self.last_transaction_poll = None;
         }
         if let Some((title, data, source)) = tab_to_add {
             self.add_tab(title, data, source);
         }
+        if let Some(open) = transaction_open {
+            self.transaction_open = open;
+        }
+        if let Some((sql, tab_index)) = query_to_run {
+            self.run_query(sql, tab_index);
+        }
+        if let Some((runs, total)) = statement_runs {
+            self.open_statement_results(runs, total);
+        }
+        if let Some((title, sql)) = source_to_open {
+            self.open_query_tab(title, sql);
+        }
+        if let Some((title, source, plan)) = plan_tab_to_add {
+            self.add_tab(title, None, source);
+            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                tab.plan = Some(plan);
+            }
+            self.save_state();
+        }
         if let Some(schemas) = new_schemas {
+            self.offline = None;
+            if let (Some(cache), Some(conn)) = (self.schema_cache.clone(), self.config.get_last_connection()) {
+                let (connection, schemas) = (conn.name.clone(), schemas.clone());
+                std::thread::spawn(move || {
+                    if let Err(e) = cache.store_schemas(&connection, &schemas) {
+                        eprintln!("Failed to cache schema: {}", e);
+                    }
+                });
+            }
             self.schemas = schemas;
         }
-        if let Some(db) = new_database {
+        if let Some((db, replica)) = new_database {
             self.database = Some(db);
+            self.replica_database = replica;
+            self.transaction_open = false;
+            self.transaction_status = None;
+            // Queued work was meant for the previous connection
+            self.operation_queue.clear();
+            self.watches = self.config.get_last_connection()
+                .map(|conn| conn.watches.iter().cloned().map(Watch::new).collect())
+                .unwrap_or_default();
+
+            // Only run the deferred tab if the user is still looking at it
+            if let Some(tab_index) = self.run_after_connect.take() {
+                if tab_index == self.active_tab {
+                    self.reload_current_tab();
+                }
+            }
+            if std::mem::take(&mut self.reload_restored_on_connect) {
+                self.reload_restored_tabs();
+            }
         }
         if let Some(status) = new_status {
             self.status_message = status;
@@ -927,8 +4515,14 @@ impl DbClientApp {
         if let Some(conn_status) = new_connection_status {
             self.connection_status = conn_status;
         }
+        if go_offline && self.go_offline() {
+            let cached_at = self.offline_since().unwrap_or_default();
+            self.connection_status = format!("📴 Offline - schema cached {}", cached_at);
+            self.status_message = format!("{}; browsing the schema cached {}, read-only", self.status_message, cached_at);
+        }
         if close_query_panel {
             self.show_query_panel = false;
         }
+        self.start_queued_operations();
     }
 }
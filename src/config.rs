@@ -1,16 +1,276 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
+use crate::credentials::{Keyring, SecretStore, StoredSecrets};
+use crate::export::{glob_matches, AnonymizeRule, ExportSettings};
+use crate::db::ColumnInfo;
+use crate::models::{FilterRule, QueryStats};
+use crate::versioned::{self, Versioned};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseConnection {
     pub name: String,
+    #[serde(default)]
+    pub driver: Driver,
     pub host: String,
     pub port: u16,
     pub user: String,
+    // Only written to config.json when the OS credential store is unavailable
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub password: String,
+    // Never store the password; it is asked for on connect
+    #[serde(default)]
+    pub ask_password: bool,
     pub database: String,
+    #[serde(default)]
+    pub replicas: Vec<ReplicaHost>,
+    #[serde(default)]
+    pub tls: TlsSettings,
+    #[serde(default)]
+    pub environment: Environment,
+    // Show a warning banner and confirm every write statement
+    #[serde(default)]
+    pub dangerous: bool,
+    #[serde(default)]
+    pub ssh: SshSettings,
+    #[serde(default)]
+    pub network: NetworkSettings,
+    // Account name of this connection's passwords in the OS credential store
+    #[serde(default)]
+    pub credential_ref: String,
+    // Applied to exports and copied cells while "share safely" is on
+    #[serde(default)]
+    pub anonymization: Vec<AnonymizeRule>,
+    // Colors and icons of schemas in the database tree
+    #[serde(default)]
+    pub schema_styles: Vec<SchemaStyle>,
+    // Scalar queries refreshed in the watch panel
+    #[serde(default)]
+    pub watches: Vec<WatchDefinition>,
+}
+
+/// Database server type, selecting the backend implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Driver {
+    #[default]
+    Postgres,
+    MySql,
+}
+
+impl Driver {
+    pub fn all() -> Vec<Driver> {
+        vec![Driver::Postgres, Driver::MySql]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Driver::Postgres => "PostgreSQL",
+            Driver::MySql => "MySQL / MariaDB",
+        }
+    }
+
+    pub fn default_port(&self) -> u16 {
+        match self {
+            Driver::Postgres => 5432,
+            Driver::MySql => 3306,
+        }
+    }
+}
+
+/// SSH bastion the database connection is tunnelled through.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SshSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    // Private key file; when empty, password or ssh-agent authentication is used
+    pub key_path: String,
+    // Account password, or the key passphrase when a key is set
+    pub password: String,
+}
+
+impl Default for SshSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 22,
+            user: String::new(),
+            key_path: String::new(),
+            password: String::new(),
+        }
+    }
+}
+
+/// TCP options of a connection, and the proxy it is reached through. Zero durations
+/// leave the driver's defaults in place.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+    // Give up on a server that has not answered after this many seconds
+    pub connect_timeout_secs: u32,
+    // Seconds a connection may sit idle before the first keepalive probe
+    pub keepalive_idle_secs: u32,
+    // Seconds between keepalive probes the server does not answer
+    pub keepalive_interval_secs: u32,
+    pub proxy: ProxySettings,
+}
+
+impl NetworkSettings {
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        seconds(self.connect_timeout_secs)
+    }
+
+    pub fn keepalive_idle(&self) -> Option<Duration> {
+        seconds(self.keepalive_idle_secs)
+    }
+
+    pub fn keepalive_interval(&self) -> Option<Duration> {
+        seconds(self.keepalive_interval_secs)
+    }
+}
+
+fn seconds(secs: u32) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs.into()))
+}
+
+/// Where the proxy a connection goes through comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ProxyMode {
+    #[default]
+    None,
+    // ALL_PROXY / HTTPS_PROXY / HTTP_PROXY, minus the hosts in NO_PROXY
+    System,
+    Custom,
+}
+
+impl ProxyMode {
+    pub fn all() -> Vec<ProxyMode> {
+        vec![ProxyMode::None, ProxyMode::System, ProxyMode::Custom]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ProxyMode::None => "No proxy",
+            ProxyMode::System => "System proxy",
+            ProxyMode::Custom => "Custom proxy",
+        }
+    }
+}
+
+/// SOCKS5 or HTTP CONNECT proxy in front of the database (or SSH) host.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxySettings {
+    pub mode: ProxyMode,
+    // socks5://host:1080 or http://host:3128, for a custom proxy
+    pub url: String,
+    pub user: String,
+    pub password: String,
+}
+
+/// Deployment environment a connection points at, used to color its tabs.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Environment {
+    #[default]
+    None,
+    Development,
+    Staging,
+    Production,
+}
+
+impl Environment {
+    pub fn all() -> Vec<Environment> {
+        vec![Environment::None, Environment::Development, Environment::Staging, Environment::Production]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Environment::None => "None",
+            Environment::Development => "Development",
+            Environment::Staging => "Staging",
+            Environment::Production => "Production",
+        }
+    }
+
+    /// RGB color for the environment, if it has one.
+    pub fn color(&self) -> Option<(u8, u8, u8)> {
+        match self {
+            Environment::None => None,
+            Environment::Development => Some((60, 160, 80)),
+            Environment::Staging => Some((230, 190, 40)),
+            Environment::Production => Some((210, 50, 50)),
+        }
+    }
+}
+
+/// How strictly to use TLS, following libpq's `sslmode` names.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum SslMode {
+    #[default]
+    Disable,
+    Prefer,
+    Require,
+    VerifyFull,
+}
+
+impl SslMode {
+    pub fn all() -> Vec<SslMode> {
+        vec![SslMode::Disable, SslMode::Prefer, SslMode::Require, SslMode::VerifyFull]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            SslMode::Disable => "disable",
+            SslMode::Prefer => "prefer",
+            SslMode::Require => "require",
+            SslMode::VerifyFull => "verify-full",
+        }
+    }
+
+    // tokio-postgres only knows disable/prefer/require; verification is done by the connector
+    fn connection_param(&self) -> &str {
+        match self {
+            SslMode::Disable => "disable",
+            SslMode::Prefer => "prefer",
+            SslMode::Require | SslMode::VerifyFull => "require",
+        }
+    }
+}
+
+/// TLS options for a connection. Empty paths mean "not set".
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsSettings {
+    pub ssl_mode: SslMode,
+    // PEM file with the CA used to verify the server certificate
+    pub ca_cert_path: String,
+    // PEM client certificate and PKCS#8 key for certificate authentication
+    pub client_cert_path: String,
+    pub client_key_path: String,
+}
+
+impl TlsSettings {
+    /// Whether the server certificate is checked against a CA: always for verify-full,
+    /// and for prefer and require once a CA certificate is set, as libpq does.
+    pub fn verifies_certificate(&self) -> bool {
+        match self.ssl_mode {
+            SslMode::Disable => false,
+            SslMode::Prefer | SslMode::Require => !self.ca_cert_path.trim().is_empty(),
+            SslMode::VerifyFull => true,
+        }
+    }
+}
+
+/// A read-only replica of a connection's primary server. Credentials and
+/// database name are shared with the primary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicaHost {
+    pub host: String,
+    pub port: u16,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,30 +281,322 @@ pub struct SavedQuery {
     pub created_at: String,
 }
 
+/// Color and icon of the schemas in the database tree whose name matches `pattern`,
+/// case-insensitive with `*` matching anything (e.g. `legacy_*`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaStyle {
+    pub pattern: String,
+    #[serde(default)]
+    pub color: Option<[u8; 3]>,
+    #[serde(default)]
+    pub icon: String,
+}
+
+impl SchemaStyle {
+    pub fn new(pattern: String) -> Self {
+        Self { pattern, color: None, icon: String::new() }
+    }
+
+    pub fn matches(&self, schema: &str) -> bool {
+        glob_matches(&self.pattern.to_lowercase(), &schema.to_lowercase())
+    }
+}
+
+/// Named filter rules for one table, re-applied from the filter bar. Rules keep the
+/// name of their column so they still fit after columns are added or reordered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedFilterSet {
+    pub name: String,
+    pub schema: String,
+    pub table: String,
+    pub filters: Vec<FilterRule>,
+    // Column name of each rule
+    pub columns: Vec<String>,
+}
+
+impl SavedFilterSet {
+    pub fn new(name: String, schema: String, table: String, filters: &[FilterRule], columns: &[ColumnInfo]) -> Self {
+        let column_names = filters.iter()
+            .map(|filter| columns.get(filter.column_index).map(|column| column.name.clone()).unwrap_or_default())
+            .collect();
+        Self { name, schema, table, filters: filters.to_vec(), columns: column_names }
+    }
+
+    /// The rules pointed at `columns`, without those whose column no longer exists.
+    pub fn rules(&self, columns: &[ColumnInfo]) -> Vec<FilterRule> {
+        self.filters.iter()
+            .zip(&self.columns)
+            .filter_map(|(filter, name)| {
+                let column_index = columns.iter().position(|column| &column.name == name)?;
+                Some(FilterRule { column_index, ..filter.clone() })
+            })
+            .collect()
+    }
+}
+
 impl DatabaseConnection {
     pub fn new() -> Self {
         Self {
             name: String::new(),
+            driver: Driver::Postgres,
             host: "localhost".to_string(),
             port: 5432,
             user: "postgres".to_string(),
             password: String::new(),
+            ask_password: false,
             database: "postgres".to_string(),
+            replicas: Vec::new(),
+            tls: TlsSettings::default(),
+            environment: Environment::None,
+            dangerous: false,
+            ssh: SshSettings::default(),
+            network: NetworkSettings::default(),
+            credential_ref: uuid::Uuid::new_v4().to_string(),
+            anonymization: Vec::new(),
+            schema_styles: Vec::new(),
+            watches: Vec::new(),
+        }
+    }
+
+    fn ssh_credential_ref(&self) -> String {
+        format!("{}:ssh", self.credential_ref)
+    }
+
+    fn proxy_credential_ref(&self) -> String {
+        format!("{}:proxy", self.credential_ref)
+    }
+
+    /// Move changed passwords into `store`, clearing them from this (on-disk) copy.
+    /// A password stays in place if the store rejects it, so nothing is lost.
+    fn store_secrets(&mut self, store: &dyn SecretStore, stored: &StoredSecrets) {
+        if self.ask_password {
+            self.password.clear();
+        }
+        let (ssh_ref, proxy_ref) = (self.ssh_credential_ref(), self.proxy_credential_ref());
+        let secrets = [
+            (self.credential_ref.clone(), &mut self.password),
+            (ssh_ref, &mut self.ssh.password),
+            (proxy_ref, &mut self.network.proxy.password),
+        ];
+        for (account, secret) in secrets {
+            if stored.save(store, &account, secret) {
+                secret.clear();
+            }
         }
     }
 
+    /// Fill in passwords from `store`; one that cannot be read stays empty. Returns true
+    /// when the connection still needs migrating (no credential reference yet, or a
+    /// plaintext password from an old config).
+    fn load_secrets(&mut self, store: &dyn SecretStore, stored: &StoredSecrets) -> bool {
+        let mut needs_migration = false;
+        if self.credential_ref.is_empty() {
+            self.credential_ref = uuid::Uuid::new_v4().to_string();
+            needs_migration = true;
+        }
+
+        if self.ask_password {
+            self.password.clear();
+        } else if self.password.is_empty() {
+            self.password = stored.load(store, &self.credential_ref).unwrap_or_default();
+        } else {
+            needs_migration = true;
+        }
+        if self.ssh.password.is_empty() {
+            self.ssh.password = stored.load(store, &self.ssh_credential_ref()).unwrap_or_default();
+        } else {
+            needs_migration = true;
+        }
+        if self.network.proxy.password.is_empty() {
+            self.network.proxy.password = stored.load(store, &self.proxy_credential_ref()).unwrap_or_default();
+        } else {
+            needs_migration = true;
+        }
+
+        needs_migration
+    }
+
+    /// Copy of the connection with `password` filled in, for one that asks for it.
+    pub fn with_password(&self, password: &str) -> DatabaseConnection {
+        DatabaseConnection { password: password.to_string(), ..self.clone() }
+    }
+
     pub fn to_connection_string(&self) -> String {
         format!(
-            "host={} port={} user={} password={} dbname={}",
-            self.host, self.port, self.user, self.password, self.database
+            "host={} port={} user={} password={} dbname={} sslmode={}",
+            self.host, self.port, self.user, self.password, self.database, self.tls.ssl_mode.connection_param()
         )
     }
+
+    /// Give `schema` its own style ahead of any pattern, or drop it with None.
+    pub fn set_schema_style(&mut self, schema: &str, style: Option<SchemaStyle>) {
+        self.schema_styles.retain(|existing| existing.pattern != schema);
+        if let Some(style) = style {
+            self.schema_styles.insert(0, SchemaStyle { pattern: schema.to_string(), ..style });
+        }
+    }
+
+    /// Human readable description of the connection that never includes the password.
+    pub fn describe(&self) -> String {
+        format!("{} ({}@{}:{}/{})", self.name, self.user, self.host, self.port, self.database)
+    }
+
+    pub fn replica_connection_strings(&self) -> Vec<String> {
+        self.replicas
+            .iter()
+            .map(|replica| {
+                format!(
+                    "host={} port={} user={} password={} dbname={} sslmode={}",
+                    replica.host, replica.port, self.user, self.password, self.database, self.tls.ssl_mode.connection_param()
+                )
+            })
+            .collect()
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WatermarkPosition {
+    Header,
+    Footer,
+}
+
+impl WatermarkPosition {
+    pub fn as_str(&self) -> &str {
+        match self {
+            WatermarkPosition::Header => "Header",
+            WatermarkPosition::Footer => "Footer",
+        }
+    }
+}
+
+/// What double-clicking a table or view in the database tree does. A single click
+/// only selects it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TableAction {
+    OpenData,
+    OpenStructure,
+    InsertName,
+}
+
+impl TableAction {
+    pub fn all() -> Vec<TableAction> {
+        vec![TableAction::OpenData, TableAction::OpenStructure, TableAction::InsertName]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            TableAction::OpenData => "Open data",
+            TableAction::OpenStructure => "Open structure",
+            TableAction::InsertName => "Insert name into the query editor",
+        }
+    }
+}
+
+/// Application-wide preferences, edited in the Settings dialog.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    // Stamp exports and copied SQL with their source connection, query and time
+    pub watermark_enabled: bool,
+    pub watermark_position: WatermarkPosition,
+    // Starting points for new queries on a table; the first one has a shortcut
+    pub query_templates: Vec<QueryTemplate>,
+    pub table_double_click: TableAction,
+    // Tables estimated to hold more rows open on their structure, waiting for Load Data;
+    // None loads every table right away
+    pub structure_first_rows: Option<u64>,
+    // Rows Load Data fetches from such a table
+    pub preview_rows: usize,
+    // Expanded by the query editor's autocomplete
+    pub snippets: Vec<Snippet>,
+    // Offer queries to run next above a loaded table or query result
+    pub follow_up_suggestions: bool,
+    // A Contains filter box per column under the grid's header
+    pub grid_filter_row: bool,
+}
+
+impl Preferences {
+    /// Whether a table of about `estimated_rows` opens on its structure instead of loading.
+    pub fn opens_structure_first(&self, estimated_rows: Option<u64>) -> bool {
+        matches!((self.structure_first_rows, estimated_rows), (Some(threshold), Some(rows)) if rows > threshold)
+    }
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            watermark_enabled: false,
+            watermark_position: WatermarkPosition::Header,
+            query_templates: vec![QueryTemplate {
+                name: "Select rows".to_string(),
+                sql: "SELECT {columns}\nFROM {table}\nLIMIT 100".to_string(),
+            }],
+            table_double_click: TableAction::OpenData,
+            structure_first_rows: Some(1_000_000),
+            preview_rows: 1000,
+            snippets: vec![
+                Snippet { name: "sel".to_string(), body: "SELECT * FROM $1 LIMIT 100".to_string() },
+                Snippet { name: "cnt".to_string(), body: "SELECT count(*) FROM $1".to_string() },
+                Snippet { name: "selw".to_string(), body: "SELECT * FROM $1 WHERE $2 LIMIT 100".to_string() },
+            ],
+            follow_up_suggestions: true,
+            grid_filter_row: false,
+        }
+    }
+}
+
+/// SQL scaffold for a new query on a table. Placeholders are filled in by
+/// [`crate::db::sql::render_template`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryTemplate {
+    pub name: String,
+    pub sql: String,
+}
+
+/// SQL the query editor offers to expand `name` into. `$1`, `$2`, … (or `${1:default}`
+/// with text to start from) mark the places the cursor stops at, in order, on Tab.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub name: String,
+    pub body: String,
+}
+
+/// Named scalar query shown in the watch panel, re-run every `interval_secs`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchDefinition {
+    pub name: String,
+    pub sql: String,
+    pub interval_secs: u64,
+}
+
+/// Named set of export formatting choices.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportPreset {
+    pub name: String,
+    #[serde(flatten)]
+    pub settings: ExportSettings,
+}
+
+// Files listed under File → Open Recent SQL File
+const MAX_RECENT_SQL_FILES: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub connections: Vec<DatabaseConnection>,
     pub last_connection_index: Option<usize>,
+    #[serde(default)]
+    pub preferences: Preferences,
+    #[serde(default)]
+    pub export_presets: Vec<ExportPreset>,
+    // Most recently opened or saved first
+    #[serde(default)]
+    pub recent_sql_files: Vec<PathBuf>,
+    #[serde(skip)]
+    stored_secrets: StoredSecrets,
+}
+
+impl Versioned for Config {
+    const VERSION: u32 = 1;
 }
 
 impl Config {
@@ -52,32 +604,44 @@ impl Config {
         Self {
             connections: vec![],
             last_connection_index: None,
+            preferences: Preferences::default(),
+            export_presets: vec![],
+            recent_sql_files: vec![],
+            stored_secrets: StoredSecrets::default(),
         }
     }
 
     pub fn load() -> Result<Self> {
-        let config_path = Self::get_config_path()?;
+        let Some(mut config) = versioned::read::<Config>(&Self::get_config_path()?)? else {
+            return Ok(Self::new());
+        };
 
-        if config_path.exists() {
-            let content = fs::read_to_string(&config_path)?;
-            let config: Config = serde_json::from_str(&content)?;
-            Ok(config)
-        } else {
-            Ok(Self::new())
+        // Older configs kept passwords in plaintext: move them to the credential store
+        if config.load_secrets(&Keyring) {
+            config.save()?;
         }
+        Ok(config)
     }
 
-    pub fn save(&self) -> Result<()> {
-        let config_path = Self::get_config_path()?;
+    fn load_secrets(&mut self, store: &dyn SecretStore) -> bool {
+        // Every connection has to be loaded, so collect before checking
+        let migrations: Vec<bool> = self.connections.iter_mut()
+            .map(|conn| conn.load_secrets(store, &self.stored_secrets))
+            .collect();
+        migrations.contains(&true)
+    }
 
-        // Create parent directory if it doesn't exist
-        if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)?;
+    // The copy that goes to disk, with passwords moved to `store`
+    fn without_secrets(&self, store: &dyn SecretStore) -> Config {
+        let mut on_disk = self.clone();
+        for conn in &mut on_disk.connections {
+            conn.store_secrets(store, &self.stored_secrets);
         }
+        on_disk
+    }
 
-        let content = serde_json::to_string_pretty(self)?;
-        fs::write(&config_path, content)?;
-        Ok(())
+    pub fn save(&self) -> Result<()> {
+        versioned::write(&Self::get_config_path()?, &self.without_secrets(&Keyring))
     }
 
     fn get_config_path() -> Result<PathBuf> {
@@ -98,7 +662,10 @@ impl Config {
 
     pub fn delete_connection(&mut self, index: usize) {
         if index < self.connections.len() {
-            self.connections.remove(index);
+            let removed = self.connections.remove(index);
+            Keyring.delete(&removed.credential_ref);
+            Keyring.delete(&removed.ssh_credential_ref());
+            Keyring.delete(&removed.proxy_credential_ref());
 
             // Update last_connection_index if needed
             if let Some(last_idx) = self.last_connection_index {
@@ -111,6 +678,25 @@ impl Config {
         }
     }
 
+    /// Add a preset, replacing any existing preset with the same name.
+    pub fn save_export_preset(&mut self, preset: ExportPreset) {
+        match self.export_presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.export_presets.push(preset),
+        }
+    }
+
+    pub fn delete_export_preset(&mut self, name: &str) {
+        self.export_presets.retain(|p| p.name != name);
+    }
+
+    /// Move `path` to the front of the recent .sql files, keeping the most recent few.
+    pub fn remember_sql_file(&mut self, path: PathBuf) {
+        self.recent_sql_files.retain(|recent| recent != &path);
+        self.recent_sql_files.insert(0, path);
+        self.recent_sql_files.truncate(MAX_RECENT_SQL_FILES);
+    }
+
     pub fn get_connection(&self, index: usize) -> Option<&DatabaseConnection> {
         self.connections.get(index)
     }
@@ -119,43 +705,38 @@ impl Config {
         self.last_connection_index
             .and_then(|idx| self.connections.get(idx))
     }
+
+    pub fn get_last_connection_mut(&mut self) -> Option<&mut DatabaseConnection> {
+        self.last_connection_index
+            .and_then(|idx| self.connections.get_mut(idx))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SavedQueries {
     pub queries: Vec<SavedQuery>,
+    #[serde(default)]
+    pub filter_sets: Vec<SavedFilterSet>,
+}
+
+impl Versioned for SavedQueries {
+    const VERSION: u32 = 1;
 }
 
 impl SavedQueries {
     pub fn new() -> Self {
         Self {
             queries: vec![],
+            filter_sets: vec![],
         }
     }
 
     pub fn load() -> Result<Self> {
-        let queries_path = Self::get_queries_path()?;
-
-        if queries_path.exists() {
-            let content = fs::read_to_string(&queries_path)?;
-            let queries: SavedQueries = serde_json::from_str(&content)?;
-            Ok(queries)
-        } else {
-            Ok(Self::new())
-        }
+        Ok(versioned::read(&Self::get_queries_path()?)?.unwrap_or_else(Self::new))
     }
 
     pub fn save(&self) -> Result<()> {
-        let queries_path = Self::get_queries_path()?;
-
-        // Create parent directory if it doesn't exist
-        if let Some(parent) = queries_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
-        let content = serde_json::to_string_pretty(self)?;
-        fs::write(&queries_path, content)?;
-        Ok(())
+        versioned::write(&Self::get_queries_path()?, self)
     }
 
     fn get_queries_path() -> Result<PathBuf> {
@@ -182,4 +763,329 @@ impl SavedQueries {
     pub fn get_query(&self, index: usize) -> Option<&SavedQuery> {
         self.queries.get(index)
     }
+
+    pub fn filter_sets_for(&self, schema: &str, table: &str) -> Vec<&SavedFilterSet> {
+        self.filter_sets.iter().filter(|set| set.schema == schema && set.table == table).collect()
+    }
+
+    pub fn filter_set(&self, schema: &str, table: &str, name: &str) -> Option<&SavedFilterSet> {
+        self.filter_sets_for(schema, table).into_iter().find(|set| set.name == name)
+    }
+
+    /// Add a filter set, replacing the table's set of the same name.
+    pub fn save_filter_set(&mut self, set: SavedFilterSet) {
+        self.delete_filter_set(&set.schema, &set.table, &set.name);
+        self.filter_sets.push(set);
+    }
+
+    pub fn delete_filter_set(&mut self, schema: &str, table: &str, name: &str) {
+        self.filter_sets.retain(|set| !(set.schema == schema && set.table == table && set.name == name));
+    }
+}
+
+// Oldest entries are dropped beyond this many
+const MAX_HISTORY_ENTRIES: usize = 1000;
+
+/// One executed statement in the query history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub sql: String,
+    pub executed_at: String,
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub row_count: Option<usize>,
+    // Approximate size of the rows fetched
+    #[serde(default)]
+    pub bytes: Option<u64>,
+    // Set when the statement failed or was cancelled
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub connection_name: Option<String>,
+}
+
+impl HistoryEntry {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct QueryHistory {
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl Versioned for QueryHistory {
+    const VERSION: u32 = 1;
+}
+
+impl QueryHistory {
+    pub fn load() -> Result<Self> {
+        Ok(versioned::read(&Self::get_history_path()?)?.unwrap_or_default())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        versioned::write(&Self::get_history_path()?, self)
+    }
+
+    fn get_history_path() -> Result<PathBuf> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
+        Ok(home.join(".config").join("db-client").join("history.json"))
+    }
+
+    /// Append an execution, stamped with the current time. Failed executions only
+    /// keep their duration.
+    pub fn record(&mut self, sql: String, stats: QueryStats, error: Option<String>, connection_name: Option<String>) {
+        let succeeded = error.is_none();
+        self.entries.push(HistoryEntry {
+            sql,
+            executed_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            duration_ms: stats.duration_ms,
+            row_count: succeeded.then_some(stats.rows),
+            bytes: succeeded.then_some(stats.bytes),
+            error,
+            connection_name,
+        });
+
+        if self.entries.len() > MAX_HISTORY_ENTRIES {
+            let excess = self.entries.len() - MAX_HISTORY_ENTRIES;
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// Indices of entries whose SQL contains `text` (case-insensitive), newest first.
+    pub fn search(&self, text: &str) -> Vec<usize> {
+        let needle = text.trim().to_lowercase();
+        (0..self.entries.len())
+            .rev()
+            .filter(|&idx| needle.is_empty() || self.entries[idx].sql.to_lowercase().contains(&needle))
+            .collect()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&HistoryEntry> {
+        self.entries.get(index)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::testing::MemoryStore;
+
+    fn config_with_password(password: &str) -> Config {
+        let mut conn = DatabaseConnection::new();
+        conn.name = "local".to_string();
+        conn.password = password.to_string();
+        Config {
+            connections: vec![conn],
+            last_connection_index: Some(0),
+            preferences: Preferences::default(),
+            export_presets: vec![],
+            recent_sql_files: vec![],
+            stored_secrets: StoredSecrets::default(),
+        }
+    }
+
+    #[test]
+    fn test_save_moves_password_to_store() {
+        let store = MemoryStore::default();
+        let mut config = config_with_password("secret");
+        config.connections[0].network.proxy.password = "proxy-secret".to_string();
+        let on_disk = config.without_secrets(&store);
+
+        let json = serde_json::to_string(&on_disk).unwrap();
+        assert!(!json.contains("secret"));
+        assert_eq!(store.get(&config.connections[0].credential_ref).unwrap().as_deref(), Some("secret"));
+        assert_eq!(store.get(&config.connections[0].proxy_credential_ref()).unwrap().as_deref(), Some("proxy-secret"));
+        // The in-memory config keeps working
+        assert_eq!(config.connections[0].password, "secret");
+    }
+
+    #[test]
+    fn test_password_kept_when_store_unavailable() {
+        let store = MemoryStore { failing: true, ..MemoryStore::default() };
+        let on_disk = config_with_password("secret").without_secrets(&store);
+        assert_eq!(on_disk.connections[0].password, "secret");
+    }
+
+    #[test]
+    fn test_asked_password_is_never_stored() {
+        let store = MemoryStore::default();
+        let mut config = config_with_password("secret");
+        config.without_secrets(&store);
+        config.connections[0].ask_password = true;
+
+        let json = serde_json::to_string(&config.without_secrets(&store)).unwrap();
+        assert!(!json.contains("secret"));
+        assert!(store.get(&config.connections[0].credential_ref).unwrap().is_none());
+
+        let mut reloaded: Config = serde_json::from_str(&json).unwrap();
+        assert!(!reloaded.load_secrets(&store));
+        assert!(reloaded.connections[0].password.is_empty());
+        assert!(reloaded.connections[0].with_password("typed").to_connection_string().contains("password=typed"));
+    }
+
+    #[test]
+    fn test_load_migrates_plaintext_config() {
+        let store = MemoryStore::default();
+        let json = r#"{"connections":[{"name":"old","host":"h","port":5432,"user":"u","password":"pw","database":"d"}],"last_connection_index":0}"#;
+        let mut config: Config = serde_json::from_str(json).unwrap();
+
+        assert!(config.load_secrets(&store));
+        assert!(!config.connections[0].credential_ref.is_empty());
+
+        // After saving, the next load reads the password back from the store
+        let mut reloaded: Config = serde_json::from_str(&serde_json::to_string(&config.without_secrets(&store)).unwrap()).unwrap();
+        assert!(reloaded.connections[0].password.is_empty());
+        assert!(!reloaded.load_secrets(&store));
+        assert_eq!(reloaded.connections[0].password, "pw");
+    }
+
+    #[test]
+    fn test_unreadable_store_keeps_secrets() {
+        let store = MemoryStore::default();
+        let json = serde_json::to_string(&config_with_password("secret").without_secrets(&store)).unwrap();
+        assert_eq!(store.writes.get(), 1);
+
+        // A locked keychain leaves the password empty, and saving must not delete it
+        let locked = MemoryStore { entries: store.entries.clone(), unreadable: true, ..MemoryStore::default() };
+        let mut reloaded: Config = serde_json::from_str(&json).unwrap();
+        reloaded.load_secrets(&locked);
+        assert!(reloaded.connections[0].password.is_empty());
+        reloaded.without_secrets(&locked);
+        assert_eq!(locked.entries.borrow().get(&reloaded.connections[0].credential_ref).map(String::as_str), Some("secret"));
+
+        // Unchanged secrets are not written again, cleared ones are deleted
+        let mut reloaded: Config = serde_json::from_str(&json).unwrap();
+        reloaded.load_secrets(&store);
+        reloaded.without_secrets(&store);
+        assert_eq!(store.writes.get(), 1);
+        reloaded.connections[0].password.clear();
+        reloaded.without_secrets(&store);
+        assert!(store.get(&reloaded.connections[0].credential_ref).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_schema_styles() {
+        let icon = |conn: &DatabaseConnection, schema: &str| {
+            conn.schema_styles.iter().find(|style| style.matches(schema)).map(|style| style.icon.clone())
+        };
+        let mut conn = DatabaseConnection::new();
+        conn.schema_styles.push(SchemaStyle { pattern: "legacy_*".to_string(), color: Some([200, 80, 80]), icon: "⚠".to_string() });
+        assert_eq!(icon(&conn, "Legacy_Billing").as_deref(), Some("⚠"));
+        assert!(icon(&conn, "public").is_none());
+
+        // Own style wins over the pattern, and clearing it falls back to the pattern
+        let mut own = SchemaStyle::new(String::new());
+        own.icon = "⭐".to_string();
+        conn.set_schema_style("legacy_billing", Some(own.clone()));
+        conn.set_schema_style("legacy_billing", Some(own));
+        assert_eq!(conn.schema_styles.len(), 2);
+        assert_eq!(icon(&conn, "legacy_billing").as_deref(), Some("⭐"));
+        conn.set_schema_style("legacy_billing", None);
+        assert_eq!(icon(&conn, "legacy_billing").as_deref(), Some("⚠"));
+    }
+
+    #[test]
+    fn test_filter_sets() {
+        let mut filter = FilterRule::new(1);
+        filter.value = "alice".to_string();
+        let set = SavedFilterSet::new("alices".to_string(), "public".to_string(), "users".to_string(), &[filter], &[ColumnInfo::test("id", "text"), ColumnInfo::test("name", "text")]);
+
+        let mut saved = SavedQueries::new();
+        saved.save_filter_set(set.clone());
+        saved.save_filter_set(set);
+        assert_eq!(saved.filter_sets_for("public", "users").len(), 1);
+        assert!(saved.filter_sets_for("public", "orders").is_empty());
+
+        // Columns are found by name after the table changed
+        let set = saved.filter_set("public", "users", "alices").unwrap();
+        assert_eq!(set.rules(&[ColumnInfo::test("email", "text"), ColumnInfo::test("name", "text")])[0].column_index, 1);
+        assert!(set.rules(&[ColumnInfo::test("id", "text")]).is_empty());
+
+        saved.delete_filter_set("public", "users", "alices");
+        assert!(saved.filter_sets.is_empty());
+    }
+
+    #[test]
+    fn test_history_search_newest_first() {
+        let mut history = QueryHistory::default();
+        let stats = |duration_ms, rows| QueryStats { duration_ms, rows, bytes: 64 };
+        history.record("SELECT * FROM users".to_string(), stats(5, 3), None, None);
+        history.record("DELETE FROM logs".to_string(), stats(1, 0), Some("permission denied".to_string()), None);
+        history.record("select id from Users".to_string(), stats(2, 1), None, Some("local".to_string()));
+
+        assert_eq!(history.search("users"), vec![2, 0]);
+        assert_eq!(history.search(""), vec![2, 1, 0]);
+        assert!(!history.get(1).unwrap().is_success());
+        assert_eq!(history.get(0).unwrap().row_count, Some(3));
+        assert_eq!((history.get(1).unwrap().row_count, history.get(1).unwrap().bytes), (None, None));
+        assert_eq!(history.get(2).unwrap().bytes, Some(64));
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let mut history = QueryHistory::default();
+        for i in 0..MAX_HISTORY_ENTRIES + 5 {
+            history.record(format!("SELECT {}", i), QueryStats::default(), None, None);
+        }
+        assert_eq!(history.entries.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(history.entries[0].sql, "SELECT 5");
+    }
+
+    #[test]
+    fn test_export_preset_replaced_by_name() {
+        let mut config = Config::new();
+        let preset = |null_value: &str| {
+            let mut settings = ExportSettings::default();
+            settings.delimited.null_value = null_value.to_string();
+            ExportPreset { name: "Excel".to_string(), settings }
+        };
+        config.save_export_preset(preset("NULL"));
+        config.save_export_preset(preset("\\N"));
+
+        assert_eq!(config.export_presets, vec![preset("\\N")]);
+
+        let json = serde_json::to_string(&config).unwrap();
+        let reloaded: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded.export_presets, config.export_presets);
+
+        config.delete_export_preset("Excel");
+        assert!(config.export_presets.is_empty());
+    }
+
+    #[test]
+    fn test_remember_sql_file() {
+        let mut config = Config::new();
+        for i in 0..MAX_RECENT_SQL_FILES + 2 {
+            config.remember_sql_file(PathBuf::from(format!("{}.sql", i)));
+        }
+        config.remember_sql_file(PathBuf::from("5.sql"));
+
+        assert_eq!(config.recent_sql_files.len(), MAX_RECENT_SQL_FILES);
+        assert_eq!(config.recent_sql_files[0], PathBuf::from("5.sql"));
+        assert_eq!(config.recent_sql_files[1], PathBuf::from(format!("{}.sql", MAX_RECENT_SQL_FILES + 1)));
+        assert_eq!(config.recent_sql_files.iter().filter(|path| path.as_os_str() == "5.sql").count(), 1);
+    }
+
+    #[test]
+    fn test_opens_structure_first() {
+        let mut preferences = Preferences::default();
+        assert!(preferences.opens_structure_first(Some(2_000_000)));
+        assert!(!preferences.opens_structure_first(Some(1_000_000)));
+        // Tables without statistics load as usual
+        assert!(!preferences.opens_structure_first(None));
+
+        preferences.structure_first_rows = None;
+        assert!(!preferences.opens_structure_first(Some(u64::MAX)));
+
+        // Settings saved before the threshold existed get the default
+        let preferences: Preferences = serde_json::from_str(r#"{"table_double_click": "OpenData"}"#).unwrap();
+        assert_eq!((preferences.structure_first_rows, preferences.preview_rows), (Some(1_000_000), 1000));
+    }
 }
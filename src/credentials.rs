@@ -0,0 +1,120 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// Service name the OS credential store files our entries under
+const KEYRING_SERVICE: &str = "db-client";
+
+/// Where connection passwords are kept outside config.json.
+pub trait SecretStore {
+    fn set(&self, account: &str, secret: &str) -> Result<()>;
+    // None when there is no entry, an error when the store could not be read
+    fn get(&self, account: &str) -> Result<Option<String>>;
+    fn delete(&self, account: &str);
+}
+
+/// macOS Keychain / Secret Service / Windows Credential Manager via the `keyring` crate.
+pub struct Keyring;
+
+impl SecretStore for Keyring {
+    fn set(&self, account: &str, secret: &str) -> Result<()> {
+        keyring::Entry::new(KEYRING_SERVICE, account)?.set_password(secret)?;
+        Ok(())
+    }
+
+    fn get(&self, account: &str) -> Result<Option<String>> {
+        match keyring::Entry::new(KEYRING_SERVICE, account)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn delete(&self, account: &str) {
+        if let Ok(entry) = keyring::Entry::new(KEYRING_SERVICE, account) {
+            let _ = entry.delete_credential();
+        }
+    }
+}
+
+/// What the store is known to hold for each account, as last read or written, shared
+/// by every copy of the config. Accounts missing here could not be read, so saving
+/// never deletes them.
+#[derive(Debug, Clone, Default)]
+pub struct StoredSecrets(Arc<Mutex<HashMap<String, String>>>);
+
+impl StoredSecrets {
+    /// The secret of `account`, empty when there is none. None when the store could
+    /// not be read (e.g. a locked keychain).
+    pub fn load(&self, store: &dyn SecretStore, account: &str) -> Option<String> {
+        match store.get(account) {
+            Ok(secret) => {
+                let secret = secret.unwrap_or_default();
+                self.0.lock().unwrap().insert(account.to_string(), secret.clone());
+                Some(secret)
+            }
+            Err(e) => {
+                eprintln!("Could not read password from the OS credential store: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Bring `account` in line with `secret`, writing only when it changed and deleting
+    /// only a secret known to be stored that was cleared. Returns true when the store
+    /// holds `secret`, so it can be left out of config.json.
+    pub fn save(&self, store: &dyn SecretStore, account: &str, secret: &str) -> bool {
+        let mut known = self.0.lock().unwrap();
+        match known.get(account) {
+            Some(stored) if stored == secret => return true,
+            Some(_) if secret.is_empty() => store.delete(account),
+            None if secret.is_empty() => return true,
+            _ => {
+                if let Err(e) = store.set(account, secret) {
+                    eprintln!("Could not store password in the OS credential store: {}", e);
+                    return false;
+                }
+            }
+        }
+        known.insert(account.to_string(), secret.to_string());
+        true
+    }
+}
+
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    /// In-memory store for tests; `failing` simulates a machine without a keychain and
+    /// `unreadable` one whose keychain is locked.
+    #[derive(Default)]
+    pub struct MemoryStore {
+        pub entries: RefCell<HashMap<String, String>>,
+        pub failing: bool,
+        pub unreadable: bool,
+        pub writes: Cell<usize>,
+    }
+
+    impl SecretStore for MemoryStore {
+        fn set(&self, account: &str, secret: &str) -> Result<()> {
+            if self.failing {
+                anyhow::bail!("no keychain available");
+            }
+            self.writes.set(self.writes.get() + 1);
+            self.entries.borrow_mut().insert(account.to_string(), secret.to_string());
+            Ok(())
+        }
+
+        fn get(&self, account: &str) -> Result<Option<String>> {
+            if self.unreadable {
+                anyhow::bail!("keychain is locked");
+            }
+            Ok(self.entries.borrow().get(account).cloned())
+        }
+
+        fn delete(&self, account: &str) {
+            self.entries.borrow_mut().remove(account);
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::fmt;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_postgres::CancelToken;
+use crate::config::{Driver, NetworkSettings, SshSettings, TlsSettings};
+use crate::models::CellValue;
+use crate::db::sql::{TableFilter, TableOrder};
+use crate::db::{ColumnInfo, ColumnLineage, ForeignKeyTarget, QueryStream, ResourceReport, SchemaInfo, SourceObject, StatementTimeout, TableDefinition, TableStructure};
+use super::client::PostgresDatabase;
+use super::mysql::MySqlDatabase;
+
+/// Rows of a result set with their column descriptions. Rows hold typed `CellValue`s,
+/// with NULL kept distinct from text.
+pub type ResultSet = (Vec<ColumnInfo>, Vec<Vec<CellValue>>);
+
+/// What running a single statement produced.
+#[derive(Debug, Clone)]
+pub enum StatementOutcome {
+    // Anything returning a result set, even an empty one (SELECT, ... RETURNING)
+    Rows(ResultSet),
+    // Rows inserted, updated or deleted; 0 for DDL
+    Affected(u64),
+}
+
+/// Statement controlling the query panel's manual transaction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransactionControl {
+    Begin,
+    Commit,
+    Rollback,
+}
+
+impl TransactionControl {
+    pub fn sql(&self) -> &str {
+        match self {
+            TransactionControl::Begin => "START TRANSACTION",
+            TransactionControl::Commit => "COMMIT",
+            TransactionControl::Rollback => "ROLLBACK",
+        }
+    }
+
+    /// Past tense for status messages.
+    pub fn done(&self) -> &str {
+        match self {
+            TransactionControl::Begin => "Transaction started",
+            TransactionControl::Commit => "Transaction committed",
+            TransactionControl::Rollback => "Transaction rolled back",
+        }
+    }
+}
+
+/// Transaction state of a connection as the server reports it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransactionStatus {
+    Idle,
+    // Open for this long, whether started from the query panel or by a typed BEGIN
+    InTransaction(Duration),
+    // A statement failed inside the transaction; the server only accepts ROLLBACK
+    Aborted,
+}
+
+impl TransactionStatus {
+    pub fn is_open(&self) -> bool {
+        *self != TransactionStatus::Idle
+    }
+}
+
+impl fmt::Display for TransactionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionStatus::Idle => f.write_str("idle"),
+            TransactionStatus::InTransaction(age) => {
+                let seconds = age.as_secs();
+                if seconds < 60 {
+                    write!(f, "idle in transaction ({}s)", seconds)
+                } else if seconds < 3600 {
+                    write!(f, "idle in transaction ({}m {:02}s)", seconds / 60, seconds % 60)
+                } else {
+                    write!(f, "idle in transaction ({}h {:02}m)", seconds / 3600, seconds / 60 % 60)
+                }
+            }
+            TransactionStatus::Aborted => f.write_str("transaction aborted"),
+        }
+    }
+}
+
+/// Statements that open, commit and roll back a unit of work: a transaction of its own,
+/// or a savepoint when the manual transaction is open, so it is not committed early.
+pub(crate) fn unit_of_work(in_transaction: bool) -> [&'static str; 3] {
+    if in_transaction {
+        ["SAVEPOINT unit_of_work", "RELEASE SAVEPOINT unit_of_work", "ROLLBACK TO SAVEPOINT unit_of_work"]
+    } else {
+        ["START TRANSACTION", "COMMIT", "ROLLBACK"]
+    }
+}
+
+/// Everything the UI needs from a database server. One implementation per driver.
+#[async_trait]
+pub trait DatabaseBackend: Send + Sync {
+    /// Handle that can abort whatever statement this connection is running.
+    fn cancel_handle(&self) -> CancelHandle;
+
+    async fn list_schemas_with_tables(&self) -> Result<Vec<SchemaInfo>>;
+
+    /// Up to `limit` rows of a table, only those meeting the condition `filter` and in
+    /// the order `order` if given.
+    async fn query_table(&self, table_name: &str, filter: Option<&TableFilter>, order: Option<&TableOrder>, limit: i64) -> Result<ResultSet>;
+
+    /// Base columns behind the output columns of a view, keyed by column name.
+    /// Empty for plain tables and backends that cannot tell.
+    async fn view_lineage(&self, _schema: &str, _view: &str, _columns: &[String]) -> Result<ColumnLineage> {
+        Ok(ColumnLineage::new())
+    }
+
+    /// Column that `schema.table.column` references through a foreign key, if it is one.
+    async fn foreign_key_target(&self, schema: &str, table: &str, column: &str) -> Result<Option<ForeignKeyTarget>>;
+
+    /// Indexes, constraints and triggers of a table, each with its definition.
+    async fn table_structure(&self, schema: &str, table: &str) -> Result<TableStructure>;
+
+    /// CREATE TABLE and what else a SQL dump needs to recreate `schema.table`.
+    async fn table_definition(&self, schema: &str, table: &str) -> Result<TableDefinition>;
+
+    /// Read `columns` of `schema.table` for a SQL dump, handing the rows to `sink` in
+    /// batches as they arrive. Values are the text the server writes for them (MySQL's
+    /// binary values hex-encoded), None for NULL. An error from `sink` stops the read.
+    async fn dump_rows(
+        &self,
+        schema: &str,
+        table: &str,
+        columns: &[ColumnInfo],
+        sink: &mut (dyn FnMut(Vec<Vec<Option<String>>>) -> Result<()> + Send),
+    ) -> Result<()>;
+
+    /// SQL that (re)creates a view or routine, as the server reports it.
+    async fn object_source(&self, schema: &str, object: &SourceObject) -> Result<String>;
+
+    async fn execute_query(&self, query: &str, timeout: StatementTimeout) -> Result<ResultSet>;
+
+    /// Run a single statement, telling a (possibly empty) result set apart from a
+    /// statement that only reports how many rows it changed.
+    async fn execute_statement(&self, sql: &str, timeout: StatementTimeout) -> Result<StatementOutcome>;
+
+    /// Like `execute_statement`, binding `params` to the statement's placeholders as text
+    /// the server converts to the types it expects there, or NULL for None.
+    async fn execute_with_params(&self, _sql: &str, _params: &[Option<String>], _timeout: StatementTimeout) -> Result<StatementOutcome> {
+        Err(anyhow::anyhow!("Query parameters are not supported by this driver"))
+    }
+
+    /// Like `execute_query`, but hands rows to `stream` in batches as they arrive, with
+    /// `params` bound to the query's placeholders. Backends that cannot stream deliver
+    /// the whole result at the end. Statements without a result set only report how
+    /// many rows they affected.
+    async fn stream_query(&self, query: &str, params: &[Option<String>], timeout: StatementTimeout, stream: &Mutex<QueryStream>) -> Result<()> {
+        let outcome = if params.is_empty() {
+            self.execute_statement(query, timeout).await?
+        } else {
+            self.execute_with_params(query, params, timeout).await?
+        };
+        let mut stream = stream.lock().unwrap();
+        match outcome {
+            StatementOutcome::Rows((columns, rows)) => {
+                stream.columns = Some(columns);
+                stream.push(rows);
+            }
+            StatementOutcome::Affected(count) => stream.affected_rows = Some(count),
+        }
+        Ok(())
+    }
+
+    /// Run UPDATE statements in a single transaction. Each statement must touch exactly
+    /// one row, otherwise everything is rolled back. Returns the number of rows updated.
+    async fn apply_updates(&self, statements: &[String]) -> Result<u64>;
+
+    /// Run `statements` in a single transaction, rolling all of them back when one fails.
+    async fn execute_in_transaction(&self, statements: &[String]) -> Result<()>;
+
+    /// Load `rows` into `columns` of `schema.table` with COPY FROM STDIN, taking them from
+    /// the iterator a batch at a time and adding each batch sent to `sent`; None values
+    /// are NULL. The COPY is a single statement, so a row the server rejects, or an error
+    /// reading one, loads none of them. Returns the rows copied.
+    async fn copy_in(
+        &self,
+        _schema: &str,
+        _table: &str,
+        _columns: &[String],
+        _rows: &mut (dyn Iterator<Item = Result<Vec<Option<String>>>> + Send),
+        _sent: &AtomicUsize,
+    ) -> Result<u64> {
+        Err(anyhow::anyhow!("Importing files is not supported by this driver"))
+    }
+
+    /// Open, commit or roll back the manual transaction. While it is open every
+    /// statement on this connection runs inside it.
+    async fn transaction(&self, control: TransactionControl) -> Result<()>;
+
+    /// Whether this connection is in a transaction, asked from the server so one opened
+    /// by a typed BEGIN counts too. None for backends that cannot tell.
+    async fn transaction_status(&self) -> Result<Option<TransactionStatus>> {
+        Ok(None)
+    }
+
+    /// Run `sql` and discard any rows it returns.
+    async fn execute_batch(&self, sql: &str) -> Result<()>;
+
+    /// Run `EXPLAIN (FORMAT JSON)` for `query` and return the plan document.
+    async fn explain_json(&self, query: &str, analyze: bool) -> Result<serde_json::Value>;
+
+    /// Re-run `query` under `EXPLAIN (ANALYZE, BUFFERS)` to measure its timing and I/O.
+    /// This executes the statement again, so only call it for read-only queries.
+    async fn resource_report(&self, query: &str) -> Result<ResourceReport> {
+        ResourceReport::from_plan(&self.explain_json(query, true).await?)
+    }
+}
+
+/// Driver-specific way to interrupt a running statement from another connection.
+#[derive(Clone)]
+pub enum CancelHandle {
+    Postgres(CancelToken),
+    MySql(mysql_async::Opts, u32), // connection options, server thread id
+}
+
+/// Connect to `connection_string` with the backend for `driver`.
+pub async fn connect(
+    driver: Driver,
+    connection_string: &str,
+    tls: &TlsSettings,
+    ssh: &SshSettings,
+    network: &NetworkSettings,
+) -> Result<Arc<dyn DatabaseBackend>> {
+    Ok(match driver {
+        Driver::Postgres => Arc::new(PostgresDatabase::connect(connection_string, tls, ssh, network).await?),
+        Driver::MySql => Arc::new(MySqlDatabase::connect(connection_string, tls, ssh, network).await?),
+    })
+}
+
+/// Ask the server to cancel the statement associated with `handle`.
+/// The cancel request opens a new connection, so it needs the same TLS settings.
+pub async fn cancel(handle: CancelHandle, tls: &TlsSettings) -> Result<()> {
+    match handle {
+        CancelHandle::Postgres(token) => PostgresDatabase::cancel(token, tls).await,
+        CancelHandle::MySql(opts, thread_id) => MySqlDatabase::cancel(opts, thread_id).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_status() {
+        assert!(!TransactionStatus::Idle.is_open());
+        assert!(TransactionStatus::Aborted.is_open());
+        assert_eq!(TransactionStatus::InTransaction(Duration::from_secs(42)).to_string(), "idle in transaction (42s)");
+        assert_eq!(TransactionStatus::InTransaction(Duration::from_secs(125)).to_string(), "idle in transaction (2m 05s)");
+        assert_eq!(TransactionStatus::InTransaction(Duration::from_secs(3 * 3600 + 7 * 60)).to_string(), "idle in transaction (3h 07m)");
+    }
+}
@@ -1,106 +1,393 @@
 use anyhow::Result;
-use tokio_postgres::{Client, NoTls, Row};
-use chrono::{NaiveDateTime, DateTime, Utc};
-use crate::db::{ColumnInfo, SchemaInfo};
+use async_trait::async_trait;
+use futures_util::{pin_mut, SinkExt, TryStreamExt};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tokio_postgres::error::SqlState;
+use bytes::{Bytes, BytesMut};
+use tokio_postgres::types::{to_sql_checked, Format, FromSql, IsNull, ToSql, Type};
+use tokio_postgres::{CancelToken, Client, NoTls, Row, SimpleQueryMessage};
+use crate::config::{Driver, NetworkSettings, SshSettings, TlsSettings};
+use super::pg_types::{EnumLabel, Inet, Interval, MacAddress};
+use super::lineage::{resolve_lineage, ColumnLineage, SourceColumn, ViewDependencies};
+use super::backend::unit_of_work;
+use super::sql::{copy_in_statement, dump_select, postgres_table_definition, push_copy_line, quote_ident, ColumnDefault, ColumnDefinition, TableFilter, TableOrder};
+use super::ssh::{open_tunnel, Tunnel};
+use super::timeout::RESTORE_TIMEOUT;
+use crate::models::CellValue;
+use crate::db::{CancelHandle, ColumnInfo, DatabaseBackend, ForeignKeyTarget, FunctionInfo, QueryStream, ResultSet, SchemaInfo, SourceObject, StatementOutcome, StatementTimeout, TableDefinition, TableObject, TablePrivileges, TableStructure, TransactionControl, TransactionStatus, STREAM_BATCH_SIZE};
 
-pub struct Database {
+// Rows sent to the server per message of a COPY FROM STDIN
+const COPY_BATCH_ROWS: usize = 1000;
+
+pub struct PostgresDatabase {
     client: Client,
+    // The query panel's manual transaction is open
+    in_transaction: AtomicBool,
+    // Keeps the SSH forward open for as long as the connection lives
+    _tunnel: Option<Tunnel>,
 }
 
-// Helper function to convert PostgreSQL values to strings
-fn row_value_to_string(row: &Row, idx: usize) -> String {
-    // Try various types in order
+// A query parameter sent as text, which the server parses into whatever type it
+// inferred for the placeholder, like a literal written in its place
+#[derive(Debug)]
+struct TextParam<'a>(Option<&'a str>);
 
-    // String/text types
-    if let Ok(val) = row.try_get::<_, String>(idx) {
-        return val;
+impl ToSql for TextParam<'_> {
+    fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        match self.0 {
+            Some(text) => {
+                out.extend_from_slice(text.as_bytes());
+                Ok(IsNull::No)
+            }
+            None => Ok(IsNull::Yes),
+        }
     }
 
-    // Integer types
-    if let Ok(val) = row.try_get::<_, i32>(idx) {
-        return val.to_string();
-    }
-    if let Ok(val) = row.try_get::<_, i64>(idx) {
-        return val.to_string();
-    }
-    if let Ok(val) = row.try_get::<_, i16>(idx) {
-        return val.to_string();
+    fn accepts(_: &Type) -> bool {
+        true
     }
 
-    // Floating point types
-    if let Ok(val) = row.try_get::<_, f32>(idx) {
-        return val.to_string();
+    fn encode_format(&self, _: &Type) -> Format {
+        Format::Text
     }
-    if let Ok(val) = row.try_get::<_, f64>(idx) {
-        return val.to_string();
+
+    to_sql_checked!();
+}
+
+// Accepts any column type, so NULL can be told apart from a value we can't decode
+struct AnyValue {
+    is_null: bool,
+}
+
+impl<'a> FromSql<'a> for AnyValue {
+    fn from_sql(_: &Type, _: &'a [u8]) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(AnyValue { is_null: false })
     }
 
-    // Boolean
-    if let Ok(val) = row.try_get::<_, bool>(idx) {
-        return val.to_string();
+    fn from_sql_null(_: &Type) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(AnyValue { is_null: true })
     }
 
-    // UUID
-    if let Ok(val) = row.try_get::<_, uuid::Uuid>(idx) {
-        return val.to_string();
+    fn accepts(_: &Type) -> bool {
+        true
     }
+}
+
+// Result columns as reported by the server; generic queries only have name and type
+fn result_columns(columns: &[tokio_postgres::Column]) -> Vec<ColumnInfo> {
+    columns
+        .iter()
+        .map(|col| ColumnInfo {
+            name: col.name().to_string(),
+            data_type: format!("{:?}", col.type_()),
+            is_primary_key: false,
+            is_foreign_key: false,
+        })
+        .collect()
+}
 
-    // Timestamp types
-    if let Ok(val) = row.try_get::<_, NaiveDateTime>(idx) {
-        return val.to_string();
+// Read a PostgreSQL value into the closest cell type. Each read only succeeds for a
+// matching column type and yields None for NULL, so NULL is never guessed.
+fn row_value(row: &Row, idx: usize) -> CellValue {
+    fn read<'a, T: FromSql<'a>>(row: &'a Row, idx: usize, cell: impl FnOnce(T) -> CellValue) -> Option<CellValue> {
+        row.try_get::<_, Option<T>>(idx)
+            .ok()
+            .map(|value| value.map_or(CellValue::Null, cell))
     }
-    if let Ok(val) = row.try_get::<_, DateTime<Utc>>(idx) {
-        return val.to_string();
+    // One-dimensional arrays of the element types above; NULL elements stay NULL
+    fn array<T>(cell: impl Fn(T) -> CellValue) -> impl FnOnce(Vec<Option<T>>) -> CellValue {
+        move |values| CellValue::Array(values.into_iter().map(|value| value.map_or(CellValue::Null, &cell)).collect())
     }
 
-    // JSON types
-    if let Ok(val) = row.try_get::<_, serde_json::Value>(idx) {
-        return val.to_string();
-    }
+    read(row, idx, CellValue::Text)
+        // Integer types
+        .or_else(|| read(row, idx, |v: i32| CellValue::Int(v.into())))
+        .or_else(|| read(row, idx, CellValue::Int))
+        .or_else(|| read(row, idx, |v: i16| CellValue::Int(v.into())))
+        // Floating point types; real goes through its shortest text form so 0.1 stays 0.1
+        .or_else(|| read(row, idx, |v: f32| CellValue::Float(v.to_string().parse().unwrap_or(v.into()))))
+        .or_else(|| read(row, idx, CellValue::Float))
+        .or_else(|| read(row, idx, CellValue::Bool))
+        .or_else(|| read(row, idx, CellValue::Uuid))
+        // Date and timestamp types
+        .or_else(|| read(row, idx, CellValue::Timestamp))
+        .or_else(|| read(row, idx, CellValue::TimestampTz))
+        .or_else(|| read(row, idx, CellValue::Date))
+        .or_else(|| read(row, idx, |v: serde_json::Value| CellValue::Json(v.to_string())))
+        .or_else(|| read(row, idx, CellValue::Bytes))
+        // numeric keeps its exact digits and scale as text; values past rust_decimal's
+        // 28 digits (and NaN) are not decoded
+        .or_else(|| read(row, idx, |v: rust_decimal::Decimal| CellValue::Text(v.to_string())))
+        .or_else(|| read(row, idx, |v: Interval| CellValue::Text(v.to_string())))
+        .or_else(|| read(row, idx, |v: Inet| CellValue::Text(v.to_string())))
+        .or_else(|| read(row, idx, |v: MacAddress| CellValue::Text(v.to_string())))
+        .or_else(|| read(row, idx, |v: EnumLabel| CellValue::Text(v.0)))
+        .or_else(|| read(row, idx, array(CellValue::Text)))
+        .or_else(|| read(row, idx, array(|v: i32| CellValue::Int(v.into()))))
+        .or_else(|| read(row, idx, array(CellValue::Int)))
+        .or_else(|| read(row, idx, array(|v: i16| CellValue::Int(v.into()))))
+        .or_else(|| read(row, idx, array(|v: f32| CellValue::Float(v.to_string().parse().unwrap_or(v.into())))))
+        .or_else(|| read(row, idx, array(CellValue::Float)))
+        .or_else(|| read(row, idx, array(CellValue::Bool)))
+        .or_else(|| read(row, idx, array(CellValue::Uuid)))
+        .or_else(|| read(row, idx, array(CellValue::Timestamp)))
+        .or_else(|| read(row, idx, array(CellValue::TimestampTz)))
+        .or_else(|| read(row, idx, array(CellValue::Date)))
+        .or_else(|| read(row, idx, array(|v: serde_json::Value| CellValue::Json(v.to_string()))))
+        .or_else(|| read(row, idx, array(CellValue::Bytes)))
+        .or_else(|| read(row, idx, array(|v: rust_decimal::Decimal| CellValue::Text(v.to_string()))))
+        .or_else(|| read(row, idx, array(|v: Interval| CellValue::Text(v.to_string()))))
+        .or_else(|| read(row, idx, array(|v: Inet| CellValue::Text(v.to_string()))))
+        .or_else(|| read(row, idx, array(|v: EnumLabel| CellValue::Text(v.0))))
+        // Types without a decoder show their type name
+        .unwrap_or_else(|| match row.try_get::<_, AnyValue>(idx) {
+            Ok(AnyValue { is_null: true }) => CellValue::Null,
+            _ => CellValue::Text(format!("<{}>", row.columns()[idx].type_().name())),
+        })
+}
 
-    // Byte arrays
-    if let Ok(val) = row.try_get::<_, Vec<u8>>(idx) {
-        return format!("<{} bytes>", val.len());
-    }
+/// True when `error` is the server reporting that the statement was cancelled.
+pub fn is_cancelled(error: &anyhow::Error) -> bool {
+    let postgres = error
+        .downcast_ref::<tokio_postgres::Error>()
+        .and_then(|e| e.code())
+        .is_some_and(|code| *code == SqlState::QUERY_CANCELED);
+    postgres || super::mysql::is_interrupted(error)
+}
 
-    // If all else fails, check if it's NULL
-    "(NULL)".to_string()
+/// True when the statement was cancelled because it hit the statement time limit.
+pub fn is_timeout(error: &anyhow::Error) -> bool {
+    let postgres = error
+        .downcast_ref::<tokio_postgres::Error>()
+        .and_then(|e| e.as_db_error())
+        .is_some_and(|e| *e.code() == SqlState::QUERY_CANCELED && e.message().contains("statement timeout"));
+    postgres || super::mysql::is_timed_out(error)
 }
 
-impl Database {
-    pub async fn connect(connection_string: &str) -> Result<Self> {
-        let (client, connection) = tokio_postgres::connect(connection_string, NoTls).await?;
+impl PostgresDatabase {
+    pub async fn connect(connection_string: &str, tls: &TlsSettings, ssh: &SshSettings, network: &NetworkSettings) -> Result<Self> {
+        let (connection_string, tunnel) = open_tunnel(connection_string, ssh, network).await?;
+        let mut config: tokio_postgres::Config = connection_string.parse()?;
+        if let Some(timeout) = network.connect_timeout() {
+            config.connect_timeout(timeout);
+        }
+        if let Some(idle) = network.keepalive_idle() {
+            config.keepalives_idle(idle);
+        }
+        if let Some(interval) = network.keepalive_interval() {
+            config.keepalives_interval(interval);
+        }
+
+        let client = match super::tls::make_connector(tls)? {
+            Some(connector) => {
+                let (client, connection) = config.connect(connector).await?;
+                Self::spawn_connection(connection);
+                client
+            }
+            None => {
+                let (client, connection) = config.connect(NoTls).await?;
+                Self::spawn_connection(connection);
+                client
+            }
+        };
+
+        Ok(PostgresDatabase { client, in_transaction: AtomicBool::new(false), _tunnel: tunnel })
+    }
 
-        // Keep connection alive in background task
+    // Keep connection alive in background task
+    fn spawn_connection<S, T>(connection: tokio_postgres::Connection<S, T>)
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+        T: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+    {
         tokio::spawn(async move {
             if let Err(e) = connection.await {
                 eprintln!("Database connection error: {}", e);
             }
         });
+    }
+
+
+    /// Ask the server to cancel the statement associated with `token`.
+    /// The cancel request opens a new connection, so it needs the same TLS settings.
+    pub async fn cancel(token: CancelToken, tls: &TlsSettings) -> Result<()> {
+        match super::tls::make_connector(tls)? {
+            Some(connector) => token.cancel_query(connector).await?,
+            None => token.cancel_query(NoTls).await?,
+        }
+        Ok(())
+    }
+
+    // Whether a manual transaction is open on this connection
+    fn in_transaction(&self) -> bool {
+        self.in_transaction.load(Ordering::SeqCst)
+    }
+
+    /// Limit the statements run until `end_timeout` to `timeout`. Returns the setting it
+    /// replaced, None when there is no limit.
+    async fn begin_timeout(&self, timeout: StatementTimeout) -> Result<Option<String>> {
+        let Some(statement) = timeout.set_statement() else {
+            return Ok(None);
+        };
+        Ok(Some(self.client.query_one(&statement, &[]).await?.get(0)))
+    }
+
+    async fn end_timeout(&self, previous: Option<String>, succeeded: bool) -> Result<()> {
+        // After a failure the manual transaction refuses everything until it is rolled
+        // back, which undoes the change too
+        if let (Some(previous), true) = (previous, succeeded || !self.in_transaction()) {
+            self.client.query_one(RESTORE_TIMEOUT, &[&previous]).await?;
+        }
+        Ok(())
+    }
+
+    // Describe `query`, then read its rows from the portal as the server sends them
+    async fn stream_rows(&self, query: &str, params: &[Option<String>], stream: &Mutex<QueryStream>) -> Result<()> {
+        let statement = self.client.prepare(query).await?;
+        let params: Vec<TextParam> = params.iter().map(|param| TextParam(param.as_deref())).collect();
+        if statement.columns().is_empty() {
+            let affected = self.client.execute_raw(&statement, &params).await?;
+            stream.lock().unwrap().affected_rows = Some(affected);
+            return Ok(());
+        }
+        stream.lock().unwrap().columns = Some(result_columns(statement.columns()));
+
+        let rows = self.client.query_raw(&statement, &params).await?;
+        pin_mut!(rows);
+        let mut batch = Vec::with_capacity(STREAM_BATCH_SIZE);
+        while let Some(row) = rows.try_next().await? {
+            batch.push((0..row.len()).map(|i| row_value(&row, i)).collect());
+            if batch.len() >= STREAM_BATCH_SIZE {
+                stream.lock().unwrap().push(std::mem::replace(&mut batch, Vec::with_capacity(STREAM_BATCH_SIZE)));
+            }
+        }
+        stream.lock().unwrap().push(batch);
+        Ok(())
+    }
+
+    async fn run_statement(&self, sql: &str) -> Result<StatementOutcome> {
+        let statement = self.client.prepare(sql).await?;
+        if statement.columns().is_empty() {
+            return Ok(StatementOutcome::Affected(self.client.execute(&statement, &[]).await?));
+        }
+
+        let columns = result_columns(statement.columns());
+        let rows = self.client.query(&statement, &[]).await?;
+        let data = rows.iter().map(|row| (0..row.len()).map(|i| row_value(row, i)).collect()).collect();
+        Ok(StatementOutcome::Rows((columns, data)))
+    }
+
+    // UPDATE counts when granted on any column, so column-level grants still allow editing
+    async fn table_privileges(&self) -> Result<Vec<(String, String, TablePrivileges)>> {
+        let rows = self
+            .client
+            .query(
+                "SELECT n.nspname::text, c.relname::text,
+                     has_table_privilege(c.oid, 'INSERT'),
+                     has_any_column_privilege(c.oid, 'UPDATE'),
+                     has_table_privilege(c.oid, 'DELETE'),
+                     has_table_privilege(c.oid, 'TRUNCATE')
+                 FROM pg_class c
+                 JOIN pg_namespace n ON n.oid = c.relnamespace
+                 WHERE c.relkind IN ('r', 'p')
+                     AND n.nspname NOT IN ('pg_catalog', 'information_schema', 'pg_toast')",
+                &[],
+            )
+            .await?;
 
-        Ok(Database { client })
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let privileges = TablePrivileges { insert: row.get(2), update: row.get(3), delete: row.get(4), truncate: row.get(5) };
+                (row.get(0), row.get(1), privileges)
+            })
+            .collect())
     }
 
-    pub async fn list_all_tables_grouped(&self) -> Result<Vec<SchemaInfo>> {
-        // Get all tables grouped by schema in a single query
+    async fn list_all_tables_grouped(&self) -> Result<Vec<SchemaInfo>> {
+        // Get all tables and views grouped by schema in a single query
         let rows = self
             .client
             .query(
-                "SELECT table_schema, table_name
+                "SELECT table_schema, table_name, table_type
                  FROM information_schema.tables
                  WHERE table_schema NOT IN ('pg_catalog', 'information_schema', 'pg_toast')
-                 AND table_type IN ('BASE TABLE', 'VIEW', 'MATERIALIZED VIEW')
+                 AND table_type IN ('BASE TABLE', 'VIEW')
                  ORDER BY table_schema, table_name",
                 &[],
             )
             .await?;
 
-        let mut schemas_map: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
+        let mut schemas_map: std::collections::HashMap<String, SchemaInfo> = std::collections::HashMap::new();
+        fn schema_entry(map: &mut std::collections::HashMap<String, SchemaInfo>, name: String) -> &mut SchemaInfo {
+            map.entry(name.clone()).or_insert_with(|| SchemaInfo::new(name))
+        }
 
         for row in rows {
             let schema: String = row.get(0);
             let table: String = row.get(1);
-            schemas_map.entry(schema).or_default().push(table);
+            let table_type: String = row.get(2);
+            let info = schema_entry(&mut schemas_map, schema);
+            if table_type == "VIEW" {
+                info.views.push(table);
+            } else {
+                info.tables.push(table);
+            }
+        }
+
+        // Materialized views are missing from information_schema
+        let matview_rows = self
+            .client
+            .query(
+                "SELECT schemaname::text, matviewname::text
+                 FROM pg_matviews
+                 WHERE schemaname NOT IN ('pg_catalog', 'information_schema', 'pg_toast')
+                 ORDER BY schemaname, matviewname",
+                &[],
+            )
+            .await?;
+        for row in matview_rows {
+            schema_entry(&mut schemas_map, row.get(0)).materialized_views.push(row.get(1));
+        }
+
+        // Functions and procedures, leaving out those installed by extensions
+        let function_rows = self
+            .client
+            .query(
+                "SELECT n.nspname::text, p.proname::text, pg_get_function_identity_arguments(p.oid),
+                     CASE WHEN p.prokind = 'p' THEN NULL ELSE pg_get_function_result(p.oid) END,
+                     p.prokind = 'p'
+                 FROM pg_proc p
+                 JOIN pg_namespace n ON n.oid = p.pronamespace
+                 WHERE n.nspname NOT IN ('pg_catalog', 'information_schema', 'pg_toast')
+                     AND p.prokind IN ('f', 'p')
+                     AND NOT EXISTS (SELECT 1 FROM pg_depend d WHERE d.objid = p.oid AND d.deptype = 'e')
+                 ORDER BY n.nspname, p.proname",
+                &[],
+            )
+            .await?;
+        for row in function_rows {
+            schema_entry(&mut schemas_map, row.get(0)).functions.push(FunctionInfo {
+                name: row.get(1),
+                arguments: row.get(2),
+                result: row.get(3),
+                is_procedure: row.get(4),
+            });
+        }
+
+        let sequence_rows = self
+            .client
+            .query(
+                "SELECT sequence_schema::text, sequence_name::text
+                 FROM information_schema.sequences
+                 WHERE sequence_schema NOT IN ('pg_catalog', 'information_schema', 'pg_toast')
+                 ORDER BY sequence_schema, sequence_name",
+                &[],
+            )
+            .await?;
+        for row in sequence_rows {
+            schema_entry(&mut schemas_map, row.get(0)).sequences.push(row.get(1));
         }
 
         // Get all columns for all tables in a single query
@@ -108,23 +395,66 @@ impl Database {
             .client
             .query(
                 "SELECT
-                    c.table_schema,
-                    c.table_name,
-                    c.column_name,
+                    c.table_schema::text,
+                    c.table_name::text,
+                    c.column_name::text,
                     CASE
                         WHEN c.character_maximum_length IS NOT NULL THEN c.data_type || '(' || c.character_maximum_length || ')'
                         WHEN c.numeric_precision IS NOT NULL AND c.numeric_scale IS NOT NULL THEN c.data_type || '(' || c.numeric_precision || ',' || c.numeric_scale || ')'
                         WHEN c.datetime_precision IS NOT NULL AND c.datetime_precision != 6 THEN c.udt_name || '(' || c.datetime_precision || ')'
                         WHEN c.datetime_precision IS NOT NULL AND c.datetime_precision = 6 THEN c.udt_name || '(6)'
                         ELSE c.udt_name
-                    END as full_data_type
+                    END::text as full_data_type,
+                    c.ordinal_position::int
                  FROM information_schema.columns c
                  WHERE c.table_schema NOT IN ('pg_catalog', 'information_schema', 'pg_toast')
-                 ORDER BY c.table_schema, c.table_name, c.ordinal_position",
+                 UNION ALL
+                 SELECT n.nspname::text, c.relname::text, a.attname::text, format_type(a.atttypid, a.atttypmod), a.attnum::int
+                 FROM pg_attribute a
+                 JOIN pg_class c ON c.oid = a.attrelid
+                 JOIN pg_namespace n ON n.oid = c.relnamespace
+                 WHERE c.relkind = 'm' AND a.attnum > 0 AND NOT a.attisdropped
+                 ORDER BY 1, 2, 5",
                 &[],
             )
             .await?;
 
+        // Grants of the connected role; only a hint for the UI, so a failure leaves them unknown
+        match self.table_privileges().await {
+            Ok(privileges) => {
+                for (schema, table, table_privileges) in privileges {
+                    if let Some(info) = schemas_map.get_mut(&schema) {
+                        info.table_privileges.insert(table, table_privileges);
+                    }
+                }
+            }
+            Err(e) => eprintln!("Failed to load table privileges: {}", e),
+        }
+
+        // Planner statistics, -1 for tables never analyzed; also only a hint
+        let estimate_rows = self
+            .client
+            .query(
+                "SELECT n.nspname::text, c.relname::text, c.reltuples::bigint
+                 FROM pg_class c
+                 JOIN pg_namespace n ON n.oid = c.relnamespace
+                 WHERE c.relkind IN ('r', 'p', 'm') AND c.reltuples >= 0
+                     AND n.nspname NOT IN ('pg_catalog', 'information_schema', 'pg_toast')",
+                &[],
+            )
+            .await;
+        match estimate_rows {
+            Ok(rows) => {
+                for row in rows {
+                    let estimate: i64 = row.get(2);
+                    if let Some(info) = schemas_map.get_mut(row.get::<_, &str>(0)) {
+                        info.row_estimates.insert(row.get(1), estimate as u64);
+                    }
+                }
+            }
+            Err(e) => eprintln!("Failed to load row estimates: {}", e),
+        }
+
         // Get all primary keys in one query
         let pk_rows = self
             .client
@@ -200,17 +530,12 @@ impl Database {
         }
 
         let mut result: Vec<SchemaInfo> = schemas_map
-            .into_iter()
-            .map(|(name, tables)| {
-                let table_columns = table_columns_map
-                    .get(&name)
-                    .cloned()
+            .into_values()
+            .map(|mut info| {
+                info.table_columns = table_columns_map
+                    .remove(&info.name)
                     .unwrap_or_default();
-                SchemaInfo {
-                    name,
-                    tables,
-                    table_columns,
-                }
+                info
             })
             .collect();
 
@@ -218,22 +543,380 @@ impl Database {
 
         // If no schemas found, ensure public schema exists
         if result.is_empty() {
-            result.push(SchemaInfo {
-                name: "public".to_string(),
-                tables: vec![],
-                table_columns: std::collections::HashMap::new(),
-            });
+            result.push(SchemaInfo::new("public".to_string()));
         }
 
         Ok(result)
     }
+}
+
+#[async_trait]
+impl DatabaseBackend for PostgresDatabase {
+    fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle::Postgres(self.client.cancel_token())
+    }
+
+    async fn apply_updates(&self, statements: &[String]) -> Result<u64> {
+        let [begin, commit, rollback] = unit_of_work(self.in_transaction());
+        self.client.batch_execute(begin).await?;
 
-    pub async fn list_schemas_with_tables(&self) -> Result<Vec<SchemaInfo>> {
+        let mut updated = 0;
+        for statement in statements {
+            let result = self.client.execute(statement.as_str(), &[]).await;
+            match result {
+                Ok(1) => updated += 1,
+                Ok(count) => {
+                    self.client.batch_execute(rollback).await?;
+                    return Err(anyhow::anyhow!("Expected to update 1 row but {} matched: {}", count, statement));
+                }
+                Err(e) => {
+                    self.client.batch_execute(rollback).await?;
+                    return Err(e.into());
+                }
+            }
+        }
+
+        self.client.batch_execute(commit).await?;
+        Ok(updated)
+    }
+
+    async fn execute_in_transaction(&self, statements: &[String]) -> Result<()> {
+        let [begin, commit, rollback] = unit_of_work(self.in_transaction());
+        self.client.batch_execute(begin).await?;
+
+        for statement in statements {
+            if let Err(e) = self.client.batch_execute(statement).await {
+                self.client.batch_execute(rollback).await?;
+                return Err(e.into());
+            }
+        }
+
+        self.client.batch_execute(commit).await?;
+        Ok(())
+    }
+
+    async fn copy_in(
+        &self,
+        schema: &str,
+        table: &str,
+        columns: &[String],
+        rows: &mut (dyn Iterator<Item = Result<Vec<Option<String>>>> + Send),
+        sent: &AtomicUsize,
+    ) -> Result<u64> {
+        let sink = self.client.copy_in::<_, Bytes>(&copy_in_statement(schema, table, columns)).await?;
+        pin_mut!(sink);
+        let mut lines = String::new();
+        let mut batch = 0;
+        // Dropping the sink on an error aborts the COPY
+        for row in rows {
+            push_copy_line(&mut lines, &row?);
+            batch += 1;
+            if batch == COPY_BATCH_ROWS {
+                sink.send(Bytes::from(std::mem::take(&mut lines))).await?;
+                sent.fetch_add(batch, Ordering::Relaxed);
+                batch = 0;
+            }
+        }
+        if batch > 0 {
+            sink.send(Bytes::from(lines)).await?;
+            sent.fetch_add(batch, Ordering::Relaxed);
+        }
+        Ok(sink.finish().await?)
+    }
+
+    async fn transaction(&self, control: TransactionControl) -> Result<()> {
+        let result = self.client.batch_execute(control.sql()).await;
+        // COMMIT and ROLLBACK end the transaction even when they report an error
+        self.in_transaction.store(control == TransactionControl::Begin && result.is_ok(), Ordering::SeqCst);
+        Ok(result?)
+    }
+
+    async fn transaction_status(&self) -> Result<Option<TransactionStatus>> {
+        // Outside a transaction block the probe is its own transaction, so both clocks agree.
+        // It goes through the simple query protocol, where Parse and Execute can't split them.
+        let probe = "SELECT EXTRACT(EPOCH FROM statement_timestamp() - transaction_timestamp())::float8";
+        let status = match self.client.simple_query(probe).await {
+            Ok(messages) => {
+                let seconds = messages.iter()
+                    .find_map(|message| match message {
+                        SimpleQueryMessage::Row(row) => row.get(0).and_then(|value| value.parse::<f64>().ok()),
+                        _ => None,
+                    })
+                    .ok_or_else(|| anyhow::anyhow!("Transaction status probe returned no row"))?;
+                if seconds > 0.0 {
+                    TransactionStatus::InTransaction(std::time::Duration::from_secs_f64(seconds))
+                } else {
+                    TransactionStatus::Idle
+                }
+            }
+            Err(e) if e.code() == Some(&SqlState::IN_FAILED_SQL_TRANSACTION) => TransactionStatus::Aborted,
+            Err(e) => return Err(e.into()),
+        };
+        // A typed BEGIN or COMMIT changes what the edits and timeouts must run inside
+        self.in_transaction.store(status.is_open(), Ordering::SeqCst);
+        Ok(Some(status))
+    }
+
+    /// Run `sql` with the simple query protocol and discard any rows it returns.
+    async fn execute_batch(&self, sql: &str) -> Result<()> {
+        self.client.batch_execute(sql).await?;
+        Ok(())
+    }
+
+    async fn explain_json(&self, query: &str, analyze: bool) -> Result<serde_json::Value> {
+        let statement = crate::export::explain_statement(query, analyze);
+        let row = self.client.query_one(statement.as_str(), &[]).await?;
+        Ok(row.try_get::<_, serde_json::Value>(0)?)
+    }
+
+    async fn list_schemas_with_tables(&self) -> Result<Vec<SchemaInfo>> {
         // Use the more efficient grouped query
         self.list_all_tables_grouped().await
     }
 
-    pub async fn query_table(&self, table_name: &str, limit: i64) -> Result<(Vec<ColumnInfo>, Vec<Vec<String>>)> {
+    async fn view_lineage(&self, schema: &str, view: &str, columns: &[String]) -> Result<ColumnLineage> {
+        let is_view = self
+            .client
+            .query_opt(
+                "SELECT 1
+                 FROM pg_class c
+                 JOIN pg_namespace n ON n.oid = c.relnamespace
+                 WHERE n.nspname = $1 AND c.relname = $2 AND c.relkind IN ('v', 'm')",
+                &[&schema, &view],
+            )
+            .await?
+            .is_some();
+        if !is_view {
+            return Ok(ColumnLineage::new());
+        }
+
+        // Columns every view's rewrite rule reads, so nested views resolve without more round trips
+        let rows = self
+            .client
+            .query(
+                "SELECT DISTINCT vn.nspname, v.relname, sn.nspname, s.relname, a.attname
+                 FROM pg_rewrite r
+                 JOIN pg_class v ON v.oid = r.ev_class
+                 JOIN pg_namespace vn ON vn.oid = v.relnamespace
+                 JOIN pg_depend d ON d.classid = 'pg_rewrite'::regclass
+                     AND d.objid = r.oid
+                     AND d.refclassid = 'pg_class'::regclass
+                     AND d.refobjsubid > 0
+                 JOIN pg_class s ON s.oid = d.refobjid AND s.oid <> v.oid
+                 JOIN pg_namespace sn ON sn.oid = s.relnamespace
+                 JOIN pg_attribute a ON a.attrelid = s.oid AND a.attnum = d.refobjsubid
+                 WHERE v.relkind IN ('v', 'm')
+                     AND vn.nspname NOT IN ('pg_catalog', 'information_schema')",
+                &[],
+            )
+            .await?;
+
+        let mut dependencies = ViewDependencies::new();
+        for row in rows {
+            dependencies
+                .entry((row.get(0), row.get(1)))
+                .or_default()
+                .push(SourceColumn { schema: row.get(2), relation: row.get(3), column: row.get(4) });
+        }
+
+        Ok(resolve_lineage(schema, view, columns, &dependencies))
+    }
+
+    async fn foreign_key_target(&self, schema: &str, table: &str, column: &str) -> Result<Option<ForeignKeyTarget>> {
+        // Pair columns of composite keys by their position in the referenced key
+        let row = self
+            .client
+            .query_opt(
+                "SELECT ref.table_schema::text, ref.table_name::text, ref.column_name::text
+                 FROM information_schema.key_column_usage kcu
+                 JOIN information_schema.referential_constraints rc
+                     ON rc.constraint_schema = kcu.constraint_schema
+                     AND rc.constraint_name = kcu.constraint_name
+                 JOIN information_schema.key_column_usage ref
+                     ON ref.constraint_schema = rc.unique_constraint_schema
+                     AND ref.constraint_name = rc.unique_constraint_name
+                     AND ref.ordinal_position = kcu.position_in_unique_constraint
+                 WHERE kcu.table_schema = $1 AND kcu.table_name = $2 AND kcu.column_name = $3
+                 LIMIT 1",
+                &[&schema, &table, &column],
+            )
+            .await?;
+
+        Ok(row.map(|row| ForeignKeyTarget { schema: row.get(0), table: row.get(1), column: row.get(2) }))
+    }
+
+    async fn object_source(&self, schema: &str, object: &SourceObject) -> Result<String> {
+        let not_found = || anyhow::anyhow!("{}.{} not found", schema, object.name());
+        match object {
+            SourceObject::View(name) | SourceObject::MaterializedView(name) => {
+                let row = self
+                    .client
+                    .query_opt(
+                        "SELECT pg_get_viewdef(c.oid, true)
+                         FROM pg_class c
+                         JOIN pg_namespace n ON n.oid = c.relnamespace
+                         WHERE n.nspname = $1 AND c.relname = $2 AND c.relkind IN ('v', 'm')",
+                        &[&schema, name],
+                    )
+                    .await?
+                    .ok_or_else(not_found)?;
+                let definition: String = row.get(0);
+                let create = match object {
+                    SourceObject::MaterializedView(_) => "CREATE MATERIALIZED VIEW",
+                    _ => "CREATE OR REPLACE VIEW",
+                };
+                Ok(format!("{} {}.{} AS\n{}", create, quote_ident(schema), quote_ident(name), definition.trim_start()))
+            }
+            SourceObject::Function(function) => {
+                let row = self
+                    .client
+                    .query_opt(
+                        "SELECT pg_get_functiondef(p.oid)
+                         FROM pg_proc p
+                         JOIN pg_namespace n ON n.oid = p.pronamespace
+                         WHERE n.nspname = $1 AND p.proname = $2
+                             AND pg_get_function_identity_arguments(p.oid) = $3",
+                        &[&schema, &function.name, &function.arguments],
+                    )
+                    .await?
+                    .ok_or_else(not_found)?;
+                Ok(row.get(0))
+            }
+        }
+    }
+
+    async fn table_structure(&self, schema: &str, table: &str) -> Result<TableStructure> {
+        let objects = |rows: Vec<Row>| -> Vec<TableObject> {
+            rows.iter()
+                .map(|row| TableObject { name: row.get(0), kind: row.get(1), definition: row.get(2) })
+                .collect()
+        };
+
+        let indexes = self
+            .client
+            .query(
+                "SELECT ic.relname::text,
+                     CASE WHEN i.indisprimary THEN 'PRIMARY' WHEN i.indisunique THEN 'UNIQUE' ELSE 'INDEX' END,
+                     pg_get_indexdef(i.indexrelid)
+                 FROM pg_index i
+                 JOIN pg_class ic ON ic.oid = i.indexrelid
+                 JOIN pg_class t ON t.oid = i.indrelid
+                 JOIN pg_namespace n ON n.oid = t.relnamespace
+                 WHERE n.nspname = $1 AND t.relname = $2
+                 ORDER BY ic.relname",
+                &[&schema, &table],
+            )
+            .await?;
+
+        let constraints = self
+            .client
+            .query(
+                "SELECT con.conname::text,
+                     CASE con.contype
+                         WHEN 'p' THEN 'PRIMARY KEY'
+                         WHEN 'u' THEN 'UNIQUE'
+                         WHEN 'c' THEN 'CHECK'
+                         WHEN 'f' THEN 'FOREIGN KEY'
+                         WHEN 'x' THEN 'EXCLUDE'
+                         ELSE con.contype::text
+                     END,
+                     pg_get_constraintdef(con.oid)
+                 FROM pg_constraint con
+                 JOIN pg_class t ON t.oid = con.conrelid
+                 JOIN pg_namespace n ON n.oid = t.relnamespace
+                 WHERE n.nspname = $1 AND t.relname = $2
+                 ORDER BY con.conname",
+                &[&schema, &table],
+            )
+            .await?;
+
+        // Internal triggers implement foreign keys and are already listed as constraints
+        let triggers = self
+            .client
+            .query(
+                "SELECT tg.tgname::text,
+                     CASE WHEN tg.tgenabled = 'D' THEN 'DISABLED' ELSE 'ENABLED' END,
+                     pg_get_triggerdef(tg.oid)
+                 FROM pg_trigger tg
+                 JOIN pg_class t ON t.oid = tg.tgrelid
+                 JOIN pg_namespace n ON n.oid = t.relnamespace
+                 WHERE n.nspname = $1 AND t.relname = $2 AND NOT tg.tgisinternal
+                 ORDER BY tg.tgname",
+                &[&schema, &table],
+            )
+            .await?;
+
+        Ok(TableStructure {
+            indexes: objects(indexes),
+            constraints: objects(constraints),
+            triggers: objects(triggers),
+        })
+    }
+
+    async fn table_definition(&self, schema: &str, table: &str) -> Result<TableDefinition> {
+        // attgenerated needs PostgreSQL 12
+        let rows = self
+            .client
+            .query(
+                "SELECT a.attname::text, format_type(a.atttypid, a.atttypmod), a.attnotnull,
+                     pg_get_expr(d.adbin, d.adrelid), a.attidentity::text, a.attgenerated::text
+                 FROM pg_attribute a
+                 JOIN pg_class t ON t.oid = a.attrelid
+                 JOIN pg_namespace n ON n.oid = t.relnamespace
+                 LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+                 WHERE n.nspname = $1 AND t.relname = $2 AND a.attnum > 0 AND NOT a.attisdropped
+                 ORDER BY a.attnum",
+                &[&schema, &table],
+            )
+            .await?;
+        if rows.is_empty() {
+            anyhow::bail!("{}.{} not found", schema, table);
+        }
+
+        let columns: Vec<ColumnDefinition> = rows
+            .iter()
+            .map(|row| {
+                let expression: Option<String> = row.get(3);
+                let (identity, generated): (String, String) = (row.get(4), row.get(5));
+                let default = match (expression, identity.as_str(), generated.as_str()) {
+                    (Some(expression), _, "s") => ColumnDefault::Generated(expression),
+                    (_, "a", _) => ColumnDefault::Identity { always: true },
+                    (_, "d", _) => ColumnDefault::Identity { always: false },
+                    (Some(expression), _, _) => ColumnDefault::Expression(expression),
+                    (None, _, _) => ColumnDefault::None,
+                };
+                ColumnDefinition { name: row.get(0), data_type: row.get(1), not_null: row.get(2), default }
+            })
+            .collect();
+
+        let structure = self.table_structure(schema, table).await?;
+        Ok(postgres_table_definition(schema, table, &columns, &structure))
+    }
+
+    async fn dump_rows(
+        &self,
+        schema: &str,
+        table: &str,
+        columns: &[ColumnInfo],
+        sink: &mut (dyn FnMut(Vec<Vec<Option<String>>>) -> Result<()> + Send),
+    ) -> Result<()> {
+        if columns.is_empty() {
+            return Ok(());
+        }
+        let no_params: [&str; 0] = [];
+        let rows = self.client.query_raw(dump_select(Driver::Postgres, schema, table, columns).as_str(), no_params).await?;
+        pin_mut!(rows);
+        let mut batch = Vec::with_capacity(STREAM_BATCH_SIZE);
+        while let Some(row) = rows.try_next().await? {
+            batch.push((0..row.len()).map(|i| row.get::<_, Option<String>>(i)).collect());
+            if batch.len() >= STREAM_BATCH_SIZE {
+                sink(std::mem::replace(&mut batch, Vec::with_capacity(STREAM_BATCH_SIZE)))?;
+            }
+        }
+        sink(batch)
+    }
+
+    async fn query_table(&self, table_name: &str, filter: Option<&TableFilter>, order: Option<&TableOrder>, limit: i64) -> Result<ResultSet> {
         // Parse schema and table name
         let (schema, table) = if table_name.contains('.') {
             let parts: Vec<&str> = table_name.split('.').collect();
@@ -314,14 +997,19 @@ impl Database {
             .collect();
 
         // Get data - use proper schema qualification
-        let data_query = format!("SELECT * FROM {}.{} LIMIT {}", schema, table, limit);
-        let rows = self.client.query(&data_query, &[]).await?;
+        let where_clause = filter.map(|filter| format!(" WHERE {}", filter.sql)).unwrap_or_default();
+        let order_clause = order.map(|order| format!(" ORDER BY {} {}", quote_ident(&order.column), order.direction())).unwrap_or_default();
+        let data_query = format!("SELECT * FROM {}.{}{}{} LIMIT {}", schema, table, where_clause, order_clause, limit);
+        let params: Vec<&(dyn ToSql + Sync)> = filter
+            .map(|filter| filter.params.iter().map(|param| param as &(dyn ToSql + Sync)).collect())
+            .unwrap_or_default();
+        let rows = self.client.query(&data_query, &params).await?;
 
-        let data: Vec<Vec<String>> = rows
+        let data: Vec<Vec<CellValue>> = rows
             .iter()
             .map(|row| {
                 (0..row.len())
-                    .map(|i| row_value_to_string(row, i))
+                    .map(|i| row_value(row, i))
                     .collect()
             })
             .collect();
@@ -329,30 +1017,42 @@ impl Database {
         Ok((columns, data))
     }
 
-    pub async fn execute_query(&self, query: &str) -> Result<(Vec<ColumnInfo>, Vec<Vec<String>>)> {
-        let rows = self.client.query(query, &[]).await?;
+    async fn stream_query(&self, query: &str, params: &[Option<String>], timeout: StatementTimeout, stream: &Mutex<QueryStream>) -> Result<()> {
+        let previous = self.begin_timeout(timeout.for_statement(query)).await?;
+        let result = self.stream_rows(query, params, stream).await;
+        // The statement's own error matters more than one putting the limit back
+        let restored = self.end_timeout(previous, result.is_ok()).await;
+        result?;
+        restored
+    }
+
+    async fn execute_statement(&self, sql: &str, timeout: StatementTimeout) -> Result<StatementOutcome> {
+        let previous = self.begin_timeout(timeout.for_statement(sql)).await?;
+        let result = self.run_statement(sql).await;
+        let restored = self.end_timeout(previous, result.is_ok()).await;
+        let outcome = result?;
+        restored?;
+        Ok(outcome)
+    }
+
+    async fn execute_query(&self, query: &str, timeout: StatementTimeout) -> Result<ResultSet> {
+        let previous = self.begin_timeout(timeout.for_statement(query)).await?;
+        let result = self.client.query(query, &[]).await;
+        let restored = self.end_timeout(previous, result.is_ok()).await;
+        let rows = result?;
+        restored?;
 
         if rows.is_empty() {
             return Ok((vec![], vec![]));
         }
 
-        // For generic queries, we only have basic column info
-        let columns: Vec<ColumnInfo> = rows[0]
-            .columns()
-            .iter()
-            .map(|col| ColumnInfo {
-                name: col.name().to_string(),
-                data_type: format!("{:?}", col.type_()),
-                is_primary_key: false,
-                is_foreign_key: false,
-            })
-            .collect();
+        let columns = result_columns(rows[0].columns());
 
-        let data: Vec<Vec<String>> = rows
+        let data: Vec<Vec<CellValue>> = rows
             .iter()
             .map(|row| {
                 (0..row.len())
-                    .map(|i| row_value_to_string(row, i))
+                    .map(|i| row_value(row, i))
                     .collect()
             })
             .collect();
@@ -0,0 +1,472 @@
+//! End-to-end tests of the PostgreSQL backend against a throwaway server started
+//! with testcontainers. Run with `cargo test --features integration-tests`; they
+//! need a running Docker daemon.
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use futures_util::{pin_mut, SinkExt, TryStreamExt};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use testcontainers_modules::postgres::Postgres;
+use testcontainers_modules::testcontainers::runners::AsyncRunner;
+use testcontainers_modules::testcontainers::{ContainerAsync, ImageExt};
+use crate::config::{Driver, NetworkSettings, SshSettings, TlsSettings};
+use crate::export::{DumpRows, ExportFormat, ExportJob, ExportSettings, SqlDump};
+use crate::models::{AlterPlan, CellValue, NullabilityChange};
+use super::client::PostgresDatabase;
+use super::{is_timeout, run_statements, QueryStream, split_statements, DatabaseBackend, Privilege, SourceObject, StatementOutcome, StatementTimeout, TablePrivileges, TransactionControl, TransactionStatus};
+
+struct TestDatabase {
+    db: PostgresDatabase,
+    connection_string: String,
+    // Stops and removes the server when the test ends
+    _container: ContainerAsync<Postgres>,
+}
+
+/// Fresh server with `setup` already executed.
+async fn start(setup: &str) -> TestDatabase {
+    let container = Postgres::default().with_tag("16-alpine").start().await.expect("could not start Postgres, is Docker running?");
+    let host = container.get_host().await.unwrap();
+    let port = container.get_host_port_ipv4(5432).await.unwrap();
+    let connection_string = format!("host={} port={} user=postgres password=postgres dbname=postgres", host, port);
+
+    let db = PostgresDatabase::connect(&connection_string, &TlsSettings::default(), &SshSettings::default(), &NetworkSettings::default())
+        .await
+        .unwrap();
+    db.execute_batch(setup).await.unwrap();
+    TestDatabase { db, connection_string, _container: container }
+}
+
+/// A plain client of the test server, for what the backend does not expose.
+async fn raw_client(test: &TestDatabase) -> tokio_postgres::Client {
+    let (client, connection) = tokio_postgres::connect(&test.connection_string, tokio_postgres::NoTls).await.unwrap();
+    tokio::spawn(connection);
+    client
+}
+
+fn column<'a>(columns: &'a [super::ColumnInfo], name: &str) -> (usize, &'a super::ColumnInfo) {
+    columns.iter().enumerate().find(|(_, c)| c.name == name).unwrap_or_else(|| panic!("no column {}", name))
+}
+
+#[tokio::test]
+async fn test_query_table_converts_types() {
+    let test = start(
+        "CREATE TYPE mood AS ENUM ('sad', 'happy');
+        CREATE TABLE exotic (
+            id serial PRIMARY KEY,
+            small int2,
+            big int8,
+            ratio real,
+            precise double precision,
+            flag boolean,
+            token uuid,
+            day date,
+            at timestamp,
+            at_tz timestamptz,
+            doc jsonb,
+            raw bytea,
+            label varchar(20),
+            address inet,
+            network cidr,
+            mac macaddr,
+            amount numeric(12, 2),
+            span interval,
+            mood mood,
+            moods mood[],
+            spot point,
+            tags text[],
+            scores int4[],
+            nothing text
+        );
+        INSERT INTO exotic (small, big, ratio, precise, flag, token, day, at, at_tz, doc, raw, label, address, network, mac, amount, span, mood, moods, spot, tags, scores, nothing)
+        VALUES (7, 9007199254740993, 0.1, 2.5, true, 'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11', '2024-02-29',
+                '2024-02-29 13:45:00', '2024-02-29 13:45:00+00', '{\"a\": [1, 2]}', '\\x00ff', 'héllo', '10.0.0.1', '10.0.0.0/8', '08:00:2b:01:02:03', 1234567890.50,
+                '1 year 2 mons 3 days 04:05:06.5', 'happy', '{sad,happy}', '(1,2)', '{a,\"b c\",NULL}', '{1,2}', NULL);",
+    )
+    .await;
+
+    let (columns, rows) = test.db.query_table("public.exotic", None, None, 100).await.unwrap();
+    assert_eq!(rows.len(), 1);
+    let value = |name: &str| rows[0][column(&columns, name).0].clone();
+
+    assert!(column(&columns, "id").1.is_primary_key);
+    assert_eq!(column(&columns, "label").1.data_type, "character varying(20)");
+
+    let day = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+    assert_eq!(value("id"), CellValue::Int(1));
+    assert_eq!(value("small"), CellValue::Int(7));
+    // Past the f64 integer range, so a float detour would show up here
+    assert_eq!(value("big"), CellValue::Int(9007199254740993));
+    assert_eq!(value("ratio"), CellValue::Float(0.1));
+    assert_eq!(value("precise"), CellValue::Float(2.5));
+    assert_eq!(value("flag"), CellValue::Bool(true));
+    assert_eq!(value("token"), CellValue::Uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11".parse().unwrap()));
+    assert_eq!(value("day"), CellValue::Date(day));
+    assert_eq!(value("at"), CellValue::Timestamp(day.and_hms_opt(13, 45, 0).unwrap()));
+    assert_eq!(value("at_tz"), CellValue::TimestampTz(Utc.with_ymd_and_hms(2024, 2, 29, 13, 45, 0).unwrap()));
+    assert_eq!(value("doc"), CellValue::Json("{\"a\":[1,2]}".to_string()));
+    assert_eq!(value("raw"), CellValue::Bytes(vec![0x00, 0xff]));
+    assert_eq!(value("label"), CellValue::from("héllo"));
+    assert_eq!(value("tags"), CellValue::Array(vec![CellValue::from("a"), CellValue::from("b c"), CellValue::Null]));
+    assert_eq!(value("tags").to_string(), "{a, \"b c\", NULL}");
+    assert_eq!(value("scores"), CellValue::Array(vec![CellValue::Int(1), CellValue::Int(2)]));
+    assert_eq!(value("address"), CellValue::from("10.0.0.1"));
+    assert_eq!(value("network"), CellValue::from("10.0.0.0/8"));
+    assert_eq!(value("mac"), CellValue::from("08:00:2b:01:02:03"));
+    assert_eq!(value("amount"), CellValue::from("1234567890.50"));
+    assert_eq!(value("span"), CellValue::from("1 year 2 mons 3 days 04:05:06.5"));
+    assert_eq!(value("mood"), CellValue::from("happy"));
+    assert_eq!(value("moods"), CellValue::Array(vec![CellValue::from("sad"), CellValue::from("happy")]));
+    // No decoder: the type name stands in for the value
+    assert_eq!(value("spot"), CellValue::from("<point>"));
+    assert_eq!(value("nothing"), CellValue::Null);
+}
+
+#[tokio::test]
+async fn test_csv_export_of_table() {
+    let test = start(
+        "CREATE TABLE people (id int PRIMARY KEY, name text, born date);
+        INSERT INTO people VALUES (1, 'Ann, the first', '1990-01-02'), (2, NULL, NULL);",
+    )
+    .await;
+
+    let (columns, rows) = test.db.query_table("public.people", None, None, 100).await.unwrap();
+    let mut settings = ExportSettings { date_format: "%d/%m/%Y".to_string(), ..ExportSettings::default() };
+    let path = std::env::temp_dir().join(format!("integration-export-{}.csv", std::process::id()));
+    let job = |settings: ExportSettings| ExportJob {
+        format: ExportFormat::CSV,
+        settings,
+        columns: columns.clone(),
+        rows: rows.clone(),
+        header_comment: None,
+        footer_comment: None,
+    };
+
+    assert_eq!(job(settings.clone()).write_to(&path).unwrap(), 2);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "id,name,born\n1,\"Ann, the first\",02/01/1990\n2,,\n");
+
+    settings.date_format.clear();
+    settings.delimited.null_value = "NULL".to_string();
+    job(settings).write_to(&path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "id,name,born\n1,\"Ann, the first\",1990-01-02\n2,NULL,NULL\n");
+    std::fs::remove_file(&path).ok();
+}
+
+#[tokio::test]
+async fn test_schema_metadata() {
+    let test = start(
+        "CREATE TABLE customers (id int PRIMARY KEY, email text UNIQUE);
+        CREATE TABLE orders (
+            id int PRIMARY KEY,
+            customer_id int REFERENCES customers (id),
+            total numeric(10, 2) CHECK (total >= 0)
+        );
+        CREATE INDEX orders_customer_idx ON orders (customer_id);
+        CREATE VIEW big_orders AS SELECT id, total FROM orders WHERE total > 100;
+        CREATE MATERIALIZED VIEW order_totals AS SELECT customer_id, sum(total) AS total FROM orders GROUP BY customer_id;
+        CREATE FUNCTION order_count(customer int) RETURNS bigint LANGUAGE sql AS 'SELECT count(*) FROM orders WHERE customer_id = customer';
+        CREATE SEQUENCE invoice_numbers;
+        CREATE FUNCTION touch() RETURNS trigger LANGUAGE plpgsql AS $$ BEGIN RETURN NEW; END $$;
+        CREATE TRIGGER orders_touch BEFORE UPDATE ON orders FOR EACH ROW EXECUTE FUNCTION touch();",
+    )
+    .await;
+
+    let schemas = test.db.list_schemas_with_tables().await.unwrap();
+    let public = schemas.iter().find(|s| s.name == "public").expect("public schema listed");
+    assert_eq!(public.tables, vec!["customers", "orders"]);
+    assert_eq!(public.views, vec!["big_orders"]);
+    assert_eq!(public.materialized_views, vec!["order_totals"]);
+    assert_eq!(public.sequences, vec!["invoice_numbers"]);
+    let order_count = public.functions.iter().find(|f| f.name == "order_count").expect("function listed");
+    assert_eq!(order_count.signature(), "order_count(customer integer) → bigint");
+    // Materialized views have no information_schema columns
+    let matview_columns: Vec<_> = public.table_columns["order_totals"].iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(matview_columns, vec!["customer_id", "total"]);
+    let orders = &public.table_columns["orders"];
+    assert!(orders.iter().any(|c| c.name == "id" && c.is_primary_key));
+    assert!(orders.iter().any(|c| c.name == "customer_id" && c.is_foreign_key));
+
+    let target = test.db.foreign_key_target("public", "orders", "customer_id").await.unwrap().expect("foreign key found");
+    assert_eq!((target.schema.as_str(), target.table.as_str(), target.column.as_str()), ("public", "customers", "id"));
+    assert!(test.db.foreign_key_target("public", "orders", "total").await.unwrap().is_none());
+
+    let structure = test.db.table_structure("public", "orders").await.unwrap();
+    assert!(structure.indexes.iter().any(|i| i.name == "orders_customer_idx"));
+    assert!(structure.constraints.iter().any(|c| c.definition.contains("REFERENCES customers(id)")));
+    assert!(structure.constraints.iter().any(|c| c.definition.starts_with("CHECK")));
+    assert!(structure.triggers.iter().any(|t| t.name == "orders_touch"));
+
+    let view = test.db.object_source("public", &SourceObject::View("big_orders".to_string())).await.unwrap();
+    assert!(view.starts_with("CREATE OR REPLACE VIEW \"public\".\"big_orders\" AS"));
+    let function = test.db.object_source("public", &SourceObject::Function(order_count.clone())).await.unwrap();
+    assert!(function.contains("CREATE OR REPLACE FUNCTION public.order_count(customer integer)"));
+}
+
+#[tokio::test]
+async fn test_table_privileges() {
+    let test = start(
+        "CREATE TABLE notes (id int PRIMARY KEY, body text);
+        CREATE ROLE reader;
+        GRANT USAGE ON SCHEMA public TO reader;
+        GRANT SELECT, INSERT ON notes TO reader;
+        GRANT UPDATE (body) ON notes TO reader;
+        SET ROLE reader;",
+    )
+    .await;
+
+    let schemas = test.db.list_schemas_with_tables().await.unwrap();
+    let public = schemas.iter().find(|s| s.name == "public").expect("public schema listed");
+    // A column-level UPDATE grant is enough to edit that column
+    assert_eq!(public.table_privileges["notes"], TablePrivileges { insert: true, update: true, delete: false, truncate: false });
+    assert!(public.denied("notes", Privilege::Delete).is_some());
+}
+
+#[tokio::test]
+async fn test_statements_and_timeouts() {
+    let test = start("CREATE TABLE items (id int PRIMARY KEY, qty int); INSERT INTO items VALUES (1, 1), (2, 2), (3, 3);").await;
+
+    let statements = split_statements("UPDATE items SET qty = qty + 1 WHERE id > 1;\nSELECT * FROM items WHERE id > 10;\nSELEC 1;\nSELECT 1;");
+    let runs = run_statements(&test.db, statements, StatementTimeout::None).await;
+    // Stops at the syntax error
+    assert_eq!(runs.len(), 3);
+    assert!(matches!(runs[0].outcome, Ok(StatementOutcome::Affected(2))));
+    // An empty result still knows its columns
+    match &runs[1].outcome {
+        Ok(StatementOutcome::Rows((columns, rows))) => {
+            assert_eq!(columns.len(), 2);
+            assert!(rows.is_empty());
+        }
+        other => panic!("expected rows, got {:?}", other),
+    }
+    assert!(runs[2].outcome.is_err());
+
+    // Writes report a row count instead of opening an empty result
+    let stream = std::sync::Mutex::new(QueryStream::default());
+    test.db.stream_query("DELETE FROM items WHERE id = 3", &[], StatementTimeout::Seconds5, &stream).await.unwrap();
+    let stream = stream.into_inner().unwrap();
+    assert_eq!((stream.affected_rows, stream.columns.is_none()), (Some(1), true));
+
+    // Parameters are sent as text and parsed into the type the server inferred
+    let stream = std::sync::Mutex::new(QueryStream::default());
+    let params = [Some("2".to_string()), Some("it's".to_string()), None];
+    test.db.stream_query("SELECT id, $2::text, $3::int FROM items WHERE id = $1", &params, StatementTimeout::None, &stream).await.unwrap();
+    let rows = stream.into_inner().unwrap().take_rows();
+    assert_eq!(rows, vec![vec![CellValue::Int(2), CellValue::Text("it's".to_string()), CellValue::Null]]);
+
+    let error = test.db.execute_query("SELECT pg_sleep(10)", StatementTimeout::Seconds5).await.unwrap_err();
+    assert!(is_timeout(&error));
+    // The limit is put back after the statement, even a failed one
+    let (_, rows) = test.db.execute_query("SHOW statement_timeout", StatementTimeout::None).await.unwrap();
+    assert_eq!(rows, vec![vec![CellValue::from("0")]]);
+
+    // Statements that cannot run in a transaction block can be limited too, and the
+    // session's own limit comes back afterwards
+    test.db.execute_batch("SET statement_timeout = '1min'").await.unwrap();
+    test.db.execute_statement("VACUUM items", StatementTimeout::Seconds5).await.unwrap();
+    let (_, rows) = test.db.execute_query("SHOW statement_timeout", StatementTimeout::None).await.unwrap();
+    assert_eq!(rows, vec![vec![CellValue::from("1min")]]);
+}
+
+#[tokio::test]
+async fn test_manual_transaction() {
+    let test = start("CREATE TABLE items (id int PRIMARY KEY, qty int); INSERT INTO items VALUES (1, 1);").await;
+    let qty = || async { test.db.execute_query("SELECT qty FROM items WHERE id = 1", StatementTimeout::None).await.unwrap().1 };
+
+    test.db.transaction(TransactionControl::Begin).await.unwrap();
+    // Neither the timeout wrapper nor the grid edits may commit the open transaction
+    test.db.execute_query("UPDATE items SET qty = 2 WHERE id = 1", StatementTimeout::Seconds5).await.unwrap();
+    assert!(test.db.apply_updates(&["UPDATE items SET qty = 3 WHERE id > 0".to_string()]).await.is_ok());
+    assert!(test.db.apply_updates(&["UPDATE items SET qty = 4 WHERE id > 1".to_string()]).await.is_err());
+    assert_eq!(qty().await, vec![vec![CellValue::Int(3)]]);
+    test.db.transaction(TransactionControl::Rollback).await.unwrap();
+    assert_eq!(qty().await, vec![vec![CellValue::Int(1)]]);
+
+    test.db.transaction(TransactionControl::Begin).await.unwrap();
+    test.db.execute_batch("UPDATE items SET qty = 5").await.unwrap();
+    test.db.transaction(TransactionControl::Commit).await.unwrap();
+    assert_eq!(qty().await, vec![vec![CellValue::Int(5)]]);
+}
+
+#[tokio::test]
+async fn test_transaction_status() {
+    let test = start("CREATE TABLE items (id int PRIMARY KEY);").await;
+    assert_eq!(test.db.transaction_status().await.unwrap(), Some(TransactionStatus::Idle));
+
+    // A typed BEGIN is seen too
+    test.db.execute_batch("BEGIN").await.unwrap();
+    test.db.execute_batch("INSERT INTO items VALUES (1)").await.unwrap();
+    assert!(matches!(test.db.transaction_status().await.unwrap(), Some(TransactionStatus::InTransaction(_))));
+    assert!(test.db.execute_batch("SELECT 1 / 0").await.is_err());
+    assert_eq!(test.db.transaction_status().await.unwrap(), Some(TransactionStatus::Aborted));
+    test.db.transaction(TransactionControl::Rollback).await.unwrap();
+    assert_eq!(test.db.transaction_status().await.unwrap(), Some(TransactionStatus::Idle));
+}
+
+#[tokio::test]
+async fn test_alter_plan_in_transaction() {
+    let test = start("CREATE TABLE people (id int PRIMARY KEY, age text, nick text); INSERT INTO people VALUES (1, ' 42 ', NULL), (2, '', 'x');").await;
+    let columns = test.db.list_schemas_with_tables().await.unwrap()
+        .into_iter()
+        .find(|s| s.name == "public")
+        .map(|s| s.table_columns["people"].clone())
+        .unwrap();
+
+    let mut plan = AlterPlan::new("public".to_string(), "people".to_string(), &columns);
+    plan.columns[1].new_type = "integer".to_string();
+    plan.columns[1].new_name = "age_years".to_string();
+    plan.columns[2].nullability = NullabilityChange::SetNotNull;
+    // The NULL nick fails SET NOT NULL, so the type change and rename roll back too
+    assert!(test.db.execute_in_transaction(&plan.statements().unwrap()).await.is_err());
+    let (columns, _) = test.db.execute_query("SELECT * FROM people", StatementTimeout::None).await.unwrap();
+    assert_eq!(columns[1].name, "age");
+
+    plan.columns[2].nullability = NullabilityChange::Keep;
+    test.db.execute_in_transaction(&plan.statements().unwrap()).await.unwrap();
+    let (_, rows) = test.db.execute_query("SELECT age_years FROM people ORDER BY id", StatementTimeout::None).await.unwrap();
+    assert_eq!(rows, vec![vec![CellValue::Int(42)], vec![CellValue::Null]]);
+}
+
+// A SQL dump of `public`'s `tables`, rows read as the backend streams them
+async fn dump_tables(db: &PostgresDatabase, dump: &SqlDump, tables: &[&str]) -> String {
+    let mut sql = dump.header(Some("public"));
+    let mut foreign_keys = Vec::new();
+    for table in tables {
+        let definition = db.table_definition("public", table).await.unwrap();
+        let mut table_dump = dump.table(&mut sql, "public", table, &definition);
+        db.dump_rows("public", table, &definition.columns, &mut |rows| {
+            table_dump.push_rows(&mut sql, &rows);
+            Ok(())
+        }).await.unwrap();
+        table_dump.finish(&mut sql);
+        foreign_keys.extend(definition.foreign_keys.clone());
+    }
+    sql.push_str(&dump.footer(&foreign_keys));
+    sql
+}
+
+#[tokio::test]
+async fn test_sql_dump_round_trip() {
+    let test = start(
+        "CREATE TABLE users (id int PRIMARY KEY, name text NOT NULL DEFAULT 'anonymous');
+        CREATE TABLE orders (
+            id serial PRIMARY KEY,
+            number bigint GENERATED ALWAYS AS IDENTITY,
+            user_id int REFERENCES users (id),
+            total numeric(10,2) CHECK (total >= 0),
+            tax numeric GENERATED ALWAYS AS (total * 0.2) STORED
+        );
+        CREATE INDEX orders_total_idx ON orders (total);
+        INSERT INTO users VALUES (1, 'Ann');
+        INSERT INTO orders (user_id, total) VALUES (1, 10), (NULL, 2.5);",
+    )
+    .await;
+
+    // Referencing tables first: foreign keys are only added at the end
+    let dump = SqlDump { driver: Driver::Postgres, rows: DumpRows::Insert, create_tables: true };
+    let sql = dump_tables(&test.db, &dump, &["orders", "users"]).await;
+
+    test.db.execute_batch("DROP TABLE orders, users").await.unwrap();
+    test.db.execute_batch(&sql).await.unwrap();
+    let (_, rows) = test.db.execute_query("SELECT id, number, user_id, tax::text FROM orders ORDER BY id", StatementTimeout::None).await.unwrap();
+    assert_eq!(rows, vec![
+        vec![CellValue::Int(1), CellValue::Int(1), CellValue::Int(1), CellValue::from("2.000")],
+        vec![CellValue::Int(2), CellValue::Int(2), CellValue::Null, CellValue::from("0.500")],
+    ]);
+
+    // Sequences continue after the loaded rows, and the constraints came back
+    test.db.execute_batch("INSERT INTO orders (total) VALUES (1)").await.unwrap();
+    let (_, rows) = test.db.execute_query("SELECT id, number FROM orders WHERE total = 1", StatementTimeout::None).await.unwrap();
+    assert_eq!(rows, vec![vec![CellValue::Int(3), CellValue::Int(3)]]);
+    assert!(test.db.execute_batch("INSERT INTO orders (user_id) VALUES (2)").await.is_err());
+    assert!(test.db.execute_batch("INSERT INTO orders (total) VALUES (-1)").await.is_err());
+    let structure = test.db.table_structure("public", "orders").await.unwrap();
+    assert!(structure.indexes.iter().any(|index| index.name == "orders_total_idx"));
+}
+
+#[tokio::test]
+async fn test_sql_dump_keeps_server_text() {
+    let test = start(
+        "CREATE EXTENSION citext;
+        CREATE TABLE odd (id int, at time, price money, name citext, blobs bytea[], span interval, ratio float8);
+        INSERT INTO odd VALUES
+            (1, '23:59:59.5', 12.34, 'MiXeD', ARRAY['\\x010203'::bytea, NULL], '1 day 02:00', 'NaN'),
+            (2, NULL, NULL, NULL, '{}', NULL, '-Infinity');",
+    )
+    .await;
+    let select = "SELECT id, at::text, price::text, name::text, blobs::text, span::text, ratio::text FROM odd ORDER BY id";
+    let (_, before) = test.db.execute_query(select, StatementTimeout::None).await.unwrap();
+    assert_eq!(before[0][4], CellValue::from("{\"\\\\x010203\",NULL}"));
+
+    let dump = SqlDump { driver: Driver::Postgres, rows: DumpRows::Insert, create_tables: true };
+    let sql = dump_tables(&test.db, &dump, &["odd"]).await;
+    test.db.execute_batch("DROP TABLE odd").await.unwrap();
+    test.db.execute_batch(&sql).await.unwrap();
+    let (_, after) = test.db.execute_query(select, StatementTimeout::None).await.unwrap();
+    assert_eq!(after, before);
+
+    // COPY blocks only load through psql, so check the line itself
+    let dump = SqlDump { rows: DumpRows::Copy, ..dump };
+    let sql = dump_tables(&test.db, &dump, &["odd"]).await;
+    assert!(sql.contains("1\t23:59:59.5\t$12.34\tMiXeD\t{\"\\\\\\\\x010203\",NULL}\t1 day 02:00:00\tNaN\n"));
+}
+
+#[tokio::test]
+async fn test_copy_dump_round_trip() {
+    let test = start(
+        "CREATE TABLE odd (id int, body text, blob bytea, tags text[], at timestamptz);
+        INSERT INTO odd VALUES
+            (1, 'tab\there\\ and\r\nnewline', '\\x00ff', ARRAY['a b', NULL, '\\N'], '2024-03-05 12:00+02'),
+            (2, NULL, NULL, '{}', NULL),
+            (3, '', '\\x', ARRAY[''], 'infinity');",
+    )
+    .await;
+    let client = raw_client(&test).await;
+
+    // The dump's COPY block is what the server itself writes with COPY TO STDOUT
+    let dump = SqlDump { driver: Driver::Postgres, rows: DumpRows::Copy, create_tables: false };
+    let sql = dump_tables(&test.db, &dump, &["odd"]).await;
+    let (_, block) = sql.split_once("FROM STDIN;\n").unwrap();
+    let (block, _) = block.split_once("\\.\n").unwrap();
+    let stream = client.copy_out("COPY (SELECT * FROM odd ORDER BY id) TO STDOUT").await.unwrap();
+    pin_mut!(stream);
+    let mut server = Vec::new();
+    while let Some(chunk) = stream.try_next().await.unwrap() {
+        server.extend_from_slice(&chunk);
+    }
+    assert_eq!(block, String::from_utf8(server).unwrap());
+
+    // And loads back into the same rows
+    let select = "SELECT id, body, encode(blob, 'hex'), tags::text, at::text FROM odd ORDER BY id";
+    let (_, before) = test.db.execute_query(select, StatementTimeout::None).await.unwrap();
+    test.db.execute_batch("TRUNCATE odd").await.unwrap();
+    let sink = client.copy_in("COPY odd FROM STDIN").await.unwrap();
+    pin_mut!(sink);
+    sink.send(bytes::Bytes::from(block.to_string())).await.unwrap();
+    assert_eq!(sink.finish().await.unwrap(), 3);
+    let (_, after) = test.db.execute_query(select, StatementTimeout::None).await.unwrap();
+    assert_eq!(after, before);
+}
+
+#[tokio::test]
+async fn test_copy_in() {
+    let test = start("CREATE TABLE notes (id int PRIMARY KEY, body text, at date);").await;
+    let columns = vec!["id".to_string(), "body".to_string()];
+    let sent = AtomicUsize::new(0);
+    let mut rows: Vec<Vec<Option<String>>> = (1..=2500).map(|id| vec![Some(id.to_string()), None]).collect();
+    rows[0][1] = Some("tab\there\\ and\r\nnewline".to_string());
+
+    assert_eq!(test.db.copy_in("public", "notes", &columns, &mut rows.into_iter().map(Ok), &sent).await.unwrap(), 2500);
+    assert_eq!(sent.load(Ordering::Relaxed), 2500);
+    let (_, copied) = test.db.execute_query("SELECT body FROM notes WHERE id IN (1, 2) ORDER BY id", StatementTimeout::None).await.unwrap();
+    assert_eq!(copied, vec![vec![CellValue::from("tab\there\\ and\r\nnewline")], vec![CellValue::Null]]);
+
+    // A rejected row loads none of them
+    let rows = vec![vec![Some("5000".to_string()), None], vec![Some("not a number".to_string()), None]];
+    assert!(test.db.copy_in("public", "notes", &columns, &mut rows.into_iter().map(Ok), &AtomicUsize::new(0)).await.is_err());
+    // And so does a file that turns out unreadable after the first batch went out
+    let mut rows = (5000..6500).map(|id| match id {
+        6200 => Err(anyhow::anyhow!("Invalid CSV")),
+        id => Ok(vec![Some(id.to_string()), None]),
+    });
+    assert!(test.db.copy_in("public", "notes", &columns, &mut rows, &AtomicUsize::new(0)).await.is_err());
+    let (_, count) = test.db.execute_query("SELECT count(*) FROM notes", StatementTimeout::None).await.unwrap();
+    assert_eq!(count, vec![vec![CellValue::Int(2500)]]);
+}
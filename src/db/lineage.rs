@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+// Views nested deeper than this are not followed further
+const MAX_DEPTH: usize = 16;
+
+/// Base column a view depends on, as recorded in `pg_depend` for the view's rewrite rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceColumn {
+    pub schema: String,
+    pub relation: String,
+    pub column: String,
+}
+
+impl SourceColumn {
+    fn qualified(&self) -> String {
+        format!("{}.{}.{}", self.schema, self.relation, self.column)
+    }
+}
+
+/// Columns each view depends on, keyed by (schema, view).
+pub type ViewDependencies = HashMap<(String, String), Vec<SourceColumn>>;
+
+/// Where a view's output columns come from: `schema.relation.column` hops, nearest first.
+pub type ColumnLineage = HashMap<String, Vec<String>>;
+
+/// Trace each output column of `view` back through layered views to where it comes from.
+///
+/// PostgreSQL records which columns a view reads, not which output column each one feeds,
+/// so a column is matched to a source column of the same name. The result maps column names
+/// to the chain of hops, nearest first; computed or renamed columns are left out.
+pub fn resolve_lineage(
+    schema: &str,
+    view: &str,
+    columns: &[String],
+    dependencies: &ViewDependencies,
+) -> ColumnLineage {
+    columns
+        .iter()
+        .filter_map(|column| {
+            let chain = trace(schema, view, column, dependencies);
+            (!chain.is_empty()).then(|| (column.clone(), chain))
+        })
+        .collect()
+}
+
+fn trace(schema: &str, view: &str, column: &str, dependencies: &ViewDependencies) -> Vec<String> {
+    let mut chain = Vec::new();
+    let mut current = (schema.to_string(), view.to_string());
+
+    while chain.len() < MAX_DEPTH {
+        let Some(sources) = dependencies.get(&current) else {
+            break;
+        };
+        let candidates: Vec<&SourceColumn> = sources.iter().filter(|s| s.column == column).collect();
+        match candidates.as_slice() {
+            [] => break,
+            [source] => {
+                chain.push(source.qualified());
+                current = (source.schema.clone(), source.relation.clone());
+            }
+            // Same column name in several joined relations: report them and stop
+            several => {
+                let names: Vec<String> = several.iter().map(|s| s.qualified()).collect();
+                chain.push(names.join(" or "));
+                break;
+            }
+        }
+    }
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(schema: &str, relation: &str, column: &str) -> SourceColumn {
+        SourceColumn { schema: schema.to_string(), relation: relation.to_string(), column: column.to_string() }
+    }
+
+    fn key(schema: &str, view: &str) -> (String, String) {
+        (schema.to_string(), view.to_string())
+    }
+
+    #[test]
+    fn test_follows_layered_views() {
+        let mut dependencies = ViewDependencies::new();
+        dependencies.insert(key("reports", "monthly"), vec![source("public", "orders_v", "total"), source("public", "orders_v", "month")]);
+        dependencies.insert(key("public", "orders_v"), vec![source("public", "orders", "total"), source("public", "orders", "month")]);
+
+        let columns = vec!["total".to_string(), "order_count".to_string()];
+        let lineage = resolve_lineage("reports", "monthly", &columns, &dependencies);
+
+        assert_eq!(lineage.len(), 1);
+        assert_eq!(lineage["total"], vec!["public.orders_v.total", "public.orders.total"]);
+    }
+
+    #[test]
+    fn test_ambiguous_columns_list_every_candidate() {
+        let mut dependencies = ViewDependencies::new();
+        dependencies.insert(key("public", "joined"), vec![source("public", "a", "id"), source("public", "b", "id")]);
+
+        let lineage = resolve_lineage("public", "joined", &["id".to_string()], &dependencies);
+        assert_eq!(lineage["id"], vec!["public.a.id or public.b.id"]);
+    }
+
+    #[test]
+    fn test_tables_have_no_lineage() {
+        let lineage = resolve_lineage("public", "users", &["id".to_string()], &ViewDependencies::new());
+        assert!(lineage.is_empty());
+    }
+}
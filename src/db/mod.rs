@@ -1,7 +1,33 @@
 mod models;
+mod backend;
 mod client;
+mod pg_types;
+mod lineage;
+mod mysql;
 mod operations;
+mod params;
+mod privileges;
+pub mod routing;
+pub mod sql;
+mod timeout;
+mod tls;
+mod ssh;
+mod proxy;
+mod report;
+mod script;
+mod stream;
+#[cfg(all(test, feature = "integration-tests"))]
+mod integration_tests;
 
-pub use models::{ColumnInfo, SchemaInfo};
-pub use client::Database;
-pub use operations::AsyncOperation;
+pub use models::{ColumnInfo, ForeignKeyTarget, FunctionInfo, SchemaInfo, SourceObject, TableDefinition, TableObject, TableStructure};
+pub use backend::{cancel, connect, CancelHandle, DatabaseBackend, ResultSet, StatementOutcome, TransactionControl, TransactionStatus};
+pub use client::{is_cancelled, is_timeout};
+pub use lineage::ColumnLineage;
+pub use operations::{AsyncOperation, PlanDestination};
+pub use params::{bind_placeholders, placeholders};
+pub use privileges::{Privilege, TablePrivileges};
+pub use routing::QueryTarget;
+pub use timeout::StatementTimeout;
+pub use report::ResourceReport;
+pub use script::{command_tag, run_script, run_statements, split_statements, ScriptProgress, StatementRun, StatementSummary};
+pub use stream::{QueryStream, STREAM_BATCH_SIZE};
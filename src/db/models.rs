@@ -1,17 +1,139 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use crate::db::{Privilege, TablePrivileges};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SchemaInfo {
     pub name: String,
-    pub tables: Vec<String>,
+    pub tables: Vec<String>, // base tables only
+    pub views: Vec<String>,
+    pub materialized_views: Vec<String>,
+    pub functions: Vec<FunctionInfo>, // functions and procedures
+    pub sequences: Vec<String>,
+    // Columns of tables, views and materialized views, by name
     pub table_columns: HashMap<String, Vec<ColumnInfo>>,
+    // Grants of the connected role on base tables, by name. Missing when the server
+    // could not tell, in which case every action is offered and the server decides.
+    #[serde(skip)]
+    pub table_privileges: HashMap<String, TablePrivileges>,
+    // Row counts of tables and materialized views as last estimated by the server's
+    // statistics, by name; missing when it has none
+    pub row_estimates: HashMap<String, u64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl SchemaInfo {
+    pub fn new(name: String) -> Self {
+        Self { name, ..Self::default() }
+    }
+
+    /// Everything rows can be selected from: tables, views and materialized views.
+    pub fn relations(&self) -> impl Iterator<Item = &String> {
+        self.tables.iter().chain(&self.views).chain(&self.materialized_views)
+    }
+
+    pub fn estimated_rows(&self, table: &str) -> Option<u64> {
+        self.row_estimates.get(table).copied()
+    }
+
+    /// Why `privilege` on `table` is out of reach, for the tooltip of the action it
+    /// disables. None when granted or unknown.
+    pub fn denied(&self, table: &str, privilege: Privilege) -> Option<String> {
+        let privileges = self.table_privileges.get(table)?;
+        (!privileges.allows(privilege)).then(|| format!(
+            "Needs the {} privilege on {}.{}, which the current role has not been granted",
+            privilege.as_str(), self.name, table
+        ))
+    }
+}
+
+/// Function or stored procedure; overloads are told apart by their arguments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionInfo {
+    pub name: String,
+    pub arguments: String, // e.g. "user_id integer, active boolean"
+    pub result: Option<String>, // None for procedures
+    pub is_procedure: bool,
+}
+
+impl FunctionInfo {
+    pub fn signature(&self) -> String {
+        match &self.result {
+            Some(result) => format!("{}({}) → {}", self.name, self.arguments, result),
+            None => format!("{}({})", self.name, self.arguments),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ColumnInfo {
     pub name: String,
     pub data_type: String,
     pub is_primary_key: bool,
     pub is_foreign_key: bool,
 }
+
+#[cfg(test)]
+impl ColumnInfo {
+    /// A column of `data_type` that is neither a primary nor a foreign key.
+    pub fn test(name: &str, data_type: &str) -> Self {
+        Self { name: name.to_string(), data_type: data_type.to_string(), is_primary_key: false, is_foreign_key: false }
+    }
+}
+
+/// Schema object whose defining SQL can be opened from the tree.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SourceObject {
+    View(String),
+    MaterializedView(String),
+    Function(FunctionInfo),
+}
+
+impl SourceObject {
+    pub fn name(&self) -> &str {
+        match self {
+            SourceObject::View(name) | SourceObject::MaterializedView(name) => name,
+            SourceObject::Function(function) => &function.name,
+        }
+    }
+}
+
+/// Column a foreign key column points at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignKeyTarget {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+}
+
+/// Index, constraint or trigger of a table together with the SQL that defines it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableObject {
+    pub name: String,
+    pub kind: String, // e.g. UNIQUE, CHECK, BEFORE INSERT
+    pub definition: String,
+}
+
+/// What a SQL dump needs to recreate a table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableDefinition {
+    // CREATE TABLE statement, without the closing semicolon
+    pub create: String,
+    // Columns the dump gives values for, in table order; generated columns are left out
+    pub columns: Vec<ColumnInfo>,
+    // Statements moving sequences past the dumped rows
+    pub after_rows: Vec<String>,
+    // CREATE INDEX for indexes the CREATE TABLE does not make, run once the rows are in
+    pub indexes: Vec<String>,
+    // Foreign keys, added after every table of the dump is loaded
+    pub foreign_keys: Vec<String>,
+    // INSERTs must override GENERATED ALWAYS identity columns
+    pub overriding_system_value: bool,
+}
+
+/// What the Structure view of a table tab lists besides the columns.
+#[derive(Debug, Clone, Default)]
+pub struct TableStructure {
+    pub indexes: Vec<TableObject>,
+    pub constraints: Vec<TableObject>,
+    pub triggers: Vec<TableObject>,
+}
@@ -0,0 +1,860 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use mysql_async::consts::ColumnType;
+use mysql_async::prelude::{Protocol, Queryable};
+use mysql_async::{Column, Conn, Opts, OptsBuilder, Params, QueryResult, Row, SslOpts, Value};
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Mutex;
+use crate::config::{Driver, NetworkSettings, SshSettings, SslMode, TlsSettings};
+use super::backend::unit_of_work;
+use super::ssh::{open_tunnel, Tunnel};
+use super::sql::{dump_select, quote_literal_for, TableFilter, TableOrder};
+use crate::db::{CancelHandle, ColumnInfo, DatabaseBackend, ForeignKeyTarget, FunctionInfo, ResultSet, SchemaInfo, SourceObject, StatementOutcome, StatementTimeout, TableDefinition, TableObject, TablePrivileges, TableStructure, TransactionControl, STREAM_BATCH_SIZE};
+use crate::models::CellValue;
+
+// Databases that belong to the server itself
+const SYSTEM_SCHEMAS: &str = "'mysql', 'information_schema', 'performance_schema', 'sys'";
+
+// Server error codes for a statement stopped by `KILL QUERY` or a time limit
+const ER_QUERY_INTERRUPTED: u16 = 1317;
+const ER_QUERY_TIMEOUT: u16 = 3024; // MySQL max_execution_time
+const ER_STATEMENT_TIMEOUT: u16 = 1969; // MariaDB max_statement_time
+
+// Binary collation id, used by BLOB and VARBINARY columns
+const BINARY_CHARSET: u16 = 63;
+
+pub struct MySqlDatabase {
+    // MySQL connections handle one statement at a time, so calls take turns
+    conn: Mutex<Conn>,
+    opts: Opts,
+    thread_id: u32,
+    is_mariadb: bool,
+    // The query panel's manual transaction is open
+    in_transaction: AtomicBool,
+    // Keeps the SSH forward open for as long as the connection lives
+    _tunnel: Option<Tunnel>,
+}
+
+/// True when `error` is the server reporting that the statement was interrupted.
+pub(super) fn is_interrupted(error: &anyhow::Error) -> bool {
+    server_error_code(error)
+        .is_some_and(|code| matches!(code, ER_QUERY_INTERRUPTED | ER_QUERY_TIMEOUT | ER_STATEMENT_TIMEOUT))
+}
+
+/// True when the statement was interrupted because it hit the session time limit.
+pub(super) fn is_timed_out(error: &anyhow::Error) -> bool {
+    server_error_code(error).is_some_and(|code| matches!(code, ER_QUERY_TIMEOUT | ER_STATEMENT_TIMEOUT))
+}
+
+fn server_error_code(error: &anyhow::Error) -> Option<u16> {
+    match error.downcast_ref::<mysql_async::Error>()? {
+        mysql_async::Error::Server(e) => Some(e.code),
+        _ => None,
+    }
+}
+
+/// Build connection options from the same `key=value` string used for PostgreSQL.
+/// `hostaddr`, when present, is where to dial; `host` is still used for TLS verification.
+fn parse_connection_string(connection_string: &str, tls: &TlsSettings) -> Result<OptsBuilder> {
+    let params: HashMap<&str, &str> = connection_string
+        .split_whitespace()
+        .filter_map(|pair| pair.split_once('='))
+        .collect();
+
+    let host = params.get("host").copied().unwrap_or("localhost");
+    let port = match params.get("port") {
+        Some(port) => port.parse::<u16>().map_err(|_| anyhow::anyhow!("Invalid port: {}", port))?,
+        None => Driver::MySql.default_port(),
+    };
+    let non_empty = |key: &str| params.get(key).filter(|value| !value.is_empty()).map(|value| value.to_string());
+
+    let mut builder = OptsBuilder::default()
+        .ip_or_hostname(params.get("hostaddr").copied().unwrap_or(host))
+        .tcp_port(port)
+        .user(non_empty("user"))
+        .pass(non_empty("password"))
+        .db_name(non_empty("dbname"))
+        // Report matched rows for UPDATE, like PostgreSQL, even when values are unchanged
+        .client_found_rows(true);
+
+    if let Some(ssl_opts) = ssl_opts(tls, host)? {
+        builder = builder.ssl_opts(ssl_opts);
+    }
+    Ok(builder)
+}
+
+fn ssl_opts(tls: &TlsSettings, host: &str) -> Result<Option<SslOpts>> {
+    if !tls.client_cert_path.is_empty() {
+        anyhow::bail!("Client certificate authentication is not supported for MySQL connections");
+    }
+    let ca_cert_path = tls.ca_cert_path.trim();
+    Ok(match tls.ssl_mode {
+        SslMode::Disable => None,
+        // Check the chain against a set CA but not the host name, like libpq's verify-ca
+        SslMode::Prefer | SslMode::Require if tls.verifies_certificate() => Some(SslOpts::default()
+            .with_root_certs(vec![PathBuf::from(ca_cert_path).into()])
+            .with_danger_skip_domain_validation(true)),
+        // Encrypt without checking who is on the other end, like libpq's require
+        SslMode::Prefer | SslMode::Require => Some(SslOpts::default()
+            .with_danger_accept_invalid_certs(true)
+            .with_danger_skip_domain_validation(true)),
+        SslMode::VerifyFull => {
+            let mut opts = SslOpts::default().with_danger_tls_hostname_override(Some(host.to_string()));
+            if !ca_cert_path.is_empty() {
+                opts = opts.with_root_certs(vec![PathBuf::from(ca_cert_path).into()]);
+            }
+            Some(opts)
+        }
+    })
+}
+
+// Run `statements` as one unit of work, each expected to update exactly one row
+async fn update_rows(conn: &mut Conn, statements: &[String], [begin, commit, rollback]: [&str; 3]) -> Result<u64> {
+    conn.query_drop(begin).await?;
+    let mut updated = 0;
+    for statement in statements {
+        let error = match conn.query_drop(statement.as_str()).await {
+            Ok(()) if conn.affected_rows() == 1 => {
+                updated += 1;
+                continue;
+            }
+            Ok(()) => anyhow::anyhow!("Expected to update 1 row but {} matched: {}", conn.affected_rows(), statement),
+            Err(e) => e.into(),
+        };
+        // The statement's error is the one worth reporting
+        if let Err(e) = conn.query_drop(rollback).await {
+            eprintln!("Failed to roll back the updates: {}", e);
+        }
+        return Err(error);
+    }
+    conn.query_drop(commit).await?;
+    Ok(updated)
+}
+
+// Read a MySQL value into the same cell types the PostgreSQL backend produces
+fn value_to_cell(value: &Value, column: &Column) -> CellValue {
+    match value {
+        Value::NULL => CellValue::Null,
+        Value::Bytes(bytes) if column.character_set() == BINARY_CHARSET && is_blob_type(column.column_type()) => {
+            CellValue::Bytes(bytes.clone())
+        }
+        Value::Bytes(bytes) if column.column_type() == ColumnType::MYSQL_TYPE_JSON => {
+            CellValue::Json(String::from_utf8_lossy(bytes).to_string())
+        }
+        Value::Bytes(bytes) => CellValue::Text(String::from_utf8_lossy(bytes).to_string()),
+        Value::Int(v) => CellValue::Int(*v),
+        Value::UInt(v) => i64::try_from(*v).map(CellValue::Int).unwrap_or_else(|_| CellValue::Text(v.to_string())),
+        Value::Float(v) => CellValue::Float(v.to_string().parse().unwrap_or((*v).into())),
+        Value::Double(v) => CellValue::Float(*v),
+        Value::Date(year, month, day, hour, minute, second, micros) => {
+            let date = NaiveDate::from_ymd_opt(*year as i32, *month as u32, *day as u32);
+            let timestamp = date.and_then(|d| d.and_hms_micro_opt(*hour as u32, *minute as u32, *second as u32, *micros));
+            match (date, timestamp) {
+                (Some(date), _) if column.column_type() == ColumnType::MYSQL_TYPE_DATE => CellValue::Date(date),
+                (_, Some(timestamp)) if column.column_type() != ColumnType::MYSQL_TYPE_DATE => CellValue::Timestamp(timestamp),
+                // Zero dates like 0000-00-00 have no calendar value
+                _ => {
+                    let mut text = format!("{:04}-{:02}-{:02}", year, month, day);
+                    if column.column_type() != ColumnType::MYSQL_TYPE_DATE {
+                        text.push_str(&format!(" {:02}:{:02}:{:02}", hour, minute, second));
+                    }
+                    CellValue::Text(text)
+                }
+            }
+        }
+        // Durations may be negative or longer than a day, so they stay text
+        Value::Time(negative, days, hours, minutes, seconds, micros) => {
+            let hours = *days * 24 + *hours as u32;
+            let mut text = format!("{}{:02}:{:02}:{:02}", if *negative { "-" } else { "" }, hours, minutes, seconds);
+            if *micros > 0 {
+                text.push_str(&format!(".{:06}", micros));
+            }
+            CellValue::Text(text)
+        }
+    }
+}
+
+fn is_blob_type(column_type: ColumnType) -> bool {
+    matches!(
+        column_type,
+        ColumnType::MYSQL_TYPE_TINY_BLOB
+            | ColumnType::MYSQL_TYPE_MEDIUM_BLOB
+            | ColumnType::MYSQL_TYPE_LONG_BLOB
+            | ColumnType::MYSQL_TYPE_BLOB
+            | ColumnType::MYSQL_TYPE_VAR_STRING
+            | ColumnType::MYSQL_TYPE_STRING
+    )
+}
+
+/// SQL type name for a result column, as far as the wire protocol tells us.
+fn type_name(column_type: ColumnType) -> &'static str {
+    match column_type {
+        ColumnType::MYSQL_TYPE_TINY => "tinyint",
+        ColumnType::MYSQL_TYPE_SHORT => "smallint",
+        ColumnType::MYSQL_TYPE_INT24 => "mediumint",
+        ColumnType::MYSQL_TYPE_LONG => "integer",
+        ColumnType::MYSQL_TYPE_LONGLONG => "bigint",
+        ColumnType::MYSQL_TYPE_FLOAT => "float",
+        ColumnType::MYSQL_TYPE_DOUBLE => "double",
+        ColumnType::MYSQL_TYPE_DECIMAL | ColumnType::MYSQL_TYPE_NEWDECIMAL => "decimal",
+        ColumnType::MYSQL_TYPE_DATE => "date",
+        ColumnType::MYSQL_TYPE_TIME => "time",
+        ColumnType::MYSQL_TYPE_DATETIME => "datetime",
+        ColumnType::MYSQL_TYPE_TIMESTAMP => "timestamp",
+        ColumnType::MYSQL_TYPE_YEAR => "year",
+        ColumnType::MYSQL_TYPE_BIT => "bit",
+        ColumnType::MYSQL_TYPE_JSON => "json",
+        ColumnType::MYSQL_TYPE_ENUM => "enum",
+        ColumnType::MYSQL_TYPE_SET => "set",
+        ColumnType::MYSQL_TYPE_GEOMETRY => "geometry",
+        ColumnType::MYSQL_TYPE_TINY_BLOB
+        | ColumnType::MYSQL_TYPE_MEDIUM_BLOB
+        | ColumnType::MYSQL_TYPE_LONG_BLOB
+        | ColumnType::MYSQL_TYPE_BLOB => "blob",
+        ColumnType::MYSQL_TYPE_NULL => "null",
+        _ => "varchar",
+    }
+}
+
+// STATISTICS has one row per indexed column; `columns` is their list in index order,
+// None for indexes made only of expressions
+fn index_object(name: String, unique: bool, index_type: &str, columns: Option<&str>) -> TableObject {
+    let columns = columns.unwrap_or("<expression>");
+    let (kind, definition) = if name == "PRIMARY" {
+        ("PRIMARY", format!("PRIMARY KEY USING {} ({})", index_type, columns))
+    } else if unique {
+        ("UNIQUE", format!("UNIQUE INDEX {} USING {} ({})", quote_identifier(&name), index_type, columns))
+    } else {
+        ("INDEX", format!("INDEX {} USING {} ({})", quote_identifier(&name), index_type, columns))
+    };
+    TableObject { name, kind: kind.to_string(), definition }
+}
+
+fn column_info(col: &Column) -> ColumnInfo {
+    ColumnInfo {
+        name: col.name_str().to_string(),
+        data_type: type_name(col.column_type()).to_string(),
+        is_primary_key: false,
+        is_foreign_key: false,
+    }
+}
+
+// Result set if the statement has one, even when it is empty, otherwise the affected row count
+async fn statement_outcome(conn: &mut Conn, sql: &str, params: &[Option<String>]) -> Result<StatementOutcome> {
+    if params.is_empty() {
+        collect_outcome(conn.query_iter(sql).await?).await
+    } else {
+        let values = params.iter().map(|param| param.as_deref().map_or(Value::NULL, Value::from)).collect();
+        collect_outcome(conn.exec_iter(sql, Params::Positional(values)).await?).await
+    }
+}
+
+async fn collect_outcome<P: Protocol>(mut result: QueryResult<'_, '_, P>) -> Result<StatementOutcome> {
+    let columns = result.columns();
+    let rows: Vec<Row> = result.collect().await?;
+    let affected = result.affected_rows();
+    result.drop_result().await?;
+
+    Ok(match columns {
+        Some(columns) if !columns.is_empty() => {
+            let (_, data) = rows_to_result_set(&rows);
+            StatementOutcome::Rows((columns.iter().map(column_info).collect(), data))
+        }
+        _ => StatementOutcome::Affected(affected),
+    })
+}
+
+fn rows_to_result_set(rows: &[Row]) -> ResultSet {
+    let Some(first) = rows.first() else {
+        return (vec![], vec![]);
+    };
+
+    let columns: Vec<ColumnInfo> = first.columns_ref().iter().map(column_info).collect();
+
+    let data: Vec<Vec<CellValue>> = rows
+        .iter()
+        .map(|row| {
+            row.columns_ref()
+                .iter()
+                .enumerate()
+                .map(|(i, col)| value_to_cell(row.as_ref(i).unwrap_or(&Value::NULL), col))
+                .collect()
+        })
+        .collect();
+
+    (columns, data)
+}
+
+// GRANTEE as information_schema spells it, e.g. 'app'@'%' for CURRENT_USER() app@%
+fn grantee(current_user: &str) -> String {
+    let (user, host) = current_user.rsplit_once('@').unwrap_or((current_user, "%"));
+    format!("'{}'@'{}'", user, host)
+}
+
+// TRUNCATE TABLE needs DROP in MySQL
+fn table_privileges(granted: &HashSet<String>) -> TablePrivileges {
+    TablePrivileges {
+        insert: granted.contains("INSERT"),
+        update: granted.contains("UPDATE"),
+        delete: granted.contains("DELETE"),
+        truncate: granted.contains("DROP"),
+    }
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+fn quote_literal(value: &str) -> String {
+    quote_literal_for(Driver::MySql, value)
+}
+
+impl MySqlDatabase {
+    pub async fn connect(connection_string: &str, tls: &TlsSettings, ssh: &SshSettings, network: &NetworkSettings) -> Result<Self> {
+        let (connection_string, tunnel) = open_tunnel(connection_string, ssh, network).await?;
+
+        let mut builder = parse_connection_string(&connection_string, tls)?;
+        if let Some(idle) = network.keepalive_idle() {
+            builder = builder.tcp_keepalive(Some(idle.as_millis() as u32));
+        }
+        let opts = Opts::from(builder);
+        let mut conn = match network.connect_timeout() {
+            Some(limit) => tokio::time::timeout(limit, Conn::new(opts.clone())).await
+                .map_err(|_| anyhow::anyhow!("Connection timed out after {}s", limit.as_secs()))??,
+            None => Conn::new(opts.clone()).await?,
+        };
+        let thread_id = conn.id();
+        let version: Option<String> = conn.query_first("SELECT VERSION()").await?;
+        let is_mariadb = version.is_some_and(|v| v.contains("MariaDB"));
+
+        Ok(MySqlDatabase {
+            conn: Mutex::new(conn),
+            opts,
+            thread_id,
+            is_mariadb,
+            in_transaction: AtomicBool::new(false),
+            _tunnel: tunnel,
+        })
+    }
+
+    /// Stop the statement running on server thread `thread_id` from a second connection.
+    pub async fn cancel(opts: Opts, thread_id: u32) -> Result<()> {
+        let mut conn = Conn::new(opts).await?;
+        conn.query_drop(format!("KILL QUERY {}", thread_id)).await?;
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    // Session variable limiting statement time, in the unit this server expects
+    fn timeout_statement(&self, millis: u64) -> String {
+        if self.is_mariadb {
+            format!("SET SESSION max_statement_time = {}", millis as f64 / 1000.0)
+        } else {
+            format!("SET SESSION max_execution_time = {}", millis)
+        }
+    }
+
+    // Limit statement time to `millis`, returning the session's limit before it
+    async fn begin_timeout(&self, conn: &mut Conn, millis: u64) -> Result<u64> {
+        let current = if self.is_mariadb {
+            "SELECT CAST(@@SESSION.max_statement_time * 1000 AS UNSIGNED)"
+        } else {
+            "SELECT @@SESSION.max_execution_time"
+        };
+        let previous: Option<u64> = conn.query_first(current).await?;
+        conn.query_drop(self.timeout_statement(millis)).await?;
+        Ok(previous.unwrap_or_default())
+    }
+
+    async fn table_columns(&self, conn: &mut Conn, filter: &str) -> Result<HashMap<(String, String), Vec<ColumnInfo>>> {
+        let fk_rows: Vec<(String, String, String)> = conn
+            .query(format!(
+                "SELECT TABLE_SCHEMA, TABLE_NAME, COLUMN_NAME
+                 FROM information_schema.KEY_COLUMN_USAGE
+                 WHERE REFERENCED_TABLE_NAME IS NOT NULL AND {}",
+                filter
+            ))
+            .await?;
+        let foreign_keys: HashSet<(String, String, String)> = fk_rows.into_iter().collect();
+
+        let column_rows: Vec<(String, String, String, String, String)> = conn
+            .query(format!(
+                "SELECT TABLE_SCHEMA, TABLE_NAME, COLUMN_NAME, COLUMN_TYPE, COLUMN_KEY
+                 FROM information_schema.COLUMNS
+                 WHERE {}
+                 ORDER BY TABLE_SCHEMA, TABLE_NAME, ORDINAL_POSITION",
+                filter
+            ))
+            .await?;
+
+        let mut columns: HashMap<(String, String), Vec<ColumnInfo>> = HashMap::new();
+        for (schema, table, name, data_type, key) in column_rows {
+            let is_foreign_key = foreign_keys.contains(&(schema.clone(), table.clone(), name.clone()));
+            columns.entry((schema, table)).or_default().push(ColumnInfo {
+                name,
+                data_type,
+                is_primary_key: key == "PRI",
+                is_foreign_key,
+            });
+        }
+        Ok(columns)
+    }
+
+    /// Write grants of the connected account on every base table, combining global,
+    /// database and table level grants. None while roles are active: their grants are
+    /// missing from information_schema, so tables would look read-only when they are not.
+    async fn table_privileges(&self, conn: &mut Conn) -> Result<Option<HashMap<(String, String), TablePrivileges>>> {
+        // Servers without roles reject CURRENT_ROLE(), which is as good as none being active
+        let role: Option<Option<String>> = conn.query_first("SELECT CURRENT_ROLE()").await.unwrap_or(None);
+        if role.flatten().is_some_and(|role| role != "NONE") {
+            return Ok(None);
+        }
+
+        let current_user: Option<String> = conn.query_first("SELECT CURRENT_USER()").await?;
+        let grantee = quote_literal(&grantee(&current_user.unwrap_or_default()));
+        // Database grants may use LIKE wildcards in the database name
+        let rows: Vec<(String, String, Option<String>)> = conn
+            .query(format!(
+                "SELECT t.TABLE_SCHEMA, t.TABLE_NAME, g.PRIVILEGE_TYPE
+                 FROM information_schema.TABLES t
+                 LEFT JOIN (
+                     SELECT '%' AS SCHEMA_PATTERN, NULL AS TABLE_NAME, PRIVILEGE_TYPE
+                     FROM information_schema.USER_PRIVILEGES WHERE GRANTEE = {0}
+                     UNION ALL
+                     SELECT TABLE_SCHEMA, NULL, PRIVILEGE_TYPE
+                     FROM information_schema.SCHEMA_PRIVILEGES WHERE GRANTEE = {0}
+                     UNION ALL
+                     SELECT TABLE_SCHEMA, TABLE_NAME, PRIVILEGE_TYPE
+                     FROM information_schema.TABLE_PRIVILEGES WHERE GRANTEE = {0}
+                 ) g ON t.TABLE_SCHEMA LIKE g.SCHEMA_PATTERN AND (g.TABLE_NAME IS NULL OR g.TABLE_NAME = t.TABLE_NAME)
+                 WHERE t.TABLE_TYPE = 'BASE TABLE' AND t.TABLE_SCHEMA NOT IN ({1})",
+                grantee, SYSTEM_SCHEMAS
+            ))
+            .await?;
+
+        let mut granted: HashMap<(String, String), HashSet<String>> = HashMap::new();
+        for (schema, table, privilege) in rows {
+            granted.entry((schema, table)).or_default().extend(privilege);
+        }
+        Ok(Some(granted.into_iter().map(|(table, granted)| (table, table_privileges(&granted))).collect()))
+    }
+}
+
+#[async_trait]
+impl DatabaseBackend for MySqlDatabase {
+    fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle::MySql(self.opts.clone(), self.thread_id)
+    }
+
+    async fn list_schemas_with_tables(&self) -> Result<Vec<SchemaInfo>> {
+        let mut conn = self.conn.lock().await;
+        let filter = format!("TABLE_SCHEMA NOT IN ({})", SYSTEM_SCHEMAS);
+
+        // TABLE_ROWS is InnoDB's estimate, NULL for views
+        let table_rows: Vec<(String, String, String, Option<u64>)> = conn
+            .query(format!(
+                "SELECT TABLE_SCHEMA, TABLE_NAME, TABLE_TYPE, TABLE_ROWS
+                 FROM information_schema.TABLES
+                 WHERE {}
+                 ORDER BY TABLE_SCHEMA, TABLE_NAME",
+                filter
+            ))
+            .await?;
+        let mut columns = self.table_columns(&mut conn, &filter).await?;
+        // Only a hint for the UI, so a failure leaves them unknown
+        let mut privileges = self.table_privileges(&mut conn).await.unwrap_or_else(|e| {
+            eprintln!("Failed to load table privileges: {}", e);
+            None
+        }).unwrap_or_default();
+
+        // Parameters are listed without a mode for functions, CONCAT_WS skips it
+        let routine_rows: Vec<(String, String, String, Option<String>, Option<String>)> = conn
+            .query(format!(
+                "SELECT r.ROUTINE_SCHEMA, r.ROUTINE_NAME, r.ROUTINE_TYPE,
+                     (SELECT GROUP_CONCAT(CONCAT_WS(' ', p.PARAMETER_MODE, p.PARAMETER_NAME, p.DTD_IDENTIFIER)
+                             ORDER BY p.ORDINAL_POSITION SEPARATOR ', ')
+                      FROM information_schema.PARAMETERS p
+                      WHERE p.SPECIFIC_SCHEMA = r.ROUTINE_SCHEMA AND p.SPECIFIC_NAME = r.SPECIFIC_NAME
+                          AND p.ORDINAL_POSITION > 0),
+                     r.DTD_IDENTIFIER
+                 FROM information_schema.ROUTINES r
+                 WHERE r.ROUTINE_SCHEMA NOT IN ({})
+                 ORDER BY r.ROUTINE_SCHEMA, r.ROUTINE_NAME",
+                SYSTEM_SCHEMAS
+            ))
+            .await?;
+
+        let mut schemas: BTreeMap<String, SchemaInfo> = BTreeMap::new();
+        for (schema, table, table_type, estimated_rows) in table_rows {
+            let info = schemas.entry(schema.clone()).or_insert_with(|| SchemaInfo::new(schema.clone()));
+            let table_columns = columns.remove(&(schema, table.clone())).unwrap_or_default();
+            info.table_columns.insert(table.clone(), table_columns);
+            if let Some(table_privileges) = privileges.remove(&(info.name.clone(), table.clone())) {
+                info.table_privileges.insert(table.clone(), table_privileges);
+            }
+            if let Some(estimated_rows) = estimated_rows {
+                info.row_estimates.insert(table.clone(), estimated_rows);
+            }
+            if table_type.contains("VIEW") {
+                info.views.push(table);
+            } else {
+                info.tables.push(table);
+            }
+        }
+        for (schema, name, routine_type, arguments, result) in routine_rows {
+            let is_procedure = routine_type == "PROCEDURE";
+            schemas.entry(schema.clone()).or_insert_with(|| SchemaInfo::new(schema)).functions.push(FunctionInfo {
+                name,
+                arguments: arguments.unwrap_or_default(),
+                result: if is_procedure { None } else { result },
+                is_procedure,
+            });
+        }
+        Ok(schemas.into_values().collect())
+    }
+
+    async fn query_table(&self, table_name: &str, filter: Option<&TableFilter>, order: Option<&TableOrder>, limit: i64) -> Result<ResultSet> {
+        let mut conn = self.conn.lock().await;
+        let (schema, table) = match table_name.split_once('.') {
+            Some((schema, table)) => (schema.to_string(), table.to_string()),
+            None => {
+                let current: Option<Option<String>> = conn.query_first("SELECT DATABASE()").await?;
+                let schema = current.flatten().ok_or_else(|| anyhow::anyhow!("No database selected"))?;
+                (schema, table_name.to_string())
+            }
+        };
+
+        let column_filter = format!("TABLE_SCHEMA = {} AND TABLE_NAME = {}", quote_literal(&schema), quote_literal(&table));
+        let columns = self
+            .table_columns(&mut conn, &column_filter)
+            .await?
+            .remove(&(schema.clone(), table.clone()))
+            .unwrap_or_default();
+
+        let where_clause = filter.map(|filter| format!(" WHERE {}", filter.sql)).unwrap_or_default();
+        let order_clause = order.map(|order| format!(" ORDER BY {} {}", quote_identifier(&order.column), order.direction())).unwrap_or_default();
+        let data_query = format!(
+            "SELECT * FROM {}.{}{}{} LIMIT {}",
+            quote_identifier(&schema), quote_identifier(&table), where_clause, order_clause, limit
+        );
+        let rows: Vec<Row> = match filter.filter(|filter| !filter.params.is_empty()) {
+            Some(filter) => conn.exec(data_query, filter.params.clone()).await?,
+            None => conn.query(data_query).await?,
+        };
+        let (_, data) = rows_to_result_set(&rows);
+
+        Ok((columns, data))
+    }
+
+    async fn foreign_key_target(&self, schema: &str, table: &str, column: &str) -> Result<Option<ForeignKeyTarget>> {
+        let mut conn = self.conn.lock().await;
+        let row: Option<(String, String, String)> = conn
+            .query_first(format!(
+                "SELECT REFERENCED_TABLE_SCHEMA, REFERENCED_TABLE_NAME, REFERENCED_COLUMN_NAME
+                 FROM information_schema.KEY_COLUMN_USAGE
+                 WHERE REFERENCED_TABLE_NAME IS NOT NULL
+                     AND TABLE_SCHEMA = {} AND TABLE_NAME = {} AND COLUMN_NAME = {}
+                 LIMIT 1",
+                quote_literal(schema), quote_literal(table), quote_literal(column)
+            ))
+            .await?;
+
+        Ok(row.map(|(schema, table, column)| ForeignKeyTarget { schema, table, column }))
+    }
+
+    async fn object_source(&self, schema: &str, object: &SourceObject) -> Result<String> {
+        let (statement, column) = match object {
+            SourceObject::View(_) | SourceObject::MaterializedView(_) => ("VIEW", "Create View"),
+            SourceObject::Function(function) if function.is_procedure => ("PROCEDURE", "Create Procedure"),
+            SourceObject::Function(_) => ("FUNCTION", "Create Function"),
+        };
+        let mut conn = self.conn.lock().await;
+        let row: Option<Row> = conn
+            .query_first(format!("SHOW CREATE {} {}.{}", statement, quote_identifier(schema), quote_identifier(object.name())))
+            .await?;
+        // The body is NULL without the privileges to see it
+        row.and_then(|row| row.get::<Option<String>, _>(column).flatten())
+            .ok_or_else(|| anyhow::anyhow!("No source available for {}.{}", schema, object.name()))
+    }
+
+    async fn table_definition(&self, schema: &str, table: &str) -> Result<TableDefinition> {
+        let mut conn = self.conn.lock().await;
+        let row: Option<Row> = conn
+            .query_first(format!("SHOW CREATE TABLE {}.{}", quote_identifier(schema), quote_identifier(table)))
+            .await?;
+        let create = row.and_then(|row| row.get::<String, _>("Create Table"))
+            .ok_or_else(|| anyhow::anyhow!("{}.{} not found", schema, table))?;
+
+        // GENERATION_EXPRESSION is empty on MySQL and NULL on MariaDB for plain columns
+        let columns: Vec<(String, String)> = conn
+            .query(format!(
+                "SELECT COLUMN_NAME, DATA_TYPE FROM information_schema.COLUMNS
+                 WHERE TABLE_SCHEMA = {} AND TABLE_NAME = {} AND COALESCE(GENERATION_EXPRESSION, '') = ''
+                 ORDER BY ORDINAL_POSITION",
+                quote_literal(schema), quote_literal(table)
+            ))
+            .await?;
+
+        let columns = columns.into_iter()
+            .map(|(name, data_type)| ColumnInfo { name, data_type, is_primary_key: false, is_foreign_key: false })
+            .collect();
+
+        // Foreign keys stay in the CREATE TABLE; dumps turn their checks off instead
+        Ok(TableDefinition { create, columns, ..TableDefinition::default() })
+    }
+
+    async fn dump_rows(
+        &self,
+        schema: &str,
+        table: &str,
+        columns: &[ColumnInfo],
+        sink: &mut (dyn FnMut(Vec<Vec<Option<String>>>) -> Result<()> + Send),
+    ) -> Result<()> {
+        if columns.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.conn.lock().await;
+        // The text protocol sends every value as the server writes it
+        let mut result = conn.query_iter(dump_select(Driver::MySql, schema, table, columns)).await?;
+        let mut batch = Vec::with_capacity(STREAM_BATCH_SIZE);
+        while let Some(row) = result.next().await? {
+            batch.push(row.unwrap().into_iter().map(|value| match value {
+                Value::NULL => None,
+                Value::Bytes(bytes) => Some(String::from_utf8_lossy(&bytes).into_owned()),
+                other => Some(other.as_sql(true).trim_matches('\'').to_string()),
+            }).collect());
+            if batch.len() >= STREAM_BATCH_SIZE {
+                if let Err(e) = sink(std::mem::replace(&mut batch, Vec::with_capacity(STREAM_BATCH_SIZE))) {
+                    // Read off the rest so the connection can run the next statement
+                    result.drop_result().await?;
+                    return Err(e);
+                }
+            }
+        }
+        sink(batch)
+    }
+
+    async fn table_structure(&self, schema: &str, table: &str) -> Result<TableStructure> {
+        let mut conn = self.conn.lock().await;
+        let (schema, table) = (quote_literal(schema), quote_literal(table));
+
+        let index_rows: Vec<(String, i64, String, Option<String>)> = conn
+            .query(format!(
+                "SELECT INDEX_NAME, MAX(NON_UNIQUE), MAX(INDEX_TYPE),
+                     GROUP_CONCAT(COLUMN_NAME ORDER BY SEQ_IN_INDEX SEPARATOR ', ')
+                 FROM information_schema.STATISTICS
+                 WHERE TABLE_SCHEMA = {} AND TABLE_NAME = {}
+                 GROUP BY INDEX_NAME
+                 ORDER BY INDEX_NAME",
+                schema, table
+            ))
+            .await?;
+        let indexes = index_rows
+            .into_iter()
+            .map(|(name, non_unique, index_type, columns)| index_object(name, non_unique != 0, &index_type, columns.as_deref()))
+            .collect();
+
+        // CHECK_CONSTRAINTS needs MySQL 8.0.16 or MariaDB 10.2
+        let constraint_rows: Vec<(String, String, Option<String>)> = conn
+            .query(format!(
+                "SELECT tc.CONSTRAINT_NAME, tc.CONSTRAINT_TYPE,
+                     COALESCE(cc.CHECK_CLAUSE, (
+                         SELECT GROUP_CONCAT(k.COLUMN_NAME ORDER BY k.ORDINAL_POSITION SEPARATOR ', ')
+                         FROM information_schema.KEY_COLUMN_USAGE k
+                         WHERE k.CONSTRAINT_SCHEMA = tc.CONSTRAINT_SCHEMA
+                             AND k.TABLE_NAME = tc.TABLE_NAME
+                             AND k.CONSTRAINT_NAME = tc.CONSTRAINT_NAME
+                     ))
+                 FROM information_schema.TABLE_CONSTRAINTS tc
+                 LEFT JOIN information_schema.CHECK_CONSTRAINTS cc
+                     ON cc.CONSTRAINT_SCHEMA = tc.CONSTRAINT_SCHEMA AND cc.CONSTRAINT_NAME = tc.CONSTRAINT_NAME
+                 WHERE tc.TABLE_SCHEMA = {} AND tc.TABLE_NAME = {}
+                 ORDER BY tc.CONSTRAINT_NAME",
+                schema, table
+            ))
+            .await?;
+        let constraints = constraint_rows
+            .into_iter()
+            .map(|(name, kind, body)| {
+                let body = body.unwrap_or_default();
+                let definition = if kind == "CHECK" { format!("CHECK {}", body) } else { format!("{} ({})", kind, body) };
+                TableObject { name, kind, definition }
+            })
+            .collect();
+
+        let trigger_rows: Vec<(String, String, String, String)> = conn
+            .query(format!(
+                "SELECT TRIGGER_NAME, ACTION_TIMING, EVENT_MANIPULATION, ACTION_STATEMENT
+                 FROM information_schema.TRIGGERS
+                 WHERE EVENT_OBJECT_SCHEMA = {} AND EVENT_OBJECT_TABLE = {}
+                 ORDER BY TRIGGER_NAME",
+                schema, table
+            ))
+            .await?;
+        let triggers = trigger_rows
+            .into_iter()
+            .map(|(name, timing, event, statement)| {
+                let kind = format!("{} {}", timing, event);
+                let definition = format!("{} FOR EACH ROW {}", kind, statement);
+                TableObject { name, kind, definition }
+            })
+            .collect();
+
+        Ok(TableStructure { indexes, constraints, triggers })
+    }
+
+    async fn execute_query(&self, query: &str, timeout: StatementTimeout) -> Result<ResultSet> {
+        let mut conn = self.conn.lock().await;
+        let rows: Vec<Row> = match timeout.millis() {
+            Some(millis) => {
+                let previous = self.begin_timeout(&mut conn, millis).await?;
+                let result = conn.query(query).await;
+                let restored = conn.query_drop(self.timeout_statement(previous)).await;
+                let rows = result?;
+                restored?;
+                rows
+            }
+            None => conn.query(query).await?,
+        };
+        Ok(rows_to_result_set(&rows))
+    }
+
+    async fn execute_statement(&self, sql: &str, timeout: StatementTimeout) -> Result<StatementOutcome> {
+        self.execute_with_params(sql, &[], timeout).await
+    }
+
+    async fn execute_with_params(&self, sql: &str, params: &[Option<String>], timeout: StatementTimeout) -> Result<StatementOutcome> {
+        let mut conn = self.conn.lock().await;
+        let Some(millis) = timeout.millis() else {
+            return statement_outcome(&mut conn, sql, params).await;
+        };
+        let previous = self.begin_timeout(&mut conn, millis).await?;
+        let result = statement_outcome(&mut conn, sql, params).await;
+        let restored = conn.query_drop(self.timeout_statement(previous)).await;
+        let outcome = result?;
+        restored?;
+        Ok(outcome)
+    }
+
+    async fn apply_updates(&self, statements: &[String]) -> Result<u64> {
+        let mut conn = self.conn.lock().await;
+
+        // The statements are built with standard SQL quoting ("identifiers", 'literals')
+        let sql_mode: Option<String> = conn.query_first("SELECT @@SESSION.sql_mode").await?;
+        conn.query_drop("SET SESSION sql_mode = CONCAT(@@SESSION.sql_mode, ',ANSI_QUOTES,NO_BACKSLASH_ESCAPES')").await?;
+        let restore = format!("SET SESSION sql_mode = {}", quote_literal(&sql_mode.unwrap_or_default()));
+
+        let unit = unit_of_work(self.in_transaction.load(Ordering::SeqCst));
+        let result = update_rows(&mut conn, statements, unit).await;
+        // The mode goes back whether the updates were committed, rolled back or not
+        let restored = conn.query_drop(restore.as_str()).await;
+        let updated = result?;
+        restored?;
+        Ok(updated)
+    }
+
+    // DDL commits implicitly in MySQL, so a transaction would not hold the statements together
+    async fn execute_in_transaction(&self, _statements: &[String]) -> Result<()> {
+        anyhow::bail!("Transactional schema changes are only supported for PostgreSQL connections")
+    }
+
+    async fn transaction(&self, control: TransactionControl) -> Result<()> {
+        let result = self.conn.lock().await.query_drop(control.sql()).await;
+        self.in_transaction.store(control == TransactionControl::Begin && result.is_ok(), Ordering::SeqCst);
+        Ok(result?)
+    }
+
+    async fn execute_batch(&self, sql: &str) -> Result<()> {
+        self.conn.lock().await.query_drop(sql).await?;
+        Ok(())
+    }
+
+    async fn explain_json(&self, _query: &str, _analyze: bool) -> Result<serde_json::Value> {
+        anyhow::bail!("EXPLAIN plans are only supported for PostgreSQL connections")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(column_type: ColumnType, charset: u16) -> Column {
+        Column::new(column_type).with_character_set(charset)
+    }
+
+    #[test]
+    fn test_parse_connection_string() {
+        let opts = Opts::from(parse_connection_string(
+            "host=db.example.com hostaddr=127.0.0.1 port=4406 user=app password=secret dbname=shop sslmode=disable",
+            &TlsSettings::default(),
+        ).unwrap());
+        assert_eq!(opts.ip_or_hostname(), "127.0.0.1");
+        assert_eq!(opts.tcp_port(), 4406);
+        assert_eq!(opts.user(), Some("app"));
+        assert_eq!(opts.pass(), Some("secret"));
+        assert_eq!(opts.db_name(), Some("shop"));
+        assert!(opts.ssl_opts().is_none());
+
+        let defaults = Opts::from(parse_connection_string("host=localhost password=", &TlsSettings::default()).unwrap());
+        assert_eq!(defaults.tcp_port(), 3306);
+        assert_eq!(defaults.pass(), None);
+    }
+
+    #[test]
+    fn test_client_certificates_are_rejected() {
+        let tls = TlsSettings {
+            ssl_mode: SslMode::Require,
+            client_cert_path: "/tmp/client.pem".to_string(),
+            ..TlsSettings::default()
+        };
+        assert!(parse_connection_string("host=localhost", &tls).is_err());
+    }
+
+    #[test]
+    fn test_value_to_cell() {
+        let datetime = column(ColumnType::MYSQL_TYPE_DATETIME, 0);
+        let date = column(ColumnType::MYSQL_TYPE_DATE, 0);
+        let display = |value: Value, column: &Column| value_to_cell(&value, column).to_string();
+        assert_eq!(value_to_cell(&Value::NULL, &datetime), CellValue::Null);
+        assert_eq!(display(Value::Date(2024, 3, 9, 14, 5, 0, 0), &datetime), "2024-03-09 14:05:00");
+        assert_eq!(display(Value::Date(2024, 3, 9, 0, 0, 0, 0), &date), "2024-03-09");
+        assert_eq!(display(Value::Date(0, 0, 0, 0, 0, 0, 0), &date), "0000-00-00");
+        assert_eq!(display(Value::Time(true, 1, 2, 3, 4, 0), &datetime), "-26:03:04");
+        assert_eq!(value_to_cell(&Value::UInt(u64::MAX), &datetime), CellValue::Text(u64::MAX.to_string()));
+
+        let text = column(ColumnType::MYSQL_TYPE_VAR_STRING, 45);
+        let binary = column(ColumnType::MYSQL_TYPE_BLOB, BINARY_CHARSET);
+        assert_eq!(value_to_cell(&Value::Bytes(b"caf\xc3\xa9".to_vec()), &text), CellValue::from("café"));
+        assert_eq!(display(Value::Bytes(vec![0, 1, 2]), &binary), "<3 bytes>");
+    }
+
+    #[test]
+    fn test_index_object() {
+        let primary = index_object("PRIMARY".to_string(), false, "BTREE", Some("id"));
+        assert_eq!((primary.kind.as_str(), primary.definition.as_str()), ("PRIMARY", "PRIMARY KEY USING BTREE (id)"));
+
+        let unique = index_object("uq_email".to_string(), true, "BTREE", Some("tenant_id, email"));
+        assert_eq!(unique.definition, "UNIQUE INDEX `uq_email` USING BTREE (tenant_id, email)");
+
+        let functional = index_object("idx_lower".to_string(), false, "BTREE", None);
+        assert_eq!((functional.kind.as_str(), functional.definition.as_str()), ("INDEX", "INDEX `idx_lower` USING BTREE (<expression>)"));
+    }
+
+    #[test]
+    fn test_grants() {
+        assert_eq!(grantee("app@%"), "'app'@'%'");
+        assert_eq!(grantee("odd@name@localhost"), "'odd@name'@'localhost'");
+
+        let granted: HashSet<String> = ["SELECT", "UPDATE", "DROP"].into_iter().map(String::from).collect();
+        assert_eq!(table_privileges(&granted), TablePrivileges { insert: false, update: true, delete: false, truncate: true });
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(quote_identifier("odd`name"), "`odd``name`");
+        assert_eq!(quote_literal("it's a \\ test"), "'it''s a \\\\ test'");
+    }
+}
@@ -1,14 +1,108 @@
 use poll_promise::Promise;
 use anyhow::Result;
+use std::path::PathBuf;
 use std::sync::Arc;
-use crate::db::{Database, ColumnInfo, SchemaInfo};
+use crate::models::{short_label, CellValue};
+use crate::db::{routing, CancelHandle, StatementRun, TransactionControl, ColumnInfo, ColumnLineage, DatabaseBackend, ForeignKeyTarget, QueryTarget, ResourceReport, SchemaInfo, TableStructure};
 
 // Type aliases to simplify complex Promise types
-type TableDataPromise = Promise<Result<(Vec<ColumnInfo>, Vec<Vec<String>>)>>;
-type StructurePromise = Promise<Result<(Arc<Database>, Vec<SchemaInfo>)>>;
+type TableDataPromise = Promise<Result<(Vec<ColumnInfo>, Vec<Vec<CellValue>>, ColumnLineage)>>;
+type QueryPromise = Promise<Result<Option<ResourceReport>>>; // rows arrive through the app's QueryStream
+type StatementsPromise = Promise<Vec<StatementRun>>; // stops after the first failing statement
+type UpdatePromise = Promise<Result<u64>>;
+type PlanPromise = Promise<Result<serde_json::Value>>;
+type ExportPromise = Promise<Result<usize>>;
+type ScriptPromise = Promise<Result<usize>>;
+type TailPromise = Promise<Result<(Vec<ColumnInfo>, Vec<Vec<CellValue>>)>>;
+type ForeignKeyPromise = Promise<Result<Option<ForeignKeyTarget>>>;
+type TableStructurePromise = Promise<Result<TableStructure>>;
+type SourcePromise = Promise<Result<String>>;
+type TransactionPromise = Promise<Result<()>>;
+type AlterPromise = Promise<Result<Vec<SchemaInfo>>>; // schema reloaded after the change
+type WatchPromise = Promise<Result<(Vec<ColumnInfo>, Vec<Vec<CellValue>>)>>;
+type ImportPromise = Promise<Result<u64>>; // rows copied
+
+/// Where an exported EXPLAIN plan should end up
+pub enum PlanDestination {
+    Clipboard,
+    File(PathBuf),
+    // Shown as a tree in an explain tab; `tab_index` reloads an existing one
+    Tab { sql: String, analyze: bool, tab_index: Option<usize> },
+}
+type StructurePromise = Promise<Result<(Arc<dyn DatabaseBackend>, Option<Arc<dyn DatabaseBackend>>, Vec<SchemaInfo>)>>;
 
 pub enum AsyncOperation {
     LoadStructure(StructurePromise),
-    LoadTableData(String, String, TableDataPromise, Option<usize>), // schema, table, promise, optional tab_index for reload
-    ExecuteQuery(String, TableDataPromise, Option<usize>), // query, promise, optional tab_index for reload
+    LoadTableData(String, String, TableDataPromise, Option<usize>, CancelHandle), // schema, table, promise, optional tab_index for reload, cancel handle
+    ExecuteQuery(String, QueryPromise, Option<usize>, QueryTarget, CancelHandle), // query, promise, optional tab_index for reload, server the query ran on, cancel handle
+    ExecuteStatements(String, StatementsPromise, usize, CancelHandle), // whole query, promise, number of statements, cancel handle
+    ApplyEdits(UpdatePromise, usize), // promise, tab_index whose staged edits are being written
+    ExplainPlan(PlanPromise, PlanDestination, CancelHandle),
+    Export(ExportPromise, PathBuf), // rows written, target file
+    BatchExport(ExportPromise, PathBuf), // tables written, target directory
+    SqlDump(ExportPromise, PathBuf), // tables written, target file
+    RunScript(ScriptPromise, PathBuf, CancelHandle), // statements executed, script file, cancel handle
+    TailTable(TailPromise, usize, CancelHandle), // new rows, tab_index being tailed, cancel handle
+    FollowForeignKey(ForeignKeyPromise, String), // referenced column lookup, value of the clicked cell
+    LoadTableStructure(TableStructurePromise, usize), // indexes, constraints and triggers, tab_index to show them in
+    LoadSource(SourcePromise, String), // definition of a view or routine, title of the tab to open it in
+    RefreshWatch(WatchPromise, usize, CancelHandle), // scalar result, index of the watch, cancel handle
+    Transaction(TransactionPromise, TransactionControl, Option<(String, Option<usize>)>), // control statement, query to run once the transaction is open
+    AlterColumns(AlterPromise, String, usize), // refreshed schemas, altered "schema.table", number of statements
+    CreateTempTable(TransactionPromise, String, usize), // created table name, number of rows
+    ImportFile(ImportPromise, String, CancelHandle), // "schema.table" imported into, cancel handle
+}
+
+impl AsyncOperation {
+    /// True when the operation may be changing data, so exiting mid-way is unsafe.
+    pub fn is_write(&self) -> bool {
+        match self {
+            AsyncOperation::ExecuteQuery(query, ..) | AsyncOperation::ExecuteStatements(query, ..) => !routing::is_read_only(query),
+            AsyncOperation::ApplyEdits(..) | AsyncOperation::RunScript(..) | AsyncOperation::AlterColumns(..) | AsyncOperation::ImportFile(..) => true,
+            AsyncOperation::Transaction(_, control, _) => *control == TransactionControl::Commit,
+            _ => false,
+        }
+    }
+
+    /// What the operation is doing, for the queue panel.
+    pub fn label(&self) -> String {
+        match self {
+            AsyncOperation::LoadStructure(_) => "Load schema".to_string(),
+            AsyncOperation::LoadTableData(schema, table, ..) => format!("Load {}.{}", schema, table),
+            AsyncOperation::ExecuteQuery(query, ..) | AsyncOperation::ExecuteStatements(query, ..) => short_label(query),
+            AsyncOperation::ApplyEdits(..) => "Apply staged edits".to_string(),
+            AsyncOperation::ExplainPlan(..) => "EXPLAIN".to_string(),
+            AsyncOperation::Export(_, path) => format!("Export to {}", path.display()),
+            AsyncOperation::BatchExport(_, directory) => format!("Export tables to {}", directory.display()),
+            AsyncOperation::SqlDump(_, path) => format!("Export SQL to {}", path.display()),
+            AsyncOperation::RunScript(_, path, _) => format!("Run {}", path.display()),
+            AsyncOperation::TailTable(..) => "Fetch new rows".to_string(),
+            AsyncOperation::FollowForeignKey(..) => "Follow foreign key".to_string(),
+            AsyncOperation::LoadTableStructure(..) => "Load table structure".to_string(),
+            AsyncOperation::LoadSource(_, title) => format!("Load definition of {}", title),
+            AsyncOperation::RefreshWatch(..) => "Refresh watch".to_string(),
+            AsyncOperation::Transaction(_, control, _) => control.sql().to_string(),
+            AsyncOperation::AlterColumns(_, table, _) => format!("Alter columns of {}", table),
+            AsyncOperation::CreateTempTable(_, table, _) => format!("Create temporary table {}", table),
+            AsyncOperation::ImportFile(_, table, _) => format!("Import into {}", table),
+        }
+    }
+
+    /// Cancel handle for operations that run a user-visible statement.
+    pub fn cancel_handle(&self) -> Option<&CancelHandle> {
+        match self {
+            AsyncOperation::LoadStructure(_) | AsyncOperation::ApplyEdits(..) | AsyncOperation::Export(..) | AsyncOperation::BatchExport(..) => None,
+            AsyncOperation::SqlDump(..) => None,
+            AsyncOperation::FollowForeignKey(..) | AsyncOperation::LoadTableStructure(..) | AsyncOperation::LoadSource(..) => None,
+            AsyncOperation::Transaction(..) | AsyncOperation::AlterColumns(..) | AsyncOperation::CreateTempTable(..) => None,
+            AsyncOperation::LoadTableData(.., handle) => Some(handle),
+            AsyncOperation::ExecuteQuery(.., handle) => Some(handle),
+            AsyncOperation::ExecuteStatements(.., handle) => Some(handle),
+            AsyncOperation::ExplainPlan(.., handle) => Some(handle),
+            AsyncOperation::RunScript(.., handle) => Some(handle),
+            AsyncOperation::TailTable(.., handle) => Some(handle),
+            AsyncOperation::RefreshWatch(.., handle) => Some(handle),
+            AsyncOperation::ImportFile(.., handle) => Some(handle),
+        }
+    }
 }
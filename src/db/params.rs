@@ -0,0 +1,194 @@
+//! Placeholders in query text, `:name` or PostgreSQL's `$1`, and binding them to values.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use crate::config::Driver;
+
+// A placeholder in the text, named with its sigil (":id", "$1")
+struct Placeholder {
+    range: Range<usize>,
+    name: String,
+}
+
+/// Placeholders `sql` asks values for, each once: `$1`, `$2`, … in number order, then
+/// `:name`s in the order they first appear. Casts (`::int`), array slices (`[lo:hi]`),
+/// quoted text, dollar-quoted bodies and comments are skipped.
+pub fn placeholders(sql: &str) -> Vec<String> {
+    let found = scan(sql);
+    let mut numbered: Vec<&Placeholder> = found.iter().filter(|p| p.name.starts_with('$')).collect();
+    numbered.sort_by_key(|p| p.name[1..].parse::<usize>().unwrap_or(usize::MAX));
+
+    let mut names: Vec<String> = Vec::new();
+    for placeholder in numbered.into_iter().chain(found.iter().filter(|p| p.name.starts_with(':'))) {
+        if !names.contains(&placeholder.name) {
+            names.push(placeholder.name.clone());
+        }
+    }
+    names
+}
+
+/// `sql` with its placeholders in the form `driver` binds, and the values to bind in
+/// order. PostgreSQL keeps `$n` and numbers each `:name` after them; MySQL gets a `?`
+/// per occurrence. None values, and placeholders missing from `values`, are bound to NULL.
+pub fn bind_placeholders(driver: Driver, sql: &str, values: &HashMap<String, Option<String>>) -> (String, Vec<Option<String>>) {
+    let found = scan(sql);
+    let value = |name: &str| values.get(name).cloned().flatten();
+
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    let mut params = Vec::new();
+    if driver == Driver::Postgres {
+        let highest = found.iter().filter_map(|p| p.name.strip_prefix('$')?.parse::<usize>().ok()).max().unwrap_or(0);
+        params = (1..=highest).map(|n| value(&format!("${}", n))).collect();
+        for placeholder in found.iter().filter(|p| p.name.starts_with(':')) {
+            if !positions.contains_key(placeholder.name.as_str()) {
+                params.push(value(&placeholder.name));
+                positions.insert(&placeholder.name, params.len());
+            }
+        }
+    }
+
+    let mut bound = String::with_capacity(sql.len());
+    let mut end = 0;
+    for placeholder in &found {
+        bound.push_str(&sql[end..placeholder.range.start]);
+        match driver {
+            Driver::Postgres => match positions.get(placeholder.name.as_str()) {
+                Some(position) => bound.push_str(&format!("${}", position)),
+                None => bound.push_str(&placeholder.name),
+            },
+            Driver::MySql => {
+                bound.push('?');
+                params.push(value(&placeholder.name));
+            }
+        }
+        end = placeholder.range.end;
+    }
+    bound.push_str(&sql[end..]);
+    (bound, params)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// Every placeholder occurrence, in text order
+fn scan(sql: &str) -> Vec<Placeholder> {
+    let chars: Vec<(usize, char)> = sql.char_indices().collect();
+    let at = |i: usize| chars.get(i).map(|&(_, c)| c);
+    let offset = |i: usize| chars.get(i).map_or(sql.len(), |&(offset, _)| offset);
+    let mut found = Vec::new();
+    // Depth of `[...]` subscripts, where `:` separates the bounds of a slice
+    let mut brackets = 0usize;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i].1;
+        let prev_is_ident = i > 0 && is_ident_char(chars[i - 1].1);
+        match c {
+            '\'' | '"' | '`' => {
+                // A doubled quote closes and reopens the same text, which comes out the same
+                i += 1;
+                while i < chars.len() && chars[i].1 != c {
+                    i += 1;
+                }
+                i += 1;
+            }
+            '-' if at(i + 1) == Some('-') => {
+                while i < chars.len() && chars[i].1 != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if at(i + 1) == Some('*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i].1 == '*' && at(i + 1) == Some('/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            '[' | ']' => {
+                brackets = if c == '[' { brackets + 1 } else { brackets.saturating_sub(1) };
+                i += 1;
+            }
+            ':' if at(i + 1) == Some(':') => i += 2,
+            ':' if brackets == 0 && !prev_is_ident && at(i + 1).is_some_and(|next| next.is_alphabetic() || next == '_') => {
+                let start = i;
+                i += 1;
+                while at(i).is_some_and(is_ident_char) {
+                    i += 1;
+                }
+                found.push(Placeholder { range: offset(start)..offset(i), name: sql[offset(start)..offset(i)].to_string() });
+            }
+            '$' if !prev_is_ident && at(i + 1).is_some_and(|next| next.is_ascii_digit()) => {
+                let start = i;
+                i += 1;
+                while at(i).is_some_and(|c| c.is_ascii_digit()) {
+                    i += 1;
+                }
+                found.push(Placeholder { range: offset(start)..offset(i), name: sql[offset(start)..offset(i)].to_string() });
+            }
+            '$' if !prev_is_ident => {
+                // `$$` or `$tag$` opens a body running to the same tag
+                let mut end = i + 1;
+                while at(end).is_some_and(is_ident_char) {
+                    end += 1;
+                }
+                if at(end) != Some('$') {
+                    i += 1;
+                    continue;
+                }
+                let tag = &sql[offset(i)..offset(end + 1)];
+                i = match sql[offset(end + 1)..].find(tag) {
+                    Some(close) => chars.partition_point(|&(o, _)| o < offset(end + 1) + close + tag.len()),
+                    None => chars.len(),
+                };
+            }
+            _ => i += 1,
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(placeholders("SELECT * FROM t WHERE id = :id AND kind = :kind OR parent = :id"), [":id", ":kind"]);
+        assert_eq!(placeholders("SELECT $2::int, $1"), ["$1", "$2"]);
+        assert_eq!(
+            placeholders("SELECT created::date, ':not', \":no\", $$ :body $$, $f$ $1 $f$ -- :comment\nFROM t /* :block */ WHERE a = :a"),
+            [":a"]
+        );
+        assert!(placeholders("SELECT arr[lo:hi] FROM t").is_empty());
+    }
+
+    #[test]
+    fn test_slices_are_not_placeholders() {
+        assert!(placeholders("SELECT arr[:hi], arr[lo :hi], grid[1][:n] FROM t").is_empty());
+        assert_eq!(placeholders("SELECT arr[1:2] FROM t WHERE id = :id"), [":id"]);
+        assert_eq!(placeholders("SELECT ']' || :a, arr[:n] FROM t"), [":a"]);
+    }
+
+    #[test]
+    fn test_bind_placeholders() {
+        let values = HashMap::from([(":id".to_string(), Some("7".to_string())), ("$1".to_string(), Some("x".to_string()))]);
+        let sql = "SELECT * FROM t WHERE id = :id AND name = $1 OR parent = :id";
+
+        let (bound, params) = bind_placeholders(Driver::Postgres, sql, &values);
+        assert_eq!(bound, "SELECT * FROM t WHERE id = $2 AND name = $1 OR parent = $2");
+        assert_eq!(params, [Some("x".to_string()), Some("7".to_string())]);
+
+        let (bound, params) = bind_placeholders(Driver::MySql, sql, &values);
+        assert_eq!(bound, "SELECT * FROM t WHERE id = ? AND name = ? OR parent = ?");
+        assert_eq!(params, [Some("7".to_string()), Some("x".to_string()), Some("7".to_string())]);
+    }
+
+    #[test]
+    fn test_null_and_missing_values_bind_null() {
+        let values = HashMap::from([(":none".to_string(), None), (":empty".to_string(), Some(String::new()))]);
+        let (bound, params) = bind_placeholders(Driver::Postgres, "SELECT ':id', :none, :empty, :missing", &values);
+        assert_eq!(bound, "SELECT ':id', $1, $2, $3");
+        assert_eq!(params, [None, Some(String::new()), None]);
+    }
+}
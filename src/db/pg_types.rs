@@ -0,0 +1,181 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use tokio_postgres::types::{FromSql, Kind, Type};
+
+type FromSqlResult<T> = Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+// Family bytes of the inet wire format
+const PGSQL_AF_INET: u8 = 2;
+const PGSQL_AF_INET6: u8 = 3;
+
+/// An `interval`, written the way PostgreSQL prints it by default:
+/// `1 year 2 mons 3 days 04:05:06.5`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub microseconds: i64,
+}
+
+impl<'a> FromSql<'a> for Interval {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> FromSqlResult<Self> {
+        let raw: [u8; 16] = raw.try_into().map_err(|_| "interval value is not 16 bytes")?;
+        Ok(Interval {
+            microseconds: i64::from_be_bytes(raw[0..8].try_into()?),
+            days: i32::from_be_bytes(raw[8..12].try_into()?),
+            months: i32::from_be_bytes(raw[12..16].try_into()?),
+        })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = |count: i64, singular: &str, plural: &str| {
+            format!("{} {}", count, if count.abs() == 1 { singular } else { plural })
+        };
+        let mut parts = Vec::new();
+        let (years, months) = (self.months / 12, self.months % 12);
+        if years != 0 {
+            parts.push(unit(years.into(), "year", "years"));
+        }
+        if months != 0 {
+            parts.push(unit(months.into(), "mon", "mons"));
+        }
+        if self.days != 0 {
+            parts.push(unit(self.days.into(), "day", "days"));
+        }
+
+        if self.microseconds != 0 || parts.is_empty() {
+            let sign = if self.microseconds < 0 { "-" } else { "" };
+            let total = self.microseconds.unsigned_abs();
+            let seconds = total / 1_000_000;
+            let mut time = format!("{}{:02}:{:02}:{:02}", sign, seconds / 3600, seconds / 60 % 60, seconds % 60);
+            let fraction = total % 1_000_000;
+            if fraction != 0 {
+                time.push_str(format!(".{:06}", fraction).trim_end_matches('0'));
+            }
+            parts.push(time);
+        }
+        f.write_str(&parts.join(" "))
+    }
+}
+
+/// An `inet` or `cidr` address. Host addresses are written without their prefix,
+/// networks (and every `cidr`) as `address/bits`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Inet {
+    pub address: IpAddr,
+    pub bits: u8,
+    pub is_cidr: bool,
+}
+
+impl<'a> FromSql<'a> for Inet {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> FromSqlResult<Self> {
+        let [family, bits, is_cidr, length, address @ ..] = raw else {
+            return Err("inet value is too short".into());
+        };
+        if address.len() != usize::from(*length) {
+            return Err("inet address length does not match".into());
+        }
+        let address = match (*family, address) {
+            (PGSQL_AF_INET, &[a, b, c, d]) => IpAddr::V4(Ipv4Addr::new(a, b, c, d)),
+            (PGSQL_AF_INET6, address) => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(address)?)),
+            _ => return Err(format!("unknown inet family {}", family).into()),
+        };
+        Ok(Inet { address, bits: *bits, is_cidr: *is_cidr != 0 })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INET || *ty == Type::CIDR
+    }
+}
+
+impl fmt::Display for Inet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let host_bits = if self.address.is_ipv4() { 32 } else { 128 };
+        if self.bits == host_bits && !self.is_cidr {
+            write!(f, "{}", self.address)
+        } else {
+            write!(f, "{}/{}", self.address, self.bits)
+        }
+    }
+}
+
+/// A `macaddr` or `macaddr8`, as colon-separated hex bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MacAddress(pub Vec<u8>);
+
+impl<'a> FromSql<'a> for MacAddress {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> FromSqlResult<Self> {
+        Ok(MacAddress(raw.to_vec()))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::MACADDR || *ty == Type::MACADDR8
+    }
+}
+
+impl fmt::Display for MacAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes: Vec<String> = self.0.iter().map(|byte| format!("{:02x}", byte)).collect();
+        f.write_str(&bytes.join(":"))
+    }
+}
+
+/// The label of a user-defined enum value, which is sent as its text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumLabel(pub String);
+
+impl<'a> FromSql<'a> for EnumLabel {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> FromSqlResult<Self> {
+        Ok(EnumLabel(std::str::from_utf8(raw)?.to_string()))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(ty.kind(), Kind::Enum(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval() {
+        let interval = |months, days, microseconds| Interval { months, days, microseconds }.to_string();
+        assert_eq!(interval(14, 3, 14_706_500_000), "1 year 2 mons 3 days 04:05:06.5");
+        assert_eq!(interval(1, 1, 0), "1 mon 1 day");
+        assert_eq!(interval(0, -2, -90_000_000), "-2 days -00:01:30");
+        assert_eq!(interval(-24, 0, 0), "-2 years");
+        assert_eq!(interval(0, 0, 0), "00:00:00");
+        assert_eq!(interval(0, 0, 100 * 3_600_000_000 + 1), "100:00:00.000001");
+
+        let mut raw = 14_706_500_000i64.to_be_bytes().to_vec();
+        raw.extend(3i32.to_be_bytes());
+        raw.extend(14i32.to_be_bytes());
+        assert_eq!(Interval::from_sql(&Type::INTERVAL, &raw).unwrap(), Interval { months: 14, days: 3, microseconds: 14_706_500_000 });
+        assert!(Interval::from_sql(&Type::INTERVAL, &raw[..8]).is_err());
+    }
+
+    #[test]
+    fn test_inet() {
+        let inet = |raw: &[u8]| Inet::from_sql(&Type::INET, raw).map(|inet| inet.to_string());
+        assert_eq!(inet(&[2, 32, 0, 4, 10, 0, 0, 1]).unwrap(), "10.0.0.1");
+        assert_eq!(inet(&[2, 8, 0, 4, 10, 0, 0, 1]).unwrap(), "10.0.0.1/8");
+        assert_eq!(inet(&[2, 32, 1, 4, 10, 0, 0, 1]).unwrap(), "10.0.0.1/32");
+        let mut v6 = vec![3, 64, 1, 16, 0x20, 0x01, 0x0d, 0xb8];
+        v6.extend([0; 12]);
+        assert_eq!(inet(&v6).unwrap(), "2001:db8::/64");
+        assert!(inet(&[2, 32, 0, 4, 10, 0]).is_err());
+        assert!(inet(&[9, 32, 0, 4, 10, 0, 0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_mac_address() {
+        assert_eq!(MacAddress(vec![0x08, 0x00, 0x2b, 0x01, 0x02, 0x03]).to_string(), "08:00:2b:01:02:03");
+    }
+}
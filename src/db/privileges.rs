@@ -0,0 +1,83 @@
+use super::routing;
+
+/// Table-level write that an action in the UI needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Privilege {
+    Insert,
+    Update,
+    Delete,
+    Truncate,
+}
+
+impl Privilege {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Privilege::Insert => "INSERT",
+            Privilege::Update => "UPDATE",
+            Privilege::Delete => "DELETE",
+            Privilege::Truncate => "TRUNCATE",
+        }
+    }
+
+    /// What a statement starting with INSERT, UPDATE, DELETE or TRUNCATE needs on its
+    /// table; None for anything else.
+    pub fn for_statement(sql: &str) -> Option<Self> {
+        match routing::first_keyword(sql)?.as_str() {
+            "insert" => Some(Privilege::Insert),
+            "update" => Some(Privilege::Update),
+            "delete" => Some(Privilege::Delete),
+            "truncate" => Some(Privilege::Truncate),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the connected role may perform on one table, as the server reports them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TablePrivileges {
+    pub insert: bool,
+    // Any column, so a role that may only update some columns can still edit those
+    pub update: bool,
+    pub delete: bool,
+    pub truncate: bool,
+}
+
+impl TablePrivileges {
+    pub fn allows(&self, privilege: Privilege) -> bool {
+        match privilege {
+            Privilege::Insert => self.insert,
+            Privilege::Update => self.update,
+            Privilege::Delete => self.delete,
+            Privilege::Truncate => self.truncate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::SchemaInfo;
+
+    #[test]
+    fn test_privilege_for_statement() {
+        assert_eq!(Privilege::for_statement("INSERT INTO {table} ({columns})"), Some(Privilege::Insert));
+        assert_eq!(Privilege::for_statement("-- fix typos\nupdate {table} SET"), Some(Privilege::Update));
+        assert_eq!(Privilege::for_statement("TRUNCATE {table}"), Some(Privilege::Truncate));
+        assert_eq!(Privilege::for_statement("SELECT {columns}\nFROM {table}"), None);
+        assert_eq!(Privilege::for_statement(""), None);
+    }
+
+    #[test]
+    fn test_denied() {
+        let mut schema = SchemaInfo::new("public".to_string());
+        schema.table_privileges.insert("orders".to_string(), TablePrivileges { insert: true, update: false, delete: false, truncate: false });
+
+        assert_eq!(schema.denied("orders", Privilege::Insert), None);
+        assert_eq!(
+            schema.denied("orders", Privilege::Update).as_deref(),
+            Some("Needs the UPDATE privilege on public.orders, which the current role has not been granted")
+        );
+        // Unknown tables are left to the server to refuse
+        assert_eq!(schema.denied("users", Privilege::Delete), None);
+    }
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use serde_json::Value;
+
+// Postgres block size used to turn temp block counts into bytes
+const BLOCK_SIZE: u64 = 8192;
+
+/// Timing and buffer usage of one execution, read from `EXPLAIN (ANALYZE, BUFFERS)`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResourceReport {
+    pub planning_ms: f64,
+    pub execution_ms: f64,
+    pub shared_hit_blocks: u64,
+    pub shared_read_blocks: u64,
+    pub temp_read_blocks: u64,
+    pub temp_written_blocks: u64,
+}
+
+impl ResourceReport {
+    /// Build the report from the JSON document returned by `EXPLAIN (FORMAT JSON, ANALYZE, BUFFERS)`.
+    /// The root plan node already includes the buffer counts of its children.
+    pub fn from_plan(plan: &Value) -> Result<Self> {
+        let plan = match plan {
+            Value::String(text) => serde_json::from_str(text)?,
+            other => other.clone(),
+        };
+        let root = plan.get(0).ok_or_else(|| anyhow::anyhow!("EXPLAIN output is not a JSON plan"))?;
+        let node = root.get("Plan").ok_or_else(|| anyhow::anyhow!("EXPLAIN output has no plan node"))?;
+
+        let float = |value: &Value, key: &str| value.get(key).and_then(Value::as_f64).unwrap_or(0.0);
+        let blocks = |key: &str| node.get(key).and_then(Value::as_u64).unwrap_or(0);
+
+        Ok(Self {
+            planning_ms: float(root, "Planning Time"),
+            execution_ms: float(root, "Execution Time"),
+            shared_hit_blocks: blocks("Shared Hit Blocks"),
+            shared_read_blocks: blocks("Shared Read Blocks"),
+            temp_read_blocks: blocks("Temp Read Blocks"),
+            temp_written_blocks: blocks("Temp Written Blocks"),
+        })
+    }
+
+    /// Share of shared buffer accesses served from cache, if there were any.
+    pub fn cache_hit_ratio(&self) -> Option<f64> {
+        let total = self.shared_hit_blocks + self.shared_read_blocks;
+        (total > 0).then(|| self.shared_hit_blocks as f64 / total as f64)
+    }
+
+    pub fn temp_written_bytes(&self) -> u64 {
+        self.temp_written_blocks * BLOCK_SIZE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_plan() {
+        let plan = serde_json::json!([{
+            "Plan": {
+                "Node Type": "Sort",
+                "Shared Hit Blocks": 90,
+                "Shared Read Blocks": 10,
+                "Temp Read Blocks": 4,
+                "Temp Written Blocks": 4,
+                "Plans": [{"Node Type": "Seq Scan", "Shared Hit Blocks": 90}]
+            },
+            "Planning Time": 0.25,
+            "Execution Time": 12.5
+        }]);
+
+        let report = ResourceReport::from_plan(&plan).unwrap();
+        assert_eq!(report.planning_ms, 0.25);
+        assert_eq!(report.execution_ms, 12.5);
+        assert_eq!(report.shared_hit_blocks, 90);
+        assert_eq!(report.cache_hit_ratio(), Some(0.9));
+        assert_eq!(report.temp_written_bytes(), 4 * 8192);
+    }
+
+    #[test]
+    fn test_from_plan_as_string_and_missing_buffers() {
+        let plan = Value::String(r#"[{"Plan": {"Node Type": "Result"}, "Execution Time": 0.01}]"#.to_string());
+        let report = ResourceReport::from_plan(&plan).unwrap();
+        assert_eq!(report.execution_ms, 0.01);
+        assert_eq!(report.cache_hit_ratio(), None);
+    }
+
+    #[test]
+    fn test_rejects_non_plan() {
+        assert!(ResourceReport::from_plan(&serde_json::json!({"a": 1})).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+/// Which server a statement should be sent to when a connection has replicas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryTarget {
+    Primary,
+    Replica,
+}
+
+impl QueryTarget {
+    pub fn as_str(&self) -> &str {
+        match self {
+            QueryTarget::Primary => "primary",
+            QueryTarget::Replica => "replica",
+        }
+    }
+}
+
+// Statements that may start a read-only query
+const READ_KEYWORDS: &[&str] = &["select", "with", "show", "explain", "values", "table"];
+
+// Any of these anywhere in a statement means it has to go to the primary
+// (covers `SELECT ... INTO`, `FOR UPDATE` locking and writable CTEs)
+const WRITE_KEYWORDS: &[&str] = &[
+    "insert", "update", "delete", "merge", "into", "create", "alter", "drop", "truncate",
+    "grant", "revoke", "copy", "call", "do", "lock", "vacuum", "analyze", "refresh",
+    "nextval", "setval", "share",
+];
+
+/// Returns true when every statement in `sql` is a plain read.
+///
+/// This is deliberately conservative: anything that is not recognised as a read
+/// is treated as a write so it ends up on the primary.
+pub fn is_read_only(sql: &str) -> bool {
+    let statements = split_words(sql);
+    let mut saw_statement = false;
+
+    for words in statements.iter().filter(|words| !words.is_empty()) {
+        saw_statement = true;
+        if !READ_KEYWORDS.contains(&words[0].as_str()) {
+            return false;
+        }
+        // EXPLAIN ANALYZE executes the statement, the keyword check below catches it
+        if words.iter().any(|w| WRITE_KEYWORDS.contains(&w.as_str())) {
+            return false;
+        }
+    }
+
+    saw_statement
+}
+
+/// Decide where to send `sql`, honouring the manual override from the query panel.
+pub fn route(sql: &str, force_primary: bool, replica_available: bool) -> QueryTarget {
+    if is_read_only(sql) {
+        read_target(force_primary, replica_available)
+    } else {
+        QueryTarget::Primary
+    }
+}
+
+/// Where reads the app makes itself (browsing, exporting or dumping a table) go.
+pub fn read_target(force_primary: bool, replica_available: bool) -> QueryTarget {
+    if replica_available && !force_primary {
+        QueryTarget::Replica
+    } else {
+        QueryTarget::Primary
+    }
+}
+
+/// Lowercased first word of the first statement in `sql`, skipping comments.
+pub fn first_keyword(sql: &str) -> Option<String> {
+    split_words(sql).into_iter().find_map(|words| words.into_iter().next())
+}
+
+// Split into statements of lowercase bare words, skipping comments, string
+// literals and quoted identifiers
+fn split_words(sql: &str) -> Vec<Vec<String>> {
+    let mut statements = vec![Vec::new()];
+    let mut word = String::new();
+    let mut chars = sql.chars().peekable();
+
+    fn flush(word: &mut String, statements: &mut [Vec<String>]) {
+        if !word.is_empty() {
+            if let Some(current) = statements.last_mut() {
+                current.push(word.to_lowercase());
+            }
+            word.clear();
+        }
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            '-' if chars.peek() == Some(&'-') => {
+                flush(&mut word, &mut statements);
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                flush(&mut word, &mut statements);
+                chars.next();
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            '\'' | '"' => {
+                flush(&mut word, &mut statements);
+                for next in chars.by_ref() {
+                    if next == c {
+                        break;
+                    }
+                }
+            }
+            ';' => {
+                flush(&mut word, &mut statements);
+                statements.push(Vec::new());
+            }
+            c if c.is_alphanumeric() || c == '_' => word.push(c),
+            _ => flush(&mut word, &mut statements),
+        }
+    }
+    flush(&mut word, &mut statements);
+
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_select_is_read_only() {
+        assert!(is_read_only("SELECT * FROM users WHERE id = 1"));
+        assert!(is_read_only("  select 1;"));
+        assert!(is_read_only("WITH t AS (SELECT 1) SELECT * FROM t"));
+        assert!(is_read_only("-- comment\nSELECT 1"));
+    }
+
+    #[test]
+    fn test_writes_are_not_read_only() {
+        assert!(!is_read_only("UPDATE users SET name = 'x'"));
+        assert!(!is_read_only("INSERT INTO t VALUES (1)"));
+        assert!(!is_read_only("SELECT * INTO backup FROM users"));
+        assert!(!is_read_only("SELECT * FROM users FOR UPDATE"));
+        assert!(!is_read_only("WITH d AS (DELETE FROM t RETURNING *) SELECT * FROM d"));
+        assert!(!is_read_only("SELECT 1; DROP TABLE users"));
+        assert!(!is_read_only("EXPLAIN ANALYZE DELETE FROM users"));
+    }
+
+    #[test]
+    fn test_keywords_in_literals_are_ignored() {
+        assert!(is_read_only("SELECT 'delete me' FROM notes"));
+        assert!(is_read_only("SELECT \"update\" FROM audit /* insert */"));
+    }
+
+    #[test]
+    fn test_empty_query_goes_to_primary() {
+        assert!(!is_read_only(""));
+        assert!(!is_read_only("-- just a comment"));
+    }
+
+    #[test]
+    fn test_route() {
+        assert_eq!(route("SELECT 1", false, true), QueryTarget::Replica);
+        assert_eq!(route("SELECT 1", true, true), QueryTarget::Primary);
+        assert_eq!(route("SELECT 1", false, false), QueryTarget::Primary);
+        assert_eq!(route("DELETE FROM t", false, true), QueryTarget::Primary);
+        assert_eq!(read_target(false, true), QueryTarget::Replica);
+        assert_eq!(read_target(true, true), QueryTarget::Primary);
+        assert_eq!(read_target(false, false), QueryTarget::Primary);
+    }
+}
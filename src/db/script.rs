@@ -0,0 +1,508 @@
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+use super::{is_cancelled, is_timeout, DatabaseBackend, StatementOutcome, StatementTimeout};
+
+/// One unit of work read from a SQL script.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptItem {
+    Statement { line: usize, sql: String },
+    // Something only psql understands, reported in the log instead of being sent
+    Skipped { line: usize, reason: String },
+}
+
+/// Splits a SQL script into statements one line at a time, so large dumps never
+/// have to be held in memory. Semicolons inside string literals, quoted identifiers,
+/// dollar-quoted bodies and comments do not end a statement.
+#[derive(Debug, Default)]
+pub struct StatementSplitter {
+    buffer: String,
+    // Line of the first non-comment character of the current statement
+    start_line: Option<usize>,
+    in_single_quote: bool,
+    in_double_quote: bool,
+    dollar_tag: Option<String>,
+    block_comment_depth: usize,
+    // Inside the inline data of `COPY ... FROM stdin`, up to the `\.` terminator
+    in_copy_data: bool,
+}
+
+impl StatementSplitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the next line (without its line ending). Returns the items it completed.
+    pub fn push_line(&mut self, line: &str, line_number: usize) -> Vec<ScriptItem> {
+        let mut items = Vec::new();
+
+        if self.in_copy_data {
+            if line == "\\." {
+                self.in_copy_data = false;
+            }
+            return items;
+        }
+
+        if self.start_line.is_none() && !self.in_quoted_text() && line.trim_start().starts_with('\\') {
+            items.push(ScriptItem::Skipped {
+                line: line_number,
+                reason: format!("psql meta-command skipped: {}", line.trim()),
+            });
+            return items;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+
+            if self.block_comment_depth > 0 {
+                if c == '*' && next == Some('/') {
+                    self.block_comment_depth -= 1;
+                    self.buffer.push_str("*/");
+                    i += 2;
+                    continue;
+                }
+                if c == '/' && next == Some('*') {
+                    self.block_comment_depth += 1;
+                    self.buffer.push_str("/*");
+                    i += 2;
+                    continue;
+                }
+                self.buffer.push(c);
+                i += 1;
+                continue;
+            }
+
+            if self.in_single_quote {
+                self.buffer.push(c);
+                if c == '\'' {
+                    if next == Some('\'') {
+                        self.buffer.push('\'');
+                        i += 1;
+                    } else {
+                        self.in_single_quote = false;
+                    }
+                }
+                i += 1;
+                continue;
+            }
+
+            if self.in_double_quote {
+                self.buffer.push(c);
+                if c == '"' {
+                    self.in_double_quote = false;
+                }
+                i += 1;
+                continue;
+            }
+
+            if let Some(tag) = &self.dollar_tag {
+                if line[char_offset(&chars, i)..].starts_with(tag.as_str()) {
+                    let len = tag.chars().count();
+                    self.buffer.push_str(tag);
+                    self.dollar_tag = None;
+                    i += len;
+                } else {
+                    self.buffer.push(c);
+                    i += 1;
+                }
+                continue;
+            }
+
+            match c {
+                '-' if next == Some('-') => {
+                    // Rest of the line is a comment
+                    self.buffer.extend(&chars[i..]);
+                    break;
+                }
+                '/' if next == Some('*') => {
+                    self.block_comment_depth = 1;
+                    self.buffer.push_str("/*");
+                    i += 2;
+                    continue;
+                }
+                ';' => {
+                    if let Some(item) = self.take_statement() {
+                        items.push(item);
+                    }
+                    i += 1;
+                    if self.in_copy_data {
+                        // Data starts on the next line
+                        break;
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            if !c.is_whitespace() {
+                self.start_line.get_or_insert(line_number);
+            }
+            match c {
+                '\'' => self.in_single_quote = true,
+                '"' => self.in_double_quote = true,
+                '$' => {
+                    if let Some(tag) = dollar_tag_at(&chars, i) {
+                        let len = tag.chars().count();
+                        self.buffer.push_str(&tag);
+                        self.dollar_tag = Some(tag);
+                        i += len;
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            self.buffer.push(c);
+            i += 1;
+        }
+
+        self.buffer.push('\n');
+        items
+    }
+
+    /// Flush a final statement that was not terminated by a semicolon.
+    pub fn finish(&mut self) -> Vec<ScriptItem> {
+        self.take_statement().into_iter().collect()
+    }
+
+    fn in_quoted_text(&self) -> bool {
+        self.in_single_quote || self.in_double_quote || self.dollar_tag.is_some() || self.block_comment_depth > 0
+    }
+
+    fn take_statement(&mut self) -> Option<ScriptItem> {
+        let sql = std::mem::take(&mut self.buffer).trim().to_string();
+        let line = self.start_line.take()?;
+
+        if is_copy_from_stdin(&sql) {
+            self.in_copy_data = true;
+            return Some(ScriptItem::Skipped {
+                line,
+                reason: "COPY ... FROM stdin is not supported, its data block was skipped".to_string(),
+            });
+        }
+        Some(ScriptItem::Statement { line, sql })
+    }
+}
+
+/// Statements of a query buffer, split the same way scripts are. psql meta-commands
+/// are dropped.
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let mut splitter = StatementSplitter::new();
+    let mut items: Vec<ScriptItem> = sql.lines()
+        .enumerate()
+        .flat_map(|(i, line)| splitter.push_line(line, i + 1))
+        .collect();
+    items.extend(splitter.finish());
+
+    items.into_iter()
+        .filter_map(|item| match item {
+            ScriptItem::Statement { sql, .. } => Some(sql),
+            ScriptItem::Skipped { .. } => None,
+        })
+        .collect()
+}
+
+// Byte offset of the `index`-th char
+fn char_offset(chars: &[char], index: usize) -> usize {
+    chars[..index].iter().map(|c| c.len_utf8()).sum()
+}
+
+// `$tag$` or `$$` starting at `start`, but not a `$1` parameter or part of an identifier
+fn dollar_tag_at(chars: &[char], start: usize) -> Option<String> {
+    if start > 0 && (chars[start - 1].is_alphanumeric() || chars[start - 1] == '_') {
+        return None;
+    }
+    let mut end = start + 1;
+    while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
+        end += 1;
+    }
+    if chars.get(end) != Some(&'$') || chars.get(start + 1).is_some_and(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(chars[start..=end].iter().collect())
+}
+
+fn is_copy_from_stdin(sql: &str) -> bool {
+    let words: Vec<String> = sql.lines()
+        .filter(|line| !line.trim_start().starts_with("--"))
+        .flat_map(str::split_whitespace)
+        .map(str::to_lowercase)
+        .collect();
+    words.first().is_some_and(|w| w == "copy")
+        && words.windows(2).any(|pair| pair[0] == "from" && pair[1].trim_end_matches(';') == "stdin")
+}
+
+/// One line of the runner's log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptLogEntry {
+    pub line: usize,
+    pub message: String,
+    pub is_error: bool,
+}
+
+/// Progress of a running script, shared between the runner thread and the UI.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptProgress {
+    pub total_bytes: u64,
+    pub bytes_read: u64,
+    pub executed: usize,
+    pub log: Vec<ScriptLogEntry>,
+    // Set by the UI; the runner stops before the next statement
+    pub stop_requested: bool,
+}
+
+impl ScriptProgress {
+    pub fn fraction(&self) -> f32 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        (self.bytes_read as f64 / self.total_bytes as f64) as f32
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.log.iter().filter(|entry| entry.is_error).count()
+    }
+
+    fn push_log(&mut self, line: usize, message: String, is_error: bool) {
+        self.log.push(ScriptLogEntry { line, message, is_error });
+    }
+}
+
+/// One statement of a multi-statement query and what it produced.
+#[derive(Debug, Clone)]
+pub struct StatementRun {
+    pub sql: String,
+    pub outcome: Result<StatementOutcome, String>,
+    pub duration_ms: u64,
+}
+
+impl StatementRun {
+    pub fn summary(&self) -> StatementSummary {
+        let result = match &self.outcome {
+            Ok(StatementOutcome::Rows((_, rows))) => format!("{} row(s) returned", rows.len()),
+            Ok(StatementOutcome::Affected(count)) => command_tag(&self.sql, *count),
+            Err(e) => format!("Error: {}", e),
+        };
+        StatementSummary { sql: self.sql.clone(), result, is_error: self.outcome.is_err() }
+    }
+}
+
+/// Line of the summary shown after a multi-statement query, without the rows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementSummary {
+    pub sql: String,
+    pub result: String, // e.g. "12 row(s) returned"
+    pub is_error: bool,
+}
+
+// Commands whose tag carries the number of rows they touched
+const COUNTED_COMMANDS: &[&str] = &["INSERT", "UPDATE", "DELETE", "MERGE", "COPY", "REPLACE"];
+
+// Words between CREATE and the kind of object it creates
+const CREATE_MODIFIERS: &[&str] = &["OR", "REPLACE", "UNIQUE", "TEMP", "TEMPORARY", "UNLOGGED"];
+
+/// psql-style tag for a statement that returned no rows, e.g. "UPDATE 42" or "CREATE TABLE".
+pub fn command_tag(sql: &str, affected: u64) -> String {
+    let mut words = skip_leading_comments(sql)
+        .split(|c: char| !c.is_ascii_alphabetic() && c != '_')
+        .filter(|word| !word.is_empty())
+        .map(str::to_uppercase);
+    let Some(command) = words.next() else {
+        return format!("{} row(s) affected", affected);
+    };
+
+    if COUNTED_COMMANDS.contains(&command.as_str()) || affected > 0 {
+        return format!("{} {}", command, affected);
+    }
+    let object = match command.as_str() {
+        "CREATE" => words.find(|word| !CREATE_MODIFIERS.contains(&word.as_str())),
+        "ALTER" | "DROP" => words.next(),
+        _ => None,
+    };
+    match object {
+        Some(object) => format!("{} {}", command, object),
+        None => command,
+    }
+}
+
+fn skip_leading_comments(mut sql: &str) -> &str {
+    loop {
+        sql = sql.trim_start();
+        if let Some(rest) = sql.strip_prefix("--") {
+            sql = rest.split_once('\n').map_or("", |(_, rest)| rest);
+        } else if let Some(rest) = sql.strip_prefix("/*") {
+            sql = rest.split_once("*/").map_or("", |(_, rest)| rest);
+        } else {
+            return sql;
+        }
+    }
+}
+
+/// Run `statements` one after another, stopping after the first one that fails.
+/// Each runs in its own implicit transaction, so earlier statements stay applied.
+pub async fn run_statements(db: &dyn DatabaseBackend, statements: Vec<String>, timeout: StatementTimeout) -> Vec<StatementRun> {
+    let mut runs = Vec::with_capacity(statements.len());
+    for sql in statements {
+        let started = Instant::now();
+        let outcome = match db.execute_statement(&sql, timeout).await {
+            Ok(outcome) => Ok(outcome),
+            Err(e) if is_timeout(&e) => Err("statement timeout exceeded".to_string()),
+            Err(e) if is_cancelled(&e) => Err("cancelled".to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        let failed = outcome.is_err();
+        runs.push(StatementRun { sql, outcome, duration_ms: started.elapsed().as_millis() as u64 });
+        if failed {
+            break;
+        }
+    }
+    runs
+}
+
+/// Stream `path` to the server statement by statement, each in its own implicit
+/// transaction like psql does. Results are discarded. Returns the number of
+/// statements that succeeded; with `stop_on_error` the first failure ends the run.
+pub async fn run_script(db: &dyn DatabaseBackend, path: &Path, stop_on_error: bool, progress: &Mutex<ScriptProgress>) -> Result<usize> {
+    let file = File::open(path)?;
+    progress.lock().unwrap().total_bytes = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+    let mut splitter = StatementSplitter::new();
+
+    let mut line = String::new();
+    let mut line_number = 0;
+    let mut bytes_read = 0;
+    let mut executed = 0;
+
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line)?;
+        let items = if read == 0 {
+            splitter.finish()
+        } else {
+            line_number += 1;
+            bytes_read += read as u64;
+            splitter.push_line(line.trim_end_matches(['\n', '\r']), line_number)
+        };
+
+        for item in items {
+            if progress.lock().unwrap().stop_requested {
+                return Ok(executed);
+            }
+            match item {
+                ScriptItem::Statement { line, sql } => match db.execute_batch(&sql).await {
+                    Ok(()) => {
+                        executed += 1;
+                        progress.lock().unwrap().executed = executed;
+                    }
+                    Err(e) if is_cancelled(&e) => return Err(e),
+                    Err(e) => {
+                        progress.lock().unwrap().push_log(line, e.to_string(), true);
+                        if stop_on_error {
+                            anyhow::bail!("stopped at line {}: {}", line, e);
+                        }
+                    }
+                },
+                ScriptItem::Skipped { line, reason } => progress.lock().unwrap().push_log(line, reason, false),
+            }
+        }
+
+        progress.lock().unwrap().bytes_read = bytes_read;
+        if read == 0 {
+            return Ok(executed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(script: &str) -> Vec<ScriptItem> {
+        let mut splitter = StatementSplitter::new();
+        let mut items: Vec<ScriptItem> = script.lines()
+            .enumerate()
+            .flat_map(|(i, line)| splitter.push_line(line, i + 1))
+            .collect();
+        items.extend(splitter.finish());
+        items
+    }
+
+    fn statement(line: usize, sql: &str) -> ScriptItem {
+        ScriptItem::Statement { line, sql: sql.to_string() }
+    }
+
+    #[test]
+    fn test_splits_on_semicolons_outside_literals() {
+        let items = split("INSERT INTO t VALUES ('a;b', \"c;d\"); SELECT 1;\n-- done; really\nSELECT 2");
+        assert_eq!(items, vec![
+            statement(1, "INSERT INTO t VALUES ('a;b', \"c;d\")"),
+            statement(1, "SELECT 1"),
+            statement(3, "-- done; really\nSELECT 2"),
+        ]);
+    }
+
+    #[test]
+    fn test_dollar_quoted_function_body() {
+        let script = "CREATE FUNCTION f() RETURNS int AS $body$\nBEGIN\n  RETURN 1;\nEND;\n$body$ LANGUAGE plpgsql;\nSELECT $1, 'it''s';";
+        let items = split(script);
+        assert_eq!(items.len(), 2);
+        assert!(matches!(&items[0], ScriptItem::Statement { line: 1, sql } if sql.ends_with("$body$ LANGUAGE plpgsql")));
+        assert_eq!(items[1], statement(6, "SELECT $1, 'it''s'"));
+    }
+
+    #[test]
+    fn test_block_comments_and_meta_commands() {
+        let items = split("\\connect app\n/* setup; nested /* ; */ */\nCREATE TABLE t (id int);");
+        assert_eq!(items, vec![
+            ScriptItem::Skipped { line: 1, reason: "psql meta-command skipped: \\connect app".to_string() },
+            statement(3, "/* setup; nested /* ; */ */\nCREATE TABLE t (id int)"),
+        ]);
+    }
+
+    #[test]
+    fn test_copy_from_stdin_data_is_skipped() {
+        let items = split("COPY t (id, name) FROM stdin;\n1\tone;\n2\ttwo\n\\.\nSELECT 1;");
+        assert_eq!(items.len(), 2);
+        assert!(matches!(&items[0], ScriptItem::Skipped { line: 1, .. }));
+        assert_eq!(items[1], statement(5, "SELECT 1"));
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            split_statements("SELECT 1;\n\\timing\nUPDATE t SET a = ';';\n\nDELETE FROM t"),
+            vec!["SELECT 1", "UPDATE t SET a = ';'", "DELETE FROM t"]
+        );
+        assert_eq!(split_statements("SELECT 1;  -- trailing comment"), vec!["SELECT 1"]);
+    }
+
+    #[test]
+    fn test_statement_summary() {
+        let result = |outcome| StatementRun { sql: "UPDATE t SET a = 1".to_string(), outcome, duration_ms: 0 }.summary().result;
+        assert_eq!(result(Ok(StatementOutcome::Rows((vec![], vec![vec![], vec![]])))), "2 row(s) returned");
+        assert_eq!(result(Ok(StatementOutcome::Affected(3))), "UPDATE 3");
+        let failed = StatementRun { sql: "SELEC 1".to_string(), outcome: Err("syntax error".to_string()), duration_ms: 0 }.summary();
+        assert_eq!(failed, StatementSummary { sql: "SELEC 1".to_string(), result: "Error: syntax error".to_string(), is_error: true });
+    }
+
+    #[test]
+    fn test_command_tag() {
+        assert_eq!(command_tag("update t set a = 1", 42), "UPDATE 42");
+        assert_eq!(command_tag("-- clean up\n/* old rows */ DELETE FROM t", 0), "DELETE 0");
+        assert_eq!(command_tag("CREATE OR REPLACE VIEW v AS SELECT 1", 0), "CREATE VIEW");
+        assert_eq!(command_tag("create unique index i on t (a)", 0), "CREATE INDEX");
+        assert_eq!(command_tag("DROP TABLE t", 0), "DROP TABLE");
+        assert_eq!(command_tag("TRUNCATE t", 0), "TRUNCATE");
+        assert_eq!(command_tag("", 2), "2 row(s) affected");
+    }
+
+    #[test]
+    fn test_comment_only_script_has_no_statements() {
+        assert!(split("-- nothing here;\n\n/* ; */").is_empty());
+    }
+}
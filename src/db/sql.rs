@@ -0,0 +1,571 @@
+//! Helpers for building SQL text safely.
+
+use crate::config::Driver;
+use crate::db::{ColumnInfo, ForeignKeyTarget, TableDefinition, TableStructure};
+use std::ops::Range;
+
+/// A WHERE condition for loading a table, with the values it binds as text
+/// parameters in placeholder order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableFilter {
+    pub sql: String,
+    pub params: Vec<String>,
+}
+
+/// Column a table is loaded in order of.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableOrder {
+    pub column: String,
+    pub ascending: bool,
+}
+
+impl TableOrder {
+    pub fn direction(&self) -> &str {
+        if self.ascending { "ASC" } else { "DESC" }
+    }
+}
+
+/// Quote an identifier (table, column, schema name) for PostgreSQL.
+pub fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// Quote a string literal for PostgreSQL.
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// `"schema"."table"`
+pub fn qualified_name(schema: &str, table: &str) -> String {
+    format!("{}.{}", quote_ident(schema), quote_ident(table))
+}
+
+/// `COPY schema.table (columns) FROM STDIN` in PostgreSQL's text format.
+pub fn copy_in_statement(schema: &str, table: &str, columns: &[String]) -> String {
+    let columns: Vec<String> = columns.iter().map(|column| quote_ident(column)).collect();
+    format!("COPY {} ({}) FROM STDIN", qualified_name(schema, table), columns.join(", "))
+}
+
+/// Append `row` to `out` as a line of COPY's text format: tab-separated, `\N` for NULL
+/// and backslash escapes for the characters that would end a field or line.
+pub fn push_copy_line(out: &mut String, row: &[Option<String>]) {
+    for (index, value) in row.iter().enumerate() {
+        if index > 0 {
+            out.push('\t');
+        }
+        match value {
+            None => out.push_str("\\N"),
+            Some(value) => {
+                for c in value.chars() {
+                    match c {
+                        '\\' => out.push_str("\\\\"),
+                        '\t' => out.push_str("\\t"),
+                        '\n' => out.push_str("\\n"),
+                        '\r' => out.push_str("\\r"),
+                        c => out.push(c),
+                    }
+                }
+            }
+        }
+    }
+    out.push('\n');
+}
+
+/// How a PostgreSQL column gets its value when an INSERT leaves it out.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnDefault {
+    None,
+    Expression(String),
+    Identity { always: bool },
+    // Computed from this expression and stored, never given
+    Generated(String),
+}
+
+/// A PostgreSQL column as its catalog describes it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDefinition {
+    pub name: String,
+    pub data_type: String, // as format_type() writes it, e.g. character varying(40)
+    pub not_null: bool,
+    pub default: ColumnDefault,
+}
+
+/// CREATE TABLE recreating a PostgreSQL table from its columns and structure. Columns
+/// defaulting to `nextval()` become serials, and their sequences, like identity
+/// columns', are moved past the dumped rows afterwards. Indexes not backing a
+/// constraint and foreign keys are returned apart, so that rows load before the first
+/// and tables in any order despite the second; triggers are left out.
+pub fn postgres_table_definition(schema: &str, table: &str, columns: &[ColumnDefinition], structure: &TableStructure) -> TableDefinition {
+    let name = qualified_name(schema, table);
+    let mut definition = TableDefinition::default();
+    let mut lines = Vec::new();
+
+    for column in columns {
+        let serial = match (&column.default, column.data_type.as_str()) {
+            (ColumnDefault::Expression(expression), data_type) if expression.starts_with("nextval(") => match data_type {
+                "smallint" => Some("smallserial"),
+                "integer" => Some("serial"),
+                "bigint" => Some("bigserial"),
+                _ => None,
+            },
+            _ => None,
+        };
+        let mut line = format!("    {} {}", quote_ident(&column.name), serial.unwrap_or(&column.data_type));
+        match &column.default {
+            ColumnDefault::None => {}
+            ColumnDefault::Expression(_) if serial.is_some() => {}
+            ColumnDefault::Expression(expression) => line.push_str(&format!(" DEFAULT {}", expression)),
+            ColumnDefault::Identity { always } => {
+                line.push_str(if *always { " GENERATED ALWAYS AS IDENTITY" } else { " GENERATED BY DEFAULT AS IDENTITY" });
+                definition.overriding_system_value |= always;
+            }
+            ColumnDefault::Generated(expression) => line.push_str(&format!(" GENERATED ALWAYS AS ({}) STORED", expression)),
+        }
+        if column.not_null {
+            line.push_str(" NOT NULL");
+        }
+        lines.push(line);
+
+        if serial.is_some() || matches!(column.default, ColumnDefault::Identity { .. }) {
+            let quoted = quote_ident(&column.name);
+            definition.after_rows.push(format!(
+                "SELECT setval(pg_get_serial_sequence({}, {}), max({})) FROM {} HAVING max({}) IS NOT NULL",
+                quote_literal(&name), quote_literal(&column.name), quoted, name, quoted
+            ));
+        }
+        if !matches!(column.default, ColumnDefault::Generated(_)) {
+            definition.columns.push(ColumnInfo {
+                name: column.name.clone(),
+                data_type: column.data_type.clone(),
+                is_primary_key: false,
+                is_foreign_key: false,
+            });
+        }
+    }
+
+    for constraint in &structure.constraints {
+        let line = format!("CONSTRAINT {} {}", quote_ident(&constraint.name), constraint.definition);
+        match constraint.kind.as_str() {
+            "FOREIGN KEY" => definition.foreign_keys.push(format!("ALTER TABLE {} ADD {}", name, line)),
+            "PRIMARY KEY" | "UNIQUE" | "CHECK" | "EXCLUDE" => lines.push(format!("    {}", line)),
+            _ => {}
+        }
+    }
+    // Constraints create their indexes under their own name
+    for index in &structure.indexes {
+        if !structure.constraints.iter().any(|constraint| constraint.name == index.name) {
+            definition.indexes.push(index.definition.clone());
+        }
+    }
+
+    definition.create = format!("CREATE TABLE {} (\n{}\n)", name, lines.join(",\n"));
+    definition
+}
+
+/// Quote an identifier with the syntax `driver` understands by default.
+pub fn quote_ident_for(driver: Driver, ident: &str) -> String {
+    match driver {
+        Driver::Postgres => quote_ident(ident),
+        Driver::MySql => format!("`{}`", ident.replace('`', "``")),
+    }
+}
+
+/// Quote a string literal with the syntax `driver` understands by default; MySQL
+/// reads backslashes in literals as escapes.
+pub fn quote_literal_for(driver: Driver, value: &str) -> String {
+    match driver {
+        Driver::Postgres => quote_literal(value),
+        Driver::MySql => quote_literal(&value.replace('\\', "\\\\")),
+    }
+}
+
+/// Whether a MySQL column of `data_type` holds bytes rather than text.
+pub fn is_mysql_binary_type(data_type: &str) -> bool {
+    let base = data_type.split('(').next().unwrap_or_default().trim().to_lowercase();
+    base == "binary" || base == "varbinary" || base == "bit" || base.ends_with("blob")
+}
+
+/// Query reading `columns` of `schema.table` for a SQL dump, every value as the text the
+/// server writes for it. MySQL's binary values are read hex-encoded.
+pub fn dump_select(driver: Driver, schema: &str, table: &str, columns: &[ColumnInfo]) -> String {
+    let quote = |ident: &str| quote_ident_for(driver, ident);
+    let values: Vec<String> = columns.iter()
+        .map(|column| match driver {
+            Driver::Postgres => format!("{}::text", quote(&column.name)),
+            Driver::MySql if is_mysql_binary_type(&column.data_type) => format!("HEX({})", quote(&column.name)),
+            Driver::MySql => quote(&column.name),
+        })
+        .collect();
+    format!("SELECT {} FROM {}.{}", values.join(", "), quote(schema), quote(table))
+}
+
+/// Statement selecting the row a foreign key value refers to.
+pub fn referenced_row_query(driver: Driver, target: &ForeignKeyTarget, value: &str) -> String {
+    let quote = |ident: &str| quote_ident_for(driver, ident);
+    format!(
+        "SELECT * FROM {}.{} WHERE {} = {}",
+        quote(&target.schema), quote(&target.table), quote(&target.column), quote_literal_for(driver, value)
+    )
+}
+
+/// Fill in a query template for one table. Supported placeholders are `{schema}`,
+/// `{table}` (schema-qualified), `{columns}` (comma-separated, `*` when unknown)
+/// and `{pk}` (the first primary key column, else the first column).
+pub fn render_template(template: &str, driver: Driver, schema: &str, table: &str, columns: &[ColumnInfo]) -> String {
+    let quote = |ident: &str| quote_ident_for(driver, ident);
+    let column_list = if columns.is_empty() {
+        "*".to_string()
+    } else {
+        columns.iter().map(|c| quote(&c.name)).collect::<Vec<_>>().join(", ")
+    };
+    let pk = columns.iter()
+        .find(|c| c.is_primary_key)
+        .or(columns.first())
+        .map(|c| quote(&c.name))
+        .unwrap_or_default();
+
+    template
+        .replace("{schema}", &quote(schema))
+        .replace("{table}", &format!("{}.{}", quote(schema), quote(table)))
+        .replace("{columns}", &column_list)
+        .replace("{pk}", &pk)
+}
+
+/// Width of the time buckets rows are counted in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeBucket {
+    Hour,
+    Day,
+    Week,
+}
+
+impl TimeBucket {
+    pub fn all() -> Vec<TimeBucket> {
+        vec![TimeBucket::Hour, TimeBucket::Day, TimeBucket::Week]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            TimeBucket::Hour => "hour",
+            TimeBucket::Day => "day",
+            TimeBucket::Week => "week",
+        }
+    }
+}
+
+/// Rows of a table counted per hour, day or week of a timestamp column, oldest
+/// bucket first. Weeks start on Monday with both drivers.
+pub fn bucket_query(driver: Driver, schema: &str, table: &str, column: &str, bucket: TimeBucket) -> String {
+    let quote = |ident: &str| quote_ident_for(driver, ident);
+    let column = quote(column);
+    let truncated = match (driver, bucket) {
+        (Driver::Postgres, _) => format!("date_trunc('{}', {})", bucket.as_str(), column),
+        (Driver::MySql, TimeBucket::Hour) => format!("DATE_FORMAT({}, '%Y-%m-%d %H:00:00')", column),
+        (Driver::MySql, TimeBucket::Day) => format!("DATE({})", column),
+        (Driver::MySql, TimeBucket::Week) => format!("DATE(DATE_SUB({0}, INTERVAL WEEKDAY({0}) DAY))", column),
+    };
+    format!(
+        "SELECT {} AS bucket, count(*) AS count FROM {}.{} WHERE {} IS NOT NULL GROUP BY 1 ORDER BY 1",
+        truncated, quote(schema), quote(table), column
+    )
+}
+
+/// Rows a query's trailing LIMIT/OFFSET asks for, which a query tab pages through by
+/// rewriting the clause and running the query again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SqlPage {
+    pub limit: u64,
+    pub offset: u64,
+}
+
+impl SqlPage {
+    /// 0-based page the offset falls in, counting pages of `limit` rows.
+    pub fn number(&self) -> u64 {
+        self.offset / self.limit.max(1)
+    }
+
+    pub fn previous(&self) -> Option<SqlPage> {
+        (self.offset > 0).then(|| SqlPage { limit: self.limit, offset: self.offset.saturating_sub(self.limit) })
+    }
+
+    pub fn next(&self) -> SqlPage {
+        SqlPage { limit: self.limit, offset: self.offset.saturating_add(self.limit) }
+    }
+
+    /// Pages of `limit` rows, starting at the one holding the current first row.
+    pub fn with_limit(&self, limit: u64) -> SqlPage {
+        let limit = limit.max(1);
+        SqlPage { limit, offset: self.offset / limit * limit }
+    }
+}
+
+/// The LIMIT/OFFSET ending a single read-only statement: `LIMIT n`, `LIMIT n OFFSET m`,
+/// `OFFSET m LIMIT n` or MySQL's `LIMIT m, n`. None when there is none outside subqueries.
+pub fn sql_page(sql: &str) -> Option<SqlPage> {
+    paging_clause(sql).map(|(_, page, _)| page)
+}
+
+/// `sql` with its trailing LIMIT/OFFSET set to `page`, unchanged when it has none.
+pub fn with_sql_page(sql: &str, page: SqlPage) -> String {
+    let Some((range, _, lowercase)) = paging_clause(sql) else {
+        return sql.to_string();
+    };
+    let mut clause = if page.offset == 0 {
+        format!("LIMIT {}", page.limit)
+    } else {
+        format!("LIMIT {} OFFSET {}", page.limit, page.offset)
+    };
+    if lowercase {
+        clause = clause.to_lowercase();
+    }
+    format!("{}{}{}", &sql[..range.start], clause, &sql[range.end..])
+}
+
+// Byte range of the paging clause, the page it asks for and whether it was written in lowercase
+fn paging_clause(sql: &str) -> Option<(Range<usize>, SqlPage, bool)> {
+    if !crate::db::routing::is_read_only(sql) {
+        return None;
+    }
+    let mut tokens = top_level_tokens(sql);
+    if tokens.last().is_some_and(|(token, _)| *token == ";") {
+        tokens.pop();
+    }
+    if tokens.iter().any(|(token, _)| *token == ";") {
+        return None;
+    }
+
+    let number = |index: usize| tokens.get(index).and_then(|(token, _)| token.parse::<u64>().ok());
+    let keyword = |index: usize, word: &str| tokens.get(index).is_some_and(|(token, _)| token.eq_ignore_ascii_case(word));
+    let n = tokens.len();
+    let (start, page) = if n >= 4 && keyword(n - 4, "limit") && tokens[n - 2].0 == "," {
+        (n - 4, SqlPage { limit: number(n - 1)?, offset: number(n - 3)? })
+    } else if n >= 4 && keyword(n - 4, "limit") && keyword(n - 2, "offset") {
+        (n - 4, SqlPage { limit: number(n - 3)?, offset: number(n - 1)? })
+    } else if n >= 4 && keyword(n - 4, "offset") && keyword(n - 2, "limit") {
+        (n - 4, SqlPage { limit: number(n - 1)?, offset: number(n - 3)? })
+    } else if n >= 2 && keyword(n - 2, "limit") {
+        (n - 2, SqlPage { limit: number(n - 1)?, offset: 0 })
+    } else {
+        return None;
+    };
+    if page.limit == 0 {
+        return None;
+    }
+
+    let lowercase = tokens[start].0.chars().all(|c| c.is_ascii_lowercase());
+    Some((tokens[start].1.start..tokens[n - 1].1.end, page, lowercase))
+}
+
+// Words, numbers and punctuation outside parentheses, quotes and comments, with their byte ranges
+fn top_level_tokens(sql: &str) -> Vec<(&str, Range<usize>)> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c == b'\'' || c == b'"' || c == b'`' {
+            // A doubled quote continues the same literal, so skipping pairs is enough
+            i += 1;
+            while i < bytes.len() && bytes[i] != c {
+                i += 1;
+            }
+            i += 1;
+        } else if sql[i..].starts_with("--") {
+            i = sql[i..].find('\n').map_or(bytes.len(), |end| i + end);
+        } else if sql[i..].starts_with("/*") {
+            i = sql[i + 2..].find("*/").map_or(bytes.len(), |end| i + 2 + end + 2);
+        } else if c == b'(' {
+            depth += 1;
+            i += 1;
+        } else if c == b')' {
+            depth = depth.saturating_sub(1);
+            i += 1;
+        } else if c.is_ascii_alphanumeric() || c == b'_' || c == b'$' {
+            let start = i;
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'$') {
+                i += 1;
+            }
+            if depth == 0 {
+                tokens.push((&sql[start..i], start..i));
+            }
+        } else if c.is_ascii_whitespace() || !c.is_ascii() {
+            i += 1;
+        } else {
+            if depth == 0 {
+                tokens.push((&sql[i..i + 1], i..i + 1));
+            }
+            i += 1;
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_ident() {
+        assert_eq!(quote_ident("users"), "\"users\"");
+        assert_eq!(quote_ident("we\"ird"), "\"we\"\"ird\"");
+    }
+
+    #[test]
+    fn test_quote_literal() {
+        assert_eq!(quote_literal("abc"), "'abc'");
+        assert_eq!(quote_literal("O'Brien"), "'O''Brien'");
+    }
+
+    #[test]
+    fn test_qualified_name() {
+        assert_eq!(qualified_name("public", "Users"), "\"public\".\"Users\"");
+    }
+
+    #[test]
+    fn test_copy_in() {
+        let columns = vec!["id".to_string(), "note".to_string()];
+        assert_eq!(copy_in_statement("public", "notes", &columns), "COPY \"public\".\"notes\" (\"id\", \"note\") FROM STDIN");
+
+        let mut out = String::new();
+        push_copy_line(&mut out, &[Some("1".to_string()), Some("a\tb\\c\r\nd".to_string())]);
+        push_copy_line(&mut out, &[Some("2".to_string()), None]);
+        push_copy_line(&mut out, &[Some("3".to_string()), Some("\\N".to_string())]);
+        assert_eq!(out, "1\ta\\tb\\\\c\\r\\nd\n2\t\\N\n3\t\\\\N\n");
+    }
+
+    #[test]
+    fn test_dump_select() {
+        let columns = vec![
+            ColumnInfo { is_primary_key: true, ..ColumnInfo::test("id", "text") },
+            ColumnInfo::test("photo", "longblob"),
+        ];
+        assert_eq!(dump_select(Driver::Postgres, "shop", "users", &columns), r#"SELECT "id"::text, "photo"::text FROM "shop"."users""#);
+        assert_eq!(dump_select(Driver::MySql, "shop", "users", &columns), "SELECT `id`, HEX(`photo`) FROM `shop`.`users`");
+        assert!(is_mysql_binary_type("varbinary(16)"));
+        assert!(!is_mysql_binary_type("varchar"));
+    }
+
+    #[test]
+    fn test_postgres_table_definition() {
+        let column = |name: &str, data_type: &str, not_null: bool, default: ColumnDefault| ColumnDefinition {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            not_null,
+            default,
+        };
+        let columns = vec![
+            column("id", "integer", true, ColumnDefault::Expression("nextval('orders_id_seq'::regclass)".to_string())),
+            column("number", "bigint", true, ColumnDefault::Identity { always: true }),
+            column("status", "character varying(20)", false, ColumnDefault::Expression("'new'::character varying".to_string())),
+            column("total", "numeric(10,2)", false, ColumnDefault::None),
+            column("tax", "numeric", false, ColumnDefault::Generated("(total * 0.2)".to_string())),
+        ];
+        let constraint = |name: &str, kind: &str, definition: &str| crate::db::TableObject {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            definition: definition.to_string(),
+        };
+        let structure = TableStructure {
+            indexes: vec![
+                constraint("orders_pkey", "PRIMARY", "CREATE UNIQUE INDEX orders_pkey ON shop.orders USING btree (id)"),
+                constraint("orders_status_idx", "INDEX", "CREATE INDEX orders_status_idx ON shop.orders USING btree (status)"),
+            ],
+            constraints: vec![
+                constraint("orders_pkey", "PRIMARY KEY", "PRIMARY KEY (id)"),
+                constraint("orders_user_fkey", "FOREIGN KEY", "FOREIGN KEY (user_id) REFERENCES users(id)"),
+                constraint("orders_total_not_null", "n", "NOT NULL total"),
+            ],
+            triggers: Vec::new(),
+        };
+
+        let definition = postgres_table_definition("shop", "orders", &columns, &structure);
+        assert_eq!(definition.create, r#"CREATE TABLE "shop"."orders" (
+    "id" serial NOT NULL,
+    "number" bigint GENERATED ALWAYS AS IDENTITY NOT NULL,
+    "status" character varying(20) DEFAULT 'new'::character varying,
+    "total" numeric(10,2),
+    "tax" numeric GENERATED ALWAYS AS ((total * 0.2)) STORED,
+    CONSTRAINT "orders_pkey" PRIMARY KEY (id)
+)"#);
+        let names: Vec<&str> = definition.columns.iter().map(|column| column.name.as_str()).collect();
+        assert_eq!(names, ["id", "number", "status", "total"]);
+        assert_eq!(definition.columns[2].data_type, "character varying(20)");
+        assert!(definition.overriding_system_value);
+        assert_eq!(definition.after_rows, [
+            "SELECT setval(pg_get_serial_sequence('\"shop\".\"orders\"', 'id'), max(\"id\")) FROM \"shop\".\"orders\" HAVING max(\"id\") IS NOT NULL",
+            "SELECT setval(pg_get_serial_sequence('\"shop\".\"orders\"', 'number'), max(\"number\")) FROM \"shop\".\"orders\" HAVING max(\"number\") IS NOT NULL",
+        ]);
+        assert_eq!(definition.indexes, ["CREATE INDEX orders_status_idx ON shop.orders USING btree (status)"]);
+        assert_eq!(definition.foreign_keys, [
+            "ALTER TABLE \"shop\".\"orders\" ADD CONSTRAINT \"orders_user_fkey\" FOREIGN KEY (user_id) REFERENCES users(id)",
+        ]);
+    }
+
+    #[test]
+    fn test_render_template() {
+        let columns = vec![ColumnInfo::test("name", "text"), ColumnInfo { is_primary_key: true, ..ColumnInfo::test("id", "text") }];
+        let template = "SELECT {columns} FROM {table} WHERE created_at > now() - interval '1 day' ORDER BY {pk} LIMIT 100";
+        assert_eq!(
+            render_template(template, Driver::Postgres, "public", "users", &columns),
+            "SELECT \"name\", \"id\" FROM \"public\".\"users\" WHERE created_at > now() - interval '1 day' ORDER BY \"id\" LIMIT 100"
+        );
+        assert_eq!(
+            render_template("SELECT {columns} FROM {table}", Driver::MySql, "shop", "orders", &[]),
+            "SELECT * FROM `shop`.`orders`"
+        );
+    }
+
+    #[test]
+    fn test_bucket_query() {
+        assert_eq!(
+            bucket_query(Driver::Postgres, "public", "events", "created_at", TimeBucket::Day),
+            "SELECT date_trunc('day', \"created_at\") AS bucket, count(*) AS count FROM \"public\".\"events\" \
+             WHERE \"created_at\" IS NOT NULL GROUP BY 1 ORDER BY 1"
+        );
+        assert_eq!(
+            bucket_query(Driver::MySql, "shop", "orders", "placed_at", TimeBucket::Week),
+            "SELECT DATE(DATE_SUB(`placed_at`, INTERVAL WEEKDAY(`placed_at`) DAY)) AS bucket, count(*) AS count \
+             FROM `shop`.`orders` WHERE `placed_at` IS NOT NULL GROUP BY 1 ORDER BY 1"
+        );
+    }
+
+    #[test]
+    fn test_referenced_row_query() {
+        let target = ForeignKeyTarget { schema: "public".to_string(), table: "users".to_string(), column: "id".to_string() };
+        assert_eq!(
+            referenced_row_query(Driver::Postgres, &target, "O'7"),
+            "SELECT * FROM \"public\".\"users\" WHERE \"id\" = 'O''7'"
+        );
+        assert_eq!(referenced_row_query(Driver::MySql, &target, "7"), "SELECT * FROM `public`.`users` WHERE `id` = '7'");
+        // A backslash can't escape the closing quote
+        assert_eq!(
+            referenced_row_query(Driver::MySql, &target, "\\' OR 1=1 -- "),
+            "SELECT * FROM `public`.`users` WHERE `id` = '\\\\'' OR 1=1 -- '"
+        );
+    }
+
+    #[test]
+    fn test_sql_page() {
+        assert_eq!(sql_page("SELECT * FROM t LIMIT 50"), Some(SqlPage { limit: 50, offset: 0 }));
+        assert_eq!(sql_page("select * from t order by id limit 20 offset 40;"), Some(SqlPage { limit: 20, offset: 40 }));
+        assert_eq!(sql_page("SELECT * FROM t OFFSET 10 LIMIT 5"), Some(SqlPage { limit: 5, offset: 10 }));
+        assert_eq!(sql_page("SELECT * FROM t LIMIT 30, 10"), Some(SqlPage { limit: 10, offset: 30 }));
+        assert_eq!(sql_page("SELECT * FROM (SELECT * FROM t LIMIT 5) s"), None);
+        assert_eq!(sql_page("SELECT * FROM t WHERE note = 'LIMIT 5'"), None);
+        assert_eq!(sql_page("SELECT * FROM t LIMIT ALL"), None);
+        assert_eq!(sql_page("DELETE FROM t LIMIT 5"), None);
+        assert_eq!(sql_page("SELECT 1 LIMIT 1; SELECT 2 LIMIT 1"), None);
+    }
+
+    #[test]
+    fn test_with_sql_page() {
+        let page = SqlPage { limit: 20, offset: 40 };
+        assert_eq!(with_sql_page("SELECT * FROM t LIMIT 20;", page), "SELECT * FROM t LIMIT 20 OFFSET 40;");
+        assert_eq!(with_sql_page("select * from t limit 40, 20", page.previous().unwrap()), "select * from t limit 20 offset 20");
+        assert_eq!(with_sql_page("SELECT * FROM t LIMIT 20 OFFSET 40", page.with_limit(50)), "SELECT * FROM t LIMIT 50");
+        assert_eq!(with_sql_page("SELECT * FROM t", page), "SELECT * FROM t");
+        assert_eq!(page.next().number(), 3);
+    }
+}
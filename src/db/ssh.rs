@@ -0,0 +1,368 @@
+use anyhow::{Context, Result};
+use ssh2::{CheckResult, KnownHostFileKind, KnownHostKeyFormat, Session};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use crate::config::{NetworkSettings, ProxyMode, SshSettings};
+use super::proxy::{Dialer, ProxyTunnel};
+
+// Pause between polls when neither side of a forwarded connection has data
+const IDLE_SLEEP: Duration = Duration::from_millis(2);
+
+/// Local port forwarded through an SSH bastion to a database host.
+///
+/// Every local connection gets its own SSH session, so the Postgres connection and
+/// its cancel requests never share a channel. The listener stops when this is dropped.
+pub struct SshTunnel {
+    local_port: u16,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl SshTunnel {
+    /// Authenticate against the bastion and start forwarding to `target_host:target_port`.
+    /// The bastion is reached with `dialer`, so through a proxy when there is one.
+    pub fn open(settings: &SshSettings, dialer: &Dialer, target_host: &str, target_port: u16) -> Result<Self> {
+        // Fail early on bad credentials instead of on the first database connection
+        open_session(settings, dialer).context("SSH connection failed")?;
+
+        let listener = TcpListener::bind(("127.0.0.1", 0))?;
+        let local_port = listener.local_addr()?.port();
+        listener.set_nonblocking(true)?;
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let settings = settings.clone();
+        let dialer = dialer.clone();
+        let target_host = target_host.to_string();
+        let accept_shutdown = Arc::clone(&shutdown);
+
+        thread::spawn(move || {
+            while !accept_shutdown.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let settings = settings.clone();
+                        let dialer = dialer.clone();
+                        let target_host = target_host.clone();
+                        let shutdown = Arc::clone(&accept_shutdown);
+                        thread::spawn(move || {
+                            if let Err(e) = forward_connection(stream, &settings, &dialer, &target_host, target_port, &shutdown) {
+                                eprintln!("SSH tunnel error: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(50)),
+                    Err(e) => {
+                        eprintln!("SSH tunnel listener error: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self { local_port, shutdown })
+    }
+
+    pub fn local_port(&self) -> u16 {
+        self.local_port
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Whatever a connection is forwarded through. Dropping it stops the forwarding.
+pub enum Tunnel {
+    Ssh(SshTunnel),
+    Proxy(ProxyTunnel),
+}
+
+impl Tunnel {
+    pub fn local_port(&self) -> u16 {
+        match self {
+            Tunnel::Ssh(tunnel) => tunnel.local_port(),
+            Tunnel::Proxy(tunnel) => tunnel.local_port(),
+        }
+    }
+}
+
+/// Point `connection_string` at a local tunnel when the connection goes through an SSH
+/// bastion or a proxy. Without either it is returned unchanged, with no tunnel.
+pub async fn open_tunnel(connection_string: &str, ssh: &SshSettings, network: &NetworkSettings) -> Result<(String, Option<Tunnel>)> {
+    if !ssh.enabled && network.proxy.mode == ProxyMode::None {
+        return Ok((connection_string.to_string(), None));
+    }
+    let (ssh, network, original) = (ssh.clone(), network.clone(), connection_string.to_string());
+    tokio::task::spawn_blocking(move || {
+        // Find the database host to forward to, then dial the tunnel's local port instead
+        let (_, host, port) = tunnel_connection_string(&original, 0)?;
+        let tunnel = if ssh.enabled {
+            // A proxy leads to the bastion, which reaches the database itself
+            Tunnel::Ssh(SshTunnel::open(&ssh, &Dialer::new(&network, &ssh.host)?, &host, port)?)
+        } else {
+            let dialer = Dialer::new(&network, &host)?;
+            if dialer.proxy().is_none() {
+                return Ok((original, None));
+            }
+            Tunnel::Proxy(ProxyTunnel::open(&dialer, &host, port)?)
+        };
+        let (rewritten, _, _) = tunnel_connection_string(&original, tunnel.local_port())?;
+        Ok((rewritten, Some(tunnel)))
+    }).await?
+}
+
+/// Point a `key=value` connection string at the local end of a tunnel. Returns the
+/// rewritten string with the original host and port, which the tunnel forwards to.
+/// The host name is kept (with `hostaddr` overriding where to dial) so TLS can still verify it.
+pub fn tunnel_connection_string(connection_string: &str, local_port: u16) -> Result<(String, String, u16)> {
+    let mut host = None;
+    let mut port = 5432;
+    let mut params = Vec::new();
+
+    for param in connection_string.split_whitespace() {
+        match param.split_once('=') {
+            Some(("host", value)) => {
+                host = Some(value.to_string());
+                params.push(param.to_string());
+            }
+            Some(("port", value)) => port = value.parse()?,
+            Some(("hostaddr", _)) => {}
+            _ => params.push(param.to_string()),
+        }
+    }
+
+    let host = host.ok_or_else(|| anyhow::anyhow!("Connection has no host to tunnel to"))?;
+    params.push("hostaddr=127.0.0.1".to_string());
+    params.push(format!("port={}", local_port));
+    Ok((params.join(" "), host, port))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn open_session(settings: &SshSettings, dialer: &Dialer) -> Result<Session> {
+    let tcp = dialer.dial(&settings.host, settings.port)
+        .with_context(|| format!("Cannot reach SSH host {}:{}", settings.host, settings.port))?;
+    let mut session = Session::new()?;
+    session.set_tcp_stream(tcp);
+    session.handshake()?;
+    verify_host_key(&session, &settings.host, settings.port)?;
+
+    let key_path = settings.key_path.trim();
+    if !key_path.is_empty() {
+        let passphrase = Some(settings.password.as_str()).filter(|p| !p.is_empty());
+        session.userauth_pubkey_file(&settings.user, None, &expand_home(key_path), passphrase)?;
+    } else if !settings.password.is_empty() {
+        session.userauth_password(&settings.user, &settings.password)?;
+    } else {
+        session.userauth_agent(&settings.user)?;
+    }
+
+    if !session.authenticated() {
+        anyhow::bail!("SSH authentication failed for {}", settings.user);
+    }
+    Ok(session)
+}
+
+// Refuse a bastion whose key differs from the one in ~/.ssh/known_hosts, or from the one
+// it showed when first connected to, which the app's own known_hosts file remembers
+fn verify_host_key(session: &Session, host: &str, port: u16) -> Result<()> {
+    let (key, key_type) = session.host_key()
+        .ok_or_else(|| anyhow::anyhow!("SSH host {} sent no host key", host))?;
+    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
+    let files = [home.join(".ssh").join("known_hosts")];
+    let remember_in = home.join(".config").join("db-client").join("known_hosts");
+    check_host_key(session, &files, &remember_in, host, port, key, key_type.into())
+}
+
+fn check_host_key(
+    session: &Session,
+    files: &[PathBuf],
+    remember_in: &Path,
+    host: &str,
+    port: u16,
+    key: &[u8],
+    format: KnownHostKeyFormat,
+) -> Result<()> {
+    for path in files.iter().map(PathBuf::as_path).chain([remember_in]).filter(|path| path.exists()) {
+        let mut known_hosts = session.known_hosts()?;
+        // Entries libssh2 cannot parse (certificate authorities, say) leave the file unusable
+        if let Err(e) = known_hosts.read_file(path, KnownHostFileKind::OpenSSH) {
+            eprintln!("Cannot read {}: {}", path.display(), e);
+            continue;
+        }
+        match known_hosts.check_port(host, port, key) {
+            CheckResult::Match => return Ok(()),
+            CheckResult::NotFound => {}
+            CheckResult::Mismatch => anyhow::bail!(
+                "Host key of SSH host {} has changed since it was recorded in {}; refusing to connect",
+                host, path.display()
+            ),
+            CheckResult::Failure => anyhow::bail!("Could not check the host key of {} against {}", host, path.display()),
+        }
+    }
+
+    // Trust on first use, as `ssh -o StrictHostKeyChecking=accept-new` does
+    let mut known_hosts = session.known_hosts()?;
+    if remember_in.exists() {
+        known_hosts.read_file(remember_in, KnownHostFileKind::OpenSSH)?;
+    } else if let Some(dir) = remember_in.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let name = if port == 22 { host.to_string() } else { format!("[{}]:{}", host, port) };
+    known_hosts.add(&name, key, "", format)?;
+    known_hosts.write_file(remember_in, KnownHostFileKind::OpenSSH)
+        .with_context(|| format!("Cannot remember the host key of {} in {}", host, remember_in.display()))?;
+    Ok(())
+}
+
+fn forward_connection(
+    mut stream: TcpStream,
+    settings: &SshSettings,
+    dialer: &Dialer,
+    target_host: &str,
+    target_port: u16,
+    shutdown: &AtomicBool,
+) -> Result<()> {
+    let session = open_session(settings, dialer)?;
+    let mut channel = session.channel_direct_tcpip(target_host, target_port, None)?;
+
+    // Poll both directions from one thread
+    session.set_blocking(false);
+    stream.set_nonblocking(true)?;
+
+    let mut buf = [0u8; 16 * 1024];
+    while !shutdown.load(Ordering::Relaxed) {
+        let mut idle = true;
+
+        match stream.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                write_all(&mut channel, &buf[..n], shutdown)?;
+                idle = false;
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        match channel.read(&mut buf) {
+            Ok(0) if channel.eof() => break,
+            Ok(0) => {}
+            Ok(n) => {
+                write_all(&mut stream, &buf[..n], shutdown)?;
+                idle = false;
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        if idle {
+            thread::sleep(IDLE_SLEEP);
+        }
+    }
+
+    let _ = channel.close();
+    Ok(())
+}
+
+// `write_all` for non-blocking writers
+fn write_all<W: Write>(writer: &mut W, mut data: &[u8], shutdown: &AtomicBool) -> Result<()> {
+    while !data.is_empty() && !shutdown.load(Ordering::Relaxed) {
+        match writer.write(data) {
+            Ok(0) => anyhow::bail!("Tunnel connection closed"),
+            Ok(n) => data = &data[n..],
+            Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(IDLE_SLEEP),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_all_across_partial_writes() {
+        // Writer that accepts at most 3 bytes per call and sometimes blocks
+        struct Chunked {
+            out: Vec<u8>,
+            calls: usize,
+        }
+        impl Write for Chunked {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.calls += 1;
+                if self.calls.is_multiple_of(2) {
+                    return Err(ErrorKind::WouldBlock.into());
+                }
+                let n = buf.len().min(3);
+                self.out.extend_from_slice(&buf[..n]);
+                Ok(n)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut writer = Chunked { out: Vec::new(), calls: 0 };
+        write_all(&mut writer, b"hello tunnel", &AtomicBool::new(false)).unwrap();
+        assert_eq!(writer.out, b"hello tunnel");
+    }
+
+    #[test]
+    fn test_tunnel_connection_string() {
+        let (rewritten, host, port) = tunnel_connection_string(
+            "host=db.internal port=6432 user=app password=secret dbname=main sslmode=require",
+            40000,
+        ).unwrap();
+        assert_eq!(host, "db.internal");
+        assert_eq!(port, 6432);
+        assert_eq!(
+            rewritten,
+            "host=db.internal user=app password=secret dbname=main sslmode=require hostaddr=127.0.0.1 port=40000"
+        );
+    }
+
+    #[test]
+    fn test_host_key_remembered_then_checked() {
+        let dir = std::env::temp_dir().join(format!("ssh-known-hosts-{}", std::process::id()));
+        let remember_in = dir.join("known_hosts");
+        let session = Session::new().unwrap();
+        let key = [7u8; 51];
+        let check = |host: &str, port: u16, key: &[u8]| {
+            check_host_key(&session, &[dir.join("missing")], &remember_in, host, port, key, KnownHostKeyFormat::Ed25519)
+        };
+
+        check("bastion.example.com", 2222, &key).unwrap();
+        assert!(std::fs::read_to_string(&remember_in).unwrap().starts_with("[bastion.example.com]:2222 ssh-ed25519 "));
+        check("bastion.example.com", 2222, &key).unwrap();
+        let error = check("bastion.example.com", 2222, &[8u8; 51]).unwrap_err();
+        assert!(error.to_string().contains("has changed"));
+        // Other hosts are added next to it
+        check("other.example.com", 22, &[8u8; 51]).unwrap();
+        check("bastion.example.com", 2222, &key).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unreachable_bastion_fails_to_open() {
+        let settings = SshSettings {
+            enabled: true,
+            host: "127.0.0.1".to_string(),
+            port: 1,
+            user: "nobody".to_string(),
+            ..SshSettings::default()
+        };
+        let dialer = Dialer::new(&NetworkSettings::default(), &settings.host).unwrap();
+        assert!(SshTunnel::open(&settings, &dialer, "db.internal", 5432).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+use super::ColumnInfo;
+use crate::models::{rows_size, CellValue};
+
+// Rows handed to the UI at a time while a query streams
+pub const STREAM_BATCH_SIZE: usize = 1000;
+
+/// Rows of a running query, shared between the query thread and the UI so large
+/// results show up in their tab while they are still arriving.
+#[derive(Debug, Default)]
+pub struct QueryStream {
+    // Known once the statement is described; None until then or if it failed
+    pub columns: Option<Vec<ColumnInfo>>,
+    // Received but not yet moved into the tab
+    rows: Vec<Vec<CellValue>>,
+    pub total_rows: usize,
+    // Approximate size of all rows received
+    pub total_bytes: u64,
+    // Set instead of columns and rows for statements that return no result set
+    pub affected_rows: Option<u64>,
+}
+
+impl QueryStream {
+    pub fn push(&mut self, rows: Vec<Vec<CellValue>>) {
+        self.total_rows += rows.len();
+        self.total_bytes += rows_size(&rows);
+        if self.rows.is_empty() {
+            self.rows = rows;
+        } else {
+            self.rows.extend(rows);
+        }
+    }
+
+    /// Rows received since the last call.
+    pub fn take_rows(&mut self) -> Vec<Vec<CellValue>> {
+        std::mem::take(&mut self.rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_are_taken_once() {
+        let mut stream = QueryStream::default();
+        stream.push(vec![vec![CellValue::Int(1)], vec![CellValue::Int(2)]]);
+        stream.push(vec![vec![CellValue::Int(3)]]);
+
+        assert_eq!(stream.take_rows().len(), 3);
+        assert!(stream.take_rows().is_empty());
+
+        stream.push(vec![vec![CellValue::Int(4)]]);
+        assert_eq!(stream.take_rows(), vec![vec![CellValue::Int(4)]]);
+        assert_eq!(stream.total_rows, 4);
+    }
+}
@@ -0,0 +1,89 @@
+use super::routing::first_keyword;
+
+/// Per-execution limit, applied to the session's `statement_timeout` for the one
+/// statement and then put back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatementTimeout {
+    #[default]
+    None,
+    Seconds5,
+    Seconds30,
+    Minutes5,
+}
+
+impl StatementTimeout {
+    pub fn all() -> Vec<StatementTimeout> {
+        vec![
+            StatementTimeout::None,
+            StatementTimeout::Seconds5,
+            StatementTimeout::Seconds30,
+            StatementTimeout::Minutes5,
+        ]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            StatementTimeout::None => "No limit",
+            StatementTimeout::Seconds5 => "5s",
+            StatementTimeout::Seconds30 => "30s",
+            StatementTimeout::Minutes5 => "5m",
+        }
+    }
+
+    pub fn millis(&self) -> Option<u64> {
+        match self {
+            StatementTimeout::None => None,
+            StatementTimeout::Seconds5 => Some(5_000),
+            StatementTimeout::Seconds30 => Some(30_000),
+            StatementTimeout::Minutes5 => Some(300_000),
+        }
+    }
+
+    /// The limit for `sql`. Statements opening or ending a transaction get none: they are
+    /// quick, and putting the limit back inside the transaction BEGIN opens would be
+    /// undone if it is rolled back.
+    pub fn for_statement(self, sql: &str) -> StatementTimeout {
+        match first_keyword(sql).as_deref() {
+            Some("begin" | "start" | "commit" | "end" | "rollback" | "abort") => StatementTimeout::None,
+            _ => self,
+        }
+    }
+
+    /// Query applying the limit to the session and returning the setting it replaces,
+    /// which `RESTORE_TIMEOUT` puts back. None without a limit. The statement itself is
+    /// not wrapped in a transaction, so VACUUM and the like can still be limited.
+    pub fn set_statement(&self) -> Option<String> {
+        self.millis().map(|ms| format!(
+            "SELECT current_setting('statement_timeout'), set_config('statement_timeout', '{}', false)",
+            ms
+        ))
+    }
+}
+
+/// Puts back the `statement_timeout` given as the only parameter.
+pub const RESTORE_TIMEOUT: &str = "SELECT set_config('statement_timeout', $1, false)";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_limit_has_no_statement() {
+        assert_eq!(StatementTimeout::None.set_statement(), None);
+    }
+
+    #[test]
+    fn test_set_statement_uses_millis() {
+        assert_eq!(
+            StatementTimeout::Seconds30.set_statement().as_deref(),
+            Some("SELECT current_setting('statement_timeout'), set_config('statement_timeout', '30000', false)")
+        );
+    }
+
+    #[test]
+    fn test_transaction_control_is_not_limited() {
+        assert_eq!(StatementTimeout::Seconds5.for_statement("begin"), StatementTimeout::None);
+        assert_eq!(StatementTimeout::Seconds5.for_statement("-- done\nCOMMIT;"), StatementTimeout::None);
+        assert_eq!(StatementTimeout::Seconds5.for_statement("VACUUM big"), StatementTimeout::Seconds5);
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use native_tls::{Certificate, Identity, TlsConnector};
+use postgres_native_tls::MakeTlsConnector;
+use crate::config::{SslMode, TlsSettings};
+
+/// Build the TLS connector for `settings`, or None when TLS is disabled.
+///
+/// `prefer` and `require` encrypt without checking the certificate unless a CA
+/// certificate is set, then the chain is checked but not the host name (libpq's
+/// verify-ca); `verify-full` checks the chain and host name.
+pub fn make_connector(settings: &TlsSettings) -> Result<Option<MakeTlsConnector>> {
+    if settings.ssl_mode == SslMode::Disable {
+        return Ok(None);
+    }
+
+    let mut builder = TlsConnector::builder();
+
+    if settings.ssl_mode != SslMode::VerifyFull {
+        builder
+            .danger_accept_invalid_certs(!settings.verifies_certificate())
+            .danger_accept_invalid_hostnames(true);
+    }
+
+    if !settings.ca_cert_path.trim().is_empty() {
+        let pem = std::fs::read(settings.ca_cert_path.trim())
+            .with_context(|| format!("Failed to read CA certificate {}", settings.ca_cert_path))?;
+        builder.add_root_certificate(Certificate::from_pem(&pem)?);
+    }
+
+    let cert_path = settings.client_cert_path.trim();
+    let key_path = settings.client_key_path.trim();
+    match (cert_path.is_empty(), key_path.is_empty()) {
+        (true, true) => {}
+        (false, false) => {
+            let cert = std::fs::read(cert_path)
+                .with_context(|| format!("Failed to read client certificate {}", cert_path))?;
+            let key = std::fs::read(key_path)
+                .with_context(|| format!("Failed to read client key {}", key_path))?;
+            builder.identity(Identity::from_pkcs8(&cert, &key)?);
+        }
+        _ => anyhow::bail!("Client certificate and key must be set together"),
+    }
+
+    Ok(Some(MakeTlsConnector::new(builder.build()?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_has_no_connector() {
+        assert!(make_connector(&TlsSettings::default()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_ca_certificate_is_verified() {
+        let mut settings = TlsSettings { ssl_mode: SslMode::Require, ..TlsSettings::default() };
+        assert!(!settings.verifies_certificate());
+        settings.ca_cert_path = "/etc/ssl/ca.pem".to_string();
+        assert!(settings.verifies_certificate());
+        settings.ssl_mode = SslMode::Prefer;
+        assert!(settings.verifies_certificate());
+        settings.ssl_mode = SslMode::Disable;
+        assert!(!settings.verifies_certificate());
+        let settings = TlsSettings { ssl_mode: SslMode::VerifyFull, ..TlsSettings::default() };
+        assert!(settings.verifies_certificate());
+    }
+
+    #[test]
+    fn test_client_cert_requires_key() {
+        let settings = TlsSettings {
+            ssl_mode: SslMode::Require,
+            client_cert_path: "/tmp/client.crt".to_string(),
+            ..TlsSettings::default()
+        };
+        assert!(make_connector(&settings).is_err());
+    }
+
+    #[test]
+    fn test_missing_ca_file_is_an_error() {
+        let settings = TlsSettings {
+            ssl_mode: SslMode::VerifyFull,
+            ca_cert_path: "/nonexistent/ca.pem".to_string(),
+            ..TlsSettings::default()
+        };
+        let error = make_connector(&settings).err().unwrap();
+        assert!(error.to_string().contains("CA certificate"));
+    }
+}
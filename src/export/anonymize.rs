@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use crate::db::ColumnInfo;
+use crate::models::CellValue;
+
+/// How an anonymized column's values are replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AnonymizeMethod {
+    // Stable pseudonym, so equal values still match across rows and exports
+    Hash,
+    // Keep the first n characters
+    Truncate(usize),
+    Zero,
+    Null,
+}
+
+impl AnonymizeMethod {
+    pub fn all() -> Vec<AnonymizeMethod> {
+        vec![AnonymizeMethod::Hash, AnonymizeMethod::Truncate(1), AnonymizeMethod::Zero, AnonymizeMethod::Null]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            AnonymizeMethod::Hash => "Hash",
+            AnonymizeMethod::Truncate(_) => "Truncate",
+            AnonymizeMethod::Zero => "Zero",
+            AnonymizeMethod::Null => "Set NULL",
+        }
+    }
+
+    fn apply(&self, value: &CellValue) -> CellValue {
+        if value.is_null() {
+            return CellValue::Null;
+        }
+        match self {
+            AnonymizeMethod::Hash => {
+                let text = value.to_string();
+                let hash = format!("{:016x}", fnv1a(text.as_bytes()));
+                // Emails stay recognisable as emails without revealing the domain
+                if text.contains('@') {
+                    CellValue::Text(format!("{}@example.invalid", hash))
+                } else {
+                    CellValue::Text(hash)
+                }
+            }
+            AnonymizeMethod::Truncate(keep) => CellValue::Text(value.to_string().chars().take(*keep).collect()),
+            AnonymizeMethod::Zero => match value {
+                CellValue::Int(_) => CellValue::Int(0),
+                CellValue::Float(_) => CellValue::Float(0.0),
+                _ => CellValue::Text("0".to_string()),
+            },
+            AnonymizeMethod::Null => CellValue::Null,
+        }
+    }
+}
+
+/// Replaces the values of columns whose name matches `column`, a case-insensitive
+/// pattern where `*` stands for any run of characters (e.g. `*email*`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnonymizeRule {
+    pub column: String,
+    pub method: AnonymizeMethod,
+}
+
+impl AnonymizeRule {
+    pub fn matches(&self, column: &str) -> bool {
+        glob_matches(&self.column.to_lowercase(), &column.to_lowercase())
+    }
+}
+
+/// A connection's anonymization rules resolved against one result's columns.
+/// The first matching rule wins; columns no rule matches are left untouched.
+pub struct Anonymizer {
+    methods: Vec<Option<AnonymizeMethod>>,
+}
+
+impl Anonymizer {
+    /// None when no rule applies to any of the columns.
+    pub fn new(rules: &[AnonymizeRule], columns: &[ColumnInfo]) -> Option<Self> {
+        let methods: Vec<_> = columns.iter()
+            .map(|column| rules.iter().find(|rule| rule.matches(&column.name)).map(|rule| rule.method))
+            .collect();
+        methods.iter().any(Option::is_some).then_some(Self { methods })
+    }
+
+    pub fn value(&self, column: usize, value: &CellValue) -> CellValue {
+        match self.methods.get(column).copied().flatten() {
+            Some(method) => method.apply(value),
+            None => value.clone(),
+        }
+    }
+
+    pub fn rows(&self, rows: &[Vec<CellValue>]) -> Vec<Vec<CellValue>> {
+        rows.iter()
+            .map(|row| row.iter().enumerate().map(|(column, value)| self.value(column, value)).collect())
+            .collect()
+    }
+}
+
+// FNV-1a: stable across runs and platforms, unlike std's hasher. A pseudonym, not
+// protection against someone guessing and hashing candidate values.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// True when `text` matches `pattern`, where `*` stands for any run of characters.
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((prefix, rest)) => {
+            let Some(text) = text.strip_prefix(prefix) else {
+                return false;
+            };
+            // Try every split point for the remainder of the pattern
+            text.char_indices().map(|(i, _)| i).chain([text.len()]).any(|i| glob_matches(rest, &text[i..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(column: &str, method: AnonymizeMethod) -> AnonymizeRule {
+        AnonymizeRule { column: column.to_string(), method }
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("email", "email"));
+        assert!(glob_matches("*email*", "work_email_address"));
+        assert!(glob_matches("*_name", "first_name"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("*_name", "name"));
+        assert!(!glob_matches("email", "emails"));
+        assert!(rule("*Email*", AnonymizeMethod::Hash).matches("USER_EMAIL"));
+    }
+
+    #[test]
+    fn test_anonymize_rows() {
+        let columns = vec![ColumnInfo::test("id", "text"), ColumnInfo::test("email", "text"), ColumnInfo::test("first_name", "text"), ColumnInfo::test("amount", "text"), ColumnInfo::test("notes", "text")];
+        let rules = vec![
+            rule("*email*", AnonymizeMethod::Hash),
+            rule("*name", AnonymizeMethod::Truncate(1)),
+            rule("amount", AnonymizeMethod::Zero),
+            // Shadowed by the first rule for "email"
+            rule("e*", AnonymizeMethod::Null),
+            rule("notes", AnonymizeMethod::Null),
+        ];
+        let rows = vec![
+            vec![CellValue::Int(1), CellValue::from("ann@corp.com"), CellValue::from("Ann"), CellValue::Float(12.5), CellValue::from("vip")],
+            vec![CellValue::Int(2), CellValue::from("ann@corp.com"), CellValue::Null, CellValue::Int(7), CellValue::Null],
+        ];
+
+        assert!(Anonymizer::new(&rules, &[ColumnInfo::test("id", "text")]).is_none());
+        let anonymized = Anonymizer::new(&rules, &columns).unwrap().rows(&rows);
+        assert_eq!(anonymized[0][0], CellValue::Int(1));
+        let CellValue::Text(email) = &anonymized[0][1] else { panic!("email should be text") };
+        assert!(email.ends_with("@example.invalid") && !email.contains("ann"));
+        // Same input, same pseudonym
+        assert_eq!(anonymized[0][1], anonymized[1][1]);
+        assert_eq!(anonymized[0][2], CellValue::from("A"));
+        assert_eq!(anonymized[1][2], CellValue::Null);
+        assert_eq!(anonymized[0][3], CellValue::Float(0.0));
+        assert_eq!(anonymized[1][3], CellValue::Int(0));
+        assert_eq!(anonymized[0][4], CellValue::Null);
+    }
+}
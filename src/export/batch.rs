@@ -0,0 +1,99 @@
+use super::ExportFormat;
+
+/// State of one table in a batch export.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchStatus {
+    Pending,
+    Running,
+    Done(usize), // rows written
+    Failed(String),
+}
+
+impl BatchStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, BatchStatus::Done(_) | BatchStatus::Failed(_))
+    }
+}
+
+/// Progress of a batch export, shared between the export thread and the dialog.
+#[derive(Debug, Clone, Default)]
+pub struct BatchProgress {
+    pub items: Vec<(String, String, BatchStatus)>, // schema, table, status
+    // Set by the UI; the export thread stops before the next table
+    pub stop_requested: bool,
+}
+
+impl BatchProgress {
+    pub fn new(tables: &[(String, String)]) -> Self {
+        Self {
+            items: tables.iter()
+                .map(|(schema, table)| (schema.clone(), table.clone(), BatchStatus::Pending))
+                .collect(),
+            stop_requested: false,
+        }
+    }
+
+    pub fn set_status(&mut self, index: usize, status: BatchStatus) {
+        if let Some(item) = self.items.get_mut(index) {
+            item.2 = status;
+        }
+    }
+
+    pub fn finished_count(&self) -> usize {
+        self.items.iter().filter(|(_, _, status)| status.is_finished()).count()
+    }
+
+    pub fn failed_count(&self) -> usize {
+        self.items.iter().filter(|(_, _, status)| matches!(status, BatchStatus::Failed(_))).count()
+    }
+
+    /// Share of tables finished, for the progress bar.
+    pub fn fraction(&self) -> f32 {
+        if self.items.is_empty() {
+            return 1.0;
+        }
+        self.finished_count() as f32 / self.items.len() as f32
+    }
+}
+
+/// File name for one table of a batch export, e.g. `public.users.csv`.
+pub fn batch_file_name(schema: &str, table: &str, format: ExportFormat) -> String {
+    let safe = |name: &str| -> String {
+        name.chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect()
+    };
+    format!("{}.{}.{}", safe(schema), safe(table), format.extension())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_file_name() {
+        assert_eq!(batch_file_name("public", "users", ExportFormat::CSV), "public.users.csv");
+        let json_lines = ExportFormat::all().into_iter().find(|format| format.extension() == "jsonl").unwrap();
+        assert_eq!(batch_file_name("sales", "Order Items/2024", json_lines), "sales.Order_Items_2024.jsonl");
+    }
+
+    #[test]
+    fn test_progress_counts() {
+        let tables = vec![
+            ("public".to_string(), "a".to_string()),
+            ("public".to_string(), "b".to_string()),
+            ("public".to_string(), "c".to_string()),
+            ("public".to_string(), "d".to_string()),
+        ];
+        let mut progress = BatchProgress::new(&tables);
+        assert_eq!(progress.fraction(), 0.0);
+
+        progress.set_status(0, BatchStatus::Done(10));
+        progress.set_status(1, BatchStatus::Failed("permission denied".to_string()));
+        progress.set_status(2, BatchStatus::Running);
+
+        assert_eq!(progress.finished_count(), 2);
+        assert_eq!(progress.failed_count(), 1);
+        assert_eq!(progress.fraction(), 0.5);
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::BTreeSet;
+use crate::db::ColumnInfo;
+
+/// Languages a table's structure can be copied as a model definition in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CodeTarget {
+    RustStruct,
+    PythonDataclass,
+    TypeScriptInterface,
+}
+
+impl CodeTarget {
+    pub fn all() -> Vec<CodeTarget> {
+        vec![CodeTarget::RustStruct, CodeTarget::PythonDataclass, CodeTarget::TypeScriptInterface]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            CodeTarget::RustStruct => "Rust struct",
+            CodeTarget::PythonDataclass => "Python dataclass",
+            CodeTarget::TypeScriptInterface => "TypeScript interface",
+        }
+    }
+
+    /// Model definition for `table`. Nullability isn't loaded with the schema, so
+    /// columns outside the primary key are generated as optional.
+    pub fn generate(&self, table: &str, columns: &[ColumnInfo]) -> String {
+        match self {
+            CodeTarget::RustStruct => rust_struct(table, columns),
+            CodeTarget::PythonDataclass => python_dataclass(table, columns),
+            CodeTarget::TypeScriptInterface => typescript_interface(table, columns),
+        }
+    }
+}
+
+// Language-neutral shape of a column type
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Bool,
+    Int,
+    BigInt,
+    Float,
+    Decimal,
+    Date,
+    Time,
+    Timestamp,
+    TimestampTz,
+    Json,
+    Bytes,
+    Uuid,
+    Text,
+}
+
+// Works for information_schema names ("timestamp with time zone", "int(11) unsigned")
+// as well as the driver names of query results ("Timestamptz", "Int8")
+fn kind(data_type: &str) -> Kind {
+    let data_type = data_type.to_lowercase();
+    // MySQL's convention for booleans
+    if data_type.starts_with("tinyint(1)") || data_type.starts_with("bool") {
+        return Kind::Bool;
+    }
+    let base = data_type.split('(').next().unwrap_or_default().trim();
+    let starts = |prefixes: &[&str]| prefixes.iter().any(|p| base.starts_with(p));
+
+    if starts(&["interval"]) {
+        Kind::Text
+    } else if starts(&["int8", "bigint", "bigserial"]) {
+        Kind::BigInt
+    } else if starts(&["int", "smallint", "mediumint", "tinyint", "serial", "smallserial", "year"]) {
+        Kind::Int
+    } else if starts(&["float", "real", "double"]) {
+        Kind::Float
+    } else if starts(&["numeric", "decimal"]) {
+        Kind::Decimal
+    } else if base == "timestamptz" || base == "timestamp with time zone" {
+        Kind::TimestampTz
+    } else if starts(&["timestamp", "datetime"]) {
+        Kind::Timestamp
+    } else if base == "date" {
+        Kind::Date
+    } else if starts(&["time"]) {
+        Kind::Time
+    } else if starts(&["json"]) {
+        Kind::Json
+    } else if base == "bytea" || base.contains("blob") || base.contains("binary") {
+        Kind::Bytes
+    } else if base == "uuid" {
+        Kind::Uuid
+    } else {
+        Kind::Text
+    }
+}
+
+fn is_optional(column: &ColumnInfo) -> bool {
+    !column.is_primary_key
+}
+
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        // Split camelCase column names too
+        if c.is_uppercase() && prev_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn pascal_case(name: &str) -> String {
+    let name: String = words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect();
+    match name.chars().next() {
+        Some(c) if c.is_alphabetic() => name,
+        _ => format!("T{}", name),
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let name = words(name).join("_");
+    match name.chars().next() {
+        Some(c) if c.is_alphabetic() => name,
+        _ => format!("_{}", name),
+    }
+}
+
+const RUST_KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while",
+];
+
+const PYTHON_KEYWORDS: [&str; 35] = [
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in",
+    "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with", "yield",
+];
+
+fn rust_type(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Bool => "bool",
+        Kind::Int => "i32",
+        Kind::BigInt => "i64",
+        Kind::Float => "f64",
+        Kind::Decimal => "rust_decimal::Decimal",
+        Kind::Date => "chrono::NaiveDate",
+        Kind::Time => "chrono::NaiveTime",
+        Kind::Timestamp => "chrono::NaiveDateTime",
+        Kind::TimestampTz => "chrono::DateTime<chrono::Utc>",
+        Kind::Json => "serde_json::Value",
+        Kind::Bytes => "Vec<u8>",
+        Kind::Uuid => "uuid::Uuid",
+        Kind::Text => "String",
+    }
+}
+
+fn rust_struct(table: &str, columns: &[ColumnInfo]) -> String {
+    let mut out = format!(
+        "#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, sqlx::FromRow)]\npub struct {} {{\n",
+        pascal_case(table)
+    );
+    for column in columns {
+        let mut field = snake_case(&column.name);
+        if field != column.name {
+            out.push_str(&format!("    #[serde(rename = {:?})]\n", column.name));
+            out.push_str(&format!("    #[sqlx(rename = {:?})]\n", column.name));
+        }
+        if RUST_KEYWORDS.contains(&field.as_str()) {
+            field = format!("r#{}", field);
+        }
+        let ty = rust_type(kind(&column.data_type));
+        if is_optional(column) {
+            out.push_str(&format!("    pub {}: Option<{}>,\n", field, ty));
+        } else {
+            out.push_str(&format!("    pub {}: {},\n", field, ty));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+// Python type and the import it needs, if any
+fn python_type(kind: Kind) -> (&'static str, Option<&'static str>) {
+    match kind {
+        Kind::Bool => ("bool", None),
+        Kind::Int | Kind::BigInt => ("int", None),
+        Kind::Float => ("float", None),
+        Kind::Decimal => ("Decimal", Some("from decimal import Decimal")),
+        Kind::Date => ("date", Some("from datetime import date")),
+        Kind::Time => ("time", Some("from datetime import time")),
+        Kind::Timestamp | Kind::TimestampTz => ("datetime", Some("from datetime import datetime")),
+        Kind::Json => ("Any", Some("from typing import Any")),
+        Kind::Bytes => ("bytes", None),
+        Kind::Uuid => ("UUID", Some("from uuid import UUID")),
+        Kind::Text => ("str", None),
+    }
+}
+
+fn python_dataclass(table: &str, columns: &[ColumnInfo]) -> String {
+    let mut imports = BTreeSet::from(["from dataclasses import dataclass"]);
+    let mut fields = String::new();
+    for column in columns {
+        let mut field = snake_case(&column.name);
+        if PYTHON_KEYWORDS.contains(&field.as_str()) {
+            field.push('_');
+        }
+        let (ty, import) = python_type(kind(&column.data_type));
+        imports.extend(import);
+        if is_optional(column) {
+            imports.insert("from typing import Optional");
+            fields.push_str(&format!("    {}: Optional[{}]\n", field, ty));
+        } else {
+            fields.push_str(&format!("    {}: {}\n", field, ty));
+        }
+    }
+    if fields.is_empty() {
+        fields.push_str("    pass\n");
+    }
+
+    let imports: Vec<_> = imports.into_iter().collect();
+    format!("{}\n\n\n@dataclass\nclass {}:\n{}", imports.join("\n"), pascal_case(table), fields)
+}
+
+fn typescript_type(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Bool => "boolean",
+        Kind::Int | Kind::BigInt | Kind::Float => "number",
+        // Arbitrary precision doesn't fit a JS number
+        Kind::Decimal => "string",
+        Kind::Date | Kind::Time | Kind::Timestamp | Kind::TimestampTz | Kind::Uuid | Kind::Text => "string",
+        Kind::Json => "unknown",
+        Kind::Bytes => "Uint8Array",
+    }
+}
+
+fn typescript_interface(table: &str, columns: &[ColumnInfo]) -> String {
+    let mut out = format!("export interface {} {{\n", pascal_case(table));
+    for column in columns {
+        let is_identifier = column.name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+            && column.name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+        let key = if is_identifier { column.name.clone() } else { format!("{:?}", column.name) };
+        let ty = typescript_type(kind(&column.data_type));
+        if is_optional(column) {
+            out.push_str(&format!("  {}: {} | null;\n", key, ty));
+        } else {
+            out.push_str(&format!("  {}: {};\n", key, ty));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns() -> Vec<ColumnInfo> {
+        let column = |name: &str, data_type: &str, is_primary_key: bool| ColumnInfo {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            is_primary_key,
+            is_foreign_key: false,
+        };
+        vec![
+            column("id", "bigint", true),
+            column("userName", "character varying(255)", false),
+            column("type", "text", false),
+            column("created_at", "timestamp with time zone", false),
+            column("price", "numeric(10,2)", false),
+        ]
+    }
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(kind("tinyint(1)"), Kind::Bool);
+        assert_eq!(kind("int(10) unsigned"), Kind::Int);
+        assert_eq!(kind("Int8"), Kind::BigInt);
+        assert_eq!(kind("Timestamptz"), Kind::TimestampTz);
+        assert_eq!(kind("datetime"), Kind::Timestamp);
+        assert_eq!(kind("time without time zone"), Kind::Time);
+        assert_eq!(kind("longblob"), Kind::Bytes);
+        assert_eq!(kind("varchar(40)"), Kind::Text);
+        assert_eq!(kind("interval"), Kind::Text);
+    }
+
+    #[test]
+    fn test_rust_struct() {
+        assert_eq!(
+            CodeTarget::RustStruct.generate("order_items", &columns()),
+            "#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, sqlx::FromRow)]\n\
+             pub struct OrderItems {\n    \
+                 pub id: i64,\n    \
+                 #[serde(rename = \"userName\")]\n    \
+                 #[sqlx(rename = \"userName\")]\n    \
+                 pub user_name: Option<String>,\n    \
+                 pub r#type: Option<String>,\n    \
+                 pub created_at: Option<chrono::DateTime<chrono::Utc>>,\n    \
+                 pub price: Option<rust_decimal::Decimal>,\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_python_dataclass() {
+        assert_eq!(
+            CodeTarget::PythonDataclass.generate("order_items", &columns()),
+            "from dataclasses import dataclass\n\
+             from datetime import datetime\n\
+             from decimal import Decimal\n\
+             from typing import Optional\n\n\n\
+             @dataclass\n\
+             class OrderItems:\n    \
+                 id: int\n    \
+                 user_name: Optional[str]\n    \
+                 type: Optional[str]\n    \
+                 created_at: Optional[datetime]\n    \
+                 price: Optional[Decimal]\n"
+        );
+    }
+
+    #[test]
+    fn test_typescript_interface() {
+        let mut columns = columns();
+        columns.truncate(2);
+        columns[1].name = "user name".to_string();
+        assert_eq!(
+            CodeTarget::TypeScriptInterface.generate("2fa codes", &columns),
+            "export interface T2faCodes {\n  id: number;\n  \"user name\": string | null;\n}\n"
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use std::fmt::Write;
+use crate::models::CellValue;
+
+/// Whether values of this column type are dates or timestamps.
+pub fn is_date_type(data_type: &str) -> bool {
+    let data_type = data_type.to_lowercase();
+    data_type == "date" || data_type.starts_with("timestamp") || data_type.starts_with("datetime")
+}
+
+/// Format a date or timestamp cell with a strftime-style `format`. Dates kept as text
+/// (e.g. values the driver could not type) are parsed first. Returns None for other
+/// cells, unrecognised text or an invalid format, which export the value unchanged.
+pub fn format_date(value: &CellValue, format: &str) -> Option<String> {
+    let mut out = String::new();
+    match value {
+        CellValue::Date(date) => write!(out, "{}", date.format(format)).ok()?,
+        CellValue::Timestamp(timestamp) => write!(out, "{}", timestamp.format(format)).ok()?,
+        CellValue::TimestampTz(timestamp) => write!(out, "{}", timestamp.format(format)).ok()?,
+        CellValue::Text(text) => return reformat_date(text, format),
+        _ => return None,
+    }
+    Some(out)
+}
+
+/// Reformat a displayed date/timestamp with a strftime-style `format`.
+/// Returns None when the value is not a recognised timestamp or the format is invalid,
+/// in which case the value is exported unchanged.
+fn reformat_date(value: &str, format: &str) -> Option<String> {
+    let mut out = String::new();
+
+    // Timestamps with time zone are displayed in UTC with a suffix
+    let naive = value.strip_suffix(" UTC").unwrap_or(value);
+    if let Ok(timestamp) = NaiveDateTime::parse_from_str(naive, "%Y-%m-%d %H:%M:%S%.f") {
+        write!(out, "{}", timestamp.format(format)).ok()?;
+    } else if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        write!(out, "{}", date.format(format)).ok()?;
+    } else {
+        return None;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reformat_timestamps() {
+        assert_eq!(reformat_date("2024-03-05 14:30:00", "%d/%m/%Y %H:%M").as_deref(), Some("05/03/2024 14:30"));
+        assert_eq!(reformat_date("2024-03-05 14:30:00.25 UTC", "%Y-%m-%dT%H:%M:%S").as_deref(), Some("2024-03-05T14:30:00"));
+        assert_eq!(reformat_date("2024-03-05", "%m/%d/%Y").as_deref(), Some("03/05/2024"));
+    }
+
+    #[test]
+    fn test_format_typed_cells() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        let timestamp = date.and_hms_opt(14, 30, 0).unwrap();
+        assert_eq!(format_date(&CellValue::Date(date), "%d.%m.%Y").as_deref(), Some("05.03.2024"));
+        assert_eq!(format_date(&CellValue::TimestampTz(timestamp.and_utc()), "%H:%M").as_deref(), Some("14:30"));
+        assert_eq!(format_date(&CellValue::Null, "%Y"), None);
+        assert_eq!(format_date(&CellValue::Int(2024), "%Y"), None);
+    }
+
+    #[test]
+    fn test_unparseable_values_are_left_alone() {
+        assert_eq!(reformat_date("(NULL)", "%Y"), None);
+        // A date has no time to format
+        assert_eq!(reformat_date("2024-03-05", "%H:%M"), None);
+    }
+}
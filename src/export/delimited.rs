@@ -0,0 +1,176 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use crate::db::ColumnInfo;
+use crate::models::CellValue;
+use super::{ExportSettings, Exporter};
+
+/// Options for CSV / TSV style exports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DelimitedOptions {
+    pub delimiter: u8,
+    pub quote: u8,
+    pub quote_all: bool,
+    pub include_header: bool,
+    // Written in place of NULL cells
+    pub null_value: String,
+}
+
+impl Default for DelimitedOptions {
+    fn default() -> Self {
+        Self::csv()
+    }
+}
+
+impl DelimitedOptions {
+    pub fn csv() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+            quote_all: false,
+            include_header: true,
+            null_value: String::new(),
+        }
+    }
+
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: b'\t',
+            ..Self::csv()
+        }
+    }
+}
+
+/// Comma-separated values, with the delimiter and quoting of the export settings.
+pub struct Csv;
+
+impl Exporter for Csv {
+    fn name(&self) -> &str {
+        "CSV"
+    }
+
+    fn extension(&self) -> &str {
+        "csv"
+    }
+
+    fn delimited_defaults(&self) -> Option<DelimitedOptions> {
+        Some(DelimitedOptions::csv())
+    }
+
+    fn comment_prefix(&self) -> Option<&str> {
+        Some("# ")
+    }
+
+    fn write(&self, writer: &mut (dyn Write + Send), columns: &[ColumnInfo], rows: &mut dyn Iterator<Item = &Vec<CellValue>>, settings: &ExportSettings) -> Result<usize> {
+        write_delimited(writer, columns, rows, &settings.delimited)
+    }
+}
+
+/// Tab-separated values; the same writer as CSV with a tab as the default delimiter.
+pub struct Tsv;
+
+impl Exporter for Tsv {
+    fn name(&self) -> &str {
+        "TSV"
+    }
+
+    fn extension(&self) -> &str {
+        "tsv"
+    }
+
+    fn delimited_defaults(&self) -> Option<DelimitedOptions> {
+        Some(DelimitedOptions::tsv())
+    }
+
+    fn comment_prefix(&self) -> Option<&str> {
+        Some("# ")
+    }
+
+    fn write(&self, writer: &mut (dyn Write + Send), columns: &[ColumnInfo], rows: &mut dyn Iterator<Item = &Vec<CellValue>>, settings: &ExportSettings) -> Result<usize> {
+        write_delimited(writer, columns, rows, &settings.delimited)
+    }
+}
+
+/// Write `rows` as delimited text. Returns the number of data rows written.
+pub fn write_delimited<'a, W: Write>(
+    writer: W,
+    columns: &[ColumnInfo],
+    rows: impl IntoIterator<Item = &'a Vec<CellValue>>,
+    options: &DelimitedOptions,
+) -> Result<usize> {
+    let quote_style = if options.quote_all {
+        csv::QuoteStyle::Always
+    } else {
+        csv::QuoteStyle::Necessary
+    };
+
+    let mut csv_writer = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .quote_style(quote_style)
+        .from_writer(writer);
+
+    if options.include_header {
+        csv_writer.write_record(columns.iter().map(|c| c.name.as_str()))?;
+    }
+
+    let mut count = 0;
+    for row in rows {
+        csv_writer.write_record(row.iter().map(|value| {
+            if value.is_null() { options.null_value.clone() } else { value.to_string() }
+        }))?;
+        count += 1;
+    }
+
+    csv_writer.flush()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns() -> Vec<ColumnInfo> {
+        vec![
+            ColumnInfo { is_primary_key: true, ..ColumnInfo::test("id", "int4") },
+            ColumnInfo::test("note", "text"),
+        ]
+    }
+
+    fn write(options: &DelimitedOptions) -> String {
+        let rows = vec![
+            vec![CellValue::Int(1), CellValue::from("plain")],
+            vec![CellValue::Int(2), CellValue::from("has, comma \"and quotes\"")],
+        ];
+        let mut out = Vec::new();
+        write_delimited(&mut out, &columns(), &rows, options).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_csv_quotes_when_needed() {
+        assert_eq!(write(&DelimitedOptions::csv()), "id,note\n1,plain\n2,\"has, comma \"\"and quotes\"\"\"\n");
+    }
+
+    #[test]
+    fn test_tsv_without_header() {
+        let options = DelimitedOptions { include_header: false, ..DelimitedOptions::tsv() };
+        assert_eq!(write(&options), "1\tplain\n2\t\"has, comma \"\"and quotes\"\"\"\n");
+    }
+
+    #[test]
+    fn test_quote_all() {
+        let options = DelimitedOptions { quote_all: true, ..DelimitedOptions::csv() };
+        assert!(write(&options).starts_with("\"id\",\"note\"\n\"1\",\"plain\"\n"));
+    }
+
+    #[test]
+    fn test_quote_char_and_null_value() {
+        let options = DelimitedOptions { quote: b'\'', null_value: "NULL".to_string(), ..DelimitedOptions::csv() };
+        let rows = vec![vec![CellValue::Int(1), CellValue::Null], vec![CellValue::Int(2), CellValue::from("a,b")]];
+        let mut out = Vec::new();
+        write_delimited(&mut out, &columns(), &rows, &options).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "id,note\n1,NULL\n2,'a,b'\n");
+    }
+}
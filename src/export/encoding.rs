@@ -0,0 +1,68 @@
+use encoding_rs::WINDOWS_1252;
+use serde::{Deserialize, Serialize};
+
+/// Character encoding of an exported file.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ExportEncoding {
+    #[default]
+    Utf8,
+    Utf16,
+    Windows1252,
+}
+
+impl ExportEncoding {
+    pub fn all() -> Vec<ExportEncoding> {
+        vec![ExportEncoding::Utf8, ExportEncoding::Utf16, ExportEncoding::Windows1252]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ExportEncoding::Utf8 => "UTF-8",
+            ExportEncoding::Utf16 => "UTF-16",
+            ExportEncoding::Windows1252 => "Windows-1252",
+        }
+    }
+
+    /// Encode `text` for writing. UTF-16 is little-endian with a BOM so spreadsheet
+    /// tools detect it; characters Windows-1252 cannot represent become `?`.
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        match self {
+            ExportEncoding::Utf8 => text.as_bytes().to_vec(),
+            ExportEncoding::Utf16 => {
+                let mut bytes = vec![0xFF, 0xFE];
+                bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+                bytes
+            }
+            ExportEncoding::Windows1252 => {
+                let (encoded, _, had_errors) = WINDOWS_1252.encode(text);
+                if !had_errors {
+                    return encoded.into_owned();
+                }
+                // encoding_rs substitutes HTML entities, which would corrupt the data
+                let mut buf = [0u8; 4];
+                text.chars()
+                    .flat_map(|c| {
+                        let (encoded, _, had_errors) = WINDOWS_1252.encode(c.encode_utf8(&mut buf));
+                        if had_errors { vec![b'?'] } else { encoded.into_owned() }
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf16_has_bom() {
+        assert_eq!(ExportEncoding::Utf16.encode("a€"), vec![0xFF, 0xFE, b'a', 0x00, 0xAC, 0x20]);
+    }
+
+    #[test]
+    fn test_windows_1252_replaces_unmappable() {
+        assert_eq!(ExportEncoding::Windows1252.encode("café €"), b"caf\xE9 \x80".to_vec());
+        assert_eq!(ExportEncoding::Windows1252.encode("a✓b"), b"a?b".to_vec());
+    }
+}
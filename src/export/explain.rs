@@ -0,0 +1,55 @@
+use anyhow::Result;
+use serde_json::Value;
+
+/// Wrap `sql` in `EXPLAIN (FORMAT JSON ...)`. ANALYZE executes the statement.
+pub fn explain_statement(sql: &str, analyze: bool) -> String {
+    let statement = sql.trim().trim_end_matches(';').trim_end();
+    let options = if analyze { "FORMAT JSON, ANALYZE, BUFFERS" } else { "FORMAT JSON" };
+    format!("EXPLAIN ({}) {}", options, statement)
+}
+
+/// Pretty-print a JSON plan the way pev2 / explain.dalibo.com expect it:
+/// the array returned by `EXPLAIN (FORMAT JSON)`, unchanged.
+pub fn plan_for_visualizer(plan: &Value) -> Result<String> {
+    // Some drivers hand back the plan as a JSON string rather than a document
+    let plan = match plan {
+        Value::String(text) => serde_json::from_str(text)?,
+        other => other.clone(),
+    };
+
+    if !plan.is_array() {
+        return Err(anyhow::anyhow!("EXPLAIN output is not a JSON plan"));
+    }
+
+    Ok(serde_json::to_string_pretty(&plan)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_statement() {
+        assert_eq!(explain_statement("SELECT 1;", false), "EXPLAIN (FORMAT JSON) SELECT 1");
+        assert_eq!(
+            explain_statement("  SELECT * FROM t  ", true),
+            "EXPLAIN (FORMAT JSON, ANALYZE, BUFFERS) SELECT * FROM t"
+        );
+    }
+
+    #[test]
+    fn test_plan_for_visualizer() {
+        let plan = serde_json::json!([{"Plan": {"Node Type": "Result"}}]);
+        let text = plan_for_visualizer(&plan).unwrap();
+        assert!(text.starts_with('['));
+        assert!(text.contains("\"Node Type\": \"Result\""));
+
+        let as_string = Value::String(plan.to_string());
+        assert_eq!(plan_for_visualizer(&as_string).unwrap(), text);
+    }
+
+    #[test]
+    fn test_rejects_non_plan() {
+        assert!(plan_for_visualizer(&serde_json::json!({"a": 1})).is_err());
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::Result;
+use eframe::egui::ColorImage;
+use std::io::Write;
+
+/// Encode `image`, e.g. a cropped screenshot of a chart, as an RGBA PNG.
+pub fn write_png(writer: impl Write, image: &ColorImage) -> Result<()> {
+    let [width, height] = image.size;
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(image.as_raw())?;
+    writer.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui::Color32;
+
+    #[test]
+    fn test_write_png() {
+        let image = ColorImage::new([3, 2], Color32::from_rgb(10, 20, 30));
+        let mut bytes = Vec::new();
+        write_png(&mut bytes, &image).unwrap();
+
+        let mut reader = png::Decoder::new(bytes.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height, info.color_type), (3, 2, png::ColorType::Rgba));
+        assert_eq!(&pixels[..4], &[10, 20, 30, 255]);
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use serde_json::{Map, Number, Value};
+use std::io::Write;
+use crate::db::ColumnInfo;
+use crate::models::CellValue;
+use super::{ExportSettings, Exporter};
+
+/// A pretty-printed array of objects keyed by column name.
+pub struct Json;
+
+impl Exporter for Json {
+    fn name(&self) -> &str {
+        "JSON"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn write(&self, writer: &mut (dyn Write + Send), columns: &[ColumnInfo], rows: &mut dyn Iterator<Item = &Vec<CellValue>>, _settings: &ExportSettings) -> Result<usize> {
+        write_json(writer, columns, rows)
+    }
+}
+
+/// One JSON object per line (JSONL, also known as NDJSON).
+pub struct JsonLines;
+
+impl Exporter for JsonLines {
+    fn name(&self) -> &str {
+        "JSON Lines"
+    }
+
+    fn extension(&self) -> &str {
+        "jsonl"
+    }
+
+    fn write(&self, writer: &mut (dyn Write + Send), columns: &[ColumnInfo], rows: &mut dyn Iterator<Item = &Vec<CellValue>>, _settings: &ExportSettings) -> Result<usize> {
+        write_ndjson(writer, columns, rows)
+    }
+}
+
+/// Convert a cell into a JSON value of the matching type. Text cells still go by the
+/// column type, since decimals and some driver types arrive as text.
+fn cell_to_value(cell: &CellValue, data_type: &str) -> Value {
+    match cell {
+        CellValue::Null => Value::Null,
+        CellValue::Bool(b) => Value::Bool(*b),
+        CellValue::Int(n) => Value::Number((*n).into()),
+        CellValue::Float(n) => Number::from_f64(*n).map(Value::Number).unwrap_or_else(|| Value::String(n.to_string())),
+        CellValue::Json(text) => serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.clone())),
+        CellValue::Text(text) => text_to_value(text, data_type),
+        CellValue::Array(values) => Value::Array(values.iter().map(|value| cell_to_value(value, "")).collect()),
+        _ => Value::String(cell.to_string()),
+    }
+}
+
+fn text_to_value(value: &str, data_type: &str) -> Value {
+    let data_type = data_type.to_lowercase();
+    let is_integer = ["int2", "int4", "int8", "smallint", "integer", "bigint"]
+        .iter()
+        .any(|t| data_type.starts_with(t));
+    let is_float = ["float4", "float8", "real", "double", "numeric", "decimal"]
+        .iter()
+        .any(|t| data_type.starts_with(t));
+
+    if is_integer {
+        if let Ok(n) = value.parse::<i64>() {
+            return Value::Number(n.into());
+        }
+    } else if is_float {
+        if let Some(n) = value.parse::<f64>().ok().and_then(Number::from_f64) {
+            return Value::Number(n);
+        }
+    } else if data_type.starts_with("bool") {
+        if let Ok(b) = value.parse::<bool>() {
+            return Value::Bool(b);
+        }
+    } else if data_type.starts_with("json") {
+        if let Ok(v) = serde_json::from_str(value) {
+            return v;
+        }
+    }
+
+    Value::String(value.to_string())
+}
+
+fn row_to_object(columns: &[ColumnInfo], row: &[CellValue]) -> Value {
+    let object: Map<String, Value> = columns.iter()
+        .zip(row)
+        .map(|(col, value)| (col.name.clone(), cell_to_value(value, &col.data_type)))
+        .collect();
+    Value::Object(object)
+}
+
+/// Write `rows` as a pretty-printed JSON array of objects.
+pub fn write_json<'a, W: Write>(mut writer: W, columns: &[ColumnInfo], rows: impl IntoIterator<Item = &'a Vec<CellValue>>) -> Result<usize> {
+    let array: Vec<Value> = rows.into_iter().map(|row| row_to_object(columns, row)).collect();
+    serde_json::to_writer_pretty(&mut writer, &array)?;
+    writeln!(writer)?;
+    Ok(array.len())
+}
+
+/// `rows` as a pretty-printed JSON array of objects, for the clipboard.
+pub fn json_text<'a>(columns: &[ColumnInfo], rows: impl IntoIterator<Item = &'a Vec<CellValue>>) -> String {
+    let array: Vec<Value> = rows.into_iter().map(|row| row_to_object(columns, row)).collect();
+    serde_json::to_string_pretty(&array).unwrap_or_default()
+}
+
+/// Write `rows` as newline-delimited JSON, one object per line.
+pub fn write_ndjson<'a, W: Write>(mut writer: W, columns: &[ColumnInfo], rows: impl IntoIterator<Item = &'a Vec<CellValue>>) -> Result<usize> {
+    let mut count = 0;
+    for row in rows {
+        serde_json::to_writer(&mut writer, &row_to_object(columns, row))?;
+        writeln!(writer)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns() -> Vec<ColumnInfo> {
+        vec![
+            ColumnInfo { is_primary_key: true, ..ColumnInfo::test("id", "int4") },
+            ColumnInfo::test("name", "text"),
+            ColumnInfo::test("active", "bool"),
+        ]
+    }
+
+    fn rows() -> Vec<Vec<CellValue>> {
+        vec![
+            vec![CellValue::Int(1), CellValue::from("Alice"), CellValue::Bool(true)],
+            vec![CellValue::Int(2), CellValue::Null, CellValue::Bool(false)],
+        ]
+    }
+
+    #[test]
+    fn test_ndjson_one_object_per_line() {
+        let mut out = Vec::new();
+        assert_eq!(write_ndjson(&mut out, &columns(), &rows()).unwrap(), 2);
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines, vec![
+            r#"{"id":1,"name":"Alice","active":true}"#,
+            r#"{"id":2,"name":null,"active":false}"#,
+        ]);
+    }
+
+    #[test]
+    fn test_json_array_round_trips() {
+        let mut out = Vec::new();
+        write_json(&mut out, &columns(), &rows()).unwrap();
+        let parsed: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(parsed.as_array().unwrap().len(), 2);
+        assert_eq!(parsed[0]["name"], "Alice");
+        assert_eq!(parsed[1]["id"], 2);
+    }
+
+    #[test]
+    fn test_json_text() {
+        assert_eq!(json_text(&columns(), &rows()[..1]), "[\n  {\n    \"id\": 1,\n    \"name\": \"Alice\",\n    \"active\": true\n  }\n]");
+        assert_eq!(json_text(&columns(), &[]), "[]");
+    }
+
+    #[test]
+    fn test_cell_to_value_falls_back_to_string() {
+        assert_eq!(cell_to_value(&CellValue::from("abc"), "int4"), Value::String("abc".to_string()));
+        assert_eq!(cell_to_value(&CellValue::from("1.5"), "numeric(10,2)"), serde_json::json!(1.5));
+        assert_eq!(cell_to_value(&CellValue::Json(r#"{"a":1}"#.to_string()), "jsonb"), serde_json::json!({"a": 1}));
+        assert_eq!(cell_to_value(&CellValue::Float(f64::NAN), "float8"), Value::String("NaN".to_string()));
+        let tags = CellValue::Array(vec![CellValue::from("a b"), CellValue::Null, CellValue::Int(3)]);
+        assert_eq!(cell_to_value(&tags, "_text"), serde_json::json!(["a b", null, 3]));
+    }
+}
@@ -0,0 +1,177 @@
+mod anonymize;
+mod batch;
+mod codegen;
+mod dates;
+mod delimited;
+mod encoding;
+mod explain;
+mod image;
+mod json;
+mod parquet;
+mod sql_dump;
+mod watermark;
+mod xlsx;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use crate::db::ColumnInfo;
+use crate::models::CellValue;
+
+pub use anonymize::{glob_matches, AnonymizeMethod, AnonymizeRule, Anonymizer};
+pub use batch::{batch_file_name, BatchProgress, BatchStatus};
+pub use codegen::CodeTarget;
+pub use dates::{format_date, is_date_type};
+pub use delimited::{Csv, DelimitedOptions, Tsv};
+pub use encoding::ExportEncoding;
+pub use explain::{explain_statement, plan_for_visualizer};
+pub use image::write_png;
+pub use json::{json_text, Json, JsonLines};
+pub use self::parquet::Parquet;
+pub use sql_dump::{DumpRows, SqlDump};
+pub use watermark::Watermark;
+pub use xlsx::Xlsx;
+
+/// A file format a result set can be exported to. Each format lives in its own module
+/// and is offered in the export dialogs once listed in `EXPORTERS`.
+pub trait Exporter: Sync {
+    fn name(&self) -> &str;
+
+    fn extension(&self) -> &str;
+
+    /// Delimiter and quoting the format starts out with, None when the delimited
+    /// options don't apply.
+    fn delimited_defaults(&self) -> Option<DelimitedOptions> {
+        None
+    }
+
+    /// Line comment prefix used for the watermark, if the format allows comments.
+    fn comment_prefix(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether the output is text, to which the export encoding applies. Binary
+    /// formats are written as they are.
+    fn is_text(&self) -> bool {
+        true
+    }
+
+    /// Write `rows` to `writer`. Returns the number of rows written.
+    fn write(&self, writer: &mut (dyn Write + Send), columns: &[ColumnInfo], rows: &mut dyn Iterator<Item = &Vec<CellValue>>, settings: &ExportSettings) -> Result<usize>;
+}
+
+// Every format on offer, in the order the dialogs list them
+static EXPORTERS: &[&dyn Exporter] = &[&Csv, &Tsv, &Json, &JsonLines, &Parquet, &Xlsx];
+
+/// One of the registered exporters, cheap to copy and compared by name.
+#[derive(Clone, Copy)]
+pub struct ExportFormat(&'static dyn Exporter);
+
+impl ExportFormat {
+    pub const CSV: ExportFormat = ExportFormat(&Csv);
+
+    pub fn all() -> Vec<ExportFormat> {
+        EXPORTERS.iter().map(|exporter| ExportFormat(*exporter)).collect()
+    }
+
+    pub fn exporter(&self) -> &'static dyn Exporter {
+        self.0
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.0.name()
+    }
+
+    pub fn extension(&self) -> &'static str {
+        self.0.extension()
+    }
+
+    /// Whether the delimiter/quoting options apply to this format.
+    pub fn is_delimited(&self) -> bool {
+        self.0.delimited_defaults().is_some()
+    }
+
+    pub fn comment_prefix(&self) -> Option<&'static str> {
+        self.0.comment_prefix()
+    }
+}
+
+impl PartialEq for ExportFormat {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl std::fmt::Debug for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Formatting choices shared by all export formats; saved as named presets.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    pub delimited: DelimitedOptions,
+    // strftime-style format for date/timestamp columns; empty keeps values as displayed
+    pub date_format: String,
+    pub encoding: ExportEncoding,
+}
+
+/// Everything needed to write an export off the UI thread.
+pub struct ExportJob {
+    pub format: ExportFormat,
+    pub settings: ExportSettings,
+    pub columns: Vec<ColumnInfo>,
+    pub rows: Vec<Vec<CellValue>>,
+    // Already rendered watermark comment, written before or after the data
+    pub header_comment: Option<String>,
+    pub footer_comment: Option<String>,
+}
+
+impl ExportJob {
+    /// Write the export to `path`. Returns the number of rows written.
+    pub fn write_to(&self, path: &Path) -> Result<usize> {
+        let exporter = self.format.exporter();
+        let rows = self.formatted_rows();
+        let mut writer = Vec::new();
+
+        if !exporter.is_text() {
+            let count = exporter.write(&mut writer, &self.columns, &mut rows.iter(), &self.settings)?;
+            std::fs::write(path, writer)?;
+            return Ok(count);
+        }
+
+        // Rendered as UTF-8 first, then transcoded in one go
+        if let Some(comment) = &self.header_comment {
+            writer.write_all(comment.as_bytes())?;
+        }
+
+        let count = exporter.write(&mut writer, &self.columns, &mut rows.iter(), &self.settings)?;
+
+        if let Some(comment) = &self.footer_comment {
+            writer.write_all(comment.as_bytes())?;
+        }
+
+        let text = String::from_utf8(writer)?;
+        std::fs::write(path, self.settings.encoding.encode(&text))?;
+        Ok(count)
+    }
+
+    /// Rows with the date format applied to dates and timestamps.
+    fn formatted_rows(&self) -> Vec<Vec<CellValue>> {
+        let format = self.settings.date_format.trim();
+        if format.is_empty() || !self.columns.iter().any(|c| is_date_type(&c.data_type)) {
+            return self.rows.clone();
+        }
+
+        self.rows.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|value| format_date(value, format).map(CellValue::Text).unwrap_or_else(|| value.clone()))
+                    .collect()
+            })
+            .collect()
+    }
+}
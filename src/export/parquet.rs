@@ -0,0 +1,175 @@
+use anyhow::Result;
+use arrow_array::{ArrayRef, BinaryArray, BooleanArray, Date32Array, Float64Array, Int64Array, RecordBatch, StringArray, TimestampMicrosecondArray};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::NaiveDate;
+use parquet::arrow::ArrowWriter;
+use std::io::Write;
+use std::sync::Arc;
+use crate::db::ColumnInfo;
+use crate::models::CellValue;
+use super::{ExportSettings, Exporter};
+
+// Rows per record batch, and so at most per row group
+const BATCH_ROWS: usize = 8192;
+
+/// Apache Parquet, for handing results to DuckDB, Spark or pandas. Column types follow
+/// the cells of the first batch: booleans, 64-bit integers and floats, dates,
+/// microsecond timestamps (in UTC for those with a time zone) and binary stay typed,
+/// everything else (decimals, UUIDs, JSON, mixed columns) is written as UTF-8.
+pub struct Parquet;
+
+impl Exporter for Parquet {
+    fn name(&self) -> &str {
+        "Parquet"
+    }
+
+    fn extension(&self) -> &str {
+        "parquet"
+    }
+
+    fn is_text(&self) -> bool {
+        false
+    }
+
+    fn write(&self, writer: &mut (dyn Write + Send), columns: &[ColumnInfo], rows: &mut dyn Iterator<Item = &Vec<CellValue>>, _settings: &ExportSettings) -> Result<usize> {
+        let mut batch: Vec<&Vec<CellValue>> = (&mut *rows).take(BATCH_ROWS).collect();
+        let types: Vec<DataType> = (0..columns.len()).map(|index| column_type(batch.iter().filter_map(|row| row.get(index)))).collect();
+        let fields: Vec<Field> = columns.iter().zip(&types).map(|(column, data_type)| Field::new(&column.name, data_type.clone(), true)).collect();
+        let schema = Arc::new(Schema::new(fields));
+        let mut parquet_writer = ArrowWriter::try_new(writer, Arc::clone(&schema), None)?;
+
+        let mut count = 0;
+        while !batch.is_empty() {
+            let arrays: Vec<ArrayRef> = types.iter().enumerate()
+                .map(|(index, data_type)| column_array(data_type, batch.iter().map(|row| row.get(index).unwrap_or(&CellValue::Null))))
+                .collect();
+            parquet_writer.write(&RecordBatch::try_new(Arc::clone(&schema), arrays)?)?;
+            count += batch.len();
+            batch = (&mut *rows).take(BATCH_ROWS).collect();
+        }
+
+        parquet_writer.close()?;
+        Ok(count)
+    }
+}
+
+fn timestamp_type(time_zone: Option<&str>) -> DataType {
+    DataType::Timestamp(TimeUnit::Microsecond, time_zone.map(Arc::from))
+}
+
+/// Arrow type for a column holding `cells`: the type of its non-NULL cells when they
+/// all agree (integers mixed with floats give floats), UTF-8 otherwise.
+fn column_type<'a>(cells: impl Iterator<Item = &'a CellValue>) -> DataType {
+    let mut column_type: Option<DataType> = None;
+    for cell in cells {
+        let cell_type = match cell {
+            CellValue::Null => continue,
+            CellValue::Bool(_) => DataType::Boolean,
+            CellValue::Int(_) => DataType::Int64,
+            CellValue::Float(_) => DataType::Float64,
+            CellValue::Date(_) => DataType::Date32,
+            CellValue::Timestamp(_) => timestamp_type(None),
+            CellValue::TimestampTz(_) => timestamp_type(Some("UTC")),
+            CellValue::Bytes(_) => DataType::Binary,
+            CellValue::Text(_) | CellValue::Json(_) | CellValue::Uuid(_) | CellValue::Array(_) => return DataType::Utf8,
+        };
+        column_type = match (column_type, cell_type) {
+            (None, cell_type) => Some(cell_type),
+            (Some(DataType::Int64), DataType::Float64) | (Some(DataType::Float64), DataType::Int64) => Some(DataType::Float64),
+            (Some(column_type), cell_type) if column_type == cell_type => Some(column_type),
+            _ => return DataType::Utf8,
+        };
+    }
+    column_type.unwrap_or(DataType::Utf8)
+}
+
+/// Array of `data_type` from `cells`. Cells of another type (only possible past the
+/// first batch) are converted where they hold a matching value and are NULL otherwise.
+fn column_array<'a>(data_type: &DataType, cells: impl Iterator<Item = &'a CellValue>) -> ArrayRef {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or_default();
+    match data_type {
+        DataType::Boolean => Arc::new(cells.map(|cell| match cell {
+            CellValue::Bool(value) => Some(*value),
+            CellValue::Text(text) => text.trim().parse().ok(),
+            _ => None,
+        }).collect::<BooleanArray>()),
+        DataType::Int64 => Arc::new(cells.map(|cell| match cell {
+            CellValue::Int(value) => Some(*value),
+            CellValue::Text(text) => text.trim().parse().ok(),
+            _ => None,
+        }).collect::<Int64Array>()),
+        DataType::Float64 => Arc::new(cells.map(CellValue::as_f64).collect::<Float64Array>()),
+        DataType::Date32 => Arc::new(cells
+            .map(|cell| cell.as_timestamp().map(|timestamp| (timestamp.date() - epoch).num_days() as i32))
+            .collect::<Date32Array>()),
+        DataType::Timestamp(_, time_zone) => {
+            let values = cells.map(|cell| cell.as_timestamp().map(|timestamp| timestamp.and_utc().timestamp_micros())).collect::<TimestampMicrosecondArray>();
+            match time_zone {
+                Some(time_zone) => Arc::new(values.with_timezone(time_zone.as_ref())),
+                None => Arc::new(values),
+            }
+        }
+        DataType::Binary => Arc::new(cells.map(|cell| match cell {
+            CellValue::Bytes(bytes) => Some(bytes.as_slice()),
+            _ => None,
+        }).collect::<BinaryArray>()),
+        _ => Arc::new(cells.map(|cell| (!cell.is_null()).then(|| cell.to_string())).collect::<StringArray>()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+    use chrono::{DateTime, Utc};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let columns = vec![ColumnInfo::test("id", "int4"), ColumnInfo::test("name", "text"), ColumnInfo::test("score", "float8"), ColumnInfo::test("created", "timestamptz"), ColumnInfo::test("born", "date")];
+        let created = DateTime::parse_from_rfc3339("2024-03-05T14:30:00Z").unwrap().with_timezone(&Utc);
+        let rows = [
+            vec![CellValue::Int(1), CellValue::from("Alice"), CellValue::Int(3), CellValue::TimestampTz(created), CellValue::Date(NaiveDate::from_ymd_opt(1990, 1, 2).unwrap())],
+            vec![CellValue::Int(2), CellValue::Null, CellValue::Float(4.5), CellValue::Null, CellValue::Null],
+        ];
+        let mut out = Vec::new();
+        assert_eq!(Parquet.write(&mut out, &columns, &mut rows.iter(), &ExportSettings::default()).unwrap(), 2);
+
+        let path = std::env::temp_dir().join(format!("parquet-export-{}.parquet", std::process::id()));
+        std::fs::write(&path, &out).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap()).unwrap().build().unwrap();
+        let batches: Vec<RecordBatch> = reader.map(|batch| batch.unwrap()).collect();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(batches.len(), 1);
+        let schema = batches[0].schema();
+        assert_eq!(schema.field(1).name(), "name");
+        let types: Vec<&DataType> = schema.fields().iter().map(|field| field.data_type()).collect();
+        assert_eq!(types, [&DataType::Int64, &DataType::Utf8, &DataType::Float64, &timestamp_type(Some("UTC")), &DataType::Date32]);
+        let ids = batches[0].column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(ids.value(1), 2);
+        let scores = batches[0].column(2).as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(scores.value(0), 3.0);
+        let created_at = batches[0].column(3).as_any().downcast_ref::<TimestampMicrosecondArray>().unwrap();
+        assert_eq!(created_at.value(0), created.timestamp_micros());
+        assert!(created_at.is_null(1));
+        let born = batches[0].column(4).as_any().downcast_ref::<Date32Array>().unwrap();
+        assert_eq!(born.value_as_date(0), NaiveDate::from_ymd_opt(1990, 1, 2));
+        let names = batches[0].column(1).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(names.value(0), "Alice");
+        assert!(names.is_null(1));
+    }
+
+    #[test]
+    fn test_column_type() {
+        assert_eq!(column_type([CellValue::Null, CellValue::Int(1), CellValue::Float(1.5)].iter()), DataType::Float64);
+        assert_eq!(column_type([CellValue::Int(1), CellValue::Bool(true)].iter()), DataType::Utf8);
+        assert_eq!(column_type([CellValue::Null].iter()), DataType::Utf8);
+
+        // Later cells of another type convert when they can
+        let ints = column_array(&DataType::Int64, [CellValue::from("7"), CellValue::from("seven")].iter());
+        let ints = ints.as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(ints.value(0), 7);
+        assert!(ints.is_null(1));
+    }
+}
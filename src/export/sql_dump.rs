@@ -0,0 +1,263 @@
+use crate::config::Driver;
+use crate::db::sql::{copy_in_statement, is_mysql_binary_type, push_copy_line, qualified_name, quote_ident, quote_ident_for, quote_literal_for};
+use crate::db::TableDefinition;
+
+// Rows per INSERT statement
+const INSERT_BATCH: usize = 500;
+
+/// How a SQL dump writes the rows of a table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DumpRows {
+    Insert,
+    // COPY ... FROM stdin with the rows inline, as psql loads them
+    Copy,
+}
+
+impl DumpRows {
+    /// Formats a dump for `driver` can use.
+    pub fn all(driver: Driver) -> Vec<DumpRows> {
+        match driver {
+            Driver::Postgres => vec![DumpRows::Insert, DumpRows::Copy],
+            Driver::MySql => vec![DumpRows::Insert],
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            DumpRows::Insert => "INSERT statements",
+            DumpRows::Copy => "COPY",
+        }
+    }
+}
+
+/// A SQL script recreating tables and their rows, written table by table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SqlDump {
+    pub driver: Driver,
+    pub rows: DumpRows,
+    // Whether tables, their indexes and foreign keys are created, or only filled
+    pub create_tables: bool,
+}
+
+impl SqlDump {
+    /// Statements opening the dump. `schema` is created first when the whole of it is
+    /// dumped from PostgreSQL; MySQL dumps load into the current database.
+    pub fn header(&self, schema: Option<&str>) -> String {
+        match self.driver {
+            Driver::Postgres => {
+                let mut header = "SET standard_conforming_strings = on;\n\n".to_string();
+                if let (Some(schema), true) = (schema, self.create_tables) {
+                    header.push_str(&format!("CREATE SCHEMA IF NOT EXISTS {};\n\n", quote_ident(schema)));
+                }
+                header
+            }
+            // The tables' foreign keys are part of their CREATE TABLE
+            Driver::MySql => "SET FOREIGN_KEY_CHECKS = 0;\n\n".to_string(),
+        }
+    }
+
+    /// Write the CREATE TABLE of `schema.table` (when tables are created) and return
+    /// the dump of its rows, which come in batches as the backend's `dump_rows` reads them.
+    pub fn table<'a>(&'a self, out: &mut String, schema: &str, table: &str, definition: &'a TableDefinition) -> TableDump<'a> {
+        let name = match self.driver {
+            Driver::Postgres => qualified_name(schema, table),
+            Driver::MySql => quote_ident_for(Driver::MySql, table),
+        };
+        out.push_str(&format!("-- {}\n", name));
+        if self.create_tables {
+            out.push_str(&format!("{};\n\n", definition.create));
+        }
+        TableDump { dump: self, schema: schema.to_string(), table: table.to_string(), name, definition, rows: 0 }
+    }
+
+    /// Statements closing the dump: the `foreign_keys` of every dumped table, or turning
+    /// MySQL's foreign key checks back on.
+    pub fn footer(&self, foreign_keys: &[String]) -> String {
+        match self.driver {
+            Driver::Postgres if self.create_tables => foreign_keys.iter().map(|statement| format!("{};\n", statement)).collect(),
+            Driver::Postgres => String::new(),
+            Driver::MySql => "SET FOREIGN_KEY_CHECKS = 1;\n".to_string(),
+        }
+    }
+}
+
+/// The rows of one table of a dump, written batch by batch.
+pub struct TableDump<'a> {
+    dump: &'a SqlDump,
+    schema: String,
+    table: String,
+    // Quoted the way the dump's statements name the table
+    name: String,
+    definition: &'a TableDefinition,
+    rows: usize,
+}
+
+impl TableDump<'_> {
+    /// Append `rows`, each holding the server's text for the definition's columns.
+    pub fn push_rows(&mut self, out: &mut String, rows: &[Vec<Option<String>>]) {
+        if rows.is_empty() {
+            return;
+        }
+        let columns = &self.definition.columns;
+        match self.dump.rows {
+            DumpRows::Copy => {
+                if self.rows == 0 {
+                    let names: Vec<String> = columns.iter().map(|column| column.name.clone()).collect();
+                    out.push_str(&copy_in_statement(&self.schema, &self.table, &names));
+                    out.push_str(";\n");
+                }
+                for row in rows {
+                    push_copy_line(out, row);
+                }
+            }
+            DumpRows::Insert => {
+                let quoted: Vec<String> = columns.iter().map(|column| quote_ident_for(self.dump.driver, &column.name)).collect();
+                let overriding = if self.definition.overriding_system_value { " OVERRIDING SYSTEM VALUE" } else { "" };
+                for batch in rows.chunks(INSERT_BATCH) {
+                    let values: Vec<String> = batch.iter()
+                        .map(|row| {
+                            let values: Vec<String> = columns.iter().zip(row)
+                                .map(|(column, value)| sql_literal(self.dump.driver, &column.data_type, value.as_deref()))
+                                .collect();
+                            format!("({})", values.join(", "))
+                        })
+                        .collect();
+                    out.push_str(&format!(
+                        "INSERT INTO {} ({}){} VALUES\n{};\n",
+                        self.name, quoted.join(", "), overriding, values.join(",\n")
+                    ));
+                }
+            }
+        }
+        self.rows += rows.len();
+    }
+
+    /// Close the rows and append what follows them: sequences moved past the rows, and
+    /// indexes when tables are created. Returns the number of rows written.
+    pub fn finish(self, out: &mut String) -> usize {
+        if self.rows > 0 {
+            out.push_str(match self.dump.rows {
+                DumpRows::Copy => "\\.\n\n",
+                DumpRows::Insert => "\n",
+            });
+        }
+        let definition = self.definition;
+        let indexes = if self.dump.create_tables { definition.indexes.as_slice() } else { &[] };
+        for statement in definition.after_rows.iter().chain(indexes) {
+            out.push_str(&format!("{};\n", statement));
+        }
+        if !definition.after_rows.is_empty() || !indexes.is_empty() {
+            out.push('\n');
+        }
+        self.rows
+    }
+}
+
+/// The server's text for a value of a `data_type` column as a literal of `driver`'s SQL.
+/// Finite numbers are written bare; MySQL's binary values come hex-encoded.
+pub fn sql_literal(driver: Driver, data_type: &str, value: Option<&str>) -> String {
+    let Some(value) = value else {
+        return "NULL".to_string();
+    };
+    match driver {
+        _ if is_number_type(data_type) && value.parse::<f64>().is_ok_and(f64::is_finite) => value.to_string(),
+        Driver::MySql if is_mysql_binary_type(data_type) => format!("X'{}'", value),
+        _ => quote_literal_for(driver, value),
+    }
+}
+
+fn is_number_type(data_type: &str) -> bool {
+    let base = data_type.split('(').next().unwrap_or_default().trim().to_lowercase();
+    ["smallint", "integer", "bigint", "int", "tinyint", "mediumint", "numeric", "decimal", "real", "double precision", "double", "float"]
+        .contains(&base.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ColumnInfo;
+
+    fn text(values: &[Option<&str>]) -> Vec<Option<String>> {
+        values.iter().map(|value| value.map(str::to_string)).collect()
+    }
+
+    #[test]
+    fn test_sql_literal() {
+        assert_eq!(sql_literal(Driver::Postgres, "text", Some("it's \\n")), "'it''s \\n'");
+        assert_eq!(sql_literal(Driver::MySql, "varchar", Some("it's \\n")), "'it''s \\\\n'");
+        assert_eq!(sql_literal(Driver::Postgres, "bytea", Some("\\x00ff")), "'\\x00ff'");
+        assert_eq!(sql_literal(Driver::MySql, "varbinary", Some("00FF")), "X'00FF'");
+        assert_eq!(sql_literal(Driver::Postgres, "double precision", Some("-Infinity")), "'-Infinity'");
+        assert_eq!(sql_literal(Driver::Postgres, "numeric(10,2)", Some("NaN")), "'NaN'");
+        assert_eq!(sql_literal(Driver::Postgres, "double precision", Some("2.5")), "2.5");
+        assert_eq!(sql_literal(Driver::MySql, "int", Some("-7")), "-7");
+        assert_eq!(sql_literal(Driver::Postgres, "interval", Some("1 day")), "'1 day'");
+        assert_eq!(sql_literal(Driver::Postgres, "money", Some("$1.50")), "'$1.50'");
+        assert_eq!(sql_literal(Driver::Postgres, "integer[]", Some("{1,NULL}")), "'{1,NULL}'");
+        assert_eq!(sql_literal(Driver::MySql, "date", None), "NULL");
+    }
+
+    #[test]
+    fn test_table_dump() {
+        let definition = TableDefinition {
+            create: "CREATE TABLE \"public\".\"notes\" (\n    \"id\" serial NOT NULL\n)".to_string(),
+            columns: vec![ColumnInfo::test("id", "integer"), ColumnInfo::test("body", "text")],
+            after_rows: vec!["SELECT setval('notes_id_seq', 2)".to_string()],
+            indexes: vec!["CREATE INDEX notes_body ON public.notes USING btree (body)".to_string()],
+            foreign_keys: Vec::new(),
+            overriding_system_value: false,
+        };
+        let first = vec![text(&[Some("1"), Some("a\tb")])];
+        let second = vec![text(&[Some("2"), None])];
+
+        let mut dump = SqlDump { driver: Driver::Postgres, rows: DumpRows::Copy, create_tables: true };
+        let mut out = String::new();
+        let mut table = dump.table(&mut out, "public", "notes", &definition);
+        table.push_rows(&mut out, &first);
+        table.push_rows(&mut out, &second);
+        assert_eq!(table.finish(&mut out), 2);
+        assert_eq!(out, "-- \"public\".\"notes\"\n\
+            CREATE TABLE \"public\".\"notes\" (\n    \"id\" serial NOT NULL\n);\n\n\
+            COPY \"public\".\"notes\" (\"id\", \"body\") FROM STDIN;\n1\ta\\tb\n2\t\\N\n\\.\n\n\
+            SELECT setval('notes_id_seq', 2);\n\
+            CREATE INDEX notes_body ON public.notes USING btree (body);\n\n");
+
+        dump.rows = DumpRows::Insert;
+        dump.create_tables = false;
+        let mut out = String::new();
+        let mut table = dump.table(&mut out, "public", "notes", &definition);
+        table.push_rows(&mut out, &first);
+        table.push_rows(&mut out, &second);
+        table.finish(&mut out);
+        assert_eq!(out, "-- \"public\".\"notes\"\n\
+            INSERT INTO \"public\".\"notes\" (\"id\", \"body\") VALUES\n(1, 'a\tb');\n\
+            INSERT INTO \"public\".\"notes\" (\"id\", \"body\") VALUES\n(2, NULL);\n\n\
+            SELECT setval('notes_id_seq', 2);\n\n");
+
+        // An empty table has no COPY block
+        dump.rows = DumpRows::Copy;
+        let mut out = String::new();
+        assert_eq!(dump.table(&mut out, "public", "notes", &definition).finish(&mut out), 0);
+        assert_eq!(out, "-- \"public\".\"notes\"\nSELECT setval('notes_id_seq', 2);\n\n");
+    }
+
+    #[test]
+    fn test_mysql_dump() {
+        let definition = TableDefinition {
+            create: "CREATE TABLE `notes` (\n  `id` int NOT NULL,\n  `data` blob\n)".to_string(),
+            columns: vec![ColumnInfo::test("id", "int"), ColumnInfo::test("data", "blob")],
+            ..TableDefinition::default()
+        };
+        let dump = SqlDump { driver: Driver::MySql, rows: DumpRows::Insert, create_tables: true };
+        let mut out = dump.header(Some("shop"));
+        let mut table = dump.table(&mut out, "shop", "notes", &definition);
+        table.push_rows(&mut out, &[text(&[Some("7"), Some("CAFE")])]);
+        table.finish(&mut out);
+        out.push_str(&dump.footer(&[]));
+        assert_eq!(out, "SET FOREIGN_KEY_CHECKS = 0;\n\n-- `notes`\n\
+            CREATE TABLE `notes` (\n  `id` int NOT NULL,\n  `data` blob\n);\n\n\
+            INSERT INTO `notes` (`id`, `data`) VALUES\n(7, X'CAFE');\n\n\
+            SET FOREIGN_KEY_CHECKS = 1;\n");
+        assert_eq!(DumpRows::all(Driver::MySql), [DumpRows::Insert]);
+    }
+}
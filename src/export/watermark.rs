@@ -0,0 +1,116 @@
+use crate::config::{Preferences, WatermarkPosition};
+
+/// Provenance stamp added to exported files and copied SQL so they can be
+/// traced back to the connection and query they came from.
+#[derive(Debug, Clone)]
+pub struct Watermark {
+    pub source: String,
+    pub query: Option<String>,
+    pub timestamp: String,
+}
+
+impl Watermark {
+    pub fn new(source: String, query: Option<String>) -> Self {
+        Self {
+            source,
+            query,
+            timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S %z").to_string(),
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("Source: {}", self.source)];
+        if let Some(query) = &self.query {
+            // Keep multi-line queries on a single comment line
+            let flattened: Vec<&str> = query.split_whitespace().collect();
+            lines.push(format!("Query: {}", flattened.join(" ")));
+        }
+        lines.push(format!("Exported: {}", self.timestamp));
+        lines
+    }
+
+    /// Render as a comment block, each line starting with `comment_prefix` (e.g. "-- " or "# ").
+    pub fn as_comment(&self, comment_prefix: &str) -> String {
+        self.lines()
+            .iter()
+            .map(|line| format!("{}{}\n", comment_prefix, line))
+            .collect()
+    }
+
+    /// The watermark comment split into (header, footer) according to `preferences`,
+    /// for writers that stream their content instead of building one string.
+    pub fn header_and_footer(&self, comment_prefix: &str, preferences: &Preferences) -> (Option<String>, Option<String>) {
+        if !preferences.watermark_enabled {
+            return (None, None);
+        }
+
+        let comment = self.as_comment(comment_prefix);
+        match preferences.watermark_position {
+            WatermarkPosition::Header => (Some(comment), None),
+            WatermarkPosition::Footer => (None, Some(comment)),
+        }
+    }
+
+    /// Wrap `content` with the watermark if enabled in `preferences`.
+    pub fn apply(&self, content: &str, comment_prefix: &str, preferences: &Preferences) -> String {
+        if !preferences.watermark_enabled {
+            return content.to_string();
+        }
+
+        let comment = self.as_comment(comment_prefix);
+        match preferences.watermark_position {
+            WatermarkPosition::Header => format!("{}{}", comment, content),
+            WatermarkPosition::Footer => {
+                let separator = if content.is_empty() || content.ends_with('\n') { "" } else { "\n" };
+                format!("{}{}{}", content, separator, comment)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watermark() -> Watermark {
+        Watermark {
+            source: "prod (app@db:5432/app)".to_string(),
+            query: Some("SELECT *\n  FROM users".to_string()),
+            timestamp: "2024-01-01 12:00:00 +0000".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_comment_lines() {
+        let comment = watermark().as_comment("-- ");
+        assert_eq!(
+            comment,
+            "-- Source: prod (app@db:5432/app)\n-- Query: SELECT * FROM users\n-- Exported: 2024-01-01 12:00:00 +0000\n"
+        );
+    }
+
+    #[test]
+    fn test_disabled_leaves_content_untouched() {
+        let preferences = Preferences::default();
+        assert_eq!(watermark().apply("SELECT 1", "-- ", &preferences), "SELECT 1");
+    }
+
+    #[test]
+    fn test_header_and_footer() {
+        let mut preferences = Preferences {
+            watermark_enabled: true,
+            ..Preferences::default()
+        };
+        let header = watermark().apply("SELECT 1", "-- ", &preferences);
+        assert!(header.starts_with("-- Source:"));
+        assert!(header.ends_with("SELECT 1"));
+
+        preferences.watermark_position = WatermarkPosition::Footer;
+        let footer = watermark().apply("SELECT 1", "-- ", &preferences);
+        assert!(footer.starts_with("SELECT 1\n-- Source:"));
+
+        let (header, footer) = watermark().header_and_footer("# ", &preferences);
+        assert!(header.is_none());
+        assert!(footer.unwrap().starts_with("# Source:"));
+    }
+}
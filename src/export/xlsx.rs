@@ -0,0 +1,180 @@
+use anyhow::Result;
+use chrono::{Datelike, NaiveDateTime};
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+use std::io::Write;
+use crate::db::ColumnInfo;
+use crate::models::CellValue;
+use super::{ExportSettings, Exporter};
+
+// Rows per worksheet below the header; Excel's limit is 1,048,576 including it
+const SHEET_ROWS: usize = 1_048_575;
+// Longest text a cell holds, longer values are cut
+const MAX_CELL_CHARS: usize = 32_767;
+// Integers past this lose digits as Excel numbers, so they are written as text
+const MAX_EXACT_INTEGER: u64 = 1 << 53;
+
+/// Microsoft Excel workbook. Numbers (decimals too), booleans, dates and timestamps
+/// (in UTC for those with a time zone) are native cells, everything else is text;
+/// a date format from the export settings writes dates as text in that format.
+/// Results past Excel's row limit continue on further sheets, each with the header.
+pub struct Xlsx;
+
+impl Exporter for Xlsx {
+    fn name(&self) -> &str {
+        "Excel"
+    }
+
+    fn extension(&self) -> &str {
+        "xlsx"
+    }
+
+    fn is_text(&self) -> bool {
+        false
+    }
+
+    fn write(&self, writer: &mut (dyn Write + Send), columns: &[ColumnInfo], rows: &mut dyn Iterator<Item = &Vec<CellValue>>, _settings: &ExportSettings) -> Result<usize> {
+        write_sheets(writer, columns, rows, SHEET_ROWS)
+    }
+}
+
+// Cell formats shared by every sheet
+struct Formats {
+    header: Format,
+    date: Format,
+    timestamp: Format,
+}
+
+fn write_sheets(writer: &mut (dyn Write + Send), columns: &[ColumnInfo], rows: &mut dyn Iterator<Item = &Vec<CellValue>>, sheet_rows: usize) -> Result<usize> {
+    let formats = Formats {
+        header: Format::new().set_bold(),
+        date: Format::new().set_num_format("yyyy-mm-dd"),
+        timestamp: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+    };
+    let numeric: Vec<bool> = columns.iter().map(|column| is_number_type(&column.data_type)).collect();
+    let mut workbook = Workbook::new();
+    let mut rows = rows.peekable();
+    let mut count = 0;
+
+    loop {
+        // Rows go straight to a temporary file instead of being held until the save
+        let sheet = workbook.add_worksheet_with_constant_memory();
+        for (index, column) in columns.iter().enumerate() {
+            sheet.write_string_with_format(0, index as u16, truncate(&column.name), &formats.header)?;
+        }
+        sheet.set_freeze_panes(1, 0)?;
+
+        for (index, row) in (&mut rows).take(sheet_rows).enumerate() {
+            for (column, cell) in row.iter().enumerate().take(columns.len()) {
+                write_cell(sheet, index as u32 + 1, column as u16, cell, numeric[column], &formats)?;
+            }
+            count += 1;
+        }
+        if rows.peek().is_none() {
+            break;
+        }
+    }
+
+    // Sheets this large can pass the 4 GB a plain zip entry holds
+    workbook.use_zip_large_file(count > SHEET_ROWS);
+    writer.write_all(&workbook.save_to_buffer()?)?;
+    Ok(count)
+}
+
+fn write_cell(sheet: &mut Worksheet, row: u32, column: u16, cell: &CellValue, numeric: bool, formats: &Formats) -> Result<()> {
+    // Excel counts days from 1900 and stops at 9999
+    let in_range = |timestamp: &NaiveDateTime| (1900..=9999).contains(&timestamp.year());
+    match cell {
+        CellValue::Null => {}
+        CellValue::Bool(value) => {
+            sheet.write_boolean(row, column, *value)?;
+        }
+        CellValue::Int(value) if value.unsigned_abs() <= MAX_EXACT_INTEGER => {
+            sheet.write_number(row, column, *value as f64)?;
+        }
+        CellValue::Float(value) if value.is_finite() => {
+            sheet.write_number(row, column, *value)?;
+        }
+        // Decimals arrive as text
+        CellValue::Text(text) if numeric && text.trim().parse::<f64>().is_ok_and(f64::is_finite) => {
+            sheet.write_number(row, column, text.trim().parse::<f64>()?)?;
+        }
+        CellValue::Date(date) if (1900..=9999).contains(&date.year()) => {
+            sheet.write_datetime_with_format(row, column, date, &formats.date)?;
+        }
+        CellValue::Timestamp(timestamp) if in_range(timestamp) => {
+            sheet.write_datetime_with_format(row, column, timestamp, &formats.timestamp)?;
+        }
+        CellValue::TimestampTz(timestamp) if in_range(&timestamp.naive_utc()) => {
+            sheet.write_datetime_with_format(row, column, timestamp.naive_utc(), &formats.timestamp)?;
+        }
+        other => {
+            sheet.write_string(row, column, truncate(&other.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+fn is_number_type(data_type: &str) -> bool {
+    let data_type = data_type.to_lowercase();
+    ["numeric", "decimal", "money"].iter().any(|t| data_type.starts_with(t))
+}
+
+fn truncate(text: &str) -> &str {
+    match text.char_indices().nth(MAX_CELL_CHARS) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use std::io::{Cursor, Read};
+
+    fn sheet_xml(workbook: &[u8], sheet: usize) -> Option<String> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(workbook)).unwrap();
+        let mut file = archive.by_name(&format!("xl/worksheets/sheet{}.xml", sheet)).ok()?;
+        let mut xml = String::new();
+        file.read_to_string(&mut xml).unwrap();
+        Some(xml)
+    }
+
+    #[test]
+    fn test_xlsx_cells() {
+        let columns = vec![ColumnInfo::test("id", "int8"), ColumnInfo::test("price", "numeric(10,2)"), ColumnInfo::test("name", "text"), ColumnInfo::test("born", "date"), ColumnInfo::test("seen", "timestamptz")];
+        let seen = Utc.with_ymd_and_hms(2024, 3, 5, 12, 0, 0).unwrap();
+        let rows = [
+            vec![CellValue::Int(1), CellValue::from("9.50"), CellValue::from("Ann & Bo"), CellValue::Date(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()), CellValue::TimestampTz(seen)],
+            vec![CellValue::Int(i64::MAX), CellValue::from("n/a"), CellValue::Null, CellValue::Date(NaiveDate::from_ymd_opt(1850, 1, 1).unwrap()), CellValue::Null],
+        ];
+        let mut out = Vec::new();
+        assert_eq!(Xlsx.write(&mut out, &columns, &mut rows.iter(), &ExportSettings::default()).unwrap(), 2);
+
+        let xml = sheet_xml(&out, 1).unwrap();
+        assert!(xml.contains("<is><t>price</t></is>"));
+        assert!(xml.contains(r#"<c r="A2"><v>1</v></c><c r="B2"><v>9.5</v></c>"#));
+        assert!(xml.contains("<is><t>Ann &amp; Bo</t></is>"));
+        // Days since 1900, and half a day for noon
+        assert!(xml.contains("<v>45292</v>"));
+        assert!(xml.contains("<v>45356.5</v>"));
+        assert!(xml.contains("<is><t>9223372036854775807</t></is>"));
+        assert!(xml.contains("<is><t>n/a</t></is>"));
+        assert!(xml.contains("<is><t>1850-01-01</t></is>"));
+        assert!(sheet_xml(&out, 2).is_none());
+    }
+
+    #[test]
+    fn test_xlsx_continues_on_new_sheets() {
+        let columns = vec![ColumnInfo::test("n", "int4")];
+        let rows: Vec<Vec<CellValue>> = (1..=5).map(|n| vec![CellValue::Int(n)]).collect();
+        let mut out = Vec::new();
+        assert_eq!(write_sheets(&mut out, &columns, &mut rows.iter(), 2).unwrap(), 5);
+
+        let third = sheet_xml(&out, 3).unwrap();
+        assert!(third.contains("<is><t>n</t></is>"));
+        assert!(third.contains(r#"<c r="A2"><v>5</v></c>"#));
+        assert!(!third.contains(r#"r="A3""#));
+        assert!(sheet_xml(&out, 4).is_none());
+    }
+}
@@ -1,9 +1,12 @@
 mod app;
 mod config;
+mod credentials;
 mod db;
+mod export;
 mod models;
 mod sql_editor;
 mod ui;
+mod versioned;
 
 use app::DbClientApp;
 use eframe::egui;
@@ -16,9 +19,18 @@ fn main() -> Result<(), eframe::Error> {
         ..Default::default()
     };
 
+    // A shared query file (.dexq) can be passed on the command line, e.g. via file association
+    let shared_query_path = std::env::args().nth(1).map(std::path::PathBuf::from);
+
     eframe::run_native(
         "DB Client",
         options,
-        Box::new(|cc| Box::new(DbClientApp::new(cc))),
+        Box::new(move |cc| {
+            let mut app = DbClientApp::new(cc);
+            if let Some(path) = shared_query_path {
+                app.open_shared_query(&path);
+            }
+            Box::new(app)
+        }),
     )
 }
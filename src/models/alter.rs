@@ -0,0 +1,241 @@
+use anyhow::Result;
+use std::collections::HashSet;
+use crate::db::sql::{qualified_name, quote_ident};
+use crate::db::ColumnInfo;
+
+/// What a staged column change does to its NOT NULL constraint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NullabilityChange {
+    Keep,
+    SetNotNull,
+    DropNotNull,
+}
+
+impl NullabilityChange {
+    pub fn all() -> Vec<NullabilityChange> {
+        vec![NullabilityChange::Keep, NullabilityChange::SetNotNull, NullabilityChange::DropNotNull]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            NullabilityChange::Keep => "Unchanged",
+            NullabilityChange::SetNotNull => "SET NOT NULL",
+            NullabilityChange::DropNotNull => "DROP NOT NULL",
+        }
+    }
+}
+
+/// Rename, type change and nullability flip staged for one column. Fields left as
+/// they were mean no change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnAlteration {
+    pub name: String,
+    pub data_type: String,
+    pub new_name: String,
+    pub new_type: String,
+    // Replaces the suggested USING expression when not empty
+    pub using: String,
+    pub nullability: NullabilityChange,
+}
+
+impl ColumnAlteration {
+    fn new(column: &ColumnInfo) -> Self {
+        Self {
+            name: column.name.clone(),
+            data_type: column.data_type.clone(),
+            new_name: column.name.clone(),
+            new_type: column.data_type.clone(),
+            using: String::new(),
+            nullability: NullabilityChange::Keep,
+        }
+    }
+
+    pub fn is_renamed(&self) -> bool {
+        self.new_name.trim() != self.name
+    }
+
+    pub fn is_retyped(&self) -> bool {
+        !self.new_type.trim().eq_ignore_ascii_case(&self.data_type)
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.is_renamed() || self.is_retyped() || self.nullability != NullabilityChange::Keep
+    }
+
+    /// Expression the type change converts existing values with.
+    pub fn using_expression(&self) -> String {
+        match self.using.trim() {
+            "" => suggest_using(&self.name, &self.data_type, self.new_type.trim()),
+            using => using.to_string(),
+        }
+    }
+}
+
+/// Column changes staged for one PostgreSQL table in the ALTER assistant.
+#[derive(Debug, Clone)]
+pub struct AlterPlan {
+    pub schema: String,
+    pub table: String,
+    pub columns: Vec<ColumnAlteration>,
+}
+
+impl AlterPlan {
+    pub fn new(schema: String, table: String, columns: &[ColumnInfo]) -> Self {
+        Self { schema, table, columns: columns.iter().map(ColumnAlteration::new).collect() }
+    }
+
+    pub fn changed_columns(&self) -> usize {
+        self.columns.iter().filter(|column| column.is_changed()).count()
+    }
+
+    /// One ALTER TABLE with every type and nullability change, on the current column
+    /// names, then a RENAME COLUMN per renamed column, which PostgreSQL does not allow
+    /// to be combined with other actions. Meant to run in a single transaction.
+    pub fn statements(&self) -> Result<Vec<String>> {
+        let table = qualified_name(&self.schema, &self.table);
+        let current: HashSet<&str> = self.columns.iter().map(|column| column.name.as_str()).collect();
+        let mut new_names = HashSet::new();
+        let mut actions = Vec::new();
+        let mut renames = Vec::new();
+
+        for column in &self.columns {
+            let new_name = column.new_name.trim();
+            if new_name.is_empty() {
+                anyhow::bail!("New name of column {} is empty", column.name);
+            }
+            if !new_names.insert(new_name) {
+                anyhow::bail!("More than one column would be named {}", new_name);
+            }
+            if column.is_retyped() {
+                if column.new_type.trim().is_empty() {
+                    anyhow::bail!("New type of column {} is empty", column.name);
+                }
+                actions.push(format!(
+                    "ALTER COLUMN {} TYPE {} USING {}",
+                    quote_ident(&column.name),
+                    column.new_type.trim(),
+                    column.using_expression()
+                ));
+            }
+            match column.nullability {
+                NullabilityChange::Keep => {}
+                NullabilityChange::SetNotNull => actions.push(format!("ALTER COLUMN {} SET NOT NULL", quote_ident(&column.name))),
+                NullabilityChange::DropNotNull => actions.push(format!("ALTER COLUMN {} DROP NOT NULL", quote_ident(&column.name))),
+            }
+            if column.is_renamed() {
+                // Renames run one after another, so a name can't move to another column in the same go
+                if current.contains(new_name) {
+                    anyhow::bail!("{} is still the name of another column, rename that one first", new_name);
+                }
+                renames.push(format!("ALTER TABLE {} RENAME COLUMN {} TO {}", table, quote_ident(&column.name), quote_ident(new_name)));
+            }
+        }
+
+        let mut statements = Vec::new();
+        if !actions.is_empty() {
+            statements.push(format!("ALTER TABLE {}\n    {}", table, actions.join(",\n    ")));
+        }
+        statements.extend(renames);
+        Ok(statements)
+    }
+}
+
+// Broad type families for picking a conversion
+#[derive(PartialEq)]
+enum TypeFamily {
+    Integer,
+    Decimal,
+    Boolean,
+    Text,
+    Other,
+}
+
+fn type_family(data_type: &str) -> TypeFamily {
+    let data_type = data_type.to_lowercase();
+    let starts = |prefixes: &[&str]| prefixes.iter().any(|prefix| data_type.starts_with(prefix));
+    if starts(&["int", "smallint", "bigint", "serial", "bigserial", "smallserial"]) {
+        TypeFamily::Integer
+    } else if starts(&["numeric", "decimal", "real", "double", "float"]) {
+        TypeFamily::Decimal
+    } else if starts(&["bool"]) {
+        TypeFamily::Boolean
+    } else if starts(&["text", "varchar", "character", "char", "bpchar", "citext"]) {
+        TypeFamily::Text
+    } else {
+        TypeFamily::Other
+    }
+}
+
+/// USING expression converting `column` from `from` to `to`: a plain cast, except for
+/// conversions a cast gets wrong or refuses (blank text to numbers, booleans to and
+/// from integers).
+pub fn suggest_using(column: &str, from: &str, to: &str) -> String {
+    let column = quote_ident(column);
+    match (type_family(from), type_family(to)) {
+        (TypeFamily::Text, TypeFamily::Integer | TypeFamily::Decimal) => format!("NULLIF(trim({}), '')::{}", column, to),
+        (TypeFamily::Integer, TypeFamily::Boolean) => format!("{} <> 0", column),
+        (TypeFamily::Boolean, TypeFamily::Integer | TypeFamily::Decimal) => format!("CASE WHEN {} THEN 1 ELSE 0 END", column),
+        _ => format!("{}::{}", column, to),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan() -> AlterPlan {
+        let column = |name: &str, data_type: &str| ColumnInfo {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            is_primary_key: false,
+            is_foreign_key: false,
+        };
+        AlterPlan::new("public".to_string(), "users".to_string(), &[column("id", "int4"), column("age", "text"), column("nick", "text")])
+    }
+
+    #[test]
+    fn test_statements() {
+        let mut plan = plan();
+        assert_eq!(plan.statements().unwrap(), Vec::<String>::new());
+
+        plan.columns[0].new_type = "bigint".to_string();
+        plan.columns[1].new_type = "integer".to_string();
+        plan.columns[1].new_name = "age_years ".to_string();
+        plan.columns[2].nullability = NullabilityChange::SetNotNull;
+        plan.columns[2].using = "ignored, type unchanged".to_string();
+        assert_eq!(plan.changed_columns(), 3);
+        assert_eq!(plan.statements().unwrap(), vec![
+            "ALTER TABLE \"public\".\"users\"\n    \
+             ALTER COLUMN \"id\" TYPE bigint USING \"id\"::bigint,\n    \
+             ALTER COLUMN \"age\" TYPE integer USING NULLIF(trim(\"age\"), '')::integer,\n    \
+             ALTER COLUMN \"nick\" SET NOT NULL".to_string(),
+            "ALTER TABLE \"public\".\"users\" RENAME COLUMN \"age\" TO \"age_years\"".to_string(),
+        ]);
+
+        // Own USING expression wins over the suggestion
+        plan.columns[0].using = "id * 1000".to_string();
+        assert!(plan.statements().unwrap()[0].contains("TYPE bigint USING id * 1000"));
+    }
+
+    #[test]
+    fn test_invalid_renames() {
+        let mut plan = plan();
+        plan.columns[1].new_name = "nick".to_string();
+        plan.columns[2].new_name = "age".to_string();
+        assert!(plan.statements().is_err());
+
+        plan.columns[2].new_name = "nick".to_string();
+        assert!(plan.statements().is_err());
+
+        plan.columns[1].new_name = "  ".to_string();
+        assert!(plan.statements().is_err());
+    }
+
+    #[test]
+    fn test_suggest_using() {
+        assert_eq!(suggest_using("n", "character varying(20)", "numeric(10,2)"), "NULLIF(trim(\"n\"), '')::numeric(10,2)");
+        assert_eq!(suggest_using("flag", "int4", "boolean"), "\"flag\" <> 0");
+        assert_eq!(suggest_using("flag", "bool", "smallint"), "CASE WHEN \"flag\" THEN 1 ELSE 0 END");
+        assert_eq!(suggest_using("at", "text", "timestamptz"), "\"at\"::timestamptz");
+    }
+}
@@ -0,0 +1,62 @@
+use base64::Engine;
+
+// Bytes per line of a hex dump
+const DUMP_WIDTH: usize = 16;
+
+/// Lowercase hex digits, two per byte.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Standard base64 with padding.
+pub fn to_base64(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+/// Classic hex dump of at most `limit` bytes: offset, 16 bytes in hex split in two
+/// groups of eight, and the printable ASCII characters in a gutter.
+pub fn hex_dump(bytes: &[u8], limit: usize) -> String {
+    bytes[..bytes.len().min(limit)]
+        .chunks(DUMP_WIDTH)
+        .enumerate()
+        .map(|(line, chunk)| {
+            let mut hex = String::new();
+            for index in 0..DUMP_WIDTH {
+                if index == DUMP_WIDTH / 2 {
+                    hex.push(' ');
+                }
+                match chunk.get(index) {
+                    Some(byte) => hex.push_str(&format!("{:02x} ", byte)),
+                    None => hex.push_str("   "),
+                }
+            }
+            let ascii: String = chunk.iter().map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' }).collect();
+            format!("{:08x}  {} |{}|", line * DUMP_WIDTH, hex, ascii)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encodings() {
+        assert_eq!(to_hex(&[0x00, 0xab, 0xff]), "00abff");
+        assert_eq!(to_base64(b"hello"), "aGVsbG8=");
+        assert_eq!(to_base64(&[]), "");
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        let bytes: Vec<u8> = b"Hello, binary\x00\x01\xffworld".to_vec();
+        assert_eq!(
+            hex_dump(&bytes, 1024),
+            "00000000  48 65 6c 6c 6f 2c 20 62  69 6e 61 72 79 00 01 ff  |Hello, binary...|\n\
+             00000010  77 6f 72 6c 64                                    |world|"
+        );
+        assert_eq!(hex_dump(&bytes, 4), "00000000  48 65 6c 6c                                       |Hell|");
+        assert_eq!(hex_dump(&[], 16), "");
+    }
+}
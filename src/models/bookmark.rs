@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use crate::db::ColumnInfo;
+use crate::models::{CellValue, PendingEdits};
+
+/// Rows bookmarked in a table tab, identified by their primary key values so they
+/// survive reloads and re-sorting of the grid.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Bookmarks {
+    keys: Vec<Vec<String>>,
+}
+
+impl Bookmarks {
+    pub fn keys(&self) -> &[Vec<String>] {
+        &self.keys
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn contains(&self, key: &[String]) -> bool {
+        self.keys.iter().any(|k| k == key)
+    }
+
+    /// Bookmark the row with `key`, or remove its bookmark. Returns whether it is now bookmarked.
+    pub fn toggle(&mut self, key: Vec<String>) -> bool {
+        if let Some(pos) = self.keys.iter().position(|k| *k == key) {
+            self.keys.remove(pos);
+            false
+        } else {
+            self.keys.push(key);
+            true
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.keys.len() {
+            self.keys.remove(index);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    /// Index in `rows` of the row with `key`, if it is still part of the result.
+    pub fn find_row(rows: &[Vec<CellValue>], key_columns: &[usize], key: &[String]) -> Option<usize> {
+        rows.iter().position(|row| PendingEdits::row_key(row, key_columns) == key)
+    }
+
+    /// `col = value` pairs naming a bookmarked row in the side panel.
+    pub fn label(key: &[String], columns: &[ColumnInfo], key_columns: &[usize]) -> String {
+        key_columns.iter()
+            .zip(key)
+            .map(|(&idx, value)| {
+                let name = columns.get(idx).map(|c| c.name.as_str()).unwrap_or("?");
+                format!("{} = {}", name, value)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_toggle() {
+        let mut bookmarks = Bookmarks::default();
+        assert!(bookmarks.toggle(key(&["7"])));
+        assert!(bookmarks.toggle(key(&["3"])));
+        assert!(bookmarks.contains(&key(&["7"])));
+
+        assert!(!bookmarks.toggle(key(&["7"])));
+        assert_eq!(bookmarks.keys(), &[key(&["3"])]);
+    }
+
+    #[test]
+    fn test_find_row_after_sorting() {
+        let row = |id: i64, group: &str| vec![CellValue::Int(id), CellValue::from(group), CellValue::Null];
+        let mut rows = vec![row(1, "a"), row(2, "a"), row(3, "b")];
+        // Composite key on the first two columns
+        let target = key(&["2", "a"]);
+        assert_eq!(Bookmarks::find_row(&rows, &[0, 1], &target), Some(1));
+
+        rows.reverse();
+        assert_eq!(Bookmarks::find_row(&rows, &[0, 1], &target), Some(1));
+        rows.remove(1);
+        assert_eq!(Bookmarks::find_row(&rows, &[0, 1], &target), None);
+    }
+
+    #[test]
+    fn test_label() {
+        let column = |name: &str| ColumnInfo { name: name.to_string(), data_type: "int4".to_string(), is_primary_key: true, is_foreign_key: false };
+        let columns = vec![column("tenant"), column("note"), column("id")];
+        assert_eq!(Bookmarks::label(&key(&["4", "17"]), &columns, &[0, 2]), "tenant = 4, id = 17");
+    }
+}
@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use crate::models::TableData;
+
+/// Bars drawn from a result's first two columns: labels from the first, heights from
+/// the second, e.g. the buckets and counts of a time-series query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarSeries {
+    pub labels: Vec<String>,
+    pub values: Vec<f64>,
+}
+
+impl BarSeries {
+    /// None unless `data` has two columns or more and the second one holds numbers.
+    /// Rows without a number count as zero.
+    pub fn from_data(data: &TableData) -> Option<Self> {
+        if data.columns.len() < 2 || data.rows.is_empty() {
+            return None;
+        }
+        let values: Vec<Option<f64>> = data.rows.iter().map(|row| row.get(1).and_then(|value| value.as_f64())).collect();
+        if values.iter().all(Option::is_none) {
+            return None;
+        }
+        Some(Self {
+            labels: data.rows.iter().map(|row| row.first().map(|value| value.to_string()).unwrap_or_default()).collect(),
+            values: values.into_iter().map(|value| value.unwrap_or(0.0)).collect(),
+        })
+    }
+
+    /// Largest value, the height of the tallest bar.
+    pub fn max(&self) -> f64 {
+        self.values.iter().copied().fold(0.0, f64::max)
+    }
+}
+
+/// How the Chart view of a query tab draws its points.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ChartKind {
+    #[default]
+    Line,
+    Bar,
+    Scatter,
+}
+
+impl ChartKind {
+    pub fn all() -> Vec<ChartKind> {
+        vec![ChartKind::Line, ChartKind::Bar, ChartKind::Scatter]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ChartKind::Line => "Line",
+            ChartKind::Bar => "Bar",
+            ChartKind::Scatter => "Scatter",
+        }
+    }
+}
+
+/// The columns a tab's Chart view plots against each other, and how.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChartSettings {
+    pub kind: ChartKind,
+    pub x_column: usize,
+    pub y_column: usize,
+}
+
+impl Default for ChartSettings {
+    fn default() -> Self {
+        Self { kind: ChartKind::Line, x_column: 0, y_column: 1 }
+    }
+}
+
+/// One column of a result plotted against another. Numeric x values are placed by
+/// value, in order; any other x column places the rows one after another and keeps
+/// its values as the axis labels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotSeries {
+    pub points: Vec<[f64; 2]>,
+    pub labels: Option<Vec<String>>,
+    // Rows left out because their y value is not a number
+    pub skipped: usize,
+}
+
+impl PlotSeries {
+    pub fn new(data: &TableData, rows: &[usize], x_column: usize, y_column: usize) -> Self {
+        let rows: Vec<_> = rows.iter().filter_map(|&row| data.rows.get(row)).collect();
+        let x_values: Vec<Option<f64>> = rows.iter().map(|row| row.get(x_column).and_then(|value| value.as_f64())).collect();
+        let numeric = x_values.iter().all(Option::is_some);
+
+        let mut points = Vec::new();
+        for (position, (row, x)) in rows.iter().zip(&x_values).enumerate() {
+            if let Some(y) = row.get(y_column).and_then(|value| value.as_f64()) {
+                points.push([if numeric { x.unwrap_or_default() } else { position as f64 }, y]);
+            }
+        }
+        if numeric {
+            points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        }
+        let labels = (!numeric).then(|| rows.iter().map(|row| row.get(x_column).map(|value| value.to_string()).unwrap_or_default()).collect());
+
+        Self { skipped: rows.len() - points.len(), points, labels }
+    }
+
+    /// Width that keeps neighbouring bars apart: most of the closest gap between points.
+    pub fn bar_width(&self) -> f64 {
+        let closest = self.points.windows(2)
+            .map(|pair| pair[1][0] - pair[0][0])
+            .filter(|gap| *gap > 0.0)
+            .fold(f64::INFINITY, f64::min);
+        if closest.is_finite() { closest * 0.8 } else { 0.8 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{ColumnInfo, ColumnLineage};
+    use crate::models::CellValue;
+
+    fn data(rows: Vec<Vec<CellValue>>) -> TableData {
+        let column = |name: &str| ColumnInfo { name: name.to_string(), data_type: "text".to_string(), is_primary_key: false, is_foreign_key: false };
+        TableData { name: "buckets".to_string(), columns: vec![column("bucket"), column("count")], rows, lineage: ColumnLineage::new(), generation: 0 }
+    }
+
+    #[test]
+    fn test_from_data() {
+        let series = BarSeries::from_data(&data(vec![
+            vec![CellValue::from("2024-01-01"), CellValue::Int(4)],
+            vec![CellValue::from("2024-01-02"), CellValue::Null],
+            vec![CellValue::from("2024-01-03"), CellValue::from("7.5")],
+        ])).unwrap();
+        assert_eq!(series.labels, vec!["2024-01-01", "2024-01-02", "2024-01-03"]);
+        assert_eq!(series.values, vec![4.0, 0.0, 7.5]);
+        assert_eq!(series.max(), 7.5);
+
+        assert!(BarSeries::from_data(&data(vec![vec![CellValue::from("a"), CellValue::from("b")]])).is_none());
+        assert!(BarSeries::from_data(&data(Vec::new())).is_none());
+    }
+
+    #[test]
+    fn test_plot_series() {
+        let table = data(vec![
+            vec![CellValue::Int(3), CellValue::Float(1.5)],
+            vec![CellValue::Int(1), CellValue::from("2")],
+            vec![CellValue::Int(2), CellValue::Null],
+        ]);
+        let series = PlotSeries::new(&table, &[0, 1, 2], 0, 1);
+        assert_eq!(series.points, vec![[1.0, 2.0], [3.0, 1.5]]);
+        assert_eq!((&series.labels, series.skipped), (&None, 1));
+        assert_eq!(series.bar_width(), 1.6);
+
+        // Text on the x axis places the rows in order, under their values
+        let series = PlotSeries::new(&data(vec![
+            vec![CellValue::from("b"), CellValue::Int(4)],
+            vec![CellValue::from("a"), CellValue::Int(2)],
+        ]), &[1, 0], 0, 1);
+        assert_eq!(series.points, vec![[0.0, 2.0], [1.0, 4.0]]);
+        assert_eq!(series.labels, Some(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(series.bar_width(), 0.8);
+    }
+}
@@ -0,0 +1,419 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::db::ColumnInfo;
+use crate::models::{CellValue, TableData};
+
+/// Data type shown for computed columns, which also tells them apart from the result's own
+pub const COMPUTED_TYPE: &str = "computed";
+
+/// A display column a tab computes from its other columns, e.g. `amount_cents / 100` or
+/// `first_name || ' ' || last_name`. Expressions take column names (double-quoted when
+/// they need it), numbers, 'text', + - * / %, || and parentheses; NULL in gives NULL out,
+/// like in SQL.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ComputedColumn {
+    pub name: String,
+    pub expression: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Concat,
+}
+
+#[derive(Debug, PartialEq)]
+enum Expr {
+    Column(usize),
+    Value(CellValue),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, Op, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    Text(String),
+    Name(String),
+    Op(Op),
+    Open,
+    Close,
+}
+
+/// Whether `column` was added by the tab rather than returned by the database.
+pub fn is_computed(column: &ColumnInfo) -> bool {
+    column.data_type == COMPUTED_TYPE
+}
+
+impl ComputedColumn {
+    /// Check the name and expression against the columns they would be added after.
+    pub fn validate(&self, columns: &[ColumnInfo]) -> Result<()> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            anyhow::bail!("Enter a name");
+        }
+        if columns.iter().any(|column| column.name.eq_ignore_ascii_case(name)) {
+            anyhow::bail!("There is already a column named {}", name);
+        }
+        self.parse(columns).map(|_| ())
+    }
+
+    /// The column's value for each of `rows`, which have `columns`.
+    pub fn values(&self, columns: &[ColumnInfo], rows: &[Vec<CellValue>]) -> Result<Vec<CellValue>> {
+        let expr = self.parse(columns)?;
+        Ok(rows.iter().map(|row| evaluate(&expr, row)).collect())
+    }
+
+    fn parse(&self, columns: &[ColumnInfo]) -> Result<Expr> {
+        let tokens = tokenize(&self.expression)?;
+        if tokens.is_empty() {
+            anyhow::bail!("Enter an expression");
+        }
+        let mut parser = Parser { tokens: &tokens, position: 0, columns };
+        let expr = parser.concat()?;
+        match tokens.get(parser.position) {
+            None => Ok(expr),
+            Some(token) => anyhow::bail!("Unexpected {}", token_text(token)),
+        }
+    }
+}
+
+/// Bring `data`'s computed columns in line with `computed`: they are added after the
+/// result's own columns, and rows arriving later (streaming, tailing) get their values.
+/// An expression that no longer fits the columns, e.g. after a reload without one it
+/// uses, leaves its column NULL.
+pub fn apply_computed_columns(data: &mut TableData, computed: &[ComputedColumn]) {
+    let current: Vec<&str> = data.columns.iter().filter(|c| is_computed(c)).map(|c| c.name.as_str()).collect();
+    if current.is_empty() && computed.is_empty() {
+        return;
+    }
+    if !current.iter().copied().eq(computed.iter().map(|column| column.name.as_str())) {
+        data.columns.retain(|column| !is_computed(column));
+        let base = data.columns.len();
+        for row in &mut data.rows {
+            row.truncate(base);
+        }
+        data.columns.extend(computed.iter().map(|column| ColumnInfo {
+            name: column.name.clone(),
+            data_type: COMPUTED_TYPE.to_string(),
+            is_primary_key: false,
+            is_foreign_key: false,
+        }));
+    }
+
+    let width = data.columns.len();
+    let base = width - computed.len();
+    let Some(first) = data.rows.iter().position(|row| row.len() < width) else {
+        return;
+    };
+    data.touch();
+    // Each expression sees the result's columns and the computed ones before it
+    for (offset, column) in computed.iter().enumerate() {
+        let columns = &data.columns[..base + offset];
+        let expr = column.parse(columns).ok();
+        for row in &mut data.rows[first..] {
+            if row.len() == base + offset {
+                let value = expr.as_ref().map_or(CellValue::Null, |expr| evaluate(expr, row));
+                row.push(value);
+            }
+        }
+    }
+}
+
+fn token_text(token: &Token) -> String {
+    match token {
+        Token::Number(number) => number.clone(),
+        Token::Text(text) => format!("'{}'", text),
+        Token::Name(name) => name.clone(),
+        Token::Op(op) => match op {
+            Op::Add => "+",
+            Op::Sub => "-",
+            Op::Mul => "*",
+            Op::Div => "/",
+            Op::Rem => "%",
+            Op::Concat => "||",
+        }
+        .to_string(),
+        Token::Open => "(".to_string(),
+        Token::Close => ")".to_string(),
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '\'' | '"' => {
+                // A doubled quote stands for itself
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match (chars.get(i), chars.get(i + 1)) {
+                        (Some(&q), Some(&next)) if q == c && next == c => {
+                            text.push(c);
+                            i += 2;
+                        }
+                        (Some(&q), _) if q == c => break,
+                        (Some(&other), _) => {
+                            text.push(other);
+                            i += 1;
+                        }
+                        (None, _) => anyhow::bail!("Missing closing {}", c),
+                    }
+                }
+                i += 1;
+                tokens.push(if c == '\'' { Token::Text(text) } else { Token::Name(text) });
+            }
+            _ if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|next| next.is_ascii_digit())) => {
+                let start = i;
+                while chars.get(i).is_some_and(|c| c.is_ascii_digit() || *c == '.') {
+                    i += 1;
+                }
+                tokens.push(Token::Number(chars[start..i].iter().collect()));
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while chars.get(i).is_some_and(|c| c.is_alphanumeric() || *c == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Name(chars[start..i].iter().collect()));
+            }
+            '|' if chars.get(i + 1) == Some(&'|') => {
+                tokens.push(Token::Op(Op::Concat));
+                i += 2;
+            }
+            '(' | ')' | '+' | '-' | '*' | '/' | '%' => {
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    '+' => Token::Op(Op::Add),
+                    '-' => Token::Op(Op::Sub),
+                    '*' => Token::Op(Op::Mul),
+                    '/' => Token::Op(Op::Div),
+                    _ => Token::Op(Op::Rem),
+                });
+                i += 1;
+            }
+            _ => anyhow::bail!("Unexpected {}", c),
+        }
+    }
+    Ok(tokens)
+}
+
+// Precedence as in PostgreSQL: * / % over + - over ||
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    columns: &'a [ColumnInfo],
+}
+
+impl Parser<'_> {
+    fn next_op(&mut self, ops: &[Op]) -> Option<Op> {
+        match self.tokens.get(self.position) {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                self.position += 1;
+                Some(*op)
+            }
+            _ => None,
+        }
+    }
+
+    fn binary(&mut self, ops: &[Op], operand: fn(&mut Self) -> Result<Expr>) -> Result<Expr> {
+        let mut expr = operand(self)?;
+        while let Some(op) = self.next_op(ops) {
+            expr = Expr::Binary(Box::new(expr), op, Box::new(operand(self)?));
+        }
+        Ok(expr)
+    }
+
+    fn concat(&mut self) -> Result<Expr> {
+        self.binary(&[Op::Concat], Self::sum)
+    }
+
+    fn sum(&mut self) -> Result<Expr> {
+        self.binary(&[Op::Add, Op::Sub], Self::product)
+    }
+
+    fn product(&mut self) -> Result<Expr> {
+        self.binary(&[Op::Mul, Op::Div, Op::Rem], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.next_op(&[Op::Sub]).is_some() {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.next_op(&[Op::Add]);
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let Some(token) = self.tokens.get(self.position) else {
+            anyhow::bail!("The expression ends too early");
+        };
+        self.position += 1;
+        match token {
+            Token::Number(number) => match number.parse::<i64>() {
+                Ok(int) => Ok(Expr::Value(CellValue::Int(int))),
+                Err(_) => number.parse::<f64>()
+                    .map(|float| Expr::Value(CellValue::Float(float)))
+                    .map_err(|_| anyhow::anyhow!("Invalid number {}", number)),
+            },
+            Token::Text(text) => Ok(Expr::Value(CellValue::Text(text.clone()))),
+            Token::Name(name) => {
+                // Exact spelling first, then ignoring case
+                let index = self.columns.iter().position(|column| column.name == *name)
+                    .or_else(|| self.columns.iter().position(|column| column.name.eq_ignore_ascii_case(name)))
+                    .ok_or_else(|| anyhow::anyhow!("Unknown column {}", name))?;
+                Ok(Expr::Column(index))
+            }
+            Token::Open => {
+                let expr = self.concat()?;
+                match self.tokens.get(self.position) {
+                    Some(Token::Close) => {
+                        self.position += 1;
+                        Ok(expr)
+                    }
+                    _ => anyhow::bail!("Missing closing )"),
+                }
+            }
+            token => anyhow::bail!("Unexpected {}", token_text(token)),
+        }
+    }
+}
+
+// A value as a number; decimals arrive as text
+enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+fn number(value: &CellValue) -> Option<Number> {
+    match value {
+        CellValue::Int(int) => Some(Number::Int(*int)),
+        CellValue::Float(float) => Some(Number::Float(*float)),
+        CellValue::Text(text) => {
+            let text = text.trim();
+            text.parse::<i64>().map(Number::Int).ok().or_else(|| text.parse::<f64>().ok().map(Number::Float))
+        }
+        _ => None,
+    }
+}
+
+fn float(number: Number) -> f64 {
+    match number {
+        Number::Int(int) => int as f64,
+        Number::Float(float) => float,
+    }
+}
+
+// NULL, like a value that is not a number where one is needed, or a division by zero
+fn evaluate(expr: &Expr, row: &[CellValue]) -> CellValue {
+    match expr {
+        Expr::Column(index) => row.get(*index).cloned().unwrap_or(CellValue::Null),
+        Expr::Value(value) => value.clone(),
+        Expr::Neg(operand) => match number(&evaluate(operand, row)) {
+            Some(Number::Int(int)) => int.checked_neg().map_or(CellValue::Null, CellValue::Int),
+            Some(Number::Float(float)) => CellValue::Float(-float),
+            None => CellValue::Null,
+        },
+        Expr::Binary(left, op, right) => {
+            let (left, right) = (evaluate(left, row), evaluate(right, row));
+            if left.is_null() || right.is_null() {
+                return CellValue::Null;
+            }
+            if *op == Op::Concat {
+                return CellValue::Text(format!("{}{}", left, right));
+            }
+            let (Some(left), Some(right)) = (number(&left), number(&right)) else {
+                return CellValue::Null;
+            };
+            match (left, right, op) {
+                // Division always keeps the fraction, so cents / 100 gives amounts
+                (left, right, Op::Div) => {
+                    let divisor = float(right);
+                    if divisor == 0.0 { CellValue::Null } else { CellValue::Float(float(left) / divisor) }
+                }
+                (Number::Int(left), Number::Int(right), op) => {
+                    let result = match op {
+                        Op::Add => left.checked_add(right),
+                        Op::Sub => left.checked_sub(right),
+                        Op::Mul => left.checked_mul(right),
+                        _ => left.checked_rem(right),
+                    };
+                    result.map_or(CellValue::Null, CellValue::Int)
+                }
+                (left, right, op) => {
+                    let (left, right) = (float(left), float(right));
+                    match op {
+                        Op::Add => CellValue::Float(left + right),
+                        Op::Sub => CellValue::Float(left - right),
+                        Op::Mul => CellValue::Float(left * right),
+                        _ if right == 0.0 => CellValue::Null,
+                        _ => CellValue::Float(left % right),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ColumnLineage;
+
+    fn computed(name: &str, expression: &str) -> ComputedColumn {
+        ComputedColumn { name: name.to_string(), expression: expression.to_string() }
+    }
+
+    #[test]
+    fn test_computed_values() {
+        let columns = [ColumnInfo::test("amount_cents", "text"), ColumnInfo::test("first", "text"), ColumnInfo::test("Last Name", "text")];
+        let rows = vec![
+            vec![CellValue::Int(1250), CellValue::from("Ada"), CellValue::from("Lovelace")],
+            vec![CellValue::from("99.5"), CellValue::Null, CellValue::from("Hopper")],
+        ];
+        let values = |expression: &str| computed("x", expression).values(&columns, &rows).unwrap();
+
+        assert_eq!(values("amount_cents / 100"), [CellValue::Float(12.5), CellValue::Float(0.995)]);
+        assert_eq!(values("-(AMOUNT_CENTS + 1) * 2 % 7"), [CellValue::Int(-2502 % 7), CellValue::Float(-201.0 % 7.0)]);
+        assert_eq!(values("first || ' ' || \"Last Name\""), [CellValue::from("Ada Lovelace"), CellValue::Null]);
+        assert_eq!(values("first / 0"), [CellValue::Null, CellValue::Null]);
+
+        assert!(computed("x", "missing + 1").validate(&columns).is_err());
+        assert!(computed("x", "(amount_cents").validate(&columns).is_err());
+        assert!(computed("first", "1").validate(&columns).is_err());
+    }
+
+    #[test]
+    fn test_apply_computed_columns() {
+        let mut data = TableData {
+            name: "t".to_string(),
+            columns: vec![ColumnInfo::test("n", "text")],
+            rows: vec![vec![CellValue::Int(1)]],
+            lineage: ColumnLineage::new(),
+            generation: 0,
+        };
+        let mut list = vec![computed("double", "n * 2"), computed("quadruple", "double * 2")];
+        apply_computed_columns(&mut data, &list);
+        assert_eq!(data.rows, [vec![CellValue::Int(1), CellValue::Int(2), CellValue::Int(4)]]);
+
+        // Rows arriving later are filled in, a changed list replaces the columns
+        data.rows.push(vec![CellValue::Int(5)]);
+        apply_computed_columns(&mut data, &list);
+        assert_eq!(data.rows[1], [CellValue::Int(5), CellValue::Int(10), CellValue::Int(20)]);
+        list.remove(0);
+        apply_computed_columns(&mut data, &list);
+        assert_eq!(data.columns.len(), 2);
+        assert_eq!(data.rows[1], [CellValue::Int(5), CellValue::Null]);
+    }
+}
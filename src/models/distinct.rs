@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use crate::db::ColumnInfo;
+use crate::models::{CellValue, FilterRule, TableData};
+
+// Name of the occurrence count column added to a distinct view
+const COUNT_COLUMN: &str = "count";
+
+/// A tab showing its result collapsed to distinct rows, with what it showed before so
+/// switching back restores the full rows, sort, filters and page.
+#[derive(Clone)]
+pub struct DistinctView {
+    // Names of the columns the rows were collapsed on
+    pub columns: Vec<String>,
+    pub original: TableData,
+    pub sort_column: Option<usize>,
+    pub sort_ascending: bool,
+    pub filters: Vec<FilterRule>,
+    pub current_page: usize,
+}
+
+impl DistinctView {
+    pub fn describe(&self) -> String {
+        if self.columns.len() == self.original.columns.len() {
+            "identical rows".to_string()
+        } else {
+            self.columns.join(", ")
+        }
+    }
+}
+
+/// Rows of `data` collapsed on `columns` (all columns when empty), each distinct
+/// combination once with the number of rows it stands for in a trailing count column.
+/// Most frequent first; equal counts keep the order they first appeared in. Key flags
+/// are dropped, a distinct row not being a row of the table that can be edited.
+pub fn distinct_rows(data: &TableData, columns: &[usize]) -> TableData {
+    let columns: Vec<usize> = if columns.is_empty() {
+        (0..data.columns.len()).collect()
+    } else {
+        columns.iter().copied().filter(|&column| column < data.columns.len()).collect()
+    };
+
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut groups: Vec<(Vec<CellValue>, i64)> = Vec::new();
+    for row in &data.rows {
+        let values: Vec<_> = columns.iter().map(|&column| row.get(column).cloned().unwrap_or(CellValue::Null)).collect();
+        // Debug output tells NULL from the text "NULL" and 1 from "1", unlike display text
+        let key = format!("{:?}", values);
+        match positions.get(&key) {
+            Some(&position) => groups[position].1 += 1,
+            None => {
+                positions.insert(key, groups.len());
+                groups.push((values, 1));
+            }
+        }
+    }
+    groups.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    let mut result_columns: Vec<ColumnInfo> = columns.iter()
+        .map(|&column| ColumnInfo {
+            is_primary_key: false,
+            is_foreign_key: false,
+            ..data.columns[column].clone()
+        })
+        .collect();
+    result_columns.push(ColumnInfo {
+        name: COUNT_COLUMN.to_string(),
+        data_type: "bigint".to_string(),
+        is_primary_key: false,
+        is_foreign_key: false,
+    });
+
+    TableData {
+        name: format!("{} (distinct)", data.name),
+        columns: result_columns,
+        rows: groups.into_iter()
+            .map(|(mut values, count)| {
+                values.push(CellValue::Int(count));
+                values
+            })
+            .collect(),
+        lineage: data.lineage.clone(),
+        generation: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ColumnLineage;
+
+    fn data() -> TableData {
+        let column = |name: &str, is_primary_key| ColumnInfo {
+            name: name.to_string(),
+            data_type: "text".to_string(),
+            is_primary_key,
+            is_foreign_key: false,
+        };
+        let row = |id: i64, city: &str, country: CellValue| vec![CellValue::Int(id), CellValue::from(city), country];
+        TableData {
+            name: "public.users".to_string(),
+            columns: vec![column("id", true), column("city", false), column("country", false)],
+            rows: vec![
+                row(1, "Oslo", CellValue::from("NO")),
+                row(2, "Lund", CellValue::from("SE")),
+                row(3, "Lund", CellValue::from("SE")),
+                row(4, "Oslo", CellValue::from("NO")),
+                row(5, "Lund", CellValue::from("SE")),
+                row(6, "Nowhere", CellValue::Null),
+                row(7, "Nowhere", CellValue::from("NULL")),
+            ],
+            lineage: ColumnLineage::new(),
+            generation: 0,
+        }
+    }
+
+    #[test]
+    fn test_distinct_columns() {
+        let distinct = distinct_rows(&data(), &[1, 2]);
+        let names: Vec<_> = distinct.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["city", "country", "count"]);
+        assert_eq!(distinct.rows, vec![
+            vec![CellValue::from("Lund"), CellValue::from("SE"), CellValue::Int(3)],
+            vec![CellValue::from("Oslo"), CellValue::from("NO"), CellValue::Int(2)],
+            vec![CellValue::from("Nowhere"), CellValue::Null, CellValue::Int(1)],
+            vec![CellValue::from("Nowhere"), CellValue::from("NULL"), CellValue::Int(1)],
+        ]);
+    }
+
+    #[test]
+    fn test_distinct_rows() {
+        let mut data = data();
+        data.rows.push(data.rows[0].clone());
+        let distinct = distinct_rows(&data, &[]);
+        assert_eq!(distinct.columns.len(), 4);
+        assert!(distinct.columns.iter().all(|c| !c.is_primary_key));
+        assert_eq!(distinct.rows.len(), 7);
+        assert_eq!(distinct.rows[0], vec![CellValue::Int(1), CellValue::from("Oslo"), CellValue::from("NO"), CellValue::Int(2)]);
+    }
+}
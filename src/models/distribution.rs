@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use crate::models::TableData;
+
+// Most frequent values drawn as bars of their own; the rest are summed into one
+const MAX_VALUE_BARS: usize = 30;
+// Upper bound on histogram bins, however many values there are
+const MAX_BINS: usize = 40;
+
+/// Counts drawn by the Value Distribution panel.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Distribution {
+    // Values by how often they occur, most frequent first, and how many cells hold any other value
+    Frequencies { values: Vec<(String, usize)>, others: usize },
+    // Numbers counted in equal-width bins, the first one starting at `start`
+    Histogram { start: f64, width: f64, counts: Vec<usize> },
+}
+
+/// How the values of one column of a tab are spread.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueDistribution {
+    pub column: usize,
+    pub distribution: Distribution,
+    pub values: usize, // non-NULL cells counted
+    pub nulls: usize,
+    pub distinct: usize,
+    // Every value is a number, so a histogram can be drawn
+    pub numeric: bool,
+}
+
+impl ValueDistribution {
+    /// Distribution of `column` over `rows` of `data`. `histogram` bins the numbers;
+    /// None bins them only when there are too many distinct ones to draw a bar each.
+    pub fn new(data: &TableData, rows: &[usize], column: usize, histogram: Option<bool>) -> Self {
+        let cells: Vec<_> = rows.iter()
+            .filter_map(|&row| data.rows.get(row)?.get(column))
+            .collect();
+        let present: Vec<_> = cells.iter().filter(|cell| !cell.is_null()).collect();
+        let numbers: Vec<f64> = present.iter().filter_map(|cell| cell.as_f64()).filter(|n| n.is_finite()).collect();
+        let numeric = !present.is_empty() && numbers.len() == present.len();
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for cell in &present {
+            *counts.entry(cell.to_string()).or_default() += 1;
+        }
+        let distinct = counts.len();
+
+        let distribution = if numeric && histogram.unwrap_or(distinct > MAX_VALUE_BARS) {
+            histogram_of(&numbers)
+        } else {
+            let mut values: Vec<(String, usize)> = counts.into_iter().collect();
+            values.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            let others = values.iter().skip(MAX_VALUE_BARS).map(|(_, count)| count).sum();
+            values.truncate(MAX_VALUE_BARS);
+            Distribution::Frequencies { values, others }
+        };
+
+        Self { column, distribution, values: present.len(), nulls: cells.len() - present.len(), distinct, numeric }
+    }
+
+    pub fn is_histogram(&self) -> bool {
+        matches!(self.distribution, Distribution::Histogram { .. })
+    }
+}
+
+// About the square root of the count in bins, like a spreadsheet would pick
+fn histogram_of(numbers: &[f64]) -> Distribution {
+    let min = numbers.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if numbers.is_empty() || min == max {
+        return Distribution::Histogram { start: if numbers.is_empty() { 0.0 } else { min }, width: 1.0, counts: vec![numbers.len()] };
+    }
+
+    let bins = ((numbers.len() as f64).sqrt().ceil() as usize).clamp(1, MAX_BINS);
+    let width = (max - min) / bins as f64;
+    let mut counts = vec![0; bins];
+    for number in numbers {
+        // The maximum closes the last bin
+        let bin = (((number - min) / width) as usize).min(bins - 1);
+        counts[bin] += 1;
+    }
+    Distribution::Histogram { start: min, width, counts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{ColumnInfo, ColumnLineage};
+    use crate::models::CellValue;
+
+    fn table(values: Vec<CellValue>) -> TableData {
+        let column = ColumnInfo { name: "value".to_string(), data_type: "text".to_string(), is_primary_key: false, is_foreign_key: false };
+        TableData { name: "t".to_string(), columns: vec![column], rows: values.into_iter().map(|value| vec![value]).collect(), lineage: ColumnLineage::new(), generation: 0 }
+    }
+
+    #[test]
+    fn test_frequencies() {
+        let data = table(vec![CellValue::from("b"), CellValue::from("a"), CellValue::Null, CellValue::from("b"), CellValue::from("c")]);
+        let all: Vec<usize> = (0..data.rows.len()).collect();
+        let distribution = ValueDistribution::new(&data, &all, 0, None);
+        assert_eq!(distribution.distribution, Distribution::Frequencies {
+            values: vec![("b".to_string(), 2), ("a".to_string(), 1), ("c".to_string(), 1)],
+            others: 0,
+        });
+        assert_eq!((distribution.values, distribution.nulls, distribution.distinct), (4, 1, 3));
+        assert!(!distribution.numeric);
+
+        // Only the given rows, e.g. those passing the grid filters
+        assert_eq!(ValueDistribution::new(&data, &[1, 2], 0, None).values, 1);
+
+        let many = table((0..40).map(|n| CellValue::from(format!("v{:02}", n % 35).as_str())).collect());
+        let all: Vec<usize> = (0..40).collect();
+        let Distribution::Frequencies { values, others } = ValueDistribution::new(&many, &all, 0, Some(true)).distribution else {
+            panic!("text is never binned");
+        };
+        assert_eq!(values.len(), MAX_VALUE_BARS);
+        assert_eq!(values[0], ("v00".to_string(), 2));
+        assert_eq!(others, 40 - values.iter().map(|(_, count)| count).sum::<usize>());
+    }
+
+    #[test]
+    fn test_histogram() {
+        let data = table((1..=100).map(CellValue::Int).collect());
+        let all: Vec<usize> = (0..100).collect();
+        let distribution = ValueDistribution::new(&data, &all, 0, None);
+        assert!(distribution.numeric);
+        let Distribution::Histogram { start, width, counts } = distribution.distribution else {
+            panic!("100 distinct numbers are binned");
+        };
+        assert_eq!((start, width, counts.len()), (1.0, 9.9, 10));
+        assert_eq!(counts.iter().sum::<usize>(), 100);
+        assert!(counts.iter().all(|&count| count == 10));
+
+        // Few distinct numbers get a bar each unless binning is asked for
+        let data = table(vec![CellValue::Int(3), CellValue::from("3"), CellValue::Float(3.0)]);
+        assert!(!ValueDistribution::new(&data, &[0, 1, 2], 0, None).is_histogram());
+        assert_eq!(ValueDistribution::new(&data, &[0, 1, 2], 0, Some(true)).distribution, Distribution::Histogram { start: 3.0, width: 1.0, counts: vec![3] });
+    }
+}
@@ -0,0 +1,185 @@
+use anyhow::Result;
+use crate::db::sql::{qualified_name, quote_ident, quote_literal};
+use crate::db::ColumnInfo;
+use crate::models::CellValue;
+
+/// A single staged cell change, identified by the row's primary key values so it
+/// survives re-sorting of the grid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellEdit {
+    pub key: Vec<String>,
+    pub column_index: usize,
+    pub original: String,
+    pub value: String,
+}
+
+/// Cell edits staged in a table tab, waiting to be applied or discarded.
+#[derive(Debug, Clone, Default)]
+pub struct PendingEdits {
+    pub edits: Vec<CellEdit>,
+}
+
+impl PendingEdits {
+    /// Indices of the primary key columns, or None if the table can't be edited safely.
+    pub fn key_columns(columns: &[ColumnInfo]) -> Option<Vec<usize>> {
+        let keys: Vec<usize> = columns.iter()
+            .enumerate()
+            .filter(|(_, column)| column.is_primary_key)
+            .map(|(idx, _)| idx)
+            .collect();
+        if keys.is_empty() { None } else { Some(keys) }
+    }
+
+    /// Primary key values of `row`, used to identify it in staged edits.
+    pub fn row_key(row: &[CellValue], key_columns: &[usize]) -> Vec<String> {
+        key_columns.iter()
+            .map(|&idx| row.get(idx).map(|value| value.to_string()).unwrap_or_default())
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.edits.clear();
+    }
+
+    pub fn get(&self, key: &[String], column_index: usize) -> Option<&CellEdit> {
+        self.edits.iter().find(|edit| edit.key == key && edit.column_index == column_index)
+    }
+
+    /// Stage `value` for a cell. Editing a cell back to its original value unstages it.
+    pub fn stage(&mut self, key: Vec<String>, column_index: usize, original: String, value: String) {
+        if let Some(pos) = self.edits.iter().position(|edit| edit.key == key && edit.column_index == column_index) {
+            if self.edits[pos].original == value {
+                self.edits.remove(pos);
+            } else {
+                self.edits[pos].value = value;
+            }
+        } else if original != value {
+            self.edits.push(CellEdit { key, column_index, original, value });
+        }
+    }
+
+    /// Write the staged values into `rows` after they have been applied on the server.
+    pub fn apply_to_rows(&self, rows: &mut [Vec<CellValue>], key_columns: &[usize]) {
+        for row in rows.iter_mut() {
+            let key = Self::row_key(row, key_columns);
+            for edit in self.edits.iter().filter(|edit| edit.key == key) {
+                if let Some(cell) = row.get_mut(edit.column_index) {
+                    *cell = cell.with_text(&edit.value);
+                }
+            }
+        }
+    }
+
+    /// One UPDATE per edited row, keyed on the primary key columns.
+    pub fn to_update_statements(&self, schema: &str, table: &str, columns: &[ColumnInfo]) -> Result<Vec<String>> {
+        let key_columns = Self::key_columns(columns)
+            .ok_or_else(|| anyhow::anyhow!("{}.{} has no primary key, edits can't be applied", schema, table))?;
+
+        // Group edits by row, keeping the order in which rows were first edited
+        let mut rows: Vec<(&Vec<String>, Vec<&CellEdit>)> = Vec::new();
+        for edit in &self.edits {
+            match rows.iter_mut().find(|(key, _)| *key == &edit.key) {
+                Some((_, edits)) => edits.push(edit),
+                None => rows.push((&edit.key, vec![edit])),
+            }
+        }
+
+        rows.iter()
+            .map(|(key, edits)| {
+                let assignments = edits.iter()
+                    .map(|edit| {
+                        let column = columns.get(edit.column_index)
+                            .ok_or_else(|| anyhow::anyhow!("Unknown column index {}", edit.column_index))?;
+                        Ok(format!("{} = {}", quote_ident(&column.name), quote_literal(&edit.value)))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                let conditions: Vec<String> = key_columns.iter()
+                    .zip(key.iter())
+                    .map(|(&idx, value)| format!("{} = {}", quote_ident(&columns[idx].name), quote_literal(value)))
+                    .collect();
+
+                Ok(format!(
+                    "UPDATE {} SET {} WHERE {}",
+                    qualified_name(schema, table),
+                    assignments.join(", "),
+                    conditions.join(" AND ")
+                ))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns() -> Vec<ColumnInfo> {
+        vec![
+            ColumnInfo { name: "id".to_string(), data_type: "int4".to_string(), is_primary_key: true, is_foreign_key: false },
+            ColumnInfo { name: "name".to_string(), data_type: "text".to_string(), is_primary_key: false, is_foreign_key: false },
+            ColumnInfo { name: "city".to_string(), data_type: "text".to_string(), is_primary_key: false, is_foreign_key: false },
+        ]
+    }
+
+    #[test]
+    fn test_stage_and_unstage() {
+        let mut edits = PendingEdits::default();
+        edits.stage(vec!["1".to_string()], 1, "Ann".to_string(), "Anna".to_string());
+        assert_eq!(edits.len(), 1);
+
+        // Editing again keeps the original value
+        edits.stage(vec!["1".to_string()], 1, "Anna".to_string(), "Annie".to_string());
+        assert_eq!(edits.get(&["1".to_string()], 1).unwrap().original, "Ann");
+
+        // Back to the original value unstages the edit
+        edits.stage(vec!["1".to_string()], 1, "Annie".to_string(), "Ann".to_string());
+        assert!(edits.is_empty());
+    }
+
+    #[test]
+    fn test_update_statements_grouped_by_row() {
+        let mut edits = PendingEdits::default();
+        edits.stage(vec!["1".to_string()], 1, "Ann".to_string(), "O'Neil".to_string());
+        edits.stage(vec!["2".to_string()], 2, "Oslo".to_string(), "Bergen".to_string());
+        edits.stage(vec!["1".to_string()], 2, "Rome".to_string(), "Paris".to_string());
+
+        let statements = edits.to_update_statements("public", "people", &columns()).unwrap();
+        assert_eq!(statements, vec![
+            "UPDATE \"public\".\"people\" SET \"name\" = 'O''Neil', \"city\" = 'Paris' WHERE \"id\" = '1'".to_string(),
+            "UPDATE \"public\".\"people\" SET \"city\" = 'Bergen' WHERE \"id\" = '2'".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_no_primary_key_is_an_error() {
+        let mut columns = columns();
+        columns[0].is_primary_key = false;
+        let mut edits = PendingEdits::default();
+        edits.stage(vec![], 1, "a".to_string(), "b".to_string());
+        assert!(edits.to_update_statements("public", "people", &columns).is_err());
+    }
+
+    #[test]
+    fn test_apply_to_rows() {
+        let mut rows = vec![
+            vec![CellValue::Int(1), CellValue::from("Ann"), CellValue::Int(30)],
+            vec![CellValue::Int(2), CellValue::from("Bob"), CellValue::Int(40)],
+        ];
+        let mut edits = PendingEdits::default();
+        edits.stage(vec!["2".to_string()], 1, "Bob".to_string(), "Rob".to_string());
+        edits.stage(vec!["2".to_string()], 2, "40".to_string(), "41".to_string());
+        edits.apply_to_rows(&mut rows, &[0]);
+        assert_eq!(rows[1][1], CellValue::from("Rob"));
+        assert_eq!(rows[1][2], CellValue::Int(41));
+        assert_eq!(rows[0][1], CellValue::from("Ann"));
+    }
+}
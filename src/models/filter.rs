@@ -1,4 +1,10 @@
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use crate::config::Driver;
+use crate::db::sql::{quote_ident_for, quote_literal};
+use crate::db::ColumnInfo;
+use crate::models::{parse_timestamp, CellValue};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum FilterOperator {
@@ -14,6 +20,8 @@ pub enum FilterOperator {
     GreaterThanOrEqual,
     IsNull,
     IsNotNull,
+    Regex,
+    NotRegex,
 }
 
 impl FilterOperator {
@@ -31,6 +39,8 @@ impl FilterOperator {
             FilterOperator::GreaterThanOrEqual => "greater than or equal",
             FilterOperator::IsNull => "is null",
             FilterOperator::IsNotNull => "is not null",
+            FilterOperator::Regex => "matches regex",
+            FilterOperator::NotRegex => "not matches regex",
         }
     }
 
@@ -48,6 +58,8 @@ impl FilterOperator {
             FilterOperator::GreaterThanOrEqual,
             FilterOperator::IsNull,
             FilterOperator::IsNotNull,
+            FilterOperator::Regex,
+            FilterOperator::NotRegex,
         ]
     }
 
@@ -55,48 +67,41 @@ impl FilterOperator {
         !matches!(self, FilterOperator::IsNull | FilterOperator::IsNotNull)
     }
 
-    pub fn matches(&self, cell_value: &str, filter_value: &str) -> bool {
-        let cell_lower = cell_value.to_lowercase();
-        let filter_lower = filter_value.to_lowercase();
+    pub fn is_regex(&self) -> bool {
+        matches!(self, FilterOperator::Regex | FilterOperator::NotRegex)
+    }
+
+    /// Whether `cell` passes this operator. Text operators work on the displayed value,
+    /// ignoring case unless `case_sensitive`; comparisons are numeric when both sides are
+    /// numbers and chronological when both are dates or timestamps. Regex operators need `regex`, the value compiled by `FilterRule::compile`.
+    pub fn matches(&self, cell: &CellValue, filter_value: &str, case_sensitive: bool, regex: Option<&Regex>) -> bool {
+        let fold = |text: String| if case_sensitive { text } else { text.to_lowercase() };
+        let cell_text = || fold(cell.to_string());
+        let filter_text = fold(filter_value.to_string());
+        let ordering = || match (cell.as_f64(), filter_value.trim().parse::<f64>()) {
+            (Some(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            _ => match (cell.as_timestamp(), parse_timestamp(filter_value)) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                _ => cell_text().cmp(&filter_text),
+            },
+        };
+        let regex_match = || regex.is_some_and(|regex| !cell.is_null() && regex.is_match(&cell.to_string()));
 
         match self {
-            FilterOperator::Equals => cell_lower == filter_lower,
-            FilterOperator::NotEquals => cell_lower != filter_lower,
-            FilterOperator::Contains => cell_lower.contains(&filter_lower),
-            FilterOperator::NotContains => !cell_lower.contains(&filter_lower),
-            FilterOperator::StartsWith => cell_lower.starts_with(&filter_lower),
-            FilterOperator::EndsWith => cell_lower.ends_with(&filter_lower),
-            FilterOperator::LessThan => {
-                // Try numeric comparison first
-                if let (Ok(a), Ok(b)) = (cell_value.parse::<f64>(), filter_value.parse::<f64>()) {
-                    a < b
-                } else {
-                    cell_lower < filter_lower
-                }
-            }
-            FilterOperator::LessThanOrEqual => {
-                if let (Ok(a), Ok(b)) = (cell_value.parse::<f64>(), filter_value.parse::<f64>()) {
-                    a <= b
-                } else {
-                    cell_lower <= filter_lower
-                }
-            }
-            FilterOperator::GreaterThan => {
-                if let (Ok(a), Ok(b)) = (cell_value.parse::<f64>(), filter_value.parse::<f64>()) {
-                    a > b
-                } else {
-                    cell_lower > filter_lower
-                }
-            }
-            FilterOperator::GreaterThanOrEqual => {
-                if let (Ok(a), Ok(b)) = (cell_value.parse::<f64>(), filter_value.parse::<f64>()) {
-                    a >= b
-                } else {
-                    cell_lower >= filter_lower
-                }
-            }
-            FilterOperator::IsNull => cell_value.is_empty() || cell_value.eq_ignore_ascii_case("null"),
-            FilterOperator::IsNotNull => !cell_value.is_empty() && !cell_value.eq_ignore_ascii_case("null"),
+            FilterOperator::Equals => ordering() == Ordering::Equal,
+            FilterOperator::NotEquals => ordering() != Ordering::Equal,
+            FilterOperator::Contains => cell_text().contains(&filter_text),
+            FilterOperator::NotContains => !cell_text().contains(&filter_text),
+            FilterOperator::StartsWith => cell_text().starts_with(&filter_text),
+            FilterOperator::EndsWith => cell_text().ends_with(&filter_text),
+            FilterOperator::LessThan => ordering() == Ordering::Less,
+            FilterOperator::LessThanOrEqual => ordering() != Ordering::Greater,
+            FilterOperator::GreaterThan => ordering() == Ordering::Greater,
+            FilterOperator::GreaterThanOrEqual => ordering() != Ordering::Less,
+            FilterOperator::IsNull => cell.is_null(),
+            FilterOperator::IsNotNull => !cell.is_null(),
+            FilterOperator::Regex => regex_match(),
+            FilterOperator::NotRegex => !regex_match(),
         }
     }
 }
@@ -116,12 +121,14 @@ impl FilterConjunction {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FilterRule {
     pub column_index: usize,
     pub operator: FilterOperator,
     pub value: String,
     pub conjunction: FilterConjunction, // Conjunction before this rule (except for first rule)
+    #[serde(default)]
+    pub case_sensitive: bool,
 }
 
 impl FilterRule {
@@ -131,17 +138,313 @@ impl FilterRule {
             operator: FilterOperator::Contains,
             value: String::new(),
             conjunction: FilterConjunction::And,
+            case_sensitive: false,
         }
     }
 
-    pub fn matches_row(&self, row: &[String]) -> bool {
+    /// The pattern of a regex rule, compiled. None for other operators and empty patterns.
+    pub fn compile(&self) -> Option<Result<Regex, regex::Error>> {
+        (self.operator.is_regex() && !self.value.is_empty()).then(|| {
+            RegexBuilder::new(&self.value).case_insensitive(!self.case_sensitive).build()
+        })
+    }
+
+    /// Why the pattern of a regex rule does not compile, for showing next to it.
+    pub fn regex_error(&self) -> Option<String> {
+        self.compile()?.err().map(|error| error.to_string())
+    }
+
+    /// Whether `row` passes, with `regex` compiled from this rule beforehand. Rules with
+    /// an empty value, or a pattern that doesn't compile, let every row through.
+    fn matches_row(&self, row: &[CellValue], regex: Option<&Result<Regex, regex::Error>>) -> bool {
         if let Some(cell_value) = row.get(self.column_index) {
             if self.operator.needs_value() && self.value.is_empty() {
                 return true; // Empty filter always matches
             }
-            self.operator.matches(cell_value, &self.value)
+            if let Some(Err(_)) = regex {
+                return true;
+            }
+            self.operator.matches(cell_value, &self.value, self.case_sensitive, regex.and_then(|regex| regex.as_ref().ok()))
         } else {
             false
         }
     }
 }
+
+// The rule a column's box in the grid's filter row edits: ANDed Contains on that column
+fn column_filter_position(filters: &[FilterRule], column: usize) -> Option<usize> {
+    filters.iter().position(|rule| {
+        rule.column_index == column
+            && rule.operator == FilterOperator::Contains
+            && rule.conjunction == FilterConjunction::And
+            && !rule.case_sensitive
+    })
+}
+
+/// Text of the grid's filter row box for `column`, empty when it has no rule.
+pub fn column_filter(filters: &[FilterRule], column: usize) -> &str {
+    column_filter_position(filters, column).map_or("", |index| &filters[index].value)
+}
+
+/// Set the filter row box of `column` to `value`: updates its Contains rule, adds one
+/// after the other rules, or removes it when `value` is empty.
+pub fn set_column_filter(filters: &mut Vec<FilterRule>, column: usize, value: String) {
+    match (column_filter_position(filters, column), value.is_empty()) {
+        (Some(index), true) => {
+            filters.remove(index);
+        }
+        (Some(index), false) => filters[index].value = value,
+        (None, true) => {}
+        (None, false) => filters.push(FilterRule { value, ..FilterRule::new(column) }),
+    }
+}
+
+/// Indices of the rows in `rows` that satisfy `filters`, evaluated left to right
+/// with each rule's conjunction.
+pub fn filter_row_indices(rows: &[Vec<CellValue>], filters: &[FilterRule]) -> Vec<usize> {
+    if filters.is_empty() {
+        return (0..rows.len()).collect();
+    }
+
+    // Patterns are compiled once per pass rather than per row
+    let patterns: Vec<_> = filters.iter().map(FilterRule::compile).collect();
+    rows.iter()
+        .enumerate()
+        .filter(|(_, row)| {
+            let mut result = filters[0].matches_row(row, patterns[0].as_ref());
+
+            for (filter, pattern) in filters.iter().zip(&patterns).skip(1) {
+                let matches = filter.matches_row(row, pattern.as_ref());
+                result = match filter.conjunction {
+                    FilterConjunction::And => result && matches,
+                    FilterConjunction::Or => result || matches,
+                };
+            }
+
+            result
+        })
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// `filters` as a WHERE condition, evaluated left to right like `filter_row_indices`
+/// and ignoring case unless a rule is case-sensitive. Regex rules use the server's
+/// regex flavour. Values are bound as text parameters appended to
+/// `params` (`$n` for PostgreSQL, `?` for MySQL), or inlined as literals for display
+/// when `params` is None. None when there are no filters.
+pub fn filters_to_sql(driver: Driver, filters: &[FilterRule], columns: &[ColumnInfo], mut params: Option<&mut Vec<String>>) -> Option<String> {
+    let mut condition: Option<String> = None;
+    for (index, filter) in filters.iter().enumerate() {
+        let rule = rule_to_sql(driver, filter, columns, &mut params);
+        condition = Some(match condition {
+            None => rule,
+            Some(previous) if index > 1 => format!("({}) {} {}", previous, filter.conjunction.as_str(), rule),
+            Some(previous) => format!("{} {} {}", previous, filter.conjunction.as_str(), rule),
+        });
+    }
+    condition
+}
+
+fn rule_to_sql(driver: Driver, filter: &FilterRule, columns: &[ColumnInfo], params: &mut Option<&mut Vec<String>>) -> String {
+    let Some(column) = columns.get(filter.column_index) else {
+        return "FALSE".to_string();
+    };
+    if filter.operator.needs_value() && filter.value.is_empty() {
+        return "TRUE".to_string();
+    }
+
+    let name = quote_ident_for(driver, &column.name);
+    let mut bind = |value: String| match params {
+        Some(params) => {
+            params.push(value);
+            match driver {
+                Driver::Postgres => format!("${}::text", params.len()),
+                Driver::MySql => "?".to_string(),
+            }
+        }
+        None => quote_literal(&value),
+    };
+    let text = match (driver, filter.case_sensitive) {
+        (Driver::Postgres, false) => format!("lower({}::text)", name),
+        (Driver::Postgres, true) => format!("{}::text", name),
+        (Driver::MySql, false) => format!("LOWER(CAST({} AS CHAR))", name),
+        (Driver::MySql, true) => format!("CAST({} AS BINARY)", name),
+    };
+    let fold = |value: String| if filter.case_sensitive { value } else { format!("lower({})", value) };
+    let like = |pattern: String| format!("{} LIKE {}", text, fold(pattern));
+    let regex = |pattern: String| match (driver, filter.case_sensitive) {
+        (Driver::Postgres, false) => format!("{}::text ~* {}", name, pattern),
+        (Driver::Postgres, true) => format!("{}::text ~ {}", name, pattern),
+        (Driver::MySql, false) => format!("REGEXP_LIKE({}, {}, 'i')", name, pattern),
+        (Driver::MySql, true) => format!("REGEXP_LIKE({}, {}, 'c')", name, pattern),
+    };
+    let escaped = || filter.value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    // Numbers and dates compare as such when the column holds them, anything else as text
+    let compare = |operator: &str, bind: &mut dyn FnMut(String) -> String| {
+        let value = filter.value.trim().to_string();
+        let cast = if is_numeric_type(&column.data_type) && value.parse::<f64>().is_ok() {
+            Some("numeric")
+        } else if is_date_type(&column.data_type) && parse_timestamp(&value).is_some() {
+            Some(if column.data_type.contains("tz") || column.data_type.contains("time zone") { "timestamptz" } else { "timestamp" })
+        } else {
+            None
+        };
+        match (cast, driver) {
+            (Some(cast), Driver::Postgres) => format!("{} {} {}::{}", name, operator, bind(value), cast),
+            (Some(_), Driver::MySql) => format!("{} {} {}", name, operator, bind(value)),
+            (None, _) => format!("{} {} {}", text, operator, fold(bind(filter.value.clone()))),
+        }
+    };
+
+    match filter.operator {
+        FilterOperator::Equals => compare("=", &mut bind),
+        FilterOperator::NotEquals => format!("({} IS NULL OR {})", name, compare("<>", &mut bind)),
+        FilterOperator::Contains => like(bind(format!("%{}%", escaped()))),
+        FilterOperator::NotContains => format!("({} IS NULL OR NOT {})", name, like(bind(format!("%{}%", escaped())))),
+        FilterOperator::StartsWith => like(bind(format!("{}%", escaped()))),
+        FilterOperator::EndsWith => like(bind(format!("%{}", escaped()))),
+        FilterOperator::LessThan => compare("<", &mut bind),
+        FilterOperator::LessThanOrEqual => compare("<=", &mut bind),
+        FilterOperator::GreaterThan => compare(">", &mut bind),
+        FilterOperator::GreaterThanOrEqual => compare(">=", &mut bind),
+        FilterOperator::IsNull => format!("{} IS NULL", name),
+        FilterOperator::IsNotNull => format!("{} IS NOT NULL", name),
+        FilterOperator::Regex => regex(bind(filter.value.clone())),
+        FilterOperator::NotRegex => format!("({} IS NULL OR NOT {})", name, regex(bind(filter.value.clone()))),
+    }
+}
+
+fn is_date_type(data_type: &str) -> bool {
+    let data_type = data_type.to_lowercase();
+    let base = data_type.split('(').next().unwrap_or_default();
+    base == "date" || base.starts_with("timestamp") || base.starts_with("datetime")
+}
+
+fn is_numeric_type(data_type: &str) -> bool {
+    let data_type = data_type.to_lowercase();
+    let starts = |prefixes: &[&str]| prefixes.iter().any(|prefix| data_type.starts_with(prefix));
+    starts(&["int", "smallint", "bigint", "mediumint", "tinyint", "serial", "bigserial", "smallserial", "numeric", "decimal", "real", "double", "float"])
+        && !data_type.starts_with("interval")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_matching() {
+        assert!(FilterOperator::IsNull.matches(&CellValue::Null, "", false, None));
+        assert!(!FilterOperator::IsNull.matches(&CellValue::from("null"), "", false, None));
+        assert!(FilterOperator::Equals.matches(&CellValue::Int(5), "5.0", false, None));
+        assert!(FilterOperator::GreaterThan.matches(&CellValue::Int(10), "9", false, None));
+        assert!(FilterOperator::LessThan.matches(&CellValue::from("apple"), "Banana", false, None));
+        assert!(FilterOperator::Contains.matches(&CellValue::Bool(true), "TRU", false, None));
+        assert!(!FilterOperator::Contains.matches(&CellValue::Bool(true), "TRU", true, None));
+        assert!(!FilterOperator::Equals.matches(&CellValue::from("Apple"), "apple", true, None));
+    }
+
+    #[test]
+    fn test_date_comparisons() {
+        let created = CellValue::from("2024-01-01 10:00:00+00");
+        assert!(FilterOperator::GreaterThan.matches(&created, "2024-01-01", false, None));
+        assert!(FilterOperator::LessThan.matches(&created, "2024-01-01 12:00:00+01", false, None));
+        // Lexically "2024-1-9" would sort after "2024-01-10"
+        let day = CellValue::Date(chrono::NaiveDate::from_ymd_opt(2024, 1, 9).unwrap());
+        assert!(FilterOperator::LessThan.matches(&day, "2024-01-10", false, None));
+        assert!(FilterOperator::Equals.matches(&day, "2024-01-09 00:00", false, None));
+    }
+
+    #[test]
+    fn test_regex_rules() {
+        let rows = vec![
+            vec![CellValue::from("alice@example.com")],
+            vec![CellValue::from("BOB@EXAMPLE.COM")],
+            vec![CellValue::from("not an email")],
+            vec![CellValue::Null],
+        ];
+        let mut rule = FilterRule { operator: FilterOperator::Regex, value: r"^\w+@example\.com$".to_string(), ..FilterRule::new(0) };
+        assert_eq!(filter_row_indices(&rows, &[rule.clone()]), vec![0, 1]);
+
+        rule.case_sensitive = true;
+        assert_eq!(filter_row_indices(&rows, &[rule.clone()]), vec![0]);
+
+        rule.operator = FilterOperator::NotRegex;
+        assert_eq!(filter_row_indices(&rows, &[rule.clone()]), vec![1, 2, 3]);
+
+        // A pattern still being typed filters nothing out and reports why
+        rule.value = "(unclosed".to_string();
+        assert!(rule.regex_error().is_some());
+        assert_eq!(filter_row_indices(&rows, &[rule]).len(), 4);
+    }
+
+    #[test]
+    fn test_column_filters() {
+        let mut filters = vec![FilterRule { operator: FilterOperator::Equals, value: "x".to_string(), ..FilterRule::new(0) }];
+        assert_eq!(column_filter(&filters, 0), "", "Only Contains rules show in the filter row");
+
+        set_column_filter(&mut filters, 0, "al".to_string());
+        set_column_filter(&mut filters, 1, "3".to_string());
+        assert_eq!(filters.len(), 3);
+        assert_eq!((column_filter(&filters, 0), column_filter(&filters, 1)), ("al", "3"));
+
+        set_column_filter(&mut filters, 0, "ali".to_string());
+        assert_eq!(filters[1].value, "ali");
+        set_column_filter(&mut filters, 0, String::new());
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters[0].operator, FilterOperator::Equals);
+        assert_eq!(column_filter(&filters, 0), "");
+    }
+
+    fn rule(column_index: usize, operator: FilterOperator, value: &str, conjunction: FilterConjunction) -> FilterRule {
+        FilterRule { column_index, operator, value: value.to_string(), conjunction, case_sensitive: false }
+    }
+
+    #[test]
+    fn test_filters_to_sql() {
+        let columns = vec![
+            ColumnInfo { name: "name".to_string(), data_type: "text".to_string(), is_primary_key: false, is_foreign_key: false },
+            ColumnInfo { name: "age".to_string(), data_type: "integer".to_string(), is_primary_key: false, is_foreign_key: false },
+        ];
+        let filters = vec![
+            rule(0, FilterOperator::Contains, "50%_o'k", FilterConjunction::And),
+            rule(1, FilterOperator::GreaterThan, "30", FilterConjunction::And),
+            rule(0, FilterOperator::IsNull, "", FilterConjunction::Or),
+        ];
+
+        let mut params = Vec::new();
+        assert_eq!(
+            filters_to_sql(Driver::Postgres, &filters, &columns, Some(&mut params)).unwrap(),
+            "(lower(\"name\"::text) LIKE lower($1::text) AND \"age\" > $2::text::numeric) OR \"name\" IS NULL"
+        );
+        assert_eq!(params, vec!["%50\\%\\_o'k%".to_string(), "30".to_string()]);
+
+        let mut params = Vec::new();
+        assert_eq!(
+            filters_to_sql(Driver::MySql, &filters[..2], &columns, Some(&mut params)).unwrap(),
+            "LOWER(CAST(`name` AS CHAR)) LIKE lower(?) AND `age` > ?"
+        );
+        assert_eq!(params.len(), 2);
+
+        let not_equals = vec![rule(1, FilterOperator::NotEquals, "old", FilterConjunction::And)];
+        assert_eq!(
+            filters_to_sql(Driver::Postgres, &not_equals, &columns, None).unwrap(),
+            "(\"age\" IS NULL OR lower(\"age\"::text) <> lower('old'))"
+        );
+        assert_eq!(filters_to_sql(Driver::Postgres, &[rule(0, FilterOperator::Equals, "", FilterConjunction::And)], &columns, None).unwrap(), "TRUE");
+        assert_eq!(filters_to_sql(Driver::Postgres, &[], &columns, None), None);
+
+        let mut regex = rule(0, FilterOperator::Regex, "^a", FilterConjunction::And);
+        assert_eq!(filters_to_sql(Driver::Postgres, &[regex.clone()], &columns, None).unwrap(), "\"name\"::text ~* '^a'");
+        regex.case_sensitive = true;
+        assert_eq!(filters_to_sql(Driver::MySql, &[regex], &columns, None).unwrap(), "REGEXP_LIKE(`name`, '^a', 'c')");
+        let case_sensitive = FilterRule { case_sensitive: true, ..rule(0, FilterOperator::StartsWith, "Al", FilterConjunction::And) };
+        assert_eq!(filters_to_sql(Driver::Postgres, &[case_sensitive], &columns, None).unwrap(), "\"name\"::text LIKE 'Al%'");
+
+        let created = vec![ColumnInfo { name: "created".to_string(), data_type: "timestamptz(6)".to_string(), is_primary_key: false, is_foreign_key: false }];
+        let mut params = Vec::new();
+        assert_eq!(
+            filters_to_sql(Driver::Postgres, &[rule(0, FilterOperator::GreaterThan, "2024-01-01", FilterConjunction::And)], &created, Some(&mut params)).unwrap(),
+            "\"created\" > $1::text::timestamptz"
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use crate::config::Driver;
+use crate::db::sql::quote_ident_for;
+use crate::db::{ColumnInfo, SchemaInfo};
+use crate::export::is_date_type;
+
+// Suggestions of each kind offered for one result
+const MAX_GROUPINGS: usize = 2;
+const MAX_JOINS: usize = 3;
+// Column names (or their endings) of values worth counting rows by
+const GROUPING_NAMES: &[&str] = &["status", "state", "type", "kind", "category", "role", "level", "stage"];
+// Timestamp columns preferred for "latest", in order
+const LATEST_NAMES: &[&str] = &["created_at", "inserted_at", "created", "updated_at", "modified_at", "timestamp"];
+
+/// Query offered after a result loads, opened in the editor when picked instead of run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FollowUp {
+    pub label: String,
+    pub sql: String,
+}
+
+/// Follow-ups for rows of `schema.table`, derived from its column names, types and
+/// foreign keys: counts by status-like columns, the latest rows by a timestamp and
+/// joins to the tables `*_id` columns point at.
+pub fn follow_ups(driver: Driver, schemas: &[SchemaInfo], schema: &str, table: &str) -> Vec<FollowUp> {
+    let Some(columns) = schemas.iter().find(|s| s.name == schema).and_then(|s| s.table_columns.get(table)) else {
+        return Vec::new();
+    };
+    let quote = |ident: &str| quote_ident_for(driver, ident);
+    let source = format!("{}.{}", quote(schema), quote(table));
+    let mut follow_ups = Vec::new();
+
+    let groupings = columns.iter()
+        .filter(|column| !column.is_primary_key && is_grouping_column(column))
+        .take(MAX_GROUPINGS);
+    for column in groupings {
+        let name = quote(&column.name);
+        follow_ups.push(FollowUp {
+            label: format!("Count by {}", column.name),
+            sql: format!("SELECT {0}, count(*) AS count\nFROM {1}\nGROUP BY {0}\nORDER BY count DESC", name, source),
+        });
+    }
+
+    let dates: Vec<&ColumnInfo> = columns.iter().filter(|column| is_date_type(&column.data_type)).collect();
+    let latest = LATEST_NAMES.iter()
+        .find_map(|name| dates.iter().find(|column| column.name.eq_ignore_ascii_case(name)))
+        .or(dates.first());
+    if let Some(column) = latest {
+        follow_ups.push(FollowUp {
+            label: format!("Latest 100 by {}", column.name),
+            sql: format!("SELECT *\nFROM {}\nORDER BY {} DESC\nLIMIT 100", source, quote(&column.name)),
+        });
+    }
+
+    let joins = columns.iter()
+        .filter(|column| !column.is_primary_key)
+        .filter_map(|column| Some((column, referenced_table(schemas, schema, column)?)))
+        .take(MAX_JOINS);
+    for (column, (target_schema, target_table, key)) in joins {
+        let target = format!("{}.{}", quote(target_schema), quote(target_table));
+        follow_ups.push(FollowUp {
+            label: format!("Join to {}", target_table),
+            sql: format!(
+                "SELECT *\nFROM {0}\nJOIN {1} ON {1}.{2} = {0}.{3}\nLIMIT 100",
+                source, target, quote(key), quote(&column.name)
+            ),
+        });
+    }
+
+    follow_ups
+}
+
+// Few distinct values to count by: booleans, enums and status-like names
+fn is_grouping_column(column: &ColumnInfo) -> bool {
+    let name = column.name.to_lowercase();
+    let data_type = column.data_type.to_lowercase();
+    data_type.starts_with("bool") || data_type.starts_with("enum") || data_type == "tinyint(1)"
+        || GROUPING_NAMES.iter().any(|suffix| name == *suffix || name.ends_with(&format!("_{}", suffix)))
+}
+
+// Table a `<name>_id` column points at, looked for as `<name>` and its plurals in the
+// same schema first, with the column it is keyed by
+fn referenced_table<'a>(schemas: &'a [SchemaInfo], schema: &str, column: &ColumnInfo) -> Option<(&'a str, &'a str, &'a str)> {
+    let name = column.name.to_lowercase();
+    let stem = name.strip_suffix("_id").filter(|stem| !stem.is_empty())?;
+    let mut candidates = vec![stem.to_string(), format!("{}s", stem), format!("{}es", stem)];
+    if let Some(singular) = stem.strip_suffix('y') {
+        candidates.push(format!("{}ies", singular));
+    }
+
+    let same_schema_first = schemas.iter().filter(|s| s.name == schema).chain(schemas.iter().filter(|s| s.name != schema));
+    for info in same_schema_first {
+        for (table, columns) in &info.table_columns {
+            if !candidates.iter().any(|candidate| table.eq_ignore_ascii_case(candidate)) {
+                continue;
+            }
+            let key = columns.iter().find(|c| c.is_primary_key).or_else(|| columns.iter().find(|c| c.name.eq_ignore_ascii_case("id")));
+            if let Some(key) = key {
+                return Some((&info.name, table, &key.name));
+            }
+        }
+    }
+    None
+}
+
+/// The one table a plain `SELECT … FROM table` reads, so its result gets follow-ups too.
+/// None for joins, subqueries and anything but a single SELECT.
+pub fn single_source_table(sql: &str) -> Option<(Option<String>, String)> {
+    let sql = sql.trim().trim_end_matches(';');
+    let lower = sql.to_lowercase();
+    if !lower.starts_with("select") || lower.matches("select").count() > 1 || lower.contains(" join ") || sql.contains(';') {
+        return None;
+    }
+    let from = lower.find(" from ")? + " from ".len();
+    let name = sql[from..].split_whitespace().next()?;
+    if name.contains([',', '(']) {
+        return None;
+    }
+
+    let unquote = |part: &str| part.trim_matches(|c| c == '"' || c == '`').to_string();
+    match name.split_once('.') {
+        Some((schema, table)) => Some((Some(unquote(schema)), unquote(table))),
+        None => Some((None, unquote(name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schemas() -> Vec<SchemaInfo> {
+        let mut public = SchemaInfo::new("public".to_string());
+        public.table_columns.insert("orders".to_string(), vec![
+            ColumnInfo { is_primary_key: true, ..ColumnInfo::test("id", "int4") },
+            ColumnInfo::test("customer_id", "int4"),
+            ColumnInfo::test("status", "text"),
+            ColumnInfo::test("paid", "bool"),
+            ColumnInfo::test("updated_at", "timestamptz"),
+            ColumnInfo::test("created_at", "timestamptz"),
+            ColumnInfo::test("category_id", "int4"),
+            ColumnInfo::test("unknown_id", "int4"),
+        ]);
+        public.table_columns.insert("customers".to_string(), vec![ColumnInfo { is_primary_key: true, ..ColumnInfo::test("customer_no", "int4") }]);
+        public.table_columns.insert("categories".to_string(), vec![ColumnInfo::test("id", "int4")]);
+        vec![public]
+    }
+
+    #[test]
+    fn test_follow_ups() {
+        let follow_ups = follow_ups(Driver::Postgres, &schemas(), "public", "orders");
+        let labels: Vec<&str> = follow_ups.iter().map(|f| f.label.as_str()).collect();
+        assert_eq!(labels, ["Count by status", "Count by paid", "Latest 100 by created_at", "Join to customers", "Join to categories"]);
+        assert_eq!(follow_ups[0].sql, "SELECT \"status\", count(*) AS count\nFROM \"public\".\"orders\"\nGROUP BY \"status\"\nORDER BY count DESC");
+        assert_eq!(
+            follow_ups[3].sql,
+            "SELECT *\nFROM \"public\".\"orders\"\nJOIN \"public\".\"customers\" ON \"public\".\"customers\".\"customer_no\" = \"public\".\"orders\".\"customer_id\"\nLIMIT 100"
+        );
+
+        let follow_ups = super::follow_ups(Driver::MySql, &schemas(), "public", "orders");
+        assert!(follow_ups[2].sql.contains("ORDER BY `created_at` DESC"));
+        assert!(super::follow_ups(Driver::Postgres, &schemas(), "public", "missing").is_empty());
+    }
+
+    #[test]
+    fn test_single_source_table() {
+        assert_eq!(single_source_table("SELECT * FROM orders WHERE id > 3"), Some((None, "orders".to_string())));
+        assert_eq!(single_source_table("select id from \"public\".\"orders\";"), Some((Some("public".to_string()), "orders".to_string())));
+        assert_eq!(single_source_table("SELECT * FROM orders o JOIN customers c ON c.id = o.customer_id"), None);
+        assert_eq!(single_source_table("SELECT * FROM a, b"), None);
+        assert_eq!(single_source_table("SELECT * FROM (SELECT 1) s"), None);
+        assert_eq!(single_source_table("UPDATE orders SET paid = true"), None);
+    }
+}
@@ -0,0 +1,75 @@
+// Score bonuses for the position of a matched character
+const CONSECUTIVE_BONUS: i64 = 8;
+const WORD_START_BONUS: i64 = 6;
+
+/// How well `query` matches `text` when its characters appear in order, case-insensitively,
+/// or None when they don't. Runs of consecutive characters and matches at the start of
+/// words score higher; shorter texts win ties.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut matched = 0;
+    let mut previous: Option<usize> = None;
+    for (i, c) in text.iter().enumerate() {
+        if matched == query.len() {
+            break;
+        }
+        if *c != query[matched] {
+            continue;
+        }
+        score += 1;
+        if previous == Some(i.wrapping_sub(1)) {
+            score += CONSECUTIVE_BONUS;
+        }
+        if i == 0 || !text[i - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        previous = Some(i);
+        matched += 1;
+    }
+
+    (matched == query.len()).then(|| score * 100 - text.len() as i64)
+}
+
+/// Indices of `items` matching `query`, best match first.
+pub fn fuzzy_rank<'a>(query: &str, items: impl IntoIterator<Item = &'a str>) -> Vec<usize> {
+    let mut scored: Vec<(usize, i64)> = items.into_iter()
+        .enumerate()
+        .filter_map(|(index, text)| fuzzy_score(query, text).map(|score| (index, score)))
+        .collect();
+    // Stable, so equal scores keep their original order
+    scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(index, _)| index).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("usr", "public.users").is_some());
+        assert!(fuzzy_score("USERS", "public.users").is_some());
+        assert!(fuzzy_score("srsu", "public.users").is_none());
+        assert!(fuzzy_score("close tab", "Close Tab").is_some());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+
+        // Consecutive and word-start matches beat scattered ones
+        assert!(fuzzy_score("ord", "public.order_lines") > fuzzy_score("ord", "public.other_records"));
+        // Shorter text wins a tie
+        assert!(fuzzy_score("users", "public.users") > fuzzy_score("users", "public.users_archive"));
+    }
+
+    #[test]
+    fn test_fuzzy_rank() {
+        let items = ["Reload", "public.orders", "Run SQL File", "public.order_items"];
+        assert_eq!(fuzzy_rank("order", items), vec![1, 3]);
+        assert_eq!(fuzzy_rank("rl", items), vec![0, 2]);
+        assert_eq!(fuzzy_rank("", items), vec![0, 1, 2, 3]);
+    }
+}
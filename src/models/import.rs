@@ -0,0 +1,287 @@
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize};
+use crate::db::ColumnInfo;
+use super::pasted_table::detect_delimiter;
+
+// File rows shown under the column mapping
+const PREVIEW_ROWS: usize = 10;
+
+/// Delimiters an imported file can be read with, and their names.
+pub const IMPORT_DELIMITERS: [(u8, &str); 4] = [(b',', "Comma"), (b';', "Semicolon"), (b'\t', "Tab"), (b'|', "Pipe")];
+
+/// Which fields of an imported file load as NULL.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NullHandling {
+    // Empty fields
+    Empty,
+    // Fields holding exactly this text, e.g. \N; empty fields load as empty text
+    Marker(String),
+    // None, empty fields load as empty text
+    Never,
+}
+
+impl NullHandling {
+    pub fn as_str(&self) -> &str {
+        match self {
+            NullHandling::Empty => "Empty fields",
+            NullHandling::Marker(_) => "Fields equal to",
+            NullHandling::Never => "Nothing",
+        }
+    }
+
+    pub fn is_null(&self, field: &str) -> bool {
+        match self {
+            NullHandling::Empty => field.is_empty(),
+            NullHandling::Marker(marker) => field == marker,
+            NullHandling::Never => false,
+        }
+    }
+
+    // Value `field` loads as, None for NULL
+    fn value(&self, field: &str) -> Option<String> {
+        (!self.is_null(field)).then(|| field.to_string())
+    }
+}
+
+/// Column names and first rows of a file to import.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilePreview {
+    pub names: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// How far a running import is: bytes of the file read, out of its size, and rows sent
+/// to the server so far.
+#[derive(Debug, Default)]
+pub struct ImportProgress {
+    pub file_size: AtomicU64,
+    pub read: AtomicU64,
+    pub sent: AtomicUsize,
+}
+
+/// Rows of an imported file past the header, as the values of the mapped table columns,
+/// read from the file as they are asked for.
+pub struct ImportRows {
+    records: csv::StringRecordsIntoIter<File>,
+    // File column of each value
+    file_columns: Vec<usize>,
+    null_handling: NullHandling,
+}
+
+impl ImportRows {
+    /// Bytes of the file read so far.
+    pub fn position(&self) -> u64 {
+        self.records.reader().position().byte()
+    }
+}
+
+impl Iterator for ImportRows {
+    type Item = Result<Vec<Option<String>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.records.next()? {
+            Ok(record) => record,
+            Err(e) => return Some(Err(anyhow::anyhow!("Invalid CSV: {}", e))),
+        };
+        Some(Ok(self.file_columns.iter()
+            .map(|&file_column| record.get(file_column).and_then(|field| self.null_handling.value(field)))
+            .collect()))
+    }
+}
+
+/// A CSV or TSV file to be loaded into a table, and how its columns map onto the
+/// table's.
+#[derive(Debug, Clone)]
+pub struct ImportPlan {
+    pub schema: String,
+    pub table: String,
+    pub table_columns: Vec<ColumnInfo>,
+    pub path: PathBuf,
+    pub delimiter: u8,
+    pub header: bool,
+    pub null_handling: NullHandling,
+    // Table column each file column loads into, None to skip it
+    pub mapping: Vec<Option<usize>>,
+    // The file as last read, or why it could not be
+    pub preview: Result<FilePreview, String>,
+}
+
+impl ImportPlan {
+    /// Plan loading a file, chosen with `set_path`, into `schema.table`.
+    pub fn new(schema: String, table: String, table_columns: &[ColumnInfo]) -> Self {
+        Self {
+            schema,
+            table,
+            table_columns: table_columns.to_vec(),
+            path: PathBuf::new(),
+            delimiter: b',',
+            header: true,
+            null_handling: NullHandling::Empty,
+            mapping: Vec::new(),
+            preview: Err("Choose a CSV or TSV file to import".to_string()),
+        }
+    }
+
+    /// Import `path` instead, with the delimiter guessed from its extension or first line
+    /// and the first line taken as the header.
+    pub fn set_path(&mut self, path: PathBuf) {
+        self.delimiter = if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("tsv")) {
+            b'\t'
+        } else {
+            let first_line = File::open(&path).ok()
+                .and_then(|file| BufReader::new(file).lines().next()?.ok())
+                .unwrap_or_default();
+            detect_delimiter(&first_line)
+        };
+        self.path = path;
+        self.header = true;
+        self.reload();
+    }
+
+    /// Read the preview again after the file or the way it is read changed. File columns
+    /// map onto the table columns of the same name, or by position without a header.
+    pub fn reload(&mut self) {
+        self.preview = self.read_preview().map_err(|e| e.to_string());
+        let names = self.preview.as_ref().map(|preview| preview.names.as_slice()).unwrap_or_default();
+        self.mapping = names.iter()
+            .enumerate()
+            .map(|(index, name)| match self.header {
+                true => self.table_columns.iter().position(|column| column.name.eq_ignore_ascii_case(name.trim())),
+                false => (index < self.table_columns.len()).then_some(index),
+            })
+            .collect();
+    }
+
+    fn reader(&self) -> Result<csv::Reader<File>> {
+        let file = File::open(&self.path).map_err(|e| anyhow::anyhow!("Cannot open {}: {}", self.path.display(), e))?;
+        Ok(csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(file))
+    }
+
+    fn read_preview(&self) -> Result<FilePreview> {
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        for record in self.reader()?.into_records().take(PREVIEW_ROWS + usize::from(self.header)) {
+            let record = record.map_err(|e| anyhow::anyhow!("Invalid CSV: {}", e))?;
+            rows.push(record.iter().map(str::to_string).collect());
+        }
+        if rows.is_empty() {
+            anyhow::bail!("{} is empty", self.path.display());
+        }
+
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let names = if self.header {
+            let mut header = rows.remove(0);
+            header.resize(width, String::new());
+            header.into_iter()
+                .enumerate()
+                .map(|(index, name)| if name.trim().is_empty() { format!("column{}", index + 1) } else { name })
+                .collect()
+        } else {
+            (1..=width).map(|index| format!("column{}", index)).collect()
+        };
+        Ok(FilePreview { names, rows })
+    }
+
+    // Pairs of file column and the table column it loads into
+    fn mapped_columns(&self) -> Result<Vec<(usize, usize)>> {
+        let pairs: Vec<(usize, usize)> = self.mapping.iter()
+            .enumerate()
+            .filter_map(|(file_column, table_column)| Some((file_column, (*table_column)?)))
+            .collect();
+        if pairs.is_empty() {
+            anyhow::bail!("Map at least one file column to a table column");
+        }
+        for (index, (_, table_column)) in pairs.iter().enumerate() {
+            if pairs[..index].iter().any(|(_, other)| other == table_column) {
+                anyhow::bail!("{} is loaded from more than one file column", self.table_columns[*table_column].name);
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// Why the file cannot be imported as mapped, if it cannot.
+    pub fn validate(&self) -> Result<()> {
+        if let Err(e) = &self.preview {
+            anyhow::bail!("{}", e);
+        }
+        self.mapped_columns().map(|_| ())
+    }
+
+    /// Names of the table columns loaded, in the order `rows` gives their values.
+    pub fn target_columns(&self) -> Result<Vec<String>> {
+        Ok(self.mapped_columns()?.into_iter().map(|(_, table_column)| self.table_columns[table_column].name.clone()).collect())
+    }
+
+    /// The rows of the file past the header, in the order of `target_columns`. Fields
+    /// missing from short lines are NULL.
+    pub fn rows(&self) -> Result<ImportRows> {
+        let file_columns = self.mapped_columns()?.into_iter().map(|(file_column, _)| file_column).collect();
+        let mut records = self.reader()?.into_records();
+        if self.header {
+            records.next().transpose().map_err(|e| anyhow::anyhow!("Invalid CSV: {}", e))?;
+        }
+        Ok(ImportRows { records, file_columns, null_handling: self.null_handling.clone() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(plan: &ImportPlan) -> Vec<Vec<Option<String>>> {
+        plan.rows().unwrap().collect::<Result<_>>().unwrap()
+    }
+
+    fn plan_for(name: &str, contents: &str) -> ImportPlan {
+        let path = std::env::temp_dir().join(format!("db-client-import-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        let mut plan = ImportPlan::new("public".to_string(), "people".to_string(), &[ColumnInfo::test("id", "text"), ColumnInfo::test("name", "text"), ColumnInfo::test("note", "text")]);
+        assert!(plan.validate().is_err());
+        plan.set_path(path);
+        plan
+    }
+
+    #[test]
+    fn test_import_plan_maps_by_name() {
+        let mut plan = plan_for("by-name.csv", "Name;ID;extra\nAnn;1;x\n\"Bo; Jr\";2\n");
+        assert_eq!(plan.delimiter, b';');
+        assert_eq!(plan.preview.as_ref().unwrap().names, ["Name", "ID", "extra"]);
+        assert_eq!(plan.mapping, [Some(1), Some(0), None]);
+
+        assert_eq!(plan.target_columns().unwrap(), ["name", "id"]);
+        let value = |text: &str| Some(text.to_string());
+        assert_eq!(rows(&plan), [[value("Ann"), value("1")], [value("Bo; Jr"), value("2")]]);
+        let mut streamed = plan.rows().unwrap();
+        streamed.next();
+        assert_eq!(streamed.position(), "Name;ID;extra\nAnn;1;x\n".len() as u64);
+
+        plan.mapping[2] = Some(1);
+        assert!(plan.validate().is_err());
+        plan.mapping = vec![None; 3];
+        assert!(plan.validate().is_err());
+        std::fs::remove_file(&plan.path).unwrap();
+    }
+
+    #[test]
+    fn test_import_plan_without_header() {
+        let mut plan = plan_for("positional.tsv", "1\t\\N\t\n2\tBo\tlate, again\n");
+        assert_eq!(plan.delimiter, b'\t');
+        plan.header = false;
+        plan.reload();
+        assert_eq!(plan.mapping, [Some(0), Some(1), Some(2)]);
+
+        let value = |text: &str| Some(text.to_string());
+        assert_eq!(rows(&plan)[0], [value("1"), value("\\N"), None]);
+        plan.null_handling = NullHandling::Marker("\\N".to_string());
+        assert_eq!(rows(&plan)[0], [value("1"), None, value("")]);
+        plan.null_handling = NullHandling::Never;
+        assert_eq!(rows(&plan)[1], [value("2"), value("Bo"), value("late, again")]);
+        std::fs::remove_file(&plan.path).unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+use anyhow::Result;
+use std::collections::BTreeSet;
+use crate::db::sql::{quote_ident, quote_literal};
+use crate::models::CellValue;
+
+// Values and nesting looked at when offering key paths
+const SAMPLE_ROWS: usize = 200;
+const MAX_DEPTH: usize = 4;
+
+/// How a jsonb filter compares the value at its path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JsonOperator {
+    // col->'a'->>'b' = 'text'
+    TextEquals,
+    // col->'a'->'b' = 'json'::jsonb
+    JsonEquals,
+    // col @> '{"a":{"b":json}}'
+    Contains,
+    // col->'a'->'b' IS NOT NULL
+    Exists,
+}
+
+impl JsonOperator {
+    pub fn all() -> Vec<JsonOperator> {
+        vec![JsonOperator::TextEquals, JsonOperator::JsonEquals, JsonOperator::Contains, JsonOperator::Exists]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            JsonOperator::TextEquals => "->> text equals",
+            JsonOperator::JsonEquals => "-> JSON equals",
+            JsonOperator::Contains => "@> contains",
+            JsonOperator::Exists => "has key",
+        }
+    }
+
+    pub fn needs_value(&self) -> bool {
+        !matches!(self, JsonOperator::Exists)
+    }
+}
+
+/// A condition on the value at a key path of a jsonb column, built in the JSON filter
+/// dialog and sent to the server as SQL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonFilter {
+    pub column: String,
+    pub path: Vec<String>,
+    pub operator: JsonOperator,
+    pub value: String,
+}
+
+impl JsonFilter {
+    pub fn new(column: String) -> Self {
+        Self { column, path: Vec::new(), operator: JsonOperator::TextEquals, value: String::new() }
+    }
+
+    /// The condition as PostgreSQL. JSON values that don't parse are taken as strings,
+    /// so `pro` and `"pro"` mean the same.
+    pub fn to_sql(&self) -> Result<String> {
+        if self.path.is_empty() {
+            anyhow::bail!("Pick a key");
+        }
+        let column = quote_ident(&self.column);
+        let keys: Vec<String> = self.path.iter().map(|key| quote_literal(key)).collect();
+        let (parents, last) = keys.split_at(keys.len() - 1);
+        let parent = parents.iter().fold(column.clone(), |expression, key| format!("{}->{}", expression, key));
+
+        Ok(match self.operator {
+            JsonOperator::TextEquals => format!("{}->>{} = {}", parent, last[0], quote_literal(&self.value)),
+            JsonOperator::JsonEquals => format!("{}->{} = {}::jsonb", parent, last[0], quote_literal(&self.json_value().to_string())),
+            JsonOperator::Contains => {
+                let document = self.path.iter().rev().fold(self.json_value(), |value, key| {
+                    serde_json::Value::Object(serde_json::Map::from_iter([(key.clone(), value)]))
+                });
+                format!("{} @> {}", column, quote_literal(&document.to_string()))
+            }
+            JsonOperator::Exists => format!("{}->{} IS NOT NULL", parent, last[0]),
+        })
+    }
+
+    fn json_value(&self) -> serde_json::Value {
+        serde_json::from_str(self.value.trim()).unwrap_or_else(|_| serde_json::Value::String(self.value.clone()))
+    }
+}
+
+/// Key paths found in a sample of JSON cells, objects nested a few levels deep, in
+/// alphabetical order. Paths into arrays are not offered.
+pub fn sample_json_paths<'a>(values: impl IntoIterator<Item = &'a CellValue>) -> Vec<Vec<String>> {
+    let mut paths = BTreeSet::new();
+    for value in values.into_iter().take(SAMPLE_ROWS) {
+        let CellValue::Json(text) = value else {
+            continue;
+        };
+        if let Ok(json) = serde_json::from_str::<serde_json::Value>(text) {
+            collect_paths(&json, &mut Vec::new(), &mut paths);
+        }
+    }
+    paths.into_iter().collect()
+}
+
+fn collect_paths(value: &serde_json::Value, prefix: &mut Vec<String>, paths: &mut BTreeSet<Vec<String>>) {
+    let serde_json::Value::Object(object) = value else {
+        return;
+    };
+    if prefix.len() >= MAX_DEPTH {
+        return;
+    }
+    for (key, child) in object {
+        prefix.push(key.clone());
+        paths.insert(prefix.clone());
+        collect_paths(child, prefix, paths);
+        prefix.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(path: &[&str], operator: JsonOperator, value: &str) -> JsonFilter {
+        JsonFilter {
+            column: "meta".to_string(),
+            path: path.iter().map(|key| key.to_string()).collect(),
+            operator,
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_to_sql() {
+        assert_eq!(filter(&["plan"], JsonOperator::TextEquals, "pro").to_sql().unwrap(), "\"meta\"->>'plan' = 'pro'");
+        assert_eq!(
+            filter(&["address", "city"], JsonOperator::TextEquals, "O'Hare").to_sql().unwrap(),
+            "\"meta\"->'address'->>'city' = 'O''Hare'"
+        );
+        assert_eq!(filter(&["limits", "seats"], JsonOperator::JsonEquals, "5").to_sql().unwrap(), "\"meta\"->'limits'->'seats' = '5'::jsonb");
+        assert_eq!(filter(&["flags", "beta"], JsonOperator::Contains, "true").to_sql().unwrap(), "\"meta\" @> '{\"flags\":{\"beta\":true}}'");
+        assert_eq!(filter(&["plan"], JsonOperator::Contains, "pro").to_sql().unwrap(), "\"meta\" @> '{\"plan\":\"pro\"}'");
+        assert_eq!(filter(&["deleted_at"], JsonOperator::Exists, "").to_sql().unwrap(), "\"meta\"->'deleted_at' IS NOT NULL");
+        assert!(filter(&[], JsonOperator::Exists, "").to_sql().is_err());
+    }
+
+    #[test]
+    fn test_sample_json_paths() {
+        let values = vec![
+            CellValue::Json(r#"{"plan": "pro", "address": {"city": "Lund", "geo": {"lat": 55.7}}}"#.to_string()),
+            CellValue::Json(r#"{"plan": "free", "tags": ["a", {"nested": 1}]}"#.to_string()),
+            CellValue::Json("[1, 2]".to_string()),
+            CellValue::Null,
+        ];
+        let paths: Vec<String> = sample_json_paths(&values).into_iter().map(|path| path.join(".")).collect();
+        assert_eq!(paths, vec!["address", "address.city", "address.geo", "address.geo.lat", "plan", "tags"]);
+    }
+}
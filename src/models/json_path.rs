@@ -0,0 +1,126 @@
+use anyhow::Result;
+use serde_json::Value;
+use crate::models::CellValue;
+
+/// One step of a JSON path: a key of an object or an index into an array.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonPathStep {
+    Key(String),
+    Index(usize),
+}
+
+/// Parse a path like `$.address.city`, `tags[0]` or `$['odd key'].id`. The leading `$`
+/// is optional, and an empty path is the whole document.
+pub fn parse_json_path(path: &str) -> Result<Vec<JsonPathStep>> {
+    let path = path.trim();
+    let mut rest = path.strip_prefix('$').unwrap_or(path);
+    let mut steps = Vec::new();
+    let mut first = true;
+
+    while !rest.is_empty() {
+        if let Some(bracketed) = rest.strip_prefix('[') {
+            let end = bracketed.find(']').ok_or_else(|| anyhow::anyhow!("Missing ] in {}", path))?;
+            let inner = bracketed[..end].trim();
+            let quoted = inner.strip_prefix('\'').and_then(|key| key.strip_suffix('\''))
+                .or_else(|| inner.strip_prefix('"').and_then(|key| key.strip_suffix('"')));
+            steps.push(match quoted {
+                Some(key) => JsonPathStep::Key(key.to_string()),
+                None => JsonPathStep::Index(inner.parse().map_err(|_| anyhow::anyhow!("{} is not an array index", inner))?),
+            });
+            rest = &bracketed[end + 1..];
+        } else {
+            // The first key may leave out the dot: `address.city`
+            let key_start = match rest.strip_prefix('.') {
+                Some(key) => key,
+                None if first => rest,
+                None => anyhow::bail!("Expected . or [ at {}", rest),
+            };
+            let end = key_start.find(['.', '[']).unwrap_or(key_start.len());
+            if end == 0 {
+                anyhow::bail!("Empty key in {}", path);
+            }
+            steps.push(JsonPathStep::Key(key_start[..end].to_string()));
+            rest = &key_start[end..];
+        }
+        first = false;
+    }
+    Ok(steps)
+}
+
+/// The value at `path` in `value`, None when a step does not exist.
+pub fn extract_json_path<'a>(value: &'a Value, path: &[JsonPathStep]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, step| match step {
+        JsonPathStep::Key(key) => value.get(key),
+        JsonPathStep::Index(index) => value.get(index),
+    })
+}
+
+/// Object keys as a path the parser reads back, e.g. `$.address['zip code']`.
+pub fn json_path_text(keys: &[String]) -> String {
+    keys.iter().fold("$".to_string(), |mut path, key| {
+        if !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            path.push('.');
+            path.push_str(key);
+        } else {
+            path.push_str(&format!("['{}']", key));
+        }
+        path
+    })
+}
+
+/// Text to copy for an extracted value: strings without their quotes, anything else
+/// as compact JSON.
+pub fn json_value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// The document in a JSON cell, or a text cell of a json column (e.g. on MySQL).
+pub fn cell_json(cell: &CellValue, data_type: &str) -> Option<Value> {
+    match cell {
+        CellValue::Json(text) => serde_json::from_str(text).ok(),
+        CellValue::Text(text) if data_type.to_lowercase().starts_with("json") => serde_json::from_str(text).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_path() {
+        let key = |key: &str| JsonPathStep::Key(key.to_string());
+        assert_eq!(parse_json_path("$.address.city").unwrap(), vec![key("address"), key("city")]);
+        assert_eq!(parse_json_path("tags[1]").unwrap(), vec![key("tags"), JsonPathStep::Index(1)]);
+        assert_eq!(parse_json_path("$['zip code'][\"x\"]").unwrap(), vec![key("zip code"), key("x")]);
+        assert_eq!(parse_json_path(" $ ").unwrap(), vec![]);
+        assert!(parse_json_path("$.a..b").is_err());
+        assert!(parse_json_path("$[x]").is_err());
+        assert!(parse_json_path("$.a[0").is_err());
+    }
+
+    #[test]
+    fn test_extract_json_path() {
+        let document = serde_json::json!({"address": {"city": "Oslo", "zip code": "0150"}, "tags": ["a", {"b": 2}]});
+        let extract = |path: &str| extract_json_path(&document, &parse_json_path(path).unwrap()).map(json_value_text);
+        assert_eq!(extract("$.address.city").as_deref(), Some("Oslo"));
+        assert_eq!(extract("$.tags[1]").as_deref(), Some("{\"b\":2}"));
+        assert_eq!(extract("$.tags[1].b").as_deref(), Some("2"));
+        assert_eq!(extract("$.missing"), None);
+
+        let keys = vec!["address".to_string(), "zip code".to_string()];
+        assert_eq!(json_path_text(&keys), "$.address['zip code']");
+        assert_eq!(extract(&json_path_text(&keys)).as_deref(), Some("0150"));
+    }
+
+    #[test]
+    fn test_cell_json() {
+        assert_eq!(cell_json(&CellValue::Json("{\"a\":[1]}".to_string()), "jsonb"), Some(serde_json::json!({"a": [1]})));
+        assert_eq!(cell_json(&CellValue::Json("not json".to_string()), "jsonb"), None);
+        assert_eq!(cell_json(&CellValue::from("[1]"), "JSON"), Some(serde_json::json!([1])));
+        assert_eq!(cell_json(&CellValue::from("[1]"), "text"), None);
+    }
+}
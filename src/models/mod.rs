@@ -1,7 +1,63 @@
 mod tab;
 mod state;
 mod filter;
+mod shared_query;
+mod edit;
+mod selection;
+mod plan;
+mod result_cache;
+mod tail;
+mod bookmark;
+mod view;
+mod schema_snapshot;
+mod query_log;
+mod watch;
+mod stats;
+mod queue;
+mod alter;
+mod fuzzy;
+mod distinct;
+mod chart;
+mod json_filter;
+mod row_diff;
+mod json_path;
+mod binary;
+mod replace;
+mod pasted_table;
+mod distribution;
+mod follow_up;
+mod schema_cache;
+mod computed;
+mod import;
 
-pub use tab::{Tab, TabSource, TableData};
+pub use tab::{clamp_tab_move, moved_index, parse_timestamp, sql_file_title, CellValue, Tab, TabSource, TabView, TableData, WIDE_TABLE_COLUMNS};
 pub use state::AppState;
-pub use filter::{FilterRule, FilterOperator, FilterConjunction};
+pub use filter::{column_filter, filter_row_indices, filters_to_sql, set_column_filter, FilterRule, FilterOperator, FilterConjunction};
+pub use shared_query::{SharedQuery, SharedViewSettings, SHARED_QUERY_EXTENSION};
+pub use edit::PendingEdits;
+pub use selection::{CellSelection, ColumnAggregate, SelectionStats};
+pub use plan::{ExplainPlan, PlanNode};
+pub use result_cache::ResultCache;
+pub use tail::{TailState, TAIL_INTERVALS};
+pub use bookmark::Bookmarks;
+pub use schema_snapshot::{SchemaChange, SchemaChangeKind, SchemaSnapshot};
+pub use query_log::QueryLog;
+pub use watch::{Watch, WATCH_INTERVALS};
+pub use stats::{format_bytes, rows_size, QueryStats};
+pub use queue::{short_label, OperationQueue};
+pub use alter::{AlterPlan, NullabilityChange};
+pub use fuzzy::fuzzy_rank;
+pub use distinct::{distinct_rows, DistinctView};
+pub use chart::{BarSeries, ChartKind, ChartSettings, PlotSeries};
+pub use json_filter::{sample_json_paths, JsonFilter, JsonOperator};
+pub use row_diff::RowComparison;
+pub use replace::ValueReplace;
+pub use pasted_table::PastedTable;
+pub use distribution::{Distribution, ValueDistribution};
+pub use follow_up::{follow_ups, single_source_table, FollowUp};
+pub use schema_cache::{table_sample, CachedSchema, SchemaCache};
+pub use computed::{apply_computed_columns, is_computed, ComputedColumn};
+pub use import::{ImportPlan, ImportProgress, NullHandling, IMPORT_DELIMITERS};
+pub use binary::{hex_dump, to_base64, to_hex};
+pub use json_path::{cell_json, extract_json_path, json_path_text, json_value_text, parse_json_path, JsonPathStep};
+pub use view::{cell_matches, header_label, page_count, page_range, sort_rows, TabViewModel};
@@ -0,0 +1,305 @@
+use anyhow::Result;
+use crate::config::Driver;
+use crate::db::sql::{quote_ident_for, quote_literal};
+use crate::models::parse_timestamp;
+
+// Rows per INSERT statement
+const INSERT_BATCH: usize = 500;
+
+/// Column type inferred from the pasted values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PastedType {
+    Integer,
+    Float,
+    Boolean,
+    Date,
+    Timestamp,
+    Text,
+}
+
+impl PastedType {
+    pub fn sql(&self, driver: Driver) -> &str {
+        match (self, driver) {
+            (PastedType::Integer, _) => "bigint",
+            (PastedType::Float, Driver::Postgres) => "double precision",
+            (PastedType::Float, Driver::MySql) => "double",
+            (PastedType::Boolean, _) => "boolean",
+            (PastedType::Date, _) => "date",
+            (PastedType::Timestamp, Driver::Postgres) => "timestamp",
+            (PastedType::Timestamp, Driver::MySql) => "datetime",
+            (PastedType::Text, _) => "text",
+        }
+    }
+
+    // Narrowest type holding `value`
+    fn of(value: &str) -> PastedType {
+        if value.parse::<i64>().is_ok() {
+            PastedType::Integer
+        } else if value.parse::<f64>().is_ok_and(f64::is_finite) {
+            PastedType::Float
+        } else if matches!(value.to_lowercase().as_str(), "true" | "false") {
+            PastedType::Boolean
+        } else if chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
+            PastedType::Date
+        } else if parse_timestamp(value).is_some() {
+            PastedType::Timestamp
+        } else {
+            PastedType::Text
+        }
+    }
+
+    // Type holding values of both; integers widen to floats and dates to timestamps
+    fn widen(self, other: PastedType) -> PastedType {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (PastedType::Integer, PastedType::Float) | (PastedType::Float, PastedType::Integer) => PastedType::Float,
+            (PastedType::Date, PastedType::Timestamp) | (PastedType::Timestamp, PastedType::Date) => PastedType::Timestamp,
+            _ => PastedType::Text,
+        }
+    }
+}
+
+/// Delimiter of delimited text whose first line is `first_line`: tab, semicolon or
+/// comma, whichever it has most of, and comma when it has none.
+pub fn detect_delimiter(first_line: &str) -> u8 {
+    [b'\t', b';', b',']
+        .into_iter()
+        .max_by_key(|&delimiter| first_line.bytes().filter(|&byte| byte == delimiter).count())
+        .filter(|&delimiter| first_line.as_bytes().contains(&delimiter))
+        .unwrap_or(b',')
+}
+
+/// Rows pasted as CSV, TSV or JSON, to be created as a temporary table on the
+/// current connection. Empty values and JSON nulls are NULL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PastedTable {
+    pub columns: Vec<(String, PastedType)>,
+    pub rows: Vec<Vec<Option<String>>>,
+    // Whether the first delimited line was taken as the header; None for JSON
+    pub header: Option<bool>,
+}
+
+impl PastedTable {
+    /// Parse `text`: a JSON array of objects or of plain values, or delimited lines
+    /// (tab, semicolon or comma, whichever the first line has most of). Unless `header`
+    /// says otherwise, a first line that does not fit the types of the lines below is
+    /// the header, and with only text columns there is one when there are several.
+    pub fn parse(text: &str, header: Option<bool>) -> Result<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            anyhow::bail!("Paste CSV, TSV or JSON data");
+        }
+        let table = if text.starts_with('[') || text.starts_with('{') {
+            Self::from_json(text)?
+        } else {
+            Self::from_delimited(text, header)?
+        };
+        if table.rows.is_empty() {
+            anyhow::bail!("No rows found");
+        }
+        Ok(table)
+    }
+
+    fn from_json(text: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(text).map_err(|e| anyhow::anyhow!("Invalid JSON: {}", e))?;
+        let items = match value {
+            serde_json::Value::Array(items) => items,
+            object => vec![object],
+        };
+        let text = |value: &serde_json::Value| match value {
+            serde_json::Value::Null => None,
+            serde_json::Value::String(text) => Some(text.clone()),
+            value => Some(value.to_string()),
+        };
+
+        // Columns in the order their keys first appear
+        let mut names: Vec<String> = Vec::new();
+        for item in &items {
+            if let serde_json::Value::Object(object) = item {
+                for key in object.keys() {
+                    if !names.contains(key) {
+                        names.push(key.clone());
+                    }
+                }
+            }
+        }
+        if names.is_empty() {
+            let rows = items.iter().map(|item| vec![text(item)]).collect();
+            return Ok(Self::typed(vec!["value".to_string()], rows));
+        }
+
+        let rows = items.iter()
+            .map(|item| names.iter().map(|name| item.get(name).and_then(text)).collect())
+            .collect();
+        Ok(Self::typed(names, rows))
+    }
+
+    fn from_delimited(text: &str, header: Option<bool>) -> Result<Self> {
+        let delimiter = detect_delimiter(text.lines().next().unwrap_or_default());
+
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(text.as_bytes());
+        let mut rows: Vec<Vec<Option<String>>> = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(|e| anyhow::anyhow!("Invalid CSV: {}", e))?;
+            rows.push(record.iter().map(|value| Some(value.trim().to_string()).filter(|value| !value.is_empty())).collect());
+        }
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        for row in &mut rows {
+            row.resize(width, None);
+        }
+
+        let has_header = header.unwrap_or_else(|| {
+            let body_types = Self::infer(&rows[1.min(rows.len())..], width);
+            if body_types.iter().all(|&column_type| column_type == PastedType::Text) {
+                return rows.len() > 1 && width > 1;
+            }
+            rows[0].iter().zip(&body_types).any(|(value, &column_type)| {
+                value.as_deref().is_some_and(|value| column_type != PastedType::Text && PastedType::of(value).widen(column_type) != column_type)
+            })
+        });
+        let names = if has_header {
+            rows.remove(0).into_iter()
+                .enumerate()
+                .map(|(index, name)| name.unwrap_or_else(|| format!("column{}", index + 1)))
+                .collect()
+        } else {
+            (1..=width).map(|index| format!("column{}", index)).collect()
+        };
+        Ok(Self { header: Some(has_header), ..Self::typed(names, rows) })
+    }
+
+    // Column types of `rows`; columns with only NULLs are text
+    fn infer(rows: &[Vec<Option<String>>], width: usize) -> Vec<PastedType> {
+        (0..width)
+            .map(|column| {
+                rows.iter()
+                    .filter_map(|row| row.get(column)?.as_deref())
+                    .map(PastedType::of)
+                    .reduce(PastedType::widen)
+                    .unwrap_or(PastedType::Text)
+            })
+            .collect()
+    }
+
+    fn typed(names: Vec<String>, rows: Vec<Vec<Option<String>>>) -> Self {
+        let types = Self::infer(&rows, names.len());
+        Self { columns: names.into_iter().zip(types).collect(), rows, header: None }
+    }
+
+    /// CREATE TEMPORARY TABLE and the INSERTs filling it, in batches.
+    pub fn create_statements(&self, driver: Driver, name: &str) -> Vec<String> {
+        let table = quote_ident_for(driver, name);
+        let columns: Vec<String> = self.columns.iter()
+            .map(|(column, column_type)| format!("{} {}", quote_ident_for(driver, column), column_type.sql(driver)))
+            .collect();
+        let column_names: Vec<String> = self.columns.iter().map(|(column, _)| quote_ident_for(driver, column)).collect();
+
+        let mut statements = vec![format!("CREATE TEMPORARY TABLE {} ({})", table, columns.join(", "))];
+        for batch in self.rows.chunks(INSERT_BATCH) {
+            let values: Vec<String> = batch.iter()
+                .map(|row| {
+                    let values: Vec<String> = row.iter()
+                        .zip(&self.columns)
+                        .map(|(value, (_, column_type))| match (value, column_type) {
+                            (None, _) => "NULL".to_string(),
+                            (Some(value), PastedType::Integer | PastedType::Float) => value.clone(),
+                            (Some(value), PastedType::Boolean) => value.to_uppercase(),
+                            // Offsets are applied, since MySQL reads none of them
+                            (Some(value), PastedType::Timestamp) => match parse_timestamp(value) {
+                                Some(timestamp) => quote_literal(&timestamp.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+                                None => quote_literal(value),
+                            },
+                            (Some(value), _) if driver == Driver::MySql => quote_literal(&value.replace('\\', "\\\\")),
+                            (Some(value), _) => quote_literal(value),
+                        })
+                        .collect();
+                    format!("({})", values.join(", "))
+                })
+                .collect();
+            statements.push(format!("INSERT INTO {} ({}) VALUES {}", table, column_names.join(", "), values.join(", ")));
+        }
+        statements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(text: &str) -> Option<String> {
+        Some(text.to_string())
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let table = PastedTable::parse("id,name,joined\n1,Ann,2024-01-02\n2,,2024-01-03 10:00\n", None).unwrap();
+        assert_eq!(table.columns, vec![
+            ("id".to_string(), PastedType::Integer),
+            ("name".to_string(), PastedType::Text),
+            ("joined".to_string(), PastedType::Timestamp),
+        ]);
+        assert_eq!(table.rows[1], vec![value("2"), None, value("2024-01-03 10:00")]);
+
+        // Tab separated, with a quoted value holding the other delimiters
+        let table = PastedTable::parse("code\tprice\n\"a,b;c\"\t1.5\nx\t2", None).unwrap();
+        assert_eq!(table.columns[1], ("price".to_string(), PastedType::Float));
+        assert_eq!(table.rows[0][0], value("a,b;c"));
+    }
+
+    #[test]
+    fn test_parse_without_header() {
+        // A plain list of ids has no header
+        let table = PastedTable::parse("17\n42\n99", None).unwrap();
+        assert_eq!(table.columns, vec![("column1".to_string(), PastedType::Integer)]);
+        assert_eq!(table.rows.len(), 3);
+        assert_eq!(table.header, Some(false));
+
+        let table = PastedTable::parse("ids\n17\n42", None).unwrap();
+        assert_eq!(table.columns, vec![("ids".to_string(), PastedType::Integer)]);
+        assert_eq!(table.rows.len(), 2);
+
+        // Only text: several columns have a header, a single one is a list of values
+        let table = PastedTable::parse("name;city\nAnn;Oslo", None).unwrap();
+        assert_eq!(table.columns[1].0, "city");
+        assert_eq!(PastedTable::parse("abc\ndef", None).unwrap().rows.len(), 2);
+        assert_eq!(PastedTable::parse("abc\ndef", Some(true)).unwrap().columns[0].0, "abc");
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let table = PastedTable::parse(r#"[{"id": 1, "tags": ["a"]}, {"id": 2.5, "active": true}, {"active": null}]"#, None).unwrap();
+        assert_eq!(table.columns, vec![
+            ("id".to_string(), PastedType::Float),
+            ("tags".to_string(), PastedType::Text),
+            ("active".to_string(), PastedType::Boolean),
+        ]);
+        assert_eq!(table.rows[0], vec![value("1"), value("[\"a\"]"), None]);
+
+        let table = PastedTable::parse(r#"["a0eebc99", "b1"]"#, None).unwrap();
+        assert_eq!(table.columns, vec![("value".to_string(), PastedType::Text)]);
+
+        assert!(PastedTable::parse("[]", None).is_err());
+        assert!(PastedTable::parse("[1,", None).is_err());
+        assert!(PastedTable::parse("  ", None).is_err());
+    }
+
+    #[test]
+    fn test_create_statements() {
+        let table = PastedTable::parse("id,note,ok,at\n1,it's,true,2024-01-02T10:00:00+02:00\n2,\\n,False,", None).unwrap();
+        assert_eq!(table.create_statements(Driver::Postgres, "ids"), vec![
+            "CREATE TEMPORARY TABLE \"ids\" (\"id\" bigint, \"note\" text, \"ok\" boolean, \"at\" timestamp)".to_string(),
+            "INSERT INTO \"ids\" (\"id\", \"note\", \"ok\", \"at\") VALUES (1, 'it''s', TRUE, '2024-01-02 08:00:00'), (2, '\\n', FALSE, NULL)".to_string(),
+        ]);
+        assert_eq!(
+            table.create_statements(Driver::MySql, "ids")[1],
+            "INSERT INTO `ids` (`id`, `note`, `ok`, `at`) VALUES (1, 'it''s', TRUE, '2024-01-02 08:00:00'), (2, '\\\\n', FALSE, NULL)"
+        );
+
+        let rows = (0..1200).map(|n| n.to_string()).collect::<Vec<_>>().join("\n");
+        assert_eq!(PastedTable::parse(&rows, None).unwrap().create_statements(Driver::Postgres, "ids").len(), 4);
+    }
+}
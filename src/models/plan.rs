@@ -0,0 +1,197 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Node properties shown as extra detail lines under a node
+const CONDITION_KEYS: &[&str] = &[
+    "Index Cond",
+    "Recheck Cond",
+    "Hash Cond",
+    "Merge Cond",
+    "Join Filter",
+    "Filter",
+    "Sort Key",
+    "Group Key",
+];
+
+/// One node of an `EXPLAIN (FORMAT JSON)` plan.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlanNode {
+    pub node_type: String,
+    pub relation: Option<String>,
+    pub alias: Option<String>,
+    pub index: Option<String>,
+    pub join_type: Option<String>,
+    pub startup_cost: f64,
+    pub total_cost: f64,
+    pub plan_rows: f64,
+    pub plan_width: u64,
+    // Only present with ANALYZE
+    pub actual_startup_ms: Option<f64>,
+    pub actual_total_ms: Option<f64>,
+    pub actual_rows: Option<f64>,
+    pub actual_loops: Option<f64>,
+    pub conditions: Vec<(String, String)>,
+    pub children: Vec<PlanNode>,
+}
+
+impl PlanNode {
+    fn from_json(node: &Value) -> Self {
+        let text = |key: &str| node.get(key).and_then(Value::as_str).map(str::to_string);
+        let number = |key: &str| node.get(key).and_then(Value::as_f64);
+
+        let conditions = CONDITION_KEYS.iter()
+            .filter_map(|key| {
+                let value = match node.get(*key)? {
+                    Value::String(s) => s.clone(),
+                    Value::Array(items) => items.iter()
+                        .map(|item| item.as_str().map(str::to_string).unwrap_or_else(|| item.to_string()))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    other => other.to_string(),
+                };
+                Some((key.to_string(), value))
+            })
+            .collect();
+
+        let children = node.get("Plans")
+            .and_then(Value::as_array)
+            .map(|plans| plans.iter().map(PlanNode::from_json).collect())
+            .unwrap_or_default();
+
+        Self {
+            node_type: text("Node Type").unwrap_or_else(|| "?".to_string()),
+            relation: text("Relation Name"),
+            alias: text("Alias"),
+            index: text("Index Name"),
+            join_type: text("Join Type"),
+            startup_cost: number("Startup Cost").unwrap_or(0.0),
+            total_cost: number("Total Cost").unwrap_or(0.0),
+            plan_rows: number("Plan Rows").unwrap_or(0.0),
+            plan_width: node.get("Plan Width").and_then(Value::as_u64).unwrap_or(0),
+            actual_startup_ms: number("Actual Startup Time"),
+            actual_total_ms: number("Actual Total Time"),
+            actual_rows: number("Actual Rows"),
+            actual_loops: number("Actual Loops"),
+            conditions,
+            children,
+        }
+    }
+
+    /// Node heading in the style of the text EXPLAIN output, e.g. `Index Scan using users_pkey on users u`.
+    pub fn title(&self) -> String {
+        let mut title = self.node_type.clone();
+        if let Some(join_type) = self.join_type.as_ref().filter(|j| j.as_str() != "Inner") {
+            title = format!("{} ({})", title, join_type);
+        }
+        if let Some(index) = &self.index {
+            title.push_str(&format!(" using {}", index));
+        }
+        if let Some(relation) = &self.relation {
+            title.push_str(&format!(" on {}", relation));
+            if let Some(alias) = self.alias.as_ref().filter(|a| *a != relation) {
+                title.push_str(&format!(" {}", alias));
+            }
+        }
+        title
+    }
+
+    /// Time spent in this node alone: its total time over all loops minus its children's.
+    pub fn exclusive_ms(&self) -> Option<f64> {
+        let own = self.actual_total_ms? * self.actual_loops.unwrap_or(1.0);
+        let children: f64 = self.children.iter()
+            .filter_map(|child| Some(child.actual_total_ms? * child.actual_loops.unwrap_or(1.0)))
+            .sum();
+        Some((own - children).max(0.0))
+    }
+}
+
+/// A parsed `EXPLAIN` plan, shown as a tree in an explain tab.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExplainPlan {
+    pub root: PlanNode,
+    pub planning_ms: Option<f64>,
+    pub execution_ms: Option<f64>,
+}
+
+impl ExplainPlan {
+    /// Parse the document returned by `EXPLAIN (FORMAT JSON)`, with or without ANALYZE.
+    pub fn from_json(plan: &Value) -> Result<Self> {
+        let plan = match plan {
+            Value::String(text) => serde_json::from_str(text)?,
+            other => other.clone(),
+        };
+        let root = plan.get(0).ok_or_else(|| anyhow::anyhow!("EXPLAIN output is not a JSON plan"))?;
+        let node = root.get("Plan").ok_or_else(|| anyhow::anyhow!("EXPLAIN output has no plan node"))?;
+
+        Ok(Self {
+            root: PlanNode::from_json(node),
+            planning_ms: root.get("Planning Time").and_then(Value::as_f64),
+            execution_ms: root.get("Execution Time").and_then(Value::as_f64),
+        })
+    }
+
+    pub fn is_analyzed(&self) -> bool {
+        self.root.actual_total_ms.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyzed_plan() -> Value {
+        serde_json::json!([{
+            "Plan": {
+                "Node Type": "Hash Join",
+                "Join Type": "Left",
+                "Startup Cost": 1.5,
+                "Total Cost": 40.0,
+                "Plan Rows": 100,
+                "Plan Width": 16,
+                "Actual Total Time": 5.0,
+                "Actual Rows": 90,
+                "Actual Loops": 1,
+                "Hash Cond": "(o.user_id = u.id)",
+                "Plans": [
+                    {"Node Type": "Seq Scan", "Relation Name": "orders", "Alias": "o",
+                     "Total Cost": 20.0, "Actual Total Time": 1.0, "Actual Loops": 1,
+                     "Filter": "(total > 10)"},
+                    {"Node Type": "Index Scan", "Relation Name": "users", "Alias": "users",
+                     "Index Name": "users_pkey", "Actual Total Time": 0.5, "Actual Loops": 4}
+                ]
+            },
+            "Planning Time": 0.2,
+            "Execution Time": 5.3
+        }])
+    }
+
+    #[test]
+    fn test_parse_tree() {
+        let plan = ExplainPlan::from_json(&analyzed_plan()).unwrap();
+        assert!(plan.is_analyzed());
+        assert_eq!(plan.execution_ms, Some(5.3));
+        assert_eq!(plan.root.title(), "Hash Join (Left)");
+        assert_eq!(plan.root.conditions, vec![("Hash Cond".to_string(), "(o.user_id = u.id)".to_string())]);
+        assert_eq!(plan.root.children.len(), 2);
+        assert_eq!(plan.root.children[0].title(), "Seq Scan on orders o");
+        assert_eq!(plan.root.children[1].title(), "Index Scan using users_pkey on users");
+    }
+
+    #[test]
+    fn test_exclusive_time_accounts_for_loops() {
+        let plan = ExplainPlan::from_json(&analyzed_plan()).unwrap();
+        // 5.0 - (1.0 + 0.5 * 4)
+        assert_eq!(plan.root.exclusive_ms(), Some(2.0));
+    }
+
+    #[test]
+    fn test_plan_without_analyze() {
+        let plan = ExplainPlan::from_json(&Value::String(
+            r#"[{"Plan": {"Node Type": "Result", "Total Cost": 0.01, "Plan Rows": 1}}]"#.to_string(),
+        )).unwrap();
+        assert!(!plan.is_analyzed());
+        assert_eq!(plan.root.exclusive_ms(), None);
+        assert!(ExplainPlan::from_json(&serde_json::json!({"a": 1})).is_err());
+    }
+}
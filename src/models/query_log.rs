@@ -0,0 +1,62 @@
+// Oldest entries are dropped beyond this many
+const MAX_LOG_ENTRIES: usize = 500;
+
+/// Outcome of one executed statement, as shown in the query log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryLogEntry {
+    pub logged_at: String,
+    pub sql: String,
+    pub message: String, // e.g. "UPDATE 42" or "120 rows"
+    pub duration_ms: u64,
+    pub is_error: bool,
+}
+
+/// Statements executed from the query panel during this session, oldest first.
+/// Unlike the query history it is not saved and also lists row counts of writes.
+#[derive(Debug, Default)]
+pub struct QueryLog {
+    entries: Vec<QueryLogEntry>,
+}
+
+impl QueryLog {
+    pub fn push(&mut self, sql: &str, message: String, duration_ms: u64, is_error: bool) {
+        self.entries.push(QueryLogEntry {
+            logged_at: chrono::Local::now().format("%H:%M:%S").to_string(),
+            sql: sql.trim().to_string(),
+            message,
+            duration_ms,
+            is_error,
+        });
+
+        if self.entries.len() > MAX_LOG_ENTRIES {
+            let excess = self.entries.len() - MAX_LOG_ENTRIES;
+            self.entries.drain(..excess);
+        }
+    }
+
+    pub fn entries(&self) -> &[QueryLogEntry] {
+        &self.entries
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oldest_entries_are_dropped() {
+        let mut log = QueryLog::default();
+        for i in 0..MAX_LOG_ENTRIES + 2 {
+            log.push(&format!(" DELETE FROM t WHERE id = {}\n", i), "DELETE 1".to_string(), 3, false);
+        }
+
+        assert_eq!(log.entries().len(), MAX_LOG_ENTRIES);
+        assert_eq!(log.entries()[0].sql, "DELETE FROM t WHERE id = 2");
+        log.clear();
+        assert!(log.entries().is_empty());
+    }
+}
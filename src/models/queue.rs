@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+
+// Longer labels are cut off with an ellipsis
+const MAX_LABEL_CHARS: usize = 60;
+
+/// Operation waiting for the connection, with the id the queue panel cancels it by.
+#[derive(Debug)]
+pub struct QueuedItem<T> {
+    pub id: u64,
+    pub label: String,
+    pub operation: T,
+}
+
+/// Work started while the connection was busy, run one at a time in the order it was
+/// requested once the running operation finishes.
+#[derive(Debug)]
+pub struct OperationQueue<T> {
+    items: VecDeque<QueuedItem<T>>,
+    next_id: u64,
+}
+
+impl<T> Default for OperationQueue<T> {
+    fn default() -> Self {
+        Self { items: VecDeque::new(), next_id: 0 }
+    }
+}
+
+impl<T> OperationQueue<T> {
+    /// Queue `operation` last and return its position, 1 being next to run.
+    pub fn push(&mut self, label: String, operation: T) -> usize {
+        self.items.push_back(QueuedItem { id: self.next_id, label, operation });
+        self.next_id += 1;
+        self.items.len()
+    }
+
+    /// Next operation to start, if any.
+    pub fn pop(&mut self) -> Option<T> {
+        self.items.pop_front().map(|item| item.operation)
+    }
+
+    /// Take `id` out of the queue before it starts.
+    pub fn remove(&mut self, id: u64) -> Option<T> {
+        let index = self.items.iter().position(|item| item.id == id)?;
+        self.items.remove(index).map(|item| item.operation)
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &QueuedItem<T>> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+/// One-line description of `sql` for the queue panel: whitespace collapsed and
+/// shortened to fit.
+pub fn short_label(sql: &str) -> String {
+    let collapsed = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= MAX_LABEL_CHARS {
+        return collapsed;
+    }
+    let mut label: String = collapsed.chars().take(MAX_LABEL_CHARS - 1).collect();
+    label.push('…');
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_order_and_removal() {
+        let mut queue = OperationQueue::default();
+        assert_eq!(queue.push("first".to_string(), 1), 1);
+        assert_eq!(queue.push("second".to_string(), 2), 2);
+        assert_eq!(queue.push("third".to_string(), 3), 3);
+
+        // Ids stay valid while positions shift
+        let second = queue.items().nth(1).unwrap().id;
+        assert_eq!(queue.remove(second), Some(2));
+        assert_eq!(queue.remove(second), None);
+        assert_eq!(queue.items().map(|item| item.label.as_str()).collect::<Vec<_>>(), vec!["first", "third"]);
+
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), Some(3));
+        assert!(queue.pop().is_none());
+        assert_eq!(queue.push("fourth".to_string(), 4), 1);
+    }
+
+    #[test]
+    fn test_short_label() {
+        assert_eq!(short_label("SELECT *\n  FROM users\n"), "SELECT * FROM users");
+        let long = format!("SELECT {} FROM t", "column_name, ".repeat(10));
+        let label = short_label(&long);
+        assert_eq!(label.chars().count(), MAX_LABEL_CHARS);
+        assert!(label.ends_with('…'));
+    }
+}
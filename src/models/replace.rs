@@ -0,0 +1,178 @@
+use anyhow::Result;
+use regex::{NoExpand, Regex, RegexBuilder};
+use crate::models::{PendingEdits, TableData};
+
+/// A find-and-replace over one column of a table tab, built in the Replace Values
+/// dialog and staged as cell edits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueReplace {
+    pub column: usize,
+    pub find: String,
+    pub replace: String,
+    // `find` is a regular expression and `replace` may use $1 or ${name}
+    pub regex: bool,
+    pub match_case: bool,
+}
+
+/// One cell the replace changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replacement {
+    pub row: usize, // original row index
+    pub key: Vec<String>,
+    pub original: String,
+    // Value before the replace: the staged edit if the cell has one
+    pub current: String,
+    pub value: String,
+}
+
+impl ValueReplace {
+    pub fn new(column: usize) -> Self {
+        Self { column, find: String::new(), replace: String::new(), regex: false, match_case: true }
+    }
+
+    fn pattern(&self) -> Result<Regex> {
+        if self.find.is_empty() {
+            anyhow::bail!("Enter the text to find");
+        }
+        let pattern = if self.regex { self.find.clone() } else { regex::escape(&self.find) };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.match_case)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Invalid pattern: {}", e))
+    }
+
+    /// `text` with every match replaced, None when nothing matches.
+    fn apply(&self, pattern: &Regex, text: &str) -> Option<String> {
+        if !pattern.is_match(text) {
+            return None;
+        }
+        Some(if self.regex {
+            pattern.replace_all(text, self.replace.as_str()).into_owned()
+        } else {
+            pattern.replace_all(text, NoExpand(&self.replace)).into_owned()
+        })
+    }
+
+    /// The cells of `rows` whose value changes, starting from staged edits so replaces
+    /// can be chained. NULLs are left alone.
+    pub fn preview(&self, data: &TableData, rows: &[usize], pending: &PendingEdits) -> Result<Vec<Replacement>> {
+        let key_columns = PendingEdits::key_columns(&data.columns)
+            .ok_or_else(|| anyhow::anyhow!("The table has no primary key"))?;
+        if key_columns.contains(&self.column) {
+            anyhow::bail!("Primary key values can't be replaced");
+        }
+        let pattern = self.pattern()?;
+
+        Ok(rows.iter()
+            .filter_map(|&row| {
+                let values = data.rows.get(row)?;
+                let cell = values.get(self.column).filter(|cell| !cell.is_null())?;
+                let key = PendingEdits::row_key(values, &key_columns);
+                let original = cell.to_string();
+                let current = pending.get(&key, self.column).map_or_else(|| original.clone(), |edit| edit.value.clone());
+                let value = self.apply(&pattern, &current).filter(|value| *value != current)?;
+                Some(Replacement { row, key, original, current, value })
+            })
+            .collect())
+    }
+}
+
+impl PendingEdits {
+    /// Stage every replacement, returning how many cells were staged.
+    pub fn stage_replacements(&mut self, column: usize, replacements: Vec<Replacement>) -> usize {
+        let count = replacements.len();
+        for replacement in replacements {
+            self.stage(replacement.key, column, replacement.original, replacement.value);
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ColumnInfo;
+    use crate::models::CellValue;
+
+    fn data() -> TableData {
+        let column = |name: &str, is_primary_key: bool| ColumnInfo {
+            name: name.to_string(),
+            data_type: "text".to_string(),
+            is_primary_key,
+            is_foreign_key: false,
+        };
+        TableData {
+            name: "users".to_string(),
+            columns: vec![column("id", true), column("email", false)],
+            rows: vec![
+                vec![CellValue::Int(1), CellValue::from("ann@OLD.example")],
+                vec![CellValue::Int(2), CellValue::from("bob@new.example")],
+                vec![CellValue::Int(3), CellValue::Null],
+                vec![CellValue::Int(4), CellValue::from("cy@old.example")],
+            ],
+            lineage: Default::default(),
+            generation: 0,
+        }
+    }
+
+    #[test]
+    fn test_plain_replace() {
+        let data = data();
+        let mut replace = ValueReplace::new(1);
+        replace.find = "old.example".to_string();
+        replace.replace = "$new.example".to_string();
+
+        let preview = replace.preview(&data, &[0, 1, 2, 3], &PendingEdits::default()).unwrap();
+        assert_eq!(preview.len(), 1);
+        assert_eq!(preview[0].row, 3);
+        assert_eq!(preview[0].value, "cy@$new.example");
+
+        replace.match_case = false;
+        let preview = replace.preview(&data, &[0, 1, 2, 3], &PendingEdits::default()).unwrap();
+        assert_eq!(preview.iter().map(|r| r.row).collect::<Vec<_>>(), vec![0, 3]);
+
+        // Only the given rows, e.g. the ones passing the grid filters
+        assert_eq!(replace.preview(&data, &[1, 3], &PendingEdits::default()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_regex_replace() {
+        let data = data();
+        let mut replace = ValueReplace::new(1);
+        replace.regex = true;
+        replace.find = r"^(\w+)@.*$".to_string();
+        replace.replace = "$1@example.com".to_string();
+
+        let preview = replace.preview(&data, &[0, 1, 2, 3], &PendingEdits::default()).unwrap();
+        let values: Vec<&str> = preview.iter().map(|r| r.value.as_str()).collect();
+        assert_eq!(values, vec!["ann@example.com", "bob@example.com", "cy@example.com"]);
+
+        replace.find = "(".to_string();
+        assert!(replace.preview(&data, &[0], &PendingEdits::default()).is_err());
+        replace.find.clear();
+        assert!(replace.preview(&data, &[0], &PendingEdits::default()).is_err());
+        assert!(ValueReplace { find: "1".to_string(), ..ValueReplace::new(0) }.preview(&data, &[0], &PendingEdits::default()).is_err());
+    }
+
+    #[test]
+    fn test_stage_replacements() {
+        let data = data();
+        let mut pending = PendingEdits::default();
+        pending.stage(vec!["2".to_string()], 1, "bob@new.example".to_string(), "bob@old.example".to_string());
+
+        let mut replace = ValueReplace::new(1);
+        replace.find = "old".to_string();
+        replace.replace = "legacy".to_string();
+        let preview = replace.preview(&data, &[0, 1, 2, 3], &pending).unwrap();
+        assert_eq!(preview.len(), 2);
+        assert_eq!(preview[0].current, "bob@old.example");
+
+        assert_eq!(pending.stage_replacements(1, preview), 2);
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending.get(&["2".to_string()], 1).unwrap().value, "bob@legacy.example");
+        assert_eq!(pending.get(&["2".to_string()], 1).unwrap().original, "bob@new.example");
+
+        let statements = pending.to_update_statements("public", "users", &data.columns).unwrap();
+        assert_eq!(statements[1], "UPDATE \"public\".\"users\" SET \"email\" = 'cy@legacy.example' WHERE \"id\" = '4'");
+    }
+}
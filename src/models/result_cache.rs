@@ -0,0 +1,194 @@
+use anyhow::Result;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use crate::models::TableData;
+
+// Larger results are not cached; the tab simply comes back empty after a restart
+const MAX_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+// Least recently written entries are evicted beyond this
+const MAX_TOTAL_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Tab results kept on disk between sessions, one bincode file per tab id, so
+/// state.json only has to hold each tab's source and view settings.
+#[derive(Clone)]
+pub struct ResultCache {
+    dir: PathBuf,
+    max_entry_bytes: u64,
+    max_total_bytes: u64,
+}
+
+impl ResultCache {
+    pub fn new() -> Result<Self> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
+        Ok(Self::with_dir(home.join(".config").join("db-client").join("results")))
+    }
+
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir, max_entry_bytes: MAX_ENTRY_BYTES, max_total_bytes: MAX_TOTAL_BYTES }
+    }
+
+    fn path(&self, tab_id: usize) -> PathBuf {
+        self.dir.join(format!("tab-{}.bin", tab_id))
+    }
+
+    /// Write `data` for `tab_id`, replacing any earlier entry, then evict old entries
+    /// until the cache fits its size limit again.
+    pub fn store(&self, tab_id: usize, data: &TableData) -> Result<()> {
+        let bytes = bincode::serde::encode_to_vec(data, bincode::config::standard())?;
+        if bytes.len() as u64 > self.max_entry_bytes {
+            self.remove(tab_id);
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(tab_id), bytes)?;
+        self.evict(tab_id)
+    }
+
+    /// Cached result for `tab_id`, if there is a readable one.
+    pub fn load(&self, tab_id: usize) -> Option<TableData> {
+        let bytes = fs::read(self.path(tab_id)).ok()?;
+        bincode::serde::decode_from_slice(&bytes, bincode::config::standard())
+            .ok()
+            .map(|(data, _)| data)
+    }
+
+    pub fn remove(&self, tab_id: usize) {
+        let _ = fs::remove_file(self.path(tab_id));
+    }
+
+    /// Delete entries for tabs that no longer exist.
+    pub fn retain(&self, tab_ids: &[usize]) {
+        for (path, tab_id, _, _) in self.entries() {
+            if !tab_ids.contains(&tab_id) {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+
+    // Drop the oldest entries, never `keep`, while the cache is over its limit
+    fn evict(&self, keep: usize) -> Result<()> {
+        let mut entries = self.entries();
+        let mut total: u64 = entries.iter().map(|(_, _, size, _)| size).sum();
+        entries.sort_by_key(|(_, _, _, modified)| *modified);
+
+        for (path, tab_id, size, _) in entries {
+            if total <= self.max_total_bytes {
+                break;
+            }
+            if tab_id != keep {
+                fs::remove_file(path)?;
+                total -= size;
+            }
+        }
+        Ok(())
+    }
+
+    // (path, tab id, size, last written) of every entry
+    fn entries(&self) -> Vec<(PathBuf, usize, u64, SystemTime)> {
+        let Ok(read_dir) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        read_dir
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let tab_id = path.file_name()?
+                    .to_str()?
+                    .strip_prefix("tab-")?
+                    .strip_suffix(".bin")?
+                    .parse()
+                    .ok()?;
+                let metadata = entry.metadata().ok()?;
+                Some((path, tab_id, metadata.len(), metadata.modified().ok()?))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{ColumnInfo, ColumnLineage};
+    use crate::models::CellValue;
+
+    fn cache(name: &str) -> ResultCache {
+        let dir = std::env::temp_dir().join(format!("result-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ResultCache::with_dir(dir)
+    }
+
+    fn data(rows: usize) -> TableData {
+        TableData {
+            name: "public.users".to_string(),
+            columns: vec![ColumnInfo { name: "id".to_string(), data_type: "int4".to_string(), is_primary_key: true, is_foreign_key: false }],
+            rows: (0..rows).map(|i| vec![CellValue::Int(i as i64)]).collect(),
+            lineage: ColumnLineage::new(),
+            generation: 0,
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_and_retain() {
+        let cache = cache("roundtrip");
+        cache.store(1, &data(3)).unwrap();
+        cache.store(2, &data(1)).unwrap();
+
+        let loaded = cache.load(1).unwrap();
+        assert_eq!(loaded.name, "public.users");
+        assert_eq!(loaded.rows, vec![vec![CellValue::Int(0)], vec![CellValue::Int(1)], vec![CellValue::Int(2)]]);
+
+        cache.retain(&[2]);
+        assert!(cache.load(1).is_none());
+        assert!(cache.load(2).is_some());
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn test_typed_cells_roundtrip() {
+        let cache = cache("typed");
+        let timestamp = chrono::NaiveDate::from_ymd_opt(2024, 3, 5).unwrap().and_hms_micro_opt(14, 30, 0, 250).unwrap();
+        let mut typed = data(0);
+        typed.rows.push(vec![
+            CellValue::Null,
+            CellValue::Bool(true),
+            CellValue::Float(1.5),
+            CellValue::from("text"),
+            CellValue::Date(timestamp.date()),
+            CellValue::Timestamp(timestamp),
+            CellValue::TimestampTz(timestamp.and_utc()),
+            CellValue::Json(r#"{"a":[1,2]}"#.to_string()),
+            CellValue::Bytes(vec![0, 255]),
+            CellValue::Uuid(uuid::Uuid::nil()),
+            CellValue::Array(vec![CellValue::from("a"), CellValue::Null]),
+        ]);
+        cache.store(1, &typed).unwrap();
+        assert_eq!(cache.load(1).unwrap().rows, typed.rows);
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let mut cache = cache("limits");
+        let entry_size = bincode::serde::encode_to_vec(data(100), bincode::config::standard()).unwrap().len() as u64;
+        cache.max_entry_bytes = entry_size;
+        cache.max_total_bytes = entry_size * 2;
+
+        // Too big for a single entry
+        cache.store(1, &data(200)).unwrap();
+        assert!(cache.load(1).is_none());
+
+        // The third entry pushes out the oldest one
+        cache.store(1, &data(100)).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        cache.store(2, &data(100)).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        cache.store(3, &data(100)).unwrap();
+        assert!(cache.load(1).is_none());
+        assert!(cache.load(2).is_some());
+        assert!(cache.load(3).is_some());
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::models::{CellValue, TableData};
+
+/// Two rows of a result compared column by column, to see why two records behave
+/// differently. Rows are original indices into the result.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RowComparison {
+    pub left: usize,
+    pub right: usize,
+}
+
+impl RowComparison {
+    pub fn new(left: usize, right: usize) -> Self {
+        Self { left, right }
+    }
+
+    /// False once the result no longer has both rows, e.g. after a reload.
+    pub fn is_valid(&self, data: &TableData) -> bool {
+        self.left < data.rows.len() && self.right < data.rows.len()
+    }
+
+    /// Columns whose values differ between the two rows, in column order.
+    pub fn differing_columns(&self, data: &TableData) -> Vec<usize> {
+        let (Some(left), Some(right)) = (data.rows.get(self.left), data.rows.get(self.right)) else {
+            return Vec::new();
+        };
+        (0..data.columns.len())
+            .filter(|&column| left.get(column) != right.get(column))
+            .collect()
+    }
+
+    /// Tab-separated column name and both values, one line per differing column.
+    /// `text` renders a value of a column, e.g. anonymized for sharing.
+    pub fn differences_text(&self, data: &TableData, text: impl Fn(usize, &CellValue) -> String) -> String {
+        let value = |row: usize, column: usize| data.rows[row].get(column).map(|value| text(column, value)).unwrap_or_default();
+        self.differing_columns(data)
+            .into_iter()
+            .map(|column| format!("{}\t{}\t{}", data.columns[column].name, value(self.left, column), value(self.right, column)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{ColumnInfo, ColumnLineage};
+
+    fn data() -> TableData {
+        let column = |name: &str| ColumnInfo { name: name.to_string(), data_type: "text".to_string(), is_primary_key: false, is_foreign_key: false };
+        TableData {
+            name: "users".to_string(),
+            columns: vec![column("id"), column("plan"), column("region"), column("deleted_at")],
+            rows: vec![
+                vec![CellValue::Int(1), CellValue::from("pro"), CellValue::from("eu"), CellValue::Null],
+                vec![CellValue::Int(2), CellValue::from("pro"), CellValue::from("us"), CellValue::from("2024-01-01")],
+            ],
+            lineage: ColumnLineage::new(),
+            generation: 0,
+        }
+    }
+
+    #[test]
+    fn test_differing_columns() {
+        let data = data();
+        let comparison = RowComparison::new(0, 1);
+        assert_eq!(comparison.differing_columns(&data), vec![0, 2, 3]);
+        assert_eq!(comparison.differences_text(&data, |_, value| value.to_string()), "id\t1\t2\nregion\teu\tus\ndeleted_at\t(NULL)\t2024-01-01");
+        assert!(RowComparison::new(0, 0).differing_columns(&data).is_empty());
+        assert!(!RowComparison::new(0, 2).is_valid(&data));
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use crate::db::SchemaInfo;
+use crate::models::TableData;
+
+// Rows kept of each table opened, enough to see what its data looks like
+const SAMPLE_ROWS: usize = 200;
+
+/// Schema tree of a connection as last fetched, browsed while the database is unreachable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedSchema {
+    pub cached_at: DateTime<Utc>,
+    pub schemas: Vec<SchemaInfo>,
+}
+
+/// Schema trees and table samples kept on disk per connection, in a directory of its
+/// own holding `schema.bin` and one `samples/<schema>.<table>.bin` per table opened.
+#[derive(Clone)]
+pub struct SchemaCache {
+    dir: PathBuf,
+}
+
+impl SchemaCache {
+    pub fn new() -> Result<Self> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
+        Ok(Self::with_dir(home.join(".config").join("db-client").join("offline")))
+    }
+
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn connection_dir(&self, connection: &str) -> PathBuf {
+        self.dir.join(file_name(connection))
+    }
+
+    fn sample_path(&self, connection: &str, schema: &str, table: &str) -> PathBuf {
+        self.connection_dir(connection).join("samples").join(format!("{}.{}.bin", file_name(schema), file_name(table)))
+    }
+
+    pub fn store_schemas(&self, connection: &str, schemas: &[SchemaInfo]) -> Result<()> {
+        let cached = CachedSchema { cached_at: Utc::now(), schemas: schemas.to_vec() };
+        let bytes = bincode::serde::encode_to_vec(&cached, bincode::config::standard())?;
+        let dir = self.connection_dir(connection);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("schema.bin"), bytes)?;
+        Ok(())
+    }
+
+    pub fn load_schemas(&self, connection: &str) -> Option<CachedSchema> {
+        let bytes = fs::read(self.connection_dir(connection).join("schema.bin")).ok()?;
+        bincode::serde::decode_from_slice(&bytes, bincode::config::standard())
+            .ok()
+            .map(|(cached, _)| cached)
+    }
+
+    /// Keep a `table_sample` of a table, replacing its earlier one.
+    pub fn store_sample(&self, connection: &str, schema: &str, table: &str, sample: &TableData) -> Result<()> {
+        let bytes = bincode::serde::encode_to_vec(sample, bincode::config::standard())?;
+        let path = self.sample_path(connection, schema, table);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    pub fn load_sample(&self, connection: &str, schema: &str, table: &str) -> Option<TableData> {
+        let bytes = fs::read(self.sample_path(connection, schema, table)).ok()?;
+        bincode::serde::decode_from_slice(&bytes, bincode::config::standard())
+            .ok()
+            .map(|(data, _)| data)
+    }
+}
+
+/// The first rows of a loaded table, as kept for browsing it offline.
+pub fn table_sample(data: &TableData) -> TableData {
+    TableData {
+        name: data.name.clone(),
+        columns: data.columns.clone(),
+        rows: data.rows.iter().take(SAMPLE_ROWS).cloned().collect(),
+        lineage: data.lineage.clone(),
+        generation: 0,
+    }
+}
+
+// Name safe to use as a file name on every platform, still telling most names apart
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{ColumnInfo, ColumnLineage};
+    use crate::models::CellValue;
+
+    #[test]
+    fn test_schemas_and_samples_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("schema-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cache = SchemaCache::with_dir(dir.clone());
+        assert!(cache.load_schemas("Local dev").is_none());
+
+        let mut public = SchemaInfo::new("public".to_string());
+        public.tables.push("users".to_string());
+        let columns = vec![ColumnInfo { name: "id".to_string(), data_type: "int4".to_string(), is_primary_key: true, is_foreign_key: false }];
+        public.table_columns.insert("users".to_string(), columns.clone());
+        cache.store_schemas("Local dev", &[public]).unwrap();
+
+        let cached = cache.load_schemas("Local dev").unwrap();
+        assert_eq!(cached.schemas[0].tables, ["users"]);
+        assert_eq!(cached.schemas[0].table_columns["users"][0].name, "id");
+        assert!(cache.load_schemas("Staging").is_none());
+
+        let data = TableData {
+            name: "public.users".to_string(),
+            columns,
+            rows: (0..SAMPLE_ROWS + 5).map(|i| vec![CellValue::Int(i as i64)]).collect(),
+            lineage: ColumnLineage::new(),
+            generation: 0,
+        };
+        cache.store_sample("Local dev", "public", "users", &table_sample(&data)).unwrap();
+        let sample = cache.load_sample("Local dev", "public", "users").unwrap();
+        assert_eq!(sample.rows.len(), SAMPLE_ROWS);
+        assert!(cache.load_sample("Local dev", "public", "orders").is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
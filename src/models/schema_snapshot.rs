@@ -0,0 +1,186 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use anyhow::Result;
+use crate::db::{ColumnInfo, SchemaInfo};
+
+const SCHEMA_SNAPSHOT_VERSION: u32 = 1;
+
+/// Tables and columns of a database at one point in time, saved as JSON so the live
+/// schema can later be compared against it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaSnapshot {
+    pub version: u32,
+    pub taken_at: DateTime<Utc>,
+    #[serde(default)]
+    pub connection_name: Option<String>,
+    // Keyed by "schema.table"
+    pub tables: BTreeMap<String, Vec<ColumnInfo>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaChangeKind {
+    Added,
+    Removed,
+    Altered,
+}
+
+impl SchemaChangeKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SchemaChangeKind::Added => "added",
+            SchemaChangeKind::Removed => "removed",
+            SchemaChangeKind::Altered => "altered",
+        }
+    }
+}
+
+/// One difference between a snapshot and the live schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaChange {
+    pub kind: SchemaChangeKind,
+    pub object: String, // "schema.table" or "schema.table.column"
+    pub detail: String,
+}
+
+impl SchemaSnapshot {
+    pub fn new(schemas: &[SchemaInfo], connection_name: Option<String>) -> Self {
+        let tables = schemas.iter()
+            .flat_map(|schema| {
+                schema.relations().map(move |table| {
+                    let columns = schema.table_columns.get(table).cloned().unwrap_or_default();
+                    (format!("{}.{}", schema.name, table), columns)
+                })
+            })
+            .collect();
+
+        Self {
+            version: SCHEMA_SNAPSHOT_VERSION,
+            taken_at: Utc::now(),
+            connection_name,
+            tables,
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let snapshot: SchemaSnapshot = serde_json::from_str(&content)?;
+        if snapshot.version > SCHEMA_SNAPSHOT_VERSION {
+            return Err(anyhow::anyhow!(
+                "Schema snapshot was created by a newer version (format {})",
+                snapshot.version
+            ));
+        }
+        Ok(snapshot)
+    }
+
+    /// What changed going from this snapshot to `live`, tables in name order with their
+    /// columns in the order they appear.
+    pub fn diff(&self, live: &SchemaSnapshot) -> Vec<SchemaChange> {
+        let mut changes = Vec::new();
+        let change = |kind, object: String, detail: String| SchemaChange { kind, object, detail };
+
+        for (table, old_columns) in &self.tables {
+            let Some(new_columns) = live.tables.get(table) else {
+                changes.push(change(SchemaChangeKind::Removed, table.clone(), format!("table with {} column(s)", old_columns.len())));
+                continue;
+            };
+
+            for old in old_columns {
+                let object = format!("{}.{}", table, old.name);
+                match new_columns.iter().find(|c| c.name == old.name) {
+                    None => changes.push(change(SchemaChangeKind::Removed, object, format!("column {}", old.data_type))),
+                    Some(new) => {
+                        let differences = column_differences(old, new);
+                        if !differences.is_empty() {
+                            changes.push(change(SchemaChangeKind::Altered, object, differences.join(", ")));
+                        }
+                    }
+                }
+            }
+            for new in new_columns.iter().filter(|new| !old_columns.iter().any(|c| c.name == new.name)) {
+                changes.push(change(SchemaChangeKind::Added, format!("{}.{}", table, new.name), format!("column {}", new.data_type)));
+            }
+        }
+
+        for (table, new_columns) in live.tables.iter().filter(|(table, _)| !self.tables.contains_key(*table)) {
+            changes.push(change(SchemaChangeKind::Added, table.clone(), format!("table with {} column(s)", new_columns.len())));
+        }
+
+        changes.sort_by(|a, b| a.object.cmp(&b.object));
+        changes
+    }
+}
+
+fn column_differences(old: &ColumnInfo, new: &ColumnInfo) -> Vec<String> {
+    let mut differences = Vec::new();
+    if old.data_type != new.data_type {
+        differences.push(format!("type {} → {}", old.data_type, new.data_type));
+    }
+    if old.is_primary_key != new.is_primary_key {
+        differences.push(if new.is_primary_key { "now part of the primary key" } else { "no longer part of the primary key" }.to_string());
+    }
+    if old.is_foreign_key != new.is_foreign_key {
+        differences.push(if new.is_foreign_key { "foreign key added" } else { "foreign key dropped" }.to_string());
+    }
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn snapshot(tables: &[(&str, Vec<ColumnInfo>)]) -> SchemaSnapshot {
+        let schema = SchemaInfo {
+            tables: tables.iter().map(|(name, _)| name.to_string()).collect(),
+            table_columns: tables.iter().map(|(name, columns)| (name.to_string(), columns.clone())).collect::<HashMap<_, _>>(),
+            ..SchemaInfo::new("public".to_string())
+        };
+        SchemaSnapshot::new(&[schema], Some("dev".to_string()))
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = snapshot(&[
+            ("users", vec![ColumnInfo::test("id", "integer"), ColumnInfo::test("name", "text"), ColumnInfo::test("legacy", "text")]),
+            ("audit", vec![ColumnInfo::test("id", "integer")]),
+        ]);
+        let mut id = ColumnInfo::test("id", "bigint");
+        id.is_primary_key = true;
+        let after = snapshot(&[
+            ("users", vec![id, ColumnInfo::test("name", "text"), ColumnInfo::test("email", "text")]),
+            ("orders", vec![ColumnInfo::test("id", "integer"), ColumnInfo::test("total", "numeric")]),
+        ]);
+
+        let changes: Vec<_> = before.diff(&after).into_iter().map(|c| (c.kind, c.object, c.detail)).collect();
+        assert_eq!(changes, vec![
+            (SchemaChangeKind::Removed, "public.audit".to_string(), "table with 1 column(s)".to_string()),
+            (SchemaChangeKind::Added, "public.orders".to_string(), "table with 2 column(s)".to_string()),
+            (SchemaChangeKind::Added, "public.users.email".to_string(), "column text".to_string()),
+            (SchemaChangeKind::Altered, "public.users.id".to_string(), "type integer → bigint, now part of the primary key".to_string()),
+            (SchemaChangeKind::Removed, "public.users.legacy".to_string(), "column text".to_string()),
+        ]);
+        assert!(after.diff(&after).is_empty());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let path = std::env::temp_dir().join(format!("schema-snapshot-{}.json", std::process::id()));
+        let original = snapshot(&[("users", vec![ColumnInfo::test("id", "integer")])]);
+        original.save(&path).unwrap();
+
+        let loaded = SchemaSnapshot::load(&path).unwrap();
+        assert_eq!(loaded.connection_name.as_deref(), Some("dev"));
+        assert!(original.diff(&loaded).is_empty());
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::{BTreeSet, HashSet};
+use std::ops::Range;
+use crate::models::{CellValue, TableData};
+
+/// Cells selected in the grid. Rows are positions in the filtered/sorted view,
+/// so a selection stays meaningful across pages.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CellSelection {
+    // Corner where the current rectangle started, and the opposite corner
+    anchor: Option<(usize, usize)>,
+    cursor: Option<(usize, usize)>,
+    // Cells added individually with Ctrl/Cmd+click
+    extra: BTreeSet<(usize, usize)>,
+}
+
+impl CellSelection {
+    /// Select a single cell, replacing the current selection.
+    pub fn select_cell(&mut self, row: usize, column: usize) {
+        self.anchor = Some((row, column));
+        self.cursor = Some((row, column));
+        self.extra.clear();
+    }
+
+    /// Extend the current rectangle from its anchor to `(row, column)`.
+    pub fn extend_to(&mut self, row: usize, column: usize) {
+        if self.anchor.is_none() {
+            self.anchor = Some((row, column));
+        }
+        self.cursor = Some((row, column));
+    }
+
+    /// Add or remove a single cell without touching the rest of the selection.
+    pub fn toggle_cell(&mut self, row: usize, column: usize) {
+        if self.rect_contains(row, column) {
+            // Fold the rectangle into individual cells so one of them can be removed
+            self.extra.extend(self.rect_cells());
+            self.anchor = None;
+            self.cursor = None;
+        }
+        if !self.extra.remove(&(row, column)) {
+            self.extra.insert((row, column));
+        }
+    }
+
+    /// Select every column of `row`.
+    pub fn select_row(&mut self, row: usize, column_count: usize) {
+        if column_count == 0 {
+            return;
+        }
+        self.anchor = Some((row, 0));
+        self.cursor = Some((row, column_count - 1));
+        self.extra.clear();
+    }
+
+    /// Add or remove every column of `row` without touching the rest of the selection,
+    /// so rows far apart can be picked together.
+    pub fn toggle_row(&mut self, row: usize, column_count: usize) {
+        if self.rect().is_some() {
+            self.extra.extend(self.rect_cells());
+            self.anchor = None;
+            self.cursor = None;
+        }
+        let cells: Vec<(usize, usize)> = (0..column_count).map(|column| (row, column)).collect();
+        if cells.iter().all(|cell| self.extra.contains(cell)) {
+            cells.iter().for_each(|cell| {
+                self.extra.remove(cell);
+            });
+        } else {
+            self.extra.extend(cells);
+        }
+    }
+
+    /// Corner that moves when extending (the last clicked or navigated cell).
+    pub fn cursor(&self) -> Option<(usize, usize)> {
+        self.cursor
+    }
+
+    /// Move the cursor by a step, keeping it inside `rows` and the column range.
+    /// With `extend` the anchor stays put and the rectangle grows (Shift+arrows).
+    pub fn move_cursor(&mut self, d_row: isize, d_col: isize, extend: bool, rows: Range<usize>, column_count: usize) {
+        if rows.is_empty() || column_count == 0 {
+            return;
+        }
+        let (row, column) = self.cursor.unwrap_or((rows.start, 0));
+        let row = row.saturating_add_signed(d_row).clamp(rows.start, rows.end - 1);
+        let column = column.saturating_add_signed(d_col).min(column_count - 1);
+
+        if extend {
+            self.extra.clear();
+            self.extend_to(row, column);
+        } else {
+            self.select_cell(row, column);
+        }
+    }
+
+    /// Tab-separated text for the bounding box of the selection, one line per row.
+    /// Cells inside the box that are not selected are left empty.
+    pub fn copy_text(&self, value: impl Fn(usize, usize) -> Option<String>) -> String {
+        let cells = self.cells();
+        let (Some(top), Some(bottom)) = (cells.first().map(|c| c.0), cells.last().map(|c| c.0)) else {
+            return String::new();
+        };
+        let left = cells.iter().map(|c| c.1).min().unwrap_or(0);
+        let right = cells.iter().map(|c| c.1).max().unwrap_or(0);
+
+        (top..=bottom)
+            .map(|row| {
+                (left..=right)
+                    .map(|column| if self.contains(row, column) { value(row, column).unwrap_or_default() } else { String::new() })
+                    .collect::<Vec<_>>()
+                    .join("\t")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anchor.is_none() && self.extra.is_empty()
+    }
+
+    /// Inclusive `(top, left, bottom, right)` bounds of the current rectangle.
+    pub fn rect(&self) -> Option<(usize, usize, usize, usize)> {
+        let (anchor, cursor) = (self.anchor?, self.cursor?);
+        Some((
+            anchor.0.min(cursor.0),
+            anchor.1.min(cursor.1),
+            anchor.0.max(cursor.0),
+            anchor.1.max(cursor.1),
+        ))
+    }
+
+    fn rect_contains(&self, row: usize, column: usize) -> bool {
+        self.rect()
+            .is_some_and(|(top, left, bottom, right)| (top..=bottom).contains(&row) && (left..=right).contains(&column))
+    }
+
+    fn rect_cells(&self) -> Vec<(usize, usize)> {
+        match self.rect() {
+            Some((top, left, bottom, right)) => (top..=bottom)
+                .flat_map(|row| (left..=right).map(move |column| (row, column)))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// True when the current rectangle spans every column of `row`, or the row was
+    /// added with `toggle_row`.
+    pub fn is_row_selected(&self, row: usize, column_count: usize) -> bool {
+        let in_rect = self.rect().is_some_and(|(top, left, bottom, right)| {
+            left == 0 && right + 1 >= column_count && (top..=bottom).contains(&row)
+        });
+        in_rect || (column_count > 0 && (0..column_count).all(|column| self.extra.contains(&(row, column))))
+    }
+
+    pub fn contains(&self, row: usize, column: usize) -> bool {
+        self.rect_contains(row, column) || self.extra.contains(&(row, column))
+    }
+
+    /// Every selected cell in row-major order, without duplicates.
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        let mut cells: BTreeSet<(usize, usize)> = self.extra.clone();
+        cells.extend(self.rect_cells());
+        cells.into_iter().collect()
+    }
+
+    /// Columns with at least one selected cell, in order.
+    pub fn columns(&self) -> Vec<usize> {
+        let columns: BTreeSet<usize> = self.cells().into_iter().map(|(_, column)| column).collect();
+        columns.into_iter().collect()
+    }
+
+    /// Rows with at least one selected cell, in order.
+    pub fn rows(&self) -> Vec<usize> {
+        let rows: BTreeSet<usize> = self.cells().into_iter().map(|(row, _)| row).collect();
+        rows.into_iter().collect()
+    }
+}
+
+/// Spreadsheet-style summary of the selected values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionStats {
+    pub count: usize,
+    pub distinct: usize,
+    pub nulls: usize,
+    // Only set when at least one selected value is numeric
+    pub sum: Option<f64>,
+    pub avg: Option<f64>,
+}
+
+impl SelectionStats {
+    pub fn from_values<'a>(values: impl IntoIterator<Item = &'a CellValue>) -> Self {
+        let mut count = 0;
+        let mut nulls = 0;
+        let mut distinct = HashSet::new();
+        let mut numeric_count = 0;
+        let mut sum = 0.0;
+
+        for value in values {
+            count += 1;
+            if value.is_null() {
+                nulls += 1;
+                continue;
+            }
+            distinct.insert(value.to_string());
+            if let Some(n) = value.as_f64() {
+                numeric_count += 1;
+                sum += n;
+            }
+        }
+
+        let (sum, avg) = if numeric_count > 0 {
+            (Some(sum), Some(sum / numeric_count as f64))
+        } else {
+            (None, None)
+        };
+
+        Self {
+            count,
+            distinct: distinct.len(),
+            nulls,
+            sum,
+            avg,
+        }
+    }
+
+    /// One-line text for the status bar.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![
+            format!("Count: {}", self.count),
+            format!("Distinct: {}", self.distinct),
+        ];
+        if self.nulls > 0 {
+            parts.push(format!("Nulls: {}", self.nulls));
+        }
+        if let (Some(sum), Some(avg)) = (self.sum, self.avg) {
+            parts.push(format!("Sum: {}", format_number(sum)));
+            parts.push(format!("Avg: {}", format_number(avg)));
+        }
+        parts.join("  ")
+    }
+}
+
+/// Spread of a numeric column over the rows passing the filters, shown under the grid
+/// header while it is the sort column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnAggregate {
+    pub column: usize,
+    pub count: usize, // non-NULL cells
+    pub min: f64,
+    pub median: f64,
+    pub max: f64,
+}
+
+impl ColumnAggregate {
+    /// None unless `column` holds a number in every non-NULL cell of `rows`, and at least one.
+    pub fn new(data: &TableData, rows: &[usize], column: usize) -> Option<Self> {
+        let mut numbers = Vec::with_capacity(rows.len());
+        for cell in rows.iter().filter_map(|&row| data.rows.get(row)?.get(column)) {
+            if !cell.is_null() {
+                numbers.push(cell.as_f64().filter(|n| n.is_finite())?);
+            }
+        }
+        if numbers.is_empty() {
+            return None;
+        }
+
+        numbers.sort_by(f64::total_cmp);
+        let middle = numbers.len() / 2;
+        let median = if numbers.len() % 2 == 0 { (numbers[middle - 1] + numbers[middle]) / 2.0 } else { numbers[middle] };
+        Some(Self { column, count: numbers.len(), min: numbers[0], median, max: numbers[numbers.len() - 1] })
+    }
+
+    pub fn summary(&self) -> String {
+        format!("Min: {}  Median: {}  Max: {}", format_number(self.min), format_number(self.median), format_number(self.max))
+    }
+}
+
+// Trim float noise so sums of integers print as integers
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.4}", value).trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rectangle_from_anchor_and_cursor() {
+        let mut selection = CellSelection::default();
+        selection.select_cell(3, 2);
+        selection.extend_to(1, 0);
+        assert_eq!(selection.rect(), Some((1, 0, 3, 2)));
+        assert_eq!(selection.cells().len(), 9);
+        assert!(selection.contains(2, 1));
+        assert!(!selection.contains(4, 1));
+    }
+
+    #[test]
+    fn test_toggle_cell_removes_from_rectangle() {
+        let mut selection = CellSelection::default();
+        selection.select_cell(0, 0);
+        selection.extend_to(1, 1);
+        selection.toggle_cell(1, 1);
+        assert_eq!(selection.cells(), vec![(0, 0), (0, 1), (1, 0)]);
+        assert_eq!(selection.columns(), vec![0, 1]);
+
+        selection.toggle_cell(5, 5);
+        assert!(selection.contains(5, 5));
+        selection.clear();
+        assert!(selection.is_empty());
+    }
+
+    #[test]
+    fn test_select_row() {
+        let mut selection = CellSelection::default();
+        selection.select_row(4, 3);
+        assert_eq!(selection.cells(), vec![(4, 0), (4, 1), (4, 2)]);
+        assert!(selection.is_row_selected(4, 3));
+        assert!(!selection.is_row_selected(3, 3));
+    }
+
+    #[test]
+    fn test_toggle_row() {
+        let mut selection = CellSelection::default();
+        selection.select_row(1, 2);
+        selection.toggle_row(6, 2);
+        assert_eq!(selection.rows(), vec![1, 6]);
+        assert!(selection.is_row_selected(1, 2));
+        assert!(selection.is_row_selected(6, 2));
+
+        selection.toggle_row(1, 2);
+        assert_eq!(selection.cells(), vec![(6, 0), (6, 1)]);
+    }
+
+    #[test]
+    fn test_move_cursor_extends_and_clamps() {
+        let mut selection = CellSelection::default();
+        selection.select_cell(1, 1);
+        selection.move_cursor(1, 1, true, 0..3, 3);
+        assert_eq!(selection.rect(), Some((1, 1, 2, 2)));
+
+        selection.move_cursor(5, 5, true, 0..3, 3);
+        assert_eq!(selection.cursor(), Some((2, 2)));
+
+        selection.move_cursor(-1, -5, false, 0..3, 3);
+        assert_eq!(selection.rect(), Some((1, 0, 1, 0)));
+    }
+
+    #[test]
+    fn test_copy_text_rectangle_and_gaps() {
+        let grid = [["a", "b", "c"], ["d", "e", "f"]];
+        let value = |row: usize, column: usize| grid.get(row).and_then(|r| r.get(column)).map(|v| v.to_string());
+
+        let mut selection = CellSelection::default();
+        selection.select_cell(0, 1);
+        selection.extend_to(1, 2);
+        assert_eq!(selection.copy_text(value), "b\tc\ne\tf");
+
+        selection.clear();
+        selection.toggle_cell(0, 0);
+        selection.toggle_cell(1, 2);
+        assert_eq!(selection.copy_text(value), "a\t\t\n\t\tf");
+    }
+
+    #[test]
+    fn test_stats_numeric() {
+        let values = [CellValue::Int(1), CellValue::from("2"), CellValue::Float(2.0), CellValue::Null];
+        let stats = SelectionStats::from_values(&values);
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.distinct, 2);
+        assert_eq!(stats.nulls, 1);
+        assert_eq!(stats.sum, Some(5.0));
+        assert_eq!(stats.summary(), "Count: 4  Distinct: 2  Nulls: 1  Sum: 5  Avg: 1.6667");
+    }
+
+    #[test]
+    fn test_column_aggregate() {
+        use crate::db::{ColumnInfo, ColumnLineage};
+        let column = |name: &str| ColumnInfo { name: name.to_string(), data_type: "text".to_string(), is_primary_key: false, is_foreign_key: false };
+        let rows = vec![
+            vec![CellValue::Int(7), CellValue::from("x")],
+            vec![CellValue::Null, CellValue::from("y")],
+            vec![CellValue::Float(1.5), CellValue::from("z")],
+            vec![CellValue::from("3"), CellValue::from("4")],
+            vec![CellValue::Int(10), CellValue::Null],
+        ];
+        let data = TableData { name: "t".to_string(), columns: vec![column("n"), column("s")], rows, lineage: ColumnLineage::new(), generation: 0 };
+
+        let aggregate = ColumnAggregate::new(&data, &[0, 1, 2, 3, 4], 0).unwrap();
+        assert_eq!((aggregate.count, aggregate.min, aggregate.median, aggregate.max), (4, 1.5, 5.0, 10.0));
+        assert_eq!(aggregate.summary(), "Min: 1.5  Median: 5  Max: 10");
+        // Only the given rows, e.g. those passing the filters
+        assert_eq!(ColumnAggregate::new(&data, &[0, 2, 3], 0).unwrap().median, 3.0);
+
+        assert_eq!(ColumnAggregate::new(&data, &[0, 1, 2, 3, 4], 1), None);
+        assert_eq!(ColumnAggregate::new(&data, &[1], 0), None);
+    }
+
+    #[test]
+    fn test_stats_text_has_no_sum() {
+        let values = [CellValue::from("a"), CellValue::from("b"), CellValue::from("a")];
+        let stats = SelectionStats::from_values(&values);
+        assert_eq!(stats.distinct, 2);
+        assert_eq!(stats.sum, None);
+        assert_eq!(stats.summary(), "Count: 3  Distinct: 2");
+    }
+}
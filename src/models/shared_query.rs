@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use anyhow::Result;
+use crate::models::FilterRule;
+
+pub const SHARED_QUERY_EXTENSION: &str = "dexq";
+const SHARED_QUERY_VERSION: u32 = 1;
+
+/// A self-contained query that can be passed around as a `.dexq` file.
+/// Only the connection *name* is stored, never credentials.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedQuery {
+    pub version: u32,
+    pub title: String,
+    pub sql: String,
+    #[serde(default)]
+    pub connection_name: Option<String>,
+    #[serde(default)]
+    pub view: SharedViewSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SharedViewSettings {
+    pub page_size: usize,
+    pub filters: Vec<FilterRule>,
+    pub search_text: String,
+}
+
+impl Default for SharedViewSettings {
+    fn default() -> Self {
+        Self {
+            page_size: 100,
+            filters: Vec::new(),
+            search_text: String::new(),
+        }
+    }
+}
+
+impl SharedQuery {
+    pub fn new(title: String, sql: String, connection_name: Option<String>, view: SharedViewSettings) -> Self {
+        Self {
+            version: SHARED_QUERY_VERSION,
+            title,
+            sql,
+            connection_name,
+            view,
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let shared: SharedQuery = serde_json::from_str(&content)?;
+        if shared.version > SHARED_QUERY_VERSION {
+            return Err(anyhow::anyhow!(
+                "Shared query was created by a newer version (format {})",
+                shared.version
+            ));
+        }
+        Ok(shared)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let path = std::env::temp_dir().join(format!("roundtrip-{}.{}", std::process::id(), SHARED_QUERY_EXTENSION));
+        let shared = SharedQuery::new(
+            "Active users".to_string(),
+            "SELECT * FROM users WHERE active".to_string(),
+            Some("prod".to_string()),
+            SharedViewSettings { page_size: 500, ..Default::default() },
+        );
+        shared.save(&path).unwrap();
+
+        let loaded = SharedQuery::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(loaded.title, "Active users");
+        assert_eq!(loaded.sql, "SELECT * FROM users WHERE active");
+        assert_eq!(loaded.connection_name.as_deref(), Some("prod"));
+        assert_eq!(loaded.view.page_size, 500);
+    }
+
+    #[test]
+    fn test_minimal_file_uses_defaults() {
+        let shared: SharedQuery = serde_json::from_str(r#"{"version": 1, "title": "q", "sql": "SELECT 1"}"#).unwrap();
+        assert!(shared.connection_name.is_none());
+        assert_eq!(shared.view.page_size, 100);
+    }
+
+    #[test]
+    fn test_file_never_contains_password() {
+        let shared = SharedQuery::new("q".to_string(), "SELECT 1".to_string(), Some("prod".to_string()), SharedViewSettings::default());
+        let json = serde_json::to_string(&shared).unwrap();
+        assert!(!json.contains("password"));
+    }
+}
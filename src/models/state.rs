@@ -4,6 +4,7 @@ use std::fs;
 use std::path::PathBuf;
 use anyhow::Result;
 use crate::models::Tab;
+use crate::versioned::{self, Versioned};
 
 #[derive(Serialize, Deserialize)]
 pub struct AppState {
@@ -13,6 +14,10 @@ pub struct AppState {
     pub expanded_schemas: HashSet<String>,
 }
 
+impl Versioned for AppState {
+    const VERSION: u32 = 1;
+}
+
 impl AppState {
     pub fn save_path() -> Result<PathBuf> {
         let home = dirs::home_dir()
@@ -21,23 +26,36 @@ impl AppState {
     }
 
     pub fn save(&self) -> Result<()> {
-        let path = Self::save_path()?;
+        versioned::write(&Self::save_path()?, self)
+    }
+
+    fn session_marker_path() -> Result<PathBuf> {
+        Ok(Self::save_path()?.with_file_name("session.lock"))
+    }
+
+    /// Mark a session as running until `end_session`. True when the previous session
+    /// never got there, i.e. the app crashed or was killed.
+    pub fn begin_session() -> Result<bool> {
+        let path = Self::session_marker_path()?;
+        let crashed = path.exists();
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
-        let content = serde_json::to_string_pretty(self)?;
-        fs::write(&path, content)?;
-        Ok(())
+        fs::write(&path, std::process::id().to_string())?;
+        Ok(crashed)
     }
 
-    pub fn load() -> Result<Self> {
-        let path = Self::save_path()?;
+    /// Record that the session ended cleanly.
+    pub fn end_session() -> Result<()> {
+        let path = Self::session_marker_path()?;
         if path.exists() {
-            let content = fs::read_to_string(&path)?;
-            let state: AppState = serde_json::from_str(&content)?;
-            Ok(state)
-        } else {
-            Err(anyhow::anyhow!("State file does not exist"))
+            fs::remove_file(path)?;
         }
+        Ok(())
+    }
+
+    /// The saved state, None when there is none yet.
+    pub fn load() -> Result<Option<Self>> {
+        versioned::read(&Self::save_path()?)
     }
 }
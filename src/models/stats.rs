@@ -0,0 +1,64 @@
+use crate::models::CellValue;
+
+/// Wall-clock time, rows returned and approximate bytes fetched by one query or table load.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct QueryStats {
+    pub duration_ms: u64,
+    pub rows: usize,
+    pub bytes: u64,
+}
+
+impl QueryStats {
+    pub fn measure(duration_ms: u64, rows: &[Vec<CellValue>]) -> Self {
+        Self { duration_ms, rows: rows.len(), bytes: rows_size(rows) }
+    }
+
+    /// Everything the status bar shows, e.g. "1200 rows · 45.2 KB · 120 ms".
+    pub fn summary(&self) -> String {
+        format!("{} rows · {} · {} ms", self.rows, format_bytes(self.bytes), self.duration_ms)
+    }
+}
+
+/// Approximate size of `rows` as fetched, summing the size of every value.
+pub fn rows_size(rows: &[Vec<CellValue>]) -> u64 {
+    rows.iter().flatten().map(|value| value.size_in_bytes() as u64).sum()
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MB");
+    }
+
+    #[test]
+    fn test_measure() {
+        let rows = vec![
+            vec![CellValue::Int(1), CellValue::from("héllo"), CellValue::Null],
+            vec![CellValue::Int(2), CellValue::Bytes(vec![0; 10]), CellValue::Bool(true)],
+        ];
+        let stats = QueryStats::measure(42, &rows);
+        // 8 + 6 (é is two bytes) + 0, then 8 + 10 + 1
+        assert_eq!(stats, QueryStats { duration_ms: 42, rows: 2, bytes: 33 });
+        assert_eq!(stats.summary(), "2 rows · 33 B · 42 ms");
+    }
+}
@@ -1,18 +1,224 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
-use crate::db::ColumnInfo;
-use crate::models::FilterRule;
+use std::cmp::Ordering;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use crate::config::Driver;
+use crate::db::sql::{quote_ident_for, sql_page, with_sql_page, SqlPage, TableFilter, TableOrder};
+use crate::db::{ColumnInfo, ColumnLineage, ResourceReport, TableStructure};
+use crate::models::{apply_computed_columns, distinct_rows, filter_row_indices, filters_to_sql, is_computed, page_count, sort_rows, Bookmarks, ChartSettings, ComputedColumn, DistinctView, ExplainPlan, FilterRule, PendingEdits, TailState};
+
+// How NULL cells are displayed in the grid
+const NULL_DISPLAY: &str = "(NULL)";
+/// Columns above which a result is drawn with only its first `WIDE_TABLE_SHOWN_COLUMNS`
+/// until all are asked for
+pub const WIDE_TABLE_COLUMNS: usize = 100;
+pub const WIDE_TABLE_SHOWN_COLUMNS: usize = 40;
+
+/// One value of a result row. The variant keeps the database type, so sorting,
+/// filtering and exports compare and write values without parsing display text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CellValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    // Also decimals and anything else without a closer match, as the server renders them
+    Text(String),
+    Date(NaiveDate),
+    Timestamp(NaiveDateTime),
+    TimestampTz(DateTime<Utc>),
+    // Serialized document; a serde_json::Value could not be read back from the bincode cache
+    Json(String),
+    Bytes(Vec<u8>),
+    Uuid(uuid::Uuid),
+    // One-dimensional PostgreSQL array
+    Array(Vec<CellValue>),
+}
+
+impl CellValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, CellValue::Null)
+    }
+
+    /// Approximate size of the value as fetched from the server.
+    pub fn size_in_bytes(&self) -> usize {
+        match self {
+            CellValue::Null => 0,
+            CellValue::Bool(_) => 1,
+            CellValue::Int(_) | CellValue::Float(_) | CellValue::Timestamp(_) | CellValue::TimestampTz(_) => 8,
+            CellValue::Date(_) => 4,
+            CellValue::Text(text) | CellValue::Json(text) => text.len(),
+            CellValue::Bytes(bytes) => bytes.len(),
+            CellValue::Uuid(_) => 16,
+            CellValue::Array(values) => values.iter().map(CellValue::size_in_bytes).sum(),
+        }
+    }
+
+    /// Numeric value, including text that holds a number (e.g. decimals).
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            CellValue::Int(v) => Some(*v as f64),
+            CellValue::Float(v) => Some(*v),
+            CellValue::Text(text) => text.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Point in time of a date or timestamp, in UTC for timestamps with a time zone.
+    /// Text holding one (e.g. values the driver could not type) is parsed.
+    pub fn as_timestamp(&self) -> Option<NaiveDateTime> {
+        match self {
+            CellValue::Date(date) => date.and_hms_opt(0, 0, 0),
+            CellValue::Timestamp(timestamp) => Some(*timestamp),
+            CellValue::TimestampTz(timestamp) => Some(timestamp.naive_utc()),
+            CellValue::Text(text) => parse_timestamp(text),
+            _ => None,
+        }
+    }
+
+    /// Ordering for sorting a column: NULLs last, then by value for typed cells,
+    /// numerically for numbers, chronologically for dates and timestamps (also as
+    /// text), and by text otherwise.
+    pub fn compare(&self, other: &CellValue) -> Ordering {
+        match (self, other) {
+            (CellValue::Null, CellValue::Null) => Ordering::Equal,
+            (CellValue::Null, _) => Ordering::Greater,
+            (_, CellValue::Null) => Ordering::Less,
+            (CellValue::Bool(a), CellValue::Bool(b)) => a.cmp(b),
+            (CellValue::Date(a), CellValue::Date(b)) => a.cmp(b),
+            (CellValue::Timestamp(a), CellValue::Timestamp(b)) => a.cmp(b),
+            (CellValue::TimestampTz(a), CellValue::TimestampTz(b)) => a.cmp(b),
+            (CellValue::Bytes(a), CellValue::Bytes(b)) => a.len().cmp(&b.len()),
+            _ => match (self.as_f64(), other.as_f64()) {
+                (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+                _ => match (self.as_timestamp(), other.as_timestamp()) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    _ => self.to_string().cmp(&other.to_string()),
+                },
+            },
+        }
+    }
+
+    /// The value after editing its text to `text`, keeping the type when the text still parses as one.
+    pub fn with_text(&self, text: &str) -> CellValue {
+        let parsed = match self {
+            CellValue::Bool(_) => text.parse().ok().map(CellValue::Bool),
+            CellValue::Int(_) => text.parse().ok().map(CellValue::Int),
+            CellValue::Float(_) => text.parse().ok().map(CellValue::Float),
+            CellValue::Date(_) => text.parse().ok().map(CellValue::Date),
+            CellValue::Timestamp(_) => NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f").ok().map(CellValue::Timestamp),
+            CellValue::Uuid(_) => text.parse().ok().map(CellValue::Uuid),
+            CellValue::Json(_) => serde_json::from_str::<serde_json::Value>(text).ok().map(|_| CellValue::Json(text.to_string())),
+            _ => None,
+        };
+        parsed.unwrap_or_else(|| CellValue::Text(text.to_string()))
+    }
+}
+
+/// A date or timestamp in the forms PostgreSQL and MySQL print them: `2024-01-31`,
+/// `2024-01-31 13:45[:07[.123]]` or with a `T`, optionally followed by `Z`, ` UTC` or
+/// an offset such as `+02`, `+02:00` or `-0530`. Values with a zone come back in UTC.
+pub fn parse_timestamp(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return date.and_hms_opt(0, 0, 0);
+    }
+    let text = match text.get(10..11) {
+        Some("T") => format!("{} {}", &text[..10], &text[11..]),
+        _ => text.to_string(),
+    };
+    let naive = |text: &str| {
+        ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M"].iter().find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+    };
+    if let Some(utc) = text.strip_suffix('Z').or_else(|| text.strip_suffix(" UTC")) {
+        return naive(utc);
+    }
+    if let Some(timestamp) = naive(&text) {
+        return Some(timestamp);
+    }
+
+    // Offset after the time, padded to +HHMM
+    let sign = text.rfind(['+', '-']).filter(|&index| index > 10)?;
+    let (local, offset) = text.split_at(sign);
+    let digits = offset[1..].replace(':', "");
+    let digits = if digits.len() == 2 { format!("{}00", digits) } else { digits };
+    let zoned = format!("{} {}{}", local.trim_end(), &offset[..1], digits);
+    ["%Y-%m-%d %H:%M:%S%.f %z", "%Y-%m-%d %H:%M %z"].iter()
+        .find_map(|format| DateTime::<FixedOffset>::parse_from_str(&zoned, format).ok())
+        .map(|timestamp| timestamp.naive_utc())
+}
+
+impl fmt::Display for CellValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellValue::Null => f.write_str(NULL_DISPLAY),
+            CellValue::Bool(v) => write!(f, "{}", v),
+            CellValue::Int(v) => write!(f, "{}", v),
+            CellValue::Float(v) => write!(f, "{}", v),
+            CellValue::Text(v) | CellValue::Json(v) => f.write_str(v),
+            CellValue::Date(v) => write!(f, "{}", v),
+            CellValue::Timestamp(v) => write!(f, "{}", v),
+            CellValue::TimestampTz(v) => write!(f, "{}", v),
+            CellValue::Bytes(v) => write!(f, "<{} bytes>", v.len()),
+            CellValue::Uuid(v) => write!(f, "{}", v),
+            CellValue::Array(values) => {
+                let elements: Vec<String> = values.iter().map(array_element).collect();
+                write!(f, "{{{}}}", elements.join(", "))
+            }
+        }
+    }
+}
+
+// An array element as PostgreSQL writes it, double-quoted when it would not read back
+// as the same value, so the array's text is also a valid array literal
+fn array_element(value: &CellValue) -> String {
+    if value.is_null() {
+        return "NULL".to_string();
+    }
+    let text = value.to_string();
+    let needs_quotes = text.is_empty()
+        || text.eq_ignore_ascii_case("null")
+        || text.chars().any(|c| c.is_whitespace() || matches!(c, '{' | '}' | ',' | '"' | '\\'));
+    if needs_quotes {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        text
+    }
+}
+
+impl From<&str> for CellValue {
+    fn from(text: &str) -> Self {
+        CellValue::Text(text.to_string())
+    }
+}
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct TableData {
     pub name: String,
     pub columns: Vec<ColumnInfo>,
-    pub rows: Vec<Vec<String>>,
+    pub rows: Vec<Vec<CellValue>>,
+    // Base columns behind a view's columns, shown in header tooltips
+    #[serde(default)]
+    pub lineage: ColumnLineage,
+    // Bumped when rows change in place, so views computed from them are redone
+    #[serde(skip)]
+    pub generation: u64,
+}
+
+impl TableData {
+    /// Record that rows were changed in place.
+    pub fn touch(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Tab {
     pub id: usize,
     pub title: String,
+    // Kept in the result cache rather than state.json, restored when the tab is activated
+    #[serde(skip)]
     pub data: Option<TableData>,
     #[serde(skip)]
     pub is_loading: bool,
@@ -31,16 +237,799 @@ pub struct Tab {
     pub search_match_index: usize,
     // Query input for this tab (editable SQL)
     pub query_input: String,
+    // Cell edits staged in the grid but not yet written to the database
+    #[serde(skip)]
+    pub pending_edits: PendingEdits,
+    // Timing and buffer usage of the last execution, when requested
+    #[serde(skip)]
+    pub resource_report: Option<ResourceReport>,
+    // Name of the saved connection the data came from
+    #[serde(default)]
+    pub connection_name: Option<String>,
+    // Parsed plan shown instead of the grid in explain tabs
+    #[serde(default)]
+    pub plan: Option<ExplainPlan>,
+    // Polling for new rows of a table tab
+    #[serde(skip)]
+    pub tail: Option<TailState>,
+    // Rows marked for a closer look, by primary key
+    #[serde(default)]
+    pub bookmarks: Bookmarks,
+    // Whether a table tab shows its rows or its indexes, constraints and triggers
+    #[serde(default)]
+    pub view: TabView,
+    #[serde(skip)]
+    pub structure: Option<TableStructure>,
+    // Came from the previous session and has not been reloaded since
+    #[serde(skip)]
+    pub restored: bool,
+    // SQL conditions a table tab's rows are loaded with, unlike `filters` applied by the server
+    #[serde(default)]
+    pub server_filters: Vec<String>,
+    // `filters` of a table tab sent along as a WHERE clause instead of applied to the loaded rows
+    #[serde(default)]
+    pub server_side_filtering: bool,
+    // Header clicks of a table tab reload it with ORDER BY instead of sorting the loaded rows
+    #[serde(default)]
+    pub server_side_sorting: bool,
+    // Result drawn as a bar chart above the grid, e.g. for time buckets
+    #[serde(default)]
+    pub chart: bool,
+    // Columns plotted by the Chart view of a query tab
+    #[serde(default)]
+    pub chart_settings: ChartSettings,
+    // Kept left-most and left open by the bulk close actions
+    #[serde(default)]
+    pub pinned: bool,
+    // Grid columns kept in view while scrolling horizontally
+    #[serde(default)]
+    pub frozen_columns: Vec<usize>,
+    // Rows collapsed to distinct values, with the full result kept to switch back to
+    #[serde(skip)]
+    pub distinct: Option<DistinctView>,
+    // Estimated size of a table tab opened on its structure for being large
+    #[serde(default)]
+    pub estimated_rows: Option<u64>,
+    // Rows a table tab loads at most, when fewer than usual were asked for
+    #[serde(default)]
+    pub row_limit: Option<usize>,
+    // Every column of a wide result is drawn, not just the first few
+    #[serde(default)]
+    pub show_all_columns: bool,
+    // Display columns computed from the others, added after the result's own
+    #[serde(default)]
+    pub computed_columns: Vec<ComputedColumn>,
+    // .sql file the query buffer was opened from or saved to, and its text as on disk
+    #[serde(default)]
+    pub sql_file: Option<PathBuf>,
+    #[serde(default)]
+    pub saved_sql: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum TabView {
+    #[default]
+    Data,
+    Structure,
+    Chart,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub enum TabSource {
     Table { schema: String, table: String },
     Query { sql: String },
+    Explain { sql: String, analyze: bool },
+}
+
+impl Tab {
+    pub fn new(id: usize, title: String, data: Option<TableData>, source: TabSource) -> Self {
+        // Initialize query_input from source if it's a Query
+        let query_input = match &source {
+            TabSource::Query { sql } | TabSource::Explain { sql, .. } => sql.clone(),
+            TabSource::Table { .. } => String::new(),
+        };
+
+        let mut tab = Self {
+            id,
+            title,
+            data,
+            is_loading: false,
+            sort_column: None,
+            sort_ascending: true,
+            current_page: 0,
+            page_size: 100,
+            source,
+            filters: Vec::new(),
+            search_text: String::new(),
+            search_match_index: 0,
+            query_input,
+            pending_edits: PendingEdits::default(),
+            resource_report: None,
+            connection_name: None,
+            plan: None,
+            tail: None,
+            bookmarks: Bookmarks::default(),
+            view: TabView::Data,
+            structure: None,
+            restored: false,
+            server_filters: Vec::new(),
+            server_side_filtering: false,
+            server_side_sorting: false,
+            chart: false,
+            chart_settings: ChartSettings::default(),
+            pinned: false,
+            frozen_columns: Vec::new(),
+            distinct: None,
+            estimated_rows: None,
+            row_limit: None,
+            show_all_columns: false,
+            computed_columns: Vec::new(),
+            sql_file: None,
+            saved_sql: String::new(),
+        };
+        tab.fit_page_to_sql();
+        tab
+    }
+
+    /// A large table tab whose rows wait for Load Data instead of loading on their own.
+    pub fn awaits_rows(&self) -> bool {
+        self.estimated_rows.is_some() && self.row_limit.is_none()
+    }
+
+    /// How many leading columns the grid draws: all of them, unless the result has more
+    /// than `WIDE_TABLE_COLUMNS` and they were not asked for.
+    pub fn shown_columns(&self) -> usize {
+        let columns = self.data.as_ref().map_or(0, |data| data.columns.len());
+        if columns > WIDE_TABLE_COLUMNS && !self.show_all_columns {
+            WIDE_TABLE_SHOWN_COLUMNS
+        } else {
+            columns
+        }
+    }
+
+    /// Whether the query buffer has changes not yet saved to its .sql file.
+    pub fn is_dirty(&self) -> bool {
+        self.sql_file.is_some() && self.query_input != self.saved_sql
+    }
+
+    /// Fill in the computed columns of the rows, after a load or as rows arrive. A
+    /// distinct view keeps the columns it was made from.
+    pub fn sync_computed_columns(&mut self) {
+        if let (Some(data), None) = (&mut self.data, &self.distinct) {
+            apply_computed_columns(data, &self.computed_columns);
+        }
+    }
+
+    /// Drop the computed column at grid column `column`, with the sorting, filters and
+    /// freezing that refer to it; those of later columns move along.
+    pub fn remove_computed_column(&mut self, column: usize) {
+        let Some(data) = &self.data else {
+            return;
+        };
+        let Some(index) = column.checked_sub(data.columns.len() - self.computed_columns.len()) else {
+            return;
+        };
+        self.computed_columns.remove(index);
+
+        let shifted = |other: usize| if other > column { other - 1 } else { other };
+        self.sort_column = self.sort_column.filter(|&sorted| sorted != column).map(shifted);
+        self.filters.retain(|rule| rule.column_index != column);
+        for rule in &mut self.filters {
+            rule.column_index = shifted(rule.column_index);
+        }
+        self.frozen_columns.retain(|&frozen| frozen != column);
+        for frozen in &mut self.frozen_columns {
+            *frozen = shifted(*frozen);
+        }
+        self.sync_computed_columns();
+        self.on_filters_changed();
+    }
+
+    // Computed columns only exist in the loaded rows, so filters on them are never sent
+    fn filters_computed_columns(&self, filters: &[FilterRule]) -> bool {
+        let columns = self.result_data().map(|data| data.columns.as_slice()).unwrap_or_default();
+        filters.iter().any(|rule| columns.get(rule.column_index).is_some_and(is_computed))
+    }
+
+    /// Copy of this tab under a new id, without the running tail or staged edits. The
+    /// copy is not tied to the .sql file, so the two cannot overwrite each other's saves.
+    pub fn duplicate(&self, id: usize) -> Tab {
+        Tab {
+            id,
+            is_loading: false,
+            pending_edits: PendingEdits::default(),
+            tail: None,
+            sql_file: None,
+            saved_sql: String::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Whether the Structure view replaces the grid; only table tabs have one.
+    pub fn shows_structure(&self) -> bool {
+        matches!(self.source, TabSource::Table { .. }) && self.view == TabView::Structure
+    }
+
+    /// Whether the result is plotted instead of listed in the grid.
+    pub fn shows_chart(&self) -> bool {
+        matches!(self.source, TabSource::Query { .. }) && self.view == TabView::Chart && self.data.is_some()
+    }
+
+    /// SQL that produced this tab's data, for watermarks and sharing.
+    pub fn source_sql(&self, driver: Driver) -> String {
+        match &self.source {
+            TabSource::Table { schema, table } => {
+                let mut sql = format!("SELECT * FROM {}.{}", schema, table);
+                if let Some(filter) = self.server_condition(driver, None) {
+                    sql.push_str(&format!(" WHERE {}", filter));
+                }
+                if let Some(order) = self.server_order() {
+                    sql.push_str(&format!(" ORDER BY {} {}", quote_ident_for(driver, &order.column), order.direction()));
+                }
+                sql
+            }
+            TabSource::Query { sql } | TabSource::Explain { sql, .. } => sql.clone(),
+        }
+    }
+
+    /// All server filters, plus the grid filters in server-side mode, as one condition.
+    /// None when there are none.
+    pub fn server_filter(&self, driver: Driver) -> Option<TableFilter> {
+        let mut params = Vec::new();
+        let sql = self.server_condition(driver, Some(&mut params))?;
+        Some(TableFilter { sql, params })
+    }
+
+    fn server_condition(&self, driver: Driver, params: Option<&mut Vec<String>>) -> Option<String> {
+        let mut conditions = self.server_filters.clone();
+        // The filters of the full rows, also while a distinct view is shown
+        let filters = self.distinct.as_ref().map(|view| &view.filters).unwrap_or(&self.filters);
+        if self.server_side_filtering && !self.filters_computed_columns(filters) {
+            let columns = self.result_data().map(|data| data.columns.as_slice()).unwrap_or_default();
+            conditions.extend(filters_to_sql(driver, filters, columns, params));
+        }
+        match conditions.as_slice() {
+            [] => None,
+            [condition] => Some(condition.clone()),
+            conditions => Some(conditions.iter().map(|condition| format!("({})", condition)).collect::<Vec<_>>().join(" AND ")),
+        }
+    }
+
+    /// Order a table tab sorted on the server is loaded in, None to keep the table's.
+    pub fn server_order(&self) -> Option<TableOrder> {
+        if !self.sorts_on_server() {
+            return None;
+        }
+        let column = self.result_data()?.columns.get(self.sort_column?).filter(|column| !is_computed(column))?;
+        Some(TableOrder { column: column.name.clone(), ascending: self.sort_ascending })
+    }
+
+    /// Whether sorting reloads the table rather than reordering the loaded rows. Distinct
+    /// views are always sorted locally.
+    pub fn sorts_on_server(&self) -> bool {
+        self.server_side_sorting && self.distinct.is_none() && matches!(self.source, TabSource::Table { .. })
+    }
+
+    /// Switch between sorting on the server and locally. Rows the server ordered are
+    /// sorted again locally when switching off; switching on takes a reload.
+    pub fn set_server_side_sorting(&mut self, enabled: bool) {
+        self.server_side_sorting = enabled;
+        if let (false, Some(column), Some(data)) = (enabled, self.sort_column, &mut self.data) {
+            sort_rows(&mut data.rows, column, self.sort_ascending);
+            data.touch();
+        }
+    }
+
+    /// Filters applied to the loaded rows; none when the server already applied them.
+    pub fn local_filters(&self) -> &[FilterRule] {
+        if self.server_side_filtering && self.distinct.is_none() && !self.filters_computed_columns(&self.filters) {
+            &[]
+        } else {
+            &self.filters
+        }
+    }
+
+    /// Rows that pass the tab's filters, in display order.
+    pub fn filtered_rows(&self) -> Vec<Vec<CellValue>> {
+        match &self.data {
+            Some(data) => filter_row_indices(&data.rows, self.local_filters())
+                .into_iter()
+                .map(|idx| data.rows[idx].clone())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Number of rows left after applying the tab's filters.
+    pub fn filtered_row_count(&self) -> usize {
+        self.data
+            .as_ref()
+            .map(|data| filter_row_indices(&data.rows, self.local_filters()).len())
+            .unwrap_or(0)
+    }
+
+    pub fn total_pages(&self) -> usize {
+        page_count(self.filtered_row_count(), self.page_size)
+    }
+
+    /// Keep `current_page` inside the filtered result so the grid never shows an empty page.
+    pub fn clamp_page(&mut self) {
+        self.current_page = self.current_page.min(self.total_pages() - 1);
+    }
+
+    /// Filters changed: match positions are stale and the page may now be out of range.
+    pub fn on_filters_changed(&mut self) {
+        self.search_match_index = 0;
+        self.clamp_page();
+    }
+
+    /// Search text changed: start from the first match on the first page.
+    pub fn on_search_changed(&mut self) {
+        self.search_match_index = 0;
+        self.current_page = 0;
+    }
+
+    /// Move to the next search match, wrapping around at the end.
+    pub fn next_match(&mut self, total_matches: usize) {
+        if total_matches > 0 {
+            self.search_match_index = (self.search_match_index + 1) % total_matches;
+        }
+    }
+
+    /// Move to the previous search match, wrapping around at the start.
+    pub fn previous_match(&mut self, total_matches: usize) {
+        if total_matches > 0 {
+            self.search_match_index = if self.search_match_index == 0 {
+                total_matches - 1
+            } else {
+                (self.search_match_index - 1).min(total_matches - 1)
+            };
+        }
+    }
+
+    /// Sort by `column`, flipping the direction when it is already the sort column. Tabs
+    /// sorted on the server only take note and are reordered by reloading them.
+    pub fn sort_by(&mut self, column: usize) {
+        if self.sort_column == Some(column) {
+            self.sort_ascending = !self.sort_ascending;
+        } else {
+            self.sort_column = Some(column);
+            self.sort_ascending = true;
+        }
+        let computed = self.data.as_ref().and_then(|data| data.columns.get(column)).is_some_and(is_computed);
+        if self.sorts_on_server() && !computed {
+            return;
+        }
+        if let Some(data) = &mut self.data {
+            sort_rows(&mut data.rows, column, self.sort_ascending);
+            data.touch();
+        }
+    }
+
+    pub fn set_page_size(&mut self, page_size: usize) {
+        self.page_size = page_size.max(1);
+        self.current_page = 0;
+    }
+
+    /// LIMIT/OFFSET ending a query tab's SQL, paged through by rewriting it rather than
+    /// by paging the fetched rows. None while distinct values are shown.
+    pub fn sql_page(&self) -> Option<SqlPage> {
+        match &self.source {
+            TabSource::Query { sql } if self.distinct.is_none() => sql_page(sql),
+            _ => None,
+        }
+    }
+
+    /// Rewrite the query's LIMIT/OFFSET to ask for `page`; the tab still has to run it.
+    pub fn set_sql_page(&mut self, page: SqlPage) {
+        if let TabSource::Query { sql } = &mut self.source {
+            *sql = with_sql_page(sql, page);
+            self.query_input = sql.clone();
+            self.fit_page_to_sql();
+        }
+    }
+
+    /// Show all rows of the query's LIMIT on one grid page, so its page is the grid's.
+    pub fn fit_page_to_sql(&mut self) {
+        if let Some(page) = self.sql_page() {
+            self.set_page_size(page.limit as usize);
+        }
+    }
+
+    /// Collapse the result to the distinct values of `columns` (whole rows when empty)
+    /// with occurrence counts, keeping the full rows, sort, filters and page to go back to.
+    pub fn show_distinct(&mut self, columns: &[usize]) {
+        self.show_all_rows();
+        let Some(original) = self.data.take() else {
+            return;
+        };
+        let data = distinct_rows(&original, columns);
+        // All but the trailing count column
+        let names = data.columns[..data.columns.len() - 1].iter().map(|c| c.name.clone()).collect();
+        self.distinct = Some(DistinctView {
+            columns: names,
+            original,
+            sort_column: self.sort_column.take(),
+            sort_ascending: self.sort_ascending,
+            filters: std::mem::take(&mut self.filters),
+            current_page: self.current_page,
+        });
+        self.data = Some(data);
+        self.sort_ascending = true;
+        self.current_page = 0;
+        self.search_match_index = 0;
+    }
+
+    /// Leave the distinct view, if any, for the full rows as they were.
+    pub fn show_all_rows(&mut self) {
+        if let Some(view) = self.distinct.take() {
+            self.data = Some(view.original);
+            self.sort_column = view.sort_column;
+            self.sort_ascending = view.sort_ascending;
+            self.filters = view.filters;
+            self.current_page = view.current_page;
+            self.search_match_index = 0;
+        }
+    }
+
+    /// The full result, also while a distinct view is shown.
+    pub fn result_data(&self) -> Option<&TableData> {
+        self.distinct.as_ref().map(|view| &view.original).or(self.data.as_ref())
+    }
+}
+
+/// Where the tab at `index` ends up when the tab at `from` is moved to position `to`.
+pub fn moved_index(index: usize, from: usize, to: usize) -> usize {
+    if index == from {
+        to
+    } else if from < index && index <= to {
+        index - 1
+    } else if to <= index && index < from {
+        index + 1
+    } else {
+        index
+    }
+}
+
+/// Title of a tab holding the .sql file at `path`.
+pub fn sql_file_title(path: &Path) -> String {
+    path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().to_string())
+}
+
+/// Position a tab moved from `from` towards `to` may take: pinned tabs stay in front
+/// of the others, so neither group can be dragged into the other.
+pub fn clamp_tab_move(tabs: &[Tab], from: usize, to: usize) -> usize {
+    let pinned = tabs.iter().filter(|tab| tab.pinned).count();
+    match tabs.get(from) {
+        Some(tab) if tab.pinned => to.min(pinned.saturating_sub(1)),
+        _ => to.max(pinned),
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::db::ColumnInfo;
+    use crate::models::FilterOperator;
+
+    fn tab_with_rows(count: usize) -> Tab {
+        let rows = (0..count)
+            .map(|i| vec![CellValue::Int(i as i64), CellValue::from(if i % 2 == 0 { "even" } else { "odd" })])
+            .collect();
+        let data = TableData {
+            name: "numbers".to_string(),
+            columns: vec![
+                ColumnInfo { name: "n".to_string(), data_type: "int4".to_string(), is_primary_key: true, is_foreign_key: false },
+                ColumnInfo { name: "parity".to_string(), data_type: "text".to_string(), is_primary_key: false, is_foreign_key: false },
+            ],
+            rows,
+            lineage: ColumnLineage::new(),
+            generation: 0,
+        };
+        let mut tab = Tab::new(0, "numbers".to_string(), Some(data), TabSource::Query { sql: "SELECT 1".to_string() });
+        tab.page_size = 10;
+        tab
+    }
+
+    fn equals_filter(column_index: usize, value: &str) -> FilterRule {
+        let mut rule = FilterRule::new(column_index);
+        rule.operator = FilterOperator::Equals;
+        rule.value = value.to_string();
+        rule
+    }
+
+    #[test]
+    fn test_moved_index() {
+        // Moving the first of a, b, c, d to position 2 gives b, c, a, d
+        assert_eq!((0..4).map(|index| moved_index(index, 0, 2)).collect::<Vec<_>>(), vec![2, 0, 1, 3]);
+        // Moving the last to position 1 gives a, d, b, c
+        assert_eq!((0..4).map(|index| moved_index(index, 3, 1)).collect::<Vec<_>>(), vec![0, 2, 3, 1]);
+        assert_eq!(moved_index(2, 1, 1), 2);
+    }
+
+    #[test]
+    fn test_server_filter() {
+        let mut tab = Tab::new(0, "users".to_string(), None, TabSource::Table { schema: "public".to_string(), table: "users".to_string() });
+        assert_eq!(tab.server_filter(Driver::Postgres), None);
+        assert_eq!(tab.source_sql(Driver::Postgres), "SELECT * FROM public.users");
+
+        tab.server_filters.push("\"meta\"->>'plan' = 'pro'".to_string());
+        assert_eq!(tab.source_sql(Driver::Postgres), "SELECT * FROM public.users WHERE \"meta\"->>'plan' = 'pro'");
+        tab.server_filters.push("\"meta\" @> '{\"beta\":true}' OR id = 1".to_string());
+        assert_eq!(tab.server_filter(Driver::Postgres).unwrap().sql, "(\"meta\"->>'plan' = 'pro') AND (\"meta\" @> '{\"beta\":true}' OR id = 1)");
+    }
+
+    #[test]
+    fn test_server_side_filtering() {
+        let mut tab = tab_with_rows(4);
+        tab.filters.push(equals_filter(1, "even"));
+        assert_eq!(tab.filtered_row_count(), 2);
+        assert_eq!(tab.server_filter(Driver::Postgres), None);
+
+        tab.server_side_filtering = true;
+        assert_eq!(tab.filtered_row_count(), 4);
+        let filter = tab.server_filter(Driver::Postgres).unwrap();
+        assert_eq!(filter.sql, "lower(\"parity\"::text) = lower($1::text)");
+        assert_eq!(filter.params, vec!["even".to_string()]);
+    }
+
+    #[test]
+    fn test_server_side_sorting() {
+        let mut tab = tab_with_rows(3);
+        tab.source = TabSource::Table { schema: "public".to_string(), table: "numbers".to_string() };
+        tab.server_side_sorting = true;
+        tab.sort_by(0);
+        tab.sort_by(0);
+        // Left to the server, so the loaded rows keep their order
+        assert_eq!(tab.data.as_ref().unwrap().rows[0][0], CellValue::Int(0));
+        assert_eq!(tab.server_order(), Some(TableOrder { column: "n".to_string(), ascending: false }));
+        assert_eq!(tab.source_sql(Driver::MySql), "SELECT * FROM public.numbers ORDER BY `n` DESC");
+
+        tab.set_server_side_sorting(false);
+        assert_eq!(tab.data.as_ref().unwrap().rows[0][0], CellValue::Int(2));
+        assert_eq!(tab.server_order(), None);
+    }
+
+    #[test]
+    fn test_clamp_tab_move() {
+        let mut tabs: Vec<Tab> = (0..4).map(|_| tab_with_rows(1)).collect();
+        tabs[0].pinned = true;
+        tabs[1].pinned = true;
+        assert_eq!(clamp_tab_move(&tabs, 0, 3), 1);
+        assert_eq!(clamp_tab_move(&tabs, 1, 0), 0);
+        assert_eq!(clamp_tab_move(&tabs, 3, 0), 2);
+        assert_eq!(clamp_tab_move(&tabs, 2, 3), 3);
+    }
+
+    #[test]
+    fn test_duplicate() {
+        let mut tab = tab_with_rows(3);
+        tab.pending_edits.stage(vec!["0".to_string()], 1, "even".to_string(), "changed".to_string());
+        let copy = tab.duplicate(7);
+        assert_eq!(copy.id, 7);
+        assert_eq!(copy.title, tab.title);
+        assert_eq!(copy.data.as_ref().unwrap().rows.len(), 3);
+        assert!(copy.pending_edits.is_empty());
+    }
+
+    #[test]
+    fn test_is_dirty() {
+        let mut tab = tab_with_rows(0);
+        tab.query_input = "SELECT 2".to_string();
+        assert!(!tab.is_dirty());
+
+        tab.sql_file = Some(PathBuf::from("report.sql"));
+        tab.saved_sql = "SELECT 2".to_string();
+        assert!(!tab.is_dirty());
+        tab.query_input.push_str(" + 1");
+        assert!(tab.is_dirty());
+        assert!(!tab.duplicate(1).is_dirty());
+    }
+
+    #[test]
+    fn test_distinct_view_roundtrip() {
+        let mut tab = tab_with_rows(25);
+        tab.filters.push(equals_filter(1, "even"));
+        tab.sort_by(0);
+        tab.current_page = 1;
+
+        tab.show_distinct(&[1]);
+        let data = tab.data.as_ref().unwrap();
+        assert_eq!(data.rows, vec![
+            vec![CellValue::from("even"), CellValue::Int(13)],
+            vec![CellValue::from("odd"), CellValue::Int(12)],
+        ]);
+        assert!(tab.filters.is_empty() && tab.sort_column.is_none() && tab.current_page == 0);
+        assert_eq!(tab.result_data().unwrap().rows.len(), 25);
+        assert_eq!(tab.distinct.as_ref().unwrap().describe(), "parity");
+
+        tab.show_all_rows();
+        assert!(tab.distinct.is_none());
+        assert_eq!(tab.data.as_ref().unwrap().rows.len(), 25);
+        assert_eq!(tab.filters.len(), 1);
+        assert_eq!((tab.sort_column, tab.current_page), (Some(0), 1));
+    }
+
+    #[test]
+    fn test_filter_shrinking_result_clamps_page() {
+        let mut tab = tab_with_rows(100);
+        tab.current_page = 9;
+
+        // 50 rows left -> 5 pages, last page index 4
+        tab.filters.push(equals_filter(1, "even"));
+        tab.on_filters_changed();
+        assert_eq!(tab.current_page, 4);
+        assert_eq!(tab.total_pages(), 5);
+    }
+
+    #[test]
+    fn test_filter_with_no_matches_goes_to_first_page() {
+        let mut tab = tab_with_rows(100);
+        tab.current_page = 3;
+
+        tab.filters.push(equals_filter(1, "neither"));
+        tab.on_filters_changed();
+        assert_eq!(tab.filtered_row_count(), 0);
+        assert_eq!(tab.current_page, 0);
+    }
+
+    #[test]
+    fn test_page_within_range_is_kept() {
+        let mut tab = tab_with_rows(100);
+        tab.current_page = 2;
+
+        tab.filters.push(equals_filter(1, "odd"));
+        tab.on_filters_changed();
+        assert_eq!(tab.current_page, 2, "Page 2 still exists with 50 rows");
+    }
+
+    #[test]
+    fn test_filter_change_resets_search_match() {
+        let mut tab = tab_with_rows(30);
+        tab.search_text = "1".to_string();
+        tab.search_match_index = 7;
+
+        tab.filters.push(equals_filter(1, "even"));
+        tab.on_filters_changed();
+        assert_eq!(tab.search_match_index, 0);
+    }
+
+    #[test]
+    fn test_search_change_resets_page_and_match() {
+        let mut tab = tab_with_rows(100);
+        tab.current_page = 5;
+        tab.search_match_index = 3;
+
+        tab.search_text = "4".to_string();
+        tab.on_search_changed();
+        assert_eq!(tab.current_page, 0);
+        assert_eq!(tab.search_match_index, 0);
+    }
+
+    #[test]
+    fn test_page_size_change_resets_page() {
+        let mut tab = tab_with_rows(100);
+        tab.current_page = 8;
+
+        tab.set_page_size(50);
+        assert_eq!(tab.current_page, 0);
+        assert_eq!(tab.total_pages(), 2);
+
+        tab.set_page_size(0);
+        assert_eq!(tab.page_size, 1, "Page size is never zero");
+    }
+
+    #[test]
+    fn test_sql_page_rewrites_query() {
+        let source = TabSource::Query { sql: "SELECT * FROM t LIMIT 25".to_string() };
+        let mut tab = Tab::new(0, "t".to_string(), None, source);
+        assert_eq!(tab.page_size, 25, "The grid shows the whole LIMIT");
+
+        let page = tab.sql_page().unwrap().next();
+        tab.set_sql_page(page);
+        assert!(matches!(&tab.source, TabSource::Query { sql } if sql == "SELECT * FROM t LIMIT 25 OFFSET 25"));
+        assert_eq!(tab.query_input, "SELECT * FROM t LIMIT 25 OFFSET 25");
+        assert_eq!(tab.sql_page().unwrap().number(), 1);
+
+        let table = TabSource::Table { schema: "public".to_string(), table: "t".to_string() };
+        assert!(Tab::new(1, "t".to_string(), None, table).sql_page().is_none());
+    }
+
+    #[test]
+    fn test_shown_columns() {
+        let mut tab = tab_with_rows(1);
+        assert_eq!(tab.shown_columns(), 2);
+
+        let data = tab.data.as_mut().unwrap();
+        data.columns = (0..=WIDE_TABLE_COLUMNS).map(|i| data.columns[i % 2].clone()).collect();
+        assert_eq!(tab.shown_columns(), WIDE_TABLE_SHOWN_COLUMNS);
+        tab.show_all_columns = true;
+        assert_eq!(tab.shown_columns(), WIDE_TABLE_COLUMNS + 1);
+    }
+
+    #[test]
+    fn test_remove_computed_column() {
+        let mut tab = tab_with_rows(2);
+        tab.computed_columns.push(ComputedColumn { name: "double".to_string(), expression: "n * 2".to_string() });
+        tab.computed_columns.push(ComputedColumn { name: "label".to_string(), expression: "parity || n".to_string() });
+        tab.sync_computed_columns();
+        assert_eq!(tab.data.as_ref().unwrap().rows[1][3], CellValue::from("odd1"));
+
+        tab.sort_by(3);
+        tab.filters.push(equals_filter(2, "2"));
+        tab.frozen_columns = vec![0, 3];
+        tab.remove_computed_column(2);
+        assert_eq!(tab.data.as_ref().unwrap().rows[1], [CellValue::Int(1), CellValue::from("odd"), CellValue::from("odd1")]);
+        assert_eq!((tab.sort_column, tab.filters.len(), tab.frozen_columns.as_slice()), (Some(2), 0, &[0, 2][..]));
+
+        tab.remove_computed_column(0);
+        assert_eq!(tab.computed_columns.len(), 1, "Only computed columns can be removed");
+    }
+
+    #[test]
+    fn test_removing_filters_keeps_page() {
+        let mut tab = tab_with_rows(100);
+        tab.filters.push(equals_filter(1, "even"));
+        tab.current_page = 4;
+        tab.on_filters_changed();
+
+        tab.filters.clear();
+        tab.on_filters_changed();
+        assert_eq!(tab.current_page, 4);
+        assert_eq!(tab.total_pages(), 10);
+    }
+
+    #[test]
+    fn test_sort_by_toggles_direction() {
+        let mut tab = tab_with_rows(3);
+        let first = |tab: &Tab| tab.data.as_ref().unwrap().rows[0][0].clone();
+
+        tab.sort_by(0);
+        assert_eq!((tab.sort_column, tab.sort_ascending), (Some(0), true));
+        assert_eq!(first(&tab), CellValue::Int(0));
+
+        tab.sort_by(0);
+        assert!(!tab.sort_ascending);
+        assert_eq!(first(&tab), CellValue::Int(2));
+
+        // Another column starts ascending again
+        tab.sort_by(1);
+        assert_eq!((tab.sort_column, tab.sort_ascending), (Some(1), true));
+        assert_eq!(tab.data.as_ref().unwrap().rows[0][1], CellValue::from("even"));
+    }
+
+    #[test]
+    fn test_match_navigation_wraps() {
+        let mut tab = tab_with_rows(10);
+        tab.next_match(3);
+        tab.next_match(3);
+        tab.next_match(3);
+        assert_eq!(tab.search_match_index, 0);
+
+        tab.previous_match(3);
+        assert_eq!(tab.search_match_index, 2);
+
+        // No matches leaves the index alone
+        tab.next_match(0);
+        tab.previous_match(0);
+        assert_eq!(tab.search_match_index, 2);
+    }
+
+    #[test]
+    fn test_previous_match_after_result_shrank() {
+        let mut tab = tab_with_rows(10);
+        tab.search_match_index = 9;
+        tab.previous_match(4);
+        assert_eq!(tab.search_match_index, 3);
+    }
+
+    #[test]
+    fn test_clamp_without_data() {
+        let mut tab = Tab::new(0, "empty".to_string(), None, TabSource::Query { sql: String::new() });
+        tab.current_page = 3;
+        tab.clamp_page();
+        assert_eq!(tab.current_page, 0);
+    }
     #[test]
     fn test_tab_search_reset_on_text_change() {
         // Simulate what happens when search text changes
@@ -169,4 +1158,59 @@ mod tests {
         assert_eq!(search_match_index, 0, "Should start at match index 0");
         assert_eq!(search_text, "", "Should start with empty search text");
     }
+
+    #[test]
+    fn test_cell_value_display() {
+        assert_eq!(CellValue::Null.to_string(), "(NULL)");
+        assert_eq!(CellValue::Float(1.5).to_string(), "1.5");
+        assert_eq!(CellValue::Bytes(vec![0; 3]).to_string(), "<3 bytes>");
+        let timestamp = NaiveDateTime::parse_from_str("2024-03-05 14:30:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(CellValue::Timestamp(timestamp).to_string(), "2024-03-05 14:30:00");
+        assert_eq!(CellValue::TimestampTz(timestamp.and_utc()).to_string(), "2024-03-05 14:30:00 UTC");
+
+        // Arrays read back as PostgreSQL array literals
+        let tags = CellValue::Array(vec![CellValue::from("a"), CellValue::from("b, c"), CellValue::Null, CellValue::from("say \"hi\""), CellValue::from("")]);
+        assert_eq!(tags.to_string(), r#"{a, "b, c", NULL, "say \"hi\"", ""}"#);
+        assert_eq!(CellValue::Array(vec![CellValue::Int(1), CellValue::Int(2)]).to_string(), "{1, 2}");
+        assert_eq!(CellValue::Array(vec![]).to_string(), "{}");
+        assert_eq!(CellValue::Array(vec![CellValue::Timestamp(timestamp)]).to_string(), "{\"2024-03-05 14:30:00\"}");
+    }
+
+    #[test]
+    fn test_cell_value_compare() {
+        let mut values = vec![CellValue::Int(10), CellValue::Null, CellValue::Int(9), CellValue::Float(9.5)];
+        values.sort_by(|a, b| a.compare(b));
+        assert_eq!(values, vec![CellValue::Int(9), CellValue::Float(9.5), CellValue::Int(10), CellValue::Null]);
+
+        // Decimals arrive as text but still sort as numbers
+        assert_eq!(CellValue::from("10.00").compare(&CellValue::from("9.50")), Ordering::Greater);
+        assert_eq!(CellValue::from("b").compare(&CellValue::from("a")), Ordering::Greater);
+
+        // Dates as text sort chronologically, and against typed dates
+        assert_eq!(CellValue::from("2024-02-01 09:00:00+01").compare(&CellValue::from("2024-02-01 08:30:00+00")), Ordering::Less);
+        let date = CellValue::Date(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap());
+        assert_eq!(date.compare(&CellValue::from("2024-01-01 23:59:59")), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let at = |y, m, d, h, min, s| NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, s);
+        assert_eq!(parse_timestamp("2024-03-05"), at(2024, 3, 5, 0, 0, 0));
+        assert_eq!(parse_timestamp("2024-03-05 14:30"), at(2024, 3, 5, 14, 30, 0));
+        assert_eq!(parse_timestamp("2024-03-05T14:30:15.250").map(|t| t.and_utc().timestamp_millis() % 1000), Some(250));
+        assert_eq!(parse_timestamp("2024-03-05 14:30:00 UTC"), at(2024, 3, 5, 14, 30, 0));
+        assert_eq!(parse_timestamp("2024-03-05T14:30:00Z"), at(2024, 3, 5, 14, 30, 0));
+        assert_eq!(parse_timestamp("2024-03-05 14:30:00+02"), at(2024, 3, 5, 12, 30, 0));
+        assert_eq!(parse_timestamp("2024-03-05 14:30:00-05:30"), at(2024, 3, 5, 20, 0, 0));
+        assert_eq!(parse_timestamp("2024-03"), None);
+        assert_eq!(parse_timestamp("not a date"), None);
+    }
+
+    #[test]
+    fn test_cell_value_with_text_keeps_type() {
+        assert_eq!(CellValue::Int(1).with_text("42"), CellValue::Int(42));
+        assert_eq!(CellValue::Int(1).with_text("n/a"), CellValue::from("n/a"));
+        assert_eq!(CellValue::Null.with_text("x"), CellValue::from("x"));
+        assert_eq!(CellValue::Bool(true).with_text("false"), CellValue::Bool(false));
+    }
 }
@@ -0,0 +1,131 @@
+use std::time::{Duration, Instant};
+use crate::config::Driver;
+use crate::db::sql::{quote_ident_for, quote_literal};
+use crate::models::CellValue;
+
+// Most rows fetched by a single poll; a backlog is worked off over several polls
+pub const TAIL_BATCH_SIZE: usize = 1000;
+
+/// Poll intervals offered in the tail toolbar.
+pub const TAIL_INTERVALS: [Duration; 4] = [
+    Duration::from_secs(2),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(30),
+];
+
+/// Live tail of a table tab: new rows are those whose ordering column is past the
+/// last value seen, so it only suits append-only tables keyed by an id or timestamp.
+#[derive(Debug, Clone)]
+pub struct TailState {
+    pub column: usize,
+    pub interval: Duration,
+    // Largest value of `column` seen so far; None while the table is empty
+    pub last_value: Option<String>,
+    pub last_poll: Option<Instant>,
+    pub appended: usize,
+}
+
+impl TailState {
+    pub fn new(column: usize, interval: Duration, rows: &[Vec<CellValue>]) -> Self {
+        Self {
+            column,
+            interval,
+            last_value: max_value(rows, column),
+            last_poll: None,
+            appended: 0,
+        }
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.last_poll.is_none_or(|last| now.duration_since(last) >= self.interval)
+    }
+
+    /// Time left until the next poll, for scheduling a repaint.
+    pub fn until_due(&self, now: Instant) -> Duration {
+        self.last_poll
+            .map(|last| self.interval.saturating_sub(now.duration_since(last)))
+            .unwrap_or_default()
+    }
+
+    /// Statement fetching the next batch of rows after `last_value`, oldest first.
+    pub fn query(&self, driver: Driver, schema: &str, table: &str, column_name: &str) -> String {
+        let quote = |ident: &str| quote_ident_for(driver, ident);
+        let column = quote(column_name);
+        let filter = self.last_value.as_ref()
+            .map(|value| format!(" WHERE {} > {}", column, quote_literal(value)))
+            .unwrap_or_default();
+        format!(
+            "SELECT * FROM {}.{}{} ORDER BY {} LIMIT {}",
+            quote(schema), quote(table), filter, column, TAIL_BATCH_SIZE
+        )
+    }
+
+    /// Remember how far a poll got. Rows arrive ordered by the tail column.
+    pub fn record(&mut self, rows: &[Vec<CellValue>]) {
+        if let Some(value) = rows.last().and_then(|row| row.get(self.column)).filter(|value| !value.is_null()) {
+            self.last_value = Some(value.to_string());
+        }
+        self.appended += rows.len();
+    }
+}
+
+/// Largest non-NULL value in `column`, as text for the next poll's filter.
+pub fn max_value(rows: &[Vec<CellValue>], column: usize) -> Option<String> {
+    rows.iter()
+        .filter_map(|row| row.get(column))
+        .filter(|value| !value.is_null())
+        .max_by(|a, b| a.compare(b))
+        .map(CellValue::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(values: &[&str]) -> Vec<Vec<CellValue>> {
+        let cell = |v: &str| match v {
+            "(NULL)" => CellValue::Null,
+            _ => v.parse().map(CellValue::Int).unwrap_or_else(|_| CellValue::from(v)),
+        };
+        values.iter().map(|v| vec![CellValue::from("x"), cell(v)]).collect()
+    }
+
+    #[test]
+    fn test_max_value() {
+        assert_eq!(max_value(&rows(&["9", "10", "(NULL)", "2"]), 1).as_deref(), Some("10"));
+        assert_eq!(
+            max_value(&rows(&["2024-03-01 10:00:00", "2024-12-01 09:00:00", "2024-11-30 23:59:59"]), 1).as_deref(),
+            Some("2024-12-01 09:00:00")
+        );
+        assert_eq!(max_value(&rows(&["(NULL)"]), 1), None);
+    }
+
+    #[test]
+    fn test_query_and_record() {
+        let mut tail = TailState::new(1, Duration::from_secs(5), &rows(&["41", "42"]));
+        assert_eq!(
+            tail.query(Driver::Postgres, "public", "events", "id"),
+            "SELECT * FROM \"public\".\"events\" WHERE \"id\" > '42' ORDER BY \"id\" LIMIT 1000"
+        );
+
+        tail.record(&rows(&["43", "44"]));
+        assert_eq!(tail.last_value.as_deref(), Some("44"));
+        assert_eq!(tail.appended, 2);
+
+        let empty = TailState::new(0, Duration::from_secs(5), &[]);
+        assert_eq!(empty.query(Driver::MySql, "app", "log", "ts"), "SELECT * FROM `app`.`log` ORDER BY `ts` LIMIT 1000");
+    }
+
+    #[test]
+    fn test_is_due() {
+        let now = Instant::now();
+        let mut tail = TailState::new(0, Duration::from_secs(5), &[]);
+        assert!(tail.is_due(now));
+
+        tail.last_poll = Some(now);
+        assert!(!tail.is_due(now + Duration::from_secs(1)));
+        assert!(tail.is_due(now + Duration::from_secs(5)));
+        assert_eq!(tail.until_due(now + Duration::from_secs(2)), Duration::from_secs(3));
+    }
+}
@@ -0,0 +1,198 @@
+use std::ops::Range;
+use crate::models::{filter_row_indices, CellValue, FilterRule};
+
+/// Position of a search hit in the filtered view.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchMatch {
+    pub filtered_row: usize,
+    pub original_row: usize,
+    pub column: usize,
+}
+
+/// What the grid shows for a tab's rows under its filters and search: the rows that
+/// pass, in display order, and where the current search match is. Kept free of UI
+/// types so paging, filtering and search can be tested without an egui context.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TabViewModel {
+    // Indices into the tab's rows of those passing the filters
+    pub filtered_indices: Vec<usize>,
+    pub total_matches: usize,
+    pub current_match: Option<SearchMatch>,
+}
+
+impl TabViewModel {
+    pub fn new(rows: &[Vec<CellValue>], filters: &[FilterRule], search_text: &str, current_match_index: usize) -> Self {
+        let filtered_indices = filter_row_indices(rows, filters);
+
+        let search_lower = search_text.to_lowercase();
+        let mut total_matches = 0;
+        let mut current_match = None;
+        if !search_lower.is_empty() {
+            for (filtered_row, &original_row) in filtered_indices.iter().enumerate() {
+                for (column, cell) in rows[original_row].iter().enumerate() {
+                    if cell_matches(cell, &search_lower) {
+                        if total_matches == current_match_index {
+                            current_match = Some(SearchMatch { filtered_row, original_row, column });
+                        }
+                        total_matches += 1;
+                    }
+                }
+            }
+        }
+
+        Self { filtered_indices, total_matches, current_match }
+    }
+
+    pub fn filtered_rows(&self) -> usize {
+        self.filtered_indices.len()
+    }
+
+    /// Positions in the filtered view shown on `page`.
+    pub fn page_range(&self, page: usize, page_size: usize) -> Range<usize> {
+        page_range(self.filtered_rows(), page, page_size)
+    }
+
+    /// Page holding the current match and its row within that page.
+    pub fn current_match_location(&self, page_size: usize) -> Option<(usize, usize)> {
+        let page_size = page_size.max(1);
+        self.current_match.map(|m| (m.filtered_row / page_size, m.filtered_row % page_size))
+    }
+}
+
+/// Whether `cell` contains the already lowercased search text. NULL cells have no text
+/// to match, whatever their placeholder reads.
+pub fn cell_matches(cell: &CellValue, search_lower: &str) -> bool {
+    !cell.is_null() && cell.to_string().to_lowercase().contains(search_lower)
+}
+
+/// Pages needed for `total_rows`; an empty result still has one (empty) page.
+pub fn page_count(total_rows: usize, page_size: usize) -> usize {
+    total_rows.div_ceil(page_size.max(1)).max(1)
+}
+
+/// Rows of `page`, cut off at the end of the result.
+pub fn page_range(total_rows: usize, page: usize, page_size: usize) -> Range<usize> {
+    let start = page.saturating_mul(page_size).min(total_rows);
+    let end = start.saturating_add(page_size).min(total_rows);
+    start..end
+}
+
+/// Sort rows by the cells' types in `column`; rows too short to have the column go first.
+pub fn sort_rows(rows: &mut [Vec<CellValue>], column: usize, ascending: bool) {
+    rows.sort_by(|a, b| {
+        let cmp = match (a.get(column), b.get(column)) {
+            (Some(a_val), Some(b_val)) => a_val.compare(b_val),
+            (a_val, b_val) => a_val.is_some().cmp(&b_val.is_some()),
+        };
+
+        if ascending { cmp } else { cmp.reverse() }
+    });
+}
+
+/// Column header text with the sort direction arrow when the grid is sorted by it.
+pub fn header_label(name: &str, column: usize, sort_column: Option<usize>, sort_ascending: bool) -> String {
+    match (sort_column == Some(column), sort_ascending) {
+        (true, true) => format!("{} ▲", name),
+        (true, false) => format!("{} ▼", name),
+        (false, _) => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FilterOperator;
+
+    fn rows(values: &[[&str; 2]]) -> Vec<Vec<CellValue>> {
+        values.iter().map(|row| row.iter().map(|&v| CellValue::from(v)).collect()).collect()
+    }
+
+    fn fruit() -> Vec<Vec<CellValue>> {
+        rows(&[
+            ["apple", "banana"],
+            ["cherry", "apple"],
+            ["date", "elderberry"],
+            ["apple", "fig"],
+            ["grape", "apple"],
+        ])
+    }
+
+    #[test]
+    fn test_search_match_counting() {
+        assert_eq!(TabViewModel::new(&fruit(), &[], "apple", 0).total_matches, 4);
+        assert_eq!(TabViewModel::new(&fruit(), &[], "xyz", 0).total_matches, 0);
+        // Empty search highlights nothing
+        let view = TabViewModel::new(&fruit(), &[], "", 0);
+        assert_eq!((view.total_matches, view.current_match), (0, None));
+    }
+
+    #[test]
+    fn test_search_case_insensitive_and_partial() {
+        let data = rows(&[["Pineapple", "BANANA"], ["cherry", "aPpLeSauce"]]);
+        assert_eq!(TabViewModel::new(&data, &[], "APPLE", 0).total_matches, 2);
+    }
+
+    #[test]
+    fn test_search_skips_null_cells() {
+        let data = vec![vec![CellValue::Null, CellValue::from("null")]];
+        let view = TabViewModel::new(&data, &[], "null", 0);
+        assert_eq!(view.total_matches, 1);
+        assert_eq!(view.current_match.map(|m| m.column), Some(1));
+    }
+
+    #[test]
+    fn test_current_match_location() {
+        let view = TabViewModel::new(&fruit(), &[], "apple", 2);
+        assert_eq!(view.current_match, Some(SearchMatch { filtered_row: 3, original_row: 3, column: 0 }));
+        assert_eq!(view.current_match_location(3), Some((1, 0)));
+
+        let last = TabViewModel::new(&fruit(), &[], "apple", 3);
+        assert_eq!(last.current_match_location(3), Some((1, 1)));
+        // Index past the last match, e.g. after the result shrank
+        assert_eq!(TabViewModel::new(&fruit(), &[], "apple", 4).current_match, None);
+    }
+
+    #[test]
+    fn test_search_only_counts_filtered_rows() {
+        let filter = FilterRule { operator: FilterOperator::Equals, value: "apple".to_string(), ..FilterRule::new(0) };
+        let view = TabViewModel::new(&fruit(), &[filter], "apple", 1);
+        assert_eq!(view.filtered_indices, vec![0, 3]);
+        assert_eq!(view.total_matches, 2);
+        // Second match is the second filtered row, which is row 3 of the data
+        assert_eq!(view.current_match, Some(SearchMatch { filtered_row: 1, original_row: 3, column: 0 }));
+    }
+
+    #[test]
+    fn test_paging() {
+        assert_eq!(page_count(0, 10), 1);
+        assert_eq!(page_count(10, 10), 1);
+        assert_eq!(page_count(11, 10), 2);
+        assert_eq!(page_range(25, 0, 10), 0..10);
+        assert_eq!(page_range(25, 2, 10), 20..25);
+        // Page past the end is empty rather than out of bounds
+        assert_eq!(page_range(25, 5, 10), 25..25);
+    }
+
+    #[test]
+    fn test_sort_rows() {
+        let mut data = vec![
+            vec![CellValue::Int(10)],
+            vec![CellValue::Null],
+            vec![CellValue::Int(9)],
+            vec![],
+        ];
+        sort_rows(&mut data, 0, true);
+        assert_eq!(data, vec![vec![], vec![CellValue::Int(9)], vec![CellValue::Int(10)], vec![CellValue::Null]]);
+
+        sort_rows(&mut data, 0, false);
+        assert_eq!(data[0], vec![CellValue::Null]);
+        assert_eq!(data[1], vec![CellValue::Int(10)]);
+    }
+
+    #[test]
+    fn test_header_label() {
+        assert_eq!(header_label("id", 0, Some(0), true), "id ▲");
+        assert_eq!(header_label("id", 0, Some(0), false), "id ▼");
+        assert_eq!(header_label("name", 1, Some(0), true), "name");
+    }
+}
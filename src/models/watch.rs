@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use crate::config::WatchDefinition;
+use crate::models::CellValue;
+
+/// Refresh intervals offered when adding a watch.
+pub const WATCH_INTERVALS: [Duration; 4] = [
+    Duration::from_secs(5),
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+    Duration::from_secs(300),
+];
+
+// Numeric readings kept for the sparkline
+const WATCH_HISTORY: usize = 60;
+
+/// A watch and what its query returned so far this session.
+#[derive(Debug, Clone)]
+pub struct Watch {
+    pub definition: WatchDefinition,
+    // First column of the first row of the latest successful run
+    pub value: Option<CellValue>,
+    pub error: Option<String>,
+    // Numeric readings, oldest first
+    pub history: VecDeque<f64>,
+    pub last_poll: Option<Instant>,
+}
+
+impl Watch {
+    pub fn new(definition: WatchDefinition) -> Self {
+        Self {
+            definition,
+            value: None,
+            error: None,
+            history: VecDeque::new(),
+            last_poll: None,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.definition.interval_secs.max(1))
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.last_poll.is_none_or(|last| now.duration_since(last) >= self.interval())
+    }
+
+    /// Time left until the next refresh, for scheduling a repaint.
+    pub fn until_due(&self, now: Instant) -> Duration {
+        self.last_poll
+            .map(|last| self.interval().saturating_sub(now.duration_since(last)))
+            .unwrap_or_default()
+    }
+
+    /// Keep the scalar a run returned; an empty result reads as NULL.
+    pub fn record(&mut self, result: Result<&[Vec<CellValue>], String>) {
+        match result {
+            Ok(rows) => {
+                let value = rows.first().and_then(|row| row.first()).cloned().unwrap_or(CellValue::Null);
+                if let Some(number) = value.as_f64() {
+                    self.history.push_back(number);
+                    if self.history.len() > WATCH_HISTORY {
+                        self.history.pop_front();
+                    }
+                }
+                self.value = Some(value);
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    /// Change between the last two numeric readings.
+    pub fn delta(&self) -> Option<f64> {
+        let mut recent = self.history.iter().rev();
+        let (last, previous) = (recent.next()?, recent.next()?);
+        Some(last - previous)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watch() -> Watch {
+        Watch::new(WatchDefinition { name: "failed jobs".to_string(), sql: "SELECT 1".to_string(), interval_secs: 30 })
+    }
+
+    #[test]
+    fn test_record_and_delta() {
+        let mut watch = watch();
+        watch.record(Ok(&[vec![CellValue::Int(12), CellValue::from("ignored")]]));
+        assert_eq!(watch.delta(), None);
+
+        watch.record(Ok(&[vec![CellValue::from("9.5")]]));
+        assert_eq!(watch.delta(), Some(-2.5));
+
+        // Errors keep the last value and history
+        watch.record(Err("relation does not exist".to_string()));
+        assert_eq!(watch.value, Some(CellValue::from("9.5")));
+        assert_eq!(watch.error.as_deref(), Some("relation does not exist"));
+
+        watch.record(Ok(&[]));
+        assert_eq!((watch.value.clone(), watch.error.clone(), watch.history.len()), (Some(CellValue::Null), None, 2));
+
+        for i in 0..WATCH_HISTORY {
+            watch.record(Ok(&[vec![CellValue::Int(i as i64)]]));
+        }
+        assert_eq!(watch.history.len(), WATCH_HISTORY);
+        assert_eq!(watch.history.front(), Some(&0.0));
+    }
+
+    #[test]
+    fn test_due() {
+        let mut watch = watch();
+        let now = Instant::now();
+        assert!(watch.is_due(now));
+
+        watch.last_poll = Some(now);
+        assert!(!watch.is_due(now + Duration::from_secs(29)));
+        assert!(watch.is_due(now + Duration::from_secs(30)));
+        assert_eq!(watch.until_due(now + Duration::from_secs(10)), Duration::from_secs(20));
+    }
+}
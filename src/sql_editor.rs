@@ -1,5 +1,7 @@
 use eframe::egui;
 use std::collections::{HashSet, HashMap};
+use crate::config::{Driver, Snippet};
+use crate::db::{ColumnInfo, SchemaInfo};
 
 pub struct SqlEditor {
     // Autocomplete state
@@ -7,15 +9,31 @@ pub struct SqlEditor {
     suggestions: Vec<Suggestion>,
     selected_suggestion: usize,
     cursor_pos: usize,
+    // Where to put the cursor on the next frame after text was inserted from outside
+    moved_cursor: Option<usize>,
+    // Selected character range as (start, end), None when nothing is selected
+    selection: Option<(usize, usize)>,
     word_start: usize,
     // Track table aliases (alias -> table_name)
     table_aliases: HashMap<String, String>,
+    // Tables named after FROM and JOIN, whose columns are offered without a qualifier
+    referenced_tables: Vec<String>,
+    // Placeholders of the last expanded snippet or function still ahead, as (start, end)
+    // ranges that Tab selects in turn
+    snippet_stops: Vec<(usize, usize)>,
+    // Length of the text at the end of the last frame, to follow the stops through edits
+    text_len: usize,
+    editor_id: Option<egui::Id>,
 }
 
 #[derive(Clone, Debug)]
 pub struct Suggestion {
     pub text: String,
     pub kind: SuggestionKind,
+    // Shown after the text, e.g. the data type of a column
+    pub detail: Option<String>,
+    // Inserted instead of the text, with placeholders to stop at (see `expand_template`)
+    pub template: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -23,9 +41,12 @@ pub enum SuggestionKind {
     Table,
     Column,
     Keyword,
+    Function,
+    Snippet,
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[allow(dead_code)]
 enum SqlToken {
     Keyword(String),
     Identifier(String),
@@ -65,8 +86,14 @@ impl SqlEditor {
             suggestions: Vec::new(),
             selected_suggestion: 0,
             cursor_pos: 0,
+            moved_cursor: None,
+            selection: None,
             word_start: 0,
             table_aliases: HashMap::new(),
+            referenced_tables: Vec::new(),
+            snippet_stops: Vec::new(),
+            text_len: 0,
+            editor_id: None,
         }
     }
 
@@ -74,11 +101,14 @@ impl SqlEditor {
         &mut self,
         ui: &mut egui::Ui,
         sql: &mut String,
-        tables: &[String],
+        schemas: &[SchemaInfo],
         columns: &[String],
+        snippets: &[Snippet],
+        driver: Driver,
     ) -> SqlEditorResponse {
         let mut response = SqlEditorResponse {
             execute: false,
+            execute_selection: false,
             text_changed: false,
         };
 
@@ -88,7 +118,12 @@ impl SqlEditor {
         let mut navigate_down = false;
         let mut navigate_up = false;
         let mut close_suggestions = false;
-        let mut new_cursor_pos: Option<usize> = None;
+        // Cursor, or the range to select, to apply once the text edit exists
+        let mut new_cursor_pos: Option<(usize, usize)> = self.moved_cursor.take().map(|pos| (pos, pos));
+        // Text changed from outside since the last frame, so the stops point elsewhere
+        if new_cursor_pos.is_some() || sql.len() != self.text_len {
+            self.snippet_stops.clear();
+        }
 
         if self.show_suggestions && !self.suggestions.is_empty() {
             ui.input_mut(|i| {
@@ -125,14 +160,23 @@ impl SqlEditor {
 
             // Apply suggestion acceptance
             if accept_suggestion {
-                if let Some(suggestion) = self.suggestions.get(self.selected_suggestion) {
-                    let suggestion_text = suggestion.text.clone();
-                    new_cursor_pos = Some(self.insert_suggestion(sql, &suggestion_text));
+                if let Some(suggestion) = self.suggestions.get(self.selected_suggestion).cloned() {
+                    new_cursor_pos = Some(self.insert_suggestion(sql, &suggestion));
                     self.show_suggestions = false;
                     response.text_changed = true;
                 }
             }
+        } else if !self.snippet_stops.is_empty() && self.editor_id.is_some_and(|id| ui.memory(|m| m.has_focus(id))) {
+            // Tab moves on to the next placeholder, Escape leaves them
+            ui.input_mut(|i| {
+                if i.consume_key(egui::Modifiers::NONE, egui::Key::Tab) {
+                    new_cursor_pos = Some(self.snippet_stops.remove(0));
+                } else if i.key_pressed(egui::Key::Escape) {
+                    self.snippet_stops.clear();
+                }
+            });
         }
+        let len_before_edit = sql.len();
 
         // Create the text edit with syntax highlighting
         let mut layouter = |ui: &egui::Ui, string: &str, wrap_width: f32| {
@@ -148,39 +192,55 @@ impl SqlEditor {
             .layouter(&mut layouter);
 
         let text_response = ui.add(text_edit);
+        self.editor_id = Some(text_response.id);
 
-        // If we just inserted a suggestion, set the cursor position
-        if let Some(pos) = new_cursor_pos {
+        // If we just inserted a suggestion, set the cursor position or select its placeholder
+        if let Some((start, end)) = new_cursor_pos {
             if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), text_response.id) {
-                let ccursor = egui::text::CCursor::new(pos);
-                state.cursor.set_char_range(Some(egui::text::CCursorRange::one(ccursor)));
+                let range = egui::text::CCursorRange::two(egui::text::CCursor::new(start), egui::text::CCursor::new(end));
+                state.cursor.set_char_range(Some(range));
                 state.store(ui.ctx(), text_response.id);
             }
         }
 
-        // Track cursor position - get it from the text edit state
-        let cursor_pos = if let Some(state) = egui::TextEdit::load_state(ui.ctx(), text_response.id) {
-            if let Some(range) = state.cursor.char_range() {
-                range.primary.index
-            } else {
-                sql.len()
+        // Typing over a placeholder moves the ones after it
+        if text_response.changed() && !self.snippet_stops.is_empty() {
+            let delta = sql.len() as isize - len_before_edit as isize;
+            for (start, end) in &mut self.snippet_stops {
+                *start = start.saturating_add_signed(delta);
+                *end = end.saturating_add_signed(delta);
             }
+        }
+
+        // Track cursor position - get it from the text edit state
+        let char_range = egui::TextEdit::load_state(ui.ctx(), text_response.id).and_then(|state| state.cursor.char_range());
+        let cursor_pos = if let Some(range) = char_range {
+            range.primary.index
         } else {
             sql.len()
         };
         self.cursor_pos = cursor_pos;
+        // Kept after the editor loses focus, so the Execute button still sees it
+        self.selection = char_range
+            .map(|range| (range.primary.index.min(range.secondary.index), range.primary.index.max(range.secondary.index)))
+            .filter(|(start, end)| start != end);
 
         // Check for text changes
         if text_response.changed() {
             response.text_changed = true;
-            self.update_suggestions(sql, tables, columns);
+            self.update_suggestions(sql, schemas, columns, snippets, driver);
         }
 
-        // Handle Cmd/Ctrl+Enter to execute (only when autocomplete is not showing)
+        // Handle Cmd/Ctrl+Enter to execute and Cmd/Ctrl+Shift+Enter to execute the
+        // selection (only when autocomplete is not showing)
         if text_response.has_focus() && !self.show_suggestions {
             ui.input(|i| {
                 if i.key_pressed(egui::Key::Enter) && i.modifiers.command {
-                    response.execute = true;
+                    if i.modifiers.shift {
+                        response.execute_selection = true;
+                    } else {
+                        response.execute = true;
+                    }
                 }
             });
         }
@@ -233,7 +293,7 @@ impl SqlEditor {
                             .collect();
 
                         let selected = self.selected_suggestion;
-                        let mut clicked_suggestion: Option<String> = None;
+                        let mut clicked_suggestion: Option<Suggestion> = None;
 
                         let selection_color = ui.style().visuals.selection.bg_fill;
 
@@ -245,6 +305,8 @@ impl SqlEditor {
                                     SuggestionKind::Table => ("📋", egui::Color32::from_rgb(100, 150, 255)),
                                     SuggestionKind::Column => ("📊", egui::Color32::from_rgb(100, 200, 100)),
                                     SuggestionKind::Keyword => ("🔑", egui::Color32::from_rgb(255, 150, 200)),
+                                    SuggestionKind::Function => ("ƒ", egui::Color32::from_rgb(230, 170, 60)),
+                                    SuggestionKind::Snippet => ("✂", egui::Color32::from_rgb(170, 130, 230)),
                                 };
 
                                 let button = egui::Button::new(
@@ -258,14 +320,19 @@ impl SqlEditor {
                                 })
                                 .frame(false);
 
-                                if ui.add(button).clicked() {
-                                    clicked_suggestion = Some(suggestion.text.clone());
-                                }
+                                ui.horizontal(|ui| {
+                                    if ui.add(button).clicked() {
+                                        clicked_suggestion = Some(suggestion.clone());
+                                    }
+                                    if let Some(detail) = &suggestion.detail {
+                                        ui.label(egui::RichText::new(detail).small().color(egui::Color32::GRAY));
+                                    }
+                                });
                             }
                         });
 
-                        if let Some(text) = clicked_suggestion {
-                            let _new_pos = self.insert_suggestion(sql, &text);
+                        if let Some(suggestion) = clicked_suggestion {
+                            let _new_pos = self.insert_suggestion(sql, &suggestion);
                             self.show_suggestions = false;
                             // Note: cursor position won't be set immediately on click,
                             // but will be correct on next frame
@@ -274,9 +341,18 @@ impl SqlEditor {
                 });
         }
 
+        self.text_len = sql.len();
         response
     }
 
+    /// The highlighted part of `sql`, or None when nothing but whitespace is selected.
+    pub fn selected_text(&self, sql: &str) -> Option<String> {
+        let (start, end) = self.selection?;
+        let byte_offset = |index: usize| sql.char_indices().nth(index).map(|(i, _)| i).unwrap_or(sql.len());
+        let text = sql[byte_offset(start)..byte_offset(end)].trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
     fn highlight_sql(ui: &egui::Ui, text: &str) -> egui::text::LayoutJob {
         let mut job = egui::text::LayoutJob::default();
 
@@ -320,6 +396,7 @@ impl SqlEditor {
 
     fn extract_table_aliases(&mut self, sql: &str) {
         self.table_aliases.clear();
+        self.referenced_tables.clear();
 
         let tokens = self.tokenize(sql);
         let mut i = 0;
@@ -341,9 +418,16 @@ impl SqlEditor {
                             _ => None,
                         };
 
-                        if let Some(table) = table_name {
+                        if let Some(mut table) = table_name {
                             i += 1;
 
+                            // Schema-qualified name: schema.table
+                            while let (Some(SqlToken::Dot), Some(SqlToken::Identifier(part) | SqlToken::QuotedIdentifier(part))) = (tokens.get(i), tokens.get(i + 1)) {
+                                table = format!("{}.{}", table, part);
+                                i += 2;
+                            }
+                            self.referenced_tables.push(table.clone());
+
                             // Skip whitespace
                             while i < tokens.len() && matches!(tokens[i], SqlToken::Whitespace) {
                                 i += 1;
@@ -386,7 +470,7 @@ impl SqlEditor {
         }
     }
 
-    fn update_suggestions(&mut self, sql: &str, tables: &[String], columns: &[String]) {
+    fn update_suggestions(&mut self, sql: &str, schemas: &[SchemaInfo], columns: &[String], snippets: &[Snippet], driver: Driver) {
         // Extract table aliases from the SQL
         self.extract_table_aliases(sql);
 
@@ -417,27 +501,31 @@ impl SqlEditor {
         if let Some(qual) = qualifier {
             let partial_lower = partial_name.to_lowercase();
 
+            // Only the part after the dot is replaced
+            self.word_start += qual.len() + 1;
+
             // Check if the qualifier is an alias and resolve it to a table name
-            let resolved_table = self.table_aliases.get(&qual.to_lowercase());
-
-            // TODO: Ideally we would fetch columns specifically for the resolved table
-            // from the database. For now, we show all available columns from the current result set.
-            // This works well when the current tab has data from the same table being referenced.
-            if resolved_table.is_some() || !qual.is_empty() {
-                for column in columns {
-                    if column.to_lowercase().starts_with(&partial_lower) {
-                        suggestions.push(Suggestion {
-                            text: column.clone(),
-                            kind: SuggestionKind::Column,
-                        });
+            let table = self.table_aliases.get(&qual.to_lowercase()).map_or(qual, String::as_str);
+
+            if let Some(table_columns) = table_columns(schemas, table) {
+                suggestions.extend(schema_column_suggestions(table_columns, &partial_lower));
+            } else if let Some(schema) = schemas.iter().find(|schema| schema.name.eq_ignore_ascii_case(qual)) {
+                // "schema." lists what can be selected from in it
+                for table in schema.relations() {
+                    if table.to_lowercase().starts_with(&partial_lower) {
+                        suggestions.push(Suggestion { text: table.clone(), kind: SuggestionKind::Table, detail: None, template: None });
                     }
                 }
+            } else {
+                // Unknown to the schema, e.g. a subquery alias: the current result's columns
+                suggestions.extend(result_column_suggestions(columns, &partial_lower));
             }
         } else {
             // No qualifier, use context-based suggestions
             match state {
                 ParserState::AfterFrom | ParserState::AfterJoin => {
                     // ONLY show tables after FROM or JOIN
+                    let tables = schemas.iter().flat_map(|schema| schema.relations().map(move |table| format!("{}.{}", schema.name, table)));
                     for table in tables {
                         // Match against either full name (schema.table) or just table name
                         let table_lower = table.to_lowercase();
@@ -452,44 +540,36 @@ impl SqlEditor {
 
                         if matches {
                             suggestions.push(Suggestion {
-                                text: table.clone(),
+                                text: table,
                                 kind: SuggestionKind::Table,
+                                detail: None,
+                                template: None,
                             });
                         }
                     }
                 }
             ParserState::InSelect => {
                 // In SELECT clause: show columns and some keywords
-                for column in columns {
-                    if column.to_lowercase().starts_with(&word_lower) {
-                        suggestions.push(Suggestion {
-                            text: column.clone(),
-                            kind: SuggestionKind::Column,
-                        });
-                    }
-                }
+                suggestions.extend(self.column_suggestions(schemas, columns, &word_lower));
+                suggestions.extend(function_suggestions(driver, &word_lower));
 
-                // Only show relevant keywords for SELECT (including aggregate functions)
-                let select_keywords = ["distinct", "all", "as", "from", "count", "sum", "avg", "min", "max", "cast", "coalesce"];
+                // Only show relevant keywords for SELECT
+                let select_keywords = ["distinct", "all", "as", "from"];
                 for keyword in select_keywords.iter() {
                     if keyword.starts_with(&word_lower) {
                         suggestions.push(Suggestion {
                             text: keyword.to_string(),
                             kind: SuggestionKind::Keyword,
+                            detail: None,
+                            template: None,
                         });
                     }
                 }
             }
             ParserState::InWhere | ParserState::InHaving => {
                 // In WHERE/HAVING clause: show columns and comparison keywords
-                for column in columns {
-                    if column.to_lowercase().starts_with(&word_lower) {
-                        suggestions.push(Suggestion {
-                            text: column.clone(),
-                            kind: SuggestionKind::Column,
-                        });
-                    }
-                }
+                suggestions.extend(self.column_suggestions(schemas, columns, &word_lower));
+                suggestions.extend(function_suggestions(driver, &word_lower));
 
                 // Show WHERE/HAVING-relevant keywords
                 let where_keywords = ["and", "or", "not", "in", "like", "between", "is", "null"];
@@ -498,20 +578,16 @@ impl SqlEditor {
                         suggestions.push(Suggestion {
                             text: keyword.to_string(),
                             kind: SuggestionKind::Keyword,
+                            detail: None,
+                            template: None,
                         });
                     }
                 }
             }
             ParserState::InOrderBy | ParserState::InGroupBy => {
                 // In ORDER BY or GROUP BY: show columns
-                for column in columns {
-                    if column.to_lowercase().starts_with(&word_lower) {
-                        suggestions.push(Suggestion {
-                            text: column.clone(),
-                            kind: SuggestionKind::Column,
-                        });
-                    }
-                }
+                suggestions.extend(self.column_suggestions(schemas, columns, &word_lower));
+                suggestions.extend(function_suggestions(driver, &word_lower));
 
                 // Show ordering keywords
                 let order_keywords = ["asc", "desc"];
@@ -520,6 +596,8 @@ impl SqlEditor {
                         suggestions.push(Suggestion {
                             text: keyword.to_string(),
                             kind: SuggestionKind::Keyword,
+                            detail: None,
+                            template: None,
                         });
                     }
                 }
@@ -532,6 +610,8 @@ impl SqlEditor {
                         suggestions.push(Suggestion {
                             text: keyword.to_string(),
                             kind: SuggestionKind::Keyword,
+                            detail: None,
+                            template: None,
                         });
                     }
                 }
@@ -548,6 +628,8 @@ impl SqlEditor {
                             suggestions.push(Suggestion {
                                 text: keyword.to_string(),
                                 kind: SuggestionKind::Keyword,
+                                detail: None,
+                                template: None,
                             });
                         }
                     }
@@ -555,6 +637,11 @@ impl SqlEditor {
             }
         }
 
+        // Snippets wherever an expression or statement may start
+        if qualifier.is_none() && !matches!(state, ParserState::AfterAs | ParserState::AfterFrom | ParserState::AfterJoin) {
+            suggestions.extend(snippet_suggestions(snippets, &word_lower));
+        }
+
         suggestions.sort_by(|a, b| a.text.cmp(&b.text));
 
         self.show_suggestions = !suggestions.is_empty();
@@ -562,6 +649,23 @@ impl SqlEditor {
         self.selected_suggestion = 0;
     }
 
+    /// Columns starting with `partial` (lowercase) of the tables the statement selects
+    /// from, with their types; the current result's columns when the schema knows none
+    /// of those tables.
+    fn column_suggestions(&self, schemas: &[SchemaInfo], columns: &[String], partial: &str) -> Vec<Suggestion> {
+        let resolved: Vec<_> = self.referenced_tables.iter().filter_map(|table| table_columns(schemas, table)).collect();
+        if resolved.is_empty() {
+            return result_column_suggestions(columns, partial).collect();
+        }
+
+        // A column shared by joined tables is offered once
+        let mut seen = HashSet::new();
+        resolved.into_iter()
+            .flat_map(|table_columns| schema_column_suggestions(table_columns, partial))
+            .filter(|suggestion| seen.insert(suggestion.text.clone()))
+            .collect()
+    }
+
     fn tokenize(&self, sql: &str) -> Vec<SqlToken> {
         let mut tokens = Vec::new();
         let mut chars = sql.chars().peekable();
@@ -687,7 +791,7 @@ impl SqlEditor {
                         chars.next();
                         // Consume until */
                         let mut prev = ' ';
-                        while let Some(next_c) = chars.next() {
+                        for next_c in chars.by_ref() {
                             if prev == '*' && next_c == '/' {
                                 break;
                             }
@@ -787,11 +891,11 @@ impl SqlEditor {
                             // BY follows ORDER or GROUP
                             // State should already be set correctly
                         }
-                        "as" => {
+                        "as" if state == ParserState::AfterTableName => {
                             // After table name, AS introduces an alias
-                            if state == ParserState::AfterTableName {
-                                state = ParserState::AfterAs;
-                            }
+                            state = ParserState::AfterAs;
+                        }
+                        "as" => {
                             // Otherwise, AS might be in SELECT clause, don't change state
                         }
                         "on" | "and" | "or" | "in" | "like" | "between" | "is" => {
@@ -824,9 +928,8 @@ impl SqlEditor {
                 }
                 SqlToken::Comma => {
                     // Comma means we're continuing in the same clause
-                    match state {
-                        ParserState::AfterTableName => state = ParserState::AfterFrom,
-                        _ => {}
+                    if state == ParserState::AfterTableName {
+                        state = ParserState::AfterFrom;
                     }
                 }
                 SqlToken::Star | SqlToken::Dot | SqlToken::Operator(_) | SqlToken::LeftParen
@@ -872,7 +975,22 @@ impl SqlEditor {
         (word, start)
     }
 
-    fn insert_suggestion(&mut self, text: &mut String, suggestion: &str) -> usize {
+    /// Insert `insertion` at the cursor (replacing the selection), moving the cursor
+    /// after it on the next frame.
+    pub fn insert_at_cursor(&mut self, text: &mut String, insertion: &str) {
+        let (start, end) = self.selection.unwrap_or((self.cursor_pos, self.cursor_pos));
+        // The editor counts characters, not bytes
+        let byte_index = |chars: usize| text.char_indices().nth(chars).map_or(text.len(), |(index, _)| index);
+        let (start_byte, end_byte) = (byte_index(start), byte_index(end));
+        text.replace_range(start_byte..end_byte, insertion);
+        self.cursor_pos = start + insertion.chars().count();
+        self.selection = None;
+        self.moved_cursor = Some(self.cursor_pos);
+    }
+
+    /// Replace the word being typed with `suggestion`, returning the range to select:
+    /// the first placeholder of a template, or the cursor after the text.
+    fn insert_suggestion(&mut self, text: &mut String, suggestion: &Suggestion) -> (usize, usize) {
         // Ensure word_start and cursor_pos are at valid UTF-8 boundaries
         let safe_start = if text.is_char_boundary(self.word_start) {
             self.word_start
@@ -886,17 +1004,168 @@ impl SqlEditor {
             (0..self.cursor_pos).rev().find(|&i| text.is_char_boundary(i)).unwrap_or(0)
         };
 
-        // Replace the partial word with the suggestion and add a space
-        let replacement = format!("{} ", suggestion);
+        // Templates are expanded in place; anything else is followed by a space
+        let (replacement, stops) = match &suggestion.template {
+            Some(template) => expand_template(template),
+            None => (format!("{} ", suggestion.text), Vec::new()),
+        };
         text.replace_range(safe_start..safe_end, &replacement);
-        let new_pos = safe_start + replacement.len();
-        self.cursor_pos = new_pos;
-        new_pos
+        self.snippet_stops = stops.into_iter().map(|(start, end)| (safe_start + start, safe_start + end)).collect();
+        let end = safe_start + replacement.len();
+        let selection = if self.snippet_stops.is_empty() { (end, end) } else { self.snippet_stops.remove(0) };
+        self.cursor_pos = selection.1;
+        selection
+    }
+}
+
+/// Columns of `table` (`name` or `schema.name`, in any case) in the loaded schemas.
+/// An unqualified name is looked up in every schema; the first one having it wins.
+fn table_columns<'a>(schemas: &'a [SchemaInfo], table: &str) -> Option<&'a [ColumnInfo]> {
+    let (schema, name) = match table.rsplit_once('.') {
+        Some((schema, name)) => (Some(schema), name),
+        None => (None, table),
+    };
+    schemas.iter()
+        .filter(|info| schema.is_none_or(|schema| info.name.eq_ignore_ascii_case(schema)))
+        .find_map(|info| info.table_columns.iter().find(|(table, _)| table.eq_ignore_ascii_case(name)))
+        .map(|(_, columns)| columns.as_slice())
+}
+
+fn schema_column_suggestions<'a>(columns: &'a [ColumnInfo], partial: &'a str) -> impl Iterator<Item = Suggestion> + 'a {
+    columns.iter()
+        .filter(move |column| column.name.to_lowercase().starts_with(partial))
+        .map(|column| Suggestion { text: column.name.clone(), kind: SuggestionKind::Column, detail: Some(column.data_type.clone()), template: None })
+}
+
+fn result_column_suggestions<'a>(columns: &'a [String], partial: &'a str) -> impl Iterator<Item = Suggestion> + 'a {
+    columns.iter()
+        .filter(move |column| column.to_lowercase().starts_with(partial))
+        .map(|column| Suggestion { text: column.clone(), kind: SuggestionKind::Column, detail: None, template: None })
+}
+
+/// PostgreSQL built-ins offered as functions: name, call template and what it does.
+const POSTGRES_FUNCTIONS: &[(&str, &str, &str)] = &[
+    ("now", "now()", "current date and time"),
+    ("current_date", "current_date", "today's date"),
+    ("coalesce", "coalesce(${1:value}, ${2:fallback})", "first non-NULL argument"),
+    ("nullif", "nullif(${1:value}, ${2:other})", "NULL when both are equal"),
+    ("greatest", "greatest(${1:a}, ${2:b})", "largest argument"),
+    ("least", "least(${1:a}, ${2:b})", "smallest argument"),
+    ("cast", "cast(${1:value} AS ${2:type})", "convert to a type"),
+    ("count", "count(${1:*})", "number of rows"),
+    ("sum", "sum(${1:value})", "total"),
+    ("avg", "avg(${1:value})", "mean"),
+    ("min", "min(${1:value})", "smallest value"),
+    ("max", "max(${1:value})", "largest value"),
+    ("string_agg", "string_agg(${1:value}, ${2:', '})", "values joined into text"),
+    ("array_agg", "array_agg(${1:value})", "values collected into an array"),
+    ("date_trunc", "date_trunc('${1:day}', ${2:timestamp})", "truncate to a unit"),
+    ("extract", "extract(${1:year} FROM ${2:timestamp})", "field of a date or time"),
+    ("to_char", "to_char(${1:value}, '${2:YYYY-MM-DD}')", "format as text"),
+    ("age", "age(${1:timestamp})", "interval since"),
+    ("lower", "lower(${1:text})", "lowercase"),
+    ("upper", "upper(${1:text})", "uppercase"),
+    ("length", "length(${1:text})", "number of characters"),
+    ("concat", "concat(${1:a}, ${2:b})", "join as text, ignoring NULLs"),
+    ("substring", "substring(${1:text} FROM ${2:1} FOR ${3:1})", "part of a text"),
+    ("replace", "replace(${1:text}, ${2:from}, ${3:to})", "replace every occurrence"),
+    ("split_part", "split_part(${1:text}, ${2:','}, ${3:1})", "field of delimited text"),
+    ("regexp_replace", "regexp_replace(${1:text}, '${2:pattern}', '${3:replacement}', 'g')", "replace regex matches"),
+    ("round", "round(${1:value}, ${2:2})", "round to decimal places"),
+    ("abs", "abs(${1:value})", "absolute value"),
+    ("generate_series", "generate_series(${1:1}, ${2:10})", "rows of a range"),
+    ("jsonb_build_object", "jsonb_build_object('${1:key}', ${2:value})", "object from keys and values"),
+    ("jsonb_agg", "jsonb_agg(${1:value})", "values collected into an array"),
+    ("jsonb_object_agg", "jsonb_object_agg(${1:key}, ${2:value})", "pairs collected into an object"),
+    ("jsonb_array_elements", "jsonb_array_elements(${1:array})", "row per array element"),
+    ("jsonb_array_length", "jsonb_array_length(${1:array})", "number of elements"),
+    ("jsonb_each", "jsonb_each(${1:object})", "row per key and value"),
+    ("jsonb_extract_path_text", "jsonb_extract_path_text(${1:document}, '${2:key}')", "value at a path as text"),
+    ("jsonb_set", "jsonb_set(${1:document}, '{${2:key}}', ${3:value})", "replace the value at a path"),
+    ("jsonb_typeof", "jsonb_typeof(${1:value})", "type of a value"),
+    ("jsonb_pretty", "jsonb_pretty(${1:document})", "indented text"),
+    ("to_jsonb", "to_jsonb(${1:value})", "value as jsonb"),
+    ("row_number", "row_number() OVER (ORDER BY ${1:column})", "window: position in the partition"),
+    ("rank", "rank() OVER (ORDER BY ${1:column})", "window: rank with gaps"),
+    ("dense_rank", "dense_rank() OVER (ORDER BY ${1:column})", "window: rank without gaps"),
+    ("ntile", "ntile(${1:4}) OVER (ORDER BY ${2:column})", "window: bucket number"),
+    ("lag", "lag(${1:value}) OVER (ORDER BY ${2:column})", "window: value of the previous row"),
+    ("lead", "lead(${1:value}) OVER (ORDER BY ${2:column})", "window: value of the next row"),
+    ("first_value", "first_value(${1:value}) OVER (ORDER BY ${2:column})", "window: first value of the frame"),
+    ("last_value", "last_value(${1:value}) OVER (ORDER BY ${2:column})", "window: last value of the frame"),
+];
+
+/// Built-in functions of `driver` starting with `partial` (lowercase), inserted as calls.
+fn function_suggestions(driver: Driver, partial: &str) -> impl Iterator<Item = Suggestion> + '_ {
+    let functions = if driver == Driver::Postgres { POSTGRES_FUNCTIONS } else { &[] };
+    functions.iter()
+        .filter(move |(name, _, _)| name.starts_with(partial))
+        .map(|(name, template, description)| Suggestion {
+            text: name.to_string(),
+            kind: SuggestionKind::Function,
+            detail: Some(description.to_string()),
+            template: Some(template.to_string()),
+        })
+}
+
+fn snippet_suggestions<'a>(snippets: &'a [Snippet], partial: &'a str) -> impl Iterator<Item = Suggestion> + 'a {
+    snippets.iter()
+        .filter(move |snippet| !snippet.name.is_empty() && snippet.name.to_lowercase().starts_with(partial))
+        .map(|snippet| Suggestion {
+            text: snippet.name.clone(),
+            kind: SuggestionKind::Snippet,
+            // First line of the body, enough to tell snippets apart
+            detail: snippet.body.lines().next().map(str::to_string),
+            template: Some(snippet.body.clone()),
+        })
+}
+
+/// Text of a snippet or function template and the ranges of its placeholders, in the
+/// order Tab visits them. `$1` leaves an empty stop, `${1:text}` one holding `text`;
+/// a `$` followed by anything else (e.g. dollar quoting) is kept as is.
+fn expand_template(template: &str) -> (String, Vec<(usize, usize)>) {
+    let mut text = String::new();
+    let mut stops: Vec<(u32, usize, usize)> = Vec::new();
+    let mut rest = template;
+    while let Some(dollar) = rest.find('$') {
+        text.push_str(&rest[..dollar]);
+        rest = &rest[dollar + 1..];
+
+        let (number, default, consumed) = if let Some(inner) = rest.strip_prefix('{') {
+            let parsed = inner.find('}').and_then(|close| {
+                let (number, default) = inner[..close].split_once(':').unwrap_or((&inner[..close], ""));
+                Some((number.parse().ok()?, default, close + 2))
+            });
+            match parsed {
+                Some(parsed) => parsed,
+                None => {
+                    text.push('$');
+                    continue;
+                }
+            }
+        } else {
+            let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            match rest[..digits].parse() {
+                Ok(number) => (number, "", digits),
+                Err(_) => {
+                    text.push('$');
+                    continue;
+                }
+            }
+        };
+        stops.push((number, text.len(), text.len() + default.len()));
+        text.push_str(default);
+        rest = &rest[consumed..];
     }
+    text.push_str(rest);
+
+    stops.sort_by_key(|(number, _, _)| *number);
+    (text, stops.into_iter().map(|(_, start, end)| (start, end)).collect())
 }
 
 pub struct SqlEditorResponse {
     pub execute: bool,
+    pub execute_selection: bool,
     pub text_changed: bool,
 }
 
@@ -921,3 +1190,101 @@ fn get_sql_keywords() -> HashSet<&'static str> {
 
     keywords
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schemas() -> Vec<SchemaInfo> {
+        let mut public = SchemaInfo::new("public".to_string());
+        public.tables = vec!["orders".to_string(), "users".to_string()];
+        public.table_columns.insert("orders".to_string(), vec![ColumnInfo::test("id", "integer"), ColumnInfo::test("user_id", "integer"), ColumnInfo::test("total", "numeric")]);
+        public.table_columns.insert("users".to_string(), vec![ColumnInfo::test("id", "integer"), ColumnInfo::test("email", "text")]);
+        let mut audit = SchemaInfo::new("audit".to_string());
+        audit.tables = vec!["orders".to_string()];
+        audit.table_columns.insert("orders".to_string(), vec![ColumnInfo::test("changed_at", "timestamp")]);
+        vec![public, audit]
+    }
+
+    // Suggestions with the cursor at `|`, or at the end of `sql` without one
+    fn suggest(sql: &str, columns: &[String]) -> Vec<(String, Option<String>)> {
+        let mut editor = SqlEditor::new();
+        editor.cursor_pos = sql.find('|').unwrap_or(sql.len());
+        let snippets = [Snippet { name: "sel".to_string(), body: "SELECT * FROM $1 LIMIT 100".to_string() }];
+        editor.update_suggestions(&sql.replace('|', ""), &schemas(), columns, &snippets, Driver::Postgres);
+        editor.suggestions.into_iter().map(|suggestion| (suggestion.text, suggestion.detail)).collect()
+    }
+
+    #[test]
+    fn test_table_columns() {
+        let schemas = schemas();
+        let names = |table| table_columns(&schemas, table).map(|columns| columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>());
+        assert_eq!(names("ORDERS"), Some(vec!["id", "user_id", "total"]));
+        assert_eq!(names("audit.orders"), Some(vec!["changed_at"]));
+        assert_eq!(names("audit.users"), None);
+        assert_eq!(names("missing"), None);
+    }
+
+    #[test]
+    fn test_qualified_column_suggestions() {
+        let result = vec!["from_result".to_string()];
+        // The alias may be declared after the cursor
+        assert_eq!(suggest("SELECT o.t| FROM orders o", &result), vec![("total".to_string(), Some("numeric".to_string()))]);
+
+        let sql = "SELECT * FROM orders o JOIN audit.orders a ON true WHERE o.";
+        assert_eq!(suggest(sql, &result), vec![
+            ("id".to_string(), Some("integer".to_string())),
+            ("total".to_string(), Some("numeric".to_string())),
+            ("user_id".to_string(), Some("integer".to_string())),
+        ]);
+        assert_eq!(suggest(&format!("{}a.c", &sql[..sql.len() - 2]), &result), vec![("changed_at".to_string(), Some("timestamp".to_string()))]);
+        assert_eq!(suggest("SELECT * FROM orders WHERE orders.to", &result), vec![("total".to_string(), Some("numeric".to_string()))]);
+
+        // Unknown qualifiers fall back to the columns of the current result
+        assert_eq!(suggest("SELECT * FROM (SELECT 1) s WHERE s.fr", &result), vec![("from_result".to_string(), None)]);
+    }
+
+    #[test]
+    fn test_unqualified_column_suggestions() {
+        let result = vec!["email_result".to_string()];
+        // Columns of every table joined, each name once
+        let suggestions = suggest("SELECT * FROM orders JOIN users ON true WHERE i", &result);
+        assert_eq!(suggestions.iter().filter(|(text, _)| text == "id").count(), 1);
+        assert!(suggestions.iter().all(|(text, _)| text != "email_result"));
+        assert!(suggest("SELECT * FROM users WHERE em", &result).contains(&("email".to_string(), Some("text".to_string()))));
+        // Tables the schema doesn't know leave the current result's columns
+        assert!(suggest("SELECT * FROM elsewhere WHERE em", &result).contains(&("email_result".to_string(), None)));
+    }
+
+    #[test]
+    fn test_expand_template() {
+        assert_eq!(expand_template("SELECT * FROM $1 LIMIT 100"), ("SELECT * FROM  LIMIT 100".to_string(), vec![(14, 14)]));
+        // Stops in number order, with their default text selected
+        assert_eq!(expand_template("lag(${2:value}) OVER (ORDER BY ${1:id})"), ("lag(value) OVER (ORDER BY id)".to_string(), vec![(26, 28), (4, 9)]));
+        assert_eq!(expand_template("jsonb_set(d, '{${1:key}}', v)"), ("jsonb_set(d, '{key}', v)".to_string(), vec![(15, 18)]));
+        // Dollar quoting and a lone $ are left alone
+        assert_eq!(expand_template("DO $$ BEGIN END $$; ${x}"), ("DO $$ BEGIN END $$; ${x}".to_string(), vec![]));
+        assert_eq!(expand_template("now()"), ("now()".to_string(), vec![]));
+    }
+
+    #[test]
+    fn test_function_and_snippet_suggestions() {
+        let suggestions = suggest("SELECT coal", &[]);
+        assert_eq!(suggestions, vec![("coalesce".to_string(), Some("first non-NULL argument".to_string()))]);
+        assert!(suggest("SELECT * FROM orders WHERE jsonb_ty", &[]).iter().any(|(text, _)| text == "jsonb_typeof"));
+        assert!(function_suggestions(Driver::MySql, "coal").next().is_none());
+        // Snippets at the start of a statement, not where a table name goes
+        assert_eq!(suggest("se", &[]).iter().filter(|(text, _)| text == "sel").count(), 1);
+        assert!(suggest("SELECT * FROM se", &[]).is_empty());
+
+        let mut editor = SqlEditor::new();
+        let mut sql = "SELECT * FROM orders WHERE row_nu".to_string();
+        editor.cursor_pos = sql.len();
+        editor.update_suggestions(&sql, &schemas(), &[], &[], Driver::Postgres);
+        let suggestion = editor.suggestions[0].clone();
+        let selection = editor.insert_suggestion(&mut sql, &suggestion);
+        assert_eq!(sql, "SELECT * FROM orders WHERE row_number() OVER (ORDER BY column)");
+        assert_eq!(&sql[selection.0..selection.1], "column");
+        assert!(editor.snippet_stops.is_empty());
+    }
+}
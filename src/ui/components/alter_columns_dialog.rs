@@ -0,0 +1,111 @@
+use crate::models::{AlterPlan, NullabilityChange};
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum AlterColumnsDialogEvent {
+    Apply(Vec<String>), // statements to run in one transaction
+    Close,
+}
+
+pub struct AlterColumnsDialog;
+
+impl AlterColumnsDialog {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Stage renames, type changes and nullability flips for the columns of `plan`
+    /// and preview the statements Apply will run. `applying` disables Apply while a
+    /// previous attempt is still running.
+    pub fn show(&mut self, ctx: &egui::Context, plan: &mut AlterPlan, applying: bool) -> Option<AlterColumnsDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new(format!("🛠 Alter Columns of {}.{}", plan.schema, plan.table))
+            .open(&mut is_open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(760.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().id_source("alter_columns").max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("alter_columns_grid").striped(true).num_columns(5).show(ui, |ui| {
+                        ui.strong("Column");
+                        ui.strong("New name");
+                        ui.strong("Type");
+                        ui.strong("USING");
+                        ui.strong("NOT NULL");
+                        ui.end_row();
+
+                        for (index, column) in plan.columns.iter_mut().enumerate() {
+                            let label = egui::RichText::new(&column.name).monospace();
+                            ui.label(if column.is_changed() { label.strong() } else { label })
+                                .on_hover_text(format!("Currently {}", column.data_type));
+                            ui.add(egui::TextEdit::singleline(&mut column.new_name).desired_width(140.0));
+                            ui.add(egui::TextEdit::singleline(&mut column.new_type).desired_width(140.0));
+                            if column.is_retyped() {
+                                // Empty means the suggestion, shown as the hint
+                                let suggestion = column.using_expression();
+                                ui.add(egui::TextEdit::singleline(&mut column.using)
+                                    .hint_text(suggestion)
+                                    .desired_width(200.0))
+                                    .on_hover_text("Expression converting existing values; leave empty to use the suggestion");
+                            } else {
+                                ui.label("");
+                            }
+                            egui::ComboBox::from_id_source(("alter_nullability", index))
+                                .selected_text(column.nullability.as_str())
+                                .show_ui(ui, |ui| {
+                                    for change in NullabilityChange::all() {
+                                        ui.selectable_value(&mut column.nullability, change, change.as_str());
+                                    }
+                                });
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                ui.separator();
+                let statements = plan.statements();
+                match &statements {
+                    Ok(statements) if statements.is_empty() => {
+                        ui.label(egui::RichText::new("No changes staged").color(egui::Color32::GRAY));
+                    }
+                    Ok(statements) => {
+                        ui.label(format!("{} column(s) changed, applied in one transaction:", plan.changed_columns()));
+                        let mut preview = statements.join(";\n") + ";";
+                        egui::ScrollArea::vertical().id_source("alter_preview").max_height(160.0).show(ui, |ui| {
+                            ui.add(egui::TextEdit::multiline(&mut preview)
+                                .code_editor()
+                                .desired_width(f32::INFINITY)
+                                .interactive(false));
+                        });
+                    }
+                    Err(e) => {
+                        ui.label(egui::RichText::new(format!("⚠ {}", e)).color(egui::Color32::from_rgb(200, 40, 40)));
+                    }
+                }
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    let ready = statements.as_ref().is_ok_and(|statements| !statements.is_empty());
+                    if ui.add_enabled(ready && !applying, egui::Button::new("✔ Apply")).clicked() {
+                        if let Ok(statements) = statements {
+                            event = Some(AlterColumnsDialogEvent::Apply(statements));
+                        }
+                    }
+                    if ui.button("Close").clicked() {
+                        event = Some(AlterColumnsDialogEvent::Close);
+                    }
+                    if applying {
+                        ui.label("⏳ Applying...");
+                    }
+                });
+            });
+
+        if !is_open {
+            event = Some(AlterColumnsDialogEvent::Close);
+        }
+
+        event
+    }
+}
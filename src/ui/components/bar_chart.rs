@@ -0,0 +1,75 @@
+use crate::models::BarSeries;
+use eframe::egui;
+
+// Height of the plot area, above the axis labels
+const CHART_HEIGHT: f32 = 140.0;
+
+#[derive(Debug)]
+pub enum BarChartEvent {
+    Hide,
+}
+
+pub struct BarChart;
+
+impl BarChart {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Bar chart of `series` across the available width, labelling the first and last bar
+    /// and the one under the pointer.
+    pub fn show(&mut self, ui: &mut egui::Ui, series: &BarSeries) -> Option<BarChartEvent> {
+        let mut event = None;
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(format!("📈 {} bucket(s), peak {}", series.values.len(), format_value(series.max()))).strong());
+            if ui.small_button("Hide Chart").clicked() {
+                event = Some(BarChartEvent::Hide);
+            }
+        });
+
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width(), CHART_HEIGHT), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+        let max = series.max();
+        let step = rect.width() / series.values.len() as f32;
+        // Leave a gap between bars while they are wide enough to see one
+        let gap = if step > 4.0 { 1.0 } else { 0.0 };
+        let hovered = response.hover_pos()
+            .map(|pos| ((pos.x - rect.left()) / step) as usize)
+            .filter(|&index| index < series.values.len());
+
+        for (index, value) in series.values.iter().enumerate() {
+            let height = if max > 0.0 { (value / max) as f32 * (rect.height() - 4.0) } else { 0.0 };
+            let left = rect.left() + step * index as f32;
+            let bar = egui::Rect::from_min_max(egui::pos2(left + gap, rect.bottom() - height), egui::pos2(left + step - gap, rect.bottom()));
+            let color = if hovered == Some(index) { ui.visuals().selection.bg_fill } else { ui.visuals().hyperlink_color };
+            painter.rect_filled(bar, 0.0, color);
+        }
+
+        ui.horizontal(|ui| {
+            let first = series.labels.first().cloned().unwrap_or_default();
+            let last = series.labels.last().cloned().unwrap_or_default();
+            ui.label(egui::RichText::new(first).small().weak());
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(egui::RichText::new(last).small().weak());
+            });
+        });
+
+        if let Some(index) = hovered {
+            response.on_hover_text(format!("{}: {}", series.labels[index], format_value(series.values[index])));
+        }
+        ui.separator();
+
+        event
+    }
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value)
+    }
+}
@@ -0,0 +1,231 @@
+use crate::config::ExportPreset;
+use crate::db::SchemaInfo;
+use crate::export::{BatchProgress, BatchStatus, ExportFormat, ExportSettings};
+use eframe::egui;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum BatchExportDialogEvent {
+    Start(Vec<(String, String)>, ExportFormat, ExportSettings, PathBuf), // schema/table pairs, format, settings, directory
+    Stop,
+    Close,
+}
+
+pub struct BatchExportDialog {
+    selected: BTreeSet<(String, String)>,
+    format: ExportFormat,
+    preset: Option<String>,
+    directory: String,
+    filter: String,
+}
+
+impl BatchExportDialog {
+    pub fn new() -> Self {
+        Self {
+            selected: BTreeSet::new(),
+            format: ExportFormat::CSV,
+            preset: None,
+            directory: String::new(),
+            filter: String::new(),
+        }
+    }
+
+    /// Reset the target directory for a new batch.
+    pub fn prepare(&mut self) {
+        if self.directory.is_empty() {
+            self.directory = super::default_file_path("export").to_string_lossy().to_string();
+        }
+    }
+
+    fn settings(&self, presets: &[ExportPreset]) -> ExportSettings {
+        match presets.iter().find(|p| Some(&p.name) == self.preset.as_ref()) {
+            Some(preset) => preset.settings.clone(),
+            None => ExportSettings {
+                delimited: self.format.exporter().delimited_defaults().unwrap_or_default(),
+                ..ExportSettings::default()
+            },
+        }
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        schemas: &[SchemaInfo],
+        presets: &[ExportPreset],
+        progress: Option<&BatchProgress>,
+        running: bool,
+    ) -> Option<BatchExportDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new("💾 Export Schema Data")
+            .open(&mut is_open)
+            .resizable(true)
+            .default_width(500.0)
+            .default_height(450.0)
+            .show(ctx, |ui| {
+                match progress {
+                    Some(progress) => self.show_progress(ui, progress, running, &mut event),
+                    None => self.show_selection(ui, schemas, presets, &mut event),
+                }
+            });
+
+        if !is_open {
+            event = Some(if running { BatchExportDialogEvent::Stop } else { BatchExportDialogEvent::Close });
+        }
+
+        event
+    }
+
+    fn show_selection(
+        &mut self,
+        ui: &mut egui::Ui,
+        schemas: &[SchemaInfo],
+        presets: &[ExportPreset],
+        event: &mut Option<BatchExportDialogEvent>,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("🔍");
+            ui.add(egui::TextEdit::singleline(&mut self.filter)
+                .hint_text("Filter tables...")
+                .desired_width(200.0));
+            ui.label(format!("{} selected", self.selected.len()));
+        });
+        ui.separator();
+
+        let filter = self.filter.to_lowercase();
+        egui::ScrollArea::vertical()
+            .max_height(250.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for schema in schemas {
+                    let tables: Vec<&String> = schema.relations()
+                        .filter(|table| filter.is_empty() || table.to_lowercase().contains(&filter))
+                        .collect();
+                    if tables.is_empty() {
+                        continue;
+                    }
+
+                    egui::CollapsingHeader::new(format!("📁 {} ({})", schema.name, tables.len()))
+                        .id_source(("batch_export_schema", &schema.name))
+                        .default_open(schemas.len() == 1)
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("All").clicked() {
+                                    for table in &tables {
+                                        self.selected.insert((schema.name.clone(), table.to_string()));
+                                    }
+                                }
+                                if ui.small_button("None").clicked() {
+                                    for table in &tables {
+                                        self.selected.remove(&(schema.name.clone(), table.to_string()));
+                                    }
+                                }
+                            });
+                            for table in tables {
+                                let key = (schema.name.clone(), table.clone());
+                                let mut checked = self.selected.contains(&key);
+                                if ui.checkbox(&mut checked, table.as_str()).changed() {
+                                    if checked {
+                                        self.selected.insert(key);
+                                    } else {
+                                        self.selected.remove(&key);
+                                    }
+                                }
+                            }
+                        });
+                }
+            });
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label("Format:");
+            for format in ExportFormat::all() {
+                ui.selectable_value(&mut self.format, format, format.as_str());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Preset:");
+            egui::ComboBox::from_id_source("batch_export_preset")
+                .selected_text(self.preset.as_deref().unwrap_or("Default"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.preset, None, "Default");
+                    for preset in presets {
+                        ui.selectable_value(&mut self.preset, Some(preset.name.clone()), &preset.name);
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Directory:");
+            ui.add(egui::TextEdit::singleline(&mut self.directory).desired_width(350.0));
+        });
+        ui.label(egui::RichText::new("Each table is written to <schema>.<table>.<ext> in this directory.")
+            .size(10.0)
+            .color(egui::Color32::GRAY));
+
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            let can_start = !self.selected.is_empty() && !self.directory.trim().is_empty();
+            if ui.add_enabled(can_start, egui::Button::new("Export")).clicked() {
+                *event = Some(BatchExportDialogEvent::Start(
+                    self.selected.iter().cloned().collect(),
+                    self.format,
+                    self.settings(presets),
+                    super::expand_home(self.directory.trim()),
+                ));
+            }
+            if ui.button("Cancel").clicked() {
+                *event = Some(BatchExportDialogEvent::Close);
+            }
+        });
+    }
+
+    fn show_progress(
+        &mut self,
+        ui: &mut egui::Ui,
+        progress: &BatchProgress,
+        running: bool,
+        event: &mut Option<BatchExportDialogEvent>,
+    ) {
+        progress_list(ui, progress);
+
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            if running {
+                let label = if progress.stop_requested { "Stopping..." } else { "⏹ Stop" };
+                if ui.add_enabled(!progress.stop_requested, egui::Button::new(label)).clicked() {
+                    *event = Some(BatchExportDialogEvent::Stop);
+                }
+            } else if ui.button("Close").clicked() {
+                *event = Some(BatchExportDialogEvent::Close);
+            }
+        });
+    }
+}
+
+/// Progress bar and per-table status of a batch, shared with the SQL export dialog.
+pub(super) fn progress_list(ui: &mut egui::Ui, progress: &BatchProgress) {
+    ui.add(egui::ProgressBar::new(progress.fraction())
+        .text(format!("{} / {} tables", progress.finished_count(), progress.items.len())));
+    ui.separator();
+
+    egui::ScrollArea::vertical()
+        .max_height(300.0)
+        .auto_shrink([false, true])
+        .show(ui, |ui| {
+            for (schema, table, status) in &progress.items {
+                ui.horizontal(|ui| {
+                    let (icon, detail, color) = match status {
+                        BatchStatus::Pending => ("•", String::new(), egui::Color32::GRAY),
+                        BatchStatus::Running => ("⏳", "exporting...".to_string(), egui::Color32::from_rgb(40, 110, 160)),
+                        BatchStatus::Done(rows) => ("✔", format!("{} rows", rows), egui::Color32::from_rgb(40, 140, 60)),
+                        BatchStatus::Failed(error) => ("✖", error.clone(), egui::Color32::from_rgb(200, 40, 40)),
+                    };
+                    ui.label(egui::RichText::new(icon).color(color));
+                    ui.label(format!("{}.{}", schema, table));
+                    ui.label(egui::RichText::new(detail).size(10.0).color(color));
+                });
+            }
+        });
+}
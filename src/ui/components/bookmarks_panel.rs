@@ -0,0 +1,50 @@
+use crate::db::ColumnInfo;
+use crate::models::Bookmarks;
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum BookmarksPanelEvent {
+    Jump(usize), // index into the tab's bookmarks
+    Remove(usize),
+    Clear,
+}
+
+pub struct BookmarksPanel;
+
+impl BookmarksPanel {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// List of a tab's bookmarked rows; clicking one jumps to it in the grid.
+    pub fn show(&mut self, ui: &mut egui::Ui, bookmarks: &Bookmarks, columns: &[ColumnInfo], key_columns: &[usize]) -> Option<BookmarksPanelEvent> {
+        let mut event = None;
+
+        ui.horizontal(|ui| {
+            ui.strong(format!("★ Bookmarks ({})", bookmarks.len()));
+            if ui.small_button("Clear").clicked() {
+                event = Some(BookmarksPanelEvent::Clear);
+            }
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .id_source("bookmarks_panel")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for (index, key) in bookmarks.keys().iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("✖").on_hover_text("Remove bookmark").clicked() {
+                            event = Some(BookmarksPanelEvent::Remove(index));
+                        }
+                        let label = Bookmarks::label(key, columns, key_columns);
+                        if ui.link(label).on_hover_text("Show this row").clicked() {
+                            event = Some(BookmarksPanelEvent::Jump(index));
+                        }
+                    });
+                }
+            });
+
+        event
+    }
+}
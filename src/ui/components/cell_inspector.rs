@@ -0,0 +1,181 @@
+use crate::db::ColumnInfo;
+use crate::models::{cell_json, extract_json_path, format_bytes, hex_dump, json_value_text, parse_json_path, to_base64, to_hex, CellValue};
+use eframe::egui;
+use serde_json::Value;
+
+// Levels of a JSON document expanded when it is first shown
+const OPEN_DEPTH: usize = 2;
+// Bytes of a binary value shown in the hex dump; copies and saves take all of them
+const DUMP_LIMIT: usize = 64 * 1024;
+
+#[derive(Debug)]
+pub enum CellInspectorEvent {
+    SaveBytes,
+    Close,
+}
+
+pub struct CellInspector {
+    // JSON path typed into the extraction field
+    path: String,
+}
+
+impl CellInspector {
+    pub fn new() -> Self {
+        Self { path: String::new() }
+    }
+
+    /// Start over for another cell.
+    pub fn reset(&mut self) {
+        self.path.clear();
+    }
+
+    /// The full value of one cell. JSON documents are shown as a collapsible tree with
+    /// a field extracting the value at a JSON path, binary values as a hex dump, arrays
+    /// as a list of their elements, and anything else as selectable text.
+    pub fn show(&mut self, ctx: &egui::Context, column: &ColumnInfo, value: &CellValue) -> Option<CellInspectorEvent> {
+        let mut event = None;
+        let mut is_open = true;
+        let document = cell_json(value, &column.data_type);
+
+        egui::Window::new(format!("🔍 {}", column.name))
+            .open(&mut is_open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(&column.data_type).color(egui::Color32::GRAY));
+                ui.separator();
+
+                match (&document, value) {
+                    (None, CellValue::Bytes(bytes)) => {
+                        let mut dump = hex_dump(bytes, DUMP_LIMIT);
+                        egui::ScrollArea::vertical().max_height(380.0).show(ui, |ui| {
+                            ui.add(egui::TextEdit::multiline(&mut dump).code_editor().desired_width(f32::INFINITY));
+                        });
+                        if bytes.len() > DUMP_LIMIT {
+                            ui.label(egui::RichText::new(format!("Showing the first {} of {}", format_bytes(DUMP_LIMIT as u64), format_bytes(bytes.len() as u64)))
+                                .color(egui::Color32::GRAY));
+                        }
+                    }
+                    (None, CellValue::Array(values)) => {
+                        egui::ScrollArea::vertical().max_height(380.0).show(ui, |ui| {
+                            egui::CollapsingHeader::new(format!("{} elements", values.len()))
+                                .id_source("cell_inspector_array")
+                                .default_open(true)
+                                .show(ui, |ui| {
+                                    egui::Grid::new("cell_inspector_array_grid").num_columns(2).striped(true).show(ui, |ui| {
+                                        for (index, element) in values.iter().enumerate() {
+                                            ui.label(egui::RichText::new(format!("[{}]", index + 1)).strong());
+                                            let text = egui::RichText::new(element.to_string()).monospace();
+                                            ui.label(if element.is_null() { text.color(egui::Color32::GRAY) } else { text });
+                                            ui.end_row();
+                                        }
+                                    });
+                                });
+                        });
+                    }
+                    (Some(document), _) => {
+                        egui::ScrollArea::vertical().max_height(380.0).show(ui, |ui| {
+                            json_tree(ui, "$", document, egui::Id::new("cell_inspector_json"), 0);
+                        });
+                        ui.separator();
+
+                        ui.horizontal(|ui| {
+                            ui.label("JSON path:");
+                            ui.add(egui::TextEdit::singleline(&mut self.path).hint_text("$.address.city").desired_width(260.0));
+                        });
+                        if !self.path.trim().is_empty() {
+                            match parse_json_path(&self.path) {
+                                Ok(path) => match extract_json_path(document, &path) {
+                                    Some(extracted) => {
+                                        let text = json_value_text(extracted);
+                                        ui.horizontal(|ui| {
+                                            if ui.small_button("📋").on_hover_text("Copy value").clicked() {
+                                                ui.output_mut(|o| o.copied_text = text.clone());
+                                            }
+                                            ui.label(egui::RichText::new(&text).monospace());
+                                        });
+                                    }
+                                    None => {
+                                        ui.label(egui::RichText::new("Nothing at this path").color(egui::Color32::GRAY));
+                                    }
+                                },
+                                Err(e) => {
+                                    ui.label(egui::RichText::new(format!("⚠ {}", e)).color(egui::Color32::from_rgb(220, 80, 80)));
+                                }
+                            }
+                        }
+                    }
+                    (None, _) => {
+                        let mut text = value.to_string();
+                        egui::ScrollArea::vertical().max_height(380.0).show(ui, |ui| {
+                            ui.add(egui::TextEdit::multiline(&mut text).code_editor().desired_width(f32::INFINITY).interactive(true));
+                        });
+                    }
+                }
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    let formatted = document.as_ref().and_then(|document| serde_json::to_string_pretty(document).ok());
+                    if let Some(formatted) = formatted {
+                        if ui.button("📋 Copy as Formatted JSON").clicked() {
+                            ui.output_mut(|o| o.copied_text = formatted);
+                        }
+                    } else if let CellValue::Bytes(bytes) = value {
+                        if ui.button("📋 Copy as Hex").clicked() {
+                            ui.output_mut(|o| o.copied_text = to_hex(bytes));
+                        }
+                        if ui.button("📋 Copy as Base64").clicked() {
+                            ui.output_mut(|o| o.copied_text = to_base64(bytes));
+                        }
+                        if ui.button("💾 Save to File…").clicked() {
+                            event = Some(CellInspectorEvent::SaveBytes);
+                        }
+                    } else if ui.button("📋 Copy").clicked() {
+                        ui.output_mut(|o| o.copied_text = value.to_string());
+                    }
+                    if ui.button("Close").clicked() {
+                        event = Some(CellInspectorEvent::Close);
+                    }
+                });
+            });
+
+        if !is_open {
+            event = Some(CellInspectorEvent::Close);
+        }
+        event
+    }
+}
+
+// One node of the document: objects and arrays collapse, other values are a line
+fn json_tree(ui: &mut egui::Ui, key: &str, value: &Value, id: egui::Id, depth: usize) {
+    let children: Vec<(String, &Value)> = match value {
+        Value::Object(object) => object.iter().map(|(key, child)| (key.clone(), child)).collect(),
+        Value::Array(items) => items.iter().enumerate().map(|(index, child)| (format!("[{}]", index), child)).collect(),
+        leaf => {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("{}:", key)).strong());
+                let color = match leaf {
+                    Value::String(_) => egui::Color32::from_rgb(120, 190, 120),
+                    Value::Number(_) => egui::Color32::from_rgb(100, 170, 230),
+                    _ => egui::Color32::from_rgb(220, 150, 60),
+                };
+                ui.label(egui::RichText::new(leaf.to_string()).monospace().color(color));
+            });
+            return;
+        }
+    };
+
+    let summary = match value {
+        Value::Object(_) => format!("{} {{{}}}", key, children.len()),
+        _ => format!("{} [{}]", key, children.len()),
+    };
+    egui::CollapsingHeader::new(summary)
+        .id_source(id)
+        .default_open(depth < OPEN_DEPTH)
+        .show(ui, |ui| {
+            for (child_key, child) in children {
+                json_tree(ui, &child_key, child, id.with(&child_key), depth + 1);
+            }
+        });
+}
@@ -0,0 +1,124 @@
+use crate::models::fuzzy_rank;
+use crate::ui::keymap::Command;
+use eframe::egui;
+
+// Matches listed at most, the rest is reached by typing more
+const MAX_RESULTS: usize = 12;
+
+/// Something the palette can run: a command or opening a table.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteItem {
+    Command(Command),
+    Table(String, String), // schema, table
+}
+
+impl PaletteItem {
+    fn label(&self) -> String {
+        match self {
+            PaletteItem::Command(command) => command.label().to_string(),
+            PaletteItem::Table(schema, table) => format!("{}.{}", schema, table),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CommandPaletteEvent {
+    Run(PaletteItem),
+    Close,
+}
+
+/// Ctrl/Cmd+P overlay fuzzy-searching commands and tables.
+pub struct CommandPalette {
+    query: String,
+    selected: usize,
+    request_focus: bool,
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            selected: 0,
+            request_focus: false,
+        }
+    }
+
+    /// Start from an empty search with the input focused.
+    pub fn open(&mut self) {
+        self.query.clear();
+        self.selected = 0;
+        self.request_focus = true;
+    }
+
+    /// Up/Down move the selection, Enter runs it and Escape closes the palette.
+    pub fn show(&mut self, ctx: &egui::Context, items: &[PaletteItem]) -> Option<CommandPaletteEvent> {
+        let mut event = None;
+        let labels: Vec<String> = items.iter().map(PaletteItem::label).collect();
+        let mut matches = fuzzy_rank(&self.query, labels.iter().map(String::as_str));
+        matches.truncate(MAX_RESULTS);
+
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown)) {
+            self.selected += 1;
+        }
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp)) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+
+        egui::Window::new("command_palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .fixed_size([480.0, 0.0])
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Type a command or table name...")
+                        .desired_width(f32::INFINITY)
+                );
+                if self.request_focus {
+                    response.request_focus();
+                    self.request_focus = false;
+                }
+                if response.changed() {
+                    self.selected = 0;
+                }
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    if let Some(&index) = matches.get(self.selected) {
+                        event = Some(CommandPaletteEvent::Run(items[index].clone()));
+                    }
+                }
+
+                ui.separator();
+                if matches.is_empty() {
+                    ui.label(egui::RichText::new("Nothing matches").color(egui::Color32::GRAY));
+                }
+                for (position, &index) in matches.iter().enumerate() {
+                    let item = &items[index];
+                    ui.horizontal(|ui| {
+                        let icon = match item {
+                            PaletteItem::Command(_) => "▶",
+                            PaletteItem::Table(..) => "📋",
+                        };
+                        if ui.selectable_label(position == self.selected, format!("{} {}", icon, labels[index])).clicked() {
+                            event = Some(CommandPaletteEvent::Run(item.clone()));
+                        }
+                        if let PaletteItem::Command(command) = item {
+                            if let Some(shortcut) = command.shortcut() {
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    ui.label(egui::RichText::new(ctx.format_shortcut(&shortcut)).weak());
+                                });
+                            }
+                        }
+                    });
+                }
+            });
+
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            event = Some(CommandPaletteEvent::Close);
+        }
+
+        event
+    }
+}
@@ -0,0 +1,78 @@
+use crate::models::{ComputedColumn, TableData};
+use eframe::egui;
+
+// Rows whose values are previewed
+const PREVIEW_ROWS: usize = 5;
+
+#[derive(Debug)]
+pub enum ComputedColumnDialogEvent {
+    Add,
+    Close,
+}
+
+pub struct ComputedColumnDialog;
+
+impl ComputedColumnDialog {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Edit `column`, to be added after `data`'s columns, previewing its values for the
+    /// first rows. Add is only offered once the expression fits the columns.
+    pub fn show(&mut self, ctx: &egui::Context, data: &TableData, column: &mut ComputedColumn) -> Option<ComputedColumnDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new("ƒ Computed Column")
+            .open(&mut is_open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("computed_column_grid").num_columns(2).spacing([8.0, 6.0]).show(ui, |ui| {
+                    ui.label("Name:");
+                    ui.add(egui::TextEdit::singleline(&mut column.name).desired_width(300.0));
+                    ui.end_row();
+
+                    ui.label("Expression:");
+                    ui.add(egui::TextEdit::singleline(&mut column.expression)
+                        .hint_text("amount_cents / 100")
+                        .code_editor()
+                        .desired_width(300.0));
+                    ui.end_row();
+                });
+                ui.label(egui::RichText::new("Column names (\"quoted\" if needed), numbers, 'text', + - * / %, || and ( )")
+                    .small()
+                    .color(egui::Color32::GRAY));
+
+                ui.separator();
+                let rows = &data.rows[..data.rows.len().min(PREVIEW_ROWS)];
+                let valid = column.validate(&data.columns).and_then(|_| column.values(&data.columns, rows));
+                match &valid {
+                    Ok(values) => {
+                        for value in values {
+                            ui.monospace(value.to_string());
+                        }
+                    }
+                    Err(e) => {
+                        ui.label(egui::RichText::new(e.to_string()).color(egui::Color32::from_rgb(200, 80, 80)));
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(valid.is_ok(), egui::Button::new("Add")).clicked() {
+                        event = Some(ComputedColumnDialogEvent::Add);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        event = Some(ComputedColumnDialogEvent::Close);
+                    }
+                });
+            });
+
+        if !is_open {
+            event = Some(ComputedColumnDialogEvent::Close);
+        }
+        event
+    }
+}
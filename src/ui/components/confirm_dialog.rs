@@ -0,0 +1,51 @@
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum ConfirmDialogEvent {
+    Confirm,
+    Cancel,
+}
+
+pub struct ConfirmDialog;
+
+impl ConfirmDialog {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Modal-style question with the statement(s) that are about to run shown below `message`.
+    pub fn show(&mut self, ctx: &egui::Context, title: &str, message: &str, details: &str, confirm_label: &str) -> Option<ConfirmDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new(title)
+            .open(&mut is_open)
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(message).color(egui::Color32::from_rgb(200, 40, 40)));
+                ui.add_space(5.0);
+
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    ui.label(egui::RichText::new(details).monospace());
+                });
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button(confirm_label).clicked() {
+                        event = Some(ConfirmDialogEvent::Confirm);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        event = Some(ConfirmDialogEvent::Cancel);
+                    }
+                });
+            });
+
+        if !is_open {
+            event = Some(ConfirmDialogEvent::Cancel);
+        }
+
+        event
+    }
+}
@@ -1,4 +1,5 @@
-use crate::config::DatabaseConnection;
+use crate::config::{DatabaseConnection, Driver, Environment, ProxyMode, ReplicaHost, SchemaStyle, SslMode};
+use crate::export::{AnonymizeMethod, AnonymizeRule};
 use eframe::egui;
 
 #[derive(Debug)]
@@ -25,6 +26,22 @@ impl ConnectionEditor {
                     ui.text_edit_singleline(&mut conn.name);
                 });
 
+                ui.horizontal(|ui| {
+                    ui.label("Driver:");
+                    let previous = conn.driver;
+                    egui::ComboBox::from_id_source("connection_driver")
+                        .selected_text(conn.driver.as_str())
+                        .show_ui(ui, |ui| {
+                            for driver in Driver::all() {
+                                ui.selectable_value(&mut conn.driver, driver, driver.as_str());
+                            }
+                        });
+                    // Follow the driver's default port unless a custom one was entered
+                    if conn.driver != previous && conn.port == previous.default_port() {
+                        conn.port = conn.driver.default_port();
+                    }
+                });
+
                 ui.horizontal(|ui| {
                     ui.label("Host:");
                     ui.text_edit_singleline(&mut conn.host);
@@ -42,7 +59,14 @@ impl ConnectionEditor {
 
                 ui.horizontal(|ui| {
                     ui.label("Password:");
-                    ui.add(egui::TextEdit::singleline(&mut conn.password).password(true));
+                    ui.add_enabled(!conn.ask_password, egui::TextEdit::singleline(&mut conn.password).password(true));
+                    if ui.checkbox(&mut conn.ask_password, "Ask on connect")
+                        .on_hover_text("Never store the password, not even in the OS credential store; it is asked for when connecting")
+                        .changed()
+                        && conn.ask_password
+                    {
+                        conn.password.clear();
+                    }
                 });
 
                 ui.horizontal(|ui| {
@@ -50,6 +74,221 @@ impl ConnectionEditor {
                     ui.text_edit_singleline(&mut conn.database);
                 });
 
+                ui.horizontal(|ui| {
+                    ui.label("Environment:");
+                    egui::ComboBox::from_id_source("connection_environment")
+                        .selected_text(conn.environment.as_str())
+                        .show_ui(ui, |ui| {
+                            for environment in Environment::all() {
+                                if ui.selectable_value(&mut conn.environment, environment, environment.as_str()).clicked()
+                                    && environment == Environment::Production
+                                {
+                                    // Production connections are guarded unless explicitly turned off
+                                    conn.dangerous = true;
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Tabs opened on this connection are marked with the environment color");
+                });
+
+                ui.checkbox(&mut conn.dangerous, "Production safeguards")
+                    .on_hover_text("Show a warning banner and ask for confirmation before any write statement");
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("SSL mode:");
+                    egui::ComboBox::from_id_source("ssl_mode")
+                        .selected_text(conn.tls.ssl_mode.as_str())
+                        .show_ui(ui, |ui| {
+                            for mode in SslMode::all() {
+                                ui.selectable_value(&mut conn.tls.ssl_mode, mode, mode.as_str());
+                            }
+                        });
+                });
+
+                ui.add_enabled_ui(conn.tls.ssl_mode != SslMode::Disable, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("CA certificate:");
+                        ui.text_edit_singleline(&mut conn.tls.ca_cert_path)
+                            .on_hover_text("PEM file used to verify the server; with prefer or require the host name is not checked");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Client certificate:");
+                        ui.text_edit_singleline(&mut conn.tls.client_cert_path);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Client key:");
+                        ui.text_edit_singleline(&mut conn.tls.client_key_path)
+                            .on_hover_text("PKCS#8 PEM private key for the client certificate");
+                    });
+                });
+
+                ui.separator();
+
+                ui.checkbox(&mut conn.ssh.enabled, "Connect through SSH tunnel");
+                ui.add_enabled_ui(conn.ssh.enabled, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("SSH host:");
+                        ui.text_edit_singleline(&mut conn.ssh.host);
+                        ui.label("Port:");
+                        ui.add(egui::DragValue::new(&mut conn.ssh.port).clamp_range(1..=65535));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("SSH user:");
+                        ui.text_edit_singleline(&mut conn.ssh.user);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Private key:");
+                        ui.text_edit_singleline(&mut conn.ssh.key_path)
+                            .on_hover_text("Leave empty to use the password or ssh-agent");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("SSH password:");
+                        ui.add(egui::TextEdit::singleline(&mut conn.ssh.password).password(true))
+                            .on_hover_text("Account password, or the key passphrase when a private key is set");
+                    });
+                });
+
+                ui.separator();
+
+                let network = &mut conn.network;
+                ui.horizontal(|ui| {
+                    ui.label("Proxy:");
+                    egui::ComboBox::from_id_source("proxy_mode")
+                        .selected_text(network.proxy.mode.as_str())
+                        .show_ui(ui, |ui| {
+                            for mode in ProxyMode::all() {
+                                ui.selectable_value(&mut network.proxy.mode, mode, mode.as_str());
+                            }
+                        });
+                    if network.proxy.mode == ProxyMode::System {
+                        ui.label(egui::RichText::new("ALL_PROXY, HTTPS_PROXY or HTTP_PROXY, except NO_PROXY hosts").color(egui::Color32::GRAY));
+                    }
+                });
+                ui.add_enabled_ui(network.proxy.mode == ProxyMode::Custom, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Proxy URL:");
+                        ui.add(egui::TextEdit::singleline(&mut network.proxy.url).hint_text("socks5://proxy.corp:1080"))
+                            .on_hover_text("SOCKS5 or HTTP CONNECT proxy; with SSH it leads to the SSH host");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Proxy user:");
+                        ui.text_edit_singleline(&mut network.proxy.user);
+                        ui.label("Password:");
+                        ui.add(egui::TextEdit::singleline(&mut network.proxy.password).password(true));
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Connect timeout:");
+                    ui.add(egui::DragValue::new(&mut network.connect_timeout_secs).clamp_range(0..=600).suffix(" s"))
+                        .on_hover_text("0 waits as long as the driver does");
+                    ui.label("Keepalive after:");
+                    ui.add(egui::DragValue::new(&mut network.keepalive_idle_secs).clamp_range(0..=7200).suffix(" s"))
+                        .on_hover_text("Idle seconds before TCP keepalive probes start, so firewalls keep the connection open; 0 uses the driver default");
+                    ui.label("every:");
+                    ui.add(egui::DragValue::new(&mut network.keepalive_interval_secs).clamp_range(0..=600).suffix(" s"))
+                        .on_hover_text("Seconds between unanswered probes; 0 uses the OS default. MySQL applies it only through a proxy or SSH");
+                });
+
+                ui.separator();
+
+                // Read replicas share user, password and database with the primary
+                ui.label("Read replicas:");
+                let mut replica_to_remove = None;
+                for (idx, replica) in conn.replicas.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label("Host:");
+                        ui.text_edit_singleline(&mut replica.host);
+                        ui.label("Port:");
+                        ui.add(egui::DragValue::new(&mut replica.port).clamp_range(1..=65535));
+                        if ui.small_button("✖").on_hover_text("Remove replica").clicked() {
+                            replica_to_remove = Some(idx);
+                        }
+                    });
+                }
+                if let Some(idx) = replica_to_remove {
+                    conn.replicas.remove(idx);
+                }
+                if ui.button("+ Add Replica").clicked() {
+                    conn.replicas.push(ReplicaHost {
+                        host: conn.host.clone(),
+                        port: conn.port,
+                    });
+                }
+
+                ui.separator();
+
+                ui.label("Anonymization rules:")
+                    .on_hover_text("Applied to exports and copied cells while File > Share Safely is on. \
+                                    Column patterns are case-insensitive, * matches anything; the first matching rule wins.");
+                let mut rule_to_remove = None;
+                for (idx, rule) in conn.anonymization.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label("Column:");
+                        ui.add(egui::TextEdit::singleline(&mut rule.column).hint_text("*email*").desired_width(120.0));
+                        egui::ComboBox::from_id_source(("anonymize_method", idx))
+                            .selected_text(rule.method.as_str())
+                            .show_ui(ui, |ui| {
+                                for method in AnonymizeMethod::all() {
+                                    // Keep the truncate length when re-picking Truncate
+                                    let selected = std::mem::discriminant(&rule.method) == std::mem::discriminant(&method);
+                                    if ui.selectable_label(selected, method.as_str()).clicked() && !selected {
+                                        rule.method = method;
+                                    }
+                                }
+                            });
+                        if let AnonymizeMethod::Truncate(keep) = &mut rule.method {
+                            ui.label("keep");
+                            ui.add(egui::DragValue::new(keep).clamp_range(0..=255));
+                        }
+                        if ui.small_button("✖").on_hover_text("Remove rule").clicked() {
+                            rule_to_remove = Some(idx);
+                        }
+                    });
+                }
+                if let Some(idx) = rule_to_remove {
+                    conn.anonymization.remove(idx);
+                }
+                if ui.button("+ Add Rule").clicked() {
+                    conn.anonymization.push(AnonymizeRule {
+                        column: String::new(),
+                        method: AnonymizeMethod::Hash,
+                    });
+                }
+
+                ui.separator();
+
+                ui.label("Schema styles:")
+                    .on_hover_text("Icon and color of matching schemas in the database tree. \
+                                    Schema patterns are case-insensitive, * matches anything; the first matching style wins.");
+                let mut style_to_remove = None;
+                for (idx, style) in conn.schema_styles.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label("Schema:");
+                        ui.add(egui::TextEdit::singleline(&mut style.pattern).hint_text("legacy_*").desired_width(120.0));
+                        ui.label("Icon:");
+                        ui.add(egui::TextEdit::singleline(&mut style.icon).desired_width(30.0));
+                        let mut colored = style.color.is_some();
+                        if ui.checkbox(&mut colored, "Color").changed() {
+                            style.color = colored.then_some([100, 170, 240]);
+                        }
+                        if let Some(color) = &mut style.color {
+                            ui.color_edit_button_srgb(color);
+                        }
+                        if ui.small_button("✖").on_hover_text("Remove style").clicked() {
+                            style_to_remove = Some(idx);
+                        }
+                    });
+                }
+                if let Some(idx) = style_to_remove {
+                    conn.schema_styles.remove(idx);
+                }
+                if ui.button("+ Add Style").clicked() {
+                    conn.schema_styles.push(SchemaStyle::new(String::new()));
+                }
+
                 ui.separator();
 
                 ui.horizontal(|ui| {
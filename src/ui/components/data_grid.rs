@@ -1,11 +1,48 @@
-use crate::models::{TableData, FilterRule, FilterConjunction};
+use crate::export::{json_text, Anonymizer};
+use crate::models::{column_filter, is_computed, cell_json, to_base64, to_hex, cell_matches, extract_json_path, header_label, json_path_text, json_value_text, sample_json_paths, JsonPathStep, Bookmarks, CellSelection, CellValue, ColumnAggregate, PendingEdits, SelectionStats, TabViewModel, TableData, FilterRule};
 use eframe::egui;
 use std::cell::Cell;
 
 #[derive(Debug)]
+#[allow(dead_code)]
 pub enum DataGridEvent {
     ColumnSorted(usize),
     RowSelected(Option<usize>),
+    CellEdited(usize, usize, String), // original row index, column index, new value
+    BookmarkToggled(usize),           // original row index
+    FollowForeignKey(usize, usize),   // original row index, column index
+    CompareRows(usize, usize),        // original row indices
+    InspectCell(usize, usize),        // original row index, column index
+    SaveBytes(usize, usize),          // original row index, column index of a binary cell
+    ReplaceValues(usize),             // column index
+    FreezeColumns(Vec<usize>),        // column indices kept in view, empty to unfreeze
+    ShowDistribution(usize),          // column index
+    ColumnFilterChanged(usize, String), // column index, text of its box in the filter row
+    AddComputedColumn,
+    RemoveComputedColumn(usize),      // column index
+}
+
+/// Passed to the grid when the tab's table has a primary key, so rows can be edited and bookmarked.
+pub struct GridEditContext<'a> {
+    pub pending: &'a PendingEdits,
+    pub key_columns: &'a [usize],
+    pub bookmarks: &'a Bookmarks,
+    // False when the current role may not update the table; rows can still be bookmarked
+    pub editable: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GridSummary {
+    pub filtered_rows: usize,
+    pub total_matches: usize,
+}
+
+// Cell currently being edited in place
+struct CellEditor {
+    row: usize, // original row index
+    column: usize,
+    buffer: String,
+    focused: bool,
 }
 
 #[derive(Debug, Default)]
@@ -15,41 +52,217 @@ pub struct SearchMatchInfo {
     pub current_match_row_in_page: Option<usize>, // Row index within the current page
 }
 
+// Identifies the inputs the filter/search scan depends on. Rows are compared by
+// address and length, so replacing or reloading the data invalidates the cache,
+// and by generation for changes made in place
+#[derive(PartialEq)]
+struct GridCacheKey {
+    rows_ptr: usize,
+    row_count: usize,
+    generation: u64,
+    // Computed columns come and go without touching the rows
+    column_count: usize,
+    sort_column: Option<usize>,
+    sort_ascending: bool,
+    filters: Vec<FilterRule>,
+    search_text: String,
+    current_match_index: usize,
+    page_size: usize,
+}
+
+// Results of scanning the whole result set, reused until the key changes
+struct GridCache {
+    key: GridCacheKey,
+    view: TabViewModel,
+    header_labels: Vec<String>,
+    // Of the sort column, when it is numeric
+    aggregate: Option<ColumnAggregate>,
+}
+
+// Value at a position in the filtered view
+fn cell_value<'a>(data: &'a TableData, filtered_indices: &[usize], row: usize, column: usize) -> Option<&'a CellValue> {
+    filtered_indices.get(row).and_then(|&idx| data.rows[idx].get(column))
+}
+
+// Header tooltip tracing a view column back to its base table
+fn lineage_tooltip(chain: &[String]) -> String {
+    let mut text = String::from("Comes from:");
+    for (i, hop) in chain.iter().enumerate() {
+        text.push_str(&format!("\n{}{}", if i == 0 { "  " } else { "  ← " }, hop));
+    }
+    text
+}
+
 pub struct DataGrid {
-    selected_row: Option<usize>,
+    selection: CellSelection,
+    selection_stats: Option<SelectionStats>,
+    // Mouse is held down after starting a selection in a cell
+    drag_selecting: bool,
+    cache: Option<GridCache>,
+    editor: Option<CellEditor>,
+    // Bring the last row of the page into view on the next frame
+    scroll_to_bottom: bool,
+    // Row (position in the filtered view) to scroll to and select on the next frame
+    reveal_row: Option<usize>,
+    // Vertical offset both panes are held at while columns are frozen
+    frozen_offset: f32,
 }
 
 impl DataGrid {
     pub fn new() -> Self {
         Self {
-            selected_row: None,
+            selection: CellSelection::default(),
+            selection_stats: None,
+            drag_selecting: false,
+            cache: None,
+            editor: None,
+            scroll_to_bottom: false,
+            reveal_row: None,
+            frozen_offset: 0.0,
         }
     }
 
-    fn apply_filters(rows: &[Vec<String>], filters: &[FilterRule]) -> Vec<usize> {
-        if filters.is_empty() {
-            return (0..rows.len()).collect();
+    /// Row and match counts for the current view, computed through the same cache `show` uses.
+    #[allow(clippy::too_many_arguments)]
+    pub fn summary(
+        &mut self,
+        data: &TableData,
+        sort_column: Option<usize>,
+        sort_ascending: bool,
+        page_size: usize,
+        filters: &[FilterRule],
+        search_text: &str,
+        current_match_index: usize,
+    ) -> GridSummary {
+        self.refresh_cache(data, sort_column, sort_ascending, page_size, filters, search_text, current_match_index);
+        self.cache.as_ref()
+            .map(|cache| GridSummary {
+                filtered_rows: cache.view.filtered_rows(),
+                total_matches: cache.view.total_matches,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Scroll to the last row of the current page on the next frame, e.g. after rows were appended.
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll_to_bottom = true;
+    }
+
+    /// Scroll to and select the row at `row` of the filtered view on the next frame.
+    /// The caller switches to the page containing it.
+    pub fn reveal_row(&mut self, row: usize) {
+        self.reveal_row = Some(row);
+    }
+
+    /// Stats for the selected cells, shown in the status bar.
+    pub fn selection_stats(&self) -> Option<&SelectionStats> {
+        self.selection_stats.as_ref()
+    }
+
+    /// Columns with selected cells, for transformations working on a subset of columns.
+    pub fn selected_columns(&self) -> Vec<usize> {
+        self.selection.columns()
+    }
+
+    /// Original indices of the two rows with selected cells, when exactly two have some.
+    fn compared_rows(&self, filtered_indices: &[usize]) -> Option<(usize, usize)> {
+        match self.selection.rows()[..] {
+            [first, second] => Some((*filtered_indices.get(first)?, *filtered_indices.get(second)?)),
+            _ => None,
         }
+    }
 
-        rows.iter()
-            .enumerate()
-            .filter(|(_, row)| {
-                let mut result = filters[0].matches_row(row);
-
-                for filter in filters.iter().skip(1) {
-                    let matches = filter.matches_row(row);
-                    result = match filter.conjunction {
-                        FilterConjunction::And => result && matches,
-                        FilterConjunction::Or => result || matches,
-                    };
-                }
-
-                result
+    /// The selected cells as a JSON array with one object per row and a key per
+    /// selected column, or every filtered row when nothing is selected.
+    fn selection_json(&self, data: &TableData, filtered_indices: &[usize], anonymizer: Option<&Anonymizer>) -> String {
+        let (rows, columns) = if self.selection.is_empty() {
+            (filtered_indices.to_vec(), (0..data.columns.len()).collect())
+        } else {
+            let rows = self.selection.rows().iter().filter_map(|&row| filtered_indices.get(row).copied()).collect();
+            (rows, self.selection.columns())
+        };
+        let column_info: Vec<_> = columns.iter().filter_map(|&column| data.columns.get(column).cloned()).collect();
+        let values: Vec<Vec<CellValue>> = rows.iter()
+            .map(|&row| {
+                columns.iter()
+                    .map(|&column| {
+                        let value = data.rows[row].get(column).cloned().unwrap_or(CellValue::Null);
+                        match anonymizer {
+                            Some(anonymizer) => anonymizer.value(column, &value),
+                            None => value,
+                        }
+                    })
+                    .collect()
             })
-            .map(|(idx, _)| idx)
-            .collect()
+            .collect();
+        json_text(&column_info, &values)
+    }
+
+    // Recompute stats from the values under the selection (as displayed, before staged edits)
+    fn update_selection_stats(&mut self, data: &TableData, filtered_indices: &[usize]) {
+        if self.selection.is_empty() {
+            self.selection_stats = None;
+            return;
+        }
+        let cells = self.selection.cells();
+        let values = cells.iter().filter_map(|&(row, column)| cell_value(data, filtered_indices, row, column));
+        self.selection_stats = Some(SelectionStats::from_values(values));
     }
 
+    // Recompute filtered rows, search matches and header labels only when an input changed
+    #[allow(clippy::too_many_arguments)]
+    fn refresh_cache(
+        &mut self,
+        data: &TableData,
+        sort_column: Option<usize>,
+        sort_ascending: bool,
+        page_size: usize,
+        filters: &[FilterRule],
+        search_text: &str,
+        current_match_index: usize,
+    ) {
+        let key = GridCacheKey {
+            rows_ptr: data.rows.as_ptr() as usize,
+            row_count: data.rows.len(),
+            generation: data.generation,
+            column_count: data.columns.len(),
+            sort_column,
+            sort_ascending,
+            filters: filters.to_vec(),
+            search_text: search_text.to_string(),
+            current_match_index,
+            page_size,
+        };
+
+        if self.cache.as_ref().is_some_and(|cache| cache.key == key) {
+            return;
+        }
+
+        // Selected positions refer to the previous view, drop them when rows move
+        let view_changed = self.cache.as_ref().is_none_or(|cache| {
+            cache.key.rows_ptr != key.rows_ptr
+                || cache.key.row_count != key.row_count
+                || cache.key.sort_column != key.sort_column
+                || cache.key.sort_ascending != key.sort_ascending
+                || cache.key.filters != key.filters
+        });
+        if view_changed {
+            self.selection.clear();
+            self.selection_stats = None;
+            self.reveal_row = None;
+        }
+
+        let view = TabViewModel::new(&data.rows, filters, search_text, current_match_index);
+        let header_labels = data.columns.iter()
+            .enumerate()
+            .map(|(col_index, column)| header_label(&column.name, col_index, sort_column, sort_ascending))
+            .collect();
+
+        let aggregate = sort_column.and_then(|column| ColumnAggregate::new(data, &view.filtered_indices, column));
+        self.cache = Some(GridCache { key, view, header_labels, aggregate });
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn show(
         &mut self,
         ui: &mut egui::Ui,
@@ -61,68 +274,155 @@ impl DataGrid {
         filters: &[FilterRule],
         search_text: &str,
         current_match_index: usize,
+        edit_context: Option<GridEditContext>,
+        // Copied cells go through it while sharing safely
+        anonymizer: Option<&Anonymizer>,
+        // Columns kept left of the horizontally scrolled ones
+        frozen_columns: &[usize],
+        // Leading columns drawn, fewer than all for wide results
+        shown_columns: usize,
+        // The tab's filters, when a filter row under the header edits their Contains rules
+        filter_row: Option<&[FilterRule]>,
     ) -> (Option<DataGridEvent>, SearchMatchInfo) {
         let column_to_sort = Cell::new(None);
+        let mut edited_cell: Option<(usize, usize, String)> = None;
+        let mut toggled_bookmark = None;
+        let mut followed_key = None;
+        let mut compared_rows = None;
+        let mut inspected_cell = None;
+        let mut saved_bytes = None;
+        let mut replaced_column = None;
+        let mut frozen_changed = None;
+        let mut charted_column = None;
+        let mut filtered_column = None;
+        let mut computed_change = None;
+        let mut selection_changed = false;
+        let column_count = data.columns.len();
+        let copied_text = |column: usize, value: &CellValue| match anonymizer {
+            Some(anonymizer) => anonymizer.value(column, value).to_string(),
+            None => value.to_string(),
+        };
+
+        // Not editable (e.g. a query result), drop any half-finished edit
+        let editable = edit_context.as_ref().is_some_and(|ctx| ctx.editable);
+        if !editable {
+            self.editor = None;
+        }
 
-        // Apply filters to get indices of matching rows
-        let filtered_indices = Self::apply_filters(&data.rows, filters);
+        self.refresh_cache(data, sort_column, sort_ascending, page_size, filters, search_text, current_match_index);
+        let cache = self.cache.take().expect("grid cache populated by refresh_cache");
+        let filtered_indices = &cache.view.filtered_indices;
+        let header_labels = &cache.header_labels;
+        let current_match_cell_position = cache.view.current_match.map(|m| (m.original_row, m.column));
 
         // Calculate pagination on filtered data (no filtering by search, just highlighting)
-        let total_rows = filtered_indices.len();
-        let start_row = current_page * page_size;
-        let end_row = (start_row + page_size).min(total_rows);
+        let page_rows = cache.view.page_range(current_page, page_size);
+        let (start_row, end_row) = (page_rows.start, page_rows.end);
 
         let search_lower = search_text.to_lowercase();
 
-        // Collect all search matches across filtered data to determine total count and current match position
-        let mut match_info = SearchMatchInfo::default();
-        let mut current_match_cell_position: Option<(usize, usize)> = None; // (row_index, col_index)
-        if !search_lower.is_empty() {
-            let mut match_count = 0;
-            for (filtered_idx, &original_row_idx) in filtered_indices.iter().enumerate() {
-                let row = &data.rows[original_row_idx];
-                for (col_idx, cell) in row.iter().enumerate() {
-                    if cell.to_lowercase().contains(&search_lower) {
-                        if match_count == current_match_index {
-                            // This is the current match - calculate its page and position
-                            let page = filtered_idx / page_size;
-                            let row_in_page = filtered_idx % page_size;
-                            match_info.current_match_page = Some(page);
-                            match_info.current_match_row_in_page = Some(row_in_page);
-                            current_match_cell_position = Some((original_row_idx, col_idx));
-                        }
-                        match_count += 1;
-                    }
-                }
+        // Keyboard navigation and copy, unless a text field (or the cell editor) has focus
+        let mut scroll_to_cursor = None;
+        if self.editor.is_none() && !self.selection.is_empty() && ui.memory(|m| m.focused().is_none()) {
+            let (step, extend, copy) = ui.input(|i| {
+                let step = [
+                    (egui::Key::ArrowUp, (-1, 0)),
+                    (egui::Key::ArrowDown, (1, 0)),
+                    (egui::Key::ArrowLeft, (0, -1)),
+                    (egui::Key::ArrowRight, (0, 1)),
+                ]
+                .into_iter()
+                .find(|(key, _)| i.key_pressed(*key))
+                .map(|(_, step)| step);
+                let copy = i.events.iter().any(|e| matches!(e, egui::Event::Copy));
+                (step, i.modifiers.shift, copy)
+            });
+
+            if let Some((d_row, d_col)) = step {
+                self.selection.move_cursor(d_row, d_col, extend, start_row..end_row, shown_columns.min(column_count));
+                scroll_to_cursor = self.selection.cursor().map(|(row, _)| row - start_row);
+                selection_changed = true;
             }
-            match_info.total_matches = match_count;
+            if copy {
+                let text = self.selection.copy_text(|row, column| cell_value(data, filtered_indices, row, column).map(|v| copied_text(column, v)));
+                ui.output_mut(|o| o.copied_text = text);
+            }
+        }
+
+        // Waits for the frame that shows the row's page
+        let reveal_row = self.reveal_row.filter(|row| (start_row..end_row).contains(row));
+        if let Some(row) = reveal_row {
+            self.reveal_row = None;
+            self.selection.select_row(row, column_count);
+            selection_changed = true;
         }
 
+        let current_match_location = cache.view.current_match_location(page_size);
+        let match_info = SearchMatchInfo {
+            total_matches: cache.view.total_matches,
+            current_match_page: current_match_location.map(|(page, _)| page),
+            current_match_row_in_page: current_match_location.map(|(_, row)| row),
+        };
+
+        // Frozen columns stay in a pane of their own left of the horizontally scrolled one,
+        // both held at the same vertical offset
+        let frozen: Vec<usize> = frozen_columns.iter().copied().filter(|&column| column < column_count).collect();
+        let key_columns: Vec<usize> = (0..column_count).filter(|&column| data.columns[column].is_primary_key).collect();
+        let scroll_target = if let Some(row_in_page) = scroll_to_cursor {
+            // Keep the keyboard cursor in view, otherwise scroll to the current match
+            Some((row_in_page, None))
+        } else if let Some(row) = reveal_row {
+            Some((row - start_row, Some(egui::Align::Center)))
+        } else if std::mem::take(&mut self.scroll_to_bottom) && end_row > start_row {
+            Some((end_row - start_row - 1, Some(egui::Align::BOTTOM)))
+        } else {
+            match_info.current_match_row_in_page.map(|row_in_page| (row_in_page, Some(egui::Align::Center)))
+        };
+        // Spread of a numeric sort column, under the toolbars and above its header
+        if let Some(aggregate) = &cache.aggregate {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.y = 0.0;
+                ui.label(egui::RichText::new(format!("↕ {}", data.columns[aggregate.column].name)).small().strong());
+                ui.label(egui::RichText::new(format!("{}  ({} values)", aggregate.summary(), aggregate.count)).small().color(egui::Color32::GRAY));
+            });
+        }
+
+        let synced_offset = self.frozen_offset;
         let available_height = ui.available_height();
-        egui::ScrollArea::both()
-            .id_source("data_grid")
-            .max_height(available_height)
-            .auto_shrink([false; 2])
-            .show(ui, |ui| {
-                use egui_extras::{Column, TableBuilder};
-
-                let mut table = TableBuilder::new(ui)
-                    .striped(true)
-                    .resizable(true)
-                    .vscroll(true)
-                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-                    .column(Column::initial(50.0).at_least(40.0).resizable(false)) // Line number column
-                    .columns(Column::initial(120.0).at_least(80.0).resizable(true).clip(true), data.columns.len())
-                    .min_scrolled_height(available_height);
-
-                // Scroll to the row containing the current match
-                if let Some(row_in_page) = match_info.current_match_row_in_page {
-                    table = table.scroll_to_row(row_in_page, Some(egui::Align::Center));
-                }
-
-                table
-                    .header(22.0, |mut header| {
-                        // Line number header
+
+        // One table of the grid with the columns set in `pane`, returning where it is scrolled to
+        let mut grid_table = |ui: &mut egui::Ui, pane: &[bool], line_numbers: bool, frozen_pane: bool| -> Option<f32> {
+            use egui_extras::{Column, TableBuilder};
+
+            // Id of the scroll area the table puts its rows in
+            let scroll_id = ui.make_persistent_id(egui::Id::new("scroll_area"));
+            let mut table = TableBuilder::new(ui)
+                .striped(true)
+                .resizable(true)
+                .vscroll(true)
+                .cell_layout(egui::Layout::left_to_right(egui::Align::Center));
+            if line_numbers {
+                table = table.column(Column::initial(50.0).at_least(40.0).resizable(false)); // Line number column
+            }
+            table = table
+                .columns(Column::initial(120.0).at_least(80.0).resizable(true).clip(true), pane.iter().filter(|&&shown| shown).count())
+                .min_scrolled_height(available_height);
+            if frozen_pane {
+                // The scrolled pane's bar moves both
+                table = table.scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::AlwaysHidden);
+            }
+
+            if let Some((row_in_page, align)) = scroll_target {
+                table = table.scroll_to_row(row_in_page, align);
+            } else if !frozen.is_empty() {
+                table = table.vertical_scroll_offset(synced_offset);
+            }
+
+            let header_height = if filter_row.is_some() { 46.0 } else { 22.0 };
+            table
+                .header(header_height, |mut header| {
+                    // Line number header
+                    if line_numbers {
                         header.col(|ui| {
                             ui.vertical(|ui| {
                                 ui.strong("#");
@@ -130,59 +430,113 @@ impl DataGrid {
                                 ui.separator();
                             });
                         });
+                    }
 
-                        // Data column headers
-                        for (col_index, column) in data.columns.iter().enumerate() {
-                            header.col(|ui| {
-                                ui.vertical(|ui| {
-                                    ui.horizontal(|ui| {
-                                        // Add key indicator
-                                        if column.is_primary_key {
-                                            ui.label(egui::RichText::new("🔑").color(egui::Color32::from_rgb(255, 215, 0)));
-                                        } else if column.is_foreign_key {
-                                            ui.label(egui::RichText::new("🔗").color(egui::Color32::from_rgb(150, 150, 255)));
-                                        }
-
-                                        // Create clickable header with sort indicator
-                                        let sort_indicator = if sort_column == Some(col_index) {
-                                            if sort_ascending { " ▲" } else { " ▼" }
-                                        } else {
-                                            ""
-                                        };
+                    // Data column headers
+                    for (col_index, column) in data.columns.iter().enumerate().filter(|&(col_index, _)| pane[col_index]) {
+                        header.col(|ui| {
+                            // Columns scrolled out of view keep their width but skip their contents
+                            if !ui.is_rect_visible(ui.max_rect()) {
+                                return;
+                            }
+                            ui.vertical(|ui| {
+                                ui.horizontal(|ui| {
+                                    // Add key indicator
+                                    if column.is_primary_key {
+                                        ui.label(egui::RichText::new("🔑").color(egui::Color32::from_rgb(255, 215, 0)));
+                                    } else if column.is_foreign_key {
+                                        ui.label(egui::RichText::new("🔗").color(egui::Color32::from_rgb(150, 150, 255)));
+                                    }
 
-                                        // Column name (strong)
-                                        let header_text = format!("{}{}", column.name, sort_indicator);
-                                        if ui.button(egui::RichText::new(header_text).strong()).clicked() {
-                                            column_to_sort.set(Some(col_index));
+                                    // Clickable header with cached sort indicator
+                                    let mut response = ui.button(egui::RichText::new(&header_labels[col_index]).strong());
+                                    if let Some(chain) = data.lineage.get(&column.name) {
+                                        response = response.on_hover_text(lineage_tooltip(chain));
+                                    }
+                                    if response.clicked() {
+                                        column_to_sort.set(Some(col_index));
+                                    }
+                                    response.context_menu(|ui| {
+                                        if ui.button("📊 Value Distribution").clicked() {
+                                            charted_column = Some(col_index);
+                                            ui.close_menu();
+                                        }
+                                        ui.separator();
+                                        if ui.button("📌 Freeze Up To Here").clicked() {
+                                            frozen_changed = Some((0..=col_index).collect());
+                                            ui.close_menu();
+                                        }
+                                        if !key_columns.is_empty() && key_columns != frozen && ui.button("📌 Freeze Primary Key").clicked() {
+                                            frozen_changed = Some(key_columns.clone());
+                                            ui.close_menu();
+                                        }
+                                        if !frozen.is_empty() && ui.button("Unfreeze Columns").clicked() {
+                                            frozen_changed = Some(Vec::new());
+                                            ui.close_menu();
+                                        }
+                                        ui.separator();
+                                        if ui.button("ƒ Add Computed Column…").clicked() {
+                                            computed_change = Some(DataGridEvent::AddComputedColumn);
+                                            ui.close_menu();
+                                        }
+                                        if is_computed(column) && ui.button("Remove Computed Column").clicked() {
+                                            computed_change = Some(DataGridEvent::RemoveComputedColumn(col_index));
+                                            ui.close_menu();
+                                        }
+                                        // Key columns identify the rows being updated, so they stay as they are
+                                        if editable && !column.is_primary_key && !is_computed(column) {
+                                            ui.separator();
+                                            if ui.button("🔁 Replace Values…").clicked() {
+                                                replaced_column = Some(col_index);
+                                                ui.close_menu();
+                                            }
                                         }
                                     });
+                                });
 
-                                    // Data type (gray, smaller text)
-                                    ui.label(egui::RichText::new(&column.data_type)
-                                        .size(9.0)
-                                        .color(egui::Color32::from_rgb(150, 150, 150)));
+                                // Data type (gray, smaller text)
+                                ui.label(egui::RichText::new(&column.data_type)
+                                    .size(9.0)
+                                    .color(egui::Color32::from_rgb(150, 150, 150)));
+
+                                if let Some(filters) = filter_row {
+                                    let mut text = column_filter(filters, col_index).to_string();
+                                    let response = ui.add(egui::TextEdit::singleline(&mut text)
+                                        .hint_text("Contains…")
+                                        .desired_width(f32::INFINITY));
+                                    if response.changed() {
+                                        filtered_column = Some((col_index, text));
+                                    }
+                                }
 
-                                    ui.add_space(2.0);
-                                    ui.separator();
-                                });
+                                ui.add_space(2.0);
+                                ui.separator();
                             });
-                        }
-                    })
-                    .body(|mut body| {
-                        // Only show rows for current page from filtered indices
-                        let page_indices = &filtered_indices[start_row..end_row];
-                        for (page_row_index, &original_row_index) in page_indices.iter().enumerate() {
-                            let row = &data.rows[original_row_index];
-                            let actual_row_index = start_row + page_row_index;
-                            let is_selected = self.selected_row == Some(actual_row_index);
-
-                            body.row(18.0, |mut row_ui| {
-                                // Line number cell
+                        });
+                    }
+                })
+                .body(|mut body| {
+                    // Only show rows for current page from filtered indices
+                    let page_indices = &filtered_indices[start_row..end_row];
+                    for (page_row_index, &original_row_index) in page_indices.iter().enumerate() {
+                        let row = &data.rows[original_row_index];
+                        let actual_row_index = start_row + page_row_index;
+                        let is_row_selected = self.selection.is_row_selected(actual_row_index, column_count);
+                        let row_key = edit_context.as_ref()
+                            .filter(|ctx| !ctx.pending.is_empty() || !ctx.bookmarks.is_empty())
+                            .map(|ctx| PendingEdits::row_key(row, ctx.key_columns));
+                        let is_bookmarked = edit_context.as_ref()
+                            .zip(row_key.as_ref())
+                            .is_some_and(|(ctx, key)| ctx.bookmarks.contains(key));
+
+                        body.row(18.0, |mut row_ui| {
+                            // Line number cell
+                            if line_numbers {
                                 row_ui.col(|ui| {
                                     let rect = ui.available_rect_before_wrap();
 
                                     // Add background color for selected row
-                                    if is_selected {
+                                    if is_row_selected {
                                         ui.painter().rect_filled(
                                             rect,
                                             0.0,
@@ -193,268 +547,366 @@ impl DataGrid {
                                     // Interact with entire cell area for row selection
                                     let cell_response = ui.interact(rect, ui.id().with(actual_row_index), egui::Sense::click());
 
-                                    // Left click anywhere in cell to select row
+                                    // Left click on the line number selects the whole row, Ctrl/Cmd adds it
                                     if cell_response.clicked() {
-                                        if is_selected {
-                                            self.selected_row = None;
+                                        if ui.input(|i| i.modifiers.command) {
+                                            self.selection.toggle_row(actual_row_index, column_count);
+                                        } else if is_row_selected {
+                                            self.selection.clear();
                                         } else {
-                                            self.selected_row = Some(actual_row_index);
+                                            self.selection.select_row(actual_row_index, column_count);
                                         }
+                                        selection_changed = true;
                                     }
 
-                                    // Display line number (1-indexed)
-                                    ui.label(egui::RichText::new(format!("{}", actual_row_index + 1))
-                                        .color(egui::Color32::from_rgb(150, 150, 150)));
+                                    // Display line number (1-indexed), starred when bookmarked
+                                    if is_bookmarked {
+                                        ui.label(egui::RichText::new(format!("★{}", actual_row_index + 1))
+                                            .color(egui::Color32::from_rgb(220, 160, 0)));
+                                    } else {
+                                        ui.label(egui::RichText::new(format!("{}", actual_row_index + 1))
+                                            .color(egui::Color32::from_rgb(150, 150, 150)));
+                                    }
+
+                                    let comparison = self.compared_rows(filtered_indices);
+                                    if edit_context.is_some() || comparison.is_some() {
+                                        cell_response.context_menu(|ui| {
+                                            if edit_context.is_some() {
+                                                let text = if is_bookmarked { "Remove Bookmark" } else { "★ Bookmark Row" };
+                                                if ui.button(text).clicked() {
+                                                    toggled_bookmark = Some(original_row_index);
+                                                    ui.close_menu();
+                                                }
+                                            }
+                                            if comparison.is_some() && ui.button("⇄ Compare Rows").clicked() {
+                                                compared_rows = comparison;
+                                                ui.close_menu();
+                                            }
+                                        });
+                                    }
                                 });
+                            }
 
-                                // Data cells
-                                for (col_idx, cell) in row.iter().enumerate() {
-                                    row_ui.col(|ui| {
-                                        // Get the full cell rect
-                                        let rect = ui.available_rect_before_wrap();
-
-                                        // Check if this cell matches the search text
-                                        let has_search_match = !search_lower.is_empty()
-                                            && cell.to_lowercase().contains(&search_lower);
-
-                                        // Check if this is the current match
-                                        let is_current_match = current_match_cell_position
-                                            .map(|(row_idx, c_idx)| row_idx == original_row_index && c_idx == col_idx)
-                                            .unwrap_or(false);
-
-                                        // Add background color for selected row or search match
-                                        if is_selected {
-                                            ui.painter().rect_filled(
-                                                rect,
-                                                0.0,
-                                                egui::Color32::from_rgb(200, 200, 200)
-                                            );
-                                        } else if is_current_match {
-                                            ui.painter().rect_filled(
-                                                rect,
-                                                0.0,
-                                                egui::Color32::from_rgb(255, 180, 100)  // Orange highlight for current match
-                                            );
-                                        } else if has_search_match {
-                                            ui.painter().rect_filled(
-                                                rect,
-                                                0.0,
-                                                egui::Color32::from_rgb(255, 255, 150)  // Yellow highlight for other search matches
-                                            );
-                                        }
+                            // Data cells
+                            for (col_idx, cell) in row.iter().enumerate().filter(|&(col_idx, _)| pane[col_idx]) {
+                                row_ui.col(|ui| {
+                                    let is_edited = self.editor.as_ref().is_some_and(|e| e.row == original_row_index && e.column == col_idx);
+                                    if !is_edited && !ui.is_rect_visible(ui.max_rect()) {
+                                        return;
+                                    }
 
-                                        // Interact with entire cell area for row selection
-                                        let cell_response = ui.interact(rect, ui.id().with(actual_row_index), egui::Sense::click());
+                                    // Get the full cell rect
+                                    let rect = ui.available_rect_before_wrap();
+                                    let text = cell.to_string();
 
-                                        // Left click anywhere in cell to select row
-                                        if cell_response.clicked() {
-                                            if is_selected {
-                                                self.selected_row = None;
-                                            } else {
-                                                self.selected_row = Some(actual_row_index);
-                                            }
+                                    // Check if this cell matches the search text
+                                    let has_search_match = !search_lower.is_empty() && cell_matches(cell, &search_lower);
+
+                                    // Check if this is the current match
+                                    let is_current_match = current_match_cell_position
+                                        .map(|(row_idx, c_idx)| row_idx == original_row_index && c_idx == col_idx)
+                                        .unwrap_or(false);
+
+                                    let is_selected = self.selection.contains(actual_row_index, col_idx);
+
+                                    // Add background color for selected cell or search match
+                                    if is_selected {
+                                        ui.painter().rect_filled(
+                                            rect,
+                                            0.0,
+                                            egui::Color32::from_rgb(200, 200, 200)
+                                        );
+                                    } else if is_current_match {
+                                        ui.painter().rect_filled(
+                                            rect,
+                                            0.0,
+                                            egui::Color32::from_rgb(255, 180, 100)  // Orange highlight for current match
+                                        );
+                                    } else if has_search_match {
+                                        ui.painter().rect_filled(
+                                            rect,
+                                            0.0,
+                                            egui::Color32::from_rgb(255, 255, 150)  // Yellow highlight for other search matches
+                                        );
+                                    }
+
+                                    // Interact with entire cell area for cell selection
+                                    let cell_response = ui.interact(rect, ui.id().with(actual_row_index), egui::Sense::click_and_drag());
+
+                                    ui.style_mut().wrap = Some(false);
+
+                                    // Cell being edited in place
+                                    if let Some(editor) = self.editor.as_mut().filter(|e| e.row == original_row_index && e.column == col_idx) {
+                                        let response = ui.add(
+                                            egui::TextEdit::singleline(&mut editor.buffer)
+                                                .desired_width(f32::INFINITY)
+                                        );
+                                        if !editor.focused {
+                                            response.request_focus();
+                                            editor.focused = true;
                                         }
 
-                                        ui.style_mut().wrap = Some(false);
+                                        if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                                            self.editor = None;
+                                        } else if response.lost_focus() {
+                                            edited_cell = Some((original_row_index, col_idx, editor.buffer.clone()));
+                                            self.editor = None;
+                                        }
+                                        return;
+                                    }
 
-                                        let label_response = ui.add(
-                                            egui::Label::new(cell)
-                                                .truncate(true)
-                                                .selectable(true)
+                                    // Staged edits are shown with their new value
+                                    let staged = edit_context.as_ref()
+                                        .zip(row_key.as_ref())
+                                        .and_then(|(ctx, key)| ctx.pending.get(key, col_idx));
+                                    if staged.is_some() && !is_selected {
+                                        ui.painter().rect_filled(
+                                            rect,
+                                            0.0,
+                                            egui::Color32::from_rgb(190, 220, 255)  // Blue highlight for staged edits
                                         );
+                                    }
+                                    let display_value = staged.map(|edit| edit.value.clone()).unwrap_or(text);
+                                    // Dimmed so NULL never reads like a text value
+                                    let label = if staged.is_none() && cell.is_null() {
+                                        egui::RichText::new("NULL")
+                                            .italics()
+                                            .color(egui::Color32::from_rgb(150, 150, 150))
+                                    } else {
+                                        egui::RichText::new(display_value.as_str())
+                                    };
+
+                                    // Not text-selectable, so dragging selects cells instead of text
+                                    ui.add(
+                                        egui::Label::new(label)
+                                            .truncate(true)
+                                            .selectable(false)
+                                    );
+
+                                    let modifiers = ui.input(|i| i.modifiers);
+
+                                    // Dragging selects the rectangle between the start cell and the one under the pointer
+                                    if cell_response.drag_started() && !modifiers.command {
+                                        if modifiers.shift {
+                                            self.selection.extend_to(actual_row_index, col_idx);
+                                        } else {
+                                            self.selection.select_cell(actual_row_index, col_idx);
+                                        }
+                                        self.drag_selecting = true;
+                                        selection_changed = true;
+                                    } else if self.drag_selecting
+                                        && self.selection.cursor() != Some((actual_row_index, col_idx))
+                                        && ui.rect_contains_pointer(rect)
+                                    {
+                                        self.selection.extend_to(actual_row_index, col_idx);
+                                        selection_changed = true;
+                                    }
 
-                                        // Right click context menu to copy cell value
-                                        label_response.context_menu(|ui| {
-                                            if ui.button("Copy Cell Value").clicked() {
-                                                ui.output_mut(|o| o.copied_text = cell.clone());
+                                    // Click selects the cell, Shift extends the rectangle, Ctrl/Cmd toggles single cells
+                                    if cell_response.clicked() {
+                                        if modifiers.shift {
+                                            self.selection.extend_to(actual_row_index, col_idx);
+                                        } else if modifiers.command {
+                                            self.selection.toggle_cell(actual_row_index, col_idx);
+                                        } else {
+                                            self.selection.select_cell(actual_row_index, col_idx);
+                                        }
+                                        selection_changed = true;
+                                    }
+
+                                    // Double click starts editing when the table is editable
+                                    let computed = data.columns.get(col_idx).is_some_and(is_computed);
+                                    if editable && !computed && cell_response.double_clicked() {
+                                        self.editor = Some(CellEditor {
+                                            row: original_row_index,
+                                            column: col_idx,
+                                            buffer: display_value.clone(),
+                                            focused: false,
+                                        });
+                                    }
+
+                                    // Right click context menu to copy cell value or the selection
+                                    cell_response.context_menu(|ui| {
+                                        if ui.button("Copy Cell Value").clicked() {
+                                            let text = match anonymizer {
+                                                Some(anonymizer) => anonymizer.value(col_idx, cell).to_string(),
+                                                None => display_value.clone(),
+                                            };
+                                            ui.output_mut(|o| o.copied_text = text);
+                                            ui.close_menu();
+                                        }
+                                        if !self.selection.is_empty() && ui.button("Copy Selection").clicked() {
+                                            let text = self.selection.copy_text(|row, column| cell_value(data, filtered_indices, row, column).map(|v| copied_text(column, v)));
+                                            ui.output_mut(|o| o.copied_text = text);
+                                            ui.close_menu();
+                                        }
+                                        let json_label = if self.selection.is_empty() { "Copy Results as JSON" } else { "Copy Selection as JSON" };
+                                        if ui.button(json_label).clicked() {
+                                            let text = self.selection_json(data, filtered_indices, anonymizer);
+                                            ui.output_mut(|o| o.copied_text = text);
+                                            ui.close_menu();
+                                        }
+                                        if ui.button("🔍 Inspect Cell…").clicked() {
+                                            inspected_cell = Some((original_row_index, col_idx));
+                                            ui.close_menu();
+                                        }
+                                        // JSON documents as shown, so anonymized ones are not exposed
+                                        let shown = anonymizer.map_or_else(|| cell.clone(), |anonymizer| anonymizer.value(col_idx, cell));
+                                        let data_type = data.columns.get(col_idx).map_or("", |c| c.data_type.as_str());
+                                        if let CellValue::Bytes(bytes) = &shown {
+                                            if ui.button("Copy as Hex").clicked() {
+                                                ui.output_mut(|o| o.copied_text = to_hex(bytes));
                                                 ui.close_menu();
                                             }
-                                        });
+                                            if ui.button("Copy as Base64").clicked() {
+                                                ui.output_mut(|o| o.copied_text = to_base64(bytes));
+                                                ui.close_menu();
+                                            }
+                                            if ui.button("💾 Save to File…").clicked() {
+                                                saved_bytes = Some((original_row_index, col_idx));
+                                                ui.close_menu();
+                                            }
+                                        }
+                                        if let Some(document) = cell_json(&shown, data_type) {
+                                            if ui.button("Copy as Formatted JSON").clicked() {
+                                                ui.output_mut(|o| o.copied_text = serde_json::to_string_pretty(&document).unwrap_or_default());
+                                                ui.close_menu();
+                                            }
+                                            let paths = sample_json_paths([&CellValue::Json(document.to_string())]);
+                                            ui.add_enabled_ui(!paths.is_empty(), |ui| {
+                                                ui.menu_button("Copy JSON Path Value", |ui| {
+                                                    for path in paths {
+                                                        let keys: Vec<_> = path.iter().map(|key| JsonPathStep::Key(key.clone())).collect();
+                                                        let Some(value) = extract_json_path(&document, &keys) else {
+                                                            continue;
+                                                        };
+                                                        if ui.button(json_path_text(&path)).on_hover_text(json_value_text(value)).clicked() {
+                                                            ui.output_mut(|o| o.copied_text = json_value_text(value));
+                                                            ui.close_menu();
+                                                        }
+                                                    }
+                                                });
+                                            });
+                                        }
+                                        if let Some(comparison) = self.compared_rows(filtered_indices) {
+                                            if ui.button("⇄ Compare Rows").clicked() {
+                                                compared_rows = Some(comparison);
+                                                ui.close_menu();
+                                            }
+                                        }
+                                        if data.columns.get(col_idx).is_some_and(|c| c.is_foreign_key) && !cell.is_null() {
+                                            ui.separator();
+                                            if ui.button("🔗 Follow Foreign Key").clicked() {
+                                                followed_key = Some((original_row_index, col_idx));
+                                                ui.close_menu();
+                                            }
+                                        }
                                     });
-                                }
-                            });
-                        }
-                    });
-            });
+                                });
+                            }
+                        });
+                    }
+                });
+            egui::scroll_area::State::load(ui.ctx(), scroll_id).map(|state| state.offset.y)
+        };
+
+        if frozen.is_empty() {
+            egui::ScrollArea::both()
+                .id_source("data_grid")
+                .max_height(available_height)
+                .auto_shrink([false; 2])
+                .show(ui, |ui| {
+                    let pane: Vec<bool> = (0..column_count).map(|column| column < shown_columns).collect();
+                    grid_table(ui, &pane, true, false);
+                });
+        } else {
+            let pane: Vec<bool> = (0..column_count).map(|column| frozen.contains(&column)).collect();
+            let scrolled_pane: Vec<bool> = pane.iter().enumerate().map(|(column, frozen)| !frozen && column < shown_columns).collect();
+            let (left, right) = ui.horizontal_top(|ui| {
+                let left = ui.push_id("frozen_columns", |ui| grid_table(ui, &pane, true, true)).inner;
+                let right = egui::ScrollArea::horizontal()
+                    .id_source("data_grid")
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| grid_table(ui, &scrolled_pane, false, false))
+                    .inner;
+                (left, right)
+            }).inner;
+
+            // Whichever pane was scrolled away from the shared offset moves the other one
+            let moved = [left, right].into_iter().flatten().find(|offset| (offset - synced_offset).abs() > 0.5);
+            if let Some(offset) = moved {
+                self.frozen_offset = offset;
+                ui.ctx().request_repaint();
+            }
+        }
+
+        if self.drag_selecting && !ui.input(|i| i.pointer.primary_down()) {
+            self.drag_selecting = false;
+        }
+
+        if selection_changed {
+            self.update_selection_stats(data, filtered_indices);
+        }
+
+        self.cache = Some(cache);
 
         // Handle column sort after the immutable borrow is released
         if let Some(col_index) = column_to_sort.get() {
             return (Some(DataGridEvent::ColumnSorted(col_index)), match_info);
         }
 
-        (None, match_info)
-    }
-}
+        if let Some((row, column, value)) = edited_cell {
+            return (Some(DataGridEvent::CellEdited(row, column, value)), match_info);
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::db::ColumnInfo;
-
-    fn create_test_data(rows: Vec<Vec<String>>) -> TableData {
-        TableData {
-            name: "test_table".to_string(),
-            columns: vec![
-                ColumnInfo {
-                    name: "col1".to_string(),
-                    data_type: "text".to_string(),
-                    is_primary_key: false,
-                    is_foreign_key: false,
-                },
-                ColumnInfo {
-                    name: "col2".to_string(),
-                    data_type: "text".to_string(),
-                    is_primary_key: false,
-                    is_foreign_key: false,
-                },
-            ],
-            rows,
+        if let Some((row, column)) = inspected_cell {
+            return (Some(DataGridEvent::InspectCell(row, column)), match_info);
         }
-    }
 
-    #[test]
-    fn test_search_match_counting() {
-        let data = create_test_data(vec![
-            vec!["apple".to_string(), "banana".to_string()],
-            vec!["cherry".to_string(), "apple".to_string()],
-            vec!["date".to_string(), "elderberry".to_string()],
-            vec!["apple".to_string(), "fig".to_string()],
-        ]);
-
-        // Test counting matches for "apple"
-        let search_text = "apple";
-        let mut count = 0;
-        for row in &data.rows {
-            for cell in row {
-                if cell.to_lowercase().contains(&search_text.to_lowercase()) {
-                    count += 1;
-                }
-            }
+        if let Some((row, column)) = saved_bytes {
+            return (Some(DataGridEvent::SaveBytes(row, column)), match_info);
         }
-        assert_eq!(count, 3, "Should find 3 occurrences of 'apple'");
-    }
 
-    #[test]
-    fn test_search_case_insensitive() {
-        let data = create_test_data(vec![
-            vec!["Apple".to_string(), "BANANA".to_string()],
-            vec!["cherry".to_string(), "aPpLe".to_string()],
-        ]);
-
-        let search_text = "apple";
-        let mut count = 0;
-        for row in &data.rows {
-            for cell in row {
-                if cell.to_lowercase().contains(&search_text.to_lowercase()) {
-                    count += 1;
-                }
-            }
+        if let Some(column) = charted_column {
+            return (Some(DataGridEvent::ShowDistribution(column)), match_info);
         }
-        assert_eq!(count, 2, "Should find 2 occurrences case-insensitively");
-    }
 
-    #[test]
-    fn test_search_partial_match() {
-        let data = create_test_data(vec![
-            vec!["pineapple".to_string(), "banana".to_string()],
-            vec!["apple".to_string(), "applesauce".to_string()],
-        ]);
-
-        let search_text = "apple";
-        let mut count = 0;
-        for row in &data.rows {
-            for cell in row {
-                if cell.to_lowercase().contains(&search_text.to_lowercase()) {
-                    count += 1;
-                }
-            }
+        if let Some((column, text)) = filtered_column {
+            return (Some(DataGridEvent::ColumnFilterChanged(column, text)), match_info);
         }
-        assert_eq!(count, 3, "Should find partial matches");
-    }
 
-    #[test]
-    fn test_page_calculation() {
-        let page_size = 10;
-
-        // Test various row positions
-        assert_eq!(0 / page_size, 0, "First row should be on page 0");
-        assert_eq!(9 / page_size, 0, "Row 9 should be on page 0");
-        assert_eq!(10 / page_size, 1, "Row 10 should be on page 1");
-        assert_eq!(15 / page_size, 1, "Row 15 should be on page 1");
-        assert_eq!(20 / page_size, 2, "Row 20 should be on page 2");
-    }
+        if let Some(event) = computed_change {
+            return (Some(event), match_info);
+        }
 
-    #[test]
-    fn test_row_in_page_calculation() {
-        let page_size = 10;
-
-        // Test row position within page
-        assert_eq!(0 % page_size, 0, "Row 0 should be at position 0 in page");
-        assert_eq!(9 % page_size, 9, "Row 9 should be at position 9 in page");
-        assert_eq!(10 % page_size, 0, "Row 10 should be at position 0 in page");
-        assert_eq!(15 % page_size, 5, "Row 15 should be at position 5 in page");
-        assert_eq!(23 % page_size, 3, "Row 23 should be at position 3 in page");
-    }
+        if let Some(columns) = frozen_changed {
+            return (Some(DataGridEvent::FreezeColumns(columns)), match_info);
+        }
 
-    #[test]
-    fn test_match_index_navigation() {
-        let total_matches = 5;
-
-        // Test forward navigation
-        let mut index = 0;
-        index = (index + 1) % total_matches;
-        assert_eq!(index, 1, "Next from 0 should be 1");
-
-        index = 4;
-        index = (index + 1) % total_matches;
-        assert_eq!(index, 0, "Next from 4 should wrap to 0");
-
-        // Test backward navigation
-        index = 1;
-        index = if index == 0 { total_matches - 1 } else { index - 1 };
-        assert_eq!(index, 0, "Previous from 1 should be 0");
-
-        index = 0;
-        index = if index == 0 { total_matches - 1 } else { index - 1 };
-        assert_eq!(index, 4, "Previous from 0 should wrap to 4");
-    }
+        if let Some(column) = replaced_column {
+            return (Some(DataGridEvent::ReplaceValues(column)), match_info);
+        }
 
-    #[test]
-    fn test_empty_search() {
-        let data = create_test_data(vec![
-            vec!["apple".to_string(), "banana".to_string()],
-        ]);
-
-        let search_text = "";
-        let mut count = 0;
-        for row in &data.rows {
-            for cell in row {
-                if !search_text.is_empty() && cell.to_lowercase().contains(&search_text.to_lowercase()) {
-                    count += 1;
-                }
-            }
+        if let Some(row) = toggled_bookmark {
+            return (Some(DataGridEvent::BookmarkToggled(row)), match_info);
         }
-        assert_eq!(count, 0, "Empty search should return 0 matches");
-    }
 
-    #[test]
-    fn test_no_matches() {
-        let data = create_test_data(vec![
-            vec!["apple".to_string(), "banana".to_string()],
-            vec!["cherry".to_string(), "date".to_string()],
-        ]);
-
-        let search_text = "xyz";
-        let mut count = 0;
-        for row in &data.rows {
-            for cell in row {
-                if cell.to_lowercase().contains(&search_text.to_lowercase()) {
-                    count += 1;
-                }
-            }
+        if let Some((row, column)) = followed_key {
+            return (Some(DataGridEvent::FollowForeignKey(row, column)), match_info);
         }
-        assert_eq!(count, 0, "Should find 0 matches for non-existent text");
+
+        if let Some((left, right)) = compared_rows {
+            return (Some(DataGridEvent::CompareRows(left, right)), match_info);
+        }
+
+        (None, match_info)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{ColumnInfo, ColumnLineage};
 
     #[test]
     fn test_search_match_info_default() {
@@ -465,43 +917,25 @@ mod tests {
     }
 
     #[test]
-    fn test_current_match_position() {
-        let page_size = 3;
-        let data = create_test_data(vec![
-            vec!["apple".to_string(), "banana".to_string()],  // row 0
-            vec!["cherry".to_string(), "apple".to_string()],  // row 1
-            vec!["date".to_string(), "elderberry".to_string()],  // row 2
-            vec!["apple".to_string(), "fig".to_string()],  // row 3 (page 1)
-            vec!["grape".to_string(), "apple".to_string()],  // row 4 (page 1)
-        ]);
-
-        // Simulate finding matches
-        let search_text = "apple";
-        let mut matches: Vec<(usize, usize)> = Vec::new(); // (row_idx, col_idx)
-
-        for (row_idx, row) in data.rows.iter().enumerate() {
-            for (col_idx, cell) in row.iter().enumerate() {
-                if cell.to_lowercase().contains(&search_text.to_lowercase()) {
-                    matches.push((row_idx, col_idx));
-                }
-            }
-        }
-
-        assert_eq!(matches.len(), 4, "Should find 4 matches");
-
-        // Test first match
-        let (row_idx, _) = matches[0];
-        assert_eq!(row_idx / page_size, 0, "First match should be on page 0");
-        assert_eq!(row_idx % page_size, 0, "First match should be at row 0 in page");
-
-        // Test third match
-        let (row_idx, _) = matches[2];
-        assert_eq!(row_idx / page_size, 1, "Third match should be on page 1");
-        assert_eq!(row_idx % page_size, 0, "Third match should be at row 0 in page");
-
-        // Test fourth match
-        let (row_idx, _) = matches[3];
-        assert_eq!(row_idx / page_size, 1, "Fourth match should be on page 1");
-        assert_eq!(row_idx % page_size, 1, "Fourth match should be at row 1 in page");
+    fn test_cache_follows_rows_changed_in_place() {
+        let column = ColumnInfo::test("name", "text");
+        let mut data = TableData {
+            name: "t".to_string(),
+            columns: vec![column],
+            rows: vec![vec![CellValue::from("ann")], vec![CellValue::from("bob")]],
+            lineage: ColumnLineage::new(),
+            generation: 0,
+        };
+        let mut grid = DataGrid::new();
+        let matches = |grid: &DataGrid| grid.cache.as_ref().unwrap().view.total_matches;
+
+        grid.refresh_cache(&data, None, true, 100, &[], "bob", 0);
+        assert_eq!(matches(&grid), 1);
+
+        // An applied edit rewrites the cell where it is
+        data.rows[0][0] = CellValue::from("bob");
+        data.touch();
+        grid.refresh_cache(&data, None, true, 100, &[], "bob", 0);
+        assert_eq!(matches(&grid), 2);
     }
 }
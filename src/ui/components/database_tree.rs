@@ -1,23 +1,77 @@
-use crate::db::SchemaInfo;
+use crate::config::{QueryTemplate, SchemaStyle};
+use crate::db::sql::TimeBucket;
+use crate::db::{ColumnInfo, FunctionInfo, Privilege, SchemaInfo, SourceObject};
+use crate::export::{is_date_type, CodeTarget};
 use eframe::egui;
 use std::collections::HashSet;
 
+/// Opens a query from the first template for the selected table.
+pub const NEW_QUERY_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT), egui::Key::N);
+
 #[derive(Debug)]
 pub enum DatabaseTreeEvent {
     TableClicked(String, String),
+    TableSelected(String, String),
+    TableDoubleClicked(String, String),
     TableRightClicked(String, String),
     TableToggled(String, String),
+    NewQueryFromTemplate(String, String, usize), // schema, table, template index
+    CopyAsCode(String, String, CodeTarget),
+    ShowSource(String, SourceObject), // schema, view or routine
+    RefreshMaterializedView(String, String),
+    AlterColumns(String, String),
+    ImportFile(String, String),
+    ExportSql(String, Option<String>), // schema, table or None for all of the schema's tables
+    BucketByTime(String, String, String, TimeBucket), // schema, table, timestamp column, bucket
+    SequenceValue(String, String),
     SchemaToggled(String),
+    SchemaStyled(String, Option<SchemaStyle>), // schema, its own style or None to clear it
     SearchChanged(String),
 }
 
-pub struct DatabaseTree;
+// Objects of one schema that pass the search filter
+struct SchemaMatches<'a> {
+    tables: Vec<String>,
+    views: Vec<&'a String>,
+    materialized_views: Vec<&'a String>,
+    functions: Vec<&'a FunctionInfo>,
+    sequences: Vec<&'a String>,
+}
+
+impl<'a> SchemaMatches<'a> {
+    fn new(schema: &'a SchemaInfo, matches: impl Fn(&str) -> bool) -> Self {
+        Self {
+            tables: schema.tables.iter().filter(|t| matches(t)).cloned().collect(),
+            views: schema.views.iter().filter(|v| matches(v)).collect(),
+            materialized_views: schema.materialized_views.iter().filter(|v| matches(v)).collect(),
+            functions: schema.functions.iter().filter(|f| matches(&f.name)).collect(),
+            sequences: schema.sequences.iter().filter(|s| matches(s)).collect(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.tables.len() + self.views.len() + self.materialized_views.len() + self.functions.len() + self.sequences.len()
+    }
+}
+
+pub struct DatabaseTree {
+    request_focus: bool,
+}
 
 impl DatabaseTree {
     pub fn new() -> Self {
-        Self
+        Self {
+            request_focus: false,
+        }
     }
 
+    /// Focus the search input the next time the tree is shown.
+    pub fn focus_search(&mut self) {
+        self.request_focus = true;
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn show(
         &mut self,
         ui: &mut egui::Ui,
@@ -26,6 +80,8 @@ impl DatabaseTree {
         expanded_tables: &HashSet<(String, String)>,
         selected_table: &Option<(String, String)>,
         search_query: &mut String,
+        templates: &[QueryTemplate],
+        schema_styles: &[SchemaStyle],
     ) -> Option<DatabaseTreeEvent> {
         let mut event = None;
 
@@ -37,6 +93,10 @@ impl DatabaseTree {
                     .hint_text("Search...")
                     .desired_width(180.0)
             );
+            if self.request_focus {
+                response.request_focus();
+                self.request_focus = false;
+            }
 
             if response.changed() {
                 event = Some(DatabaseTreeEvent::SearchChanged(search_query.clone()));
@@ -50,26 +110,21 @@ impl DatabaseTree {
 
         ui.separator();
 
-        // Filter schemas and tables based on search query
+        // Filter schema objects based on search query
         let search_lower = search_query.to_lowercase();
         let filtered_schemas: Vec<_> = if search_query.is_empty() {
-            schemas.iter().map(|s| (s, s.tables.clone())).collect()
+            schemas.iter().map(|s| (s, SchemaMatches::new(s, |_| true))).collect()
         } else {
             schemas
                 .iter()
                 .filter_map(|schema| {
                     let schema_matches = schema.name.to_lowercase().contains(&search_lower);
-                    let filtered_tables: Vec<_> = schema
-                        .tables
-                        .iter()
-                        .filter(|table| {
-                            schema_matches || table.to_lowercase().contains(&search_lower)
-                        })
-                        .cloned()
-                        .collect();
-
-                    if !filtered_tables.is_empty() {
-                        Some((schema, filtered_tables))
+                    let matches = SchemaMatches::new(schema, |name| {
+                        schema_matches || name.to_lowercase().contains(&search_lower)
+                    });
+
+                    if matches.len() > 0 {
+                        Some((schema, matches))
                     } else {
                         None
                     }
@@ -79,8 +134,8 @@ impl DatabaseTree {
 
         // Show results count if searching
         if !search_query.is_empty() {
-            let total_tables: usize = filtered_schemas.iter().map(|(_, tables)| tables.len()).sum();
-            ui.label(egui::RichText::new(format!("Found {} table(s) in {} schema(s)", total_tables, filtered_schemas.len()))
+            let total_objects: usize = filtered_schemas.iter().map(|(_, matches)| matches.len()).sum();
+            ui.label(egui::RichText::new(format!("Found {} object(s) in {} schema(s)", total_objects, filtered_schemas.len()))
                 .size(10.0)
                 .color(egui::Color32::GRAY));
             ui.separator();
@@ -97,7 +152,7 @@ impl DatabaseTree {
                     });
                 }
 
-                for (schema, filtered_tables) in &filtered_schemas {
+                for (schema, matches) in &filtered_schemas {
                     let is_expanded = expanded_schemas.contains(&schema.name) || !search_query.is_empty();
 
                     // Schema row with expand/collapse arrow
@@ -109,21 +164,39 @@ impl DatabaseTree {
                             }
                         }
 
-                        // Highlight schema name if it matches search
+                        // Highlight schema name if it matches search, otherwise use its style
+                        let style = schema_styles.iter().find(|style| style.matches(&schema.name));
+                        let label = match style.filter(|style| !style.icon.is_empty()) {
+                            Some(style) => format!("{} {}", style.icon, schema.name),
+                            None => schema.name.clone(),
+                        };
                         let schema_text = if !search_query.is_empty() && schema.name.to_lowercase().contains(&search_lower) {
-                            egui::RichText::new(&schema.name).strong().color(egui::Color32::from_rgb(100, 200, 255))
+                            egui::RichText::new(label).strong().color(egui::Color32::from_rgb(100, 200, 255))
+                        } else if let Some([r, g, b]) = style.and_then(|style| style.color) {
+                            egui::RichText::new(label).strong().color(egui::Color32::from_rgb(r, g, b))
                         } else {
-                            egui::RichText::new(&schema.name).strong()
+                            egui::RichText::new(label).strong()
                         };
 
-                        ui.label(schema_text);
-                        ui.label(format!("({})", filtered_tables.len()));
+                        let response = ui.add(egui::Label::new(schema_text).sense(egui::Sense::click()));
+                        response.context_menu(|ui| {
+                            if ui.button("💾 Export as SQL…").clicked() {
+                                event = Some(DatabaseTreeEvent::ExportSql(schema.name.clone(), None));
+                                ui.close_menu();
+                            }
+                            ui.separator();
+                            if let Some(styled) = Self::style_menu(ui, &schema.name, style) {
+                                event = Some(styled);
+                                ui.close_menu();
+                            }
+                        });
+                        ui.label(format!("({})", matches.tables.len()));
                     });
 
                     // Show tables if expanded or searching
                     if is_expanded {
                         ui.indent(&schema.name, |ui| {
-                            for table in filtered_tables {
+                            for table in &matches.tables {
                                 let is_selected = selected_table.as_ref() == Some(&(schema.name.clone(), table.clone()));
                                 let table_key = (schema.name.clone(), table.clone());
                                 let is_table_expanded = expanded_tables.contains(&table_key);
@@ -145,8 +218,10 @@ impl DatabaseTree {
 
                                     let response = ui.selectable_label(is_selected, table_text);
 
-                                    if response.clicked() {
-                                        event = Some(DatabaseTreeEvent::TableClicked(schema.name.clone(), table.clone()));
+                                    if response.double_clicked() {
+                                        event = Some(DatabaseTreeEvent::TableDoubleClicked(schema.name.clone(), table.clone()));
+                                    } else if response.clicked() {
+                                        event = Some(DatabaseTreeEvent::TableSelected(schema.name.clone(), table.clone()));
                                     }
 
                                     response.context_menu(|ui| {
@@ -154,6 +229,75 @@ impl DatabaseTree {
                                             event = Some(DatabaseTreeEvent::TableRightClicked(schema.name.clone(), table.clone()));
                                             ui.close_menu();
                                         }
+                                        ui.menu_button("New Query", |ui| {
+                                            if templates.is_empty() {
+                                                ui.label(egui::RichText::new("No templates, add some in Settings")
+                                                    .color(egui::Color32::GRAY));
+                                            }
+                                            for (index, template) in templates.iter().enumerate() {
+                                                let mut button = egui::Button::new(&template.name);
+                                                if index == 0 {
+                                                    button = button.shortcut_text(ui.ctx().format_shortcut(&NEW_QUERY_SHORTCUT));
+                                                }
+                                                // Writes the current role may not perform on this table
+                                                let denied = Privilege::for_statement(&template.sql)
+                                                    .and_then(|privilege| schema.denied(table, privilege));
+                                                let response = ui.add_enabled(denied.is_none(), button)
+                                                    .on_disabled_hover_text(denied.unwrap_or_default());
+                                                if response.clicked() {
+                                                    event = Some(DatabaseTreeEvent::NewQueryFromTemplate(schema.name.clone(), table.clone(), index));
+                                                    ui.close_menu();
+                                                }
+                                            }
+                                        });
+                                        ui.menu_button("Copy as…", |ui| {
+                                            for target in CodeTarget::all() {
+                                                if ui.button(target.as_str()).clicked() {
+                                                    event = Some(DatabaseTreeEvent::CopyAsCode(schema.name.clone(), table.clone(), target));
+                                                    ui.close_menu();
+                                                }
+                                            }
+                                        });
+                                        if ui.button("Alter Columns…").clicked() {
+                                            event = Some(DatabaseTreeEvent::AlterColumns(schema.name.clone(), table.clone()));
+                                            ui.close_menu();
+                                        }
+                                        let denied = schema.denied(table, Privilege::Insert);
+                                        if ui.add_enabled(denied.is_none(), egui::Button::new("📥 Import CSV/TSV…"))
+                                            .on_disabled_hover_text(denied.unwrap_or_default())
+                                            .clicked()
+                                        {
+                                            event = Some(DatabaseTreeEvent::ImportFile(schema.name.clone(), table.clone()));
+                                            ui.close_menu();
+                                        }
+                                        if ui.button("💾 Export as SQL…").clicked() {
+                                            event = Some(DatabaseTreeEvent::ExportSql(schema.name.clone(), Some(table.clone())));
+                                            ui.close_menu();
+                                        }
+                                        // Row counts over time, for tables with a date or timestamp column
+                                        let time_columns: Vec<&ColumnInfo> = schema.table_columns.get(table)
+                                            .map(|columns| columns.iter().filter(|c| is_date_type(&c.data_type)).collect())
+                                            .unwrap_or_default();
+                                        if !time_columns.is_empty() {
+                                            ui.menu_button("📈 Bucket by…", |ui| {
+                                                for column in &time_columns {
+                                                    // Pick the column first when there is more than one
+                                                    let mut buckets = |ui: &mut egui::Ui| {
+                                                        for bucket in TimeBucket::all() {
+                                                            if ui.button(bucket.as_str()).clicked() {
+                                                                event = Some(DatabaseTreeEvent::BucketByTime(schema.name.clone(), table.clone(), column.name.clone(), bucket));
+                                                                ui.close_menu();
+                                                            }
+                                                        }
+                                                    };
+                                                    if time_columns.len() == 1 {
+                                                        buckets(ui);
+                                                    } else {
+                                                        ui.menu_button(&column.name, buckets);
+                                                    }
+                                                }
+                                            });
+                                        }
                                     });
                                 });
 
@@ -202,6 +346,82 @@ impl DatabaseTree {
                                     }
                                 }
                             }
+
+                            let is_searching = !search_query.is_empty();
+                            let is_highlighted = |name: &str| is_searching && name.to_lowercase().contains(&search_lower);
+                            let highlight = |text: egui::RichText, name: &str| {
+                                if is_highlighted(name) { text.color(egui::Color32::from_rgb(100, 200, 255)) } else { text }
+                            };
+
+                            Self::section(ui, &schema.name, "Views", matches.views.len(), is_searching, |ui| {
+                                for view in &matches.views {
+                                    let is_selected = selected_table.as_ref() == Some(&(schema.name.clone(), view.to_string()));
+                                    let response = ui.selectable_label(is_selected, highlight(egui::RichText::new(format!("👁 {}", view)), view));
+                                    if response.double_clicked() {
+                                        event = Some(DatabaseTreeEvent::TableDoubleClicked(schema.name.clone(), view.to_string()));
+                                    } else if response.clicked() {
+                                        event = Some(DatabaseTreeEvent::TableSelected(schema.name.clone(), view.to_string()));
+                                    }
+                                    response.context_menu(|ui| {
+                                        if ui.button("View Data").clicked() {
+                                            event = Some(DatabaseTreeEvent::TableRightClicked(schema.name.clone(), view.to_string()));
+                                            ui.close_menu();
+                                        }
+                                        if ui.button("Show Definition").clicked() {
+                                            event = Some(DatabaseTreeEvent::ShowSource(schema.name.clone(), SourceObject::View(view.to_string())));
+                                            ui.close_menu();
+                                        }
+                                    });
+                                }
+                            });
+
+                            Self::section(ui, &schema.name, "Materialized Views", matches.materialized_views.len(), is_searching, |ui| {
+                                for view in &matches.materialized_views {
+                                    let is_selected = selected_table.as_ref() == Some(&(schema.name.clone(), view.to_string()));
+                                    let response = ui.selectable_label(is_selected, highlight(egui::RichText::new(format!("🗃 {}", view)), view));
+                                    if response.double_clicked() {
+                                        event = Some(DatabaseTreeEvent::TableDoubleClicked(schema.name.clone(), view.to_string()));
+                                    } else if response.clicked() {
+                                        event = Some(DatabaseTreeEvent::TableSelected(schema.name.clone(), view.to_string()));
+                                    }
+                                    response.context_menu(|ui| {
+                                        if ui.button("View Data").clicked() {
+                                            event = Some(DatabaseTreeEvent::TableRightClicked(schema.name.clone(), view.to_string()));
+                                            ui.close_menu();
+                                        }
+                                        if ui.button("Show Definition").clicked() {
+                                            event = Some(DatabaseTreeEvent::ShowSource(schema.name.clone(), SourceObject::MaterializedView(view.to_string())));
+                                            ui.close_menu();
+                                        }
+                                        if ui.button("Refresh").clicked() {
+                                            event = Some(DatabaseTreeEvent::RefreshMaterializedView(schema.name.clone(), view.to_string()));
+                                            ui.close_menu();
+                                        }
+                                    });
+                                }
+                            });
+
+                            Self::section(ui, &schema.name, "Functions", matches.functions.len(), is_searching, |ui| {
+                                for function in &matches.functions {
+                                    let icon = if function.is_procedure { "⚙" } else { "ƒ" };
+                                    let text = highlight(egui::RichText::new(format!("{} {}", icon, function.signature())), &function.name);
+                                    let response = ui.selectable_label(false, text)
+                                        .on_hover_text("Click to open the definition");
+                                    if response.clicked() {
+                                        event = Some(DatabaseTreeEvent::ShowSource(schema.name.clone(), SourceObject::Function((*function).clone())));
+                                    }
+                                }
+                            });
+
+                            Self::section(ui, &schema.name, "Sequences", matches.sequences.len(), is_searching, |ui| {
+                                for sequence in &matches.sequences {
+                                    let response = ui.selectable_label(false, highlight(egui::RichText::new(format!("🔢 {}", sequence)), sequence))
+                                        .on_hover_text("Click to show the current value");
+                                    if response.clicked() {
+                                        event = Some(DatabaseTreeEvent::SequenceValue(schema.name.clone(), sequence.to_string()));
+                                    }
+                                }
+                            });
                         });
                     }
                 }
@@ -209,4 +429,53 @@ impl DatabaseTree {
 
         event
     }
+
+    // Icons and colors offered in a schema's context menu; any others can be set per
+    // pattern in the connection settings
+    const SCHEMA_ICONS: [&'static str; 8] = ["📁", "⭐", "📈", "🧪", "🗄", "🔒", "🧊", "⚠"];
+    const SCHEMA_COLORS: [[u8; 3]; 6] = [[230, 90, 90], [240, 160, 60], [220, 200, 80], [110, 200, 120], [100, 170, 240], [180, 130, 230]];
+
+    // Context menu giving a schema its own icon and color
+    fn style_menu(ui: &mut egui::Ui, schema: &str, current: Option<&SchemaStyle>) -> Option<DatabaseTreeEvent> {
+        let mut style = current.cloned().unwrap_or_else(|| SchemaStyle::new(schema.to_string()));
+        let mut changed = false;
+
+        ui.label("Icon");
+        ui.horizontal(|ui| {
+            for icon in Self::SCHEMA_ICONS {
+                if ui.selectable_label(style.icon == icon, icon).clicked() {
+                    style.icon = if style.icon == icon { String::new() } else { icon.to_string() };
+                    changed = true;
+                }
+            }
+        });
+        ui.label("Color");
+        ui.horizontal(|ui| {
+            for [r, g, b] in Self::SCHEMA_COLORS {
+                let swatch = egui::RichText::new("⬛").color(egui::Color32::from_rgb(r, g, b));
+                if ui.selectable_label(style.color == Some([r, g, b]), swatch).clicked() {
+                    style.color = if style.color == Some([r, g, b]) { None } else { Some([r, g, b]) };
+                    changed = true;
+                }
+            }
+        });
+        ui.separator();
+        let own_style = current.is_some_and(|style| style.pattern == schema);
+        if ui.add_enabled(own_style, egui::Button::new("Clear Style")).clicked() {
+            return Some(DatabaseTreeEvent::SchemaStyled(schema.to_string(), None));
+        }
+        changed.then(|| DatabaseTreeEvent::SchemaStyled(schema.to_string(), Some(style)))
+    }
+
+    // Collapsible group of one kind of schema object, hidden when there are none.
+    // Forced open while searching so matches are visible.
+    fn section(ui: &mut egui::Ui, schema: &str, title: &str, count: usize, is_searching: bool, add_contents: impl FnOnce(&mut egui::Ui)) {
+        if count == 0 {
+            return;
+        }
+        egui::CollapsingHeader::new(format!("{} ({})", title, count))
+            .id_source(("tree_section", schema, title))
+            .open(is_searching.then_some(true))
+            .show(ui, add_contents);
+    }
 }
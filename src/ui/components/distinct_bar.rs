@@ -0,0 +1,64 @@
+use crate::models::{DistinctView, TableData};
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum DistinctBarEvent {
+    Show(Vec<usize>), // columns to collapse on, all when empty
+    ShowAll,
+}
+
+pub struct DistinctBar;
+
+impl DistinctBar {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Switch `data` between the full rows and their distinct values. `selected` are the
+    /// columns with selected cells; `unavailable` says why it can't be collapsed right now.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        data: &TableData,
+        selected: &[usize],
+        distinct: Option<&DistinctView>,
+        unavailable: Option<&str>,
+    ) -> Option<DistinctBarEvent> {
+        let mut event = None;
+        let columns = &data.columns;
+
+        ui.horizontal(|ui| {
+            match distinct {
+                Some(view) => {
+                    ui.label(egui::RichText::new(format!(
+                        "⧉ Distinct {}: {} of {} row(s)",
+                        view.describe(),
+                        data.rows.len(),
+                        view.original.rows.len()
+                    )).color(egui::Color32::from_rgb(40, 100, 160)));
+                    if ui.button("Show All Rows").clicked() {
+                        event = Some(DistinctBarEvent::ShowAll);
+                    }
+                }
+                None => {
+                    // A selection spanning every column means whole rows too
+                    let subset = !selected.is_empty() && selected.len() < columns.len();
+                    let text = if subset {
+                        let names: Vec<&str> = selected.iter().filter_map(|&i| columns.get(i)).map(|c| c.name.as_str()).collect();
+                        format!("⧉ Distinct {}", names.join(", "))
+                    } else {
+                        "⧉ Distinct Rows".to_string()
+                    };
+                    let button = ui.add_enabled(unavailable.is_none(), egui::Button::new(text))
+                        .on_hover_text("Collapse identical rows, or the values of the columns with selected cells, and count how often each occurs")
+                        .on_disabled_hover_text(unavailable.unwrap_or_default());
+                    if button.clicked() {
+                        event = Some(DistinctBarEvent::Show(if subset { selected.to_vec() } else { Vec::new() }));
+                    }
+                }
+            }
+        });
+
+        event
+    }
+}
@@ -0,0 +1,120 @@
+use crate::models::{Distribution, ValueDistribution};
+use eframe::egui;
+use egui_plot::{Bar, BarChart, Plot};
+
+#[derive(Debug)]
+pub enum DistributionPanelEvent {
+    Histogram(bool), // bin the numbers, or count each value
+    Close,
+}
+
+pub struct DistributionPanel;
+
+impl DistributionPanel {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Bar chart of how often each value of a column occurs, or a histogram of its numbers.
+    pub fn show(&mut self, ctx: &egui::Context, column: &str, distribution: &ValueDistribution) -> Option<DistributionPanelEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new(format!("📊 Distribution of {}", column))
+            .open(&mut is_open)
+            .resizable(true)
+            .collapsible(true)
+            .default_size([520.0, 320.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} values, {} distinct, {} NULL", distribution.values, distribution.distinct, distribution.nulls));
+                    if distribution.numeric {
+                        ui.separator();
+                        let mut histogram = distribution.is_histogram();
+                        if ui.selectable_value(&mut histogram, false, "Values").clicked()
+                            || ui.selectable_value(&mut histogram, true, "Histogram").clicked()
+                        {
+                            event = Some(DistributionPanelEvent::Histogram(histogram));
+                        }
+                    }
+                });
+
+                let (bars, labels) = bars(&distribution.distribution);
+                if let Distribution::Frequencies { others, .. } = &distribution.distribution {
+                    if *others > 0 {
+                        ui.label(egui::RichText::new(format!("{} cells hold less frequent values, not drawn", others)).color(egui::Color32::GRAY));
+                    }
+                }
+
+                let histogram = distribution.is_histogram();
+                let chart = BarChart::new(bars)
+                    .color(ui.visuals().hyperlink_color)
+                    .element_formatter(Box::new(|bar, _| format!("{}\n{}", bar.name, bar.value)));
+                let mut plot = Plot::new("value_distribution_plot")
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .allow_scroll(false)
+                    .include_y(0.0)
+                    .y_axis_width(3);
+                if !histogram {
+                    // Value names under their bars; bars sit at whole numbers
+                    plot = plot.x_axis_formatter(move |mark, _, _| {
+                        let index = mark.value.round();
+                        if (mark.value - index).abs() > f64::EPSILON || index < 0.0 {
+                            return String::new();
+                        }
+                        labels.get(index as usize).cloned().unwrap_or_default()
+                    });
+                }
+                plot.show(ui, |plot_ui| plot_ui.bar_chart(chart));
+            });
+
+        if !is_open {
+            event = Some(DistributionPanelEvent::Close);
+        }
+
+        event
+    }
+}
+
+// Bars of the chart, and the axis label of each value bar
+fn bars(distribution: &Distribution) -> (Vec<Bar>, Vec<String>) {
+    match distribution {
+        Distribution::Frequencies { values, .. } => {
+            let bars = values.iter()
+                .enumerate()
+                .map(|(index, (value, count))| Bar::new(index as f64, *count as f64).width(0.8).name(value))
+                .collect();
+            let labels = values.iter().map(|(value, _)| truncated(value)).collect();
+            (bars, labels)
+        }
+        Distribution::Histogram { start, width, counts } => {
+            let bars = counts.iter()
+                .enumerate()
+                .map(|(index, count)| {
+                    let from = start + width * index as f64;
+                    Bar::new(from + width / 2.0, *count as f64)
+                        .width(*width)
+                        .name(format!("{} – {}", format_number(from), format_number(from + width)))
+                })
+                .collect();
+            (bars, Vec::new())
+        }
+    }
+}
+
+fn truncated(value: &str) -> String {
+    if value.chars().count() > 12 {
+        format!("{}…", value.chars().take(11).collect::<String>())
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value)
+    }
+}
@@ -0,0 +1,39 @@
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum EditToolbarEvent {
+    Apply,
+    Discard,
+}
+
+pub struct EditToolbar;
+
+impl EditToolbar {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Toolbar shown while a tab has staged cell edits. `preview` is the SQL that Apply will run,
+    /// `denied` why the current role cannot run it.
+    pub fn show(&mut self, ui: &mut egui::Ui, staged_count: usize, preview: &str, denied: Option<&str>) -> Option<EditToolbarEvent> {
+        let mut event = None;
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(format!("✏ {} staged change(s)", staged_count))
+                .color(egui::Color32::from_rgb(40, 100, 200)));
+
+            let apply = ui.add_enabled(denied.is_none(), egui::Button::new("✔ Apply"))
+                .on_hover_text(preview)
+                .on_disabled_hover_text(denied.unwrap_or_default());
+            if apply.clicked() {
+                event = Some(EditToolbarEvent::Apply);
+            }
+            if ui.button("↩ Discard").on_hover_text("Drop all staged changes").clicked() {
+                event = Some(EditToolbarEvent::Discard);
+            }
+        });
+        ui.separator();
+
+        event
+    }
+}
@@ -0,0 +1,258 @@
+use crate::config::ExportPreset;
+use crate::export::{ExportEncoding, ExportFormat, ExportSettings};
+use eframe::egui;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum ExportDialogEvent {
+    Export(ExportFormat, ExportSettings, PathBuf),
+    SavePreset(ExportPreset),
+    DeletePreset(String),
+    Cancel,
+}
+
+// Delimiters offered in the dialog, besides a custom one
+const DELIMITERS: &[(u8, &str)] = &[
+    (b',', "Comma (,)"),
+    (b';', "Semicolon (;)"),
+    (b'\t', "Tab"),
+    (b'|', "Pipe (|)"),
+];
+
+// Common date formats, besides a custom one. Empty keeps values as displayed.
+const DATE_FORMATS: &[(&str, &str)] = &[
+    ("", "As displayed"),
+    ("%Y-%m-%dT%H:%M:%S", "ISO 8601"),
+    ("%Y-%m-%d", "Date only (YYYY-MM-DD)"),
+    ("%d/%m/%Y %H:%M:%S", "DD/MM/YYYY"),
+    ("%m/%d/%Y %H:%M:%S", "MM/DD/YYYY"),
+];
+
+pub struct ExportDialog {
+    format: ExportFormat,
+    settings: ExportSettings,
+    custom_delimiter: String,
+    quote_char: String,
+    preset_name: String,
+    path: String,
+    // File name without extension, used when the format changes
+    base_name: String,
+}
+
+impl ExportDialog {
+    pub fn new() -> Self {
+        Self {
+            format: ExportFormat::CSV,
+            settings: ExportSettings::default(),
+            custom_delimiter: String::new(),
+            quote_char: "\"".to_string(),
+            preset_name: String::new(),
+            path: String::new(),
+            base_name: "export".to_string(),
+        }
+    }
+
+    /// Reset the target path for a new export of `base_name` (e.g. the tab title).
+    pub fn prepare(&mut self, base_name: &str) {
+        self.base_name = base_name.chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+            .collect();
+        self.update_path();
+    }
+
+    fn update_path(&mut self) {
+        let file_name = format!("{}.{}", self.base_name, self.format.extension());
+        self.path = super::default_file_path(&file_name).to_string_lossy().to_string();
+    }
+
+    fn set_format(&mut self, format: ExportFormat) {
+        self.format = format;
+        let defaults = format.exporter().delimited_defaults().unwrap_or_default();
+        self.settings.delimited.delimiter = defaults.delimiter;
+        self.update_path();
+    }
+
+    fn apply_preset(&mut self, preset: &ExportPreset) {
+        self.settings = preset.settings.clone();
+        self.preset_name = preset.name.clone();
+        self.quote_char = (self.settings.delimited.quote as char).to_string();
+        self.custom_delimiter.clear();
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, row_count: usize, presets: &[ExportPreset]) -> Option<ExportDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new("💾 Export Results")
+            .open(&mut is_open)
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("{} row(s) will be exported (current filters applied)", row_count));
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Preset:");
+                    egui::ComboBox::from_id_source("export_preset")
+                        .selected_text(if presets.iter().any(|p| p.name == self.preset_name) {
+                            self.preset_name.as_str()
+                        } else {
+                            "(none)"
+                        })
+                        .show_ui(ui, |ui| {
+                            for preset in presets {
+                                if ui.selectable_label(preset.name == self.preset_name, &preset.name).clicked() {
+                                    self.apply_preset(preset);
+                                }
+                            }
+                        });
+
+                    ui.add(egui::TextEdit::singleline(&mut self.preset_name)
+                        .hint_text("Preset name")
+                        .desired_width(120.0));
+                    let name = self.preset_name.trim();
+                    if ui.add_enabled(!name.is_empty(), egui::Button::new("Save Preset")).clicked() {
+                        event = Some(ExportDialogEvent::SavePreset(ExportPreset {
+                            name: name.to_string(),
+                            settings: self.settings.clone(),
+                        }));
+                    }
+                    if presets.iter().any(|p| p.name == name) && ui.button("🗑").on_hover_text("Delete preset").clicked() {
+                        event = Some(ExportDialogEvent::DeletePreset(name.to_string()));
+                    }
+                });
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Format:");
+                    for format in ExportFormat::all() {
+                        if ui.selectable_label(self.format == format, format.as_str()).clicked() {
+                            self.set_format(format);
+                        }
+                    }
+                });
+
+                ui.add_enabled_ui(self.format.is_delimited(), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Delimiter:");
+                        let options = &mut self.settings.delimited;
+                        let selected = DELIMITERS.iter()
+                            .find(|(d, _)| *d == options.delimiter)
+                            .map(|(_, label)| *label)
+                            .unwrap_or("Custom");
+                        egui::ComboBox::from_id_source("export_delimiter")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                for (delimiter, label) in DELIMITERS {
+                                    ui.selectable_value(&mut options.delimiter, *delimiter, *label);
+                                }
+                            });
+
+                        ui.label("Custom:");
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut self.custom_delimiter)
+                                .char_limit(1)
+                                .desired_width(20.0)
+                        );
+                        if response.changed() {
+                            if let Some(byte) = ascii_byte(&self.custom_delimiter) {
+                                options.delimiter = byte;
+                            }
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Quote:");
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut self.quote_char)
+                                .char_limit(1)
+                                .desired_width(20.0)
+                        );
+                        if response.changed() {
+                            if let Some(byte) = ascii_byte(&self.quote_char) {
+                                self.settings.delimited.quote = byte;
+                            }
+                        }
+
+                        ui.label("NULL as:");
+                        ui.add(egui::TextEdit::singleline(&mut self.settings.delimited.null_value)
+                            .hint_text("(empty)")
+                            .desired_width(80.0));
+                    });
+
+                    if self.has_non_ascii_characters() {
+                        ui.label(egui::RichText::new("⚠ Delimiter and quote must be ASCII characters")
+                            .color(egui::Color32::from_rgb(220, 80, 80)));
+                    }
+                    ui.checkbox(&mut self.settings.delimited.include_header, "Include header row");
+                    ui.checkbox(&mut self.settings.delimited.quote_all, "Quote all fields");
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Dates:");
+                    let selected = DATE_FORMATS.iter()
+                        .find(|(f, _)| *f == self.settings.date_format)
+                        .map(|(_, label)| *label)
+                        .unwrap_or("Custom");
+                    egui::ComboBox::from_id_source("export_date_format")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for (format, label) in DATE_FORMATS {
+                                ui.selectable_value(&mut self.settings.date_format, format.to_string(), *label);
+                            }
+                        });
+                    ui.add(egui::TextEdit::singleline(&mut self.settings.date_format)
+                        .hint_text("strftime, e.g. %d.%m.%Y")
+                        .desired_width(140.0));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Encoding:");
+                    egui::ComboBox::from_id_source("export_encoding")
+                        .selected_text(self.settings.encoding.as_str())
+                        .show_ui(ui, |ui| {
+                            for encoding in ExportEncoding::all() {
+                                ui.selectable_value(&mut self.settings.encoding, encoding, encoding.as_str());
+                            }
+                        });
+                });
+
+                ui.separator();
+                ui.label("File path:");
+                ui.add(egui::TextEdit::singleline(&mut self.path).desired_width(400.0));
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    let valid = !(self.format.is_delimited() && self.has_non_ascii_characters());
+                    if ui.add_enabled(valid, egui::Button::new("Export")).clicked() && !self.path.trim().is_empty() {
+                        event = Some(ExportDialogEvent::Export(
+                            self.format,
+                            self.settings.clone(),
+                            super::expand_home(self.path.trim()),
+                        ));
+                    }
+                    if ui.button("Cancel").clicked() {
+                        event = Some(ExportDialogEvent::Cancel);
+                    }
+                });
+            });
+
+        if !is_open {
+            event = Some(ExportDialogEvent::Cancel);
+        }
+
+        event
+    }
+
+    // Whether the custom delimiter or quote holds a character the CSV writer can't use
+    fn has_non_ascii_characters(&self) -> bool {
+        [&self.custom_delimiter, &self.quote_char].iter().any(|text| !text.is_empty() && ascii_byte(text).is_none())
+    }
+}
+
+// The single ASCII character `text` holds; others take more than the one byte the
+// CSV writer accepts
+fn ascii_byte(text: &str) -> Option<u8> {
+    text.chars().next().filter(char::is_ascii).map(|c| c as u8)
+}
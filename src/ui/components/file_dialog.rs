@@ -0,0 +1,83 @@
+use eframe::egui;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum FileDialogEvent {
+    Confirm(PathBuf),
+    Cancel,
+}
+
+/// Minimal path prompt used for saving and opening files.
+pub struct FileDialog {
+    path: String,
+}
+
+impl FileDialog {
+    pub fn new() -> Self {
+        Self {
+            path: String::new(),
+        }
+    }
+
+    /// Pre-fill the path input, typically with a suggested file name in the home directory.
+    pub fn set_path(&mut self, path: PathBuf) {
+        self.path = path.to_string_lossy().to_string();
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, title: &str, action_label: &str) -> Option<FileDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new(title)
+            .open(&mut is_open)
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.vertical(|ui| {
+                    ui.label("File path:");
+                    ui.add_space(5.0);
+
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.path)
+                            .desired_width(400.0)
+                    );
+
+                    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if (ui.button(action_label).clicked() || submitted) && !self.path.trim().is_empty() {
+                            event = Some(FileDialogEvent::Confirm(expand_home(self.path.trim())));
+                        }
+                        if ui.button("Cancel").clicked() {
+                            event = Some(FileDialogEvent::Cancel);
+                        }
+                    });
+                });
+            });
+
+        if !is_open {
+            event = Some(FileDialogEvent::Cancel);
+        }
+
+        event
+    }
+}
+
+/// Default location for files written by the app: the user's home directory.
+pub fn default_file_path(file_name: &str) -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(file_name)
+}
+
+/// Expand a leading `~/` to the user's home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    PathBuf::from(path)
+}
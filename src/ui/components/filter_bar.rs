@@ -3,25 +3,41 @@ use crate::models::{FilterRule, FilterOperator, FilterConjunction};
 use eframe::egui;
 
 #[derive(Debug)]
+#[allow(dead_code)]
 pub enum FilterBarEvent {
     FilterAdded,
     FilterRemoved(usize),
     FiltersChanged,
     FilterApplied,
+    ServerSideToggled,
+    FilterSetSaved(String),
+    FilterSetApplied(String),
+    FilterSetDeleted(String),
+    FilterRowToggled(bool),
 }
 
-pub struct FilterBar;
+pub struct FilterBar {
+    // Name typed for the filter set being saved
+    set_name: String,
+}
 
 impl FilterBar {
     pub fn new() -> Self {
-        Self
+        Self { set_name: String::new() }
     }
 
+    /// Filter rules of a tab. `server_side` is the table tab's switch between filtering
+    /// the loaded rows and reloading with a WHERE clause, and `filter_sets` the names of
+    /// the table's saved filter sets; both None for query results. `filter_row` is whether
+    /// the grid has a box per column under its header for quick Contains filters.
     pub fn show(
         &mut self,
         ui: &mut egui::Ui,
         filters: &mut Vec<FilterRule>,
         columns: &[ColumnInfo],
+        server_side: Option<&mut bool>,
+        filter_sets: Option<&[&str]>,
+        mut filter_row: bool,
     ) -> Option<FilterBarEvent> {
         let mut event = None;
 
@@ -33,20 +49,72 @@ impl FilterBar {
             }
 
             // Remove filter button
-            if !filters.is_empty() {
-                if ui.button("➖").on_hover_text("Remove last filter").clicked() {
-                    filters.pop();
-                    event = Some(FilterBarEvent::FilterRemoved(filters.len()));
-                }
+            if !filters.is_empty() && ui.button("➖").on_hover_text("Remove last filter").clicked() {
+                filters.pop();
+                event = Some(FilterBarEvent::FilterRemoved(filters.len()));
             }
 
             ui.separator();
 
             // Apply/Search button
-            if ui.button("🔍").on_hover_text("Apply filters").clicked() {
+            let apply_hint = if server_side.as_deref() == Some(&true) { "Reload the table with these filters" } else { "Apply filters" };
+            if ui.button("🔍").on_hover_text(apply_hint).clicked() {
                 event = Some(FilterBarEvent::FilterApplied);
             }
 
+            if ui.checkbox(&mut filter_row, "Filter row")
+                .on_hover_text("A box under each column header filters it by the text typed")
+                .changed()
+            {
+                event = Some(FilterBarEvent::FilterRowToggled(filter_row));
+            }
+
+            if let Some(server_side) = server_side {
+                if ui.checkbox(server_side, "Server-side")
+                    .on_hover_text("Filter the whole table with a WHERE clause instead of only the loaded rows")
+                    .changed()
+                {
+                    event = Some(FilterBarEvent::ServerSideToggled);
+                }
+            }
+
+            if let Some(filter_sets) = filter_sets {
+                egui::ComboBox::from_id_source("saved_filter_sets")
+                    .selected_text("Saved filters")
+                    .width(120.0)
+                    .show_ui(ui, |ui| {
+                        if filter_sets.is_empty() {
+                            ui.label(egui::RichText::new("None saved for this table").color(egui::Color32::GRAY));
+                        }
+                        for name in filter_sets {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("✖").on_hover_text("Delete this filter set").clicked() {
+                                    event = Some(FilterBarEvent::FilterSetDeleted(name.to_string()));
+                                }
+                                if ui.selectable_label(false, *name).clicked() {
+                                    event = Some(FilterBarEvent::FilterSetApplied(name.to_string()));
+                                }
+                            });
+                        }
+                    });
+
+                ui.add_enabled_ui(!filters.is_empty(), |ui| {
+                    ui.menu_button("💾", |ui| {
+                        ui.label("Save these filters for the table as:");
+                        let response = ui.add(egui::TextEdit::singleline(&mut self.set_name).hint_text("name..."));
+                        let name = self.set_name.trim();
+                        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        if (ui.add_enabled(!name.is_empty(), egui::Button::new("Save")).clicked() || submitted) && !name.is_empty() {
+                            event = Some(FilterBarEvent::FilterSetSaved(name.to_string()));
+                            self.set_name.clear();
+                            ui.close_menu();
+                        }
+                    })
+                    .response
+                    .on_hover_text("Save filter set");
+                });
+            }
+
             ui.separator();
 
             // Filter count
@@ -119,6 +187,19 @@ impl FilterBar {
                     if response.changed() {
                         filters_changed = true;
                     }
+
+                    if ui.selectable_label(filter.case_sensitive, "Aa")
+                        .on_hover_text("Match case")
+                        .clicked()
+                    {
+                        filter.case_sensitive = !filter.case_sensitive;
+                        filters_changed = true;
+                    }
+
+                    if let Some(error) = filter.regex_error() {
+                        ui.label(egui::RichText::new("⚠ Invalid regex").color(egui::Color32::from_rgb(220, 80, 80)))
+                            .on_hover_text(error);
+                    }
                 }
 
                 // Remove this specific filter button
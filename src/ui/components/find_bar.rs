@@ -0,0 +1,100 @@
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum FindBarEvent {
+    SearchChanged,
+    Next,
+    Previous,
+    Close,
+}
+
+/// Find-in-results bar for data tabs (Ctrl/Cmd+F).
+pub struct FindBar {
+    request_focus: bool,
+}
+
+impl FindBar {
+    pub fn new() -> Self {
+        Self {
+            request_focus: false,
+        }
+    }
+
+    /// Focus the search input the next time the bar is shown.
+    pub fn focus(&mut self) {
+        self.request_focus = true;
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        search_text: &mut String,
+        current_match: usize,
+        total_matches: usize,
+    ) -> Option<FindBarEvent> {
+        let mut event = None;
+
+        ui.horizontal(|ui| {
+            ui.label("🔍");
+            let response = ui.add(
+                egui::TextEdit::singleline(search_text)
+                    .hint_text("Find in results...")
+                    .desired_width(300.0)
+            );
+
+            if self.request_focus {
+                response.request_focus();
+                self.request_focus = false;
+            }
+
+            if response.changed() {
+                event = Some(FindBarEvent::SearchChanged);
+            }
+
+            // Enter / Shift+Enter step through matches without leaving the input
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                event = if ui.input(|i| i.modifiers.shift) {
+                    Some(FindBarEvent::Previous)
+                } else {
+                    Some(FindBarEvent::Next)
+                };
+                response.request_focus();
+            }
+
+            if response.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                event = Some(FindBarEvent::Close);
+            }
+
+            // Match counter, e.g. "3/17"
+            let counter = if search_text.is_empty() {
+                String::new()
+            } else if total_matches == 0 {
+                "0/0".to_string()
+            } else {
+                format!("{}/{}", current_match + 1, total_matches)
+            };
+            let counter_color = if !search_text.is_empty() && total_matches == 0 {
+                egui::Color32::from_rgb(200, 60, 60)
+            } else {
+                egui::Color32::from_rgb(100, 100, 255)
+            };
+            ui.label(egui::RichText::new(counter).size(11.0).color(counter_color));
+
+            ui.add_enabled_ui(total_matches > 0, |ui| {
+                if ui.button("▲").on_hover_text("Previous match (Shift+F3)").clicked() {
+                    event = Some(FindBarEvent::Previous);
+                }
+                if ui.button("▼").on_hover_text("Next match (F3)").clicked() {
+                    event = Some(FindBarEvent::Next);
+                }
+            });
+
+            if ui.small_button("✖").on_hover_text("Close find bar (Esc)").clicked() {
+                event = Some(FindBarEvent::Close);
+            }
+        });
+        ui.add_space(5.0);
+
+        event
+    }
+}
@@ -0,0 +1,35 @@
+use crate::models::FollowUp;
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum FollowUpBarEvent {
+    Open(usize), // index of the follow-up to open in the editor
+    Hide,
+}
+
+pub struct FollowUpBar;
+
+impl FollowUpBar {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// One-click follow-up queries for the current result; picking one only opens it.
+    pub fn show(&mut self, ui: &mut egui::Ui, follow_ups: &[FollowUp]) -> Option<FollowUpBarEvent> {
+        let mut event = None;
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label(egui::RichText::new("💡 Follow up:").color(egui::Color32::GRAY));
+            for (index, follow_up) in follow_ups.iter().enumerate() {
+                if ui.small_button(&follow_up.label).on_hover_text(&follow_up.sql).clicked() {
+                    event = Some(FollowUpBarEvent::Open(index));
+                }
+            }
+            if ui.small_button("✕").on_hover_text("Stop suggesting follow-ups (Settings turns them back on)").clicked() {
+                event = Some(FollowUpBarEvent::Hide);
+            }
+        });
+
+        event
+    }
+}
@@ -0,0 +1,168 @@
+use crate::models::{ImportPlan, ImportProgress, NullHandling, IMPORT_DELIMITERS};
+use eframe::egui;
+use std::sync::atomic::Ordering;
+
+#[derive(Debug)]
+pub enum ImportDialogEvent {
+    Import,
+    Cancel,
+    Close,
+}
+
+pub struct ImportDialog {
+    path: String,
+}
+
+impl ImportDialog {
+    pub fn new() -> Self {
+        Self { path: String::new() }
+    }
+
+    /// Choose a CSV or TSV file for `plan`, how it is read and which table column each
+    /// file column loads into, previewing its first rows. `progress` is shown while the
+    /// rows are being copied.
+    pub fn show(&mut self, ctx: &egui::Context, plan: &mut ImportPlan, progress: Option<&ImportProgress>, running: bool) -> Option<ImportDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new(format!("📥 Import into {}.{}", plan.schema, plan.table))
+            .open(&mut is_open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(!running, |ui| {
+                    self.settings(ui, plan);
+                    ui.separator();
+                    mapping(ui, plan);
+                });
+
+                if let Err(e) = plan.validate() {
+                    ui.label(egui::RichText::new(e.to_string()).color(egui::Color32::GRAY));
+                }
+
+                if let (true, Some(progress)) = (running, progress) {
+                    ui.separator();
+                    let (size, read) = (progress.file_size.load(Ordering::Relaxed), progress.read.load(Ordering::Relaxed));
+                    let sent = progress.sent.load(Ordering::Relaxed);
+                    ui.add(egui::ProgressBar::new(read as f32 / size.max(1) as f32)
+                        .text(format!("{} rows sent", sent)));
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if running {
+                        if ui.button("⏹ Cancel").on_hover_text("Nothing is loaded when the import is cancelled").clicked() {
+                            event = Some(ImportDialogEvent::Cancel);
+                        }
+                    } else {
+                        if ui.add_enabled(plan.validate().is_ok(), egui::Button::new("Import"))
+                            .on_hover_text("COPY the rows into the table; if any row is rejected, none are loaded")
+                            .clicked()
+                        {
+                            event = Some(ImportDialogEvent::Import);
+                        }
+                        if ui.button("Close").clicked() {
+                            event = Some(ImportDialogEvent::Close);
+                        }
+                    }
+                });
+            });
+
+        if !is_open {
+            event = Some(if running { ImportDialogEvent::Cancel } else { ImportDialogEvent::Close });
+        }
+
+        event
+    }
+
+    // File, delimiter, header and NULL handling; changing how the file is read reads it again
+    fn settings(&mut self, ui: &mut egui::Ui, plan: &mut ImportPlan) {
+        ui.horizontal(|ui| {
+            ui.label("File:");
+            let response = ui.add(egui::TextEdit::singleline(&mut self.path)
+                .hint_text("~/exports/people.csv")
+                .desired_width(420.0));
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if (ui.button("Read").clicked() || submitted) && !self.path.trim().is_empty() {
+                plan.set_path(super::expand_home(self.path.trim()));
+            }
+        });
+
+        let mut reread = false;
+        ui.horizontal(|ui| {
+            ui.label("Delimiter:");
+            let selected = IMPORT_DELIMITERS.iter().find(|(delimiter, _)| *delimiter == plan.delimiter).map_or("Other", |(_, name)| name);
+            egui::ComboBox::from_id_source("import_delimiter")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for (delimiter, name) in IMPORT_DELIMITERS {
+                        reread |= ui.selectable_value(&mut plan.delimiter, delimiter, name).changed();
+                    }
+                });
+            reread |= ui.checkbox(&mut plan.header, "First line is the header").changed();
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("NULL:");
+            egui::ComboBox::from_id_source("import_null_handling")
+                .selected_text(plan.null_handling.as_str())
+                .show_ui(ui, |ui| {
+                    for handling in [NullHandling::Empty, NullHandling::Marker("\\N".to_string()), NullHandling::Never] {
+                        let selected = std::mem::discriminant(&plan.null_handling) == std::mem::discriminant(&handling);
+                        if ui.selectable_label(selected, handling.as_str()).clicked() && !selected {
+                            plan.null_handling = handling;
+                        }
+                    }
+                });
+            if let NullHandling::Marker(marker) = &mut plan.null_handling {
+                ui.add(egui::TextEdit::singleline(marker).desired_width(80.0));
+            }
+        });
+
+        if reread {
+            plan.reload();
+        }
+    }
+}
+
+// Each file column with the table column it loads into, over its first values
+fn mapping(ui: &mut egui::Ui, plan: &mut ImportPlan) {
+    let Ok(preview) = &plan.preview else {
+        return;
+    };
+    egui::ScrollArea::both().id_source("import_preview").max_height(280.0).show(ui, |ui| {
+        egui::Grid::new("import_preview_grid").striped(true).show(ui, |ui| {
+            for name in &preview.names {
+                ui.strong(name);
+            }
+            ui.end_row();
+
+            for (index, target) in plan.mapping.iter_mut().enumerate() {
+                let selected = target
+                    .and_then(|column| plan.table_columns.get(column))
+                    .map_or_else(|| "— skip —".to_string(), |column| column.name.clone());
+                egui::ComboBox::from_id_source(("import_mapping", index))
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(target, None, "— skip —");
+                        for (column_index, column) in plan.table_columns.iter().enumerate() {
+                            ui.selectable_value(target, Some(column_index), format!("{} ({})", column.name, column.data_type));
+                        }
+                    });
+            }
+            ui.end_row();
+
+            for row in &preview.rows {
+                for field in row.iter().take(preview.names.len()) {
+                    if plan.null_handling.is_null(field) {
+                        ui.label(egui::RichText::new("NULL").color(egui::Color32::GRAY));
+                    } else {
+                        ui.label(field);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    });
+}
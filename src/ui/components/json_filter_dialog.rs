@@ -0,0 +1,127 @@
+use crate::models::{sample_json_paths, JsonFilter, JsonOperator, TableData};
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum JsonFilterDialogEvent {
+    Add(String), // SQL condition
+    Close,
+}
+
+pub struct JsonFilterDialog {
+    // Column the paths were sampled for, and the paths
+    sampled: Option<(String, Vec<Vec<String>>)>,
+}
+
+impl JsonFilterDialog {
+    pub fn new() -> Self {
+        Self { sampled: None }
+    }
+
+    /// Build `filter` on one of `data`'s jsonb columns from key paths sampled out of the
+    /// loaded rows, previewing the SQL that Add appends to the tab's server filters.
+    pub fn show(&mut self, ctx: &egui::Context, data: &TableData, filter: &mut JsonFilter) -> Option<JsonFilterDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        let json_columns: Vec<usize> = data.columns.iter()
+            .enumerate()
+            .filter(|(_, column)| column.data_type.starts_with("jsonb"))
+            .map(|(index, _)| index)
+            .collect();
+        if self.sampled.as_ref().is_none_or(|(column, _)| *column != filter.column) {
+            let paths = data.columns.iter()
+                .position(|column| column.name == filter.column)
+                .map(|index| sample_json_paths(data.rows.iter().filter_map(|row| row.get(index))))
+                .unwrap_or_default();
+            self.sampled = Some((filter.column.clone(), paths));
+        }
+        let paths = self.sampled.as_ref().map(|(_, paths)| paths.as_slice()).unwrap_or_default();
+
+        egui::Window::new("{ } JSON Filter")
+            .open(&mut is_open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(460.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("json_filter_grid").num_columns(2).spacing([8.0, 6.0]).show(ui, |ui| {
+                    ui.label("Column:");
+                    egui::ComboBox::from_id_source("json_filter_column")
+                        .selected_text(&filter.column)
+                        .show_ui(ui, |ui| {
+                            for &index in &json_columns {
+                                let name = &data.columns[index].name;
+                                if ui.selectable_label(filter.column == *name, name).clicked() {
+                                    *filter = JsonFilter::new(name.clone());
+                                }
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Key:");
+                    let selected = if filter.path.is_empty() { "Pick a key...".to_string() } else { filter.path.join(" → ") };
+                    egui::ComboBox::from_id_source("json_filter_path")
+                        .selected_text(selected)
+                        .width(300.0)
+                        .show_ui(ui, |ui| {
+                            if paths.is_empty() {
+                                ui.label(egui::RichText::new("No object keys in the loaded rows").color(egui::Color32::GRAY));
+                            }
+                            for path in paths {
+                                ui.selectable_value(&mut filter.path, path.clone(), path.join(" → "));
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Operator:");
+                    egui::ComboBox::from_id_source("json_filter_operator")
+                        .selected_text(filter.operator.as_str())
+                        .show_ui(ui, |ui| {
+                            for operator in JsonOperator::all() {
+                                ui.selectable_value(&mut filter.operator, operator, operator.as_str());
+                            }
+                        });
+                    ui.end_row();
+
+                    if filter.operator.needs_value() {
+                        ui.label("Value:");
+                        ui.add(egui::TextEdit::singleline(&mut filter.value)
+                            .hint_text("text, or JSON such as 5, true or {\"a\": 1}")
+                            .desired_width(300.0));
+                        ui.end_row();
+                    }
+                });
+
+                ui.separator();
+                let sql = filter.to_sql();
+                match &sql {
+                    Ok(sql) => {
+                        ui.label(egui::RichText::new(sql).monospace());
+                    }
+                    Err(e) => {
+                        ui.label(egui::RichText::new(e.to_string()).color(egui::Color32::GRAY));
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(sql.is_ok(), egui::Button::new("➕ Add Filter"))
+                        .on_hover_text("Append to the tab's server filters and reload")
+                        .clicked()
+                    {
+                        if let Ok(sql) = sql {
+                            event = Some(JsonFilterDialogEvent::Add(sql));
+                        }
+                    }
+                    if ui.button("Close").clicked() {
+                        event = Some(JsonFilterDialogEvent::Close);
+                    }
+                });
+            });
+
+        if !is_open {
+            event = Some(JsonFilterDialogEvent::Close);
+        }
+
+        event
+    }
+}
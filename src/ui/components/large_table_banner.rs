@@ -0,0 +1,43 @@
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum LargeTableBannerEvent {
+    LoadPreview, // the first preview rows
+    LoadMore,    // as many rows as other tables load
+}
+
+pub struct LargeTableBanner;
+
+impl LargeTableBanner {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Notice over a table tab opened on its structure for holding about `estimated_rows`.
+    /// `loaded` is the row limit it was loaded with, None while its rows wait for Load Data.
+    pub fn show(&mut self, ui: &mut egui::Ui, estimated_rows: u64, loaded: Option<usize>, preview_rows: usize, max_rows: usize) -> Option<LargeTableBannerEvent> {
+        let mut event = None;
+
+        ui.horizontal(|ui| {
+            match loaded {
+                None => {
+                    ui.label(egui::RichText::new(format!("⚠ About {} rows (estimated), so the rows were not loaded", estimated_rows))
+                        .color(egui::Color32::from_rgb(200, 130, 0)));
+                    if ui.button(format!("⬇ Load data (first {} rows)", preview_rows)).clicked() {
+                        event = Some(LargeTableBannerEvent::LoadPreview);
+                    }
+                }
+                Some(limit) => {
+                    ui.label(egui::RichText::new(format!("At most the first {} of about {} rows are loaded", limit, estimated_rows))
+                        .color(egui::Color32::GRAY));
+                    if limit < max_rows && ui.small_button(format!("Load up to {} rows", max_rows)).clicked() {
+                        event = Some(LargeTableBannerEvent::LoadMore);
+                    }
+                }
+            }
+        });
+        ui.separator();
+
+        event
+    }
+}
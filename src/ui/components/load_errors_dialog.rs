@@ -0,0 +1,38 @@
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum LoadErrorsDialogEvent {
+    Close,
+}
+
+pub struct LoadErrorsDialog;
+
+impl LoadErrorsDialog {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Shown at startup when settings files could not be read; each error names the
+    /// copy the file was kept as.
+    pub fn show(&mut self, ctx: &egui::Context, errors: &[String]) -> Option<LoadErrorsDialogEvent> {
+        let mut event = None;
+
+        egui::Window::new("⚠ Settings not loaded")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                for error in errors {
+                    ui.label(error);
+                }
+                ui.label(egui::RichText::new("Defaults are used instead. Copy settings back from the kept file, then restart.").color(egui::Color32::GRAY));
+                ui.add_space(10.0);
+
+                if ui.button("OK").clicked() {
+                    event = Some(LoadErrorsDialogEvent::Close);
+                }
+            });
+
+        event
+    }
+}
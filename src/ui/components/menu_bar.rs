@@ -1,11 +1,27 @@
 use eframe::egui;
+use std::path::PathBuf;
 
 #[derive(Debug)]
 pub enum MenuBarEvent {
     ShowSettings,
+    ShareQuery,
+    OpenSharedQuery,
+    ExportSchemaData,
+    SaveSchemaSnapshot,
+    CompareSchemaSnapshot,
+    RunSqlFile,
+    OpenSqlFile,
+    OpenRecentSqlFile(PathBuf),
+    SaveSqlFile,
+    SaveSqlFileAs,
     Quit,
     ToggleQueryPanel,
+    ToggleQueryLog,
+    ToggleWatches,
     Refresh,
+    CopyExplainPlan,
+    SaveExplainPlan,
+    PasteTable,
 }
 
 pub struct MenuBar;
@@ -15,7 +31,7 @@ impl MenuBar {
         Self
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui, connection_status: &str) -> Option<MenuBarEvent> {
+    pub fn show(&mut self, ui: &mut egui::Ui, connection_status: &str, share_safely: &mut bool, recent_sql_files: &[PathBuf]) -> Option<MenuBarEvent> {
         let mut event = None;
 
         egui::menu::bar(ui, |ui| {
@@ -24,6 +40,62 @@ impl MenuBar {
                     event = Some(MenuBarEvent::ShowSettings);
                     ui.close_menu();
                 }
+                ui.separator();
+                if ui.button("Open SQL File...").clicked() {
+                    event = Some(MenuBarEvent::OpenSqlFile);
+                    ui.close_menu();
+                }
+                ui.add_enabled_ui(!recent_sql_files.is_empty(), |ui| {
+                    ui.menu_button("Open Recent SQL File", |ui| {
+                        for path in recent_sql_files {
+                            let name = path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().to_string());
+                            if ui.button(name).on_hover_text(path.display().to_string()).clicked() {
+                                event = Some(MenuBarEvent::OpenRecentSqlFile(path.clone()));
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                });
+                if ui.button("Save SQL File").clicked() {
+                    event = Some(MenuBarEvent::SaveSqlFile);
+                    ui.close_menu();
+                }
+                if ui.button("Save SQL File As...").clicked() {
+                    event = Some(MenuBarEvent::SaveSqlFileAs);
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui.button("Share Query...").clicked() {
+                    event = Some(MenuBarEvent::ShareQuery);
+                    ui.close_menu();
+                }
+                if ui.button("Open Shared Query...").clicked() {
+                    event = Some(MenuBarEvent::OpenSharedQuery);
+                    ui.close_menu();
+                }
+                if ui.button("Export Schema Data...").clicked() {
+                    event = Some(MenuBarEvent::ExportSchemaData);
+                    ui.close_menu();
+                }
+                if ui.button("Save Schema Snapshot...").clicked() {
+                    event = Some(MenuBarEvent::SaveSchemaSnapshot);
+                    ui.close_menu();
+                }
+                if ui.button("Compare Schema With Snapshot...")
+                    .on_hover_text("List tables and columns added, removed or altered since the snapshot")
+                    .clicked()
+                {
+                    event = Some(MenuBarEvent::CompareSchemaSnapshot);
+                    ui.close_menu();
+                }
+                if ui.button("Run SQL File...").clicked() {
+                    event = Some(MenuBarEvent::RunSqlFile);
+                    ui.close_menu();
+                }
+                ui.separator();
+                ui.checkbox(share_safely, "Share Safely")
+                    .on_hover_text("Anonymize exports and copied cells with the connection's anonymization rules");
+                ui.separator();
                 if ui.button("Quit").clicked() {
                     event = Some(MenuBarEvent::Quit);
                 }
@@ -33,6 +105,34 @@ impl MenuBar {
                 if ui.button("Show Query Panel").clicked() {
                     event = Some(MenuBarEvent::ToggleQueryPanel);
                 }
+                if ui.button("Query Log").on_hover_text("Results and timings of the statements run this session").clicked() {
+                    event = Some(MenuBarEvent::ToggleQueryLog);
+                }
+                if ui.button("Watches").on_hover_text("Scalar queries refreshed on an interval").clicked() {
+                    event = Some(MenuBarEvent::ToggleWatches);
+                }
+            });
+
+            ui.menu_button("Query", |ui| {
+                if ui.button("Copy EXPLAIN Plan (JSON)")
+                    .on_hover_text("Copy the plan for explain.dalibo.com / pev2")
+                    .clicked()
+                {
+                    event = Some(MenuBarEvent::CopyExplainPlan);
+                    ui.close_menu();
+                }
+                if ui.button("Save EXPLAIN Plan...").clicked() {
+                    event = Some(MenuBarEvent::SaveExplainPlan);
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui.button("Paste as Temporary Table...")
+                    .on_hover_text("Create a temporary table from pasted CSV, TSV or JSON rows to join against")
+                    .clicked()
+                {
+                    event = Some(MenuBarEvent::PasteTable);
+                    ui.close_menu();
+                }
             });
 
             ui.separator();
@@ -47,6 +147,9 @@ impl MenuBar {
 
             ui.separator();
             ui.label(connection_status);
+            if *share_safely {
+                ui.label(egui::RichText::new("🛡 Sharing safely").color(egui::Color32::from_rgb(40, 140, 60)));
+            }
         });
 
         event
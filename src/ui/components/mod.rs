@@ -9,15 +9,99 @@ mod tab_bar;
 mod pagination;
 mod data_grid;
 mod filter_bar;
+mod file_dialog;
+mod edit_toolbar;
+mod find_bar;
+mod export_dialog;
+mod confirm_dialog;
+mod resource_report;
+mod quit_dialog;
+mod query_history_dialog;
+mod plan_view;
+mod batch_export_dialog;
+mod script_runner_dialog;
+mod tail_bar;
+mod bookmarks_panel;
+mod structure_view;
+mod schema_diff_dialog;
+mod statement_summary_dialog;
+mod query_log_panel;
+mod watch_panel;
+mod restored_tab_banner;
+mod queue_panel;
+mod alter_columns_dialog;
+mod command_palette;
+mod distinct_bar;
+mod bar_chart;
+mod server_filter_bar;
+mod json_filter_dialog;
+mod row_compare_dialog;
+mod session_restore_dialog;
+mod cell_inspector;
+mod replace_values_dialog;
+mod paste_table_dialog;
+mod import_dialog;
+mod load_errors_dialog;
+mod password_prompt_dialog;
+mod distribution_panel;
+mod result_chart;
+mod large_table_banner;
+mod follow_up_bar;
+mod param_prompt_dialog;
+mod wide_table_banner;
+mod computed_column_dialog;
+mod sql_dump_dialog;
 
 pub use menu_bar::{MenuBar, MenuBarEvent};
-pub use status_bar::StatusBar;
+pub use status_bar::{StatusBar, StatusBarEvent};
 pub use query_panel::{QueryPanel, QueryPanelEvent};
 pub use saved_queries_dialog::{SavedQueriesDialog, SavedQueriesDialogEvent, SaveQueryDialog, SaveQueryDialogEvent};
 pub use settings_dialog::{SettingsDialog, SettingsDialogEvent};
 pub use connection_editor::{ConnectionEditor, ConnectionEditorEvent};
-pub use database_tree::{DatabaseTree, DatabaseTreeEvent};
+pub use database_tree::{DatabaseTree, DatabaseTreeEvent, NEW_QUERY_SHORTCUT};
 pub use tab_bar::{TabBar, TabBarEvent};
 pub use pagination::{PaginationControls, PaginationEvent};
-pub use data_grid::{DataGrid, DataGridEvent};
+pub use data_grid::{DataGrid, DataGridEvent, GridEditContext};
 pub use filter_bar::{FilterBar, FilterBarEvent};
+pub use file_dialog::{default_file_path, expand_home, FileDialog, FileDialogEvent};
+pub use edit_toolbar::{EditToolbar, EditToolbarEvent};
+pub use find_bar::{FindBar, FindBarEvent};
+pub use export_dialog::{ExportDialog, ExportDialogEvent};
+pub use confirm_dialog::{ConfirmDialog, ConfirmDialogEvent};
+pub use resource_report::ResourceReportPanel;
+pub use quit_dialog::{QuitDialog, QuitDialogEvent};
+pub use query_history_dialog::{QueryHistoryDialog, QueryHistoryDialogEvent};
+pub use plan_view::PlanView;
+pub use batch_export_dialog::{BatchExportDialog, BatchExportDialogEvent};
+pub use script_runner_dialog::{ScriptRunnerDialog, ScriptRunnerDialogEvent};
+pub use tail_bar::{TailBar, TailBarEvent};
+pub use bookmarks_panel::{BookmarksPanel, BookmarksPanelEvent};
+pub use structure_view::{StructureView, StructureViewEvent};
+pub use schema_diff_dialog::{SchemaDiffDialog, SchemaDiffDialogEvent};
+pub use statement_summary_dialog::{StatementSummaryDialog, StatementSummaryDialogEvent};
+pub use query_log_panel::{QueryLogPanel, QueryLogPanelEvent};
+pub use watch_panel::{WatchPanel, WatchPanelEvent};
+pub use restored_tab_banner::{RestoredTabBanner, RestoredTabBannerEvent};
+pub use queue_panel::{QueuePanel, QueuePanelEvent};
+pub use alter_columns_dialog::{AlterColumnsDialog, AlterColumnsDialogEvent};
+pub use command_palette::{CommandPalette, CommandPaletteEvent, PaletteItem};
+pub use distinct_bar::{DistinctBar, DistinctBarEvent};
+pub use bar_chart::{BarChart, BarChartEvent};
+pub use server_filter_bar::{ServerFilterBar, ServerFilterBarEvent};
+pub use json_filter_dialog::{JsonFilterDialog, JsonFilterDialogEvent};
+pub use row_compare_dialog::{RowCompareDialog, RowCompareDialogEvent};
+pub use session_restore_dialog::{SessionRestoreDialog, SessionRestoreDialogEvent};
+pub use cell_inspector::{CellInspector, CellInspectorEvent};
+pub use replace_values_dialog::{ReplaceValuesDialog, ReplaceValuesDialogEvent};
+pub use paste_table_dialog::{PasteTableDialog, PasteTableDialogEvent};
+pub use import_dialog::{ImportDialog, ImportDialogEvent};
+pub use load_errors_dialog::{LoadErrorsDialog, LoadErrorsDialogEvent};
+pub use password_prompt_dialog::{PasswordPromptDialog, PasswordPromptDialogEvent};
+pub use distribution_panel::{DistributionPanel, DistributionPanelEvent};
+pub use result_chart::{ResultChart, ResultChartEvent};
+pub use large_table_banner::{LargeTableBanner, LargeTableBannerEvent};
+pub use follow_up_bar::{FollowUpBar, FollowUpBarEvent};
+pub use param_prompt_dialog::{ParamPromptDialog, ParamPromptDialogEvent};
+pub use wide_table_banner::{WideTableBanner, WideTableBannerEvent};
+pub use computed_column_dialog::{ComputedColumnDialog, ComputedColumnDialogEvent};
+pub use sql_dump_dialog::{SqlDumpDialog, SqlDumpDialogEvent};
@@ -1,10 +1,15 @@
+use crate::db::sql::SqlPage;
+use crate::models::{page_count, page_range};
 use eframe::egui;
 
 #[derive(Debug)]
 pub enum PaginationEvent {
     Reload,
+    Export,
     PageSizeChanged(usize),
     PageChanged(usize),
+    ServerSortChanged(bool),
+    SqlPageChanged(SqlPage), // rewrite the query's LIMIT/OFFSET and run it again
 }
 
 pub struct PaginationControls;
@@ -14,26 +19,48 @@ impl PaginationControls {
         Self
     }
 
+    /// Paging of the filtered rows. `server_sort` is whether a table tab sorts by
+    /// reloading, None for tabs that can only sort locally. With `sql_page`, the LIMIT/OFFSET
+    /// of a query tab's SQL and the rows it fetched, pages come from the server instead.
     pub fn show(
         &mut self,
         ui: &mut egui::Ui,
         current_page: usize,
         page_size: usize,
         total_rows: usize,
+        server_sort: Option<bool>,
+        sql_page: Option<(SqlPage, usize)>,
     ) -> Option<PaginationEvent> {
         let mut event = None;
 
-        let total_pages = total_rows.div_ceil(page_size);
-        let start_row = current_page * page_size;
-        let end_row = (start_row + page_size).min(total_rows);
+        let total_pages = page_count(total_rows, page_size);
+        let rows = page_range(total_rows, current_page, page_size);
 
         ui.horizontal(|ui| {
             if ui.button("🔄 Reload").clicked() {
                 event = Some(PaginationEvent::Reload);
             }
+            if ui.button("💾 Export").on_hover_text("Export the filtered rows to a file").clicked() {
+                event = Some(PaginationEvent::Export);
+            }
+            if let Some(mut server_sort) = server_sort {
+                if ui.checkbox(&mut server_sort, "Sort on server")
+                    .on_hover_text("Clicking a header reloads the table with ORDER BY instead of sorting only the loaded rows")
+                    .changed()
+                {
+                    event = Some(PaginationEvent::ServerSortChanged(server_sort));
+                }
+            }
 
             ui.separator();
 
+            if let Some((page, fetched_rows)) = sql_page {
+                if let Some(changed) = Self::show_sql_page(ui, page, fetched_rows) {
+                    event = Some(changed);
+                }
+                return;
+            }
+
             ui.label("Rows per page:");
 
             for size in [50, 100, 500, 1000, 5000] {
@@ -52,9 +79,9 @@ impl PaginationControls {
             ui.label(format!(
                 "Page {} of {} ({}-{} of {} rows)",
                 current_page + 1,
-                total_pages.max(1),
-                start_row + 1,
-                end_row,
+                total_pages,
+                (rows.start + 1).min(rows.end),
+                rows.end,
                 total_rows
             ));
 
@@ -67,4 +94,35 @@ impl PaginationControls {
 
         event
     }
+
+    // Pages of the query's LIMIT, each fetched by rewriting its OFFSET. Only a page
+    // shorter than the LIMIT is known to be the last.
+    fn show_sql_page(ui: &mut egui::Ui, page: SqlPage, fetched_rows: usize) -> Option<PaginationEvent> {
+        let mut event = None;
+
+        ui.label("LIMIT:");
+        for size in [50, 100, 500, 1000, 5000] {
+            if ui.selectable_label(page.limit == size, format!("{}", size)).clicked() && page.limit != size {
+                event = Some(PaginationEvent::SqlPageChanged(page.with_limit(size)));
+            }
+        }
+
+        ui.separator();
+
+        if ui.add_enabled(page.offset > 0, egui::Button::new("◀ Previous")).clicked() {
+            event = page.previous().map(PaginationEvent::SqlPageChanged);
+        }
+
+        let first = page.offset + 1;
+        let last = page.offset + fetched_rows as u64;
+        ui.label(format!("Page {} (rows {}-{})", page.number() + 1, first.min(last), last))
+            .on_hover_text(format!("Fetched with LIMIT {} OFFSET {}", page.limit, page.offset));
+
+        let has_more = fetched_rows as u64 >= page.limit;
+        if ui.add_enabled(has_more, egui::Button::new("Next ▶")).clicked() {
+            event = Some(PaginationEvent::SqlPageChanged(page.next()));
+        }
+
+        event
+    }
 }
@@ -0,0 +1,68 @@
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum ParamPromptDialogEvent {
+    Run(Vec<(String, Option<String>)>), // placeholder (":id", "$1"), value or NULL
+    Cancel,
+}
+
+pub struct ParamPromptDialog {
+    // Placeholder, its text and whether NULL is bound instead
+    params: Vec<(String, String, bool)>,
+    focused: bool,
+}
+
+impl ParamPromptDialog {
+    /// `params` are the query's placeholders in order, prefilled with the values they
+    /// were last run with.
+    pub fn new(params: Vec<(String, Option<String>)>) -> Self {
+        let params = params.into_iter()
+            .map(|(name, value)| (name, value.clone().unwrap_or_default(), value.is_none()))
+            .collect();
+        Self { params, focused: false }
+    }
+
+    /// Asks for the value of each placeholder before the query runs. Values are sent
+    /// as bound parameters, never pasted into the SQL.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<ParamPromptDialogEvent> {
+        let mut event = None;
+
+        egui::Window::new("Query parameters")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                let mut submitted = false;
+                egui::Grid::new("param_prompt_grid").num_columns(3).spacing([8.0, 6.0]).show(ui, |ui| {
+                    for (index, (name, value, is_null)) in self.params.iter_mut().enumerate() {
+                        ui.monospace(name.as_str());
+                        let response = ui.add_enabled(!*is_null, egui::TextEdit::singleline(value).desired_width(240.0));
+                        ui.checkbox(is_null, "NULL");
+                        if index == 0 && !self.focused {
+                            response.request_focus();
+                            self.focused = true;
+                        }
+                        submitted |= response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        ui.end_row();
+                    }
+                });
+                ui.add_space(4.0);
+                ui.weak("The server converts each value to the type the query expects there");
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button("▶ Run").clicked() || submitted {
+                        let values = self.params.iter()
+                            .map(|(name, value, is_null)| (name.clone(), (!is_null).then(|| value.clone())))
+                            .collect();
+                        event = Some(ParamPromptDialogEvent::Run(values));
+                    }
+                    if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        event = Some(ParamPromptDialogEvent::Cancel);
+                    }
+                });
+            });
+
+        event
+    }
+}
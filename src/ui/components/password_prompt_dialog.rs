@@ -0,0 +1,56 @@
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum PasswordPromptDialogEvent {
+    Connect(String, bool), // password, remember it until the app closes
+    Cancel,
+}
+
+pub struct PasswordPromptDialog {
+    // Connection being opened, as shown to the user
+    description: String,
+    password: String,
+    remember: bool,
+    focused: bool,
+}
+
+impl PasswordPromptDialog {
+    pub fn new(description: String) -> Self {
+        Self { description, password: String::new(), remember: false, focused: false }
+    }
+
+    /// Asks for the password of a connection that never stores it.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<PasswordPromptDialogEvent> {
+        let mut event = None;
+
+        egui::Window::new("🔑 Password required")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("Connect to {}", self.description));
+                ui.add_space(6.0);
+
+                let response = ui.add(egui::TextEdit::singleline(&mut self.password).password(true).hint_text("Password"));
+                if !self.focused {
+                    response.request_focus();
+                    self.focused = true;
+                }
+                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                ui.checkbox(&mut self.remember, "Remember for this session")
+                    .on_hover_text("Kept in memory until the app closes, never written to disk");
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button("Connect").clicked() || submitted {
+                        event = Some(PasswordPromptDialogEvent::Connect(std::mem::take(&mut self.password), self.remember));
+                    }
+                    if ui.button("Cancel").clicked() {
+                        event = Some(PasswordPromptDialogEvent::Cancel);
+                    }
+                });
+            });
+
+        event
+    }
+}
@@ -0,0 +1,127 @@
+use crate::config::Driver;
+use crate::models::PastedTable;
+use eframe::egui;
+
+// Pasted rows shown under the detected columns
+const PREVIEW_ROWS: usize = 5;
+
+#[derive(Debug)]
+pub enum PasteTableDialogEvent {
+    Create(PastedTable, String), // parsed rows, table name
+    Close,
+}
+
+pub struct PasteTableDialog {
+    text: String,
+    name: String,
+    // None detects the header from the column types
+    header: Option<bool>,
+    // Parse of `text`, redone when it or `header` changes
+    parsed: Option<Result<PastedTable, String>>,
+}
+
+impl PasteTableDialog {
+    pub fn new() -> Self {
+        Self { text: String::new(), name: "pasted".to_string(), header: None, parsed: None }
+    }
+
+    /// Text area for CSV, TSV or JSON rows, previewing the columns and types they
+    /// would get. Create makes them a temporary table on the current connection.
+    pub fn show(&mut self, ctx: &egui::Context, driver: Driver, running: bool) -> Option<PasteTableDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new("📋 Paste as Temporary Table")
+            .open(&mut is_open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().id_source("paste_table_text").max_height(200.0).show(ui, |ui| {
+                    let response = ui.add(egui::TextEdit::multiline(&mut self.text)
+                        .hint_text("id,email\n1,ann@example.com\n\nor [{\"id\": 1}, ...]")
+                        .code_editor()
+                        .desired_rows(8)
+                        .desired_width(f32::INFINITY));
+                    if response.changed() {
+                        self.header = None;
+                        self.parsed = None;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Table name:");
+                    ui.add(egui::TextEdit::singleline(&mut self.name).desired_width(200.0));
+                });
+
+                ui.separator();
+                let (text, header) = (&self.text, self.header);
+                let table = self.parsed.get_or_insert_with(|| PastedTable::parse(text, header).map_err(|e| e.to_string()));
+                let mut header_changed = None;
+                match &*table {
+                    Ok(table) => {
+                        if let Some(mut header) = table.header {
+                            if ui.checkbox(&mut header, "First line is the header").changed() {
+                                header_changed = Some(header);
+                            }
+                        }
+                        ui.label(format!("{} rows, {} columns", table.rows.len(), table.columns.len()));
+                        egui::ScrollArea::both().id_source("paste_table_preview").max_height(180.0).show(ui, |ui| {
+                            egui::Grid::new("paste_table_preview_grid").striped(true).show(ui, |ui| {
+                                for (name, column_type) in &table.columns {
+                                    ui.vertical(|ui| {
+                                        ui.strong(name);
+                                        ui.label(egui::RichText::new(column_type.sql(driver)).size(9.0).color(egui::Color32::GRAY));
+                                    });
+                                }
+                                ui.end_row();
+                                for row in table.rows.iter().take(PREVIEW_ROWS) {
+                                    for value in row {
+                                        match value {
+                                            Some(value) => ui.label(value),
+                                            None => ui.label(egui::RichText::new("NULL").color(egui::Color32::GRAY)),
+                                        };
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                    }
+                    Err(e) => {
+                        ui.label(egui::RichText::new(e).color(egui::Color32::GRAY));
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    let name = self.name.trim();
+                    let ready = table.is_ok() && !name.is_empty() && !running;
+                    if ui.add_enabled(ready, egui::Button::new("Create Table"))
+                        .on_hover_text("CREATE TEMPORARY TABLE on the current connection; it is dropped when the session ends")
+                        .clicked()
+                    {
+                        if let Ok(table) = table {
+                            event = Some(PasteTableDialogEvent::Create(table.clone(), name.to_string()));
+                        }
+                    }
+                    if running {
+                        ui.spinner();
+                    }
+                    if ui.button("Close").clicked() {
+                        event = Some(PasteTableDialogEvent::Close);
+                    }
+                });
+
+                if header_changed.is_some() {
+                    self.header = header_changed;
+                    self.parsed = None;
+                }
+            });
+
+        if !is_open {
+            event = Some(PasteTableDialogEvent::Close);
+        }
+
+        event
+    }
+}
@@ -0,0 +1,86 @@
+use crate::models::{ExplainPlan, PlanNode};
+use eframe::egui;
+
+// Share of the execution time above which a node is highlighted as a hot spot
+const HOT_SPOT_SHARE: f64 = 0.3;
+
+pub struct PlanView;
+
+impl PlanView {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Render the plan as an expandable tree, one collapsible row per node.
+    pub fn show(&mut self, ui: &mut egui::Ui, tab_id: usize, plan: &ExplainPlan) {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(if plan.is_analyzed() { "EXPLAIN ANALYZE" } else { "EXPLAIN" }).strong());
+            if let Some(planning) = plan.planning_ms {
+                ui.separator();
+                ui.label(format!("Planning {:.2} ms", planning));
+            }
+            if let Some(execution) = plan.execution_ms {
+                ui.separator();
+                ui.label(format!("Execution {:.2} ms", execution));
+            }
+        });
+        ui.separator();
+
+        egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                let id = egui::Id::new(("plan_view", tab_id));
+                self.show_node(ui, id, &plan.root, plan.execution_ms);
+            });
+    }
+
+    fn show_node(&self, ui: &mut egui::Ui, id: egui::Id, node: &PlanNode, execution_ms: Option<f64>) {
+        let header = |ui: &mut egui::Ui| {
+            let hot = match (node.exclusive_ms(), execution_ms) {
+                (Some(own), Some(total)) if total > 0.0 => own / total >= HOT_SPOT_SHARE,
+                _ => false,
+            };
+            let title = egui::RichText::new(node.title()).strong();
+            ui.label(if hot { title.color(egui::Color32::from_rgb(200, 60, 0)) } else { title });
+
+            let gray = egui::Color32::GRAY;
+            ui.label(egui::RichText::new(format!(
+                "cost={:.2}..{:.2} rows={} width={}",
+                node.startup_cost, node.total_cost, node.plan_rows, node.plan_width
+            )).size(11.0).color(gray).family(egui::FontFamily::Monospace));
+
+            if let Some(total) = node.actual_total_ms {
+                ui.label(egui::RichText::new(format!(
+                    "actual time={:.3}..{:.3} rows={} loops={}",
+                    node.actual_startup_ms.unwrap_or(0.0),
+                    total,
+                    node.actual_rows.unwrap_or(0.0),
+                    node.actual_loops.unwrap_or(1.0)
+                )).size(11.0).color(egui::Color32::from_rgb(40, 110, 160)).family(egui::FontFamily::Monospace));
+            }
+        };
+
+        if node.children.is_empty() && node.conditions.is_empty() {
+            ui.horizontal(|ui| {
+                // Line up with the headers of expandable siblings
+                ui.add_space(ui.spacing().indent);
+                header(ui);
+            });
+            return;
+        }
+
+        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true)
+            .show_header(ui, header)
+            .body(|ui| {
+                for (key, value) in &node.conditions {
+                    ui.label(egui::RichText::new(format!("{}: {}", key, value))
+                        .size(11.0)
+                        .color(egui::Color32::DARK_GRAY)
+                        .family(egui::FontFamily::Monospace));
+                }
+                for (i, child) in node.children.iter().enumerate() {
+                    self.show_node(ui, id.with(i), child, execution_ms);
+                }
+            });
+    }
+}
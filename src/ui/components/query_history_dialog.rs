@@ -0,0 +1,129 @@
+use eframe::egui;
+use crate::config::QueryHistory;
+use crate::models::format_bytes;
+
+#[derive(Debug)]
+pub enum QueryHistoryDialogEvent {
+    Load(usize),
+    Rerun(usize),
+    Clear,
+    Close,
+}
+
+pub struct QueryHistoryDialog {
+    search_text: String,
+}
+
+impl QueryHistoryDialog {
+    pub fn new() -> Self {
+        Self {
+            search_text: String::new(),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, history: &QueryHistory) -> Option<QueryHistoryDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new("🕘 Query History")
+            .open(&mut is_open)
+            .resizable(true)
+            .default_width(650.0)
+            .default_height(450.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("🔍");
+                    ui.add(egui::TextEdit::singleline(&mut self.search_text)
+                        .hint_text("Search SQL...")
+                        .desired_width(300.0));
+                    if !self.search_text.is_empty() && ui.small_button("✖").clicked() {
+                        self.search_text.clear();
+                    }
+                });
+                ui.separator();
+
+                let matches = history.search(&self.search_text);
+                if matches.is_empty() {
+                    ui.label(if history.entries.is_empty() {
+                        "No queries executed yet."
+                    } else {
+                        "No matching queries."
+                    });
+                } else {
+                    egui::ScrollArea::vertical()
+                        .max_height(350.0)
+                        .show(ui, |ui| {
+                            for index in matches {
+                                let entry = &history.entries[index];
+                                ui.group(|ui| {
+                                    ui.horizontal(|ui| {
+                                        let (icon, color) = if entry.is_success() {
+                                            ("✔", egui::Color32::from_rgb(40, 140, 60))
+                                        } else {
+                                            ("✖", egui::Color32::from_rgb(200, 40, 40))
+                                        };
+                                        ui.label(egui::RichText::new(icon).color(color));
+
+                                        let outcome = match (&entry.row_count, &entry.error) {
+                                            (_, Some(error)) => error.clone(),
+                                            (Some(count), None) => match entry.bytes {
+                                                Some(bytes) => format!("{} rows · {}", count, format_bytes(bytes)),
+                                                None => format!("{} rows", count),
+                                            },
+                                            (None, None) => String::new(),
+                                        };
+                                        let connection = entry.connection_name.as_ref()
+                                            .map(|name| format!(" · {}", name))
+                                            .unwrap_or_default();
+                                        ui.label(egui::RichText::new(format!(
+                                            "{} · {} ms · {}{}",
+                                            entry.executed_at, entry.duration_ms, outcome, connection
+                                        ))
+                                            .size(10.0)
+                                            .color(egui::Color32::GRAY));
+
+                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                            if ui.button("▶ Re-run").clicked() {
+                                                event = Some(QueryHistoryDialogEvent::Rerun(index));
+                                            }
+                                            if ui.button("📥 Load").clicked() {
+                                                event = Some(QueryHistoryDialogEvent::Load(index));
+                                            }
+                                        });
+                                    });
+
+                                    let preview: String = if entry.sql.chars().count() > 200 {
+                                        format!("{}...", entry.sql.chars().take(200).collect::<String>())
+                                    } else {
+                                        entry.sql.clone()
+                                    };
+                                    ui.label(egui::RichText::new(preview)
+                                        .size(10.0)
+                                        .color(egui::Color32::DARK_GRAY)
+                                        .family(egui::FontFamily::Monospace));
+                                });
+                                ui.add_space(4.0);
+                            }
+                        });
+                }
+
+                ui.add_space(10.0);
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.button("Close").clicked() {
+                        event = Some(QueryHistoryDialogEvent::Close);
+                    }
+                    if !history.entries.is_empty() && ui.button("🗑 Clear History").clicked() {
+                        event = Some(QueryHistoryDialogEvent::Clear);
+                    }
+                });
+            });
+
+        if !is_open {
+            event = Some(QueryHistoryDialogEvent::Close);
+        }
+
+        event
+    }
+}
@@ -0,0 +1,55 @@
+use crate::models::QueryLog;
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum QueryLogPanelEvent {
+    Clear,
+    Close,
+}
+
+pub struct QueryLogPanel;
+
+impl QueryLogPanel {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Outcome, time taken and statement of everything run this session, newest at the bottom.
+    pub fn show(&mut self, ui: &mut egui::Ui, log: &QueryLog) -> Option<QueryLogPanelEvent> {
+        let mut event = None;
+
+        ui.horizontal(|ui| {
+            ui.strong("📜 Query Log");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("✖").on_hover_text("Hide the query log").clicked() {
+                    event = Some(QueryLogPanelEvent::Close);
+                }
+                if ui.small_button("Clear").clicked() {
+                    event = Some(QueryLogPanelEvent::Clear);
+                }
+            });
+        });
+        ui.separator();
+
+        if log.entries().is_empty() {
+            ui.label("Executed statements and their results appear here");
+            return event;
+        }
+
+        egui::ScrollArea::vertical().auto_shrink([false, false]).stick_to_bottom(true).show(ui, |ui| {
+            egui::Grid::new("query_log").striped(true).num_columns(4).show(ui, |ui| {
+                for entry in log.entries() {
+                    ui.label(&entry.logged_at);
+                    let message = egui::RichText::new(&entry.message).strong();
+                    ui.label(if entry.is_error { message.color(egui::Color32::from_rgb(200, 40, 40)) } else { message });
+                    ui.label(format!("{} ms", entry.duration_ms));
+                    let first_line = entry.sql.lines().next().unwrap_or_default();
+                    ui.label(egui::RichText::new(first_line).monospace()).on_hover_text(&entry.sql);
+                    ui.end_row();
+                }
+            });
+        });
+
+        event
+    }
+}
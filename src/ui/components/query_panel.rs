@@ -1,49 +1,125 @@
 use eframe::egui;
+use std::collections::HashMap;
+use crate::config::{Driver, Snippet};
+use crate::db::{routing, QueryTarget, SchemaInfo, StatementTimeout};
 use crate::sql_editor::SqlEditor;
 
 #[derive(Debug)]
 pub enum QueryPanelEvent {
     Execute,
+    ExecuteSelection(String),
     Clear,
     Close,
     SaveQuery,
     LoadQuery,
+    ShowHistory,
+    Explain(bool), // analyze
+    ShareQuery,
+    Cancel,
 }
 
 pub struct QueryPanel {
-    sql_editor: SqlEditor,
+    // Editor state (cursor, suggestions, snippet stops) of each tab's SQL buffer, by tab
+    // id; None for the buffer used while no tab is open
+    sql_editors: HashMap<Option<usize>, SqlEditor>,
 }
 
 impl QueryPanel {
     pub fn new() -> Self {
         Self {
-            sql_editor: SqlEditor::new(),
+            sql_editors: HashMap::new(),
         }
     }
 
+    fn sql_editor(&mut self, tab_id: Option<usize>) -> &mut SqlEditor {
+        self.sql_editors.entry(tab_id).or_insert_with(SqlEditor::new)
+    }
+
+    /// Insert text at the cursor of the editor of `tab_id`, e.g. a table name from the database tree.
+    pub fn insert_at_cursor(&mut self, tab_id: Option<usize>, query_input: &mut String, text: &str) {
+        self.sql_editor(tab_id).insert_at_cursor(query_input, text);
+    }
+
+    /// Drop the editor state of a closed tab.
+    pub fn forget_tab(&mut self, tab_id: usize) {
+        self.sql_editors.remove(&Some(tab_id));
+    }
+
+    /// Editor for `query_input`, the SQL buffer of tab `tab_id`, with the execution controls.
+    #[allow(clippy::too_many_arguments)]
     pub fn show(
         &mut self,
         ui: &mut egui::Ui,
+        tab_id: Option<usize>,
         query_input: &mut String,
-        tables: &[String],
+        schemas: &[SchemaInfo],
         columns: &[String],
+        snippets: &[Snippet],
+        driver: Driver,
+        force_primary: Option<&mut bool>,
+        statement_timeout: &mut StatementTimeout,
+        resource_report: &mut bool,
+        transaction_mode: &mut bool,
+        running: bool,
     ) -> Option<QueryPanelEvent> {
         let mut event = None;
 
         ui.vertical(|ui| {
             ui.label("SQL Query:");
 
-            let editor_response = self.sql_editor.show(ui, query_input, tables, columns);
+            // Its own id keeps the text edit's cursor and undo history apart from other tabs'
+            let sql_editor = self.sql_editor(tab_id);
+            let editor_response = ui.push_id(("sql_editor", tab_id), |ui| sql_editor.show(ui, query_input, schemas, columns, snippets, driver)).inner;
+            let selection = sql_editor.selected_text(query_input);
 
-            if editor_response.execute {
-                event = Some(QueryPanelEvent::Execute);
+            if editor_response.execute || editor_response.execute_selection {
+                event = match &selection {
+                    Some(sql) => Some(QueryPanelEvent::ExecuteSelection(sql.clone())),
+                    // The selection shortcut does nothing without a selection
+                    None if editor_response.execute => Some(QueryPanelEvent::Execute),
+                    None => None,
+                };
             }
 
             ui.add_space(5.0);
             ui.horizontal(|ui| {
-                if ui.button("Execute").clicked() {
+                if running {
+                    if ui.button("⏹ Cancel").on_hover_text("Cancel the running query").clicked() {
+                        event = Some(QueryPanelEvent::Cancel);
+                    }
+                } else if let Some(sql) = &selection {
+                    if ui.button("Execute Selection").on_hover_text("Run only the highlighted SQL (Cmd/Ctrl+Shift+Enter)").clicked() {
+                        event = Some(QueryPanelEvent::ExecuteSelection(sql.clone()));
+                    }
+                } else if ui.button("Execute").on_hover_text("Run the whole query (Cmd/Ctrl+Enter)").clicked() {
                     event = Some(QueryPanelEvent::Execute);
                 }
+                if ui.add_enabled(!running, egui::Button::new("🔍 Explain"))
+                    .on_hover_text("Show the query plan as a tree in a new tab")
+                    .clicked()
+                {
+                    event = Some(QueryPanelEvent::Explain(false));
+                }
+                if ui.add_enabled(!running, egui::Button::new("Explain Analyze"))
+                    .on_hover_text("Run read-only queries with EXPLAIN ANALYZE and show actual times and row counts")
+                    .clicked()
+                {
+                    event = Some(QueryPanelEvent::Explain(true));
+                }
+                egui::ComboBox::from_id_source("statement_timeout")
+                    .selected_text(format!("⏱ {}", statement_timeout.as_str()))
+                    .width(90.0)
+                    .show_ui(ui, |ui| {
+                        for timeout in StatementTimeout::all() {
+                            ui.selectable_value(statement_timeout, timeout, timeout.as_str());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Statement timeout for this execution");
+                ui.checkbox(resource_report, "📊 Report")
+                    .on_hover_text("After read-only queries, re-run them with EXPLAIN (ANALYZE, BUFFERS) and show timing and buffer usage");
+                ui.checkbox(transaction_mode, "🔓 Transaction")
+                    .on_hover_text("Begin a transaction before executing and keep it open until you commit or roll back from the status bar");
                 if ui.button("Clear").clicked() {
                     event = Some(QueryPanelEvent::Clear);
                 }
@@ -56,6 +132,25 @@ impl QueryPanel {
                 if ui.button("📂 Load").on_hover_text("Load saved query").clicked() {
                     event = Some(QueryPanelEvent::LoadQuery);
                 }
+                if ui.button("🕘 History").on_hover_text("Search and re-run previously executed queries").clicked() {
+                    event = Some(QueryPanelEvent::ShowHistory);
+                }
+                if ui.button("🔗 Share").on_hover_text("Save query as a shareable .dexq file").clicked() {
+                    event = Some(QueryPanelEvent::ShareQuery);
+                }
+
+                // Replica routing, only shown when the connection has a live replica
+                if let Some(force_primary) = force_primary {
+                    ui.separator();
+                    ui.checkbox(force_primary, "Force primary")
+                        .on_hover_text("Send read-only queries to the primary instead of the replica");
+
+                    let (text, color) = match routing::route(query_input, *force_primary, true) {
+                        QueryTarget::Replica => ("→ replica", egui::Color32::from_rgb(40, 140, 60)),
+                        QueryTarget::Primary => ("→ primary", egui::Color32::from_rgb(200, 120, 0)),
+                    };
+                    ui.label(egui::RichText::new(text).size(10.0).color(color));
+                }
 
                 ui.separator();
 
@@ -0,0 +1,57 @@
+use crate::models::OperationQueue;
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum QueuePanelEvent {
+    CancelRunning,
+    Remove(u64), // id of the queued operation
+    Clear,
+}
+
+pub struct QueuePanel;
+
+impl QueuePanel {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// The running operation followed by everything waiting for the connection, in the
+    /// order it will run.
+    pub fn show<T>(&mut self, ui: &mut egui::Ui, running: Option<&str>, can_cancel: bool, queue: &OperationQueue<T>) -> Option<QueuePanelEvent> {
+        let mut event = None;
+
+        ui.horizontal(|ui| {
+            ui.strong(format!("⏳ Queue ({} waiting)", queue.len()));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("Clear").on_hover_text("Drop everything that has not started yet").clicked() {
+                    event = Some(QueuePanelEvent::Clear);
+                }
+            });
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            egui::Grid::new("operation_queue").striped(true).num_columns(3).show(ui, |ui| {
+                if let Some(label) = running {
+                    ui.label(egui::RichText::new("Running").color(egui::Color32::from_rgb(40, 140, 60)).strong());
+                    ui.label(egui::RichText::new(label).monospace());
+                    if can_cancel && ui.small_button("⏹").on_hover_text("Cancel the running operation").clicked() {
+                        event = Some(QueuePanelEvent::CancelRunning);
+                    }
+                    ui.end_row();
+                }
+                for (position, item) in queue.items().enumerate() {
+                    ui.label(format!("#{}", position + 1))
+                        .on_hover_text(format!("Starts after {} more operation(s)", position + running.is_some() as usize));
+                    ui.label(egui::RichText::new(&item.label).monospace());
+                    if ui.small_button("✖").on_hover_text("Remove from the queue").clicked() {
+                        event = Some(QueuePanelEvent::Remove(item.id));
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+
+        event
+    }
+}
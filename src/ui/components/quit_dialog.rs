@@ -0,0 +1,50 @@
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum QuitDialogEvent {
+    Wait,
+    CancelAndQuit,
+    Stay,
+}
+
+pub struct QuitDialog;
+
+impl QuitDialog {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Asked when the window is closed while a write statement is still running;
+    /// `can_cancel` is false for writes that have no way to be cancelled.
+    pub fn show(&mut self, ctx: &egui::Context, waiting: bool, can_cancel: bool) -> Option<QuitDialogEvent> {
+        let mut event = None;
+
+        egui::Window::new("⚠ Write in progress")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                if waiting {
+                    ui.label("Waiting for the running write to finish, the app will close afterwards...");
+                } else {
+                    ui.label("A statement that changes data is still running.");
+                    ui.label("Quitting now could leave the change half-applied on the client side.");
+                }
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    if !waiting && ui.button("Wait and quit").clicked() {
+                        event = Some(QuitDialogEvent::Wait);
+                    }
+                    if can_cancel && ui.button("Cancel it and quit").clicked() {
+                        event = Some(QuitDialogEvent::CancelAndQuit);
+                    }
+                    if ui.button("Keep open").clicked() {
+                        event = Some(QuitDialogEvent::Stay);
+                    }
+                });
+            });
+
+        event
+    }
+}
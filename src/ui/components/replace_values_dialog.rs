@@ -0,0 +1,99 @@
+use crate::models::{PendingEdits, TableData, ValueReplace};
+use eframe::egui;
+
+// Changed cells listed in the preview; staging takes all of them
+const PREVIEW_ROWS: usize = 200;
+
+#[derive(Debug)]
+pub enum ReplaceValuesDialogEvent {
+    Stage,
+    Close,
+}
+
+pub struct ReplaceValuesDialog;
+
+impl ReplaceValuesDialog {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Find and replace in one column of `data`, over the rows passing the grid filters.
+    /// Lists the cells that change; Stage adds them to the tab's pending edits.
+    pub fn show(&mut self, ctx: &egui::Context, data: &TableData, rows: &[usize], pending: &PendingEdits, replace: &mut ValueReplace) -> Option<ReplaceValuesDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+        let column = data.columns.get(replace.column).map(|column| column.name.as_str()).unwrap_or_default();
+
+        egui::Window::new(format!("🔁 Replace Values in {}", column))
+            .open(&mut is_open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("replace_values_grid").num_columns(2).spacing([8.0, 6.0]).show(ui, |ui| {
+                    ui.label("Find:");
+                    ui.add(egui::TextEdit::singleline(&mut replace.find).desired_width(320.0));
+                    ui.end_row();
+
+                    ui.label("Replace with:");
+                    let hint = if replace.regex { "$1 or ${name} insert captured groups" } else { "" };
+                    ui.add(egui::TextEdit::singleline(&mut replace.replace).hint_text(hint).desired_width(320.0));
+                    ui.end_row();
+
+                    ui.label("");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut replace.match_case, "Match case");
+                        ui.checkbox(&mut replace.regex, "Regular expression");
+                    });
+                    ui.end_row();
+                });
+
+                ui.separator();
+                let preview = replace.preview(data, rows, pending);
+                match &preview {
+                    Ok(replacements) if replacements.is_empty() => {
+                        ui.label(egui::RichText::new("No cells change").color(egui::Color32::GRAY));
+                    }
+                    Ok(replacements) => {
+                        ui.label(format!("{} of {} cells change", replacements.len(), rows.len()));
+                        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                            egui::Grid::new("replace_values_preview").num_columns(3).striped(true).show(ui, |ui| {
+                                for replacement in replacements.iter().take(PREVIEW_ROWS) {
+                                    ui.label(egui::RichText::new(format!("{}", replacement.row + 1)).color(egui::Color32::GRAY));
+                                    ui.label(egui::RichText::new(&replacement.current).monospace().color(egui::Color32::from_rgb(220, 100, 100)));
+                                    ui.label(egui::RichText::new(&replacement.value).monospace().color(egui::Color32::from_rgb(100, 180, 100)));
+                                    ui.end_row();
+                                }
+                            });
+                            if replacements.len() > PREVIEW_ROWS {
+                                ui.label(egui::RichText::new(format!("… and {} more", replacements.len() - PREVIEW_ROWS)).color(egui::Color32::GRAY));
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        ui.label(egui::RichText::new(e.to_string()).color(egui::Color32::GRAY));
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    let count = preview.as_ref().map_or(0, |replacements| replacements.len());
+                    if ui.add_enabled(count > 0, egui::Button::new(format!("Stage {} Edits", count)))
+                        .on_hover_text("Add the changes to the pending edits, to review and apply as UPDATEs")
+                        .clicked()
+                    {
+                        event = Some(ReplaceValuesDialogEvent::Stage);
+                    }
+                    if ui.button("Close").clicked() {
+                        event = Some(ReplaceValuesDialogEvent::Close);
+                    }
+                });
+            });
+
+        if !is_open {
+            event = Some(ReplaceValuesDialogEvent::Close);
+        }
+
+        event
+    }
+}
@@ -0,0 +1,44 @@
+use crate::db::ResourceReport;
+use eframe::egui;
+
+pub struct ResourceReportPanel;
+
+impl ResourceReportPanel {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// One-line summary of the last execution's timing and buffer usage.
+    pub fn show(&mut self, ui: &mut egui::Ui, report: &ResourceReport) {
+        let gray = egui::Color32::from_rgb(120, 120, 120);
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("📊").size(11.0));
+            ui.label(egui::RichText::new(format!(
+                "Planning {:.2} ms · Execution {:.2} ms",
+                report.planning_ms, report.execution_ms
+            )).size(11.0));
+
+            ui.separator();
+            let cache = report.cache_hit_ratio()
+                .map(|ratio| format!(" ({:.1}% cached)", ratio * 100.0))
+                .unwrap_or_default();
+            ui.label(egui::RichText::new(format!(
+                "Shared blocks: {} hit, {} read{}",
+                report.shared_hit_blocks, report.shared_read_blocks, cache
+            )).size(11.0).color(gray));
+
+            ui.separator();
+            let temp_color = if report.temp_written_blocks > 0 {
+                egui::Color32::from_rgb(200, 120, 0)  // Spilled to disk
+            } else {
+                gray
+            };
+            ui.label(egui::RichText::new(format!(
+                "Temp: {} blocks read, {} KB written",
+                report.temp_read_blocks,
+                report.temp_written_bytes() / 1024
+            )).size(11.0).color(temp_color));
+        });
+    }
+}
@@ -0,0 +1,37 @@
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum RestoredTabBannerEvent {
+    Reload,
+    Dismiss,
+}
+
+pub struct RestoredTabBanner;
+
+impl RestoredTabBanner {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Prompt over a tab restored from the last session. `other_connection` names the
+    /// connection a reload switches to when it is not the current one.
+    pub fn show(&mut self, ui: &mut egui::Ui, other_connection: Option<&str>) -> Option<RestoredTabBannerEvent> {
+        let mut event = None;
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("↻ Restored from the last session, the data may be out of date").color(egui::Color32::from_rgb(200, 130, 0)));
+            if let Some(name) = other_connection {
+                ui.label(format!("(reloading connects to '{}')", name));
+            }
+            if ui.small_button("Reload now").clicked() {
+                event = Some(RestoredTabBannerEvent::Reload);
+            }
+            if ui.small_button("Dismiss").clicked() {
+                event = Some(RestoredTabBannerEvent::Dismiss);
+            }
+        });
+        ui.separator();
+
+        event
+    }
+}
@@ -0,0 +1,100 @@
+use crate::models::{ChartKind, ChartSettings, PlotSeries, TableData};
+use eframe::egui;
+use egui_plot::{Bar, BarChart, Line, Plot, PlotPoints, Points};
+
+#[derive(Debug)]
+pub enum ResultChartEvent {
+    Changed,   // columns or kind picked
+    ExportPng, // save the plot area as an image
+}
+
+pub struct ResultChart {
+    // Screen area of the plot last drawn, cropped from screenshots for PNG export
+    plot_rect: Option<egui::Rect>,
+}
+
+impl ResultChart {
+    pub fn new() -> Self {
+        Self { plot_rect: None }
+    }
+
+    pub fn plot_rect(&self) -> Option<egui::Rect> {
+        self.plot_rect
+    }
+
+    /// Plot one column of `rows` against another, with pickers for both and the kind of chart.
+    pub fn show(&mut self, ui: &mut egui::Ui, data: &TableData, rows: &[usize], settings: &mut ChartSettings) -> Option<ResultChartEvent> {
+        let mut event = None;
+        if data.columns.is_empty() {
+            return None;
+        }
+        let last = data.columns.len() - 1;
+        settings.x_column = settings.x_column.min(last);
+        settings.y_column = settings.y_column.min(last);
+
+        let series = PlotSeries::new(data, rows, settings.x_column, settings.y_column);
+        ui.horizontal(|ui| {
+            for (label, id, column) in [("X:", "chart_x_column", &mut settings.x_column), ("Y:", "chart_y_column", &mut settings.y_column)] {
+                ui.label(label);
+                egui::ComboBox::from_id_source(id)
+                    .selected_text(&data.columns[*column].name)
+                    .show_ui(ui, |ui| {
+                        for (index, info) in data.columns.iter().enumerate() {
+                            if ui.selectable_value(column, index, &info.name).changed() {
+                                event = Some(ResultChartEvent::Changed);
+                            }
+                        }
+                    });
+            }
+            ui.separator();
+            for kind in ChartKind::all() {
+                if ui.selectable_value(&mut settings.kind, kind, kind.as_str()).changed() {
+                    event = Some(ResultChartEvent::Changed);
+                }
+            }
+            ui.separator();
+            if ui.button("💾 Export PNG").clicked() {
+                event = Some(ResultChartEvent::ExportPng);
+            }
+
+            let mut summary = format!("{} points", series.points.len());
+            if series.skipped > 0 {
+                summary.push_str(&format!(", {} rows without a numeric {}", series.skipped, data.columns[settings.y_column].name));
+            }
+            ui.label(egui::RichText::new(summary).color(egui::Color32::GRAY));
+        });
+
+        let name = data.columns[settings.y_column].name.clone();
+        let color = ui.visuals().hyperlink_color;
+        let mut plot = Plot::new("result_chart_plot")
+            .x_axis_label(data.columns[settings.x_column].name.clone())
+            .y_axis_label(name.clone());
+        if let Some(labels) = series.labels.clone() {
+            // Row values under their positions, which are whole numbers
+            plot = plot.x_axis_formatter(move |mark, _, _| {
+                let index = mark.value.round();
+                if (mark.value - index).abs() > f64::EPSILON || index < 0.0 {
+                    return String::new();
+                }
+                labels.get(index as usize).cloned().unwrap_or_default()
+            });
+        }
+
+        let kind = settings.kind;
+        let response = plot.show(ui, |plot_ui| {
+            let points: PlotPoints = series.points.clone().into();
+            match kind {
+                ChartKind::Line => plot_ui.line(Line::new(points).name(&name).color(color)),
+                ChartKind::Scatter => plot_ui.points(Points::new(points).name(&name).color(color).radius(3.0)),
+                ChartKind::Bar => {
+                    let width = series.bar_width();
+                    let bars = series.points.iter().map(|[x, y]| Bar::new(*x, *y).width(width)).collect();
+                    plot_ui.bar_chart(BarChart::new(bars).name(&name).color(color));
+                }
+            }
+        });
+        self.plot_rect = Some(response.response.rect);
+
+        event
+    }
+}
@@ -0,0 +1,96 @@
+use crate::export::Anonymizer;
+use crate::models::{CellValue, RowComparison, TableData};
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum RowCompareDialogEvent {
+    Close,
+}
+
+pub struct RowCompareDialog {
+    only_differences: bool,
+}
+
+impl RowCompareDialog {
+    pub fn new() -> Self {
+        Self { only_differences: true }
+    }
+
+    /// The two rows of `comparison` side by side, differing columns highlighted, with
+    /// buttons copying single values or every difference. Copies go through `anonymizer`.
+    pub fn show(&mut self, ctx: &egui::Context, data: &TableData, comparison: RowComparison, anonymizer: Option<&Anonymizer>) -> Option<RowCompareDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        let differing = comparison.differing_columns(data);
+        let copied_text = |column: usize, value: &CellValue| match anonymizer {
+            Some(anonymizer) => anonymizer.value(column, value).to_string(),
+            None => value.to_string(),
+        };
+        let highlight = egui::Color32::from_rgb(220, 120, 0);
+
+        egui::Window::new("⇄ Compare Rows")
+            .open(&mut is_open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} of {} columns differ", differing.len(), data.columns.len()));
+                    ui.checkbox(&mut self.only_differences, "Only differences");
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical().max_height(420.0).show(ui, |ui| {
+                    egui::Grid::new("row_compare_grid").striped(true).num_columns(3).show(ui, |ui| {
+                        ui.label(egui::RichText::new("Column").strong());
+                        ui.label(egui::RichText::new("First row").strong());
+                        ui.label(egui::RichText::new("Second row").strong());
+                        ui.end_row();
+
+                        for (index, column) in data.columns.iter().enumerate() {
+                            let differs = differing.contains(&index);
+                            if self.only_differences && !differs {
+                                continue;
+                            }
+                            let name = egui::RichText::new(&column.name);
+                            ui.label(if differs { name.strong().color(highlight) } else { name });
+                            for row in [comparison.left, comparison.right] {
+                                let value = data.rows[row].get(index).unwrap_or(&CellValue::Null);
+                                ui.horizontal(|ui| {
+                                    if ui.small_button("📋").on_hover_text("Copy value").clicked() {
+                                        ui.output_mut(|o| o.copied_text = copied_text(index, value));
+                                    }
+                                    let text = egui::RichText::new(value.to_string()).monospace();
+                                    ui.label(if differs { text.color(highlight) } else { text });
+                                });
+                            }
+                            ui.end_row();
+                        }
+                    });
+                    if self.only_differences && differing.is_empty() {
+                        ui.label(egui::RichText::new("The rows are identical").color(egui::Color32::GRAY));
+                    }
+                });
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!differing.is_empty(), egui::Button::new("📋 Copy Differences"))
+                        .on_hover_text("Column, first and second value of each difference, tab-separated")
+                        .clicked()
+                    {
+                        ui.output_mut(|o| o.copied_text = comparison.differences_text(data, copied_text));
+                    }
+                    if ui.button("Close").clicked() {
+                        event = Some(RowCompareDialogEvent::Close);
+                    }
+                });
+            });
+
+        if !is_open {
+            event = Some(RowCompareDialogEvent::Close);
+        }
+
+        event
+    }
+}
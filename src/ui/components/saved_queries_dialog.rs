@@ -126,18 +126,17 @@ impl SaveQueryDialog {
                     let response = ui.text_edit_singleline(&mut self.query_name);
 
                     // Auto-focus the text field
-                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                        if !self.query_name.trim().is_empty() {
-                            event = Some(SaveQueryDialogEvent::Save(self.query_name.clone()));
-                        }
+                    if response.lost_focus()
+                        && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                        && !self.query_name.trim().is_empty()
+                    {
+                        event = Some(SaveQueryDialogEvent::Save(self.query_name.clone()));
                     }
 
                     ui.add_space(10.0);
                     ui.horizontal(|ui| {
-                        if ui.button("Save").clicked() {
-                            if !self.query_name.trim().is_empty() {
-                                event = Some(SaveQueryDialogEvent::Save(self.query_name.clone()));
-                            }
+                        if ui.button("Save").clicked() && !self.query_name.trim().is_empty() {
+                            event = Some(SaveQueryDialogEvent::Save(self.query_name.clone()));
                         }
                         if ui.button("Cancel").clicked() {
                             event = Some(SaveQueryDialogEvent::Cancel);
@@ -0,0 +1,71 @@
+use crate::models::{SchemaChange, SchemaChangeKind};
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum SchemaDiffDialogEvent {
+    Close,
+}
+
+pub struct SchemaDiffDialog;
+
+impl SchemaDiffDialog {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Differences between a saved schema snapshot (described by `source`) and the live schema.
+    pub fn show(&mut self, ctx: &egui::Context, source: &str, changes: &[SchemaChange]) -> Option<SchemaDiffDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new("🧭 Schema Changes")
+            .open(&mut is_open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.label(source);
+                ui.add_space(5.0);
+
+                if changes.is_empty() {
+                    ui.label(egui::RichText::new("✔ The live schema matches the snapshot").color(egui::Color32::from_rgb(40, 140, 60)));
+                } else {
+                    let count = |kind| changes.iter().filter(|c| c.kind == kind).count();
+                    ui.label(format!(
+                        "{} added, {} removed, {} altered",
+                        count(SchemaChangeKind::Added),
+                        count(SchemaChangeKind::Removed),
+                        count(SchemaChangeKind::Altered)
+                    ));
+                    ui.separator();
+
+                    egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                        egui::Grid::new("schema_changes").striped(true).num_columns(3).show(ui, |ui| {
+                            for change in changes {
+                                let color = match change.kind {
+                                    SchemaChangeKind::Added => egui::Color32::from_rgb(40, 140, 60),
+                                    SchemaChangeKind::Removed => egui::Color32::from_rgb(200, 40, 40),
+                                    SchemaChangeKind::Altered => egui::Color32::from_rgb(200, 130, 0),
+                                };
+                                ui.label(egui::RichText::new(change.kind.as_str()).color(color).strong());
+                                ui.label(egui::RichText::new(&change.object).monospace());
+                                ui.label(&change.detail);
+                                ui.end_row();
+                            }
+                        });
+                    });
+                }
+
+                ui.add_space(10.0);
+                if ui.button("Close").clicked() {
+                    event = Some(SchemaDiffDialogEvent::Close);
+                }
+            });
+
+        if !is_open {
+            event = Some(SchemaDiffDialogEvent::Close);
+        }
+
+        event
+    }
+}
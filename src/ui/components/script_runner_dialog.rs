@@ -0,0 +1,105 @@
+use crate::db::ScriptProgress;
+use eframe::egui;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum ScriptRunnerDialogEvent {
+    Run(PathBuf, bool), // script path, stop on error
+    Stop,
+    Close,
+}
+
+pub struct ScriptRunnerDialog {
+    path: String,
+    stop_on_error: bool,
+}
+
+impl ScriptRunnerDialog {
+    pub fn new() -> Self {
+        Self {
+            path: String::new(),
+            stop_on_error: true,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, progress: Option<&ScriptProgress>, running: bool) -> Option<ScriptRunnerDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new("▶ Run SQL File")
+            .open(&mut is_open)
+            .resizable(true)
+            .default_width(550.0)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(!running, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("File:");
+                        ui.add(egui::TextEdit::singleline(&mut self.path)
+                            .hint_text("~/seeds/data.sql")
+                            .desired_width(380.0));
+                    });
+                    ui.checkbox(&mut self.stop_on_error, "Stop on first error");
+                    ui.label(egui::RichText::new(
+                        "Statements run one at a time in autocommit mode; results are discarded.",
+                    ).size(10.0).color(egui::Color32::GRAY));
+                });
+
+                if let Some(progress) = progress {
+                    ui.separator();
+                    ui.add(egui::ProgressBar::new(progress.fraction()).show_percentage());
+                    ui.label(format!(
+                        "{} statement(s) executed, {} error(s)",
+                        progress.executed,
+                        progress.error_count()
+                    ));
+
+                    if !progress.log.is_empty() {
+                        egui::ScrollArea::vertical()
+                            .max_height(200.0)
+                            .auto_shrink([false, true])
+                            .stick_to_bottom(true)
+                            .show(ui, |ui| {
+                                for entry in &progress.log {
+                                    let color = if entry.is_error {
+                                        egui::Color32::from_rgb(200, 40, 40)
+                                    } else {
+                                        egui::Color32::from_rgb(200, 120, 0)
+                                    };
+                                    ui.label(egui::RichText::new(format!("Line {}: {}", entry.line, entry.message))
+                                        .size(11.0)
+                                        .color(color)
+                                        .family(egui::FontFamily::Monospace));
+                                }
+                            });
+                    }
+                }
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if running {
+                        let stopping = progress.is_some_and(|p| p.stop_requested);
+                        let label = if stopping { "Stopping..." } else { "⏹ Stop" };
+                        if ui.add_enabled(!stopping, egui::Button::new(label)).clicked() {
+                            event = Some(ScriptRunnerDialogEvent::Stop);
+                        }
+                    } else {
+                        if ui.add_enabled(!self.path.trim().is_empty(), egui::Button::new("Run")).clicked() {
+                            event = Some(ScriptRunnerDialogEvent::Run(
+                                super::expand_home(self.path.trim()),
+                                self.stop_on_error,
+                            ));
+                        }
+                        if ui.button("Close").clicked() {
+                            event = Some(ScriptRunnerDialogEvent::Close);
+                        }
+                    }
+                });
+            });
+
+        if !is_open {
+            event = Some(if running { ScriptRunnerDialogEvent::Stop } else { ScriptRunnerDialogEvent::Close });
+        }
+
+        event
+    }
+}
@@ -0,0 +1,49 @@
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum ServerFilterBarEvent {
+    BuildJsonFilter,
+    Remove(usize),
+    Clear,
+}
+
+pub struct ServerFilterBar;
+
+impl ServerFilterBar {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Conditions a table tab is loaded with, each removable, and the button opening the
+    /// JSON filter builder when the table has jsonb columns.
+    pub fn show(&mut self, ui: &mut egui::Ui, filters: &[String], has_json_columns: bool) -> Option<ServerFilterBarEvent> {
+        let mut event = None;
+        if filters.is_empty() && !has_json_columns {
+            return None;
+        }
+
+        ui.horizontal_wrapped(|ui| {
+            if has_json_columns && ui.button("{ } JSON Filter…")
+                .on_hover_text("Build ->, ->> and @> conditions from keys found in the loaded rows")
+                .clicked()
+            {
+                event = Some(ServerFilterBarEvent::BuildJsonFilter);
+            }
+            if !filters.is_empty() {
+                ui.label("Server filters:");
+                for (index, filter) in filters.iter().enumerate() {
+                    ui.label(egui::RichText::new(filter).monospace());
+                    if ui.small_button("✖").on_hover_text("Remove and reload").clicked() {
+                        event = Some(ServerFilterBarEvent::Remove(index));
+                    }
+                }
+                if filters.len() > 1 && ui.small_button("Clear All").clicked() {
+                    event = Some(ServerFilterBarEvent::Clear);
+                }
+            }
+        });
+        ui.separator();
+
+        event
+    }
+}
@@ -0,0 +1,45 @@
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum SessionRestoreDialogEvent {
+    Restore,
+    Discard,
+}
+
+pub struct SessionRestoreDialog;
+
+impl SessionRestoreDialog {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Asked at startup when the previous session did not shut down cleanly.
+    pub fn show(&mut self, ctx: &egui::Context, tab_count: usize) -> Option<SessionRestoreDialogEvent> {
+        let mut event = None;
+
+        egui::Window::new("↻ Restore previous session?")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("The app did not shut down cleanly last time.");
+                ui.label(format!(
+                    "Restoring reopens its {} tab(s) and reloads them from the database, reconnecting where needed.",
+                    tab_count
+                ));
+                ui.label(egui::RichText::new("Queries that change data are not rerun; their tabs offer a reload instead.").color(egui::Color32::GRAY));
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button("Restore and reload").clicked() {
+                        event = Some(SessionRestoreDialogEvent::Restore);
+                    }
+                    if ui.button("Start fresh").clicked() {
+                        event = Some(SessionRestoreDialogEvent::Discard);
+                    }
+                });
+            });
+
+        event
+    }
+}
@@ -1,4 +1,4 @@
-use crate::config::Config;
+use crate::config::{Config, QueryTemplate, Snippet, TableAction, WatermarkPosition};
 use eframe::egui;
 
 #[derive(Debug)]
@@ -7,6 +7,7 @@ pub enum SettingsDialogEvent {
     Edit(usize),
     Delete(usize),
     NewConnection,
+    PreferencesChanged,
     Close,
 }
 
@@ -17,7 +18,7 @@ impl SettingsDialog {
         Self
     }
 
-    pub fn show(&mut self, ctx: &egui::Context, config: &Config) -> Option<SettingsDialogEvent> {
+    pub fn show(&mut self, ctx: &egui::Context, config: &mut Config) -> Option<SettingsDialogEvent> {
         let mut event = None;
 
         egui::Window::new("Settings")
@@ -56,6 +57,141 @@ impl SettingsDialog {
 
                 ui.separator();
 
+                ui.heading("Preferences");
+                ui.separator();
+
+                let preferences = &mut config.preferences;
+                let mut preferences_changed = false;
+
+                ui.horizontal(|ui| {
+                    preferences_changed |= ui.checkbox(&mut preferences.watermark_enabled, "Watermark exports and copied SQL")
+                        .on_hover_text("Add a comment with the source connection, query and timestamp")
+                        .changed();
+
+                    ui.add_enabled_ui(preferences.watermark_enabled, |ui| {
+                        egui::ComboBox::from_id_source("watermark_position")
+                            .selected_text(preferences.watermark_position.as_str())
+                            .show_ui(ui, |ui| {
+                                for position in [WatermarkPosition::Header, WatermarkPosition::Footer] {
+                                    preferences_changed |= ui.selectable_value(&mut preferences.watermark_position, position, position.as_str()).changed();
+                                }
+                            });
+                    });
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Double-clicking a table:");
+                    egui::ComboBox::from_id_source("table_double_click")
+                        .selected_text(preferences.table_double_click.as_str())
+                        .show_ui(ui, |ui| {
+                            for action in TableAction::all() {
+                                preferences_changed |= ui.selectable_value(&mut preferences.table_double_click, action, action.as_str()).changed();
+                            }
+                        });
+                });
+
+                preferences_changed |= ui.checkbox(&mut preferences.follow_up_suggestions, "Suggest follow-up queries above results").changed();
+
+                ui.horizontal(|ui| {
+                    let mut structure_first = preferences.structure_first_rows.is_some();
+                    if ui.checkbox(&mut structure_first, "Open tables estimated above").changed() {
+                        preferences.structure_first_rows = structure_first.then_some(1_000_000);
+                        preferences_changed = true;
+                    }
+                    if let Some(rows) = &mut preferences.structure_first_rows {
+                        preferences_changed |= ui.add(egui::DragValue::new(rows).speed(10_000).clamp_range(1..=u64::MAX)).changed();
+                    }
+                    ui.label("rows on their structure, loading the first");
+                    preferences_changed |= ui.add(egui::DragValue::new(&mut preferences.preview_rows).speed(10).clamp_range(1..=100_000)).changed();
+                    ui.label("on request");
+                });
+
+                ui.add_space(6.0);
+                ui.label(egui::RichText::new("Query Templates").strong());
+                ui.label(egui::RichText::new("Placeholders: {schema}, {table}, {columns}, {pk}. Open from a table's context menu; the first template also opens with Ctrl+Shift+N.")
+                    .size(10.0)
+                    .color(egui::Color32::GRAY));
+
+                let mut remove = None;
+                egui::ScrollArea::vertical()
+                    .id_source("query_templates")
+                    .max_height(220.0)
+                    .show(ui, |ui| {
+                        for (index, template) in preferences.query_templates.iter_mut().enumerate() {
+                            ui.horizontal(|ui| {
+                                preferences_changed |= ui.add(egui::TextEdit::singleline(&mut template.name)
+                                    .hint_text("Name")
+                                    .desired_width(200.0))
+                                    .changed();
+                                if ui.small_button("🗑").on_hover_text("Delete template").clicked() {
+                                    remove = Some(index);
+                                }
+                            });
+                            preferences_changed |= ui.add(egui::TextEdit::multiline(&mut template.sql)
+                                .font(egui::TextStyle::Monospace)
+                                .desired_rows(3)
+                                .desired_width(f32::INFINITY))
+                                .changed();
+                            ui.add_space(4.0);
+                        }
+                    });
+                if let Some(index) = remove {
+                    preferences.query_templates.remove(index);
+                    preferences_changed = true;
+                }
+                if ui.button("+ New Template").clicked() {
+                    preferences.query_templates.push(QueryTemplate {
+                        name: "New template".to_string(),
+                        sql: "SELECT {columns}\nFROM {table}\nLIMIT 100".to_string(),
+                    });
+                    preferences_changed = true;
+                }
+
+                ui.add_space(6.0);
+                ui.label(egui::RichText::new("Snippets").strong());
+                ui.label(egui::RichText::new("Offered by the query editor's autocomplete when typing their name. $1, $2, … (or ${1:text}) mark where Tab moves the cursor.")
+                    .size(10.0)
+                    .color(egui::Color32::GRAY));
+
+                let mut remove = None;
+                egui::ScrollArea::vertical()
+                    .id_source("snippets")
+                    .max_height(160.0)
+                    .show(ui, |ui| {
+                        for (index, snippet) in preferences.snippets.iter_mut().enumerate() {
+                            ui.horizontal(|ui| {
+                                preferences_changed |= ui.add(egui::TextEdit::singleline(&mut snippet.name)
+                                    .hint_text("Name")
+                                    .desired_width(80.0))
+                                    .changed();
+                                preferences_changed |= ui.add(egui::TextEdit::singleline(&mut snippet.body)
+                                    .font(egui::TextStyle::Monospace)
+                                    .desired_width(400.0))
+                                    .changed();
+                                if ui.small_button("🗑").on_hover_text("Delete snippet").clicked() {
+                                    remove = Some(index);
+                                }
+                            });
+                        }
+                    });
+                if let Some(index) = remove {
+                    preferences.snippets.remove(index);
+                    preferences_changed = true;
+                }
+                if ui.button("+ New Snippet").clicked() {
+                    preferences.snippets.push(Snippet {
+                        name: "new".to_string(),
+                        body: "SELECT $1 FROM $2".to_string(),
+                    });
+                    preferences_changed = true;
+                }
+
+                if preferences_changed {
+                    event = Some(SettingsDialogEvent::PreferencesChanged);
+                }
+
+                ui.separator();
+
                 if ui.button("Close").clicked() {
                     event = Some(SettingsDialogEvent::Close);
                 }
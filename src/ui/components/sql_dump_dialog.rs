@@ -0,0 +1,106 @@
+use crate::config::Driver;
+use crate::export::{BatchProgress, DumpRows, SqlDump};
+use eframe::egui;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum SqlDumpDialogEvent {
+    Start(SqlDump, PathBuf),
+    Stop,
+    Close,
+}
+
+pub struct SqlDumpDialog {
+    rows: DumpRows,
+    create_tables: bool,
+    path: String,
+}
+
+impl SqlDumpDialog {
+    pub fn new() -> Self {
+        Self {
+            rows: DumpRows::Insert,
+            create_tables: true,
+            path: String::new(),
+        }
+    }
+
+    /// Suggest a file named after what is dumped, `schema.table.sql` or `schema.sql`.
+    pub fn prepare(&mut self, schema: &str, table: Option<&str>, driver: Driver) {
+        let name = match table {
+            Some(table) => format!("{}.{}.sql", schema, table),
+            None => format!("{}.sql", schema),
+        };
+        self.path = super::default_file_path(&name).to_string_lossy().to_string();
+        if !DumpRows::all(driver).contains(&self.rows) {
+            self.rows = DumpRows::Insert;
+        }
+    }
+
+    /// Choose how `title` is dumped and where to, then follow the tables' progress.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        title: &str,
+        driver: Driver,
+        progress: Option<&BatchProgress>,
+        running: bool,
+    ) -> Option<SqlDumpDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new(format!("💾 Export {} as SQL", title))
+            .open(&mut is_open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                if let Some(progress) = progress {
+                    super::batch_export_dialog::progress_list(ui, progress);
+                    ui.add_space(10.0);
+                    if running {
+                        let label = if progress.stop_requested { "Stopping..." } else { "⏹ Stop" };
+                        if ui.add_enabled(!progress.stop_requested, egui::Button::new(label))
+                            .on_hover_text("No file is written when the export is stopped")
+                            .clicked()
+                        {
+                            event = Some(SqlDumpDialogEvent::Stop);
+                        }
+                    } else if ui.button("Close").clicked() {
+                        event = Some(SqlDumpDialogEvent::Close);
+                    }
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Rows as:");
+                    for rows in DumpRows::all(driver) {
+                        ui.selectable_value(&mut self.rows, rows, rows.as_str());
+                    }
+                });
+                ui.checkbox(&mut self.create_tables, "CREATE TABLE statements")
+                    .on_hover_text("Also create the tables, their indexes and foreign keys; otherwise only rows are loaded");
+                ui.horizontal(|ui| {
+                    ui.label("File:");
+                    ui.add(egui::TextEdit::singleline(&mut self.path).desired_width(360.0));
+                });
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!self.path.trim().is_empty(), egui::Button::new("Export")).clicked() {
+                        let dump = SqlDump { driver, rows: self.rows, create_tables: self.create_tables };
+                        event = Some(SqlDumpDialogEvent::Start(dump, super::expand_home(self.path.trim())));
+                    }
+                    if ui.button("Cancel").clicked() {
+                        event = Some(SqlDumpDialogEvent::Close);
+                    }
+                });
+            });
+
+        if !is_open {
+            event = Some(if running { SqlDumpDialogEvent::Stop } else { SqlDumpDialogEvent::Close });
+        }
+
+        event
+    }
+}
@@ -0,0 +1,62 @@
+use crate::db::StatementSummary;
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum StatementSummaryDialogEvent {
+    Close,
+}
+
+pub struct StatementSummaryDialog;
+
+impl StatementSummaryDialog {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// What each statement of a multi-statement query did; `total` is how many were
+    /// submitted, so a run stopped by an error shows what was never executed.
+    pub fn show(&mut self, ctx: &egui::Context, summary: &[StatementSummary], total: usize) -> Option<StatementSummaryDialogEvent> {
+        let mut event = None;
+        let mut is_open = true;
+
+        egui::Window::new("📋 Statement Results")
+            .open(&mut is_open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                if summary.len() < total {
+                    ui.label(egui::RichText::new(format!("Stopped after {} of {} statements", summary.len(), total))
+                        .color(egui::Color32::from_rgb(200, 40, 40)));
+                } else {
+                    ui.label(format!("{} statements executed", total));
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    egui::Grid::new("statement_summary").striped(true).num_columns(3).show(ui, |ui| {
+                        for (index, line) in summary.iter().enumerate() {
+                            ui.label(format!("{}", index + 1));
+                            // First line is enough to recognise the statement
+                            let first_line = line.sql.lines().find(|l| !l.trim().is_empty()).unwrap_or_default();
+                            ui.label(egui::RichText::new(first_line).monospace()).on_hover_text(&line.sql);
+                            let result = egui::RichText::new(&line.result);
+                            ui.label(if line.is_error { result.color(egui::Color32::from_rgb(200, 40, 40)) } else { result });
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                ui.add_space(10.0);
+                if ui.button("Close").clicked() {
+                    event = Some(StatementSummaryDialogEvent::Close);
+                }
+            });
+
+        if !is_open {
+            event = Some(StatementSummaryDialogEvent::Close);
+        }
+
+        event
+    }
+}
@@ -1,4 +1,18 @@
+use crate::db::TransactionStatus;
+use crate::models::QueryStats;
 use eframe::egui;
+use std::time::Duration;
+
+// Open this long, a transaction is flagged: it holds its locks and keeps VACUUM from
+// removing rows deleted since it began
+const LONG_TRANSACTION: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug)]
+pub enum StatusBarEvent {
+    Cancel,
+    Commit,
+    Rollback,
+}
 
 pub struct StatusBar;
 
@@ -7,14 +21,73 @@ impl StatusBar {
         Self
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui, status_message: &str, row_count: Option<usize>) {
+    #[allow(clippy::too_many_arguments)]
+    pub fn show(&mut self, ui: &mut egui::Ui, status_message: &str, row_count: Option<usize>, selection_summary: Option<String>, stats: Option<QueryStats>, can_cancel: bool, transaction_open: bool, transaction_status: Option<TransactionStatus>) -> Option<StatusBarEvent> {
+        let mut event = None;
+
         ui.horizontal(|ui| {
+            let open_color = egui::Color32::from_rgb(200, 130, 0);
+            let alert_color = egui::Color32::from_rgb(210, 60, 60);
+            match (transaction_status, transaction_open) {
+                (Some(TransactionStatus::Aborted), _) => {
+                    ui.label(egui::RichText::new("⛔ Transaction aborted").color(alert_color).strong())
+                        .on_hover_text("A statement failed; the server ignores everything until the transaction is rolled back");
+                    if ui.small_button("↩ Rollback").clicked() {
+                        event = Some(StatusBarEvent::Rollback);
+                    }
+                    ui.separator();
+                }
+                (status, open) if open || status.is_some_and(|status| status.is_open()) => {
+                    let (text, color, hover) = match status {
+                        Some(status @ TransactionStatus::InTransaction(age)) if age >= LONG_TRANSACTION => (
+                            format!("⚠ {}", status),
+                            alert_color,
+                            "Left open for a long time: it keeps holding its locks and blocks VACUUM from cleaning up the tables it could see",
+                        ),
+                        Some(status @ TransactionStatus::InTransaction(_)) => (format!("🔓 {}", status), open_color, "Changes are not visible to others until committed"),
+                        // Begun here and not polled yet
+                        _ => ("🔓 Transaction open".to_string(), open_color, "Changes are not visible to others until committed"),
+                    };
+                    ui.label(egui::RichText::new(text).color(color).strong()).on_hover_text(hover);
+                    if ui.small_button("✔ Commit").clicked() {
+                        event = Some(StatusBarEvent::Commit);
+                    }
+                    if ui.small_button("↩ Rollback").clicked() {
+                        event = Some(StatusBarEvent::Rollback);
+                    }
+                    ui.separator();
+                }
+                (Some(TransactionStatus::Idle), _) => {
+                    ui.label(egui::RichText::new("idle").color(egui::Color32::GRAY))
+                        .on_hover_text("No transaction is open on the server");
+                    ui.separator();
+                }
+                _ => {}
+            }
+            if can_cancel {
+                // Static indicator: an animated spinner would repaint every frame
+                ui.label("⏳");
+                if ui.small_button("⏹ Cancel").on_hover_text("Cancel the running query").clicked() {
+                    event = Some(StatusBarEvent::Cancel);
+                }
+            }
             ui.label(status_message);
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if let Some(count) = row_count {
                     ui.label(format!("{} rows", count));
                 }
+                if let Some(summary) = selection_summary {
+                    ui.separator();
+                    ui.label(summary);
+                }
+                if let Some(stats) = stats {
+                    ui.separator();
+                    ui.label(stats.summary())
+                        .on_hover_text("Last query or table load: wall-clock time, rows returned and approximate bytes fetched");
+                }
             });
         });
+
+        event
     }
 }
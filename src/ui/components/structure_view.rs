@@ -0,0 +1,70 @@
+use crate::db::{TableObject, TableStructure};
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum StructureViewEvent {
+    Refresh,
+}
+
+pub struct StructureView;
+
+impl StructureView {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Indexes, constraints and triggers of a table tab, one collapsible section each.
+    pub fn show(&mut self, ui: &mut egui::Ui, tab_id: usize, structure: Option<&TableStructure>) -> Option<StructureViewEvent> {
+        let mut event = None;
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("🏗 Structure").strong());
+            if ui.small_button("🔄 Refresh").clicked() {
+                event = Some(StructureViewEvent::Refresh);
+            }
+        });
+        ui.separator();
+
+        let Some(structure) = structure else {
+            ui.label(egui::RichText::new("Structure not loaded, press Refresh").color(egui::Color32::GRAY));
+            return event;
+        };
+
+        egui::ScrollArea::both()
+            .id_source(("structure_view", tab_id))
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                Self::section(ui, tab_id, "Indexes", &structure.indexes);
+                Self::section(ui, tab_id, "Constraints", &structure.constraints);
+                Self::section(ui, tab_id, "Triggers", &structure.triggers);
+            });
+
+        event
+    }
+
+    fn section(ui: &mut egui::Ui, tab_id: usize, title: &str, objects: &[TableObject]) {
+        egui::CollapsingHeader::new(egui::RichText::new(format!("{} ({})", title, objects.len())).strong())
+            .id_source(("structure_section", tab_id, title))
+            .default_open(true)
+            .show(ui, |ui| {
+                if objects.is_empty() {
+                    ui.label(egui::RichText::new("None").color(egui::Color32::GRAY));
+                    return;
+                }
+                egui::Grid::new(("structure_grid", tab_id, title))
+                    .striped(true)
+                    .num_columns(3)
+                    .spacing([16.0, 4.0])
+                    .show(ui, |ui| {
+                        for object in objects {
+                            ui.label(&object.name);
+                            ui.label(egui::RichText::new(&object.kind).size(11.0).color(egui::Color32::from_rgb(40, 110, 160)));
+                            ui.label(egui::RichText::new(&object.definition)
+                                .family(egui::FontFamily::Monospace)
+                                .size(11.0));
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
@@ -5,29 +5,135 @@ use eframe::egui;
 pub enum TabBarEvent {
     TabActivated(usize),
     TabClosed(usize),
+    TabMoved(usize, usize), // from, to
+    CloseOthers(usize),
+    CloseToRight(usize),
+    Duplicate(usize),
+    CloseAll,
+    TogglePin(usize),
+    Renamed(usize, String),
 }
 
-pub struct TabBar;
+pub struct TabBar {
+    // Tab being dragged to a new position
+    dragged: Option<usize>,
+    // Tab whose title is being edited, and the title typed so far
+    renaming: Option<(usize, String)>,
+    focus_rename: bool,
+}
 
 impl TabBar {
     pub fn new() -> Self {
-        Self
+        Self { dragged: None, renaming: None, focus_rename: false }
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui, tabs: &[Tab], active_tab: usize) -> Option<TabBarEvent> {
+    /// `tab_colors` holds the environment color of each tab's connection, if any.
+    /// Tabs are reordered by dragging, closed with a middle-click, renamed with a
+    /// double-click and have a context menu. Pinned tabs show a pin instead of a close button.
+    pub fn show(&mut self, ui: &mut egui::Ui, tabs: &[Tab], active_tab: usize, tab_colors: &[Option<egui::Color32>]) -> Option<TabBarEvent> {
         let mut event = None;
 
         if !tabs.is_empty() {
+            let pointer = ui.input(|i| i.pointer.hover_pos());
+            let mut drop_target = None;
+
             ui.horizontal(|ui| {
                 for (i, tab) in tabs.iter().enumerate() {
                     let is_active = i == active_tab;
-                    let tab_label = egui::RichText::new(&tab.title).strong();
 
-                    if ui.selectable_label(is_active, tab_label).clicked() {
+                    if let Some((_, title)) = self.renaming.as_mut().filter(|(index, _)| *index == i) {
+                        let response = ui.add(egui::TextEdit::singleline(title).desired_width(140.0));
+                        if self.focus_rename {
+                            response.request_focus();
+                            self.focus_rename = false;
+                        }
+                        if response.lost_focus() {
+                            // Escape keeps the old title, anything else that leaves the field saves
+                            let cancelled = ui.input(|i| i.key_pressed(egui::Key::Escape));
+                            if let (false, Some((_, title))) = (cancelled, self.renaming.take()) {
+                                if !title.trim().is_empty() {
+                                    event = Some(TabBarEvent::Renamed(i, title.trim().to_string()));
+                                }
+                            }
+                            self.renaming = None;
+                        }
+                        ui.separator();
+                        continue;
+                    }
+
+                    let mut title = if tab.pinned { format!("📌 {}", tab.title) } else { tab.title.clone() };
+                    if tab.is_dirty() {
+                        title.push_str(" ●");
+                    }
+                    let tab_label = egui::RichText::new(title).strong();
+
+                    let mut response = ui.selectable_label(is_active, tab_label).interact(egui::Sense::drag());
+                    if let Some(path) = &tab.sql_file {
+                        response = response.on_hover_text(path.display().to_string());
+                    }
+                    if response.clicked() {
                         event = Some(TabBarEvent::TabActivated(i));
                     }
+                    if response.double_clicked() {
+                        self.renaming = Some((i, tab.title.clone()));
+                        self.focus_rename = true;
+                    }
+                    if response.middle_clicked() {
+                        event = Some(TabBarEvent::TabClosed(i));
+                    }
+                    if response.drag_started() {
+                        self.dragged = Some(i);
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button(if tab.pinned { "Unpin Tab" } else { "Pin Tab" }).clicked() {
+                            event = Some(TabBarEvent::TogglePin(i));
+                            ui.close_menu();
+                        }
+                        if ui.button("Rename...").clicked() {
+                            self.renaming = Some((i, tab.title.clone()));
+                            self.focus_rename = true;
+                            ui.close_menu();
+                        }
+                        if ui.button("Duplicate Tab").clicked() {
+                            event = Some(TabBarEvent::Duplicate(i));
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui.add_enabled(tabs.len() > 1, egui::Button::new("Close Others")).clicked() {
+                            event = Some(TabBarEvent::CloseOthers(i));
+                            ui.close_menu();
+                        }
+                        if ui.add_enabled(i + 1 < tabs.len(), egui::Button::new("Close to the Right")).clicked() {
+                            event = Some(TabBarEvent::CloseToRight(i));
+                            ui.close_menu();
+                        }
+                        if ui.button("Close All").on_hover_text("Pinned tabs stay open").clicked() {
+                            event = Some(TabBarEvent::CloseAll);
+                            ui.close_menu();
+                        }
+                    });
+
+                    // Environment strip under the title
+                    if let Some(color) = tab_colors.get(i).copied().flatten() {
+                        let rect = response.rect;
+                        let strip = egui::Rect::from_min_max(
+                            egui::pos2(rect.left(), rect.bottom() - 3.0),
+                            rect.right_bottom(),
+                        );
+                        ui.painter().rect_filled(strip, 0.0, color);
+                    }
 
-                    if ui.small_button("✖").clicked() {
+                    // Marker on the side the dragged tab would land on
+                    if let (Some(from), Some(pointer)) = (self.dragged, pointer) {
+                        let rect = response.rect;
+                        if from != i && rect.x_range().contains(pointer.x) {
+                            drop_target = Some(i);
+                            let x = if i > from { rect.right() + 2.0 } else { rect.left() - 2.0 };
+                            ui.painter().vline(x, rect.y_range(), ui.visuals().selection.stroke);
+                        }
+                    }
+
+                    if !tab.pinned && ui.small_button("✖").clicked() {
                         event = Some(TabBarEvent::TabClosed(i));
                     }
 
@@ -36,6 +142,12 @@ impl TabBar {
             });
 
             ui.separator();
+
+            if self.dragged.is_some() && ui.input(|i| i.pointer.any_released()) {
+                if let (Some(from), Some(to)) = (self.dragged.take(), drop_target) {
+                    event = Some(TabBarEvent::TabMoved(from, to));
+                }
+            }
         }
 
         event
@@ -0,0 +1,77 @@
+use crate::db::ColumnInfo;
+use crate::models::{TailState, TAIL_INTERVALS};
+use eframe::egui;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum TailBarEvent {
+    Start(usize, Duration), // ordering column, poll interval
+    Stop,
+}
+
+pub struct TailBar {
+    column: Option<usize>,
+    interval: Duration,
+}
+
+impl TailBar {
+    pub fn new() -> Self {
+        Self {
+            column: None,
+            interval: TAIL_INTERVALS[1],
+        }
+    }
+
+    /// Controls for tailing a table tab, or the running tail's status.
+    pub fn show(&mut self, ui: &mut egui::Ui, columns: &[ColumnInfo], tail: Option<&TailState>) -> Option<TailBarEvent> {
+        let mut event = None;
+
+        ui.horizontal(|ui| {
+            match tail {
+                Some(tail) => {
+                    let column = columns.get(tail.column).map(|c| c.name.as_str()).unwrap_or("?");
+                    ui.label(egui::RichText::new(format!(
+                        "📡 Tailing by {} every {}s, {} new row(s)",
+                        column,
+                        tail.interval.as_secs(),
+                        tail.appended
+                    )).color(egui::Color32::from_rgb(40, 140, 60)));
+                    if ui.button("⏹ Stop").clicked() {
+                        event = Some(TailBarEvent::Stop);
+                    }
+                }
+                None => {
+                    // Ids and timestamps are the usual ordering columns of append-only tables
+                    let column = self.column
+                        .filter(|&index| index < columns.len())
+                        .or_else(|| columns.iter().position(|c| c.is_primary_key))
+                        .or_else(|| columns.iter().position(|c| crate::export::is_date_type(&c.data_type)));
+
+                    ui.label("📡 Tail by");
+                    egui::ComboBox::from_id_source("tail_column")
+                        .selected_text(column.and_then(|i| columns.get(i)).map(|c| c.name.as_str()).unwrap_or("column..."))
+                        .show_ui(ui, |ui| {
+                            for (index, c) in columns.iter().enumerate() {
+                                ui.selectable_value(&mut self.column, Some(index), &c.name);
+                            }
+                        });
+                    egui::ComboBox::from_id_source("tail_interval")
+                        .selected_text(format!("every {}s", self.interval.as_secs()))
+                        .show_ui(ui, |ui| {
+                            for interval in TAIL_INTERVALS {
+                                ui.selectable_value(&mut self.interval, interval, format!("every {}s", interval.as_secs()));
+                            }
+                        });
+                    let start = ui.add_enabled(column.is_some(), egui::Button::new("▶ Start"))
+                        .on_hover_text("Fetch rows past the last seen value of this column and append them");
+                    if let (true, Some(column)) = (start.clicked(), column) {
+                        event = Some(TailBarEvent::Start(column, self.interval));
+                    }
+                }
+            }
+        });
+        ui.separator();
+
+        event
+    }
+}
@@ -0,0 +1,138 @@
+use crate::config::WatchDefinition;
+use crate::models::{Watch, WATCH_INTERVALS};
+use eframe::egui;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum WatchPanelEvent {
+    Add(WatchDefinition),
+    Refresh(usize), // index into the watches
+    Remove(usize),
+    Close,
+}
+
+pub struct WatchPanel {
+    name: String,
+    sql: String,
+    interval: Duration,
+}
+
+impl WatchPanel {
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            sql: String::new(),
+            interval: WATCH_INTERVALS[1],
+        }
+    }
+
+    /// Current value of every watch with its change since the previous run and a
+    /// sparkline of recent readings, plus a form to add another.
+    pub fn show(&mut self, ui: &mut egui::Ui, watches: &[Watch]) -> Option<WatchPanelEvent> {
+        let mut event = None;
+
+        ui.horizontal(|ui| {
+            ui.strong(format!("👁 Watches ({})", watches.len()));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("✖").on_hover_text("Hide the watch panel").clicked() {
+                    event = Some(WatchPanelEvent::Close);
+                }
+            });
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .id_source("watch_panel")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for (index, watch) in watches.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.strong(&watch.definition.name)
+                            .on_hover_text(format!("{}\n\nevery {}s", watch.definition.sql, watch.definition.interval_secs));
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button("🗑").on_hover_text("Remove watch").clicked() {
+                                event = Some(WatchPanelEvent::Remove(index));
+                            }
+                            if ui.small_button("⟳").on_hover_text("Refresh now").clicked() {
+                                event = Some(WatchPanelEvent::Refresh(index));
+                            }
+                        });
+                    });
+                    ui.horizontal(|ui| {
+                        let value = watch.value.as_ref().map(|v| v.to_string()).unwrap_or_else(|| "…".to_string());
+                        ui.label(egui::RichText::new(value).size(18.0).monospace());
+                        if let Some(delta) = watch.delta() {
+                            let text = match delta {
+                                d if d > 0.0 => format!("▲ {}", format_number(d)),
+                                d if d < 0.0 => format!("▼ {}", format_number(-d)),
+                                _ => "= 0".to_string(),
+                            };
+                            ui.label(egui::RichText::new(text).weak()).on_hover_text("Change since the previous refresh");
+                        }
+                    });
+                    sparkline(ui, &watch.history);
+                    if let Some(error) = &watch.error {
+                        ui.label(egui::RichText::new(error).small().color(egui::Color32::from_rgb(200, 40, 40)));
+                    }
+                    ui.separator();
+                }
+
+                ui.collapsing("➕ Add watch", |ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.name).hint_text("Name, e.g. Failed jobs"));
+                    ui.add(egui::TextEdit::multiline(&mut self.sql)
+                        .hint_text("SELECT count(*) FROM jobs WHERE state = 'failed'")
+                        .code_editor()
+                        .desired_rows(3));
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_source("watch_interval")
+                            .selected_text(format!("every {}s", self.interval.as_secs()))
+                            .show_ui(ui, |ui| {
+                                for interval in WATCH_INTERVALS {
+                                    ui.selectable_value(&mut self.interval, interval, format!("every {}s", interval.as_secs()));
+                                }
+                            });
+                        let ready = !self.name.trim().is_empty() && !self.sql.trim().is_empty();
+                        if ui.add_enabled(ready, egui::Button::new("Add")).clicked() {
+                            event = Some(WatchPanelEvent::Add(WatchDefinition {
+                                name: std::mem::take(&mut self.name).trim().to_string(),
+                                sql: std::mem::take(&mut self.sql).trim().to_string(),
+                                interval_secs: self.interval.as_secs(),
+                            }));
+                        }
+                    });
+                });
+            });
+
+        event
+    }
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+fn sparkline(ui: &mut egui::Ui, values: &VecDeque<f64>) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width().min(200.0), 24.0), egui::Sense::hover());
+    if values.len() < 2 {
+        return;
+    }
+
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    // A flat series is drawn through the middle
+    let span = max - min;
+    let step = rect.width() / (values.len() - 1) as f32;
+    let points = values.iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let height = if span > 0.0 { ((value - min) / span) as f32 } else { 0.5 };
+            egui::pos2(rect.left() + step * i as f32, rect.bottom() - rect.height() * height)
+        })
+        .collect();
+    ui.painter().add(egui::Shape::line(points, egui::Stroke::new(1.5, ui.visuals().hyperlink_color)));
+}
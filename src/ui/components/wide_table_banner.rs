@@ -0,0 +1,39 @@
+use eframe::egui;
+
+#[derive(Debug)]
+pub enum WideTableBannerEvent {
+    ShowAllColumns,
+    ShowFirstColumns,
+}
+
+pub struct WideTableBanner;
+
+impl WideTableBanner {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Notice over a result with too many columns to draw all of them smoothly, of which
+    /// the grid draws the first `shown`.
+    pub fn show(&mut self, ui: &mut egui::Ui, columns: usize, shown: usize) -> Option<WideTableBannerEvent> {
+        let mut event = None;
+
+        ui.horizontal(|ui| {
+            if shown < columns {
+                ui.label(egui::RichText::new(format!("⚠ {} columns, only the first {} are shown", columns, shown))
+                    .color(egui::Color32::from_rgb(200, 130, 0)));
+                if ui.button("Show all columns").on_hover_text("Scrolling may be slower").clicked() {
+                    event = Some(WideTableBannerEvent::ShowAllColumns);
+                }
+            } else {
+                ui.label(egui::RichText::new(format!("All {} columns are shown", columns)).color(egui::Color32::GRAY));
+                if ui.small_button("Show fewer").clicked() {
+                    event = Some(WideTableBannerEvent::ShowFirstColumns);
+                }
+            }
+        });
+        ui.separator();
+
+        event
+    }
+}
@@ -0,0 +1,135 @@
+use crate::ui::components::{MenuBarEvent, NEW_QUERY_SHORTCUT};
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers};
+
+/// Something the user can do from the keyboard or the command palette.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    OpenPalette,
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    ExecuteQuery,
+    FocusSearch,
+    Reload,
+    NewQueryFromTemplate,
+    ToggleQueryPanel,
+    ToggleQueryLog,
+    ToggleWatches,
+    RefreshSchemas,
+    RunSqlFile,
+    ExportSchemaData,
+    SaveSchemaSnapshot,
+    CompareSchemaSnapshot,
+    ShareQuery,
+    OpenSharedQuery,
+    ShowSettings,
+    Quit,
+}
+
+impl Command {
+    /// Every command, in the order the palette lists them before anything is typed.
+    pub fn all() -> Vec<Command> {
+        vec![
+            Command::OpenPalette,
+            Command::NewTab,
+            Command::CloseTab,
+            Command::NextTab,
+            Command::PreviousTab,
+            Command::ExecuteQuery,
+            Command::FocusSearch,
+            Command::Reload,
+            Command::NewQueryFromTemplate,
+            Command::ToggleQueryPanel,
+            Command::ToggleQueryLog,
+            Command::ToggleWatches,
+            Command::RefreshSchemas,
+            Command::RunSqlFile,
+            Command::ExportSchemaData,
+            Command::SaveSchemaSnapshot,
+            Command::CompareSchemaSnapshot,
+            Command::ShareQuery,
+            Command::OpenSharedQuery,
+            Command::ShowSettings,
+            Command::Quit,
+        ]
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            Command::OpenPalette => "Command Palette",
+            Command::NewTab => "New Query Tab",
+            Command::CloseTab => "Close Tab",
+            Command::NextTab => "Next Tab",
+            Command::PreviousTab => "Previous Tab",
+            Command::ExecuteQuery => "Execute Query",
+            Command::FocusSearch => "Search Tables",
+            Command::Reload => "Reload Tab",
+            Command::NewQueryFromTemplate => "New Query from Template for Selected Table",
+            Command::ToggleQueryPanel => "Toggle Query Panel",
+            Command::ToggleQueryLog => "Toggle Query Log",
+            Command::ToggleWatches => "Toggle Watches",
+            Command::RefreshSchemas => "Refresh Schemas",
+            Command::RunSqlFile => "Run SQL File...",
+            Command::ExportSchemaData => "Export Schema Data...",
+            Command::SaveSchemaSnapshot => "Save Schema Snapshot...",
+            Command::CompareSchemaSnapshot => "Compare with Schema Snapshot...",
+            Command::ShareQuery => "Share Query...",
+            Command::OpenSharedQuery => "Open Shared Query...",
+            Command::ShowSettings => "Settings...",
+            Command::Quit => "Quit",
+        }
+    }
+
+    pub fn shortcut(&self) -> Option<KeyboardShortcut> {
+        let command = |key| KeyboardShortcut::new(Modifiers::COMMAND, key);
+        match self {
+            Command::OpenPalette => Some(command(Key::P)),
+            Command::NewTab => Some(command(Key::T)),
+            Command::CloseTab => Some(command(Key::W)),
+            Command::NextTab => Some(command(Key::PageDown)),
+            Command::PreviousTab => Some(command(Key::PageUp)),
+            Command::ExecuteQuery => Some(command(Key::Enter)),
+            Command::FocusSearch => Some(KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::F)),
+            Command::Reload => Some(command(Key::R)),
+            Command::NewQueryFromTemplate => Some(NEW_QUERY_SHORTCUT),
+            _ => None,
+        }
+    }
+
+    /// The menu bar event this command stands for, so both are handled the same way.
+    pub fn menu_event(&self) -> Option<MenuBarEvent> {
+        match self {
+            Command::ToggleQueryPanel => Some(MenuBarEvent::ToggleQueryPanel),
+            Command::ToggleQueryLog => Some(MenuBarEvent::ToggleQueryLog),
+            Command::ToggleWatches => Some(MenuBarEvent::ToggleWatches),
+            Command::RefreshSchemas => Some(MenuBarEvent::Refresh),
+            Command::RunSqlFile => Some(MenuBarEvent::RunSqlFile),
+            Command::ExportSchemaData => Some(MenuBarEvent::ExportSchemaData),
+            Command::SaveSchemaSnapshot => Some(MenuBarEvent::SaveSchemaSnapshot),
+            Command::CompareSchemaSnapshot => Some(MenuBarEvent::CompareSchemaSnapshot),
+            Command::ShareQuery => Some(MenuBarEvent::ShareQuery),
+            Command::OpenSharedQuery => Some(MenuBarEvent::OpenSharedQuery),
+            Command::ShowSettings => Some(MenuBarEvent::ShowSettings),
+            Command::Quit => Some(MenuBarEvent::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Consume the first command shortcut pressed this frame. Shortcuts with Shift are
+/// checked first, as egui also matches Cmd+Shift+F against Cmd+F. Execute Query only
+/// fires while no text field has focus, the SQL editor handling its own Cmd+Enter.
+pub fn pressed_command(ctx: &egui::Context) -> Option<Command> {
+    let mut commands: Vec<(Command, KeyboardShortcut)> = Command::all()
+        .into_iter()
+        .filter_map(|command| command.shortcut().map(|shortcut| (command, shortcut)))
+        .collect();
+    commands.sort_by_key(|(_, shortcut)| !shortcut.modifiers.shift);
+
+    let editing = ctx.memory(|memory| memory.focused().is_some());
+    commands.into_iter()
+        .filter(|(command, _)| !(editing && *command == Command::ExecuteQuery))
+        .find(|(_, shortcut)| ctx.input_mut(|i| i.consume_shortcut(shortcut)))
+        .map(|(command, _)| command)
+}
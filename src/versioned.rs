@@ -0,0 +1,197 @@
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A settings file whose layout is numbered, so older files are upgraded on load
+/// instead of failing to deserialize.
+pub trait Versioned: Serialize + DeserializeOwned {
+    /// Layout written by this build. Files from before versioning count as 0.
+    const VERSION: u32;
+
+    /// Rewrite `document` from layout `from` to `from + 1`. Fields that only gained a
+    /// `#[serde(default)]` need no step here.
+    fn migrate(_from: u32, _document: &mut Value) {}
+}
+
+/// Version recorded in a settings document, 0 when it has none.
+pub fn version_of(document: &Value) -> u32 {
+    document.get("version").and_then(Value::as_u64).map_or(0, |version| version as u32)
+}
+
+/// Bring `document` up to `T::VERSION` and deserialize it.
+pub fn upgrade<T: Versioned>(mut document: Value) -> Result<T> {
+    for from in version_of(&document)..T::VERSION {
+        T::migrate(from, &mut document);
+    }
+    Ok(serde_json::from_value(document)?)
+}
+
+/// Read `path`, None when it does not exist. A file that can't be read is copied aside
+/// first, so the defaults the caller falls back to never overwrite it unnoticed; the
+/// error names the copy. So is a file written by a newer build, whose unknown fields
+/// would be lost on the next save. Each copy is named with the time it was made.
+pub fn read<T: Versioned>(path: &Path) -> Result<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)?;
+    let now = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let loaded = serde_json::from_str::<Value>(&content).map_err(anyhow::Error::from).and_then(|document| {
+        let version = version_of(&document);
+        if version > T::VERSION {
+            backup(path, &format!("v{}.{}", version, now))?;
+        }
+        upgrade(document)
+    });
+    match loaded {
+        Ok(value) => Ok(Some(value)),
+        Err(e) => {
+            let copy = backup(path, &now)?;
+            anyhow::bail!("{} could not be read ({}); it was kept as {}", file_name(path), e, file_name(&copy))
+        }
+    }
+}
+
+/// Write `value` to `path` with its layout version, replacing the file only once the
+/// new content is complete.
+pub fn write<T: Versioned>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut document = Map::new();
+    document.insert("version".to_string(), T::VERSION.into());
+    match serde_json::to_value(value)? {
+        Value::Object(fields) => document.extend(fields.into_iter().filter(|(key, _)| key != "version")),
+        _ => anyhow::bail!("{} is not a JSON object", file_name(path)),
+    }
+
+    let partial = path.with_extension("json.tmp");
+    fs::write(&partial, serde_json::to_string_pretty(&document)?)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+// Copy `path` to `<name>.<suffix>.bak` beside it, unless an earlier copy already
+// holds the same content
+fn backup(path: &Path, suffix: &str) -> Result<PathBuf> {
+    let content = fs::read(path)?;
+    let name = file_name(path);
+    if let Some(dir) = path.parent().and_then(|dir| fs::read_dir(dir).ok()) {
+        let same = dir.filter_map(|entry| entry.ok()).map(|entry| entry.path()).find(|copy| {
+            let copy_name = file_name(copy);
+            copy_name.starts_with(&format!("{}.", name)) && copy_name.ends_with(".bak") && fs::read(copy).is_ok_and(|copied| copied == content)
+        });
+        if let Some(copy) = same {
+            return Ok(copy);
+        }
+    }
+    // Copies made within the same second are numbered apart
+    let copy = (1..)
+        .map(|n| match n {
+            1 => path.with_file_name(format!("{}.{}.bak", name, suffix)),
+            n => path.with_file_name(format!("{}.{}-{}.bak", name, suffix, n)),
+        })
+        .find(|copy| !copy.exists())
+        .unwrap_or_default();
+    fs::write(&copy, &content)?;
+    Ok(copy)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Settings {
+        name: String,
+        #[serde(default)]
+        limit: usize,
+    }
+
+    impl Versioned for Settings {
+        const VERSION: u32 = 2;
+
+        fn migrate(from: u32, document: &mut Value) {
+            // Layout 2 renamed `title` to `name`
+            if from == 1 {
+                if let Some(title) = document.as_object_mut().and_then(|fields| fields.remove("title")) {
+                    document["name"] = title;
+                }
+            }
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("db-client-versioned-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let settings: Settings = upgrade(serde_json::json!({"name": "a"})).unwrap();
+        assert_eq!(settings, Settings { name: "a".to_string(), limit: 0 });
+        let settings: Settings = upgrade(serde_json::json!({"version": 1, "title": "b", "limit": 5})).unwrap();
+        assert_eq!(settings, Settings { name: "b".to_string(), limit: 5 });
+        assert_eq!(version_of(&serde_json::json!({"version": 2})), 2);
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let dir = temp_dir("roundtrip");
+        let path = dir.join("settings.json");
+        assert!(read::<Settings>(&path).unwrap().is_none());
+
+        let settings = Settings { name: "a".to_string(), limit: 3 };
+        write(&path, &settings).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.trim_start().starts_with("{\n  \"version\": 2,"));
+        assert_eq!(read::<Settings>(&path).unwrap(), Some(settings));
+        assert!(!dir.join("settings.json.tmp").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_file_is_kept() {
+        let dir = temp_dir("unreadable");
+        let path = dir.join("settings.json");
+        fs::write(&path, r#"{"version": 2, "name": 7}"#).unwrap();
+        let error = read::<Settings>(&path).unwrap_err().to_string();
+        assert!(error.starts_with("settings.json could not be read"), "{}", error);
+        let copies: Vec<_> = fs::read_dir(&dir).unwrap().filter_map(|entry| entry.ok()).map(|entry| entry.file_name().to_string_lossy().into_owned()).collect();
+        assert!(copies.iter().any(|name| name.starts_with("settings.json.") && name.ends_with(".bak")), "{:?}", copies);
+
+        // A newer build's file loads, with a copy keeping the fields this build drops
+        let newer_copies = || -> Vec<String> {
+            let mut copies: Vec<String> = fs::read_dir(&dir).unwrap()
+                .map(|entry| entry.unwrap().path())
+                .filter(|copy| copy.extension().is_some_and(|extension| extension == "bak"))
+                .map(|copy| fs::read_to_string(copy).unwrap())
+                .filter(|content| content.contains("added_later"))
+                .collect();
+            copies.sort();
+            copies
+        };
+        let first = r#"{"version": 9, "name": "a", "added_later": true}"#;
+        fs::write(&path, first).unwrap();
+        assert!(read::<Settings>(&path).unwrap().is_some());
+        assert!(read::<Settings>(&path).unwrap().is_some());
+        assert_eq!(newer_copies(), [first]);
+
+        // A later copy of a changed file keeps the earlier one
+        let second = r#"{"version": 9, "name": "b", "added_later": true}"#;
+        fs::write(&path, second).unwrap();
+        assert!(read::<Settings>(&path).unwrap().is_some());
+        assert_eq!(newer_copies(), [first, second]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}